@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu pipefail
+
+# Benchmarks the quote hot path against a baseline built from `$1` (default
+# main) and fails if Criterion reports a regression past its noise threshold.
+
+ref=${1:-main}
+target=$(pwd)/target
+worktree=$(mktemp -d)
+trap 'git worktree remove --force "$worktree"' EXIT
+
+git worktree add --detach "$worktree" "$ref"
+(cd "$worktree" && CARGO_TARGET_DIR=$target cargo bench -p hylo-quotes \
+  --bench quote_hot_path -- --save-baseline "$ref")
+cargo bench -p hylo-quotes --bench quote_hot_path -- --baseline "$ref" \
+  | tee "$target/bench-$ref.log"
+! grep -q "Performance has regressed" "$target/bench-$ref.log"
@@ -0,0 +1,65 @@
+//! Records compute unit baselines from simulations of every Hylo instruction
+//! variant.
+//!
+//! Configured through the environment:
+//! * `RPC_URL`, `RPC_WS_URL` - cluster endpoints, such as a local validator
+//!   started from a snapshot
+//! * `HYLO_CU_BASELINES` - table path, defaults to `cu-baselines.json`
+//! * `HYLO_CU_USER` - funded wallet to simulate as, defaults to the reference
+//!   wallet
+//!
+//! The table is rewritten only when the deployed program versions differ from
+//! those it was recorded against.
+
+use std::{env, fs};
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::{Context, Result};
+use hylo_clients::cu_calibration::{
+  CuBaselineTable, CuCalibrator, ProgramVersion,
+};
+use hylo_clients::exchange_client::ExchangeClient;
+use hylo_clients::program_client::ProgramClient;
+use hylo_clients::stability_pool_client::StabilityPoolClient;
+use hylo_clients::util::{cluster_from_env, REFERENCE_WALLET};
+
+fn read_table(path: &str) -> Option<CuBaselineTable> {
+  fs::read_to_string(path)
+    .ok()
+    .and_then(|json| serde_json::from_str(&json).ok())
+    .and_then(|value| CuBaselineTable::from_json(&value).ok())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  let path = env::var("HYLO_CU_BASELINES")
+    .unwrap_or_else(|_| "cu-baselines.json".to_string());
+  let user = env::var("HYLO_CU_USER").map_or(Ok(REFERENCE_WALLET), |user| {
+    user
+      .parse::<Pubkey>()
+      .with_context(|| format!("Invalid HYLO_CU_USER {user}"))
+  })?;
+  let cluster = cluster_from_env()?;
+  let config = CommitmentConfig::confirmed();
+  let exchange = ExchangeClient::new_random_keypair(cluster.clone(), config)?;
+  let stability_pool =
+    StabilityPoolClient::new_random_keypair(cluster, config)?;
+  let versions = ProgramVersion::fetch_hylo(&exchange.program().rpc()).await?;
+  match read_table(&path) {
+    Some(table) if table.is_current(&versions) => {
+      eprintln!("{path} is current");
+      Ok(())
+    }
+    _ => {
+      let table = CuCalibrator::new(exchange, stability_pool, user)
+        .calibrate()
+        .await?;
+      let json = serde_json::to_string_pretty(&table.to_json())?;
+      fs::write(&path, json)
+        .with_context(|| format!("Failed to write {path}"))?;
+      eprintln!("Recorded {} baselines to {path}", table.baselines.len());
+      Ok(())
+    }
+  }
+}
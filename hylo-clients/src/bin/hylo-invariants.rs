@@ -0,0 +1,88 @@
+//! Continuous invariant checker for protocol auditors.
+//!
+//! Configured through the environment:
+//! * `RPC_URL`, `RPC_WS_URL` - cluster endpoints
+//! * `HYLO_INVARIANTS_INTERVAL_SECS` - seconds between checks, defaults to 60
+//! * `HYLO_INVARIANTS_SOL_TOLERANCE_BPS` - allowed total SOL cache drift,
+//!   defaults to 10
+//! * `ALERT_WEBHOOK_URL` - generic webhook sink
+//! * `ALERT_DISCORD_WEBHOOK_URL` - Discord channel webhook sink
+//! * `ALERT_TELEGRAM_BOT_TOKEN`, `ALERT_TELEGRAM_CHAT_ID` - Telegram sink
+//!
+//! Violations are also printed to stderr, so the binary is usable without any
+//! sink configured.
+
+use std::env;
+use std::time::Duration;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use fix::prelude::{UFix64, N4};
+use hylo_clients::exchange_client::ExchangeClient;
+use hylo_clients::keeper::alert::{
+  Alert, AlertRouter, AlertSink, DiscordSink, Severity, TelegramSink,
+  WebhookSink,
+};
+use hylo_clients::keeper::invariants::InvariantChecker;
+use hylo_clients::program_client::ProgramClient;
+use hylo_clients::util::cluster_from_env;
+
+/// Prints alerts to stderr.
+struct StderrSink;
+
+#[async_trait]
+impl AlertSink for StderrSink {
+  async fn send(&self, alert: &Alert) -> Result<()> {
+    eprintln!("[{}] {alert}", alert.severity());
+    Ok(())
+  }
+}
+
+fn env_u64(key: &str, default: u64) -> Result<u64> {
+  env::var(key).map_or(Ok(default), |value| {
+    value
+      .parse()
+      .with_context(|| format!("Invalid {key} {value}"))
+  })
+}
+
+fn router_from_env() -> AlertRouter {
+  let router = AlertRouter::new(Severity::Info).with_sink(StderrSink);
+  let router = match env::var("ALERT_WEBHOOK_URL") {
+    Ok(url) => router.with_sink(WebhookSink::new(url)),
+    Err(_) => router,
+  };
+  let router = match env::var("ALERT_DISCORD_WEBHOOK_URL") {
+    Ok(url) => router.with_sink(DiscordSink::new(url)),
+    Err(_) => router,
+  };
+  match (
+    env::var("ALERT_TELEGRAM_BOT_TOKEN"),
+    env::var("ALERT_TELEGRAM_CHAT_ID"),
+  ) {
+    (Ok(token), Ok(chat_id)) => {
+      router.with_sink(TelegramSink::new(token, chat_id))
+    }
+    _ => router,
+  }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  let interval = env_u64("HYLO_INVARIANTS_INTERVAL_SECS", 60)?;
+  let tolerance = env_u64("HYLO_INVARIANTS_SOL_TOLERANCE_BPS", 10)?;
+  let client = ExchangeClient::new_random_keypair(
+    cluster_from_env()?,
+    CommitmentConfig::confirmed(),
+  )?;
+  InvariantChecker::new(
+    client,
+    router_from_env(),
+    Duration::from_secs(interval),
+    UFix64::<N4>::new(tolerance),
+  )
+  .run()
+  .await;
+  Ok(())
+}
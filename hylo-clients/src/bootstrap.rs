@@ -0,0 +1,289 @@
+//! End-to-end deployment of a fresh Hylo protocol instance.
+//!
+//! Standing up devnet forks and integration environments takes a fixed
+//! sequence of admin instructions across both programs. [`Bootstrapper`]
+//! plans that sequence as one transaction per [`BootstrapStep`], skipping
+//! steps whose accounts already exist, so an interrupted deployment can be
+//! resumed by running it again.
+
+use std::fmt::{self, Display};
+
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::{TokenMint, HYUSD, SHYUSD};
+use hylo_idl::exchange::client::args;
+use hylo_idl::stake_pools::LstStakePool;
+use itertools::Itertools;
+
+use crate::exchange_client::ExchangeClient;
+use crate::program_client::{ProgramClient, VersionedTransactionData};
+use crate::stability_pool_client::StabilityPoolClient;
+use crate::util::deserialize_lookup_table;
+
+/// Accounts needed to register one LST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LstRegistration {
+  pub lst_mint: Pubkey,
+  pub stake_pool_state: Pubkey,
+  pub sanctum_calculator_program: Pubkey,
+  pub sanctum_calculator_state: Pubkey,
+  pub stake_pool_program: Pubkey,
+  pub stake_pool_program_data: Pubkey,
+}
+
+impl From<LstStakePool> for LstRegistration {
+  fn from(pool: LstStakePool) -> LstRegistration {
+    LstRegistration {
+      lst_mint: pool.mint,
+      stake_pool_state: pool.stake_pool_state,
+      sanctum_calculator_program: pool.program.calculator_program(),
+      sanctum_calculator_state: pool.program.calculator_state(),
+      stake_pool_program: pool.program.program_id(),
+      stake_pool_program_data: pool.program.program_data(),
+    }
+  }
+}
+
+impl LstRegistration {
+  /// Registration accounts for a supported LST from
+  /// [`hylo_idl::stake_pools`], or `None` for unknown mints.
+  #[must_use]
+  pub fn known(lst_mint: &Pubkey) -> Option<LstRegistration> {
+    LstStakePool::find(lst_mint).map(LstRegistration::from)
+  }
+}
+
+/// Parameters of a deployment.
+pub struct BootstrapConfig {
+  pub upgrade_authority: Pubkey,
+  pub treasury: Pubkey,
+  pub protocol: args::InitializeProtocol,
+
+  /// Recent slot deriving the LST registry lookup table address. Reuse the
+  /// same slot to resume a deployment.
+  pub registry_slot: u64,
+
+  /// LSTs to register, in order
+  pub lsts: Vec<LstRegistration>,
+}
+
+impl BootstrapConfig {
+  /// Address of the LST registry lookup table for [`Self::registry_slot`].
+  #[must_use]
+  pub fn lst_registry(&self) -> Pubkey {
+    pda::new_lst_registry(self.registry_slot)
+  }
+}
+
+/// One deployment transaction, in execution order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapStep {
+  InitializeProtocol,
+  InitializeMints,
+  InitializeLstRegistry,
+  InitializeLstRegistryCalculators,
+  RegisterLst(Pubkey),
+  InitializeStabilityPool,
+  InitializeLpTokenMint,
+}
+
+impl Display for BootstrapStep {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      BootstrapStep::InitializeProtocol => f.write_str("initialize_protocol"),
+      BootstrapStep::InitializeMints => f.write_str("initialize_mints"),
+      BootstrapStep::InitializeLstRegistry => {
+        f.write_str("initialize_lst_registry")
+      }
+      BootstrapStep::InitializeLstRegistryCalculators => {
+        f.write_str("initialize_lst_registry_calculators")
+      }
+      BootstrapStep::RegisterLst(mint) => write!(f, "register_lst({mint})"),
+      BootstrapStep::InitializeStabilityPool => {
+        f.write_str("initialize_stability_pool")
+      }
+      BootstrapStep::InitializeLpTokenMint => {
+        f.write_str("initialize_lp_token_mint")
+      }
+    }
+  }
+}
+
+/// Transaction still to be sent for a step.
+pub struct BootstrapTransaction {
+  pub step: BootstrapStep,
+  pub transaction: VersionedTransactionData,
+}
+
+/// Deployment steps split by whether their accounts already exist.
+pub struct BootstrapPlan {
+  /// Steps already applied on-chain
+  pub completed: Vec<BootstrapStep>,
+
+  /// Transactions for remaining steps, in execution order
+  pub pending: Vec<BootstrapTransaction>,
+}
+
+/// Sequences protocol deployment across the exchange and stability pool.
+pub struct Bootstrapper {
+  exchange: ExchangeClient,
+  stability_pool: StabilityPoolClient,
+}
+
+impl Bootstrapper {
+  /// Deploys with both clients, which must share the admin payer.
+  #[must_use]
+  pub fn new(
+    exchange: ExchangeClient,
+    stability_pool: StabilityPoolClient,
+  ) -> Bootstrapper {
+    Bootstrapper {
+      exchange,
+      stability_pool,
+    }
+  }
+
+  /// Checks which steps are applied and builds transactions for the rest.
+  ///
+  /// # Errors
+  /// - Failed to fetch accounts
+  /// - Existing LST registry cannot be deserialized
+  /// - Failed to build transaction instructions
+  pub async fn plan(&self, config: &BootstrapConfig) -> Result<BootstrapPlan> {
+    let lst_registry = config.lst_registry();
+    let keys = [
+      *pda::HYLO,
+      HYUSD::MINT,
+      lst_registry,
+      *pda::POOL_CONFIG,
+      SHYUSD::MINT,
+    ]
+    .into_iter()
+    .chain(config.lsts.iter().map(|lst| pda::lst_header(lst.lst_mint)))
+    .collect_vec();
+    let accounts = self.exchange.get_multiple_accounts(&keys).await?;
+    let exists = |key: &Pubkey| {
+      keys
+        .iter()
+        .position(|k| k == key)
+        .and_then(|i| accounts.get(i))
+        .is_some_and(Option::is_some)
+    };
+    // Calculators extend the registry with its 16 account preamble
+    let calculators = accounts
+      .get(2)
+      .and_then(Option::as_ref)
+      .map(|account| deserialize_lookup_table(&lst_registry, account))
+      .transpose()?
+      .is_some_and(|table| table.addresses.len() >= 16);
+    let steps = [
+      (BootstrapStep::InitializeProtocol, exists(&pda::HYLO)),
+      (BootstrapStep::InitializeMints, exists(&HYUSD::MINT)),
+      (BootstrapStep::InitializeLstRegistry, exists(&lst_registry)),
+      (BootstrapStep::InitializeLstRegistryCalculators, calculators),
+    ]
+    .into_iter()
+    .chain(config.lsts.iter().map(|lst| {
+      let header = pda::lst_header(lst.lst_mint);
+      (BootstrapStep::RegisterLst(lst.lst_mint), exists(&header))
+    }))
+    .chain([
+      (
+        BootstrapStep::InitializeStabilityPool,
+        exists(&pda::POOL_CONFIG),
+      ),
+      (BootstrapStep::InitializeLpTokenMint, exists(&SHYUSD::MINT)),
+    ])
+    .collect_vec();
+    let (completed, pending): (Vec<_>, Vec<_>) =
+      steps.into_iter().partition(|(_, done)| *done);
+    Ok(BootstrapPlan {
+      completed: completed.into_iter().map(|(step, _)| step).collect(),
+      pending: pending
+        .into_iter()
+        .map(|(step, _)| {
+          Ok(BootstrapTransaction {
+            step,
+            transaction: self.build_step(config, step)?,
+          })
+        })
+        .collect::<Result<_>>()?,
+    })
+  }
+
+  /// Plans the deployment and sends pending transactions one at a time,
+  /// stopping at the first failure.
+  ///
+  /// # Errors
+  /// - Planning fails
+  /// - Any transaction fails to send or confirm
+  pub async fn run(
+    &self,
+    config: &BootstrapConfig,
+  ) -> Result<Vec<(BootstrapStep, Signature)>> {
+    let plan = self.plan(config).await?;
+    stream::iter(plan.pending)
+      .then(|tx| async move {
+        let signature = match tx.step {
+          BootstrapStep::InitializeStabilityPool
+          | BootstrapStep::InitializeLpTokenMint => {
+            self
+              .stability_pool
+              .send_v0_transaction(&tx.transaction)
+              .await
+          }
+          _ => self.exchange.send_v0_transaction(&tx.transaction).await,
+        }?;
+        Ok::<_, anyhow::Error>((tx.step, signature))
+      })
+      .try_collect()
+      .await
+  }
+
+  /// Builds the transaction for one step.
+  fn build_step(
+    &self,
+    config: &BootstrapConfig,
+    step: BootstrapStep,
+  ) -> Result<VersionedTransactionData> {
+    match step {
+      BootstrapStep::InitializeProtocol => self.exchange.initialize_protocol(
+        config.upgrade_authority,
+        config.treasury,
+        &config.protocol,
+      ),
+      BootstrapStep::InitializeMints => self.exchange.initialize_mints(),
+      BootstrapStep::InitializeLstRegistry => {
+        self.exchange.initialize_lst_registry(config.registry_slot)
+      }
+      BootstrapStep::InitializeLstRegistryCalculators => self
+        .exchange
+        .initialize_lst_registry_calculators(config.lst_registry()),
+      BootstrapStep::RegisterLst(mint) => {
+        let lst = config
+          .lsts
+          .iter()
+          .find(|lst| lst.lst_mint == mint)
+          .ok_or(anyhow!("LST {mint} not in bootstrap config"))?;
+        self.exchange.register_lst(
+          config.lst_registry(),
+          lst.lst_mint,
+          lst.stake_pool_state,
+          lst.sanctum_calculator_program,
+          lst.sanctum_calculator_state,
+          lst.stake_pool_program,
+          lst.stake_pool_program_data,
+        )
+      }
+      BootstrapStep::InitializeStabilityPool => self
+        .stability_pool
+        .initialize_stability_pool(config.upgrade_authority),
+      BootstrapStep::InitializeLpTokenMint => {
+        self.stability_pool.initialize_lp_token_mint()
+      }
+    }
+  }
+}
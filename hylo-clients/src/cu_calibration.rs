@@ -0,0 +1,366 @@
+//! Simulation-derived compute unit baselines.
+//!
+//! [`CuCalibrator`] simulates every exchange and stability pool instruction
+//! variant against a cluster, or a local validator started from a snapshot,
+//! and records the units each consumed into a [`CuBaselineTable`]. The table
+//! carries the deploy slot of both programs it was measured against, so
+//! [`CuBaselineTable::is_current`] flags an upgrade that calls for
+//! recalibration. `ComputeUnitBaselines::with_table` loads it into the
+//! estimator in [`crate::estimate`].
+
+use std::collections::HashMap;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_idl::deployment::Deployment;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_idl::{exchange, stability_pool};
+use serde_json::{json, Value};
+
+use crate::exchange_client::ExchangeClient;
+use crate::program_client::ProgramClient;
+use crate::program_logs::ProgramLogs;
+use crate::stability_pool_client::StabilityPoolClient;
+use crate::transaction::{
+  BuildTransactionData, LstSwapArgs, MintArgs, RedeemArgs, StabilityPoolArgs,
+  SwapArgs,
+};
+use crate::util::simulation_config;
+
+/// LST deposited or swapped by calibration simulations.
+const CALIBRATION_LST_AMOUNT: UFix64<N9> = UFix64::constant(1_000_000);
+
+/// hyUSD, xSOL or sHYUSD spent by calibration simulations.
+const CALIBRATION_TOKEN_AMOUNT: UFix64<N6> = UFix64::constant(10_000);
+
+/// Deployed version of a program, as its last deploy slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+  pub program_id: Pubkey,
+  pub deploy_slot: u64,
+}
+
+impl ProgramVersion {
+  /// Fetches the deploy slots of the exchange and stability pool programs.
+  ///
+  /// # Errors
+  /// - Failed to fetch program data accounts
+  /// - Program data account missing or malformed
+  pub async fn fetch_hylo(rpc: &RpcClient) -> Result<Vec<ProgramVersion>> {
+    let deployment = Deployment::MAINNET;
+    let programs = [exchange::ID, stability_pool::ID];
+    let keys = [
+      deployment.exchange_program_data(),
+      deployment.stability_pool_program_data(),
+    ];
+    let accounts = rpc.get_multiple_accounts(&keys).await?;
+    programs
+      .into_iter()
+      .zip(accounts)
+      .map(|(program_id, account)| {
+        // `UpgradeableLoaderState::ProgramData` is a 4 byte variant tag
+        // followed by the slot of the last deploy.
+        let slot = account
+          .as_ref()
+          .and_then(|account| account.data.get(4..12))
+          .and_then(|bytes| bytes.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(anyhow!("Malformed program data for {program_id}"))?;
+        Ok(ProgramVersion {
+          program_id,
+          deploy_slot: slot,
+        })
+      })
+      .collect()
+  }
+}
+
+/// Units one instruction consumed in a calibration simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedBaseline {
+  /// Variant simulated, e.g. `mint_stablecoin/JITOSOL`
+  pub label: String,
+  pub program_id: Pubkey,
+  pub discriminator: [u8; 8],
+  pub units: u64,
+}
+
+/// Baselines recorded against specific program versions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CuBaselineTable {
+  pub versions: Vec<ProgramVersion>,
+  pub baselines: Vec<RecordedBaseline>,
+}
+
+impl CuBaselineTable {
+  /// Whether the table was recorded against exactly `versions`.
+  #[must_use]
+  pub fn is_current(&self, versions: &[ProgramVersion]) -> bool {
+    versions.len() == self.versions.len()
+      && versions
+        .iter()
+        .all(|version| self.versions.contains(version))
+  }
+
+  /// Largest recorded units per program and discriminator, so that variants
+  /// sharing an instruction (e.g. one mint per LST) are all covered.
+  #[must_use]
+  pub fn max_units(&self) -> HashMap<(Pubkey, [u8; 8]), u64> {
+    self
+      .baselines
+      .iter()
+      .fold(HashMap::new(), |mut units, recorded| {
+        let entry = units
+          .entry((recorded.program_id, recorded.discriminator))
+          .or_insert(0);
+        *entry = (*entry).max(recorded.units);
+        units
+      })
+  }
+
+  #[must_use]
+  pub fn to_json(&self) -> Value {
+    json!({
+      "versions": self.versions.iter().map(|version| json!({
+        "program_id": version.program_id.to_string(),
+        "deploy_slot": version.deploy_slot,
+      })).collect::<Vec<_>>(),
+      "baselines": self.baselines.iter().map(|recorded| json!({
+        "label": recorded.label,
+        "program_id": recorded.program_id.to_string(),
+        "discriminator": recorded.discriminator,
+        "units": recorded.units,
+      })).collect::<Vec<_>>(),
+    })
+  }
+
+  /// Parses a table written by [`CuBaselineTable::to_json`].
+  ///
+  /// # Errors
+  /// - Missing or mistyped fields
+  pub fn from_json(value: &Value) -> Result<CuBaselineTable> {
+    let versions = json_array(value, "versions")?
+      .iter()
+      .map(|version| {
+        Ok(ProgramVersion {
+          program_id: json_pubkey(version)?,
+          deploy_slot: json_u64(version, "deploy_slot")?,
+        })
+      })
+      .collect::<Result<_>>()?;
+    let baselines = json_array(value, "baselines")?
+      .iter()
+      .map(|recorded| {
+        let discriminator = json_array(recorded, "discriminator")?
+          .iter()
+          .map(|byte| {
+            byte
+              .as_u64()
+              .and_then(|byte| u8::try_from(byte).ok())
+              .ok_or(anyhow!("Invalid discriminator byte {byte}"))
+          })
+          .collect::<Result<Vec<u8>>>()?;
+        Ok(RecordedBaseline {
+          label: recorded
+            .get("label")
+            .and_then(Value::as_str)
+            .ok_or(anyhow!("Missing baseline label"))?
+            .to_string(),
+          program_id: json_pubkey(recorded)?,
+          discriminator: discriminator
+            .try_into()
+            .map_err(|_| anyhow!("Discriminator must be 8 bytes"))?,
+          units: json_u64(recorded, "units")?,
+        })
+      })
+      .collect::<Result<_>>()?;
+    Ok(CuBaselineTable {
+      versions,
+      baselines,
+    })
+  }
+}
+
+fn json_array<'a>(value: &'a Value, key: &str) -> Result<&'a Vec<Value>> {
+  value
+    .get(key)
+    .and_then(Value::as_array)
+    .ok_or(anyhow!("Missing array {key}"))
+}
+
+fn json_u64(value: &Value, key: &str) -> Result<u64> {
+  value
+    .get(key)
+    .and_then(Value::as_u64)
+    .ok_or(anyhow!("Missing integer {key}"))
+}
+
+fn json_pubkey(value: &Value) -> Result<Pubkey> {
+  value
+    .get("program_id")
+    .and_then(Value::as_str)
+    .ok_or(anyhow!("Missing program_id"))?
+    .parse()
+    .context("Invalid program_id")
+}
+
+fn is_hylo(program_id: &Pubkey) -> bool {
+  *program_id == exchange::ID || *program_id == stability_pool::ID
+}
+
+/// Pairs the Hylo instructions of a simulated transaction with the units
+/// their top-level invocations consumed.
+fn record(
+  label: &str,
+  instructions: &[Instruction],
+  logs: &[String],
+) -> Vec<RecordedBaseline> {
+  let consumed = ProgramLogs::parse(logs)
+    .top_level_units()
+    .into_iter()
+    .filter(|(program_id, _)| is_hylo(program_id));
+  instructions
+    .iter()
+    .filter(|ix| is_hylo(&ix.program_id))
+    .zip(consumed)
+    .filter_map(|(ix, (_, units))| {
+      ix.data.first_chunk::<8>().map(|disc| RecordedBaseline {
+        label: label.to_string(),
+        program_id: ix.program_id,
+        discriminator: *disc,
+        units,
+      })
+    })
+    .collect()
+}
+
+/// Simulates Hylo instruction variants from a funded wallet.
+pub struct CuCalibrator {
+  exchange: ExchangeClient,
+  stability_pool: StabilityPoolClient,
+  user: Pubkey,
+}
+
+impl CuCalibrator {
+  /// Calibrator simulating as `user`, which must hold jitoSOL, hyUSD, xSOL
+  /// and sHYUSD, such as [`crate::util::REFERENCE_WALLET`].
+  #[must_use]
+  pub fn new(
+    exchange: ExchangeClient,
+    stability_pool: StabilityPoolClient,
+    user: Pubkey,
+  ) -> CuCalibrator {
+    CuCalibrator {
+      exchange,
+      stability_pool,
+      user,
+    }
+  }
+
+  /// Builds and simulates one variant, recording its Hylo instructions.
+  async fn simulate<C, I, O>(
+    &self,
+    client: &C,
+    label: &str,
+    inputs: <C as BuildTransactionData<I, O>>::Inputs,
+  ) -> Result<Vec<RecordedBaseline>>
+  where
+    C: BuildTransactionData<I, O> + ProgramClient + Sync,
+  {
+    let vtd = client
+      .build(inputs)
+      .await
+      .with_context(|| format!("Failed to build {label}"))?;
+    let tx = client
+      .build_simulation_transaction(&self.user, &vtd)
+      .await?;
+    let result = client
+      .program()
+      .rpc()
+      .simulate_transaction_with_config(&tx, simulation_config())
+      .await?
+      .value;
+    match (result.err, result.logs) {
+      (Some(err), _) => Err(anyhow!("Simulation of {label} failed: {err}")),
+      (None, Some(logs)) => Ok(record(label, &vtd.instructions, &logs)),
+      (None, None) => Err(anyhow!("Simulation of {label} returned no logs")),
+    }
+  }
+
+  /// Simulates every instruction variant and records a table against the
+  /// current program versions.
+  ///
+  /// # Errors
+  /// - Failed to fetch program versions
+  /// - Any variant fails to build or simulate
+  pub async fn calibrate(&self) -> Result<CuBaselineTable> {
+    let user = self.user;
+    let (exchange, pool) = (&self.exchange, &self.stability_pool);
+    let mint = || MintArgs {
+      amount: CALIBRATION_LST_AMOUNT,
+      user,
+      slippage_config: None,
+    };
+    let redeem = || RedeemArgs {
+      amount: CALIBRATION_TOKEN_AMOUNT,
+      user,
+      slippage_config: None,
+    };
+    let swap = || SwapArgs {
+      amount: CALIBRATION_TOKEN_AMOUNT,
+      user,
+      slippage_config: None,
+    };
+    let pool_args = || StabilityPoolArgs {
+      amount: CALIBRATION_TOKEN_AMOUNT,
+      user,
+    };
+    let lst_swap = LstSwapArgs {
+      amount_lst_a: CALIBRATION_LST_AMOUNT,
+      lst_a_mint: JITOSOL::MINT,
+      lst_b_mint: HYLOSOL::MINT,
+      user,
+      slippage_config: None,
+    };
+    let versions =
+      ProgramVersion::fetch_hylo(&exchange.program().rpc()).await?;
+    let (a, b, c, d, e, f, g, h, i) = futures::try_join!(
+      self.simulate::<_, JITOSOL, HYUSD>(
+        exchange,
+        "mint_stablecoin/JITOSOL",
+        mint()
+      ),
+      self.simulate::<_, JITOSOL, XSOL>(
+        exchange,
+        "mint_levercoin/JITOSOL",
+        mint()
+      ),
+      self.simulate::<_, HYUSD, JITOSOL>(
+        exchange,
+        "redeem_stablecoin/JITOSOL",
+        redeem()
+      ),
+      self.simulate::<_, XSOL, JITOSOL>(
+        exchange,
+        "redeem_levercoin/JITOSOL",
+        redeem()
+      ),
+      self.simulate::<_, HYUSD, XSOL>(exchange, "swap_stable_to_lever", swap()),
+      self.simulate::<_, XSOL, HYUSD>(exchange, "swap_lever_to_stable", swap()),
+      self.simulate::<_, JITOSOL, HYLOSOL>(
+        exchange,
+        "swap_lst/JITOSOL/HYLOSOL",
+        lst_swap
+      ),
+      self.simulate::<_, HYUSD, SHYUSD>(pool, "user_deposit", pool_args()),
+      self.simulate::<_, SHYUSD, HYUSD>(pool, "user_withdraw", pool_args()),
+    )?;
+    Ok(CuBaselineTable {
+      versions,
+      baselines: [a, b, c, d, e, f, g, h, i].concat(),
+    })
+  }
+}
@@ -0,0 +1,245 @@
+//! Test token and oracle utilities for devnet and local validators.
+//!
+//! Lets integrators run end-to-end flows without forking mainnet:
+//! * [`DevnetFaucet`] creates SPL mints standing in for LSTs and mints them to
+//!   test wallets
+//! * [`mock_price_update`] builds a Pyth `PriceUpdateV2` account to preload,
+//!   e.g. with `solana-test-validator --account`
+//! * [`devnet_bootstrap_config`] and [`seed_deployment`] deploy a Hylo instance
+//!   with permissive test parameters via [`Bootstrapper`]
+
+use std::sync::Arc;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::rent::Rent;
+use anchor_client::solana_sdk::signature::{Keypair, Signature};
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token;
+use anchor_spl::token::spl_token::instruction::{initialize_mint2, mint_to};
+use anchor_spl::token::spl_token::state::Mint;
+use anyhow::Result;
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::mock_pyth::MockPriceUpdate;
+use hylo_idl::exchange::client::args;
+use hylo_idl::exchange::types::{
+  FeePair, LevercoinFees, StablecoinFees, UFixValue64 as IdlUFixValue64,
+  YieldHarvestConfig,
+};
+use solana_system_interface::instruction::create_account;
+
+use crate::bootstrap::{
+  BootstrapConfig, BootstrapStep, Bootstrapper, LstRegistration,
+};
+use crate::program_client::VersionedTransactionData;
+use crate::util::build_v0_transaction;
+
+/// Mints test tokens with a keypair holding mint authority.
+pub struct DevnetFaucet {
+  rpc_client: Arc<RpcClient>,
+  authority: Arc<Keypair>,
+}
+
+impl DevnetFaucet {
+  /// Faucet paying for and signing with `authority`.
+  #[must_use]
+  pub fn new(rpc_client: Arc<RpcClient>, authority: Arc<Keypair>) -> Self {
+    DevnetFaucet {
+      rpc_client,
+      authority,
+    }
+  }
+
+  /// Creates a new SPL mint with `decimals`, e.g. 9 for a test LST, under
+  /// the faucet's mint authority.
+  ///
+  /// # Errors
+  /// - Failed to fetch rent or blockhash
+  /// - Transaction fails to send or confirm
+  pub async fn create_mint(&self, decimals: u8) -> Result<Pubkey> {
+    let mint = Keypair::new();
+    let lamports = self
+      .rpc_client
+      .get_minimum_balance_for_rent_exemption(Mint::LEN)
+      .await?;
+    let instructions = create_mint_instructions(
+      &self.authority.pubkey(),
+      &mint,
+      decimals,
+      lamports,
+    )?;
+    self.send(instructions, &[&mint]).await?;
+    Ok(mint.pubkey())
+  }
+
+  /// Mints `amount` base units of `mint` to the ATA of `owner`, creating
+  /// the ATA if needed.
+  ///
+  /// # Errors
+  /// - Faucet is not the mint authority
+  /// - Transaction fails to send or confirm
+  pub async fn mint_to(
+    &self,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+  ) -> Result<Signature> {
+    let instructions =
+      mint_to_instructions(&self.authority.pubkey(), &mint, &owner, amount)?;
+    self.send(instructions, &[]).await
+  }
+
+  async fn send(
+    &self,
+    instructions: Vec<Instruction>,
+    signers: &[&Keypair],
+  ) -> Result<Signature> {
+    let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
+    let tx = build_v0_transaction(
+      &VersionedTransactionData::new(instructions, vec![]),
+      &self.authority,
+      signers,
+      recent_blockhash,
+    )?;
+    let signature = self.rpc_client.send_and_confirm_transaction(&tx).await?;
+    Ok(signature)
+  }
+}
+
+/// Instructions allocating and initializing `mint` under `authority`.
+///
+/// # Errors
+/// - Failed to build the initialize instruction
+pub fn create_mint_instructions(
+  authority: &Pubkey,
+  mint: &Keypair,
+  decimals: u8,
+  lamports: u64,
+) -> Result<Vec<Instruction>> {
+  let create = create_account(
+    authority,
+    &mint.pubkey(),
+    lamports,
+    u64::try_from(Mint::LEN)?,
+    &token::ID,
+  );
+  let initialize =
+    initialize_mint2(&token::ID, &mint.pubkey(), authority, None, decimals)?;
+  Ok(vec![create, initialize])
+}
+
+/// Instructions creating the ATA of `owner` and minting `amount` into it.
+///
+/// # Errors
+/// - Failed to build the mint instruction
+pub fn mint_to_instructions(
+  authority: &Pubkey,
+  mint: &Pubkey,
+  owner: &Pubkey,
+  amount: u64,
+) -> Result<Vec<Instruction>> {
+  let ata = get_associated_token_address(owner, mint);
+  let create_ata = create_associated_token_account_idempotent(
+    authority,
+    owner,
+    mint,
+    &token::ID,
+  );
+  let mint_ix = mint_to(&token::ID, mint, &ata, authority, &[], amount)?;
+  Ok(vec![create_ata, mint_ix])
+}
+
+/// Builds a fully verified SOL/USD `PriceUpdateV2` account owned by the Pyth
+/// receiver, published at `publish_time` and posted at `posted_slot`.
+///
+/// # Errors
+/// - Price does not fit Pyth's signed price field
+/// - Account serialization fails
+pub fn mock_price_update(
+  price: UFix64<N8>,
+  conf: UFix64<N8>,
+  publish_time: i64,
+  posted_slot: u64,
+) -> Result<Account> {
+  let data = MockPriceUpdate::sol_usd(price, conf, publish_time, posted_slot)
+    .account_data()?;
+  Ok(Account {
+    lamports: Rent::default().minimum_balance(data.len()),
+    data,
+    owner: pyth_solana_receiver_sdk::ID,
+    executable: false,
+    rent_epoch: 0,
+  })
+}
+
+/// Converts a fixed point value to its exchange IDL form.
+fn idl_value<Exp: Integer>(value: UFix64<Exp>) -> IdlUFixValue64 {
+  UFixValue64::from(value).into()
+}
+
+/// Protocol parameters for test deployments: 60 second oracle interval,
+/// 150% and 130% stability thresholds, low fees and half of yield to the
+/// stability pool.
+#[must_use]
+pub fn devnet_protocol_args() -> args::InitializeProtocol {
+  let fee_pair = |mint: u64, redeem: u64| FeePair {
+    mint: idl_value(UFix64::<N4>::new(mint)),
+    redeem: idl_value(UFix64::<N4>::new(redeem)),
+  };
+  args::InitializeProtocol {
+    oracle_interval_secs: 60,
+    stability_threshold_1: idl_value(UFix64::<N2>::new(150)),
+    stability_threshold_2: idl_value(UFix64::<N2>::new(130)),
+    stablecoin_fees: StablecoinFees {
+      normal: fee_pair(10, 10),
+      mode_1: fee_pair(20, 5),
+    },
+    levercoin_fees: LevercoinFees {
+      normal: fee_pair(10, 10),
+      mode_1: fee_pair(5, 20),
+      mode_2: fee_pair(5, 50),
+    },
+    yield_harvest_config: YieldHarvestConfig {
+      allocation: idl_value(UFix64::<N4>::new(5_000)),
+      fee: idl_value(UFix64::<N4>::new(1_000)),
+    },
+  }
+}
+
+/// Deployment config with [`devnet_protocol_args`], using the admin as
+/// upgrade authority and treasury.
+#[must_use]
+pub fn devnet_bootstrap_config(
+  admin: Pubkey,
+  registry_slot: u64,
+  lsts: Vec<LstRegistration>,
+) -> BootstrapConfig {
+  BootstrapConfig {
+    upgrade_authority: admin,
+    treasury: admin,
+    protocol: devnet_protocol_args(),
+    registry_slot,
+    lsts,
+  }
+}
+
+/// Seeds a test deployment, resuming any steps left from an earlier run
+/// with the same `registry_slot`.
+///
+/// # Errors
+/// - Any bootstrap transaction fails
+pub async fn seed_deployment(
+  bootstrapper: &Bootstrapper,
+  admin: Pubkey,
+  registry_slot: u64,
+  lsts: Vec<LstRegistration>,
+) -> Result<Vec<(BootstrapStep, Signature)>> {
+  let config = devnet_bootstrap_config(admin, registry_slot, lsts);
+  bootstrapper.run(&config).await
+}
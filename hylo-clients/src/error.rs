@@ -0,0 +1,215 @@
+//! Typed errors at the client API.
+//!
+//! Client methods return [`anyhow::Result`] so each layer can add context
+//! as a failure propagates, from a NAV or fee computation in hylo-core, or
+//! an instruction failing on-chain, up through the RPC call that carried
+//! it. [`HyloError`] classifies such a chain by its root failure without
+//! flattening it: it displays as the wrapped error and its
+//! [`source`](StdError::source) continues down the original chain, so `{:#}`
+//! still reaches the `exchange_math` error that started it.
+//!
+//! Program errors are recognized by number and name, so they classify the
+//! same on any deployment. Logs of a failed preflight attached by
+//! [`ProgramClient::send_v0_transaction`] take precedence over errors raised
+//! off-chain.
+//!
+//! [`ProgramClient::send_v0_transaction`]:
+//!   crate::program_client::ProgramClient::send_v0_transaction
+
+use std::error::Error as StdError;
+use std::fmt::{self, Display};
+
+use anchor_client::solana_client::client_error::ClientError as RpcError;
+use anchor_lang::error::Error as AnchorError;
+use hylo_core::error::CoreError;
+use hylo_idl::exchange::errors::ExchangeError;
+use hylo_idl::stability_pool::errors::StabilityPoolError;
+
+use crate::program_logs::ProgramLogs;
+
+/// Root failure of an error chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+  /// hylo-core protocol math, raised off-chain or logged by a program
+  Core {
+    number: u32,
+    name: String,
+  },
+
+  /// Custom error of the exchange program
+  Exchange(ExchangeError),
+
+  /// Custom error of the stability pool program
+  StabilityPool(StabilityPoolError),
+
+  /// Anchor framework error, e.g. a failed account constraint, or a custom
+  /// error of another program
+  Program {
+    number: u32,
+    name: String,
+  },
+
+  /// RPC transport or node failure with no program error logged
+  Rpc,
+  Other,
+}
+
+impl ErrorKind {
+  /// Kind of the program error numbered `number` and named `name`.
+  #[must_use]
+  pub fn from_program_error(number: u32, name: &str) -> ErrorKind {
+    let exchange =
+      ExchangeError::from_code(number).filter(|error| error.name() == name);
+    let pool = StabilityPoolError::from_code(number)
+      .filter(|error| error.name() == name);
+    match (exchange, pool) {
+      (Some(error), _) => ErrorKind::Exchange(error),
+      (None, Some(error)) => ErrorKind::StabilityPool(error),
+      (None, None) if is_core_number(number) => ErrorKind::Core {
+        number,
+        name: name.to_string(),
+      },
+      (None, None) => ErrorKind::Program {
+        number,
+        name: name.to_string(),
+      },
+    }
+  }
+
+  /// Whether this is `error` from hylo-core.
+  #[must_use]
+  pub fn is_core(&self, error: CoreError) -> bool {
+    let core = u32::from(error);
+    matches!(self, ErrorKind::Core { number, .. } if *number == core)
+  }
+}
+
+/// hylo-core numbers its errors from 7000, above every program's range.
+fn is_core_number(number: u32) -> bool {
+  number >= u32::from(CoreError::TotalSolCacheDecrement)
+}
+
+/// First program error logged in a failed preflight.
+fn logged_kind(logs: &ProgramLogs) -> Option<ErrorKind> {
+  logs
+    .anchor_errors()
+    .first()
+    .map(|log| ErrorKind::from_program_error(log.number, &log.code))
+}
+
+/// Kind of an Anchor error raised off-chain, e.g. by hylo-core math.
+fn raised_kind(error: &AnchorError) -> Option<ErrorKind> {
+  match error {
+    AnchorError::AnchorError(error) => Some(ErrorKind::from_program_error(
+      error.error_code_number,
+      &error.error_name,
+    )),
+    AnchorError::ProgramError(_) => None,
+  }
+}
+
+fn classify(error: &anyhow::Error) -> ErrorKind {
+  let logged = error.downcast_ref::<ProgramLogs>().and_then(logged_kind);
+  let raised = || {
+    error
+      .chain()
+      .find_map(|cause| cause.downcast_ref::<AnchorError>())
+      .and_then(raised_kind)
+  };
+  let rpc = || {
+    error
+      .chain()
+      .any(|cause| cause.is::<RpcError>())
+      .then_some(ErrorKind::Rpc)
+  };
+  logged
+    .or_else(raised)
+    .or_else(rpc)
+    .unwrap_or(ErrorKind::Other)
+}
+
+/// Error from the client API, classified by its root failure with the full
+/// chain kept.
+#[derive(Debug)]
+pub struct HyloError {
+  kind: ErrorKind,
+  error: anyhow::Error,
+}
+
+impl HyloError {
+  #[must_use]
+  pub fn kind(&self) -> &ErrorKind {
+    &self.kind
+  }
+
+  /// Whether the root failure is `error` from hylo-core.
+  #[must_use]
+  pub fn is_core(&self, error: CoreError) -> bool {
+    self.kind.is_core(error)
+  }
+
+  /// Wrapped error, with every context added along the way.
+  #[must_use]
+  pub fn into_inner(self) -> anyhow::Error {
+    self.error
+  }
+}
+
+impl Display for HyloError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    Display::fmt(&self.error, f)
+  }
+}
+
+impl StdError for HyloError {
+  fn source(&self) -> Option<&(dyn StdError + 'static)> {
+    self.error.source()
+  }
+}
+
+impl From<anyhow::Error> for HyloError {
+  fn from(error: anyhow::Error) -> HyloError {
+    HyloError {
+      kind: classify(&error),
+      error,
+    }
+  }
+}
+
+impl From<AnchorError> for HyloError {
+  fn from(error: AnchorError) -> HyloError {
+    anyhow::Error::from(error).into()
+  }
+}
+
+impl From<CoreError> for HyloError {
+  fn from(error: CoreError) -> HyloError {
+    AnchorError::from(error).into()
+  }
+}
+
+impl From<ExchangeError> for HyloError {
+  fn from(error: ExchangeError) -> HyloError {
+    AnchorError::from(error).into()
+  }
+}
+
+impl From<StabilityPoolError> for HyloError {
+  fn from(error: StabilityPoolError) -> HyloError {
+    AnchorError::from(error).into()
+  }
+}
+
+/// Attaches the logs of a failed preflight, as
+/// [`ProgramClient::send_v0_transaction`] does.
+///
+/// [`ProgramClient::send_v0_transaction`]:
+///   crate::program_client::ProgramClient::send_v0_transaction
+impl From<RpcError> for HyloError {
+  fn from(error: RpcError) -> HyloError {
+    match ProgramLogs::from_client_error(&error) {
+      Some(logs) => anyhow::Error::new(error).context(logs).into(),
+      None => anyhow::Error::new(error).into(),
+    }
+  }
+}
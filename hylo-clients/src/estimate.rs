@@ -0,0 +1,192 @@
+//! Transaction size and compute unit estimation.
+//!
+//! Predicts serialized size and a compute budget for a
+//! [`VersionedTransactionData`] so oversized transactions fail before they
+//! reach the RPC.
+
+use std::collections::HashMap;
+
+use anchor_client::solana_sdk::compute_budget::{
+  self, ComputeBudgetInstruction,
+};
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{v0, VersionedMessage};
+use anchor_client::solana_sdk::packet::PACKET_DATA_SIZE;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anchor_spl::{associated_token, token};
+use anyhow::{ensure, Result};
+use hylo_idl::{exchange, stability_pool};
+
+use crate::cu_calibration::CuBaselineTable;
+use crate::program_client::VersionedTransactionData;
+
+/// Maximum compute units a single transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Fallback for Hylo instructions without a recorded baseline.
+pub const DEFAULT_HYLO_INSTRUCTION_CUS: u64 = 100_000;
+
+/// Fallback for instructions from programs outside Hylo.
+pub const DEFAULT_EXTERNAL_INSTRUCTION_CUS: u64 = 30_000;
+
+/// Predicted footprint of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionEstimate {
+  /// Serialized size in bytes, including signatures.
+  pub size: usize,
+
+  /// Sum of per-instruction compute unit baselines.
+  pub compute_units: u64,
+}
+
+impl TransactionEstimate {
+  /// Checks the estimate against packet and compute limits.
+  ///
+  /// # Errors
+  /// - Serialized size exceeds [`PACKET_DATA_SIZE`]
+  /// - Compute units exceed [`MAX_COMPUTE_UNIT_LIMIT`]
+  pub fn validate(self) -> Result<Self> {
+    ensure!(
+      self.size <= PACKET_DATA_SIZE,
+      "Transaction size {} exceeds limit of {PACKET_DATA_SIZE} bytes",
+      self.size
+    );
+    ensure!(
+      self.compute_units <= MAX_COMPUTE_UNIT_LIMIT,
+      "Compute units {} exceed limit of {MAX_COMPUTE_UNIT_LIMIT}",
+      self.compute_units
+    );
+    Ok(self)
+  }
+
+  /// Builds a compute unit limit instruction for this estimate.
+  #[must_use]
+  pub fn compute_unit_limit_instruction(&self) -> Instruction {
+    let units = u32::try_from(self.compute_units).unwrap_or(u32::MAX);
+    ComputeBudgetInstruction::set_compute_unit_limit(units)
+  }
+}
+
+/// Per-instruction compute unit baselines, keyed by program and the first 8
+/// bytes of instruction data (the Anchor discriminator).
+#[derive(Debug, Clone)]
+pub struct ComputeUnitBaselines {
+  baselines: HashMap<(Pubkey, [u8; 8]), u64>,
+  program_defaults: HashMap<Pubkey, u64>,
+}
+
+impl Default for ComputeUnitBaselines {
+  fn default() -> Self {
+    let program_defaults = HashMap::from([
+      (exchange::ID, DEFAULT_HYLO_INSTRUCTION_CUS),
+      (stability_pool::ID, DEFAULT_HYLO_INSTRUCTION_CUS),
+      (associated_token::ID, 25_000),
+      (token::ID, 5_000),
+      (compute_budget::ID, 150),
+    ]);
+    ComputeUnitBaselines {
+      baselines: HashMap::new(),
+      program_defaults,
+    }
+  }
+}
+
+impl ComputeUnitBaselines {
+  /// Records a baseline for a specific instruction.
+  #[must_use]
+  pub fn with_baseline(
+    mut self,
+    program_id: Pubkey,
+    discriminator: [u8; 8],
+    units: u64,
+  ) -> Self {
+    self.baselines.insert((program_id, discriminator), units);
+    self
+  }
+
+  /// Records a fallback baseline for any instruction of a program.
+  #[must_use]
+  pub fn with_program_default(
+    mut self,
+    program_id: Pubkey,
+    units: u64,
+  ) -> Self {
+    self.program_defaults.insert(program_id, units);
+    self
+  }
+
+  /// Records every baseline of a calibration table, taking the largest
+  /// units among variants of the same instruction.
+  #[must_use]
+  pub fn with_table(self, table: &CuBaselineTable) -> Self {
+    table.max_units().into_iter().fold(
+      self,
+      |baselines, ((program_id, discriminator), units)| {
+        baselines.with_baseline(program_id, discriminator, units)
+      },
+    )
+  }
+
+  /// Baseline compute units for one instruction.
+  #[must_use]
+  pub fn instruction_units(&self, instruction: &Instruction) -> u64 {
+    instruction
+      .data
+      .first_chunk::<8>()
+      .and_then(|disc| self.baselines.get(&(instruction.program_id, *disc)))
+      .or_else(|| self.program_defaults.get(&instruction.program_id))
+      .copied()
+      .unwrap_or(DEFAULT_EXTERNAL_INSTRUCTION_CUS)
+  }
+
+  /// Estimates size and compute units for transaction data paid by `payer`.
+  ///
+  /// # Errors
+  /// - Message fails to compile against the given lookup tables
+  /// - Serialization fails
+  pub fn estimate(
+    &self,
+    VersionedTransactionData {
+      instructions,
+      lookup_tables,
+    }: &VersionedTransactionData,
+    payer: &Pubkey,
+  ) -> Result<TransactionEstimate> {
+    let message = v0::Message::try_compile(
+      payer,
+      instructions,
+      lookup_tables,
+      Hash::default(),
+    )?;
+    let num_sigs = message.header.num_required_signatures.into();
+    let tx = VersionedTransaction {
+      signatures: vec![Signature::default(); num_sigs],
+      message: VersionedMessage::V0(message),
+    };
+    let size = usize::try_from(bincode::serialized_size(&tx)?)?;
+    let compute_units = instructions
+      .iter()
+      .map(|ix| self.instruction_units(ix))
+      .sum();
+    Ok(TransactionEstimate {
+      size,
+      compute_units,
+    })
+  }
+
+  /// Estimates and validates transaction data against network limits.
+  ///
+  /// # Errors
+  /// - Estimation fails
+  /// - Size or compute limits exceeded
+  pub fn check(
+    &self,
+    vtd: &VersionedTransactionData,
+    payer: &Pubkey,
+  ) -> Result<TransactionEstimate> {
+    self.estimate(vtd, payer)?.validate()
+  }
+}
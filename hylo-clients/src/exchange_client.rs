@@ -4,22 +4,28 @@ use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::Program;
 use anyhow::{anyhow, Result};
+use hylo_core::fee_controller::{FeeController, LevercoinFees, StablecoinFees};
 use hylo_core::idl::tokens::{TokenMint, HYUSD, XSOL};
 use hylo_core::idl::{exchange, pda};
 use hylo_core::pyth::SOL_USD_PYTH_FEED;
+use hylo_core::stability_mode::StabilityController;
+use hylo_idl::exchange::accounts::Hylo;
 use hylo_idl::exchange::client::{accounts, args};
 use hylo_idl::exchange::events::ExchangeStats;
 use hylo_idl::exchange::instruction_builders;
 
+use crate::bootstrap::LstRegistration;
 use crate::instructions::ExchangeInstructionBuilder as ExchangeIB;
 use crate::program_client::{ProgramClient, VersionedTransactionData};
+use crate::rpc_gate::RpcGate;
 use crate::syntax_helpers::InstructionBuilderExt;
 use crate::transaction::{
   BuildTransactionData, LstSwapArgs, MintArgs, RedeemArgs, SwapArgs,
   TransactionSyntax,
 };
 use crate::util::{
-  EXCHANGE_LOOKUP_TABLE, LST, LST_REGISTRY_LOOKUP_TABLE, REFERENCE_WALLET,
+  build_lst_registry_chunks, EXCHANGE_LOOKUP_TABLE, LST,
+  LST_REGISTRY_LOOKUP_TABLE, REFERENCE_WALLET,
 };
 
 /// Client for interacting with the Hylo Exchange program.
@@ -78,6 +84,7 @@ use crate::util::{
 pub struct ExchangeClient {
   program: Program<Arc<Keypair>>,
   keypair: Arc<Keypair>,
+  gate: Option<Arc<RpcGate>>,
 }
 
 impl ProgramClient for ExchangeClient {
@@ -87,7 +94,11 @@ impl ProgramClient for ExchangeClient {
     program: Program<Arc<Keypair>>,
     keypair: Arc<Keypair>,
   ) -> ExchangeClient {
-    ExchangeClient { program, keypair }
+    ExchangeClient {
+      program,
+      keypair,
+      gate: None,
+    }
   }
 
   fn program(&self) -> &Program<Arc<Keypair>> {
@@ -97,9 +108,23 @@ impl ProgramClient for ExchangeClient {
   fn keypair(&self) -> Arc<Keypair> {
     self.keypair.clone()
   }
+
+  fn gate(&self) -> Option<&RpcGate> {
+    self.gate.as_deref()
+  }
 }
 
 impl ExchangeClient {
+  /// Routes account fetches through `gate`, sharing its rate limit and
+  /// in-flight requests with every other client on the same gate.
+  #[must_use]
+  pub fn with_gate(self, gate: Arc<RpcGate>) -> Self {
+    ExchangeClient {
+      gate: Some(gate),
+      ..self
+    }
+  }
+
   /// Initializes the Hylo exchange protocol.
   ///
   /// # Errors
@@ -186,6 +211,29 @@ impl ExchangeClient {
     Ok(VersionedTransactionData::one(instruction))
   }
 
+  /// Registers a supported LST, resolving its stake pool and calculator
+  /// accounts from [`hylo_idl::stake_pools`].
+  ///
+  /// # Errors
+  /// - LST has no known stake pool
+  pub fn register_known_lst(
+    &self,
+    lst_registry: Pubkey,
+    lst_mint: Pubkey,
+  ) -> Result<VersionedTransactionData> {
+    let lst = LstRegistration::known(&lst_mint)
+      .ok_or(anyhow!("No known stake pool for LST {lst_mint}"))?;
+    self.register_lst(
+      lst_registry,
+      lst.lst_mint,
+      lst.stake_pool_state,
+      lst.sanctum_calculator_program,
+      lst.sanctum_calculator_state,
+      lst.stake_pool_program,
+      lst.stake_pool_program_data,
+    )
+  }
+
   /// Builds transaction data for LST price oracle crank.
   ///
   /// # Errors
@@ -229,6 +277,40 @@ impl ExchangeClient {
     Ok(VersionedTransactionData::new(instructions, lookup_tables))
   }
 
+  /// Builds one `harvest_yield` transaction per chunk of at most
+  /// `lsts_per_tx` registered LSTs, for registries too large to crank in a
+  /// single transaction.
+  ///
+  /// # Errors
+  /// - Failed to load lookup tables
+  /// - Invalid chunk size or malformed registry
+  /// - Failed to build transaction data
+  pub async fn harvest_yield_chunked(
+    &self,
+    lsts_per_tx: usize,
+  ) -> Result<Vec<VersionedTransactionData>> {
+    let registry_lut =
+      self.load_lookup_table(&LST_REGISTRY_LOOKUP_TABLE).await?;
+    let exchange_lut = self.load_lookup_table(&EXCHANGE_LOOKUP_TABLE).await?;
+    build_lst_registry_chunks(&registry_lut, lsts_per_tx)?
+      .into_iter()
+      .map(|remaining_accounts| {
+        let instruction = instruction_builders::harvest_yield(
+          self.program.payer(),
+          LST_REGISTRY_LOOKUP_TABLE,
+          remaining_accounts,
+        );
+        let instructions = self
+          .program
+          .request()
+          .instruction(instruction)
+          .instructions()?;
+        let lookup_tables = vec![registry_lut.clone(), exchange_lut.clone()];
+        Ok(VersionedTransactionData::new(instructions, lookup_tables))
+      })
+      .collect()
+  }
+
   /// Gets exchange stats via RPC simulation.
   ///
   /// Uses `REFERENCE_WALLET` as the fee payer to allow simulation without
@@ -304,6 +386,79 @@ impl ExchangeClient {
     Ok(VersionedTransactionData::one(instruction))
   }
 
+  /// Updates the stability mode thresholds.
+  ///
+  /// # Errors
+  /// - Thresholds are not `X.XX` values above 1.0 in descending order
+  pub fn update_stability_thresholds(
+    &self,
+    args: &args::UpdateStabilityThresholds,
+  ) -> Result<VersionedTransactionData> {
+    StabilityController::new(
+      args.new_stability_threshold_1.try_into()?,
+      args.new_stability_threshold_2.try_into()?,
+    )?;
+    let instruction = instruction_builders::update_stability_thresholds(
+      self.program.payer(),
+      args,
+    );
+    Ok(VersionedTransactionData::one(instruction))
+  }
+
+  /// Updates the stablecoin fee table.
+  ///
+  /// # Errors
+  /// - Any fee is not a basis point value below 100%
+  pub fn update_stablecoin_fees(
+    &self,
+    args: &args::UpdateStablecoinFees,
+  ) -> Result<VersionedTransactionData> {
+    StablecoinFees::from(args.new_stablecoin_fees).validate()?;
+    let instruction =
+      instruction_builders::update_stablecoin_fees(self.program.payer(), args);
+    Ok(VersionedTransactionData::one(instruction))
+  }
+
+  /// Updates the levercoin fee table.
+  ///
+  /// # Errors
+  /// - Any fee is not a basis point value below 100%
+  pub fn update_levercoin_fees(
+    &self,
+    args: &args::UpdateLevercoinFees,
+  ) -> Result<VersionedTransactionData> {
+    LevercoinFees::from(args.new_levercoin_fees).validate()?;
+    let instruction =
+      instruction_builders::update_levercoin_fees(self.program.payer(), args);
+    Ok(VersionedTransactionData::one(instruction))
+  }
+
+  /// Loads the treasury configured on the Hylo account.
+  ///
+  /// # Errors
+  /// - Failed to fetch or deserialize the Hylo account
+  pub async fn load_treasury(&self) -> Result<Pubkey> {
+    let hylo = self.program.account::<Hylo>(*pda::HYLO).await?;
+    Ok(hylo.treasury)
+  }
+
+  /// Withdraws the fee vault balance of `fee_token_mint` to the treasury ATA.
+  ///
+  /// # Errors
+  /// - Failed to build transaction instructions
+  pub fn withdraw_fees(
+    &self,
+    treasury: Pubkey,
+    fee_token_mint: Pubkey,
+  ) -> Result<VersionedTransactionData> {
+    let instruction = instruction_builders::withdraw_fees(
+      self.program.payer(),
+      treasury,
+      fee_token_mint,
+    );
+    Ok(VersionedTransactionData::one(instruction))
+  }
+
   /// Updates the LST swap fee.
   ///
   /// # Errors
@@ -0,0 +1,266 @@
+//! Tabular exporters for decoded Hylo events.
+//!
+//! Flattens exchange and stability pool events into rows of exact decimal
+//! strings, then writes them as CSV or (with the `parquet` feature) Parquet.
+//!
+//! ```rust,no_run
+//! use hylo_clients::export::write_csv;
+//! use hylo_idl::exchange::events::MintStablecoinEventV2;
+//!
+//! # fn example(events: &[MintStablecoinEventV2]) -> anyhow::Result<()> {
+//! let file = std::fs::File::create("mint_stablecoin.csv")?;
+//! write_csv(file, events)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::UFixValue64;
+use itertools::Itertools;
+
+/// Renders a single event field as a table cell.
+pub trait Cell {
+  fn cell(&self) -> String;
+}
+
+impl Cell for u64 {
+  fn cell(&self) -> String {
+    self.to_string()
+  }
+}
+
+impl Cell for Pubkey {
+  fn cell(&self) -> String {
+    self.to_string()
+  }
+}
+
+impl Cell for UFixValue64 {
+  fn cell(&self) -> String {
+    format_ufix_value(*self)
+  }
+}
+
+impl Cell for hylo_idl::exchange::types::UFixValue64 {
+  fn cell(&self) -> String {
+    format_ufix_value((*self).into())
+  }
+}
+
+impl Cell for hylo_idl::stability_pool::types::UFixValue64 {
+  fn cell(&self) -> String {
+    format_ufix_value((*self).into())
+  }
+}
+
+impl Cell for Vec<Pubkey> {
+  fn cell(&self) -> String {
+    self.iter().join(" ")
+  }
+}
+
+/// Formats a fixed point value as an exact decimal string.
+#[must_use]
+pub fn format_ufix_value(UFixValue64 { bits, exp }: UFixValue64) -> String {
+  let digits = bits.to_string();
+  let scale = usize::from(exp.unsigned_abs());
+  if exp >= 0 {
+    format!("{digits}{}", "0".repeat(scale))
+  } else {
+    let padded = format!("{digits:0>width$}", width = scale + 1);
+    let (int, frac) = padded.split_at(padded.len() - scale);
+    format!("{int}.{frac}")
+  }
+}
+
+/// Event type that can be flattened into a fixed set of columns.
+pub trait EventRecord {
+  /// Table name, matching the IDL event name.
+  const NAME: &'static str;
+
+  /// Column headers, in the same order as [`EventRecord::row`].
+  const COLUMNS: &'static [&'static str];
+
+  /// Flattens the event into one cell per column.
+  fn row(&self) -> Vec<String>;
+}
+
+macro_rules! event_record {
+  ($program:ident::$event:ident { $($field:ident),+ $(,)? }) => {
+    impl EventRecord for hylo_idl::$program::events::$event {
+      const NAME: &'static str = stringify!($event);
+      const COLUMNS: &'static [&'static str] = &[$(stringify!($field)),+];
+
+      fn row(&self) -> Vec<String> {
+        vec![$(self.$field.cell()),+]
+      }
+    }
+  };
+}
+
+event_record!(exchange::MintStablecoinEventV2 {
+  minted,
+  nav,
+  sol_usd_price,
+  lst_mint,
+  lst_sol_price,
+  collateral_deposited,
+  fees_deposited,
+});
+
+event_record!(exchange::RedeemStablecoinEventV2 {
+  redeemed,
+  nav,
+  sol_usd_price,
+  lst_mint,
+  lst_sol_price,
+  collateral_withdrawn,
+  fees_deposited,
+});
+
+event_record!(exchange::MintLevercoinEventV2 {
+  minted,
+  nav,
+  sol_usd_price,
+  lst_mint,
+  lst_sol_price,
+  collateral_deposited,
+  fees_deposited,
+});
+
+event_record!(exchange::RedeemLevercoinEventV2 {
+  redeemed,
+  nav,
+  sol_usd_price,
+  lst_mint,
+  lst_sol_price,
+  collateral_withdrawn,
+  fees_deposited,
+});
+
+event_record!(exchange::SwapStableToLeverEventV1 {
+  stablecoin_burned,
+  stablecoin_fees,
+  stablecoin_nav,
+  levercoin_minted,
+  levercoin_nav,
+});
+
+event_record!(exchange::SwapLeverToStableEventV1 {
+  levercoin_burned,
+  levercoin_nav,
+  stablecoin_minted_user,
+  stablecoin_minted_fees,
+  stablecoin_nav,
+});
+
+event_record!(exchange::SwapLstEventV0 {
+  lst_a_mint,
+  lst_a_in,
+  lst_a_fees_extracted,
+  lst_b_mint,
+  lst_b_out,
+});
+
+event_record!(exchange::HarvestYieldEventV2 {
+  harvest_token_mint,
+  total_sol_harvested,
+  fees_extracted,
+  token_to_pool,
+  sol_usd_price,
+});
+
+event_record!(exchange::UpdateLstPricesEvent {
+  updated_mints,
+  new_total_sol,
+});
+
+event_record!(exchange::WithdrawFeesEvent {
+  mint,
+  vault,
+  treasury_ata,
+  amount,
+});
+
+event_record!(stability_pool::UserDepositEvent {
+  stablecoin_deposited,
+  lp_token_nav,
+  lp_token_minted,
+});
+
+event_record!(stability_pool::UserWithdrawEventV1 {
+  lp_token_burned,
+  stablecoin_withdrawn,
+  stablecoin_fees,
+  stablecoin_nav,
+  levercoin_withdrawn,
+  levercoin_nav,
+});
+
+event_record!(stability_pool::RebalanceStableToLeverEvent {
+  stablecoin_swapped
+});
+
+event_record!(stability_pool::RebalanceLeverToStableEvent {
+  levercoin_swapped
+});
+
+/// Writes events as CSV with a header row.
+/// Cells are decimal numbers or base58 keys, so no quoting is needed.
+///
+/// # Errors
+/// - Underlying writer fails
+pub fn write_csv<E: EventRecord, W: Write>(
+  mut writer: W,
+  events: &[E],
+) -> Result<()> {
+  writeln!(writer, "{}", E::COLUMNS.join(","))?;
+  events
+    .iter()
+    .try_for_each(|event| writeln!(writer, "{}", event.row().join(",")))?;
+  writer.flush()?;
+  Ok(())
+}
+
+/// Writes events as a single Parquet row group of UTF-8 columns, preserving
+/// exact decimal representations.
+///
+/// # Errors
+/// - Record batch construction
+/// - Parquet encoding or underlying writer fails
+#[cfg(feature = "parquet")]
+pub fn write_parquet<E: EventRecord, W: Write + Send>(
+  writer: W,
+  events: &[E],
+) -> Result<()> {
+  use std::sync::Arc;
+
+  use arrow::array::{ArrayRef, StringArray};
+  use arrow::datatypes::{DataType, Field, Schema};
+  use arrow::record_batch::RecordBatch;
+  use parquet::arrow::ArrowWriter;
+
+  let schema = Arc::new(Schema::new(
+    E::COLUMNS
+      .iter()
+      .map(|name| Field::new(*name, DataType::Utf8, false))
+      .collect_vec(),
+  ));
+  let rows = events.iter().map(EventRecord::row).collect_vec();
+  let columns = (0..E::COLUMNS.len())
+    .map(|i| {
+      let column: ArrayRef = Arc::new(StringArray::from_iter_values(
+        rows.iter().map(|row| row[i].as_str()),
+      ));
+      column
+    })
+    .collect_vec();
+  let batch = RecordBatch::try_new(schema.clone(), columns)?;
+  let mut parquet = ArrowWriter::try_new(writer, schema, None)?;
+  parquet.write(&batch)?;
+  parquet.close()?;
+  Ok(())
+}
@@ -0,0 +1,147 @@
+//! Instruction and transaction encodings for governance tooling.
+//!
+//! Admin operations built with the SDK are usually executed by a DAO or
+//! multisig rather than signed locally. [`TransactionExport`] renders built
+//! transaction data in the forms those tools import:
+//!
+//! * Squads: base58 serialized transaction
+//! * Explorer inspector: base64 serialized message, or a ready inspector URL
+//! * Realms: one base64 `InstructionData` per instruction, see
+//!   [`realms_instructions`]
+//!
+//! Signatures are left empty for the executing tool to fill.
+
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{v0, VersionedMessage};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anchor_client::Cluster;
+use anyhow::Result;
+use base64::prelude::{Engine, BASE64_STANDARD};
+
+use crate::program_client::VersionedTransactionData;
+
+/// Base URL of the Solana Explorer transaction inspector.
+pub const EXPLORER_INSPECTOR_URL: &str =
+  "https://explorer.solana.com/tx/inspector";
+
+/// Unsigned transaction in the encodings governance tools import.
+#[derive(Debug, Clone)]
+pub struct TransactionExport {
+  pub transaction: VersionedTransaction,
+}
+
+impl TransactionExport {
+  /// Compiles `vtd` for `payer`, typically the multisig vault or governance
+  /// treasury executing it.
+  ///
+  /// Tools that execute later replace `recent_blockhash`, so
+  /// `Hash::default()` is fine when the export is only imported.
+  ///
+  /// # Errors
+  /// - Message fails to compile against the given lookup tables
+  pub fn new(
+    VersionedTransactionData {
+      instructions,
+      lookup_tables,
+    }: &VersionedTransactionData,
+    payer: &Pubkey,
+    recent_blockhash: Hash,
+  ) -> Result<TransactionExport> {
+    let message = v0::Message::try_compile(
+      payer,
+      instructions,
+      lookup_tables,
+      recent_blockhash,
+    )?;
+    let num_sigs = message.header.num_required_signatures.into();
+    let transaction = VersionedTransaction {
+      signatures: vec![Signature::default(); num_sigs],
+      message: VersionedMessage::V0(message),
+    };
+    Ok(TransactionExport { transaction })
+  }
+
+  /// Base58 serialized transaction, as imported by Squads.
+  ///
+  /// # Errors
+  /// - Serialization fails
+  pub fn to_base58(&self) -> Result<String> {
+    Ok(bs58::encode(bincode::serialize(&self.transaction)?).into_string())
+  }
+
+  /// Base64 serialized transaction.
+  ///
+  /// # Errors
+  /// - Serialization fails
+  pub fn to_base64(&self) -> Result<String> {
+    Ok(BASE64_STANDARD.encode(bincode::serialize(&self.transaction)?))
+  }
+
+  /// Base64 serialized message, as pasted into the explorer inspector.
+  #[must_use]
+  pub fn message_base64(&self) -> String {
+    BASE64_STANDARD.encode(self.transaction.message.serialize())
+  }
+
+  /// Explorer inspector link decoding this transaction on `cluster`.
+  #[must_use]
+  pub fn inspector_url(&self, cluster: &Cluster) -> String {
+    let message = percent_encode(&self.message_base64());
+    let cluster = match cluster {
+      Cluster::Mainnet => String::new(),
+      Cluster::Devnet => "&cluster=devnet".to_string(),
+      Cluster::Testnet => "&cluster=testnet".to_string(),
+      other => {
+        format!("&cluster=custom&customUrl={}", percent_encode(other.url()))
+      }
+    };
+    format!("{EXPLORER_INSPECTOR_URL}?message={message}{cluster}")
+  }
+}
+
+/// Base64 Borsh `InstructionData` of SPL Governance, as pasted into a Realms
+/// custom instruction: program ID, account metas with signer and writable
+/// flags, then instruction data, each vector prefixed by its `u32` length.
+#[must_use]
+pub fn realms_instruction(instruction: &Instruction) -> String {
+  let len = |n: usize| u32::try_from(n).unwrap_or(u32::MAX).to_le_bytes();
+  let accounts = instruction.accounts.iter().flat_map(|meta| {
+    meta
+      .pubkey
+      .to_bytes()
+      .into_iter()
+      .chain([u8::from(meta.is_signer), u8::from(meta.is_writable)])
+  });
+  let bytes: Vec<u8> = instruction
+    .program_id
+    .to_bytes()
+    .into_iter()
+    .chain(len(instruction.accounts.len()))
+    .chain(accounts)
+    .chain(len(instruction.data.len()))
+    .chain(instruction.data.iter().copied())
+    .collect();
+  BASE64_STANDARD.encode(bytes)
+}
+
+/// Realms encoding of every instruction, in order.
+#[must_use]
+pub fn realms_instructions(instructions: &[Instruction]) -> Vec<String> {
+  instructions.iter().map(realms_instruction).collect()
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+  value
+    .bytes()
+    .map(|byte| match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        char::from(byte).to_string()
+      }
+      _ => format!("%{byte:02X}"),
+    })
+    .collect()
+}
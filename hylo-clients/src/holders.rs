@@ -0,0 +1,207 @@
+//! Holder concentration of hyUSD, sHYUSD and xSOL.
+//!
+//! Token accounts are listed with `getProgramAccounts` filtered by mint, then
+//! aggregated by owner so wallets with several accounts count once. A
+//! [`ConcentrationReport`] ranks the largest holders and summarizes how much
+//! of the circulating supply they control, including the Herfindahl-Hirschman
+//! index (sum of squared shares) used for stablecoin risk monitoring.
+
+use std::collections::HashMap;
+
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::{
+  RpcAccountInfoConfig, RpcProgramAccountsConfig,
+};
+use anchor_client::solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use anyhow::Result;
+use fix::prelude::*;
+use futures::future::try_join_all;
+use hylo_core::idl::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
+use itertools::Itertools;
+
+/// Tokens covered by [`protocol_concentration`].
+pub const REPORTED_MINTS: [Pubkey; 3] = [HYUSD::MINT, SHYUSD::MINT, XSOL::MINT];
+
+/// One owner's aggregated balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holder {
+  pub owner: Pubkey,
+  pub amount: u64,
+  /// Fraction of the held supply
+  pub share: UFix64<N4>,
+}
+
+/// Ranked holders of one mint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcentrationReport {
+  pub mint: Pubkey,
+
+  /// Owners with a non-zero balance
+  pub holder_count: usize,
+
+  /// Sum of all token account balances
+  pub held_supply: u64,
+
+  /// Largest holders, descending
+  pub top: Vec<Holder>,
+
+  /// Combined share of [`Self::top`]
+  pub top_share: UFix64<N4>,
+
+  /// Sum of squared holder shares, from near zero (dispersed) to one, in
+  /// squared basis points so that dispersed holdings do not round to zero
+  pub herfindahl: UFix64<N8>,
+}
+
+/// `part / whole` in basis points, zero when `whole` is zero.
+fn share_of(part: u128, whole: u128) -> UFix64<N4> {
+  let bps = part.saturating_mul(10_000).checked_div(whole).unwrap_or(0);
+  UFix64::new(u64::try_from(bps).unwrap_or(u64::MAX))
+}
+
+/// Sum of squared shares `sum_of_squares / held²` in squared basis points,
+/// zero when `held` is zero.
+///
+/// Dividing by `held` once first keeps the scaling within `u128`, and loses
+/// less than one base unit of the weighted mean holding.
+fn herfindahl_of(sum_of_squares: u128, held: u128) -> UFix64<N8> {
+  let weighted_mean = sum_of_squares.checked_div(held).unwrap_or(0);
+  let bps_squared = weighted_mean
+    .saturating_mul(100_000_000)
+    .checked_div(held)
+    .unwrap_or(0);
+  UFix64::new(u64::try_from(bps_squared).unwrap_or(u64::MAX))
+}
+
+/// Ranks `balances` (owner, amount) of `mint`, keeping the `top_n` largest.
+#[must_use]
+pub fn concentration(
+  mint: Pubkey,
+  balances: &[(Pubkey, u64)],
+  top_n: usize,
+) -> ConcentrationReport {
+  let by_owner = balances.iter().filter(|(_, amount)| *amount > 0).fold(
+    HashMap::new(),
+    |mut acc, (owner, amount)| {
+      let total: &mut u64 = acc.entry(*owner).or_default();
+      *total = total.saturating_add(*amount);
+      acc
+    },
+  );
+  let ranked = by_owner
+    .into_iter()
+    .sorted_by(|(a_owner, a), (b_owner, b)| {
+      b.cmp(a).then_with(|| a_owner.cmp(b_owner))
+    })
+    .collect_vec();
+  let held: u128 = ranked.iter().map(|(_, amount)| u128::from(*amount)).sum();
+  let sum_of_squares: u128 = ranked
+    .iter()
+    .map(|(_, amount)| u128::from(*amount).saturating_pow(2))
+    .fold(0, u128::saturating_add);
+  let top = ranked
+    .iter()
+    .take(top_n)
+    .map(|(owner, amount)| Holder {
+      owner: *owner,
+      amount: *amount,
+      share: share_of(u128::from(*amount), held),
+    })
+    .collect_vec();
+  let top_held = top.iter().map(|holder| u128::from(holder.amount)).sum();
+  ConcentrationReport {
+    mint,
+    holder_count: ranked.len(),
+    held_supply: u64::try_from(held).unwrap_or(u64::MAX),
+    top_share: share_of(top_held, held),
+    herfindahl: herfindahl_of(sum_of_squares, held),
+    top,
+  }
+}
+
+/// Owner and balance of every token account for `mint`.
+///
+/// # Errors
+/// - `getProgramAccounts` request fails
+/// - Account data cannot be unpacked as a token account
+pub async fn token_balances(
+  rpc_client: &RpcClient,
+  mint: &Pubkey,
+) -> Result<Vec<(Pubkey, u64)>> {
+  let config = RpcProgramAccountsConfig {
+    filters: Some(vec![
+      RpcFilterType::DataSize(u64::try_from(TokenAccount::LEN)?),
+      RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+    ]),
+    account_config: RpcAccountInfoConfig {
+      encoding: Some(UiAccountEncoding::Base64),
+      ..RpcAccountInfoConfig::default()
+    },
+    ..RpcProgramAccountsConfig::default()
+  };
+  rpc_client
+    .get_program_accounts_with_config(&token::ID, config)
+    .await?
+    .iter()
+    .map(|(_, account)| {
+      let token_account = TokenAccount::unpack(&account.data)?;
+      Ok((token_account.owner, token_account.amount))
+    })
+    .collect()
+}
+
+/// Fetches holders of `mint` and ranks the `top_n` largest.
+///
+/// # Errors
+/// - Token accounts cannot be fetched or unpacked
+pub async fn mint_concentration(
+  rpc_client: &RpcClient,
+  mint: Pubkey,
+  top_n: usize,
+) -> Result<ConcentrationReport> {
+  let balances = token_balances(rpc_client, &mint).await?;
+  Ok(concentration(mint, &balances, top_n))
+}
+
+/// Concentration reports for hyUSD, sHYUSD and xSOL, fetched concurrently.
+///
+/// # Errors
+/// - Token accounts cannot be fetched or unpacked
+pub async fn protocol_concentration(
+  rpc_client: &RpcClient,
+  top_n: usize,
+) -> Result<Vec<ConcentrationReport>> {
+  try_join_all(
+    REPORTED_MINTS
+      .iter()
+      .map(|mint| mint_concentration(rpc_client, *mint, top_n)),
+  )
+  .await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn herfindahl_keeps_dispersed_holdings() {
+    let balances = (0..1_000)
+      .map(|_| (Pubkey::new_unique(), 1_000_000))
+      .collect_vec();
+    let report = concentration(HYUSD::MINT, &balances, 10);
+    assert_eq!(report.herfindahl, UFix64::new(100_000));
+    assert_eq!(report.top_share, UFix64::new(100));
+  }
+
+  #[test]
+  fn herfindahl_of_single_holder_is_one() {
+    let report =
+      concentration(XSOL::MINT, &[(Pubkey::new_unique(), u64::MAX)], 1);
+    assert_eq!(report.herfindahl, UFix64::new(100_000_000));
+  }
+}
@@ -0,0 +1,139 @@
+//! Detects drift between deployed program IDLs and the ones compiled into
+//! `declare_program!`.
+//!
+//! Account and event decoding trusts the baked layouts, so a program upgrade
+//! that reorders fields deserializes into garbage rather than failing.
+//! [`IdlGuard::check_idl`] fetches the on-chain IDL and reports any
+//! difference as an [`IdlMismatch`] for the caller to log or refuse on.
+
+use std::fmt::{self, Display};
+
+use anchor_lang_idl_spec::Idl;
+use anyhow::Result;
+
+use crate::exchange_client::ExchangeClient;
+use crate::program_client::ProgramClient;
+use crate::runtime_idl::RuntimeIdl;
+use crate::stability_pool_client::StabilityPoolClient;
+
+/// Difference between the baked and deployed IDL of a program.
+///
+/// NB: Items compare in full, so doc comment edits also count as changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlMismatch {
+  pub program: String,
+  pub baked_version: String,
+  pub deployed_version: String,
+
+  /// Types, accounts, events, and instructions changed or removed on chain
+  pub changed: Vec<String>,
+
+  /// Instructions only in the deployed IDL
+  pub added_instructions: Vec<String>,
+}
+
+impl IdlMismatch {
+  /// Compares two IDLs, returning `None` when they agree.
+  #[must_use]
+  pub fn between(baked: &Idl, deployed: &Idl) -> Option<IdlMismatch> {
+    let changed: Vec<String> =
+      diff("type", &baked.types, &deployed.types, |t| &t.name)
+        .chain(diff("account", &baked.accounts, &deployed.accounts, |a| {
+          &a.name
+        }))
+        .chain(diff("event", &baked.events, &deployed.events, |e| &e.name))
+        .chain(diff(
+          "instruction",
+          &baked.instructions,
+          &deployed.instructions,
+          |ix| &ix.name,
+        ))
+        .collect();
+    let added_instructions: Vec<String> = deployed
+      .instructions
+      .iter()
+      .filter(|ix| baked.instructions.iter().all(|b| b.name != ix.name))
+      .map(|ix| ix.name.clone())
+      .collect();
+    let same_version = baked.metadata.version == deployed.metadata.version;
+    if same_version && changed.is_empty() && added_instructions.is_empty() {
+      None
+    } else {
+      Some(IdlMismatch {
+        program: baked.metadata.name.clone(),
+        baked_version: baked.metadata.version.clone(),
+        deployed_version: deployed.metadata.version.clone(),
+        changed,
+        added_instructions,
+      })
+    }
+  }
+
+  /// Whether baked layouts may no longer decode deployed data.
+  #[must_use]
+  pub fn breaks_layout(&self) -> bool {
+    !self.changed.is_empty()
+  }
+}
+
+impl Display for IdlMismatch {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} IDL mismatch: baked {}, deployed {}",
+      self.program, self.baked_version, self.deployed_version
+    )?;
+    if !self.changed.is_empty() {
+      write!(f, "; changed {}", self.changed.join(", "))?;
+    }
+    if !self.added_instructions.is_empty() {
+      write!(f, "; added {}", self.added_instructions.join(", "))?;
+    }
+    Ok(())
+  }
+}
+
+/// Names of baked items missing from or different in the deployed list.
+fn diff<'a, T: PartialEq>(
+  kind: &'a str,
+  baked: &'a [T],
+  deployed: &'a [T],
+  name: fn(&T) -> &String,
+) -> impl Iterator<Item = String> + 'a {
+  baked
+    .iter()
+    .filter(move |item| {
+      deployed
+        .iter()
+        .find(|d| name(d) == name(item))
+        .is_none_or(|d| d != *item)
+    })
+    .map(move |item| format!("{kind} {}", name(item)))
+}
+
+/// Client whose program IDL is compiled in and can be checked on chain.
+#[async_trait::async_trait]
+pub trait IdlGuard: ProgramClient + Sync {
+  /// IDL JSON the client's `declare_program!` was generated from.
+  const BAKED_IDL: &'static str;
+
+  /// Fetches the deployed IDL and compares it to [`Self::BAKED_IDL`].
+  ///
+  /// # Errors
+  /// - Deployed IDL cannot be fetched or decoded
+  /// - Baked IDL fails to parse
+  async fn check_idl(&self) -> Result<Option<IdlMismatch>> {
+    let baked: Idl = serde_json::from_str(Self::BAKED_IDL)?;
+    let deployed =
+      RuntimeIdl::fetch(&self.program().rpc(), Self::PROGRAM_ID).await?;
+    Ok(IdlMismatch::between(&baked, deployed.idl()))
+  }
+}
+
+impl IdlGuard for ExchangeClient {
+  const BAKED_IDL: &'static str = hylo_idl::exchange::IDL_JSON;
+}
+
+impl IdlGuard for StabilityPoolClient {
+  const BAKED_IDL: &'static str = hylo_idl::stability_pool::IDL_JSON;
+}
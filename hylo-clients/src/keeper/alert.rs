@@ -0,0 +1,279 @@
+//! Notification sinks for keeper tasks and watchers.
+//!
+//! Tasks raise an [`Alert`] and hand it to an [`AlertRouter`], which fans it
+//! out to every configured [`AlertSink`] at or above its minimum severity.
+//! Webhook, Discord, and Telegram sinks are available with the `alerts`
+//! feature; anything else implements [`AlertSink`] directly.
+
+use std::fmt::{self, Display};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use hylo_core::stability_mode::StabilityMode;
+use itertools::Itertools;
+
+use crate::keeper::invariants::Invariant;
+
+/// How urgently an operator should look at an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  Info,
+  Warning,
+  Critical,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Severity::Info => f.write_str("INFO"),
+      Severity::Warning => f.write_str("WARNING"),
+      Severity::Critical => f.write_str("CRITICAL"),
+    }
+  }
+}
+
+/// Operator-facing event raised by a keeper or watcher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+  /// Protocol moved between stability modes
+  ModeTransition {
+    from: StabilityMode,
+    to: StabilityMode,
+  },
+
+  /// A keeper round or watcher poll failed
+  TaskFailed { task: &'static str, error: String },
+
+  /// SOL/USD oracle has not published within the allowed age
+  StaleOracle { publish_time: i64, now: i64 },
+
+  /// An on-chain protocol invariant does not hold
+  InvariantViolated {
+    invariant: Invariant,
+    detail: String,
+  },
+}
+
+impl Alert {
+  /// Builds a [`Alert::TaskFailed`] keeping the full error chain.
+  #[must_use]
+  pub fn task_failed(task: &'static str, error: &anyhow::Error) -> Alert {
+    Alert::TaskFailed {
+      task,
+      error: format!("{error:#}"),
+    }
+  }
+
+  /// Entering depeg is critical, any other deterioration a warning, and a
+  /// recovery informational.
+  #[must_use]
+  pub fn severity(&self) -> Severity {
+    match self {
+      Alert::ModeTransition {
+        to: StabilityMode::Depeg,
+        ..
+      }
+      | Alert::StaleOracle { .. }
+      | Alert::InvariantViolated { .. } => Severity::Critical,
+      Alert::ModeTransition { from, to } if to > from => Severity::Warning,
+      Alert::ModeTransition { .. } => Severity::Info,
+      Alert::TaskFailed { .. } => Severity::Warning,
+    }
+  }
+}
+
+impl Display for Alert {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Alert::ModeTransition { from, to } => {
+        write!(f, "Stability mode changed from {from} to {to}")
+      }
+      Alert::TaskFailed { task, error } => write!(f, "{task} failed: {error}"),
+      Alert::StaleOracle { publish_time, now } => write!(
+        f,
+        "SOL/USD oracle is {}s old (published at {publish_time})",
+        now.saturating_sub(*publish_time)
+      ),
+      Alert::InvariantViolated { invariant, detail } => {
+        write!(f, "Invariant {invariant} violated: {detail}")
+      }
+    }
+  }
+}
+
+/// Destination for alerts.
+#[async_trait::async_trait]
+pub trait AlertSink: Send + Sync {
+  /// Delivers one alert.
+  ///
+  /// # Errors
+  /// - Delivery fails
+  async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+/// Fans alerts out to every sink.
+pub struct AlertRouter {
+  sinks: Vec<Box<dyn AlertSink>>,
+  min_severity: Severity,
+}
+
+impl AlertRouter {
+  /// Router dropping alerts below `min_severity`.
+  #[must_use]
+  pub fn new(min_severity: Severity) -> AlertRouter {
+    AlertRouter {
+      sinks: vec![],
+      min_severity,
+    }
+  }
+
+  #[must_use]
+  pub fn with_sink<S: AlertSink + 'static>(mut self, sink: S) -> AlertRouter {
+    self.sinks.push(Box::new(sink));
+    self
+  }
+
+  /// Sends `alert` to all sinks concurrently.
+  ///
+  /// # Errors
+  /// - Any sink fails, after every sink has been tried
+  pub async fn notify(&self, alert: &Alert) -> Result<()> {
+    if alert.severity() < self.min_severity {
+      Ok(())
+    } else {
+      let failures = join_all(self.sinks.iter().map(|sink| sink.send(alert)))
+        .await
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|e| format!("{e:#}"))
+        .collect_vec();
+      if failures.is_empty() {
+        Ok(())
+      } else {
+        Err(anyhow!("Alert delivery failed: {}", failures.join("; ")))
+      }
+    }
+  }
+
+  /// Sends every alert in `alerts`, logging delivery failures instead of
+  /// returning them, so that a failing sink never stops the task raising
+  /// them.
+  pub async fn deliver(&self, alerts: &[Alert]) {
+    join_all(alerts.iter().map(|alert| self.notify(alert)))
+      .await
+      .into_iter()
+      .filter_map(Result::err)
+      .for_each(|e| log_failure("Dropped alert", &e));
+  }
+}
+
+/// Reports a failure a task recovers from through `tracing`. Without the
+/// feature the report is dropped; the failure still reaches [`TaskHealth`]
+/// and, where configured, an [`AlertRouter`].
+///
+/// [`TaskHealth`]: crate::keeper::health::TaskHealth
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn log_failure(context: &str, error: &anyhow::Error) {
+  #[cfg(feature = "tracing")]
+  tracing::warn!("{context}: {error:#}");
+}
+
+#[cfg(feature = "alerts")]
+pub use http_sinks::{DiscordSink, TelegramSink, WebhookSink};
+
+#[cfg(feature = "alerts")]
+mod http_sinks {
+  use anyhow::Result;
+  use serde_json::{json, Value};
+
+  use super::{Alert, AlertSink};
+
+  async fn post(http: &reqwest::Client, url: &str, body: &Value) -> Result<()> {
+    http.post(url).json(body).send().await?.error_for_status()?;
+    Ok(())
+  }
+
+  /// Posts `{ "severity": .., "message": .. }` to a generic webhook.
+  pub struct WebhookSink {
+    http: reqwest::Client,
+    url: String,
+  }
+
+  impl WebhookSink {
+    #[must_use]
+    pub fn new(url: String) -> WebhookSink {
+      WebhookSink {
+        http: reqwest::Client::new(),
+        url,
+      }
+    }
+  }
+
+  #[async_trait::async_trait]
+  impl AlertSink for WebhookSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+      let body = json!({
+        "severity": alert.severity().to_string(),
+        "message": alert.to_string(),
+      });
+      post(&self.http, &self.url, &body).await
+    }
+  }
+
+  /// Posts to a Discord channel webhook.
+  pub struct DiscordSink {
+    http: reqwest::Client,
+    webhook_url: String,
+  }
+
+  impl DiscordSink {
+    #[must_use]
+    pub fn new(webhook_url: String) -> DiscordSink {
+      DiscordSink {
+        http: reqwest::Client::new(),
+        webhook_url,
+      }
+    }
+  }
+
+  #[async_trait::async_trait]
+  impl AlertSink for DiscordSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+      let body = json!({
+        "content": format!("**{}** {alert}", alert.severity()),
+      });
+      post(&self.http, &self.webhook_url, &body).await
+    }
+  }
+
+  /// Sends messages to a Telegram chat through the Bot API.
+  pub struct TelegramSink {
+    http: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+  }
+
+  impl TelegramSink {
+    #[must_use]
+    pub fn new(bot_token: String, chat_id: String) -> TelegramSink {
+      TelegramSink {
+        http: reqwest::Client::new(),
+        bot_token,
+        chat_id,
+      }
+    }
+  }
+
+  #[async_trait::async_trait]
+  impl AlertSink for TelegramSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+      let url =
+        format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+      let body = json!({
+        "chat_id": self.chat_id,
+        "text": format!("[{}] {alert}", alert.severity()),
+      });
+      post(&self.http, &url, &body).await
+    }
+  }
+}
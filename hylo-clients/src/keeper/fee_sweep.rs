@@ -0,0 +1,180 @@
+//! Sweeps accumulated fee vault balances to the protocol treasury.
+//!
+//! Mint and redeem fees accrue in a vault per fee token: one for each LST,
+//! plus hyUSD and xSOL for swaps. [`FeeSweeper`] polls those vaults and sends
+//! a `withdraw_fees` transaction for each one holding at least its threshold.
+
+use std::time::Duration;
+
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use anyhow::Result;
+use fix::prelude::UFix64;
+use futures::future::try_join_all;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::TokenMint;
+use itertools::Itertools;
+
+use crate::exchange_client::ExchangeClient;
+use crate::keeper::alert::{log_failure, Alert, AlertRouter};
+use crate::keeper::health::TaskHealth;
+use crate::program_client::ProgramClient;
+
+/// Fee vault to watch, with the balance at which it is swept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SweepTarget {
+  pub mint: Pubkey,
+
+  /// Minimum vault balance in base units
+  pub threshold: u64,
+}
+
+impl SweepTarget {
+  /// Watches the fee vault of token `T`.
+  #[must_use]
+  pub fn new<T: TokenMint>(threshold: UFix64<T::Exp>) -> SweepTarget {
+    SweepTarget {
+      mint: T::MINT,
+      threshold: threshold.bits,
+    }
+  }
+}
+
+/// Fee vault balance at the time of a poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultBalance {
+  pub target: SweepTarget,
+  pub amount: u64,
+}
+
+impl VaultBalance {
+  /// Whether the balance is nonzero and at or above the target threshold.
+  #[must_use]
+  pub fn should_sweep(&self) -> bool {
+    self.amount > 0 && self.amount >= self.target.threshold
+  }
+}
+
+/// Keeper task withdrawing fee vaults to the treasury.
+pub struct FeeSweeper {
+  client: ExchangeClient,
+  targets: Vec<SweepTarget>,
+  interval: Duration,
+  health: TaskHealth,
+}
+
+impl FeeSweeper {
+  /// Name the sweeper records rounds and reports failures under.
+  pub const TASK: &str = "fee sweep";
+
+  #[must_use]
+  pub fn new(
+    client: ExchangeClient,
+    targets: Vec<SweepTarget>,
+    interval: Duration,
+  ) -> FeeSweeper {
+    FeeSweeper {
+      client,
+      targets,
+      interval,
+      health: TaskHealth::new(),
+    }
+  }
+
+  /// Records every round in `health` under [`Self::TASK`].
+  #[must_use]
+  pub fn with_health(self, health: TaskHealth) -> FeeSweeper {
+    FeeSweeper { health, ..self }
+  }
+
+  /// Fetches the current balance of every target fee vault.
+  /// Vaults that do not exist yet report zero.
+  ///
+  /// # Errors
+  /// - Failed to fetch vault accounts
+  /// - Vault data cannot be unpacked as a token account
+  pub async fn balances(&self) -> Result<Vec<VaultBalance>> {
+    let vaults = self
+      .targets
+      .iter()
+      .map(|target| pda::fee_vault(target.mint))
+      .collect_vec();
+    let accounts = self.client.get_multiple_accounts(&vaults).await?;
+    self
+      .targets
+      .iter()
+      .zip(accounts)
+      .map(|(target, account)| {
+        let amount = account
+          .map(|acc| TokenAccount::unpack(&acc.data))
+          .transpose()?
+          .map_or(0, |vault| vault.amount);
+        Ok(VaultBalance {
+          target: *target,
+          amount,
+        })
+      })
+      .try_collect()
+  }
+
+  /// Sweeps every vault at or above its threshold.
+  ///
+  /// # Errors
+  /// - Failed to fetch balances or the treasury
+  /// - Any withdrawal transaction fails
+  pub async fn sweep_once(&self) -> Result<Vec<Signature>> {
+    let due = self
+      .balances()
+      .await?
+      .into_iter()
+      .filter(VaultBalance::should_sweep)
+      .collect_vec();
+    if due.is_empty() {
+      Ok(vec![])
+    } else {
+      let treasury = self.client.load_treasury().await?;
+      try_join_all(due.iter().map(|balance| async move {
+        let vtd = self.client.withdraw_fees(treasury, balance.target.mint)?;
+        self.client.send_v0_transaction(&vtd).await
+      }))
+      .await
+    }
+  }
+
+  /// Runs [`Self::sweep_once`] every `interval`, passing each round's
+  /// signatures to `on_sweep`. Failed rounds are logged and the schedule
+  /// keeps going.
+  pub async fn run<F: FnMut(Vec<Signature>)>(&self, on_sweep: F) {
+    stream::repeat(())
+      .fold(on_sweep, |mut on_sweep, ()| async move {
+        let outcome = self.sweep_once().await;
+        self.health.record(Self::TASK, &outcome);
+        match outcome {
+          Ok(signatures) => on_sweep(signatures),
+          Err(e) => log_failure("Fee sweep round failed", &e),
+        }
+        tokio::time::sleep(self.interval).await;
+        on_sweep
+      })
+      .await;
+  }
+
+  /// Like [`Self::run`], but also reports failed rounds through `alerts`.
+  /// Alerts that cannot be delivered are logged.
+  pub async fn run_with_alerts(&self, alerts: &AlertRouter) {
+    stream::repeat(())
+      .for_each(|()| async move {
+        let outcome = self.sweep_once().await;
+        self.health.record(Self::TASK, &outcome);
+        if let Err(e) = outcome {
+          log_failure("Fee sweep round failed", &e);
+          alerts.deliver(&[Alert::task_failed(Self::TASK, &e)]).await;
+        }
+        tokio::time::sleep(self.interval).await;
+      })
+      .await;
+  }
+}
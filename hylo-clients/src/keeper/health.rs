@@ -0,0 +1,197 @@
+//! Progress tracking for keeper tasks, read by health endpoints.
+//!
+//! Keeper tasks run forever and report failures through alerts, but an
+//! orchestrator restarting a stuck process needs a liveness signal instead.
+//! [`TaskHealth`] records the outcome of every round of each registered
+//! task, and reports a task unhealthy once it has gone longer than its
+//! allowed age without a successful round. Each keeper records its rounds
+//! into the record passed to its `with_health`, under its `TASK` name.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+/// Progress of one registered task.
+#[derive(Debug, Clone)]
+struct TaskRecord {
+  max_age: Duration,
+  registered_at: Instant,
+  last_success: Option<Instant>,
+  last_error: Option<String>,
+  consecutive_failures: u32,
+}
+
+/// Health of one task at the time of a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskStatus {
+  pub task: &'static str,
+
+  /// Time since the last successful round, or since registration if none
+  /// has succeeded yet
+  pub since_success: Duration,
+
+  /// Longest `since_success` at which the task is healthy
+  pub max_age: Duration,
+
+  /// Whether any round has succeeded since registration
+  pub ever_succeeded: bool,
+
+  /// Failed rounds since the last successful one
+  pub consecutive_failures: u32,
+
+  /// Error chain of the most recent failed round
+  pub last_error: Option<String>,
+}
+
+impl TaskStatus {
+  /// Whether the task succeeded within its allowed age.
+  #[must_use]
+  pub fn is_healthy(&self) -> bool {
+    self.since_success <= self.max_age
+  }
+}
+
+/// Shared record of keeper task rounds. Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct TaskHealth {
+  tasks: Arc<Mutex<BTreeMap<&'static str, TaskRecord>>>,
+}
+
+impl TaskHealth {
+  #[must_use]
+  pub fn new() -> TaskHealth {
+    TaskHealth::default()
+  }
+
+  /// Expects `task` to succeed at least once every `max_age`, starting now.
+  /// Registering a task again resets its record.
+  #[must_use]
+  pub fn with_task(self, task: &'static str, max_age: Duration) -> TaskHealth {
+    self.with_task_at(task, max_age, Instant::now())
+  }
+
+  fn with_task_at(
+    self,
+    task: &'static str,
+    max_age: Duration,
+    now: Instant,
+  ) -> TaskHealth {
+    self.lock().insert(
+      task,
+      TaskRecord {
+        max_age,
+        registered_at: now,
+        last_success: None,
+        last_error: None,
+        consecutive_failures: 0,
+      },
+    );
+    self
+  }
+
+  /// Records the outcome of one round of `task`. Rounds of unregistered
+  /// tasks are ignored.
+  pub fn record<T>(&self, task: &'static str, outcome: &Result<T>) {
+    self.record_at(task, outcome, Instant::now());
+  }
+
+  fn record_at<T>(
+    &self,
+    task: &'static str,
+    outcome: &Result<T>,
+    now: Instant,
+  ) {
+    if let Some(record) = self.lock().get_mut(task) {
+      match outcome {
+        Ok(_) => {
+          record.last_success = Some(now);
+          record.consecutive_failures = 0;
+        }
+        Err(e) => {
+          record.last_error = Some(format!("{e:#}"));
+          record.consecutive_failures =
+            record.consecutive_failures.saturating_add(1);
+        }
+      }
+    }
+  }
+
+  /// Status of every registered task, by task name.
+  #[must_use]
+  pub fn statuses(&self) -> Vec<TaskStatus> {
+    self.statuses_at(Instant::now())
+  }
+
+  fn statuses_at(&self, now: Instant) -> Vec<TaskStatus> {
+    self
+      .lock()
+      .iter()
+      .map(|(task, record)| TaskStatus {
+        task: *task,
+        since_success: now.saturating_duration_since(
+          record.last_success.unwrap_or(record.registered_at),
+        ),
+        max_age: record.max_age,
+        ever_succeeded: record.last_success.is_some(),
+        consecutive_failures: record.consecutive_failures,
+        last_error: record.last_error.clone(),
+      })
+      .collect()
+  }
+
+  fn lock(&self) -> MutexGuard<'_, BTreeMap<&'static str, TaskRecord>> {
+    self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::anyhow;
+
+  use super::*;
+
+  const TASK: &str = "test task";
+
+  const MAX_AGE: Duration = Duration::from_secs(20);
+
+  fn status(health: &TaskHealth, now: Instant) -> Option<TaskStatus> {
+    health
+      .statuses_at(now)
+      .into_iter()
+      .find(|status| status.task == TASK)
+  }
+
+  #[test]
+  fn stale_task_reports_unhealthy() {
+    let start = Instant::now();
+    let later = start + Duration::from_secs(30);
+    let health = TaskHealth::new().with_task_at(TASK, MAX_AGE, start);
+    health.record_at(TASK, &Ok(()), start);
+    assert!(status(&health, start).is_some_and(|status| status.is_healthy()));
+    health.record_at::<()>(TASK, &Err(anyhow!("rpc down")), later);
+    let stale = status(&health, later);
+    assert!(stale.as_ref().is_some_and(|status| !status.is_healthy()));
+    assert_eq!(stale.map(|status| status.consecutive_failures), Some(1));
+    health.record_at(TASK, &Ok(()), later);
+    assert!(status(&health, later).is_some_and(|status| status.is_healthy()));
+  }
+
+  #[test]
+  fn never_succeeded_task_goes_stale_from_registration() {
+    let start = Instant::now();
+    let health = TaskHealth::new().with_task_at(TASK, MAX_AGE, start);
+    assert!(status(&health, start).is_some_and(|status| status.is_healthy()));
+    let stale = status(&health, start + Duration::from_secs(30));
+    assert!(stale.as_ref().is_some_and(|status| !status.is_healthy()));
+    assert!(stale.is_some_and(|status| !status.ever_succeeded));
+  }
+
+  #[test]
+  fn ignores_unregistered_tasks() {
+    let health = TaskHealth::new();
+    health.record(TASK, &Ok(()));
+    assert!(health.statuses().is_empty());
+  }
+}
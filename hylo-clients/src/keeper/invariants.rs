@@ -0,0 +1,288 @@
+//! Continuous verification of on-chain protocol invariants.
+//!
+//! [`InvariantChecker`] reads protocol accounts directly over RPC, without
+//! going through program simulation, and raises an
+//! [`Alert::InvariantViolated`] when any of these fail:
+//! * `total_sol_cache` matches LST vault balances priced in SOL, within a
+//!   tolerance
+//! * hyUSD supply is backed by TVL, so the collateral ratio is at least 1.
+//!   Falling through the stability thresholds is reported by the stability
+//!   watcher instead.
+//! * Stability pool token accounts are owned by the pool authority
+
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::sysvar;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use futures::stream::{self, StreamExt};
+use hylo_core::amount_format::format_amount;
+use hylo_core::exchange_math::total_value_locked;
+use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::{TokenMint, HYUSD};
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::pyth::{query_pyth_price, OracleConfig, SOL_USD_PYTH_FEED};
+use hylo_core::total_sol_cache::TotalSolCache;
+use itertools::Itertools;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::exchange_client::ExchangeClient;
+use crate::keeper::alert::{Alert, AlertRouter};
+use crate::program_client::ProgramClient;
+use crate::util::{
+  deserialize_anchor_account, lst_registry_entries, LST_REGISTRY_LOOKUP_TABLE,
+};
+
+/// Protocol invariant verified by [`InvariantChecker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+  /// Cached total SOL agrees with LST vault balances
+  TotalSolCache,
+
+  /// hyUSD supply is backed by TVL
+  StablecoinSupply,
+
+  /// Stability pool token accounts are owned by `POOL_AUTH`
+  PoolOwnership,
+}
+
+impl Invariant {
+  #[must_use]
+  pub fn name(self) -> &'static str {
+    match self {
+      Invariant::TotalSolCache => "total_sol_cache",
+      Invariant::StablecoinSupply => "stablecoin_supply",
+      Invariant::PoolOwnership => "pool_ownership",
+    }
+  }
+}
+
+impl Display for Invariant {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+/// Protocol accounts read for one round of checks.
+struct Snapshot {
+  hylo: Hylo,
+  sol_usd: PriceUpdateV2,
+  clock: Clock,
+  hyusd_mint: Mint,
+  pools: Vec<(Pubkey, TokenAccount)>,
+  lsts: Vec<(LstHeader, TokenAccount)>,
+}
+
+/// Keeper task alerting when a protocol invariant stops holding.
+pub struct InvariantChecker {
+  client: ExchangeClient,
+  alerts: AlertRouter,
+  interval: Duration,
+  sol_tolerance: UFix64<N4>,
+}
+
+impl InvariantChecker {
+  /// Checker allowing `sol_tolerance` relative drift between the total SOL
+  /// cache and vault balances.
+  #[must_use]
+  pub fn new(
+    client: ExchangeClient,
+    alerts: AlertRouter,
+    interval: Duration,
+    sol_tolerance: UFix64<N4>,
+  ) -> InvariantChecker {
+    InvariantChecker {
+      client,
+      alerts,
+      interval,
+      sol_tolerance,
+    }
+  }
+
+  /// Reads protocol accounts and returns an alert per violated invariant.
+  ///
+  /// Each invariant is checked independently: one that cannot be evaluated,
+  /// for instance on a stale oracle price, is reported as an
+  /// [`Alert::TaskFailed`] named after it, and the others still run.
+  ///
+  /// # Errors
+  /// - Registry or protocol accounts cannot be fetched or deserialized
+  pub async fn check(&self) -> Result<Vec<Alert>> {
+    let snapshot = self.snapshot().await?;
+    let checks = [
+      (
+        Invariant::TotalSolCache,
+        self.check_total_sol(&snapshot).map(Vec::from_iter),
+      ),
+      (
+        Invariant::StablecoinSupply,
+        check_stablecoin_supply(&snapshot).map(Vec::from_iter),
+      ),
+      (
+        Invariant::PoolOwnership,
+        Ok(check_pool_ownership(&snapshot)),
+      ),
+    ];
+    Ok(
+      checks
+        .into_iter()
+        .flat_map(|(invariant, outcome)| {
+          outcome
+            .unwrap_or_else(|e| vec![Alert::task_failed(invariant.name(), &e)])
+        })
+        .collect(),
+    )
+  }
+
+  /// Checks every `interval`, delivering alerts. Failed checks are reported
+  /// as [`Alert::TaskFailed`] and retried, and alerts that cannot be
+  /// delivered are logged without stopping the checker.
+  pub async fn run(&self) {
+    stream::repeat(())
+      .for_each(|()| async move {
+        let alerts = self
+          .check()
+          .await
+          .unwrap_or_else(|e| vec![Alert::task_failed("invariants", &e)]);
+        self.alerts.deliver(&alerts).await;
+        tokio::time::sleep(self.interval).await;
+      })
+      .await;
+  }
+
+  async fn snapshot(&self) -> Result<Snapshot> {
+    let table = self
+      .client
+      .load_lookup_table(&LST_REGISTRY_LOOKUP_TABLE)
+      .await?;
+    let registry = lst_registry_entries(&table)?;
+    let keys = [
+      *pda::HYLO,
+      SOL_USD_PYTH_FEED,
+      sysvar::clock::ID,
+      HYUSD::MINT,
+      *pda::HYUSD_POOL,
+      *pda::XSOL_POOL,
+    ]
+    .into_iter()
+    .chain(registry.iter().flat_map(|lst| [lst.header, lst.vault]))
+    .collect_vec();
+    let accounts = self
+      .client
+      .get_multiple_accounts(&keys)
+      .await?
+      .into_iter()
+      .zip(&keys)
+      .map(|(account, key)| account.ok_or(anyhow!("Account {key} missing")))
+      .collect::<Result<Vec<_>>>()?;
+    match accounts.as_slice() {
+      [hylo, sol_usd, clock, hyusd_mint, hyusd_pool, xsol_pool, lsts @ ..] => {
+        Ok(Snapshot {
+          hylo: deserialize_anchor_account(hylo)?,
+          sol_usd: deserialize_anchor_account(sol_usd)?,
+          clock: bincode::deserialize(&clock.data)?,
+          hyusd_mint: Mint::unpack(&hyusd_mint.data)?,
+          pools: vec![
+            (*pda::HYUSD_POOL, TokenAccount::unpack(&hyusd_pool.data)?),
+            (*pda::XSOL_POOL, TokenAccount::unpack(&xsol_pool.data)?),
+          ],
+          lsts: lsts
+            .iter()
+            .tuples()
+            .map(|(header, vault)| {
+              Ok((
+                deserialize_anchor_account(header)?,
+                TokenAccount::unpack(&vault.data)?,
+              ))
+            })
+            .collect::<Result<_>>()?,
+        })
+      }
+      _ => Err(anyhow!("Protocol accounts missing")),
+    }
+  }
+
+  /// Compares the total SOL cache against vault balances priced in SOL.
+  /// Skipped while the cache awaits its first update of the epoch.
+  fn check_total_sol(&self, snapshot: &Snapshot) -> Result<Option<Alert>> {
+    let epoch = snapshot.clock.epoch;
+    let cache: TotalSolCache = snapshot.hylo.total_sol_cache.into();
+    if cache.current_update_epoch == epoch {
+      let cached = cache.get_validated(epoch)?;
+      let vaults = snapshot.lsts.iter().try_fold(
+        UFix64::<N9>::zero(),
+        |total, (header, vault)| {
+          let price: LstSolPrice = header.price_sol.into();
+          let sol = price.convert_sol(UFix64::new(vault.amount), epoch)?;
+          total
+            .checked_add(&sol)
+            .ok_or(anyhow!("Vault SOL total overflow"))
+        },
+      )?;
+      let drift = cached.max(vaults).saturating_sub(&cached.min(vaults));
+      let allowed = vaults
+        .mul_div_floor(self.sol_tolerance, UFix64::one())
+        .ok_or(anyhow!("Vault SOL tolerance overflow"))?;
+      Ok((drift > allowed).then(|| Alert::InvariantViolated {
+        invariant: Invariant::TotalSolCache,
+        detail: format!(
+          "cache holds {} SOL but vaults hold {} SOL",
+          format_amount(cached),
+          format_amount(vaults)
+        ),
+      }))
+    } else {
+      Ok(None)
+    }
+  }
+}
+
+/// Checks that hyUSD supply is backed by TVL.
+///
+/// Supply above TVL at the lowest stability threshold is a normal entry into
+/// mode 2; only supply exceeding the collateral itself breaks the invariant.
+fn check_stablecoin_supply(snapshot: &Snapshot) -> Result<Option<Alert>> {
+  let hylo = &snapshot.hylo;
+  let oracle_config = OracleConfig::new(
+    hylo.oracle_interval_secs,
+    hylo.oracle_conf_tolerance.try_into()?,
+  );
+  let price =
+    query_pyth_price(&snapshot.clock, &snapshot.sol_usd, oracle_config)?;
+  let total_sol: UFix64<N9> = hylo.total_sol_cache.total_sol.try_into()?;
+  let tvl = total_value_locked(total_sol, price.lower)?;
+  let supply = UFix64::<N6>::new(snapshot.hyusd_mint.supply);
+  Ok(
+    (supply.convert::<N9>() > tvl).then(|| Alert::InvariantViolated {
+      invariant: Invariant::StablecoinSupply,
+      detail: format!(
+        "{} hyUSD outstanding exceeds {} USD of collateral",
+        format_amount(supply),
+        format_amount(tvl)
+      ),
+    }),
+  )
+}
+
+/// Checks that each stability pool token account is owned by `POOL_AUTH`.
+fn check_pool_ownership(snapshot: &Snapshot) -> Vec<Alert> {
+  snapshot
+    .pools
+    .iter()
+    .filter(|(_, account)| account.owner != *pda::POOL_AUTH)
+    .map(|(key, account)| Alert::InvariantViolated {
+      invariant: Invariant::PoolOwnership,
+      detail: format!(
+        "{key} is owned by {} instead of {}",
+        account.owner,
+        *pda::POOL_AUTH
+      ),
+    })
+    .collect()
+}
@@ -0,0 +1,374 @@
+//! Cranks `update_lst_prices` when cached LST prices fall behind.
+//!
+//! Each `LstHeader` caches its LST price in SOL for one epoch. Stake pools
+//! move the actual price on every epoch boundary, and occasionally within an
+//! epoch when a pool is topped up or slashed. [`LstPriceUpdater`] prices every
+//! SPL stake pool locally and sends the crank when a new epoch has started or
+//! a local price drifts from its cached header by more than a threshold.
+//!
+//! Stake pools are only repriced once their own `update_stake_pool_balance`
+//! has run in the new epoch, so a new epoch triggers the crank only after
+//! every SPL pool behind a stale header has caught up.
+//!
+//! The crank's remaining accounts are derived from the LST registry lookup
+//! table, so newly registered LSTs are picked up without reconfiguration.
+
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::sysvar;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use futures::stream::{self, StreamExt};
+use hylo_core::amount_format::format_amount;
+use hylo_core::idl::exchange::accounts::LstHeader;
+use hylo_core::idl::exchange::types::LstStakePoolProgram;
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::stake_pool::StakePoolSnapshot;
+use itertools::Itertools;
+
+use crate::exchange_client::ExchangeClient;
+use crate::keeper::alert::{log_failure, Alert, AlertRouter};
+use crate::keeper::health::TaskHealth;
+use crate::program_client::ProgramClient;
+use crate::util::{lst_registry_entries, LST_REGISTRY_LOOKUP_TABLE};
+
+/// Cached and locally computed price of one registered LST.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LstPriceReading {
+  pub mint: Pubkey,
+
+  /// Price held by the `LstHeader`
+  pub cached: LstSolPrice,
+
+  /// Price from the stake pool for the current epoch. `None` for pools not
+  /// in the SPL layout, or not yet updated for the epoch.
+  pub local: Option<UFix64<N9>>,
+
+  /// Epoch the stake pool was last updated in. `None` for pools not in the
+  /// SPL layout, which are priced without waiting on a pool update.
+  pub pool_update_epoch: Option<u64>,
+}
+
+impl LstPriceReading {
+  /// Whether the stake pool has been updated for `epoch`, so the header can
+  /// be repriced.
+  #[must_use]
+  pub fn pool_ready(&self, epoch: u64) -> bool {
+    self
+      .pool_update_epoch
+      .is_none_or(|updated| updated == epoch)
+  }
+
+  /// Relative difference between the local and cached price, if both are
+  /// for `epoch`.
+  ///
+  /// # Errors
+  /// - Cached price is malformed or zero
+  pub fn drift(&self, epoch: u64) -> Result<Option<UFix64<N4>>> {
+    match self.local {
+      Some(local) if self.cached.epoch == epoch => {
+        let cached: UFix64<N9> = self.cached.price.try_into()?;
+        let drift = local
+          .max(cached)
+          .saturating_sub(&local.min(cached))
+          .mul_div_ceil(UFix64::<N9>::one(), cached)
+          .ok_or(anyhow!("LST price drift overflow for {}", self.mint))?;
+        Ok(Some(drift.convert()))
+      }
+      _ => Ok(None),
+    }
+  }
+}
+
+/// Reason to send `update_lst_prices`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateTrigger {
+  /// A header still holds a price from a past epoch
+  NewEpoch { cached_epoch: u64, epoch: u64 },
+
+  /// A local price moved past the drift threshold within the epoch
+  Drift { mint: Pubkey, drift: UFix64<N4> },
+}
+
+impl Display for UpdateTrigger {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      UpdateTrigger::NewEpoch {
+        cached_epoch,
+        epoch,
+      } => write!(f, "prices cached in epoch {cached_epoch}, now {epoch}"),
+      UpdateTrigger::Drift { mint, drift } => {
+        write!(f, "{mint} drifted {}", format_amount(*drift))
+      }
+    }
+  }
+}
+
+/// Finds the reason to update prices in `epoch`, if any. A new epoch takes
+/// precedence over drift, then the largest drift past `drift_threshold`.
+///
+/// Headers from a past epoch trigger nothing until every one of their stake
+/// pools has been updated for `epoch`; the crank would fail before then.
+///
+/// # Errors
+/// - A cached price is malformed or zero
+pub fn update_trigger(
+  readings: &[LstPriceReading],
+  epoch: u64,
+  drift_threshold: UFix64<N4>,
+) -> Result<Option<UpdateTrigger>> {
+  let stale = readings
+    .iter()
+    .filter(|reading| reading.cached.epoch < epoch)
+    .collect_vec();
+  let pools_ready = stale.iter().all(|reading| reading.pool_ready(epoch));
+  match stale.iter().map(|reading| reading.cached.epoch).min() {
+    Some(cached_epoch) if pools_ready => Ok(Some(UpdateTrigger::NewEpoch {
+      cached_epoch,
+      epoch,
+    })),
+    Some(_) => Ok(None),
+    None => {
+      let drifts = readings
+        .iter()
+        .map(|reading| Ok(reading.drift(epoch)?.map(|d| (reading.mint, d))))
+        .collect::<Result<Vec<_>>>()?;
+      Ok(
+        drifts
+          .into_iter()
+          .flatten()
+          .filter(|(_, drift)| *drift > drift_threshold)
+          .max_by_key(|(_, drift)| *drift)
+          .map(|(mint, drift)| UpdateTrigger::Drift { mint, drift }),
+      )
+    }
+  }
+}
+
+/// Result of one update round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LstPriceOutcome {
+  /// Cached prices are current and within the drift threshold
+  Current,
+
+  /// Price update transaction confirmed
+  Updated {
+    trigger: UpdateTrigger,
+    signature: Signature,
+  },
+}
+
+/// Keeper task refreshing cached LST prices.
+pub struct LstPriceUpdater {
+  client: ExchangeClient,
+  alerts: AlertRouter,
+  interval: Duration,
+  drift_threshold: UFix64<N4>,
+  health: TaskHealth,
+}
+
+impl LstPriceUpdater {
+  /// Name the updater records rounds and reports failures under.
+  pub const TASK: &str = "lst price update";
+
+  /// Updater cranking on each new epoch, and whenever a local price differs
+  /// from its cached price by more than `drift_threshold`.
+  #[must_use]
+  pub fn new(
+    client: ExchangeClient,
+    alerts: AlertRouter,
+    interval: Duration,
+    drift_threshold: UFix64<N4>,
+  ) -> LstPriceUpdater {
+    LstPriceUpdater {
+      client,
+      alerts,
+      interval,
+      drift_threshold,
+      health: TaskHealth::new(),
+    }
+  }
+
+  /// Records every round in `health` under [`Self::TASK`].
+  #[must_use]
+  pub fn with_health(self, health: TaskHealth) -> LstPriceUpdater {
+    LstPriceUpdater { health, ..self }
+  }
+
+  /// Reads every registered LST header and stake pool, returning the
+  /// current epoch with a reading per LST.
+  ///
+  /// # Errors
+  /// - Registry, header, pool or clock accounts cannot be fetched
+  /// - A header cannot be deserialized
+  pub async fn readings(&self) -> Result<(u64, Vec<LstPriceReading>)> {
+    let table = self
+      .client
+      .load_lookup_table(&LST_REGISTRY_LOOKUP_TABLE)
+      .await?;
+    let lsts = lst_registry_entries(&table)?;
+    let keys = std::iter::once(sysvar::clock::ID)
+      .chain(lsts.iter().flat_map(|lst| [lst.header, lst.pool_state]))
+      .collect_vec();
+    let accounts = self
+      .client
+      .get_multiple_accounts(&keys)
+      .await?
+      .into_iter()
+      .zip(&keys)
+      .map(|(account, key)| account.ok_or(anyhow!("Account {key} missing")))
+      .collect::<Result<Vec<_>>>()?;
+    match accounts.split_first() {
+      Some((clock, pairs)) => {
+        let clock: Clock = bincode::deserialize(&clock.data)?;
+        let readings = lsts
+          .iter()
+          .zip(pairs.iter().tuples())
+          .map(|(lst, (header, pool))| {
+            let header =
+              LstHeader::try_deserialize(&mut header.data.as_slice())
+                .with_context(|| {
+                  format!("Invalid LstHeader for {}", lst.mint)
+                })?;
+            let snapshot = match header.stake_program {
+              LstStakePoolProgram::Marinade => None,
+              _ => StakePoolSnapshot::try_from_spl_bytes(&pool.data).ok(),
+            };
+            let local = snapshot
+              .and_then(|pool| pool.estimate_epoch_price(clock.epoch).ok())
+              .and_then(|price| price.price.try_into().ok());
+            Ok(LstPriceReading {
+              mint: lst.mint,
+              cached: header.price_sol.into(),
+              local,
+              pool_update_epoch: snapshot.map(|pool| pool.last_update_epoch),
+            })
+          })
+          .collect::<Result<Vec<_>>>()?;
+        Ok((clock.epoch, readings))
+      }
+      None => Err(anyhow!("Clock sysvar missing")),
+    }
+  }
+
+  /// Sends `update_lst_prices` if a new epoch started or any price drifted.
+  ///
+  /// # Errors
+  /// - Failed to read LST prices
+  /// - Update transaction fails
+  pub async fn update_once(&self) -> Result<LstPriceOutcome> {
+    let (epoch, readings) = self.readings().await?;
+    match update_trigger(&readings, epoch, self.drift_threshold)? {
+      None => Ok(LstPriceOutcome::Current),
+      Some(trigger) => {
+        let vtd = self.client.update_lst_prices().await?;
+        let signature = self.client.send_v0_transaction(&vtd).await?;
+        Ok(LstPriceOutcome::Updated { trigger, signature })
+      }
+    }
+  }
+
+  /// Runs [`Self::update_once`] every `interval`, passing each outcome to
+  /// `on_round`. Failed rounds are logged, reported as [`Alert::TaskFailed`]
+  /// and retried, and alerts that cannot be delivered are logged without
+  /// stopping the updater.
+  pub async fn run<F: FnMut(&LstPriceOutcome)>(&self, on_round: F) {
+    stream::repeat(())
+      .fold(on_round, |mut on_round, ()| async move {
+        let outcome = self.update_once().await;
+        self.health.record(Self::TASK, &outcome);
+        match outcome {
+          Ok(outcome) => on_round(&outcome),
+          Err(e) => {
+            log_failure("LST price update failed", &e);
+            self
+              .alerts
+              .deliver(&[Alert::task_failed(Self::TASK, &e)])
+              .await;
+          }
+        }
+        tokio::time::sleep(self.interval).await;
+        on_round
+      })
+      .await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EPOCH: u64 = 700;
+
+  fn reading(
+    cached: (u64, u64),
+    local: Option<u64>,
+    pool_update_epoch: Option<u64>,
+  ) -> LstPriceReading {
+    let (price, epoch) = cached;
+    LstPriceReading {
+      mint: Pubkey::new_unique(),
+      cached: LstSolPrice::new(UFix64::<N9>::new(price).into(), epoch),
+      local: local.map(UFix64::new),
+      pool_update_epoch,
+    }
+  }
+
+  fn threshold() -> UFix64<N4> {
+    UFix64::new(10)
+  }
+
+  #[test]
+  fn triggers_by_branch() -> Result<()> {
+    let current = reading((1_100_000_000, EPOCH), None, Some(EPOCH));
+    let updated = reading((1_100_000_000, EPOCH - 1), None, Some(EPOCH));
+    let older = reading((1_100_000_000, EPOCH - 3), None, Some(EPOCH));
+    let pending = reading((1_100_000_000, EPOCH - 1), None, Some(EPOCH - 1));
+    let marinade = reading((1_100_000_000, EPOCH - 1), None, None);
+    let small = reading((1_100_000_000, EPOCH), Some(1_100_110_000), None);
+    let edge = reading((1_000_000_000, EPOCH), Some(1_001_000_000), None);
+    let large = reading((1_000_000_000, EPOCH), Some(1_002_000_000), None);
+    let larger = reading((1_000_000_000, EPOCH), Some(997_000_000), None);
+    let new_epoch = |cached_epoch| {
+      Some(UpdateTrigger::NewEpoch {
+        cached_epoch,
+        epoch: EPOCH,
+      })
+    };
+    let drift = |reading: &LstPriceReading, bps| {
+      Some(UpdateTrigger::Drift {
+        mint: reading.mint,
+        drift: UFix64::new(bps),
+      })
+    };
+    let cases = [
+      (vec![], None),
+      (vec![current], None),
+      (vec![current, updated], new_epoch(EPOCH - 1)),
+      (vec![updated, older], new_epoch(EPOCH - 3)),
+      (vec![updated, pending], None),
+      (vec![pending, large], None),
+      (vec![marinade], new_epoch(EPOCH - 1)),
+      (vec![updated, large], new_epoch(EPOCH - 1)),
+      (vec![small], None),
+      (vec![edge], None),
+      (vec![small, large], drift(&large, 20)),
+      (vec![large, larger], drift(&larger, 30)),
+    ];
+    cases.into_iter().try_for_each(|(readings, expected)| {
+      assert_eq!(update_trigger(&readings, EPOCH, threshold())?, expected);
+      Ok(())
+    })
+  }
+
+  #[test]
+  fn zero_cached_price_fails() {
+    let zero = reading((0, EPOCH), Some(1_000_000_000), None);
+    assert!(update_trigger(&[zero], EPOCH, threshold()).is_err());
+  }
+}
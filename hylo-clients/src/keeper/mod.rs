@@ -0,0 +1,13 @@
+//! Long-running maintenance tasks for protocol operators.
+//!
+//! Tasks report through [`alert::AlertRouter`] so operators are paged on
+//! failures without wiring their own notifications.
+
+pub mod alert;
+pub mod fee_sweep;
+pub mod health;
+pub mod invariants;
+pub mod lst_price;
+pub mod rebalance;
+pub mod submission;
+pub mod watcher;
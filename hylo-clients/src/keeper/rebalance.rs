@@ -0,0 +1,483 @@
+//! Cranks stability pool rebalances when they pay for themselves.
+//!
+//! Below the first stability threshold the pool swaps hyUSD into xSOL to lift
+//! the collateral ratio, and back in normal mode it swaps xSOL back to hyUSD.
+//! [`RebalanceCranker`] reads exchange and pool stats, estimates the value
+//! each eligible rebalance moves, and only sends it when the priority fee
+//! costs at most a configured share of that value.
+
+use std::fmt::{self, Display};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::sysvar;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use futures::stream::{self, StreamExt};
+use hylo_core::exchange_math::max_swappable_stablecoin;
+use hylo_core::idl::exchange::accounts::Hylo;
+use hylo_core::idl::pda;
+use hylo_core::operation_matrix::ProtocolOperation::{
+  RebalanceLeverToStable, RebalanceStableToLever,
+};
+use hylo_core::pyth::{
+  query_pyth_price, OracleConfig, PriceRange, SOL_USD_PYTH_FEED,
+};
+use hylo_core::stability_mode::{StabilityController, StabilityMode};
+use hylo_core::stability_pool_math::{
+  amount_lever_to_swap, amount_stable_to_swap,
+};
+use hylo_idl::exchange::events::ExchangeStats;
+use hylo_idl::stability_pool::events::StabilityPoolStats;
+use hylo_idl::stability_pool::instruction_builders;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::exchange_client::ExchangeClient;
+use crate::keeper::alert::{log_failure, Alert, AlertRouter};
+use crate::keeper::health::TaskHealth;
+use crate::keeper::submission::LeaderAwareSubmitter;
+use crate::priority_fee::{
+  PriorityFeeSource, RecentPrioritizationFees, TransactionClass,
+};
+use crate::program_client::{ProgramClient, VersionedTransactionData};
+use crate::stability_pool_client::StabilityPoolClient;
+use crate::util::{EXCHANGE_LOOKUP_TABLE, STABILITY_POOL_LOOKUP_TABLE};
+
+/// Base fee charged per transaction signature.
+pub const BASE_SIGNATURE_FEE_LAMPORTS: u64 = 5_000;
+
+/// Stability pool swap direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebalanceDirection {
+  /// Pool hyUSD swapped into xSOL, while CR is below the first threshold
+  StableToLever,
+
+  /// Pool xSOL swapped back into hyUSD, while in normal mode
+  LeverToStable,
+}
+
+impl Display for RebalanceDirection {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RebalanceDirection::StableToLever => f.write_str("stable_to_lever"),
+      RebalanceDirection::LeverToStable => f.write_str("lever_to_stable"),
+    }
+  }
+}
+
+/// Rebalance the protocol currently allows, with the value it would move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebalanceOpportunity {
+  pub direction: RebalanceDirection,
+
+  /// Pool tokens swapped out: hyUSD for [`RebalanceDirection::StableToLever`]
+  /// and xSOL for [`RebalanceDirection::LeverToStable`]
+  pub amount: UFix64<N6>,
+
+  /// USD value of `amount` at its token NAV
+  pub value_usd: UFix64<N6>,
+}
+
+impl RebalanceOpportunity {
+  /// Finds the rebalance eligible under the current stats, if any.
+  ///
+  /// Each direction runs only in the modes the operation matrix allows, see
+  /// [`ProtocolOperation`](hylo_core::operation_matrix::ProtocolOperation).
+  /// Swaps out of hyUSD target the next higher stability threshold, and
+  /// swaps back into hyUSD stop at the first threshold to stay in normal
+  /// mode.
+  ///
+  /// # Errors
+  /// - Stats hold malformed fixed point values
+  /// - Swap amount arithmetic overflows
+  pub fn detect(
+    controller: &StabilityController,
+    exchange: &ExchangeStats,
+    pool: &StabilityPoolStats,
+  ) -> Result<Option<RebalanceOpportunity>> {
+    let mode: StabilityMode = exchange.stability_mode.into();
+    let tvl: UFix64<N9> = exchange.total_value_locked.try_into()?;
+    let supply: UFix64<N6> = exchange.stablecoin_supply.try_into()?;
+    let stablecoin_in_pool: UFix64<N6> = pool.stablecoin_in_pool.try_into()?;
+    let levercoin_in_pool: UFix64<N6> = pool.levercoin_in_pool.try_into()?;
+    let stable_to_lever = controller
+      .prev_stability_threshold(mode)
+      .filter(|_| {
+        RebalanceStableToLever.is_active(mode)
+          && stablecoin_in_pool > UFix64::zero()
+      })
+      .map(|target| {
+        let amount =
+          amount_stable_to_swap(stablecoin_in_pool, target, supply, tvl)?;
+        let nav: UFix64<N9> = exchange.stablecoin_nav.try_into()?;
+        priced_opportunity(RebalanceDirection::StableToLever, amount, nav)
+      })
+      .transpose()?;
+    let lever_to_stable = (RebalanceLeverToStable.is_active(mode)
+      && levercoin_in_pool > UFix64::zero())
+    .then(|| {
+      let nav: UFix64<N9> = exchange.levercoin_nav.try_into()?;
+      let max_stablecoin = max_swappable_stablecoin(
+        controller.stability_threshold_1(),
+        tvl,
+        supply,
+      )?;
+      let amount = amount_lever_to_swap(
+        levercoin_in_pool,
+        PriceRange::one(nav),
+        max_stablecoin,
+      )?;
+      priced_opportunity(RebalanceDirection::LeverToStable, amount, nav)
+    })
+    .transpose()?;
+    let opportunity = stable_to_lever.or(lever_to_stable);
+    Ok(opportunity.filter(|o| o.amount > UFix64::zero()))
+  }
+}
+
+/// Prices `amount` at `nav` into an opportunity.
+fn priced_opportunity(
+  direction: RebalanceDirection,
+  amount: UFix64<N6>,
+  nav: UFix64<N9>,
+) -> Result<RebalanceOpportunity> {
+  let value_usd = amount
+    .mul_div_floor(nav, UFix64::one())
+    .ok_or(anyhow!("Rebalance value overflow"))?;
+  Ok(RebalanceOpportunity {
+    direction,
+    amount,
+    value_usd,
+  })
+}
+
+/// Result of one crank round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebalanceOutcome {
+  /// No rebalance is eligible
+  Idle,
+
+  /// Eligible, but the priority fee exceeds the allowed share of its value
+  Unprofitable {
+    opportunity: RebalanceOpportunity,
+    cost_usd: UFix64<N6>,
+  },
+
+  /// Rebalance transaction confirmed
+  Sent {
+    opportunity: RebalanceOpportunity,
+    cost_lamports: u64,
+    signature: Signature,
+  },
+}
+
+/// Running totals across crank rounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebalanceMetrics {
+  pub rounds: u64,
+  pub idle: u64,
+  pub unprofitable: u64,
+  pub failures: u64,
+
+  /// Failed rounds since the last successful one
+  pub consecutive_failures: u32,
+
+  pub stable_to_lever: u64,
+  pub lever_to_stable: u64,
+
+  /// Sum of [`RebalanceOpportunity::value_usd`] over sent rebalances
+  pub value_rebalanced_usd: UFix64<N6>,
+
+  /// Sum of fees paid by sent rebalances
+  pub fees_paid_lamports: u64,
+}
+
+impl RebalanceMetrics {
+  /// Adds one round's outcome to the totals.
+  #[must_use]
+  pub fn record(self, outcome: &Result<RebalanceOutcome>) -> RebalanceMetrics {
+    let metrics = RebalanceMetrics {
+      rounds: self.rounds.saturating_add(1),
+      consecutive_failures: 0,
+      ..self
+    };
+    match outcome {
+      Ok(RebalanceOutcome::Idle) => RebalanceMetrics {
+        idle: metrics.idle.saturating_add(1),
+        ..metrics
+      },
+      Ok(RebalanceOutcome::Unprofitable { .. }) => RebalanceMetrics {
+        unprofitable: metrics.unprofitable.saturating_add(1),
+        ..metrics
+      },
+      Ok(RebalanceOutcome::Sent {
+        opportunity,
+        cost_lamports,
+        ..
+      }) => {
+        let (stable_to_lever, lever_to_stable) = match opportunity.direction {
+          RebalanceDirection::StableToLever => (
+            metrics.stable_to_lever.saturating_add(1),
+            metrics.lever_to_stable,
+          ),
+          RebalanceDirection::LeverToStable => (
+            metrics.stable_to_lever,
+            metrics.lever_to_stable.saturating_add(1),
+          ),
+        };
+        RebalanceMetrics {
+          stable_to_lever,
+          lever_to_stable,
+          value_rebalanced_usd: metrics
+            .value_rebalanced_usd
+            .saturating_add(&opportunity.value_usd),
+          fees_paid_lamports: metrics
+            .fees_paid_lamports
+            .saturating_add(*cost_lamports),
+          ..metrics
+        }
+      }
+      Err(_) => RebalanceMetrics {
+        failures: metrics.failures.saturating_add(1),
+        consecutive_failures: self.consecutive_failures.saturating_add(1),
+        ..metrics
+      },
+    }
+  }
+}
+
+/// Total fee in lamports for one signature at `compute_unit_price`
+/// micro-lamports over `compute_units`.
+#[must_use]
+pub fn priority_fee_lamports(
+  compute_unit_price: u64,
+  compute_units: u32,
+) -> u64 {
+  let micro_lamports =
+    u128::from(compute_unit_price).saturating_mul(u128::from(compute_units));
+  let priority = micro_lamports.div_ceil(1_000_000);
+  BASE_SIGNATURE_FEE_LAMPORTS
+    .saturating_add(u64::try_from(priority).unwrap_or(u64::MAX))
+}
+
+/// Doublings of the crank interval after consecutive failed rounds.
+const MAX_BACKOFF_DOUBLINGS: u32 = 5;
+
+/// Wait before the next round: `interval`, doubled per consecutive failure
+/// up to [`MAX_BACKOFF_DOUBLINGS`] times.
+fn backoff(interval: Duration, consecutive_failures: u32) -> Duration {
+  interval.saturating_mul(1 << consecutive_failures.min(MAX_BACKOFF_DOUBLINGS))
+}
+
+/// Keeper task sending stability pool rebalances.
+pub struct RebalanceCranker {
+  exchange: ExchangeClient,
+  stability_pool: StabilityPoolClient,
+  alerts: AlertRouter,
+  interval: Duration,
+  compute_units: u32,
+  max_cost_share: UFix64<N4>,
+  submitter: Option<LeaderAwareSubmitter>,
+  fee_source: Arc<dyn PriorityFeeSource>,
+  health: TaskHealth,
+}
+
+impl RebalanceCranker {
+  /// Name the cranker records rounds and reports failures under.
+  pub const TASK: &str = "rebalance";
+
+  /// Cranker requesting `compute_units` per rebalance and skipping any whose
+  /// fee exceeds `max_cost_share` of the USD value it moves. Bids the median
+  /// recent priority fee for pool writes.
+  #[must_use]
+  pub fn new(
+    exchange: ExchangeClient,
+    stability_pool: StabilityPoolClient,
+    alerts: AlertRouter,
+    interval: Duration,
+    compute_units: u32,
+    max_cost_share: UFix64<N4>,
+  ) -> RebalanceCranker {
+    let fee_source = Arc::new(RecentPrioritizationFees::new(Arc::new(
+      stability_pool.program().rpc(),
+    )));
+    RebalanceCranker {
+      exchange,
+      stability_pool,
+      alerts,
+      interval,
+      compute_units,
+      max_cost_share,
+      submitter: None,
+      fee_source,
+      health: TaskHealth::new(),
+    }
+  }
+
+  /// Sends rebalances through `submitter` instead of the program RPC.
+  #[must_use]
+  pub fn with_submitter(
+    self,
+    submitter: LeaderAwareSubmitter,
+  ) -> RebalanceCranker {
+    RebalanceCranker {
+      submitter: Some(submitter),
+      ..self
+    }
+  }
+
+  /// Prices rebalances as [`TransactionClass::Crank`] with `fee_source`.
+  #[must_use]
+  pub fn with_fee_source(
+    self,
+    fee_source: Arc<dyn PriorityFeeSource>,
+  ) -> RebalanceCranker {
+    RebalanceCranker { fee_source, ..self }
+  }
+
+  /// Records every round in `health` under [`Self::TASK`].
+  #[must_use]
+  pub fn with_health(self, health: TaskHealth) -> RebalanceCranker {
+    RebalanceCranker { health, ..self }
+  }
+
+  /// Detects an eligible rebalance and sends it if profitable.
+  ///
+  /// # Errors
+  /// - Stats simulation fails
+  /// - Protocol, oracle or clock accounts cannot be fetched or deserialized
+  /// - Oracle price is stale or outside confidence tolerance
+  /// - Rebalance transaction fails
+  pub async fn crank_once(&self) -> Result<RebalanceOutcome> {
+    let exchange = self.exchange.get_stats().await?;
+    let pool = self.stability_pool.get_stats().await?;
+    let (hylo, sol_usd) = self.load_protocol().await?;
+    let controller = StabilityController::new(
+      hylo.stability_threshold_1.try_into()?,
+      hylo.stability_threshold_2.try_into()?,
+    )?;
+    match RebalanceOpportunity::detect(&controller, &exchange, &pool)? {
+      None => Ok(RebalanceOutcome::Idle),
+      Some(opportunity) => {
+        let compute_unit_price = self
+          .fee_source
+          .compute_unit_price(TransactionClass::Crank, &[*pda::POOL_CONFIG])
+          .await?;
+        let cost_lamports =
+          priority_fee_lamports(compute_unit_price, self.compute_units);
+        let cost_usd: UFix64<N6> = UFix64::<N9>::new(cost_lamports)
+          .mul_div_ceil(sol_usd.upper, UFix64::one())
+          .map(UFix64::convert)
+          .ok_or(anyhow!("Rebalance cost overflow"))?;
+        let allowed = opportunity
+          .value_usd
+          .mul_div_floor(self.max_cost_share, UFix64::one())
+          .ok_or(anyhow!("Rebalance cost allowance overflow"))?;
+        if cost_usd <= allowed {
+          let signature =
+            self.send(opportunity.direction, compute_unit_price).await?;
+          Ok(RebalanceOutcome::Sent {
+            opportunity,
+            cost_lamports,
+            signature,
+          })
+        } else {
+          Ok(RebalanceOutcome::Unprofitable {
+            opportunity,
+            cost_usd,
+          })
+        }
+      }
+    }
+  }
+
+  /// Cranks every `interval`, passing updated metrics to `on_round`.
+  ///
+  /// Failed rounds are logged, reported as [`Alert::TaskFailed`] and
+  /// retried, waiting twice as long after each consecutive failure. Alerts
+  /// that cannot be delivered are logged without stopping the cranker.
+  pub async fn run<F: FnMut(&RebalanceMetrics)>(&self, on_round: F) {
+    stream::repeat(())
+      .fold(
+        (RebalanceMetrics::default(), on_round),
+        |(metrics, mut on_round), ()| async move {
+          let outcome = self.crank_once().await;
+          self.health.record(Self::TASK, &outcome);
+          let metrics = metrics.record(&outcome);
+          on_round(&metrics);
+          if let Err(e) = outcome {
+            log_failure("Rebalance round failed", &e);
+            self
+              .alerts
+              .deliver(&[Alert::task_failed(Self::TASK, &e)])
+              .await;
+          }
+          let wait = backoff(self.interval, metrics.consecutive_failures);
+          tokio::time::sleep(wait).await;
+          (metrics, on_round)
+        },
+      )
+      .await;
+  }
+
+  /// Loads the Hylo account and its validated SOL/USD price.
+  async fn load_protocol(&self) -> Result<(Hylo, PriceRange<N8>)> {
+    let keys = [*pda::HYLO, SOL_USD_PYTH_FEED, sysvar::clock::ID];
+    let accounts = self.exchange.get_multiple_accounts(&keys).await?;
+    match accounts.as_slice() {
+      [Some(hylo), Some(sol_usd), Some(clock)] => {
+        let hylo = Hylo::try_deserialize(&mut hylo.data.as_slice())?;
+        let sol_usd =
+          PriceUpdateV2::try_deserialize(&mut sol_usd.data.as_slice())?;
+        let clock: Clock = bincode::deserialize(&clock.data)?;
+        let oracle_config = OracleConfig::new(
+          hylo.oracle_interval_secs,
+          hylo.oracle_conf_tolerance.try_into()?,
+        );
+        let price = query_pyth_price(&clock, &sol_usd, oracle_config)?;
+        Ok((hylo, price))
+      }
+      _ => Err(anyhow!("Protocol accounts missing")),
+    }
+  }
+
+  /// Sends the rebalance in `direction` at `compute_unit_price`.
+  async fn send(
+    &self,
+    direction: RebalanceDirection,
+    compute_unit_price: u64,
+  ) -> Result<Signature> {
+    let payer = self.stability_pool.program().payer();
+    let rebalance = match direction {
+      RebalanceDirection::StableToLever => {
+        instruction_builders::rebalance_stable_to_lever(payer)
+      }
+      RebalanceDirection::LeverToStable => {
+        instruction_builders::rebalance_lever_to_stable(payer)
+      }
+    };
+    let instructions = vec![
+      ComputeBudgetInstruction::set_compute_unit_limit(self.compute_units),
+      ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+      rebalance,
+    ];
+    let lookup_tables = self
+      .stability_pool
+      .load_multiple_lookup_tables(&[
+        EXCHANGE_LOOKUP_TABLE,
+        STABILITY_POOL_LOOKUP_TABLE,
+      ])
+      .await?;
+    let tx_args = VersionedTransactionData::new(instructions, lookup_tables);
+    match &self.submitter {
+      Some(submitter) => {
+        submitter.submit_v0(&self.stability_pool, &tx_args).await
+      }
+      None => self.stability_pool.send_v0_transaction(&tx_args).await,
+    }
+  }
+}
@@ -0,0 +1,239 @@
+//! Transaction submission timed to the leader schedule.
+//!
+//! RPC nodes forward a transaction to the current and next few leaders, then
+//! drop it once their slots pass. Cranks competing with oracle updates land
+//! more reliably when resent as each new leader window opens.
+//! [`LeaderAwareSubmitter`] reads upcoming slot leaders, sends just ahead of
+//! each distinct leader's window, and stops once the transaction lands or its
+//! blockhash expires.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use itertools::Itertools;
+
+use crate::program_client::{ProgramClient, VersionedTransactionData};
+
+/// Consecutive slots assigned to each leader.
+pub const LEADER_WINDOW_SLOTS: u64 = 4;
+
+/// Run of slots produced by one leader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaderWindow {
+  pub leader: Pubkey,
+  pub first_slot: u64,
+}
+
+/// Groups per-slot `leaders`, starting at `start_slot`, into windows.
+#[must_use]
+pub fn leader_windows(
+  start_slot: u64,
+  leaders: &[Pubkey],
+) -> Vec<LeaderWindow> {
+  leaders
+    .iter()
+    .copied()
+    .zip(start_slot..)
+    .dedup_by(|(a, _), (b, _)| a == b)
+    .map(|(leader, first_slot)| LeaderWindow { leader, first_slot })
+    .collect()
+}
+
+/// Sends a transaction once per upcoming leader window until it lands.
+pub struct LeaderAwareSubmitter {
+  rpc_client: Arc<RpcClient>,
+  max_leaders: usize,
+  lead_slots: u64,
+  poll_interval: Duration,
+}
+
+impl LeaderAwareSubmitter {
+  /// Submitter retrying across up to `max_leaders` distinct leaders, sending
+  /// one slot ahead of each window.
+  #[must_use]
+  pub fn new(
+    rpc_client: Arc<RpcClient>,
+    max_leaders: usize,
+  ) -> LeaderAwareSubmitter {
+    LeaderAwareSubmitter {
+      rpc_client,
+      max_leaders,
+      lead_slots: 1,
+      poll_interval: Duration::from_millis(400),
+    }
+  }
+
+  /// Slots ahead of each window to send, covering forwarding latency.
+  #[must_use]
+  pub fn with_lead_slots(self, lead_slots: u64) -> LeaderAwareSubmitter {
+    LeaderAwareSubmitter { lead_slots, ..self }
+  }
+
+  /// Interval between slot polls while waiting for a window.
+  #[must_use]
+  pub fn with_poll_interval(
+    self,
+    poll_interval: Duration,
+  ) -> LeaderAwareSubmitter {
+    LeaderAwareSubmitter {
+      poll_interval,
+      ..self
+    }
+  }
+
+  /// Windows of the current and next leaders, up to `max_leaders`.
+  ///
+  /// # Errors
+  /// - Failed to fetch the current slot or slot leaders
+  pub async fn upcoming_windows(&self) -> Result<Vec<LeaderWindow>> {
+    let slot = self.rpc_client.get_slot().await?;
+    let limit =
+      u64::try_from(self.max_leaders)?.saturating_mul(LEADER_WINDOW_SLOTS);
+    let leaders = self.rpc_client.get_slot_leaders(slot, limit).await?;
+    Ok(
+      leader_windows(slot, &leaders)
+        .into_iter()
+        .take(self.max_leaders)
+        .collect(),
+    )
+  }
+
+  /// Sends `tx` ahead of each upcoming leader window, skipping preflight and
+  /// RPC retries, until it is observed on-chain.
+  ///
+  /// # Errors
+  /// - Transaction is unsigned
+  /// - RPC requests fail
+  /// - Transaction executed with an error
+  /// - Blockhash expired, or no leader landed it
+  #[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+      skip_all,
+      err,
+      fields(
+        signature = tracing::field::Empty,
+        leaders = tracing::field::Empty,
+      ),
+    )
+  )]
+  pub async fn submit(&self, tx: &VersionedTransaction) -> Result<Signature> {
+    let signature = tx
+      .signatures
+      .first()
+      .copied()
+      .ok_or(anyhow!("Transaction is unsigned"))?;
+    let windows = self.upcoming_windows().await?;
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+      .record("signature", tracing::field::display(signature))
+      .record("leaders", windows.len());
+    let config = RpcSendTransactionConfig {
+      skip_preflight: true,
+      max_retries: Some(0),
+      ..RpcSendTransactionConfig::default()
+    };
+    let landed = stream::iter(&windows)
+      .map(Ok::<_, anyhow::Error>)
+      .try_fold(None, |landed, window| async move {
+        match landed {
+          Some(status) => Ok(Some(status)),
+          None => {
+            self
+              .wait_for_slot(window.first_slot.saturating_sub(self.lead_slots))
+              .await?;
+            match self.rpc_client.get_signature_status(&signature).await? {
+              Some(status) => Ok(Some(status)),
+              None => {
+                self.ensure_blockhash_valid(tx, &signature).await?;
+                self
+                  .rpc_client
+                  .send_transaction_with_config(tx, config)
+                  .await?;
+                Ok(None)
+              }
+            }
+          }
+        }
+      })
+      .await?;
+    let status = match landed {
+      Some(status) => Some(status),
+      None => {
+        let last_slot = windows.last().map_or(0, |w| w.first_slot);
+        self
+          .wait_for_slot(last_slot.saturating_add(LEADER_WINDOW_SLOTS))
+          .await?;
+        self.rpc_client.get_signature_status(&signature).await?
+      }
+    };
+    match status {
+      Some(Ok(())) => Ok(signature),
+      Some(Err(e)) => Err(anyhow!("Transaction {signature} failed: {e}")),
+      None => Err(anyhow!(
+        "Transaction {signature} did not land within {} leaders",
+        windows.len()
+      )),
+    }
+  }
+
+  /// Builds `vtd` with the client's payer and submits it.
+  ///
+  /// # Errors
+  /// - Failed to build transaction
+  /// - Submission fails, see [`Self::submit`]
+  pub async fn submit_v0<C: ProgramClient + Sync>(
+    &self,
+    client: &C,
+    vtd: &VersionedTransactionData,
+  ) -> Result<Signature> {
+    let tx = client.build_v0_transaction(vtd).await?;
+    self.submit(&tx).await
+  }
+
+  /// Polls until the cluster reaches `slot`.
+  async fn wait_for_slot(&self, slot: u64) -> Result<()> {
+    stream::repeat(())
+      .map(Ok::<(), anyhow::Error>)
+      .try_skip_while(|_| async move {
+        let current = self.rpc_client.get_slot().await?;
+        if current < slot {
+          tokio::time::sleep(self.poll_interval).await;
+          Ok(true)
+        } else {
+          Ok(false)
+        }
+      })
+      .try_next()
+      .await
+      .map(|_| ())
+  }
+
+  /// Fails once `tx` can no longer land.
+  async fn ensure_blockhash_valid(
+    &self,
+    tx: &VersionedTransaction,
+    signature: &Signature,
+  ) -> Result<()> {
+    let valid = self
+      .rpc_client
+      .is_blockhash_valid(
+        tx.message.recent_blockhash(),
+        CommitmentConfig::processed(),
+      )
+      .await?;
+    if valid {
+      Ok(())
+    } else {
+      Err(anyhow!("Blockhash expired before {signature} landed"))
+    }
+  }
+}
@@ -0,0 +1,118 @@
+//! Watches stability mode and oracle freshness.
+//!
+//! [`StabilityWatcher`] polls `get_stats` and the SOL/USD price account and
+//! raises an [`Alert`] when the stability mode changes, when the oracle falls
+//! behind, or when a poll itself fails.
+
+use std::time::Duration;
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::sysvar;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use hylo_core::pyth::SOL_USD_PYTH_FEED;
+use hylo_core::stability_mode::StabilityMode;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::exchange_client::ExchangeClient;
+use crate::keeper::alert::{Alert, AlertRouter};
+use crate::keeper::health::TaskHealth;
+use crate::program_client::ProgramClient;
+
+/// Keeper task alerting on stability mode transitions and stale oracles.
+pub struct StabilityWatcher {
+  client: ExchangeClient,
+  alerts: AlertRouter,
+  interval: Duration,
+  max_oracle_age_secs: u64,
+  health: TaskHealth,
+}
+
+impl StabilityWatcher {
+  /// Name the watcher records polls and reports failures under.
+  pub const TASK: &str = "watcher";
+
+  #[must_use]
+  pub fn new(
+    client: ExchangeClient,
+    alerts: AlertRouter,
+    interval: Duration,
+    max_oracle_age_secs: u64,
+  ) -> StabilityWatcher {
+    StabilityWatcher {
+      client,
+      alerts,
+      interval,
+      max_oracle_age_secs,
+      health: TaskHealth::new(),
+    }
+  }
+
+  /// Records every poll in `health` under [`Self::TASK`].
+  #[must_use]
+  pub fn with_health(self, health: TaskHealth) -> StabilityWatcher {
+    StabilityWatcher { health, ..self }
+  }
+
+  /// Reads the current mode and collects alerts relative to `last_mode`.
+  ///
+  /// # Errors
+  /// - Stats simulation fails
+  /// - Oracle or clock account cannot be fetched or deserialized
+  pub async fn poll(
+    &self,
+    last_mode: Option<StabilityMode>,
+  ) -> Result<(StabilityMode, Vec<Alert>)> {
+    let mode: StabilityMode =
+      self.client.get_stats().await?.stability_mode.into();
+    let transition = last_mode
+      .filter(|last| *last != mode)
+      .map(|from| Alert::ModeTransition { from, to: mode });
+    let stale = self.oracle_alert().await?;
+    Ok((mode, transition.into_iter().chain(stale).collect()))
+  }
+
+  /// Polls every `interval`, delivering alerts. Failed polls are reported
+  /// as [`Alert::TaskFailed`] and retried, and alerts that cannot be
+  /// delivered are logged without stopping the watcher.
+  pub async fn run(&self) {
+    stream::repeat(())
+      .fold(None, |last_mode, ()| async move {
+        let outcome = self.poll(last_mode).await;
+        self.health.record(Self::TASK, &outcome);
+        let (mode, alerts) = match outcome {
+          Ok((mode, alerts)) => (Some(mode), alerts),
+          Err(e) => (last_mode, vec![Alert::task_failed(Self::TASK, &e)]),
+        };
+        self.alerts.deliver(&alerts).await;
+        tokio::time::sleep(self.interval).await;
+        mode
+      })
+      .await;
+  }
+
+  async fn oracle_alert(&self) -> Result<Option<Alert>> {
+    let keys = [SOL_USD_PYTH_FEED, sysvar::clock::ID];
+    let accounts = self.client.get_multiple_accounts(&keys).await?;
+    match accounts.as_slice() {
+      [Some(oracle), Some(clock)] => {
+        let oracle =
+          PriceUpdateV2::try_deserialize(&mut oracle.data.as_slice())
+            .map_err(|e| anyhow!("Failed to deserialize Pyth: {e}"))?;
+        let clock: Clock = bincode::deserialize(&clock.data)?;
+        let publish_time = oracle.price_message.publish_time;
+        let age = clock.unix_timestamp.saturating_sub(publish_time);
+        Ok(
+          (age.unsigned_abs() > self.max_oracle_age_secs && age > 0).then_some(
+            Alert::StaleOracle {
+              publish_time,
+              now: clock.unix_timestamp,
+            },
+          ),
+        )
+      }
+      _ => Err(anyhow!("SOL/USD oracle or clock account missing")),
+    }
+  }
+}
@@ -31,12 +31,99 @@
 //!   hyUSD and xSOL
 //! - [`stability_pool_client::StabilityPoolClient`] - Deposit/withdraw
 //!   operations for sHYUSD
+//! - [`prefetch::AccountPrefetch`] - Batches protocol account reads into
+//!   chunked `getMultipleAccounts` calls
+//! - [`rpc_gate::RpcGate`] - Rate limits account fetches on a shared endpoint
+//!   and coalesces identical concurrent requests
+//! - [`priority_fee::PriorityFeeSource`] - Compute unit pricing per transaction
+//!   class from recent fees or an external estimator (`fee-estimator` feature)
+//! - [`cu_calibration::CuCalibrator`] - Records simulated compute units per
+//!   instruction for [`estimate::ComputeUnitBaselines`]; run standalone as the
+//!   `hylo-cu-calibrate` binary (`cu-calibrator` feature)
+//!
+//! ## Deployment
+//!
+//! - [`bootstrap::Bootstrapper`] - Deploys a fresh protocol instance for devnet
+//!   forks and integration environments, resuming partial runs
+//! - [`parameter_plan::ParameterPlan`] - Minimal ordered admin updates from
+//!   on-chain fees, thresholds and oracle tolerance to a target
+//! - [`governance::TransactionExport`] - Squads, Realms and explorer inspector
+//!   encodings of built admin transactions
+//! - `devnet` (`devnet` feature) - Test LST mints, mock Pyth price accounts and
+//!   seeded test deployments
+//!
+//! ## Keepers
+//!
+//! - [`keeper::fee_sweep::FeeSweeper`] - Sweeps fee vaults to the treasury
+//! - [`keeper::watcher::StabilityWatcher`] - Alerts on mode transitions and
+//!   stale oracles
+//! - [`keeper::invariants::InvariantChecker`] - Alerts on violated on-chain
+//!   invariants; run standalone as the `hylo-invariants` binary
+//!   (`invariant-checker` feature)
+//! - [`keeper::rebalance::RebalanceCranker`] - Sends stability pool rebalances
+//!   when worth their priority fee
+//! - [`keeper::lst_price::LstPriceUpdater`] - Updates cached LST prices on each
+//!   new epoch or when stake pool prices drift
+//! - [`keeper::health::TaskHealth`] - Tracks the last successful round of each
+//!   task for health endpoints
+//! - [`keeper::submission::LeaderAwareSubmitter`] - Resends transactions ahead
+//!   of each upcoming leader window
+//!
+//! ## Analytics
+//!
+//! - [`holders`] - Top hyUSD, sHYUSD and xSOL holders and supply concentration
+//! - [`state_diff`] - Field-level diffs of Hylo state before and after a
+//!   transaction
+//! - [`sol_reconciliation::SolReconciliation`] - Total SOL cache recomputed
+//!   from stake pool priced vaults, and the LST mix for routing mints and
+//!   redemptions toward target weights
+//! - [`program_logs`] - Compute units, Anchor errors and `msg!` output parsed
+//!   from simulation and submission logs
+//!
+//! ## Errors
+//!
+//! - [`error::HyloError`] - Classifies a failure by its hylo-core, program or
+//!   RPC root cause, keeping the full context chain
+//!
+//! ## Exporters
+//!
+//! - [`export`] - CSV and Parquet (`parquet` feature) sinks for decoded events
+//!
+//! ## Runtime IDL
+//!
+//! - `runtime_idl` (`runtime-idl` feature) - Builds instructions from the
+//!   on-chain IDL for methods without static builders
+//! - `idl_guard` (`runtime-idl` feature) - Flags deployed IDLs that drift from
+//!   the baked-in layouts
 
+pub mod bootstrap;
+pub mod cu_calibration;
+#[cfg(feature = "devnet")]
+pub mod devnet;
+pub mod error;
+pub mod estimate;
 pub mod exchange_client;
+pub mod export;
+pub mod governance;
+pub mod holders;
+#[cfg(feature = "runtime-idl")]
+pub mod idl_guard;
 pub mod instructions;
+pub mod keeper;
+pub mod lst_header_cache;
+pub mod lst_removal;
+pub mod parameter_plan;
+pub mod prefetch;
 pub mod prelude;
+pub mod priority_fee;
 pub mod program_client;
+pub mod program_logs;
+pub mod rpc_gate;
+#[cfg(feature = "runtime-idl")]
+pub mod runtime_idl;
+pub mod sol_reconciliation;
 pub mod stability_pool_client;
+pub mod state_diff;
 pub mod syntax_helpers;
 pub mod transaction;
 pub mod util;
@@ -0,0 +1,198 @@
+//! Shared cache of [`LstHeader`] accounts keyed by LST mint.
+//!
+//! Headers for every LST in the registry lookup table are fetched in batched
+//! `getMultipleAccounts` calls and served from memory until the configured
+//! [`RefreshPolicy`] deems them stale.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use hylo_idl::exchange::accounts::LstHeader;
+use itertools::Itertools;
+use tokio::sync::RwLock;
+
+use crate::prefetch::get_multiple_accounts_chunked;
+use crate::util::{
+  deserialize_lookup_table, lst_registry_entries, LST_REGISTRY_LOOKUP_TABLE,
+};
+
+/// Determines when cached headers must be refetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPolicy {
+  /// Maximum age of a cached header.
+  pub ttl: Duration,
+
+  /// Treat headers whose cached price predates the current epoch as stale,
+  /// regardless of TTL.
+  pub epoch_aware: bool,
+}
+
+impl Default for RefreshPolicy {
+  fn default() -> Self {
+    RefreshPolicy {
+      ttl: Duration::from_secs(30),
+      epoch_aware: true,
+    }
+  }
+}
+
+#[derive(Clone)]
+struct CachedHeader {
+  header: LstHeader,
+  fetched_at: Instant,
+}
+
+impl CachedHeader {
+  fn is_fresh(&self, policy: RefreshPolicy, current_epoch: u64) -> bool {
+    let within_ttl = self.fetched_at.elapsed() < policy.ttl;
+    let epoch_ok =
+      !policy.epoch_aware || self.header.price_sol.epoch >= current_epoch;
+    within_ttl && epoch_ok
+  }
+}
+
+/// Caches headers for all registered LSTs.
+///
+/// Cheap to share behind an [`Arc`] between quoting, keepers, and stats.
+pub struct LstHeaderCache {
+  rpc_client: Arc<RpcClient>,
+  policy: RefreshPolicy,
+  headers: RwLock<HashMap<Pubkey, CachedHeader>>,
+}
+
+impl LstHeaderCache {
+  #[must_use]
+  pub fn new(rpc_client: Arc<RpcClient>, policy: RefreshPolicy) -> Self {
+    LstHeaderCache {
+      rpc_client,
+      policy,
+      headers: RwLock::new(HashMap::new()),
+    }
+  }
+
+  #[must_use]
+  pub fn policy(&self) -> RefreshPolicy {
+    self.policy
+  }
+
+  /// Refetches every header in the LST registry, replacing the cache.
+  ///
+  /// # Errors
+  /// - Registry lookup table fetch or deserialization
+  /// - Header account missing or malformed
+  pub async fn refresh(&self) -> Result<()> {
+    let registry = self.load_registry().await?;
+    let keys = lst_registry_entries(&registry)?
+      .iter()
+      .map(|lst| lst.header)
+      .collect_vec();
+    let fetched_at = Instant::now();
+    let fresh: HashMap<Pubkey, CachedHeader> =
+      get_multiple_accounts_chunked(&self.rpc_client, &keys)
+        .await?
+        .into_iter()
+        .zip(&keys)
+        .map(|(account, key)| {
+          let account =
+            account.ok_or(anyhow!("LstHeader account not found {key}"))?;
+          let header = LstHeader::try_deserialize(&mut account.data.as_slice())
+            .with_context(|| format!("Invalid LstHeader account {key}"))?;
+          Ok((header.mint, CachedHeader { header, fetched_at }))
+        })
+        .collect::<Result<_>>()?;
+    *self.headers.write().await = fresh;
+    Ok(())
+  }
+
+  /// Gets the header for an LST mint, refreshing if stale or missing.
+  ///
+  /// # Errors
+  /// - Refresh fails
+  /// - Mint not present in the LST registry
+  pub async fn get(
+    &self,
+    mint: &Pubkey,
+    current_epoch: u64,
+  ) -> Result<LstHeader> {
+    match self.get_cached(mint, current_epoch).await {
+      Some(header) => Ok(header),
+      None => {
+        self.refresh().await?;
+        self
+          .headers
+          .read()
+          .await
+          .get(mint)
+          .map(|cached| cached.header.clone())
+          .ok_or(anyhow!("LstHeader not registered for mint {mint}"))
+      }
+    }
+  }
+
+  /// Gets all registered headers keyed by mint, refreshing if any is stale.
+  ///
+  /// # Errors
+  /// - Refresh fails
+  pub async fn get_all(
+    &self,
+    current_epoch: u64,
+  ) -> Result<HashMap<Pubkey, LstHeader>> {
+    let all_fresh = {
+      let headers = self.headers.read().await;
+      !headers.is_empty()
+        && headers
+          .values()
+          .all(|cached| cached.is_fresh(self.policy, current_epoch))
+    };
+    if !all_fresh {
+      self.refresh().await?;
+    }
+    let headers = self
+      .headers
+      .read()
+      .await
+      .iter()
+      .map(|(mint, cached)| (*mint, cached.header.clone()))
+      .collect();
+    Ok(headers)
+  }
+
+  /// Returns the cached header only if it is fresh under the policy.
+  pub async fn get_cached(
+    &self,
+    mint: &Pubkey,
+    current_epoch: u64,
+  ) -> Option<LstHeader> {
+    self
+      .headers
+      .read()
+      .await
+      .get(mint)
+      .filter(|cached| cached.is_fresh(self.policy, current_epoch))
+      .map(|cached| cached.header.clone())
+  }
+
+  /// Drops a single mint from the cache, forcing a refetch on next access.
+  pub async fn invalidate(&self, mint: &Pubkey) {
+    self.headers.write().await.remove(mint);
+  }
+
+  /// Mints currently held in the cache, regardless of freshness.
+  pub async fn mints(&self) -> Vec<Pubkey> {
+    self.headers.read().await.keys().copied().collect_vec()
+  }
+
+  async fn load_registry(&self) -> Result<AddressLookupTableAccount> {
+    let account = self
+      .rpc_client
+      .get_account(&LST_REGISTRY_LOOKUP_TABLE)
+      .await?;
+    deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &account)
+  }
+}
@@ -0,0 +1,112 @@
+//! Planning support for removing an LST from the exchange.
+//!
+//! The exchange IDL has no instruction to delist or disable an LST, so a
+//! removal is a program upgrade preceded by an unwind of the LST's vaults.
+//! [`LstUnwindReport`] captures what is still held for the LST so that admins
+//! can size that unwind before proposing the upgrade.
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_core::idl::pda;
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_idl::exchange::accounts::LstHeader;
+
+/// Outstanding balances held by the exchange for one LST.
+#[derive(Debug, Clone, Copy)]
+pub struct LstUnwindReport {
+  pub lst_mint: Pubkey,
+
+  /// Collateral backing hyUSD and xSOL
+  pub vault_amount: UFix64<N9>,
+
+  /// Fees not yet withdrawn to the treasury
+  pub fee_vault_amount: UFix64<N9>,
+
+  /// Last registered LST price in SOL
+  pub price_sol: LstSolPrice,
+
+  /// Epoch at which the report was taken
+  pub epoch: u64,
+}
+
+impl LstUnwindReport {
+  /// Fetches the LST header and vault balances.
+  ///
+  /// # Errors
+  /// - Failed to fetch accounts or the current epoch
+  /// - LST is not registered
+  /// - Vault data cannot be unpacked as token accounts
+  pub async fn fetch(
+    rpc_client: &RpcClient,
+    lst_mint: Pubkey,
+  ) -> Result<LstUnwindReport> {
+    let keys = [
+      pda::lst_header(lst_mint),
+      pda::vault(lst_mint),
+      pda::fee_vault(lst_mint),
+    ];
+    let accounts = rpc_client.get_multiple_accounts(&keys).await?;
+    let epoch = rpc_client.get_epoch_info().await?.epoch;
+    match accounts.as_slice() {
+      [header, vault, fee_vault] => {
+        let header = header
+          .as_ref()
+          .ok_or(anyhow!("LST {lst_mint} is not registered"))?;
+        let header = LstHeader::try_deserialize(&mut header.data.as_slice())
+          .with_context(|| format!("Invalid LstHeader for {lst_mint}"))?;
+        Ok(LstUnwindReport {
+          lst_mint,
+          vault_amount: token_balance(vault.as_ref())?,
+          fee_vault_amount: token_balance(fee_vault.as_ref())?,
+          price_sol: header.price_sol.into(),
+          epoch,
+        })
+      }
+      _ => Err(anyhow!("Unexpected account count for LST {lst_mint}")),
+    }
+  }
+
+  /// Total LST held across the collateral and fee vaults.
+  ///
+  /// # Errors
+  /// - Arithmetic overflow
+  pub fn total_lst(&self) -> Result<UFix64<N9>> {
+    self
+      .vault_amount
+      .checked_add(&self.fee_vault_amount)
+      .ok_or(anyhow!("LST balance overflow for {}", self.lst_mint))
+  }
+
+  /// SOL value of the collateral vault at the registered price.
+  ///
+  /// # Errors
+  /// - Registered price is not for [`Self::epoch`]
+  /// - Arithmetic overflow
+  pub fn vault_sol(&self) -> Result<UFix64<N9>> {
+    let sol = self.price_sol.convert_sol(self.vault_amount, self.epoch)?;
+    Ok(sol)
+  }
+
+  /// Whether both vaults are empty and the LST can be dropped without an
+  /// unwind.
+  #[must_use]
+  pub fn is_drained(&self) -> bool {
+    self.vault_amount == UFix64::zero()
+      && self.fee_vault_amount == UFix64::zero()
+  }
+}
+
+/// Token balance of an optional account, zero if it does not exist.
+fn token_balance(account: Option<&Account>) -> Result<UFix64<N9>> {
+  let amount = account
+    .map(|acc| TokenAccount::unpack(&acc.data))
+    .transpose()?
+    .map_or(0, |token| token.amount);
+  Ok(UFix64::new(amount))
+}
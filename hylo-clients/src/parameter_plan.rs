@@ -0,0 +1,294 @@
+//! Planning admin changes to protocol parameters.
+//!
+//! [`ParameterTargets`] lists the fees, stability thresholds and oracle
+//! tolerance an admin wants. [`ParameterPlan::fetch`] reads the current
+//! `Hylo` and `PoolConfig` accounts and emits one update instruction per
+//! parameter that differs, in a fixed order, leaving matching parameters
+//! untouched. Targets are validated with [`hylo_core::validated_args`] before
+//! any instruction is built.
+//!
+//! Instructions are signed by the `admin` given to the plan. When the admin
+//! is a multisig, pass its vault as the admin and hand the plan to a
+//! [`MultisigWrapper`] that turns the instructions into a proposal.
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_core::idl::pda;
+use hylo_core::validated_args;
+use hylo_idl::exchange::accounts::Hylo;
+use hylo_idl::exchange::instruction_builders as exchange_builders;
+use hylo_idl::exchange::types::{
+  FeePair, LevercoinFees, StablecoinFees, UFixValue64 as ExchangeValue,
+};
+use hylo_idl::stability_pool::accounts::PoolConfig;
+use hylo_idl::stability_pool::instruction_builders as pool_builders;
+use hylo_idl::stability_pool::types::UFixValue64 as PoolValue;
+
+/// Desired protocol parameters; `None` leaves a parameter as it is.
+#[derive(Clone, Default)]
+pub struct ParameterTargets {
+  /// Oracle confidence as a share of price, above 0% and below 100%
+  pub oracle_conf_tolerance: Option<UFix64<N8>>,
+
+  /// Stability mode thresholds, `threshold_1 > threshold_2 > 1.0`
+  pub stability_thresholds: Option<(UFix64<N2>, UFix64<N2>)>,
+  pub stablecoin_fees: Option<StablecoinFees>,
+  pub levercoin_fees: Option<LevercoinFees>,
+  pub lst_swap_fee: Option<UFix64<N4>>,
+  pub withdrawal_fee: Option<UFix64<N4>>,
+}
+
+/// Update instruction for one parameter that differs from its target.
+#[derive(Clone, Debug)]
+pub struct ParameterChange {
+  /// Parameter updated, named as its account field
+  pub parameter: &'static str,
+  pub instruction: Instruction,
+}
+
+/// Turns admin instructions into instructions proposing them to a multisig.
+pub trait MultisigWrapper {
+  /// Wraps `instructions`, all signed by the multisig vault.
+  ///
+  /// # Errors
+  /// * Instructions cannot be encoded as a proposal
+  fn wrap(&self, instructions: Vec<Instruction>) -> Result<Vec<Instruction>>;
+}
+
+/// Ordered parameter updates taking on-chain state to a target.
+#[derive(Clone, Debug)]
+pub struct ParameterPlan {
+  pub admin: Pubkey,
+  pub changes: Vec<ParameterChange>,
+}
+
+/// `bits` rescaled from `exp` down to `to_exp`, `None` on overflow.
+fn rescaled_bits(bits: u64, exp: i8, to_exp: i8) -> Option<u128> {
+  let shift = u32::try_from(i32::from(exp) - i32::from(to_exp)).ok()?;
+  10u128.checked_pow(shift)?.checked_mul(u128::from(bits))
+}
+
+/// Whether two fixed point values are equal, whatever their exponents,
+/// e.g. `10e-4 == 1e-3`.
+fn same_bits((a_bits, a_exp): (u64, i8), (b_bits, b_exp): (u64, i8)) -> bool {
+  let exp = a_exp.min(b_exp);
+  rescaled_bits(a_bits, a_exp, exp)
+    .zip(rescaled_bits(b_bits, b_exp, exp))
+    .is_some_and(|(a, b)| a == b)
+}
+
+fn same_value(a: &ExchangeValue, b: &ExchangeValue) -> bool {
+  same_bits((a.bits, a.exp), (b.bits, b.exp))
+}
+
+fn same_pair(a: &FeePair, b: &FeePair) -> bool {
+  same_value(&a.mint, &b.mint) && same_value(&a.redeem, &b.redeem)
+}
+
+fn same_stablecoin_fees(a: &StablecoinFees, b: &StablecoinFees) -> bool {
+  same_pair(&a.normal, &b.normal) && same_pair(&a.mode_1, &b.mode_1)
+}
+
+fn same_levercoin_fees(a: &LevercoinFees, b: &LevercoinFees) -> bool {
+  same_pair(&a.normal, &b.normal)
+    && same_pair(&a.mode_1, &b.mode_1)
+    && same_pair(&a.mode_2, &b.mode_2)
+}
+
+fn same_pool_value(a: &PoolValue, b: &PoolValue) -> bool {
+  same_bits((a.bits, a.exp), (b.bits, b.exp))
+}
+
+impl ParameterPlan {
+  /// Updates taking `hylo` and `pool_config` to `targets`.
+  ///
+  /// Oracle tolerance is applied first since it bounds every price the
+  /// later parameters act on, then stability thresholds, exchange fees, and
+  /// the stability pool withdrawal fee last.
+  ///
+  /// # Errors
+  /// * A target fails validation, see [`hylo_core::validated_args`]
+  pub fn diff(
+    admin: Pubkey,
+    hylo: &Hylo,
+    pool_config: &PoolConfig,
+    targets: &ParameterTargets,
+  ) -> Result<ParameterPlan> {
+    let change = |parameter, instruction| ParameterChange {
+      parameter,
+      instruction,
+    };
+    let oracle = targets
+      .oracle_conf_tolerance
+      .map(validated_args::update_oracle_conf_tolerance)
+      .transpose()?
+      .filter(|args| {
+        !same_value(
+          &args.new_oracle_conf_tolerance,
+          &hylo.oracle_conf_tolerance,
+        )
+      })
+      .map(|args| {
+        change(
+          "oracle_conf_tolerance",
+          exchange_builders::update_oracle_conf_tolerance(admin, &args),
+        )
+      });
+    let thresholds = targets
+      .stability_thresholds
+      .map(|(threshold_1, threshold_2)| {
+        validated_args::update_stability_thresholds(threshold_1, threshold_2)
+      })
+      .transpose()?
+      .filter(|args| {
+        !same_value(
+          &args.new_stability_threshold_1,
+          &hylo.stability_threshold_1,
+        ) || !same_value(
+          &args.new_stability_threshold_2,
+          &hylo.stability_threshold_2,
+        )
+      })
+      .map(|args| {
+        change(
+          "stability_thresholds",
+          exchange_builders::update_stability_thresholds(admin, &args),
+        )
+      });
+    let stablecoin_fees = targets
+      .stablecoin_fees
+      .clone()
+      .map(validated_args::update_stablecoin_fees)
+      .transpose()?
+      .filter(|args| {
+        !same_stablecoin_fees(&args.new_stablecoin_fees, &hylo.stablecoin_fees)
+      })
+      .map(|args| {
+        change(
+          "stablecoin_fees",
+          exchange_builders::update_stablecoin_fees(admin, &args),
+        )
+      });
+    let levercoin_fees = targets
+      .levercoin_fees
+      .clone()
+      .map(validated_args::update_levercoin_fees)
+      .transpose()?
+      .filter(|args| {
+        !same_levercoin_fees(&args.new_levercoin_fees, &hylo.levercoin_fees)
+      })
+      .map(|args| {
+        change(
+          "levercoin_fees",
+          exchange_builders::update_levercoin_fees(admin, &args),
+        )
+      });
+    let lst_swap_fee = targets
+      .lst_swap_fee
+      .map(validated_args::update_lst_swap_fee)
+      .transpose()?
+      .filter(|args| !same_value(&args.new_lst_swap_fee, &hylo.lst_swap_fee))
+      .map(|args| {
+        change(
+          "lst_swap_fee",
+          exchange_builders::update_lst_swap_fee(admin, &args),
+        )
+      });
+    let withdrawal_fee = targets
+      .withdrawal_fee
+      .map(validated_args::update_withdrawal_fee)
+      .transpose()?
+      .filter(|args| {
+        !same_pool_value(&args.new_withdrawal_fee, &pool_config.withdrawal_fee)
+      })
+      .map(|args| {
+        change(
+          "withdrawal_fee",
+          pool_builders::update_withdrawal_fee(admin, &args),
+        )
+      });
+    let changes = [
+      oracle,
+      thresholds,
+      stablecoin_fees,
+      levercoin_fees,
+      lst_swap_fee,
+      withdrawal_fee,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    Ok(ParameterPlan { admin, changes })
+  }
+
+  /// Reads the `Hylo` and `PoolConfig` accounts and plans updates to
+  /// `targets`.
+  ///
+  /// # Errors
+  /// * Failed to fetch or deserialize either account
+  /// * A target fails validation
+  pub async fn fetch(
+    rpc_client: &RpcClient,
+    admin: Pubkey,
+    targets: &ParameterTargets,
+  ) -> Result<ParameterPlan> {
+    let accounts = rpc_client
+      .get_multiple_accounts(&[*pda::HYLO, *pda::POOL_CONFIG])
+      .await?;
+    match accounts.as_slice() {
+      [Some(hylo), Some(pool_config)] => {
+        let hylo = Hylo::try_deserialize(&mut hylo.data.as_slice())
+          .context("Invalid Hylo account")?;
+        let pool_config =
+          PoolConfig::try_deserialize(&mut pool_config.data.as_slice())
+            .context("Invalid PoolConfig account")?;
+        ParameterPlan::diff(admin, &hylo, &pool_config, targets)
+      }
+      _ => Err(anyhow!("Hylo or PoolConfig account not found")),
+    }
+  }
+
+  /// Whether on-chain state already matches every target.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+
+  /// Update instructions in plan order, signed by the admin.
+  #[must_use]
+  pub fn instructions(&self) -> Vec<Instruction> {
+    self
+      .changes
+      .iter()
+      .map(|change| change.instruction.clone())
+      .collect()
+  }
+
+  /// Update instructions wrapped into a multisig proposal.
+  ///
+  /// # Errors
+  /// * Wrapping fails
+  pub fn wrap(
+    &self,
+    wrapper: &impl MultisigWrapper,
+  ) -> Result<Vec<Instruction>> {
+    wrapper.wrap(self.instructions())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn same_bits_compares_across_exponents() {
+    assert!(same_bits((10, -4), (1, -3)));
+    assert!(same_bits((0, -8), (0, 0)));
+    assert!(!same_bits((11, -4), (1, -3)));
+    assert!(!same_bits((u64::MAX, 0), (1, -40)));
+  }
+}
@@ -0,0 +1,160 @@
+//! Batched account prefetching.
+//!
+//! `getMultipleAccounts` returns at most [`MAX_MULTIPLE_ACCOUNTS`] accounts
+//! per request. [`get_multiple_accounts_chunked`] splits larger key sets and
+//! fetches the chunks concurrently, and [`AccountPrefetch`] loads every
+//! account needed to read protocol state, including the header and vault of
+//! each registered LST, in as few round trips as possible.
+
+use std::collections::HashMap;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::sysvar;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::{IsInitialized, Pack};
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use futures::future::try_join_all;
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
+use hylo_core::pyth::SOL_USD_PYTH_FEED;
+use itertools::Itertools;
+
+use crate::util::{
+  deserialize_lookup_table, lst_registry_entries, LstRegistryEntry,
+  LST_REGISTRY_LOOKUP_TABLE,
+};
+
+/// Most accounts one `getMultipleAccounts` request may ask for.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Fetches `keys` in concurrent requests of at most
+/// [`MAX_MULTIPLE_ACCOUNTS`], preserving order.
+///
+/// # Errors
+/// - Any request fails
+pub async fn get_multiple_accounts_chunked(
+  rpc_client: &RpcClient,
+  keys: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+  let chunks = try_join_all(
+    keys
+      .chunks(MAX_MULTIPLE_ACCOUNTS)
+      .map(|chunk| rpc_client.get_multiple_accounts(chunk)),
+  )
+  .await?;
+  Ok(chunks.into_iter().flatten().collect())
+}
+
+/// Protocol-wide accounts read by quoting and monitoring: exchange and pool
+/// configuration, token mints, pool token accounts, oracle and clock.
+#[must_use]
+pub fn protocol_pubkeys() -> Vec<Pubkey> {
+  vec![
+    *pda::HYLO,
+    *pda::POOL_CONFIG,
+    HYUSD::MINT,
+    XSOL::MINT,
+    SHYUSD::MINT,
+    *pda::HYUSD_POOL,
+    *pda::XSOL_POOL,
+    SOL_USD_PYTH_FEED,
+    sysvar::clock::ID,
+  ]
+}
+
+/// Accounts fetched in one batch, keyed by address.
+#[derive(Debug, Clone, Default)]
+pub struct AccountPrefetch {
+  accounts: HashMap<Pubkey, Account>,
+  lst_registry: Vec<LstRegistryEntry>,
+}
+
+impl AccountPrefetch {
+  /// Fetches `keys`, omitting accounts that do not exist.
+  ///
+  /// # Errors
+  /// - Any request fails
+  pub async fn fetch(
+    rpc_client: &RpcClient,
+    keys: &[Pubkey],
+  ) -> Result<AccountPrefetch> {
+    let accounts = get_multiple_accounts_chunked(rpc_client, keys)
+      .await?
+      .into_iter()
+      .zip(keys)
+      .filter_map(|(account, key)| account.map(|account| (*key, account)))
+      .collect();
+    Ok(AccountPrefetch {
+      accounts,
+      lst_registry: vec![],
+    })
+  }
+
+  /// Fetches [`protocol_pubkeys`] plus the header and vault of every LST in
+  /// the registry lookup table, in one request after reading the table.
+  ///
+  /// # Errors
+  /// - Registry lookup table is missing or malformed
+  /// - Any request fails
+  pub async fn fetch_protocol(
+    rpc_client: &RpcClient,
+  ) -> Result<AccountPrefetch> {
+    let table_account =
+      rpc_client.get_account(&LST_REGISTRY_LOOKUP_TABLE).await?;
+    let table =
+      deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &table_account)?;
+    let lst_registry = lst_registry_entries(&table)?;
+    let keys = protocol_pubkeys()
+      .into_iter()
+      .chain(lst_registry.iter().flat_map(|lst| [lst.header, lst.vault]))
+      .collect_vec();
+    let prefetch = AccountPrefetch::fetch(rpc_client, &keys).await?;
+    Ok(AccountPrefetch {
+      lst_registry,
+      ..prefetch
+    })
+  }
+
+  /// Registry entry of each registered LST, when fetched with
+  /// [`Self::fetch_protocol`].
+  #[must_use]
+  pub fn lst_registry(&self) -> &[LstRegistryEntry] {
+    &self.lst_registry
+  }
+
+  #[must_use]
+  pub fn get(&self, key: &Pubkey) -> Option<&Account> {
+    self.accounts.get(key)
+  }
+
+  /// Fetched account at `key`.
+  ///
+  /// # Errors
+  /// - Account was not fetched or does not exist
+  pub fn account(&self, key: &Pubkey) -> Result<&Account> {
+    self.get(key).ok_or(anyhow!("Account {key} not prefetched"))
+  }
+
+  /// Deserializes the Anchor account at `key`, discriminator included.
+  ///
+  /// # Errors
+  /// - Account is missing
+  /// - Deserialization to `T` fails
+  pub fn anchor<T: AccountDeserialize>(&self, key: &Pubkey) -> Result<T> {
+    let account = self.account(key)?;
+    T::try_deserialize(&mut account.data.as_slice())
+      .map_err(|e| anyhow!("Failed to deserialize account {key}: {e}"))
+  }
+
+  /// Unpacks the SPL account at `key`.
+  ///
+  /// # Errors
+  /// - Account is missing
+  /// - Unpacking to `T` fails
+  pub fn spl<T: Pack + IsInitialized>(&self, key: &Pubkey) -> Result<T> {
+    let account = self.account(key)?;
+    Ok(T::unpack(&account.data)?)
+  }
+}
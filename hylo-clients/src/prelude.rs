@@ -3,14 +3,16 @@ pub use anchor_client::solana_sdk::signature::Signature;
 pub use anchor_client::Cluster;
 pub use anchor_lang::prelude::Pubkey;
 pub use anyhow::Result;
-pub use fix::prelude::*;
-pub use hylo_core::idl::tokens::{HYUSD, JITOSOL, SHYUSD, XSOL};
+pub use hylo_core::prelude::*;
 
+pub use crate::error::{ErrorKind, HyloError};
+pub use crate::estimate::{ComputeUnitBaselines, TransactionEstimate};
 pub use crate::exchange_client::ExchangeClient;
 pub use crate::instructions::{
   ExchangeInstructionBuilder, InstructionBuilder,
   StabilityPoolInstructionBuilder,
 };
+pub use crate::lst_header_cache::{LstHeaderCache, RefreshPolicy};
 pub use crate::program_client::{ProgramClient, VersionedTransactionData};
 pub use crate::stability_pool_client::StabilityPoolClient;
 pub use crate::syntax_helpers::InstructionBuilderExt;
@@ -0,0 +1,296 @@
+//! Compute unit price selection per transaction class.
+//!
+//! User trades are latency sensitive and bid higher, while cranks can wait
+//! for a cheaper slot. A [`PriorityFeeSource`] prices each
+//! [`TransactionClass`] from the accounts a transaction writes, and
+//! [`with_compute_budget`] prepends the resulting compute budget to
+//! [`VersionedTransactionData`]. Prices come from recent prioritization fees
+//! via RPC, a fixed schedule, or, with the `fee-estimator` feature, an
+//! external `getPriorityFeeEstimate` endpoint.
+
+use std::sync::Arc;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+
+use crate::estimate::TransactionEstimate;
+use crate::program_client::VersionedTransactionData;
+
+/// Urgency of a transaction, used to pick its compute unit price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionClass {
+  /// Mints, redeems, swaps and pool deposits signed by a user
+  UserTrade,
+  /// Keeper transactions such as rebalances and fee sweeps
+  Crank,
+}
+
+/// Provides compute unit prices in micro-lamports.
+#[async_trait::async_trait]
+pub trait PriorityFeeSource: Send + Sync {
+  /// Compute unit price for a transaction of `class` writing `writable`.
+  ///
+  /// # Errors
+  /// - Source could not be queried
+  async fn compute_unit_price(
+    &self,
+    class: TransactionClass,
+    writable: &[Pubkey],
+  ) -> Result<u64>;
+}
+
+/// Fixed compute unit price per class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPriorityFee {
+  pub user_trade: u64,
+  pub crank: u64,
+}
+
+#[async_trait::async_trait]
+impl PriorityFeeSource for FixedPriorityFee {
+  async fn compute_unit_price(
+    &self,
+    class: TransactionClass,
+    _writable: &[Pubkey],
+  ) -> Result<u64> {
+    match class {
+      TransactionClass::UserTrade => Ok(self.user_trade),
+      TransactionClass::Crank => Ok(self.crank),
+    }
+  }
+}
+
+/// Value at `percentile` (0 to 100) of `fees`, or zero if empty.
+#[must_use]
+pub fn fee_percentile(fees: &[u64], percentile: u8) -> u64 {
+  let sorted = fees.iter().copied().sorted().collect_vec();
+  let rank = sorted
+    .len()
+    .saturating_sub(1)
+    .saturating_mul(usize::from(percentile.min(100)))
+    .div_ceil(100);
+  sorted.get(rank).copied().unwrap_or(0)
+}
+
+/// Percentile of fees paid in recent slots for the written accounts, from
+/// `getRecentPrioritizationFees`.
+pub struct RecentPrioritizationFees {
+  rpc_client: Arc<RpcClient>,
+  user_trade_percentile: u8,
+  crank_percentile: u8,
+}
+
+impl RecentPrioritizationFees {
+  /// Source bidding the 75th percentile for user trades and the median for
+  /// cranks.
+  #[must_use]
+  pub fn new(rpc_client: Arc<RpcClient>) -> RecentPrioritizationFees {
+    RecentPrioritizationFees {
+      rpc_client,
+      user_trade_percentile: 75,
+      crank_percentile: 50,
+    }
+  }
+
+  /// Overrides the percentile bid for each class.
+  ///
+  /// # Errors
+  /// - Either percentile exceeds 100
+  pub fn with_percentiles(
+    self,
+    user_trade_percentile: u8,
+    crank_percentile: u8,
+  ) -> Result<RecentPrioritizationFees> {
+    ensure!(
+      user_trade_percentile <= 100 && crank_percentile <= 100,
+      "Fee percentiles must be at most 100"
+    );
+    Ok(RecentPrioritizationFees {
+      user_trade_percentile,
+      crank_percentile,
+      ..self
+    })
+  }
+}
+
+#[async_trait::async_trait]
+impl PriorityFeeSource for RecentPrioritizationFees {
+  async fn compute_unit_price(
+    &self,
+    class: TransactionClass,
+    writable: &[Pubkey],
+  ) -> Result<u64> {
+    let fees = self
+      .rpc_client
+      .get_recent_prioritization_fees(writable)
+      .await?
+      .into_iter()
+      .map(|fee| fee.prioritization_fee)
+      .collect_vec();
+    let percentile = match class {
+      TransactionClass::UserTrade => self.user_trade_percentile,
+      TransactionClass::Crank => self.crank_percentile,
+    };
+    Ok(fee_percentile(&fees, percentile))
+  }
+}
+
+/// Distinct writable accounts across all instructions of `vtd`.
+#[must_use]
+pub fn writable_accounts(vtd: &VersionedTransactionData) -> Vec<Pubkey> {
+  vtd
+    .instructions
+    .iter()
+    .flat_map(|ix| &ix.accounts)
+    .filter(|meta| meta.is_writable)
+    .map(|meta| meta.pubkey)
+    .unique()
+    .collect()
+}
+
+/// Prepends compute unit limit and price instructions to `vtd`, sizing the
+/// limit from `estimate` and pricing it for `class` from `source`.
+///
+/// # Errors
+/// - Fee source fails
+pub async fn with_compute_budget<S: PriorityFeeSource + ?Sized>(
+  source: &S,
+  class: TransactionClass,
+  estimate: TransactionEstimate,
+  vtd: VersionedTransactionData,
+) -> Result<VersionedTransactionData> {
+  let price = source
+    .compute_unit_price(class, &writable_accounts(&vtd))
+    .await?;
+  let instructions = [
+    estimate.compute_unit_limit_instruction(),
+    ComputeBudgetInstruction::set_compute_unit_price(price),
+  ]
+  .into_iter()
+  .chain(vtd.instructions)
+  .collect();
+  Ok(VersionedTransactionData::new(
+    instructions,
+    vtd.lookup_tables,
+  ))
+}
+
+#[cfg(feature = "fee-estimator")]
+pub use estimator::{PriorityFeeEstimator, PriorityLevel};
+
+#[cfg(feature = "fee-estimator")]
+mod estimator {
+  use anchor_lang::prelude::Pubkey;
+  use anyhow::{anyhow, Result};
+  use serde_json::{json, Value};
+
+  use super::{PriorityFeeSource, TransactionClass};
+
+  /// Priority levels accepted by `getPriorityFeeEstimate`.
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  pub enum PriorityLevel {
+    Min,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+    UnsafeMax,
+  }
+
+  impl PriorityLevel {
+    fn as_str(self) -> &'static str {
+      match self {
+        PriorityLevel::Min => "Min",
+        PriorityLevel::Low => "Low",
+        PriorityLevel::Medium => "Medium",
+        PriorityLevel::High => "High",
+        PriorityLevel::VeryHigh => "VeryHigh",
+        PriorityLevel::UnsafeMax => "UnsafeMax",
+      }
+    }
+  }
+
+  /// External estimator serving the `getPriorityFeeEstimate` JSON-RPC
+  /// method, as offered by Helius and Triton.
+  pub struct PriorityFeeEstimator {
+    http: reqwest::Client,
+    url: String,
+    user_trade: PriorityLevel,
+    crank: PriorityLevel,
+  }
+
+  impl PriorityFeeEstimator {
+    /// Estimator at `url` requesting `High` for user trades and `Medium`
+    /// for cranks.
+    #[must_use]
+    pub fn new(url: String) -> PriorityFeeEstimator {
+      PriorityFeeEstimator {
+        http: reqwest::Client::new(),
+        url,
+        user_trade: PriorityLevel::High,
+        crank: PriorityLevel::Medium,
+      }
+    }
+
+    /// Overrides the priority level requested for each class.
+    #[must_use]
+    pub fn with_levels(
+      self,
+      user_trade: PriorityLevel,
+      crank: PriorityLevel,
+    ) -> PriorityFeeEstimator {
+      PriorityFeeEstimator {
+        user_trade,
+        crank,
+        ..self
+      }
+    }
+  }
+
+  #[async_trait::async_trait]
+  impl PriorityFeeSource for PriorityFeeEstimator {
+    async fn compute_unit_price(
+      &self,
+      class: TransactionClass,
+      writable: &[Pubkey],
+    ) -> Result<u64> {
+      let level = match class {
+        TransactionClass::UserTrade => self.user_trade,
+        TransactionClass::Crank => self.crank,
+      };
+      let account_keys = writable
+        .iter()
+        .map(|key| Value::String(key.to_string()))
+        .collect();
+      let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getPriorityFeeEstimate",
+        "params": [{
+          "accountKeys": Value::Array(account_keys),
+          "options": { "priorityLevel": level.as_str() },
+        }],
+      });
+      let response: Value = self
+        .http
+        .post(&self.url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+      let estimate = response
+        .pointer("/result/priorityFeeEstimate")
+        .and_then(Value::as_f64)
+        .ok_or(anyhow!("Malformed priority fee estimate: {response}"))?;
+      // Float to integer casts saturate, rounding up keeps the bid
+      #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+      let price = estimate.max(0.0).ceil() as u64;
+      Ok(price)
+    }
+  }
+}
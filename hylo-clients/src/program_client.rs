@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use anchor_client::solana_sdk::account::Account;
 use anchor_client::solana_sdk::address_lookup_table::AddressLookupTableAccount;
 use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
 use anchor_client::solana_sdk::instruction::Instruction;
@@ -10,13 +11,18 @@ use anchor_client::solana_sdk::transaction::VersionedTransaction;
 use anchor_client::{Client, Cluster, Program};
 use anchor_lang::prelude::AccountMeta;
 use anchor_lang::{AnchorDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
 use anyhow::{anyhow, Result};
 use base64::prelude::{Engine, BASE64_STANDARD};
 use itertools::Itertools;
 
+use crate::prefetch::get_multiple_accounts_chunked;
+use crate::program_logs::{ProgramLogs, SimulationDiagnostics};
+use crate::rpc_gate::RpcGate;
 use crate::util::{
-  build_lst_registry, build_v0_transaction, deserialize_lookup_table,
-  parse_event, simulation_config, LST_REGISTRY_LOOKUP_TABLE,
+  build_lst_registry, build_v0_transaction, closable_user_mints,
+  close_user_ata_instruction, deserialize_lookup_table, parse_event,
+  simulation_config, CLOSABLE_USER_MINTS, LST_REGISTRY_LOOKUP_TABLE,
 };
 
 /// Components from which a [`VersionedTransaction`] can be built.
@@ -60,6 +66,11 @@ pub trait ProgramClient: Sized {
 
   fn keypair(&self) -> Arc<Keypair>;
 
+  /// Gate account fetches go through, if the client has one.
+  fn gate(&self) -> Option<&RpcGate> {
+    None
+  }
+
   /// Constructs the program client with a given keypair and associated program
   /// ID.
   ///
@@ -136,7 +147,20 @@ pub trait ProgramClient: Sized {
   ///
   /// # Errors
   /// - Failed to build transaction
-  /// - Failed to send and confirm transaction
+  /// - Failed to send and confirm transaction; a failed preflight carries its
+  ///   [`ProgramLogs`] as context
+  #[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+      skip_all,
+      err,
+      fields(
+        program_id = %Self::PROGRAM_ID,
+        instructions = args.instructions.len(),
+        signature = tracing::field::Empty,
+      ),
+    )
+  )]
   async fn send_v0_transaction(
     &self,
     args: &VersionedTransactionData,
@@ -146,10 +170,46 @@ pub trait ProgramClient: Sized {
       .program()
       .rpc()
       .send_and_confirm_transaction(&tx)
-      .await?;
+      .await
+      .map_err(|err| match ProgramLogs::from_client_error(&err) {
+        Some(logs) => anyhow::Error::new(err).context(logs),
+        None => err.into(),
+      })?;
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("signature", tracing::field::display(sig));
     Ok(sig)
   }
 
+  /// Fetches `keys` through [`Self::gate`], or straight from the program's
+  /// RPC client without one.
+  ///
+  /// # Errors
+  /// - Any request fails
+  async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>> {
+    match self.gate() {
+      Some(gate) => gate.get_multiple_accounts(keys).await,
+      None => get_multiple_accounts_chunked(&self.program().rpc(), keys).await,
+    }
+  }
+
+  /// Fetches the account at `key` like [`Self::get_multiple_accounts`].
+  ///
+  /// # Errors
+  /// - Request fails
+  /// - Account does not exist
+  async fn get_account(&self, key: &Pubkey) -> Result<Account> {
+    self
+      .get_multiple_accounts(std::slice::from_ref(key))
+      .await?
+      .into_iter()
+      .next()
+      .flatten()
+      .ok_or(anyhow!("Account {key} not found"))
+  }
+
   /// Loads LST registry lookup table and parses it into `remaining_accounts`.
   ///
   /// # Errors
@@ -170,7 +230,7 @@ pub trait ProgramClient: Sized {
     &self,
     key: &Pubkey,
   ) -> Result<AddressLookupTableAccount> {
-    let account = self.program().rpc().get_account(key).await?;
+    let account = self.get_account(key).await?;
     deserialize_lookup_table(key, &account)
   }
 
@@ -183,8 +243,6 @@ pub trait ProgramClient: Sized {
     pubkeys: &[Pubkey],
   ) -> Result<Vec<AddressLookupTableAccount>> {
     self
-      .program()
-      .rpc()
       .get_multiple_accounts(pubkeys)
       .await?
       .iter()
@@ -199,6 +257,33 @@ pub trait ProgramClient: Sized {
       .try_collect()
   }
 
+  /// Builds transaction data closing the user's hyUSD, xSOL, sHYUSD, and wSOL
+  /// ATAs that are empty (or only hold wrapped SOL), reclaiming rent.
+  ///
+  /// # Errors
+  /// - Failed to fetch token accounts
+  /// - Token account data cannot be unpacked
+  /// - No closable accounts found
+  async fn close_empty_user_atas(
+    &self,
+    user: &Pubkey,
+  ) -> Result<VersionedTransactionData> {
+    let atas = CLOSABLE_USER_MINTS
+      .iter()
+      .map(|mint| get_associated_token_address(user, mint))
+      .collect_vec();
+    let accounts = self.get_multiple_accounts(&atas).await?;
+    let instructions: Vec<Instruction> = closable_user_mints(&accounts)?
+      .iter()
+      .map(|mint| close_user_ata_instruction(user, mint))
+      .try_collect()?;
+    if instructions.is_empty() {
+      Err(anyhow!("No closable token accounts for {user}"))
+    } else {
+      Ok(VersionedTransactionData::new(instructions, vec![]))
+    }
+  }
+
   /// Simulates transaction and returns deserialized return data.
   ///
   /// # Errors
@@ -265,4 +350,19 @@ pub trait ProgramClient: Sized {
     let compute_units = result.value.units_consumed;
     Ok((event, compute_units))
   }
+
+  /// Simulates transaction and parses its logs, whether or not it fails.
+  ///
+  /// # Errors
+  /// * Simulation request fails
+  async fn simulate_with_diagnostics(
+    &self,
+    tx: &VersionedTransaction,
+  ) -> Result<SimulationDiagnostics> {
+    let rpc = self.program().rpc();
+    let result = rpc
+      .simulate_transaction_with_config(tx, simulation_config())
+      .await?;
+    Ok(SimulationDiagnostics::from(&result.value))
+  }
 }
@@ -0,0 +1,335 @@
+//! Structured diagnostics from program log lines.
+//!
+//! Events are decoded from `Program data:` lines, but the runtime and Anchor
+//! log more than events: the instruction each program ran, compute units
+//! consumed, `msg!` output, and the code and message of a failed
+//! constraint. [`ProgramLogs::parse`] turns those lines into [`LogEntry`]s,
+//! each attributed to the program that logged it.
+//!
+//! [`ProgramClient::send_v0_transaction`] attaches the logs of a failed
+//! preflight to its error; recover them with
+//! `anyhow::Error::downcast_ref::<ProgramLogs>`.
+//! [`ProgramClient::simulate_with_diagnostics`] returns them with the
+//! simulation result.
+//!
+//! [`ProgramClient::send_v0_transaction`]:
+//!   crate::program_client::ProgramClient::send_v0_transaction
+//! [`ProgramClient::simulate_with_diagnostics`]:
+//!   crate::program_client::ProgramClient::simulate_with_diagnostics
+
+use std::fmt::{self, Display};
+
+use anchor_client::solana_client::client_error::{
+  ClientError, ClientErrorKind,
+};
+use anchor_client::solana_client::rpc_request::{
+  RpcError, RpcResponseErrorData,
+};
+use anchor_client::solana_client::rpc_response::RpcSimulateTransactionResult;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+/// Error logged by Anchor when an instruction fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorErrorLog {
+  pub program_id: Pubkey,
+
+  /// Error variant name, e.g. `SlippageExceeded`
+  pub code: String,
+
+  /// Error number, `6000` and up for program errors
+  pub number: u32,
+  pub message: String,
+
+  /// Source location or account that raised the error, if logged
+  pub origin: Option<String>,
+}
+
+impl Display for AnchorErrorLog {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} ({}): {}", self.code, self.number, self.message)?;
+    match &self.origin {
+      Some(origin) => write!(f, ", {origin}"),
+      None => Ok(()),
+    }
+  }
+}
+
+/// One parsed log line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEntry {
+  /// Program invoked at a stack depth, 1 for top-level instructions
+  Invoke {
+    program_id: Pubkey,
+    depth: usize,
+  },
+
+  /// Anchor instruction name, e.g. `MintStablecoin`
+  Instruction {
+    program_id: Pubkey,
+    name: String,
+  },
+
+  /// `msg!` output not otherwise recognized
+  Message {
+    program_id: Pubkey,
+    message: String,
+  },
+  AnchorError(AnchorErrorLog),
+
+  /// Base64 event data
+  Data {
+    program_id: Pubkey,
+    data: String,
+  },
+
+  /// Base64 instruction return data
+  Return {
+    program_id: Pubkey,
+    data: String,
+  },
+  ComputeUnits {
+    program_id: Pubkey,
+    depth: usize,
+    consumed: u64,
+    limit: u64,
+  },
+  Success {
+    program_id: Pubkey,
+  },
+  Failed {
+    program_id: Pubkey,
+    reason: String,
+  },
+
+  /// Runtime dropped the remaining lines
+  Truncated,
+
+  /// Line in no known format
+  Unknown(String),
+}
+
+/// Parses the fields of an Anchor error message, after the `AnchorError`
+/// prefix.
+fn anchor_error(program_id: Pubkey, message: &str) -> Option<AnchorErrorLog> {
+  let rest = message.strip_prefix("AnchorError ")?;
+  let (origin, rest) = rest.split_once(". Error Code: ")?;
+  let (code, rest) = rest.split_once(". Error Number: ")?;
+  let (number, message) = rest.split_once(". Error Message: ")?;
+  Some(AnchorErrorLog {
+    program_id,
+    code: code.to_string(),
+    number: number.parse().ok()?,
+    message: message.trim_end_matches('.').to_string(),
+    origin: (origin != "occurred").then(|| origin.to_string()),
+  })
+}
+
+/// Parses a line logged while `program_id` is the innermost program.
+fn entry(program_id: Option<Pubkey>, depth: usize, line: &str) -> LogEntry {
+  let words: Vec<&str> = line.split_whitespace().collect();
+  let id = |word: &str| word.parse::<Pubkey>().ok();
+  let logged = |message: &str| {
+    program_id.map_or(LogEntry::Unknown(line.to_string()), |program_id| {
+      message
+        .strip_prefix("Instruction: ")
+        .map(|name| LogEntry::Instruction {
+          program_id,
+          name: name.to_string(),
+        })
+        .or_else(|| {
+          anchor_error(program_id, message).map(LogEntry::AnchorError)
+        })
+        .unwrap_or(LogEntry::Message {
+          program_id,
+          message: message.to_string(),
+        })
+    })
+  };
+  match (words.as_slice(), line) {
+    (_, "Log truncated") => LogEntry::Truncated,
+    (_, _) if line.starts_with("Program log: ") => {
+      logged(line.trim_start_matches("Program log: "))
+    }
+    (["Program", "data:", data], _) => match program_id {
+      Some(program_id) => LogEntry::Data {
+        program_id,
+        data: (*data).to_string(),
+      },
+      None => LogEntry::Unknown(line.to_string()),
+    },
+    (["Program", "return:", program, data], _) => match id(program) {
+      Some(program_id) => LogEntry::Return {
+        program_id,
+        data: (*data).to_string(),
+      },
+      None => LogEntry::Unknown(line.to_string()),
+    },
+    (["Program", program, "invoke", level], _) => {
+      match (id(program), level.trim_matches(['[', ']']).parse()) {
+        (Some(program_id), Ok(depth)) => LogEntry::Invoke { program_id, depth },
+        _ => LogEntry::Unknown(line.to_string()),
+      }
+    }
+    (["Program", program, "consumed", consumed, "of", limit, ..], _) => {
+      match (id(program), consumed.parse(), limit.parse()) {
+        (Some(program_id), Ok(consumed), Ok(limit)) => LogEntry::ComputeUnits {
+          program_id,
+          depth,
+          consumed,
+          limit,
+        },
+        _ => LogEntry::Unknown(line.to_string()),
+      }
+    }
+    (["Program", program, "success"], _) => match id(program) {
+      Some(program_id) => LogEntry::Success { program_id },
+      None => LogEntry::Unknown(line.to_string()),
+    },
+    (["Program", program, "failed:", ..], _) => {
+      match (id(program), line.split_once("failed: ")) {
+        (Some(program_id), Some((_, reason))) => LogEntry::Failed {
+          program_id,
+          reason: reason.to_string(),
+        },
+        _ => LogEntry::Unknown(line.to_string()),
+      }
+    }
+    _ => LogEntry::Unknown(line.to_string()),
+  }
+}
+
+/// Parsed log lines of one transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProgramLogs {
+  pub entries: Vec<LogEntry>,
+}
+
+impl ProgramLogs {
+  /// Parses `lines` in order, tracking the invocation stack to attribute
+  /// each line to its program.
+  #[must_use]
+  pub fn parse(lines: &[String]) -> ProgramLogs {
+    let (_, entries) = lines.iter().fold(
+      (Vec::<Pubkey>::new(), Vec::new()),
+      |(mut stack, mut entries), line| {
+        let entry = entry(stack.last().copied(), stack.len(), line);
+        match &entry {
+          LogEntry::Invoke { program_id, .. } => stack.push(*program_id),
+          LogEntry::Success { .. } | LogEntry::Failed { .. } => {
+            stack.pop();
+          }
+          _ => {}
+        }
+        entries.push(entry);
+        (stack, entries)
+      },
+    );
+    ProgramLogs { entries }
+  }
+
+  /// Logs of a failed preflight carried by an RPC error, if any.
+  #[must_use]
+  pub fn from_client_error(error: &ClientError) -> Option<ProgramLogs> {
+    match error.kind() {
+      ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+        ..
+      }) => result.logs.as_deref().map(ProgramLogs::parse),
+      _ => None,
+    }
+  }
+
+  /// Units consumed by each top-level instruction's program, in order.
+  #[must_use]
+  pub fn top_level_units(&self) -> Vec<(Pubkey, u64)> {
+    self
+      .entries
+      .iter()
+      .filter_map(|entry| match entry {
+        LogEntry::ComputeUnits {
+          program_id,
+          depth: 1,
+          consumed,
+          ..
+        } => Some((*program_id, *consumed)),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// Anchor instruction names with their programs, in order.
+  #[must_use]
+  pub fn instructions(&self) -> Vec<(Pubkey, &str)> {
+    self
+      .entries
+      .iter()
+      .filter_map(|entry| match entry {
+        LogEntry::Instruction { program_id, name } => {
+          Some((*program_id, name.as_str()))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// Anchor errors logged, innermost first as the runtime logs them.
+  #[must_use]
+  pub fn anchor_errors(&self) -> Vec<&AnchorErrorLog> {
+    self
+      .entries
+      .iter()
+      .filter_map(|entry| match entry {
+        LogEntry::AnchorError(error) => Some(error),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// First program failure reported by the runtime.
+  #[must_use]
+  pub fn failure(&self) -> Option<(Pubkey, &str)> {
+    self.entries.iter().find_map(|entry| match entry {
+      LogEntry::Failed { program_id, reason } => {
+        Some((*program_id, reason.as_str()))
+      }
+      _ => None,
+    })
+  }
+}
+
+impl Display for ProgramLogs {
+  /// Summary of the failure: the first Anchor error, else the first runtime
+  /// failure.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (self.anchor_errors().first(), self.failure()) {
+      (Some(error), _) => write!(f, "Program {}: {error}", error.program_id),
+      (None, Some((program_id, reason))) => {
+        write!(f, "Program {program_id} failed: {reason}")
+      }
+      (None, None) => write!(f, "{} log lines, no failure", self.entries.len()),
+    }
+  }
+}
+
+/// Simulation outcome with parsed logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationDiagnostics {
+  /// Transaction error, rendered, if the simulation failed
+  pub err: Option<String>,
+  pub units_consumed: Option<u64>,
+  pub logs: ProgramLogs,
+}
+
+impl From<&RpcSimulateTransactionResult> for SimulationDiagnostics {
+  fn from(result: &RpcSimulateTransactionResult) -> Self {
+    SimulationDiagnostics {
+      err: result.err.as_ref().map(ToString::to_string),
+      units_consumed: result.units_consumed,
+      logs: result
+        .logs
+        .as_deref()
+        .map(ProgramLogs::parse)
+        .unwrap_or_default(),
+    }
+  }
+}
@@ -0,0 +1,214 @@
+//! Rate limiting and request coalescing for shared RPC endpoints.
+//!
+//! Many quoting workers against one endpoint tend to ask for the same
+//! protocol accounts at the same moment, and together can exhaust its quota.
+//! [`RpcGate`] sits in front of an [`RpcClient`]: identical concurrent
+//! account fetches share one in-flight request, and every request that does
+//! go out first takes a token from a [`TokenBucket`]. Program clients given
+//! a gate with `with_gate` send their account fetches through it.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+use crate::prefetch::{get_multiple_accounts_chunked, MAX_MULTIPLE_ACCOUNTS};
+
+/// Token bucket shared by every request through a gate.
+///
+/// Tokens refill continuously up to `capacity`. A request taking more tokens
+/// than are available borrows against future refills and waits out the
+/// debt, so callers queue in arrival order without polling.
+#[derive(Debug)]
+pub struct TokenBucket {
+  capacity: f64,
+  refill_per_sec: f64,
+  state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+  tokens: f64,
+  refilled_at: Instant,
+}
+
+impl TokenBucket {
+  /// Full bucket holding `capacity` tokens, refilling `refill_per_sec`.
+  #[must_use]
+  pub fn new(capacity: u32, refill_per_sec: NonZeroU32) -> TokenBucket {
+    TokenBucket {
+      capacity: f64::from(capacity),
+      refill_per_sec: f64::from(refill_per_sec.get()),
+      state: Mutex::new(BucketState {
+        tokens: f64::from(capacity),
+        refilled_at: Instant::now(),
+      }),
+    }
+  }
+
+  /// Takes `tokens` at `now`, returning how long the caller must wait before
+  /// its request may go out.
+  fn reserve(&self, tokens: u32, now: Instant) -> Duration {
+    let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+    let elapsed = now.saturating_duration_since(state.refilled_at);
+    let refilled = (state.tokens + elapsed.as_secs_f64() * self.refill_per_sec)
+      .min(self.capacity);
+    state.tokens = refilled - f64::from(tokens);
+    state.refilled_at = now;
+    Duration::from_secs_f64((-state.tokens).max(0.0) / self.refill_per_sec)
+  }
+
+  /// Waits until `tokens` are available and takes them.
+  pub async fn acquire(&self, tokens: u32) {
+    let wait = self.reserve(tokens, Instant::now());
+    if !wait.is_zero() {
+      tokio::time::sleep(wait).await;
+    }
+  }
+}
+
+type SharedFetch =
+  Shared<BoxFuture<'static, Result<Arc<Vec<Option<Account>>>, Arc<String>>>>;
+
+/// Rate limited, coalescing front for account fetches on one endpoint.
+///
+/// Cheap to share behind an [`Arc`] between quoting workers.
+pub struct RpcGate {
+  rpc_client: Arc<RpcClient>,
+  bucket: Arc<TokenBucket>,
+  in_flight: Arc<Mutex<HashMap<Vec<Pubkey>, SharedFetch>>>,
+}
+
+impl RpcGate {
+  #[must_use]
+  pub fn new(rpc_client: Arc<RpcClient>, bucket: TokenBucket) -> RpcGate {
+    RpcGate {
+      rpc_client,
+      bucket: Arc::new(bucket),
+      in_flight: Arc::new(Mutex::new(HashMap::new())),
+    }
+  }
+
+  #[must_use]
+  pub fn rpc_client(&self) -> &Arc<RpcClient> {
+    &self.rpc_client
+  }
+
+  /// Fetches `keys` like [`get_multiple_accounts_chunked`], taking one token
+  /// per request. Joins an identical fetch already in flight instead of
+  /// sending another.
+  ///
+  /// # Errors
+  /// - Any request fails, for every caller sharing the fetch
+  pub async fn get_multiple_accounts(
+    &self,
+    keys: &[Pubkey],
+  ) -> Result<Vec<Option<Account>>> {
+    let fetch = {
+      let mut in_flight = self
+        .in_flight
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+      in_flight
+        .entry(keys.to_vec())
+        .or_insert_with(|| self.fetch(keys.to_vec()))
+        .clone()
+    };
+    fetch
+      .await
+      .map(|accounts| accounts.as_ref().clone())
+      .map_err(|e| anyhow!("{e}"))
+  }
+
+  /// Fetches the account at `key` through the gate.
+  ///
+  /// # Errors
+  /// - Request fails
+  /// - Account does not exist
+  pub async fn get_account(&self, key: &Pubkey) -> Result<Account> {
+    self
+      .get_multiple_accounts(std::slice::from_ref(key))
+      .await?
+      .into_iter()
+      .next()
+      .flatten()
+      .ok_or(anyhow!("Account {key} not found"))
+  }
+
+  /// Rate limited fetch of `keys`, shareable between waiting callers.
+  ///
+  /// The fetch removes its own in-flight entry as it completes, so a result
+  /// is never served to later callers, even when every waiter was cancelled
+  /// first. A fetch abandoned midway stays in flight and is resumed by the
+  /// next caller for the same keys.
+  fn fetch(&self, keys: Vec<Pubkey>) -> SharedFetch {
+    let rpc_client = Arc::clone(&self.rpc_client);
+    let bucket = Arc::clone(&self.bucket);
+    let in_flight = Arc::clone(&self.in_flight);
+    async move {
+      let requests = keys.len().div_ceil(MAX_MULTIPLE_ACCOUNTS).max(1);
+      bucket
+        .acquire(u32::try_from(requests).unwrap_or(u32::MAX))
+        .await;
+      let fetched = get_multiple_accounts_chunked(&rpc_client, &keys)
+        .await
+        .map(Arc::new)
+        .map_err(|e| Arc::new(format!("{e:#}")));
+      in_flight
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&keys);
+      fetched
+    }
+    .boxed()
+    .shared()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Gate in front of an endpoint refusing connections, whose bucket makes
+  /// each request wait about 50ms.
+  fn gate() -> RpcGate {
+    let rpc_client = Arc::new(RpcClient::new("http://127.0.0.1:1".into()));
+    RpcGate::new(
+      rpc_client,
+      TokenBucket::new(0, NonZeroU32::new(20).unwrap_or(NonZeroU32::MIN)),
+    )
+  }
+
+  fn in_flight(gate: &RpcGate) -> usize {
+    gate.in_flight.lock().map_or(0, |in_flight| in_flight.len())
+  }
+
+  #[tokio::test]
+  async fn completed_fetch_leaves_no_entry() {
+    let gate = gate();
+    let keys = [Pubkey::new_unique()];
+    assert!(gate.get_multiple_accounts(&keys).await.is_err());
+    assert_eq!(in_flight(&gate), 0);
+  }
+
+  #[tokio::test]
+  async fn cancelled_fetch_resumes_for_next_caller() {
+    let gate = gate();
+    let keys = [Pubkey::new_unique()];
+    let cancelled = tokio::time::timeout(
+      Duration::from_millis(1),
+      gate.get_multiple_accounts(&keys),
+    )
+    .await;
+    assert!(cancelled.is_err());
+    assert_eq!(in_flight(&gate), 1);
+    assert!(gate.get_multiple_accounts(&keys).await.is_err());
+    assert_eq!(in_flight(&gate), 0);
+  }
+}
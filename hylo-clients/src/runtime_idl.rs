@@ -0,0 +1,532 @@
+//! Runtime IDL fetch and dynamic instruction building.
+//!
+//! Reads the Anchor IDL a program publishes on chain and builds instructions
+//! from it by name, for methods the static builders in `hylo_idl` do not wrap
+//! yet. Arguments are given as JSON and Borsh encoded against the fetched type
+//! definitions, so a program upgrade does not have to wait on an SDK release.
+//!
+//! ```rust,no_run
+//! use std::collections::HashMap;
+//!
+//! use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+//! use hylo_clients::runtime_idl::RuntimeIdl;
+//! use hylo_idl::exchange;
+//!
+//! # async fn example(rpc: &RpcClient) -> anyhow::Result<()> {
+//! let idl = RuntimeIdl::fetch(rpc, exchange::ID).await?;
+//! let accounts = HashMap::new();
+//! let args = serde_json::json!({});
+//! let ix = idl.instruction("get_stats", &accounts, &args)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_lang::idl::IdlAccount;
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang_idl_spec::{
+  Idl, IdlArrayLen, IdlDefinedFields, IdlInstruction, IdlInstructionAccount,
+  IdlInstructionAccountItem, IdlType, IdlTypeDef, IdlTypeDefTy,
+};
+use anyhow::{anyhow, Context, Result};
+use flate2::read::ZlibDecoder;
+use serde_json::Value;
+
+/// Discriminator, authority, and data length precede the compressed IDL.
+const IDL_HEADER_LEN: usize = 8 + 32 + 4;
+
+/// Anchor IDL fetched from a deployed program.
+#[derive(Debug, Clone)]
+pub struct RuntimeIdl {
+  program_id: Pubkey,
+  idl: Idl,
+}
+
+impl RuntimeIdl {
+  /// Fetches and decompresses the program's IDL account.
+  ///
+  /// # Errors
+  /// - IDL account does not exist
+  /// - Account data is not a valid compressed IDL
+  pub async fn fetch(rpc: &RpcClient, program_id: Pubkey) -> Result<Self> {
+    let address = IdlAccount::address(&program_id);
+    let account = rpc
+      .get_account(&address)
+      .await
+      .with_context(|| format!("No IDL account for {program_id}"))?;
+    Self::from_account_data(program_id, &account.data)
+  }
+
+  /// Decodes raw IDL account data.
+  ///
+  /// # Errors
+  /// - Data is shorter than its declared length
+  /// - Decompression or JSON parsing fails
+  pub fn from_account_data(program_id: Pubkey, data: &[u8]) -> Result<Self> {
+    let len_bytes: [u8; 4] = data
+      .get(IDL_HEADER_LEN - 4..IDL_HEADER_LEN)
+      .and_then(|bytes| bytes.try_into().ok())
+      .ok_or(anyhow!("IDL account header is truncated"))?;
+    let len = usize::try_from(u32::from_le_bytes(len_bytes))?;
+    let compressed = data
+      .get(IDL_HEADER_LEN..IDL_HEADER_LEN + len)
+      .ok_or(anyhow!("IDL account data is truncated"))?;
+    let mut json = String::new();
+    ZlibDecoder::new(compressed).read_to_string(&mut json)?;
+    let idl = serde_json::from_str(&json)?;
+    Ok(RuntimeIdl { program_id, idl })
+  }
+
+  #[must_use]
+  pub fn idl(&self) -> &Idl {
+    &self.idl
+  }
+
+  /// Looks up an instruction by its IDL name.
+  ///
+  /// # Errors
+  /// - No instruction with that name
+  pub fn instruction_def(&self, name: &str) -> Result<&IdlInstruction> {
+    self
+      .idl
+      .instructions
+      .iter()
+      .find(|ix| ix.name == name)
+      .ok_or(anyhow!("Instruction {name} not in IDL"))
+  }
+
+  /// Builds an instruction from the fetched IDL.
+  ///
+  /// # Arguments
+  /// * `name` - Instruction name as written in the IDL (`snake_case`)
+  /// * `accounts` - Account addresses by IDL name; fixed addresses and optional
+  ///   accounts may be omitted
+  /// * `args` - JSON object with one entry per instruction argument
+  ///
+  /// # Errors
+  /// - Unknown instruction
+  /// - Required account or argument missing
+  /// - Argument does not match its IDL type
+  pub fn instruction(
+    &self,
+    name: &str,
+    accounts: &HashMap<String, Pubkey>,
+    args: &Value,
+  ) -> Result<Instruction> {
+    let def = self.instruction_def(name)?;
+    let metas = flatten_accounts(&def.accounts)
+      .into_iter()
+      .map(|account| self.account_meta(account, accounts))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Instruction {
+      program_id: self.program_id,
+      accounts: metas,
+      data: self.instruction_data(name, args)?,
+    })
+  }
+
+  /// Discriminator and Borsh encoded `args` of the instruction `name`.
+  ///
+  /// # Errors
+  /// - Unknown instruction
+  /// - Argument missing or not matching its IDL type
+  pub fn instruction_data(&self, name: &str, args: &Value) -> Result<Vec<u8>> {
+    let def = self.instruction_def(name)?;
+    let mut data = def.discriminator.clone();
+    def.args.iter().try_for_each(|arg| {
+      let value = args
+        .get(&arg.name)
+        .ok_or(anyhow!("Missing argument {}", arg.name))?;
+      self
+        .encode(&arg.ty, value, &mut data)
+        .with_context(|| format!("Invalid argument {}", arg.name))
+    })?;
+    Ok(data)
+  }
+
+  /// Resolves an account from the caller's map, the IDL's fixed address, or
+  /// the program ID placeholder Anchor uses for absent optional accounts.
+  fn account_meta(
+    &self,
+    account: &IdlInstructionAccount,
+    accounts: &HashMap<String, Pubkey>,
+  ) -> Result<AccountMeta> {
+    let pubkey = match (accounts.get(&account.name), &account.address) {
+      (Some(pubkey), _) => Ok(*pubkey),
+      (None, Some(address)) => Ok(Pubkey::from_str(address)?),
+      (None, None) if account.optional => Ok(self.program_id),
+      (None, None) => Err(anyhow!("Missing account {}", account.name)),
+    }?;
+    Ok(if account.writable {
+      AccountMeta::new(pubkey, account.signer)
+    } else {
+      AccountMeta::new_readonly(pubkey, account.signer)
+    })
+  }
+
+  fn type_def(&self, name: &str) -> Result<&IdlTypeDef> {
+    self
+      .idl
+      .types
+      .iter()
+      .find(|def| def.name == name)
+      .ok_or(anyhow!("Type {name} not in IDL"))
+  }
+
+  /// Borsh encodes a JSON value as the given IDL type.
+  fn encode(
+    &self,
+    ty: &IdlType,
+    value: &Value,
+    out: &mut Vec<u8>,
+  ) -> Result<()> {
+    match ty {
+      IdlType::Bool => {
+        out.push(u8::from(value.as_bool().ok_or(anyhow!("Expected bool"))?));
+        Ok(())
+      }
+      IdlType::U8 => push_scalar(value, out, u8::to_le_bytes),
+      IdlType::I8 => push_scalar(value, out, i8::to_le_bytes),
+      IdlType::U16 => push_scalar(value, out, u16::to_le_bytes),
+      IdlType::I16 => push_scalar(value, out, i16::to_le_bytes),
+      IdlType::U32 => push_scalar(value, out, u32::to_le_bytes),
+      IdlType::I32 => push_scalar(value, out, i32::to_le_bytes),
+      IdlType::U64 => push_scalar(value, out, u64::to_le_bytes),
+      IdlType::I64 => push_scalar(value, out, i64::to_le_bytes),
+      IdlType::U128 => push_scalar(value, out, u128::to_le_bytes),
+      IdlType::I128 => push_scalar(value, out, i128::to_le_bytes),
+      IdlType::F32 => push_scalar(value, out, f32::to_le_bytes),
+      IdlType::F64 => push_scalar(value, out, f64::to_le_bytes),
+      IdlType::String => {
+        let s = value.as_str().ok_or(anyhow!("Expected string"))?;
+        push_len(s.len(), out)?;
+        out.extend_from_slice(s.as_bytes());
+        Ok(())
+      }
+      IdlType::Bytes => {
+        let bytes = as_array(value)?
+          .iter()
+          .map(parse_scalar::<u8>)
+          .collect::<Result<Vec<_>>>()?;
+        push_len(bytes.len(), out)?;
+        out.extend_from_slice(&bytes);
+        Ok(())
+      }
+      IdlType::Pubkey => {
+        let key = value.as_str().ok_or(anyhow!("Expected pubkey string"))?;
+        out.extend_from_slice(Pubkey::from_str(key)?.as_ref());
+        Ok(())
+      }
+      IdlType::Option(inner) => {
+        if value.is_null() {
+          out.push(0);
+          Ok(())
+        } else {
+          out.push(1);
+          self.encode(inner, value, out)
+        }
+      }
+      IdlType::Vec(inner) => {
+        let items = as_array(value)?;
+        push_len(items.len(), out)?;
+        items
+          .iter()
+          .try_for_each(|item| self.encode(inner, item, out))
+      }
+      IdlType::Array(inner, IdlArrayLen::Value(len)) => {
+        let items = as_array(value)?;
+        if items.len() == *len {
+          items
+            .iter()
+            .try_for_each(|item| self.encode(inner, item, out))
+        } else {
+          Err(anyhow!("Expected {len} elements, got {}", items.len()))
+        }
+      }
+      IdlType::Defined { name, generics } if generics.is_empty() => {
+        self.encode_defined(self.type_def(name)?, value, out)
+      }
+      other => Err(anyhow!("Unsupported IDL type {other:?}")),
+    }
+  }
+
+  fn encode_defined(
+    &self,
+    def: &IdlTypeDef,
+    value: &Value,
+    out: &mut Vec<u8>,
+  ) -> Result<()> {
+    match &def.ty {
+      IdlTypeDefTy::Struct { fields } => {
+        self.encode_fields(fields.as_ref(), value, out)
+      }
+      IdlTypeDefTy::Type { alias } => self.encode(alias, value, out),
+      IdlTypeDefTy::Enum { variants } => {
+        // Unit variants are plain strings, others `{ "Variant": fields }`.
+        let (variant_name, fields_value) = match value {
+          Value::String(name) => Ok((name.as_str(), &Value::Null)),
+          Value::Object(map) if map.len() == 1 => map
+            .iter()
+            .next()
+            .map(|(name, fields)| (name.as_str(), fields))
+            .ok_or(anyhow!("Empty enum value")),
+          _ => Err(anyhow!("Expected enum variant for {}", def.name)),
+        }?;
+        let (index, variant) = variants
+          .iter()
+          .enumerate()
+          .find(|(_, variant)| variant.name == variant_name)
+          .ok_or(anyhow!("{} has no variant {variant_name}", def.name))?;
+        out.push(u8::try_from(index)?);
+        self.encode_fields(variant.fields.as_ref(), fields_value, out)
+      }
+    }
+  }
+
+  fn encode_fields(
+    &self,
+    fields: Option<&IdlDefinedFields>,
+    value: &Value,
+    out: &mut Vec<u8>,
+  ) -> Result<()> {
+    match fields {
+      None => Ok(()),
+      Some(IdlDefinedFields::Named(fields)) => {
+        fields.iter().try_for_each(|field| {
+          let field_value = value
+            .get(&field.name)
+            .ok_or(anyhow!("Missing field {}", field.name))?;
+          self.encode(&field.ty, field_value, out)
+        })
+      }
+      Some(IdlDefinedFields::Tuple(types)) => {
+        let items = as_array(value)?;
+        if items.len() == types.len() {
+          types
+            .iter()
+            .zip(items)
+            .try_for_each(|(ty, item)| self.encode(ty, item, out))
+        } else {
+          Err(anyhow!("Expected {} tuple fields", types.len()))
+        }
+      }
+    }
+  }
+}
+
+/// Flattens composite account groups into instruction order.
+fn flatten_accounts(
+  items: &[IdlInstructionAccountItem],
+) -> Vec<&IdlInstructionAccount> {
+  items
+    .iter()
+    .flat_map(|item| match item {
+      IdlInstructionAccountItem::Single(account) => vec![account],
+      IdlInstructionAccountItem::Composite(group) => {
+        flatten_accounts(&group.accounts)
+      }
+    })
+    .collect()
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>> {
+  value.as_array().ok_or(anyhow!("Expected array"))
+}
+
+/// Reads a number from JSON, accepting strings for values beyond `f64`
+/// precision such as `u64` amounts and 128-bit integers.
+fn parse_scalar<T: FromStr>(value: &Value) -> Result<T>
+where
+  T::Err: std::error::Error + Send + Sync + 'static,
+{
+  match value {
+    Value::Number(n) => Ok(n.to_string().parse()?),
+    Value::String(s) => Ok(s.parse()?),
+    _ => Err(anyhow!("Expected number")),
+  }
+}
+
+fn push_scalar<T: FromStr, const N: usize>(
+  value: &Value,
+  out: &mut Vec<u8>,
+  to_le_bytes: fn(T) -> [u8; N],
+) -> Result<()>
+where
+  T::Err: std::error::Error + Send + Sync + 'static,
+{
+  out.extend_from_slice(&to_le_bytes(parse_scalar(value)?));
+  Ok(())
+}
+
+fn push_len(len: usize, out: &mut Vec<u8>) -> Result<()> {
+  out.extend_from_slice(&u32::try_from(len)?.to_le_bytes());
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::{AnchorDeserialize, InstructionData};
+  use hylo_idl::{exchange, stability_pool};
+  use serde_json::{json, Map};
+
+  use super::*;
+
+  /// Re-encodes instruction data through a static `hylo_idl` args type.
+  type Reencode = fn(&[u8]) -> Result<Vec<u8>>;
+
+  /// Decodes `data` as `T`, failing on leftover bytes, and encodes it back
+  /// the way the static builders do.
+  fn reencode<T: AnchorDeserialize + InstructionData>(
+    data: &[u8],
+  ) -> Result<Vec<u8>> {
+    let args = data
+      .get(T::DISCRIMINATOR.len()..)
+      .ok_or(anyhow!("Data shorter than discriminator"))?;
+    Ok(T::try_from_slice(args)?.data())
+  }
+
+  fn exchange_args(name: &str) -> Option<Reencode> {
+    use exchange::client::args::*;
+    match name {
+      "get_stats" => Some(reencode::<GetStats>),
+      "harvest_yield" => Some(reencode::<HarvestYield>),
+      "initialize_lst_registry" => Some(reencode::<InitializeLstRegistry>),
+      "initialize_lst_registry_calculators" => {
+        Some(reencode::<InitializeLstRegistryCalculators>)
+      }
+      "initialize_mints" => Some(reencode::<InitializeMints>),
+      "initialize_protocol" => Some(reencode::<InitializeProtocol>),
+      "mint_levercoin" => Some(reencode::<MintLevercoin>),
+      "mint_stablecoin" => Some(reencode::<MintStablecoin>),
+      "redeem_levercoin" => Some(reencode::<RedeemLevercoin>),
+      "redeem_stablecoin" => Some(reencode::<RedeemStablecoin>),
+      "register_lst" => Some(reencode::<RegisterLst>),
+      "swap_lever_to_stable" => Some(reencode::<SwapLeverToStable>),
+      "swap_lst" => Some(reencode::<SwapLst>),
+      "swap_stable_to_lever" => Some(reencode::<SwapStableToLever>),
+      "update_admin" => Some(reencode::<UpdateAdmin>),
+      "update_levercoin_fees" => Some(reencode::<UpdateLevercoinFees>),
+      "update_lst_prices" => Some(reencode::<UpdateLstPrices>),
+      "update_lst_swap_fee" => Some(reencode::<UpdateLstSwapFee>),
+      "update_oracle_conf_tolerance" => {
+        Some(reencode::<UpdateOracleConfTolerance>)
+      }
+      "update_oracle_interval" => Some(reencode::<UpdateOracleInterval>),
+      "update_sol_usd_oracle" => Some(reencode::<UpdateSolUsdOracle>),
+      "update_stability_pool" => Some(reencode::<UpdateStabilityPool>),
+      "update_stability_thresholds" => {
+        Some(reencode::<UpdateStabilityThresholds>)
+      }
+      "update_stablecoin_fees" => Some(reencode::<UpdateStablecoinFees>),
+      "update_treasury" => Some(reencode::<UpdateTreasury>),
+      "update_yield_harvest_config" => {
+        Some(reencode::<UpdateYieldHarvestConfig>)
+      }
+      "withdraw_fees" => Some(reencode::<WithdrawFees>),
+      _ => None,
+    }
+  }
+
+  fn stability_pool_args(name: &str) -> Option<Reencode> {
+    use stability_pool::client::args::*;
+    match name {
+      "get_stats" => Some(reencode::<GetStats>),
+      "initialize_lp_token_mint" => Some(reencode::<InitializeLpTokenMint>),
+      "initialize_stability_pool" => Some(reencode::<InitializeStabilityPool>),
+      "rebalance_lever_to_stable" => Some(reencode::<RebalanceLeverToStable>),
+      "rebalance_stable_to_lever" => Some(reencode::<RebalanceStableToLever>),
+      "update_admin" => Some(reencode::<UpdateAdmin>),
+      "update_withdrawal_fee" => Some(reencode::<UpdateWithdrawalFee>),
+      "user_deposit" => Some(reencode::<UserDeposit>),
+      "user_withdraw" => Some(reencode::<UserWithdraw>),
+      _ => None,
+    }
+  }
+
+  /// JSON value of type `ty`, numbering scalars from `seed` so that a field
+  /// encoded out of place changes the bytes.
+  fn sample(idl: &RuntimeIdl, ty: &IdlType, seed: &mut u8) -> Result<Value> {
+    *seed = seed.wrapping_add(1);
+    match ty {
+      IdlType::Bool => Ok(json!(seed.is_multiple_of(2))),
+      IdlType::U8
+      | IdlType::U16
+      | IdlType::U32
+      | IdlType::U64
+      | IdlType::U128 => Ok(json!(*seed)),
+      IdlType::I8
+      | IdlType::I16
+      | IdlType::I32
+      | IdlType::I64
+      | IdlType::I128 => Ok(json!(-i16::from(*seed))),
+      IdlType::Pubkey => {
+        Ok(json!(Pubkey::new_from_array([*seed; 32]).to_string()))
+      }
+      IdlType::Option(inner) => sample(idl, inner, seed),
+      IdlType::Vec(inner) => Ok(json!([sample(idl, inner, seed)?])),
+      IdlType::Defined { name, .. } => match &idl.type_def(name)?.ty {
+        IdlTypeDefTy::Struct {
+          fields: Some(IdlDefinedFields::Named(fields)),
+        } => fields
+          .iter()
+          .map(|field| {
+            sample(idl, &field.ty, seed)
+              .map(|value| (field.name.clone(), value))
+          })
+          .collect::<Result<Map<_, _>>>()
+          .map(Value::Object),
+        other => Err(anyhow!("No sample for {name}: {other:?}")),
+      },
+      other => Err(anyhow!("No sample for {other:?}")),
+    }
+  }
+
+  /// Encodes sample arguments of every instruction with the IDL and checks
+  /// the static args type decodes them exactly and encodes the same bytes.
+  fn assert_parity(
+    program_id: Pubkey,
+    idl_json: &str,
+    args_type: fn(&str) -> Option<Reencode>,
+  ) -> Result<()> {
+    let idl = RuntimeIdl {
+      program_id,
+      idl: serde_json::from_str(idl_json)?,
+    };
+    idl.idl.instructions.iter().try_for_each(|ix| {
+      let mut seed = 0;
+      let args = ix
+        .args
+        .iter()
+        .map(|arg| {
+          sample(&idl, &arg.ty, &mut seed)
+            .map(|value| (arg.name.clone(), value))
+        })
+        .collect::<Result<Map<_, _>>>()?;
+      let data = idl.instruction_data(&ix.name, &Value::Object(args))?;
+      let reencode =
+        args_type(&ix.name).ok_or(anyhow!("No args type for {}", ix.name))?;
+      let expected = reencode(&data).with_context(|| ix.name.clone())?;
+      assert_eq!(data, expected, "{}", ix.name);
+      Ok(())
+    })
+  }
+
+  #[test]
+  fn exchange_encoding_matches_static_args() -> Result<()> {
+    assert_parity(exchange::ID, exchange::IDL_JSON, exchange_args)
+  }
+
+  #[test]
+  fn stability_pool_encoding_matches_static_args() -> Result<()> {
+    assert_parity(
+      stability_pool::ID,
+      stability_pool::IDL_JSON,
+      stability_pool_args,
+    )
+  }
+}
@@ -0,0 +1,348 @@
+//! Independent reconciliation of the exchange's total SOL cache.
+//!
+//! `total_sol_cache` is maintained incrementally from registered LST prices.
+//! [`SolReconciliation`] recomputes it from scratch, pricing every LST vault
+//! balance directly from its stake pool, and breaks the difference down per
+//! LST. Useful after epoch transitions, when registered prices move, and for
+//! incident forensics.
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_core::idl::pda;
+use hylo_core::lst_mix::LstMix;
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::stake_pool::StakePoolSnapshot;
+use hylo_core::total_sol_cache::TotalSolCache;
+use itertools::Itertools;
+
+use crate::prefetch::get_multiple_accounts_chunked;
+use crate::util::{
+  deserialize_anchor_account, deserialize_lookup_table, lst_registry_entries,
+  LST_REGISTRY_LOOKUP_TABLE,
+};
+
+/// Recomputed SOL backing of one LST vault.
+#[derive(Debug, Clone, Copy)]
+pub struct LstSolDelta {
+  pub lst_mint: Pubkey,
+
+  /// Collateral held in the LST vault
+  pub vault_amount: UFix64<N9>,
+
+  /// Price registered in the LST header, which feeds the cache
+  pub registered_price: LstSolPrice,
+
+  /// Price derived from stake pool state
+  pub stake_pool_price: UFix64<N9>,
+
+  /// Epoch in which the stake pool was last updated
+  pub stake_pool_epoch: u64,
+
+  /// Vault balance priced at the stake pool price
+  pub recomputed_sol: UFix64<N9>,
+
+  /// Recomputed SOL minus the vault balance at the registered price
+  pub delta: IFix64<N9>,
+}
+
+impl LstSolDelta {
+  /// Prices `vault_amount` at both the registered and stake pool prices.
+  ///
+  /// # Errors
+  /// - Stake pool has no token supply
+  /// - Arithmetic overflow
+  pub fn new(
+    header: &LstHeader,
+    vault_amount: UFix64<N9>,
+    stake_pool: &StakePoolSnapshot,
+  ) -> Result<LstSolDelta> {
+    let registered_price: LstSolPrice = header.price_sol.into();
+    let stake_pool_price = stake_pool.lst_sol_price()?;
+    let registered_sol = sol_value(
+      vault_amount,
+      registered_price.price.try_into()?,
+      header.mint,
+    )?;
+    let recomputed_sol =
+      sol_value(vault_amount, stake_pool_price, header.mint)?;
+    Ok(LstSolDelta {
+      lst_mint: header.mint,
+      vault_amount,
+      registered_price,
+      stake_pool_price,
+      stake_pool_epoch: stake_pool.last_update_epoch,
+      recomputed_sol,
+      delta: signed_delta(recomputed_sol, registered_sol)?,
+    })
+  }
+}
+
+/// Total SOL cache compared against stake pool priced vault balances.
+#[derive(Debug, Clone)]
+pub struct SolReconciliation {
+  /// Epoch at which the reconciliation was taken
+  pub epoch: u64,
+
+  /// Epoch of the last cache update
+  pub cache_epoch: u64,
+
+  /// SOL recorded in `total_sol_cache`
+  pub cached_sol: UFix64<N9>,
+
+  /// Sum of [`LstSolDelta::recomputed_sol`] across registered LSTs
+  pub recomputed_sol: UFix64<N9>,
+
+  /// Recomputed SOL minus cached SOL
+  pub delta: IFix64<N9>,
+
+  /// Breakdown per registered LST
+  pub lsts: Vec<LstSolDelta>,
+}
+
+impl SolReconciliation {
+  /// Fetches the total SOL cache, LST registry, vaults and stake pools.
+  ///
+  /// # Errors
+  /// - Failed to fetch accounts or the current epoch
+  /// - Registry, header, vault or stake pool data is malformed
+  /// - Arithmetic overflow
+  pub async fn fetch(rpc_client: &RpcClient) -> Result<SolReconciliation> {
+    let table_account =
+      rpc_client.get_account(&LST_REGISTRY_LOOKUP_TABLE).await?;
+    let table =
+      deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &table_account)?;
+    let registry = lst_registry_entries(&table)?;
+    let keys = std::iter::once(*pda::HYLO)
+      .chain(registry.iter().flat_map(|lst| [lst.header, lst.vault]))
+      .collect_vec();
+    let accounts = fetch_accounts(rpc_client, &keys).await?;
+    let epoch = rpc_client.get_epoch_info().await?.epoch;
+    match accounts.as_slice() {
+      [hylo, lsts @ ..] => {
+        let hylo: Hylo = deserialize_anchor_account(hylo)?;
+        let vaults = lsts
+          .iter()
+          .tuples()
+          .map(|(header, vault)| {
+            let header: LstHeader = deserialize_anchor_account(header)?;
+            let vault = TokenAccount::unpack(&vault.data)?;
+            Ok((header, UFix64::new(vault.amount)))
+          })
+          .collect::<Result<Vec<_>>>()?;
+        let pool_keys = vaults
+          .iter()
+          .map(|(header, _)| header.pool_state)
+          .collect_vec();
+        let lsts = fetch_accounts(rpc_client, &pool_keys)
+          .await?
+          .iter()
+          .zip(&vaults)
+          .map(|(pool, (header, vault_amount))| {
+            let pool = StakePoolSnapshot::try_from_spl_bytes(&pool.data)
+              .with_context(|| {
+                format!("Invalid stake pool for {}", header.mint)
+              })?;
+            LstSolDelta::new(header, *vault_amount, &pool)
+          })
+          .collect::<Result<Vec<_>>>()?;
+        SolReconciliation::new(hylo.total_sol_cache.into(), epoch, lsts)
+      }
+      [] => Err(anyhow!("Hylo account missing")),
+    }
+  }
+
+  /// Sums per-LST breakdowns and compares them with `cache`.
+  ///
+  /// # Errors
+  /// - Arithmetic overflow
+  pub fn new(
+    cache: TotalSolCache,
+    epoch: u64,
+    lsts: Vec<LstSolDelta>,
+  ) -> Result<SolReconciliation> {
+    let cached_sol: UFix64<N9> = cache.total_sol.try_into()?;
+    let recomputed_sol =
+      lsts.iter().try_fold(UFix64::<N9>::zero(), |total, lst| {
+        total
+          .checked_add(&lst.recomputed_sol)
+          .ok_or(anyhow!("Recomputed SOL total overflow"))
+      })?;
+    Ok(SolReconciliation {
+      epoch,
+      cache_epoch: cache.current_update_epoch,
+      cached_sol,
+      recomputed_sol,
+      delta: signed_delta(recomputed_sol, cached_sol)?,
+      lsts,
+    })
+  }
+
+  /// Whether the cache was last updated in [`Self::epoch`]. A stale cache is
+  /// expected to differ until the next LST price crank.
+  #[must_use]
+  pub fn is_cache_current(&self) -> bool {
+    self.cache_epoch == self.epoch
+  }
+
+  /// LST mix of the recomputed vault balances against `targets`, for
+  /// routing mints and redemptions by LST.
+  ///
+  /// # Errors
+  /// - Targets exceed 100% in total
+  /// - Arithmetic overflow
+  pub fn lst_mix(&self, targets: &[(Pubkey, UFix64<N4>)]) -> Result<LstMix> {
+    let vaults = self
+      .lsts
+      .iter()
+      .map(|lst| (lst.lst_mint, lst.recomputed_sol))
+      .collect_vec();
+    Ok(LstMix::new(&vaults, targets)?)
+  }
+}
+
+/// Fetches `keys`, failing on any missing account.
+async fn fetch_accounts(
+  rpc_client: &RpcClient,
+  keys: &[Pubkey],
+) -> Result<Vec<Account>> {
+  get_multiple_accounts_chunked(rpc_client, keys)
+    .await?
+    .into_iter()
+    .zip(keys)
+    .map(|(account, key)| account.ok_or(anyhow!("Account {key} missing")))
+    .collect()
+}
+
+/// SOL value of `amount` LST at `price`.
+fn sol_value(
+  amount: UFix64<N9>,
+  price: UFix64<N9>,
+  lst_mint: Pubkey,
+) -> Result<UFix64<N9>> {
+  price
+    .mul_div_floor(amount, UFix64::one())
+    .ok_or(anyhow!("SOL value overflow for {lst_mint}"))
+}
+
+/// Signed difference `value - reference`.
+fn signed_delta(
+  value: UFix64<N9>,
+  reference: UFix64<N9>,
+) -> Result<IFix64<N9>> {
+  let value = i64::try_from(value.bits)?;
+  let reference = i64::try_from(reference.bits)?;
+  value
+    .checked_sub(reference)
+    .map(IFix64::new)
+    .ok_or(anyhow!("SOL delta overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::idl::exchange::types::{self, LstStakePoolProgram};
+
+  use super::*;
+
+  const EPOCH: u64 = 700;
+
+  fn sol(whole: u64) -> UFix64<N9> {
+    UFix64::new(whole * 1_000_000_000)
+  }
+
+  fn header(price: u64, epoch: u64) -> LstHeader {
+    let price_sol = types::LstSolPrice {
+      price: UFix64::<N9>::new(price).into(),
+      epoch,
+    };
+    LstHeader {
+      mint: Pubkey::new_unique(),
+      vault: Pubkey::new_unique(),
+      pool_state: Pubkey::new_unique(),
+      stake_program: LstStakePoolProgram::SanctumSplMulti,
+      prev_price_sol: price_sol.clone(),
+      price_sol,
+      last_yield_harvest_epoch: epoch,
+      _reserved: [0; 64],
+    }
+  }
+
+  fn pool(total_lamports: u64, pool_token_supply: u64) -> StakePoolSnapshot {
+    StakePoolSnapshot {
+      total_lamports,
+      pool_token_supply,
+      last_update_epoch: EPOCH,
+    }
+  }
+
+  fn lsts() -> Result<Vec<LstSolDelta>> {
+    Ok(vec![
+      LstSolDelta::new(
+        &header(1_100_000_000, EPOCH),
+        sol(1_000),
+        &pool(112, 100),
+      )?,
+      LstSolDelta::new(
+        &header(1_200_000_000, EPOCH - 1),
+        sol(500),
+        &pool(125, 100),
+      )?,
+    ])
+  }
+
+  #[test]
+  fn prices_vaults_at_both_prices() -> Result<()> {
+    let deltas = lsts()?
+      .iter()
+      .map(|lst| (lst.stake_pool_price, lst.recomputed_sol, lst.delta))
+      .collect_vec();
+    assert_eq!(
+      deltas,
+      [
+        (
+          UFix64::new(1_120_000_000),
+          sol(1_120),
+          IFix64::new(20_000_000_000)
+        ),
+        (
+          UFix64::new(1_250_000_000),
+          sol(625),
+          IFix64::new(25_000_000_000)
+        ),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn reconciles_against_cache() -> Result<()> {
+    let cache = TotalSolCache {
+      current_update_epoch: EPOCH,
+      total_sol: sol(1_700).into(),
+    };
+    let reconciliation = SolReconciliation::new(cache, EPOCH, lsts()?)?;
+    assert_eq!(reconciliation.cached_sol, sol(1_700));
+    assert_eq!(reconciliation.recomputed_sol, sol(1_745));
+    assert_eq!(reconciliation.delta, IFix64::new(45_000_000_000));
+    assert!(reconciliation.is_cache_current());
+    Ok(())
+  }
+
+  #[test]
+  fn flags_lagging_cache() -> Result<()> {
+    let cache = TotalSolCache {
+      current_update_epoch: EPOCH - 1,
+      total_sol: sol(1_760).into(),
+    };
+    let reconciliation = SolReconciliation::new(cache, EPOCH, lsts()?)?;
+    assert_eq!(reconciliation.cache_epoch, EPOCH - 1);
+    assert_eq!(reconciliation.delta, IFix64::new(-15_000_000_000));
+    assert!(!reconciliation.is_cache_current());
+    Ok(())
+  }
+}
@@ -1,28 +1,97 @@
 use std::sync::Arc;
 
+use anchor_client::solana_sdk::clock::Clock;
 use anchor_client::solana_sdk::signature::{Keypair, Signature};
+use anchor_client::solana_sdk::sysvar;
 use anchor_client::Program;
 use anchor_lang::prelude::Pubkey;
-use anyhow::Result;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::idl::exchange::accounts::Hylo;
+use hylo_core::idl::pda;
+use hylo_core::pyth::{OracleConfig, SOL_USD_PYTH_FEED};
+use hylo_core::stability_mode::StabilityController;
+use hylo_idl::exchange::events::{
+  RedeemLevercoinEventV2, RedeemStablecoinEventV2,
+};
 use hylo_idl::stability_pool::client::args;
 use hylo_idl::stability_pool::events::{
   StabilityPoolStats, UserWithdrawEventV1,
 };
 use hylo_idl::stability_pool::instruction_builders;
 use hylo_idl::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 use crate::exchange_client::ExchangeClient;
 use crate::instructions::StabilityPoolInstructionBuilder as StabilityPoolIB;
+use crate::prefetch::{protocol_pubkeys, AccountPrefetch};
 use crate::program_client::{ProgramClient, VersionedTransactionData};
+use crate::rpc_gate::RpcGate;
 use crate::syntax_helpers::InstructionBuilderExt;
 use crate::transaction::{
   BuildTransactionData, RedeemArgs, StabilityPoolArgs, TransactionSyntax,
 };
 use crate::util::{
-  user_ata_instruction, EXCHANGE_LOOKUP_TABLE, LST, LST_REGISTRY_LOOKUP_TABLE,
-  REFERENCE_WALLET, STABILITY_POOL_LOOKUP_TABLE,
+  parse_event, parse_events, simulation_config, user_ata_instruction,
+  EXCHANGE_LOOKUP_TABLE, LST, LST_REGISTRY_LOOKUP_TABLE, REFERENCE_WALLET,
+  STABILITY_POOL_LOOKUP_TABLE,
 };
 
+/// Simulated legs of an sHYUSD exit to an LST.
+///
+/// The withdrawal pays out hyUSD and, once the pool has rebalanced into xSOL
+/// in mode 1 or 2, xSOL as well; each is then redeemed for the LST. Redeem
+/// legs are absent when the withdrawal pays none of that token.
+#[derive(Debug, Clone)]
+pub struct ShyusdExitDryRun {
+  pub withdraw: UserWithdrawEventV1,
+  pub stablecoin_redeem: Option<RedeemStablecoinEventV2>,
+  pub levercoin_redeem: Option<RedeemLevercoinEventV2>,
+  pub compute_units: Option<u64>,
+}
+
+impl ShyusdExitDryRun {
+  /// LST received across both redeem legs.
+  ///
+  /// # Errors
+  /// - Event amounts have unexpected exponents
+  /// - Overflow
+  pub fn lst_out(&self) -> Result<UFix64<N9>> {
+    let from_hyusd = self
+      .stablecoin_redeem
+      .as_ref()
+      .map_or(Ok(UFix64::zero()), |e| e.collateral_withdrawn.try_into())?;
+    let from_xsol = self
+      .levercoin_redeem
+      .as_ref()
+      .map_or(Ok(UFix64::zero()), |e| e.collateral_withdrawn.try_into())?;
+    from_hyusd
+      .checked_add(&from_xsol)
+      .ok_or(anyhow!("LST output overflow"))
+  }
+
+  /// LST fees paid across both redeem legs.
+  ///
+  /// # Errors
+  /// - Event amounts have unexpected exponents
+  /// - Overflow
+  pub fn lst_fees(&self) -> Result<UFix64<N9>> {
+    let from_hyusd = self
+      .stablecoin_redeem
+      .as_ref()
+      .map_or(Ok(UFix64::zero()), |e| e.fees_deposited.try_into())?;
+    let from_xsol = self
+      .levercoin_redeem
+      .as_ref()
+      .map_or(Ok(UFix64::zero()), |e| e.fees_deposited.try_into())?;
+    from_hyusd
+      .checked_add(&from_xsol)
+      .ok_or(anyhow!("LST fee overflow"))
+  }
+}
+
 /// Client for interacting with the Hylo Stability Pool program.
 ///
 /// Provides functionality for depositing and withdrawing sHYUSD from the
@@ -61,6 +130,7 @@ use crate::util::{
 pub struct StabilityPoolClient {
   program: Program<Arc<Keypair>>,
   keypair: Arc<Keypair>,
+  gate: Option<Arc<RpcGate>>,
 }
 
 impl ProgramClient for StabilityPoolClient {
@@ -70,7 +140,11 @@ impl ProgramClient for StabilityPoolClient {
     program: Program<Arc<Keypair>>,
     keypair: Arc<Keypair>,
   ) -> StabilityPoolClient {
-    StabilityPoolClient { program, keypair }
+    StabilityPoolClient {
+      program,
+      keypair,
+      gate: None,
+    }
   }
 
   fn program(&self) -> &Program<Arc<Keypair>> {
@@ -80,9 +154,23 @@ impl ProgramClient for StabilityPoolClient {
   fn keypair(&self) -> Arc<Keypair> {
     self.keypair.clone()
   }
+
+  fn gate(&self) -> Option<&RpcGate> {
+    self.gate.as_deref()
+  }
 }
 
 impl StabilityPoolClient {
+  /// Routes account fetches through `gate`, sharing its rate limit and
+  /// in-flight requests with every other client on the same gate.
+  #[must_use]
+  pub fn with_gate(self, gate: Arc<RpcGate>) -> Self {
+    StabilityPoolClient {
+      gate: Some(gate),
+      ..self
+    }
+  }
+
   /// Rebalances stability pool by swapping stablecoin to levercoin.
   ///
   /// # Errors
@@ -138,6 +226,82 @@ impl StabilityPoolClient {
     self.simulate_transaction_return(tx).await
   }
 
+  /// Current sHYUSD NAV in USD, computed from the exchange, oracle, mint
+  /// and pool accounts fetched in one request.
+  ///
+  /// # Errors
+  /// - Any account is missing or fails to deserialize
+  /// - Total SOL cache was not updated this epoch
+  /// - Oracle price is stale or too uncertain
+  /// - NAV arithmetic
+  pub async fn shyusd_nav(&self) -> Result<UFix64<N6>> {
+    let rpc = self.program.rpc();
+    let prefetch = AccountPrefetch::fetch(&rpc, &protocol_pubkeys()).await?;
+    let hylo: Hylo = prefetch.anchor(&pda::HYLO)?;
+    let sol_usd: PriceUpdateV2 = prefetch.anchor(&SOL_USD_PYTH_FEED)?;
+    let clock: Clock =
+      bincode::deserialize(&prefetch.account(&sysvar::clock::ID)?.data)?;
+    let balance = |pool: &Pubkey| {
+      prefetch
+        .spl::<TokenAccount>(pool)
+        .map(|pool| UFix64::<N6>::new(pool.amount))
+    };
+    let oracle_config = OracleConfig::new(
+      hylo.oracle_interval_secs,
+      hylo.oracle_conf_tolerance.try_into()?,
+    );
+    let stability_controller = StabilityController::new(
+      hylo.stability_threshold_1.try_into()?,
+      hylo.stability_threshold_2.try_into()?,
+    )?;
+    let context = ExchangeContext::load(
+      clock,
+      &hylo.total_sol_cache.into(),
+      stability_controller,
+      oracle_config,
+      hylo.stablecoin_fees.into(),
+      hylo.levercoin_fees.into(),
+      &sol_usd,
+      &prefetch.spl::<Mint>(&HYUSD::MINT)?,
+      Some(&prefetch.spl::<Mint>(&XSOL::MINT)?),
+    )?;
+    Ok(context.shyusd_nav(
+      balance(&pda::HYUSD_POOL)?,
+      balance(&pda::XSOL_POOL)?,
+      UFix64::new(prefetch.spl::<Mint>(&SHYUSD::MINT)?.supply),
+    )?)
+  }
+
+  /// Simulates the full sHYUSD to `OUT` exit for `args.user` as one
+  /// transaction against current accounts, returning each leg's event.
+  ///
+  /// # Errors
+  /// - Failed to build the exit transaction
+  /// - Simulation fails
+  /// - Withdrawal event missing, or any event fails to deserialize
+  pub async fn dry_run_exit<OUT: LST>(
+    &self,
+    exchange: ExchangeClient,
+    args: StabilityPoolArgs,
+  ) -> Result<ShyusdExitDryRun> {
+    let user = args.user;
+    let vtd = self
+      .build_transaction_data::<SHYUSD, OUT>((exchange, args))
+      .await?;
+    let tx = self.build_simulation_transaction(&user, &vtd).await?;
+    let result = self
+      .program
+      .rpc()
+      .simulate_transaction_with_config(&tx, simulation_config())
+      .await?;
+    Ok(ShyusdExitDryRun {
+      withdraw: parse_event(&result)?,
+      stablecoin_redeem: parse_events(&result)?.into_iter().next(),
+      levercoin_redeem: parse_events(&result)?.into_iter().next(),
+      compute_units: result.value.units_consumed,
+    })
+  }
+
   /// Initializes the stability pool.
   ///
   /// # Errors
@@ -0,0 +1,344 @@
+//! Field-level diffs of Hylo state around a transaction.
+//!
+//! [`StateSnapshot`] reads the accounts a trade touches in one
+//! `getMultipleAccounts` call and flattens them into named fields: the total
+//! SOL and yield harvest caches, LST prices, token supplies, stability pool
+//! balances, and collateral and fee vaults. LSTs are taken from the registry
+//! lookup table, so newly registered ones are included; those without a known
+//! symbol are named by their mint. [`StateSnapshot::diff`] lists the fields
+//! that changed between two snapshots, and [`capture`] takes both
+//! around a transaction:
+//!
+//! ```txt
+//! slot 301443210 -> 301443214
+//! hyUSD.supply: 1204.500000 -> 1304.500000
+//! jitoSOL.vault: 10.000000000 -> 10.793650793
+//! jitoSOL.fee_vault: 0.250000000 -> 0.250793650
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::future::Future;
+
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcAccountInfoConfig;
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::amount_format::format_amount_plain;
+use hylo_core::idl::pda;
+use hylo_core::idl::tokens::{
+  TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL,
+};
+use hylo_idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_idl::exchange::types::UFixValue64 as IdlValue;
+
+use crate::util::{
+  deserialize_lookup_table, lst_registry_entries, LstRegistryEntry,
+  LST_REGISTRY_LOOKUP_TABLE,
+};
+
+/// Renders `bits` base units of a token with `Exp` decimals.
+type Format = fn(u64) -> String;
+
+fn amount<Exp: Integer>(bits: u64) -> String {
+  format_amount_plain(UFix64::<Exp>::new(bits))
+}
+
+/// Renders a stored fixed point value with its exponent applied.
+fn fixed(value: &IdlValue) -> String {
+  let digits = value.bits.to_string();
+  match usize::try_from(-i32::from(value.exp)) {
+    Ok(0) => digits,
+    Err(_) => format!("{digits}e{}", value.exp),
+    Ok(decimals) => {
+      let padded = format!("{digits:0>width$}", width = decimals + 1);
+      let (int, frac) = padded.split_at(padded.len() - decimals);
+      format!("{int}.{frac}")
+    }
+  }
+}
+
+/// How to read the fields of one snapshotted account.
+#[derive(Clone)]
+enum Source {
+  Hylo,
+  Header(String),
+  Mint(&'static str, Format),
+  Token(String, Format),
+}
+
+/// Display name of a registered LST: its symbol where known, else its mint.
+fn lst_label(mint: Pubkey) -> String {
+  [
+    (JITOSOL::MINT, JITOSOL::SYMBOL),
+    (HYLOSOL::MINT, HYLOSOL::SYMBOL),
+  ]
+  .into_iter()
+  .find(|(known, _)| *known == mint)
+  .map_or_else(|| mint.to_string(), |(_, symbol)| symbol.to_string())
+}
+
+fn sources(registry: &[LstRegistryEntry]) -> Vec<(Pubkey, Source)> {
+  let lsts = registry.iter().flat_map(|lst| {
+    let label = lst_label(lst.mint);
+    [
+      (lst.header, Source::Header(label.clone())),
+      (
+        lst.vault,
+        Source::Token(format!("{label}.vault"), amount::<N9>),
+      ),
+      (
+        pda::fee_vault(lst.mint),
+        Source::Token(format!("{label}.fee_vault"), amount::<N9>),
+      ),
+    ]
+  });
+  let protocol = [
+    (*pda::HYLO, Source::Hylo),
+    (HYUSD::MINT, Source::Mint(HYUSD::SYMBOL, amount::<N6>)),
+    (XSOL::MINT, Source::Mint(XSOL::SYMBOL, amount::<N6>)),
+    (SHYUSD::MINT, Source::Mint(SHYUSD::SYMBOL, amount::<N6>)),
+    (
+      *pda::HYUSD_POOL,
+      Source::Token("pool.hyUSD".into(), amount::<N6>),
+    ),
+    (
+      *pda::XSOL_POOL,
+      Source::Token("pool.xSOL".into(), amount::<N6>),
+    ),
+    (
+      pda::fee_vault(HYUSD::MINT),
+      Source::Token(format!("{}.fee_vault", HYUSD::SYMBOL), amount::<N6>),
+    ),
+    (
+      pda::fee_vault(XSOL::MINT),
+      Source::Token(format!("{}.fee_vault", XSOL::SYMBOL), amount::<N6>),
+    ),
+  ];
+  protocol.into_iter().chain(lsts).collect()
+}
+
+/// Named fields read from one existing account.
+fn fields(source: &Source, account: &Account) -> Result<Vec<(String, String)>> {
+  match source {
+    Source::Hylo => {
+      let hylo = Hylo::try_deserialize(&mut account.data.as_slice())
+        .context("Invalid Hylo account")?;
+      let sol = &hylo.total_sol_cache;
+      let harvest = &hylo.yield_harvest_cache;
+      Ok(vec![
+        (
+          "total_sol_cache.epoch".into(),
+          sol.current_update_epoch.to_string(),
+        ),
+        ("total_sol_cache.total_sol".into(), fixed(&sol.total_sol)),
+        (
+          "yield_harvest_cache.epoch".into(),
+          harvest.epoch.to_string(),
+        ),
+        (
+          "yield_harvest_cache.stability_pool_cap".into(),
+          fixed(&harvest.stability_pool_cap),
+        ),
+        (
+          "yield_harvest_cache.stablecoin_yield_to_pool".into(),
+          fixed(&harvest.stablecoin_yield_to_pool),
+        ),
+      ])
+    }
+    Source::Header(symbol) => {
+      let header = LstHeader::try_deserialize(&mut account.data.as_slice())
+        .with_context(|| format!("Invalid LstHeader for {symbol}"))?;
+      Ok(vec![
+        (
+          format!("{symbol}.price_sol"),
+          fixed(&header.price_sol.price),
+        ),
+        (
+          format!("{symbol}.price_epoch"),
+          header.price_sol.epoch.to_string(),
+        ),
+        (
+          format!("{symbol}.last_yield_harvest_epoch"),
+          header.last_yield_harvest_epoch.to_string(),
+        ),
+      ])
+    }
+    Source::Mint(symbol, format) => {
+      let mint = Mint::unpack(&account.data)
+        .with_context(|| format!("Invalid {symbol} mint"))?;
+      Ok(vec![(format!("{symbol}.supply"), format(mint.supply))])
+    }
+    Source::Token(name, format) => {
+      let token = TokenAccount::unpack(&account.data)
+        .with_context(|| format!("Invalid token account for {name}"))?;
+      Ok(vec![(name.clone(), format(token.amount))])
+    }
+  }
+}
+
+/// Hylo state fields by name, as read at a slot.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StateSnapshot {
+  pub slot: u64,
+  pub fields: BTreeMap<String, String>,
+}
+
+/// One field that differs between two snapshots; `None` where the account
+/// did not exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+  pub field: String,
+  pub before: Option<String>,
+  pub after: Option<String>,
+}
+
+/// Fields changed between two snapshots, in field name order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDiff {
+  pub before_slot: u64,
+  pub after_slot: u64,
+  pub changes: Vec<FieldChange>,
+}
+
+impl StateDiff {
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+}
+
+impl Display for StateDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let show =
+      |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    write!(f, "slot {} -> {}", self.before_slot, self.after_slot)?;
+    self.changes.iter().try_for_each(|change| {
+      write!(
+        f,
+        "\n{}: {} -> {}",
+        change.field,
+        show(&change.before),
+        show(&change.after)
+      )
+    })
+  }
+}
+
+impl StateSnapshot {
+  /// Reads the snapshot at the client's commitment.
+  ///
+  /// # Errors
+  /// - Registry lookup table is missing or malformed
+  /// - Failed to fetch accounts
+  /// - An account fails to deserialize
+  pub async fn fetch(rpc_client: &RpcClient) -> Result<StateSnapshot> {
+    StateSnapshot::fetch_with(rpc_client, None).await
+  }
+
+  /// Reads the snapshot from a node that has processed `slot`.
+  ///
+  /// # Errors
+  /// - See [`Self::fetch`]
+  pub async fn fetch_at(
+    rpc_client: &RpcClient,
+    slot: u64,
+  ) -> Result<StateSnapshot> {
+    StateSnapshot::fetch_with(rpc_client, Some(slot)).await
+  }
+
+  async fn fetch_with(
+    rpc_client: &RpcClient,
+    min_context_slot: Option<u64>,
+  ) -> Result<StateSnapshot> {
+    let table_account =
+      rpc_client.get_account(&LST_REGISTRY_LOOKUP_TABLE).await?;
+    let table =
+      deserialize_lookup_table(&LST_REGISTRY_LOOKUP_TABLE, &table_account)?;
+    let sources = sources(&lst_registry_entries(&table)?);
+    let keys: Vec<Pubkey> = sources.iter().map(|(key, _)| *key).collect();
+    let config = RpcAccountInfoConfig {
+      encoding: Some(UiAccountEncoding::Base64),
+      commitment: Some(rpc_client.commitment()),
+      min_context_slot,
+      ..RpcAccountInfoConfig::default()
+    };
+    let response = rpc_client
+      .get_multiple_accounts_with_config(&keys, config)
+      .await?;
+    let fields = sources
+      .iter()
+      .zip(response.value)
+      .filter_map(|((_, source), account)| {
+        account.map(|account| fields(source, &account))
+      })
+      .collect::<Result<Vec<_>>>()?
+      .into_iter()
+      .flatten()
+      .collect();
+    Ok(StateSnapshot {
+      slot: response.context.slot,
+      fields,
+    })
+  }
+
+  /// Fields that differ from this snapshot in `after`.
+  #[must_use]
+  pub fn diff(&self, after: &StateSnapshot) -> StateDiff {
+    let changes = self
+      .fields
+      .keys()
+      .chain(after.fields.keys())
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .filter_map(|field| {
+        let before = self.fields.get(field).cloned();
+        let after = after.fields.get(field).cloned();
+        (before != after).then(|| FieldChange {
+          field: field.clone(),
+          before,
+          after,
+        })
+      })
+      .collect();
+    StateDiff {
+      before_slot: self.slot,
+      after_slot: after.slot,
+      changes,
+    }
+  }
+}
+
+/// Snapshots state, runs `transaction`, and snapshots state again once the
+/// RPC node has processed the slot the transaction landed in.
+///
+/// # Errors
+/// - Either snapshot fails
+/// - `transaction` fails
+/// - Signature status lookup fails or the transaction is not found
+pub async fn capture(
+  rpc_client: &RpcClient,
+  transaction: impl Future<Output = Result<Signature>>,
+) -> Result<(Signature, StateDiff)> {
+  let before = StateSnapshot::fetch(rpc_client).await?;
+  let signature = transaction.await?;
+  let slot = rpc_client
+    .get_signature_statuses(&[signature])
+    .await?
+    .value
+    .into_iter()
+    .next()
+    .flatten()
+    .map(|status| status.slot)
+    .ok_or(anyhow!("Transaction {signature} not found"))?;
+  let after = StateSnapshot::fetch_at(rpc_client, slot).await?;
+  Ok((signature, before.diff(&after)))
+}
@@ -7,6 +7,10 @@ use anyhow::Result;
 use fix::prelude::*;
 use hylo_core::slippage_config::SlippageConfig;
 
+use crate::estimate::ComputeUnitBaselines;
+use crate::priority_fee::{
+  with_compute_budget, PriorityFeeSource, TransactionClass,
+};
 use crate::program_client::{ProgramClient, VersionedTransactionData};
 
 /// Arguments for minting operations that deposit LST to mint hyUSD or xSOL.
@@ -81,6 +85,26 @@ pub trait TransactionSyntax {
     Ok(sig)
   }
 
+  /// Executes transaction with a compute budget sized from `baselines` and
+  /// priced as a [`TransactionClass::UserTrade`] by `fees`.
+  async fn run_priced_transaction<I, O>(
+    &self,
+    inputs: <Self as BuildTransactionData<I, O>>::Inputs,
+    baselines: &ComputeUnitBaselines,
+    fees: &dyn PriorityFeeSource,
+  ) -> Result<Signature>
+  where
+    Self: BuildTransactionData<I, O> + ProgramClient,
+  {
+    let args = self.build(inputs).await?;
+    let estimate = baselines.check(&args, &self.program().payer())?;
+    let args =
+      with_compute_budget(fees, TransactionClass::UserTrade, estimate, args)
+        .await?;
+    let sig = self.send_v0_transaction(&args).await?;
+    Ok(sig)
+  }
+
   /// Builds transaction data without executing.
   async fn build_transaction_data<I, O>(
     &self,
@@ -18,12 +18,19 @@ use anchor_client::solana_sdk::transaction::VersionedTransaction;
 use anchor_client::solana_sdk::{bs58, pubkey};
 use anchor_client::Cluster;
 use anchor_lang::prelude::AccountMeta;
-use anchor_lang::{AnchorDeserialize, Discriminator};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 use anchor_spl::token;
-use anyhow::{anyhow, bail, Context, Result};
+use anchor_spl::token::spl_token::instruction::close_account;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::spl_token::state::Account as TokenAccount;
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use fix::typenum::N9;
-use hylo_core::idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
+use hylo_core::idl::tokens::{
+  TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL,
+};
 use itertools::Itertools;
 use solana_transaction_status_client_types::{
   UiInstruction, UiParsedInstruction, UiPartiallyDecodedInstruction,
@@ -79,6 +86,17 @@ pub fn deserialize_lookup_table(
   })
 }
 
+/// Deserializes an Anchor account, discriminator included.
+///
+/// # Errors
+/// - Account data does not deserialize to `T`
+pub fn deserialize_anchor_account<T: AccountDeserialize>(
+  account: &Account,
+) -> Result<T> {
+  T::try_deserialize(&mut account.data.as_slice())
+    .map_err(|e| anyhow!("Failed to deserialize account: {e}"))
+}
+
 /// Builds a signed versioned transaction.
 ///
 /// # Errors
@@ -142,6 +160,83 @@ pub fn build_lst_registry(
   }
 }
 
+/// Partitions the LST registry into `remaining_accounts` chunks of at most
+/// `lsts_per_chunk` LSTs each. Every chunk carries the full preamble so it can
+/// be submitted as an independent crank instruction.
+///
+/// # Errors
+/// - `lsts_per_chunk` is zero
+/// - Malformed structure (preamble cannot be split at 16)
+pub fn build_lst_registry_chunks(
+  table: &AddressLookupTableAccount,
+  lsts_per_chunk: usize,
+) -> Result<Vec<Vec<AccountMeta>>> {
+  ensure!(lsts_per_chunk > 0, "LST chunk size must be positive.");
+  let preamble = table
+    .addresses
+    .get(..16)
+    .ok_or(anyhow!("Malformed LST registry preamble."))?
+    .iter()
+    .map(|key| AccountMeta::new_readonly(*key, false))
+    .collect_vec();
+  let chunks = lst_registry_entries(table)?
+    .chunks(lsts_per_chunk)
+    .map(|chunk| {
+      let lsts = chunk.iter().flat_map(LstRegistryEntry::account_metas);
+      preamble.iter().cloned().chain(lsts).collect_vec()
+    })
+    .collect_vec();
+  Ok(chunks)
+}
+
+/// Addresses of one registered LST, in registry lookup table order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LstRegistryEntry {
+  pub header: Pubkey,
+  pub mint: Pubkey,
+  pub vault: Pubkey,
+  pub pool_state: Pubkey,
+}
+
+impl LstRegistryEntry {
+  /// Remaining accounts block for this LST, with the header writable.
+  #[must_use]
+  pub fn account_metas(&self) -> [AccountMeta; 4] {
+    [
+      AccountMeta::new(self.header, false),
+      AccountMeta::new_readonly(self.mint, false),
+      AccountMeta::new_readonly(self.vault, false),
+      AccountMeta::new_readonly(self.pool_state, false),
+    ]
+  }
+}
+
+/// Extracts the header, mint, vault and stake pool address of each registered
+/// LST from the registry lookup table.
+///
+/// # Errors
+/// - Malformed structure (preamble cannot be split at 16)
+pub fn lst_registry_entries(
+  table: &AddressLookupTableAccount,
+) -> Result<Vec<LstRegistryEntry>> {
+  table
+    .addresses
+    .split_at_checked(16)
+    .map(|(_, blocks)| {
+      blocks
+        .iter()
+        .tuples()
+        .map(|(header, mint, vault, pool_state)| LstRegistryEntry {
+          header: *header,
+          mint: *mint,
+          vault: *vault,
+          pool_state: *pool_state,
+        })
+        .collect_vec()
+    })
+    .ok_or(anyhow!("Malformed LST registry preamble."))
+}
+
 /// Parses event type `E` from a simulated RPC call.
 /// NB: Drops 16 bytes for header and discriminator.
 ///
@@ -151,6 +246,24 @@ pub fn build_lst_registry(
 pub fn parse_event<E>(
   result: &Response<RpcSimulateTransactionResult>,
 ) -> Result<E>
+where
+  E: AnchorDeserialize + Discriminator,
+{
+  parse_events(result)?
+    .into_iter()
+    .next()
+    .context("Could not parse event from result")
+}
+
+/// Parses every event of type `E` emitted by a simulated RPC call, in
+/// emission order.
+///
+/// # Errors
+/// * Simulation failed
+/// * Event deserialization fails
+pub fn parse_events<E>(
+  result: &Response<RpcSimulateTransactionResult>,
+) -> Result<Vec<E>>
 where
   E: AnchorDeserialize + Discriminator,
 {
@@ -160,15 +273,15 @@ where
     ixs
       .iter()
       .flat_map(|ix| &ix.instructions)
-      .find_map(|ix| match ix {
+      .filter_map(|ix| match ix {
         UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
           UiPartiallyDecodedInstruction { data, .. },
         )) => bs58::decode(data).into_vec().ok(),
         _ => None,
       })
       .filter(|bytes| bytes.len() >= 16 && &bytes[8..16] == E::DISCRIMINATOR)
-      .context("Could not parse event from result")
-      .and_then(|bytes| Ok(E::try_from_slice(&bytes[16..])?))
+      .map(|bytes| Ok(E::try_from_slice(&bytes[16..])?))
+      .collect()
   } else {
     bail!("Simulation succeeded but no inner instructions returned")
   }
@@ -217,3 +330,92 @@ pub fn build_test_stability_pool_client() -> Result<StabilityPoolClient> {
 pub fn user_ata_instruction(user: &Pubkey, mint: &Pubkey) -> Instruction {
   create_associated_token_account_idempotent(user, user, mint, &token::ID)
 }
+
+/// User-facing Hylo flow, for deriving the token accounts it touches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserFlow {
+  /// Mint hyUSD or xSOL (`token`) from `lst`
+  Mint { lst: Pubkey, token: Pubkey },
+  /// Redeem hyUSD or xSOL (`token`) for `lst`
+  Redeem { token: Pubkey, lst: Pubkey },
+  /// Swap `from` to `to`, between hyUSD and xSOL or two LSTs
+  Swap { from: Pubkey, to: Pubkey },
+  /// Deposit hyUSD to the stability pool for sHYUSD
+  Deposit,
+  /// Withdraw sHYUSD for hyUSD and any xSOL in the pool, optionally
+  /// redeeming both for `redeem_to`
+  Withdraw { redeem_to: Option<Pubkey> },
+}
+
+impl UserFlow {
+  /// Mints of every user token account the flow reads or writes.
+  #[must_use]
+  pub fn mints(&self) -> Vec<Pubkey> {
+    match *self {
+      UserFlow::Mint { lst, token } => vec![lst, token],
+      UserFlow::Redeem { token, lst } => vec![token, lst],
+      UserFlow::Swap { from, to } => vec![from, to],
+      UserFlow::Deposit => vec![HYUSD::MINT, SHYUSD::MINT],
+      UserFlow::Withdraw { redeem_to } => {
+        [SHYUSD::MINT, HYUSD::MINT, XSOL::MINT]
+          .into_iter()
+          .chain(redeem_to)
+          .collect()
+      }
+    }
+  }
+}
+
+/// Idempotent ATA creation instructions for every user token account
+/// touched by `flows`, once per mint, so composed transactions never fail on
+/// a missing account.
+#[must_use]
+pub fn flow_ata_instructions(
+  user: &Pubkey,
+  flows: &[UserFlow],
+) -> Vec<Instruction> {
+  flows
+    .iter()
+    .flat_map(UserFlow::mints)
+    .unique()
+    .map(|mint| user_ata_instruction(user, &mint))
+    .collect()
+}
+
+/// Mints whose user ATAs are left behind by composite Hylo flows.
+pub const CLOSABLE_USER_MINTS: [Pubkey; 4] =
+  [HYUSD::MINT, XSOL::MINT, SHYUSD::MINT, native_mint::ID];
+
+/// Builds instruction closing a user's ATA for `mint`, returning rent to the
+/// user. The account must be empty unless `mint` is wSOL, in which case the
+/// remaining balance is unwrapped.
+///
+/// # Errors
+/// - Instruction construction
+pub fn close_user_ata_instruction(
+  user: &Pubkey,
+  mint: &Pubkey,
+) -> Result<Instruction> {
+  let ata = get_associated_token_address(user, mint);
+  let ix = close_account(&token::ID, &ata, user, user, &[])?;
+  Ok(ix)
+}
+
+/// Selects the mints in [`CLOSABLE_USER_MINTS`] whose user ATA exists and can
+/// be closed: empty, or holding only wrapped SOL.
+///
+/// # Errors
+/// - Token account data cannot be unpacked
+pub fn closable_user_mints(atas: &[Option<Account>]) -> Result<Vec<Pubkey>> {
+  CLOSABLE_USER_MINTS
+    .iter()
+    .zip(atas)
+    .filter_map(|(mint, account)| account.as_ref().map(|acc| (mint, acc)))
+    .map(|(mint, account)| {
+      let token_account = TokenAccount::unpack(&account.data)?;
+      let closable = token_account.amount == 0 || *mint == native_mint::ID;
+      Ok(closable.then_some(*mint))
+    })
+    .filter_map_ok(|mint| mint)
+    .try_collect()
+}
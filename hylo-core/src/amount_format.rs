@@ -0,0 +1,181 @@
+//! Human-readable rendering and strict parsing of token amounts.
+//!
+//! Amounts format with thousands separators and trailing fractional zeros
+//! trimmed, e.g. `1,234.56`. Parsing accepts the same shape, with or without
+//! separators, and rejects anything that would lose precision.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+use crate::error::CoreError::{AmountOverflow, AmountParse, AmountPrecision};
+
+/// Rounding applied when an input has more decimals than the token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+  /// Truncate toward zero.
+  Floor,
+  /// Round up if any discarded digit is nonzero.
+  Ceil,
+  /// Reject inputs that cannot be represented without loss.
+  Exact,
+}
+
+/// Number of fractional digits for a token exponent.
+pub(crate) fn decimals<Exp: Integer>() -> usize {
+  usize::from(Exp::to_i8().unsigned_abs())
+}
+
+fn group_thousands(digits: &str) -> String {
+  digits
+    .char_indices()
+    .fold(String::new(), |mut out, (i, digit)| {
+      if i > 0 && (digits.len() - i).is_multiple_of(3) {
+        out.push(',');
+      }
+      out.push(digit);
+      out
+    })
+}
+
+/// Splits base units into integer digits and trimmed fractional digits.
+fn split_digits<Exp: Integer>(amount: UFix64<Exp>) -> (String, String) {
+  let scale = decimals::<Exp>();
+  let padded = format!("{:0>width$}", amount.bits, width = scale + 1);
+  let (int, frac) = padded.split_at(padded.len() - scale);
+  (int.to_string(), frac.trim_end_matches('0').to_string())
+}
+
+fn join_digits(int: &str, frac: &str) -> String {
+  if frac.is_empty() {
+    int.to_string()
+  } else {
+    format!("{int}.{frac}")
+  }
+}
+
+/// Formats an amount with its token's decimals, e.g. `1,234.56`.
+#[must_use]
+pub fn format_amount<Exp: Integer>(amount: UFix64<Exp>) -> String {
+  let (int, frac) = split_digits(amount);
+  join_digits(&group_thousands(&int), &frac)
+}
+
+/// Formats an amount without thousands separators, e.g. `1234.56`.
+#[must_use]
+pub fn format_amount_plain<Exp: Integer>(amount: UFix64<Exp>) -> String {
+  let (int, frac) = split_digits(amount);
+  join_digits(&int, &frac)
+}
+
+/// Strips correctly placed thousands separators from an integer part.
+fn strip_separators(int: &str) -> Result<String> {
+  let groups: Vec<&str> = int.split(',').collect();
+  let (head, tail) = groups.split_first().ok_or(AmountParse)?;
+  let valid =
+    (1..=3).contains(&head.len()) && tail.iter().all(|group| group.len() == 3);
+  if groups.len() == 1 || valid {
+    Ok(groups.concat())
+  } else {
+    Err(AmountParse.into())
+  }
+}
+
+/// Parses a UI amount string into base units.
+///
+/// Accepts digits with optional thousands separators and at most the token's
+/// number of decimals. Signs, whitespace, exponents, and empty parts are
+/// rejected rather than coerced.
+pub fn parse_amount<Exp: Integer>(s: &str) -> Result<UFix64<Exp>> {
+  parse_amount_rounded(s, Rounding::Exact)
+}
+
+/// Parses a UI amount string, applying `rounding` to digits beyond the
+/// token's decimals instead of rejecting them.
+pub fn parse_amount_rounded<Exp: Integer>(
+  s: &str,
+  rounding: Rounding,
+) -> Result<UFix64<Exp>> {
+  let scale = decimals::<Exp>();
+  let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+  let dangling_point = s.contains('.') && frac.is_empty();
+  let int = strip_separators(int)?;
+  let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+  if dangling_point || int.is_empty() || !all_digits(&int) || !all_digits(frac)
+  {
+    Err(AmountParse.into())
+  } else {
+    let (kept, discarded) = frac.split_at(frac.len().min(scale));
+    let inexact = discarded.bytes().any(|b| b != b'0');
+    let bits = format!("{int}{kept:0<scale$}")
+      .parse::<u64>()
+      .map_err(|_| AmountOverflow)?;
+    match rounding {
+      Rounding::Exact if inexact => Err(AmountPrecision.into()),
+      Rounding::Ceil if inexact => bits
+        .checked_add(1)
+        .map(UFix64::new)
+        .ok_or(AmountOverflow.into()),
+      _ => Ok(UFix64::new(bits)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+  use proptest::prelude::*;
+
+  use super::{format_amount, parse_amount, parse_amount_rounded, Rounding};
+  use crate::error::CoreError::{AmountOverflow, AmountParse, AmountPrecision};
+
+  proptest! {
+    #[test]
+    fn format_parse_round_trip(bits in any::<u64>()) {
+      let amount = UFix64::<N6>::new(bits);
+      prop_assert_eq!(parse_amount::<N6>(&format_amount(amount))?, amount);
+    }
+  }
+
+  #[test]
+  fn format_examples() {
+    assert_eq!(format_amount(UFix64::<N6>::new(1_234_560_000)), "1,234.56");
+    assert_eq!(format_amount(UFix64::<N9>::new(5)), "0.000000005");
+    let million = UFix64::<N6>::new(1_000_000_000_000);
+    assert_eq!(format_amount(million), "1,000,000");
+    assert_eq!(format_amount(UFix64::<N6>::zero()), "0");
+  }
+
+  #[test]
+  fn parse_examples() -> Result<()> {
+    assert_eq!(parse_amount::<N6>("1,234.56")?, UFix64::new(1_234_560_000));
+    assert_eq!(parse_amount::<N6>("1234.56")?, UFix64::new(1_234_560_000));
+    assert_eq!(parse_amount::<N9>("0.5")?, UFix64::new(500_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_rounded() -> Result<()> {
+    let parse = parse_amount_rounded::<N6>;
+    assert_eq!(parse("0.0000015", Rounding::Floor)?, UFix64::new(1));
+    assert_eq!(parse("0.0000015", Rounding::Ceil)?, UFix64::new(2));
+    assert_eq!(parse("0.0000010", Rounding::Exact)?, UFix64::new(1));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_rejects() {
+    let parse = parse_amount::<N6>;
+    assert_eq!(parse(""), Err(AmountParse.into()));
+    assert_eq!(parse("1."), Err(AmountParse.into()));
+    assert_eq!(parse(".5"), Err(AmountParse.into()));
+    assert_eq!(parse("-1"), Err(AmountParse.into()));
+    assert_eq!(parse("+1"), Err(AmountParse.into()));
+    assert_eq!(parse("1 000"), Err(AmountParse.into()));
+    assert_eq!(parse("12,34"), Err(AmountParse.into()));
+    assert_eq!(parse("1e6"), Err(AmountParse.into()));
+    assert_eq!(parse("0.0000001"), Err(AmountPrecision.into()));
+    assert_eq!(parse("99999999999999999"), Err(AmountOverflow.into()));
+  }
+}
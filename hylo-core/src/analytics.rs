@@ -0,0 +1,165 @@
+//! Saturating arithmetic for analytics and reporting.
+//!
+//! Exchange math errors on overflow so that no transaction proceeds with a
+//! wrong amount. Dashboards would rather render an approximate figure, so the
+//! functions here compute in `u128`, saturate to `u64::MAX` when the result
+//! does not fit, and flag the value as overflowed.
+
+use fix::prelude::*;
+use fix::typenum::N15;
+
+use crate::exchange_math::{collateral_ratio_wide, total_value_locked_wide};
+
+/// Value computed in analytics mode, flagged when it saturated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reported<T> {
+  pub value: T,
+  pub overflow: bool,
+}
+
+impl<T> Reported<T> {
+  #[must_use]
+  pub fn exact(value: T) -> Reported<T> {
+    Reported {
+      value,
+      overflow: false,
+    }
+  }
+
+  #[must_use]
+  pub fn saturated(value: T) -> Reported<T> {
+    Reported {
+      value,
+      overflow: true,
+    }
+  }
+
+  /// Returns the value only if it did not saturate.
+  #[must_use]
+  pub fn exact_value(self) -> Option<T> {
+    if self.overflow {
+      None
+    } else {
+      Some(self.value)
+    }
+  }
+}
+
+/// Narrows a widened value to `u64` bits, saturating on overflow.
+#[must_use]
+pub fn saturate<Exp>(wide: UFix128<Exp>) -> Reported<UFix64<Exp>> {
+  wide.narrow::<u64>().map_or_else(
+    || Reported::saturated(UFix64::new(u64::MAX)),
+    Reported::exact,
+  )
+}
+
+/// Maps an optional wide result, treating `None` as saturated.
+fn saturate_opt<Exp>(wide: Option<UFix128<Exp>>) -> Reported<UFix64<Exp>> {
+  wide.map_or_else(|| Reported::saturated(UFix64::new(u64::MAX)), saturate)
+}
+
+/// TVL in USD at 9 decimals, saturating at `u64::MAX`.
+#[must_use]
+pub fn total_value_locked(
+  total_sol: UFix64<N9>,
+  sol_usd_price: UFix64<N8>,
+) -> Reported<UFix64<N9>> {
+  saturate_opt(total_value_locked_wide(total_sol, sol_usd_price).ok())
+}
+
+/// Collateral ratio, saturating at `u64::MAX`.
+///
+/// NB: Zero stablecoin supply reports `u64::MAX` without the overflow flag,
+/// matching [`crate::exchange_math::collateral_ratio`].
+#[must_use]
+pub fn collateral_ratio(
+  total_sol: UFix64<N9>,
+  usd_sol_price: UFix64<N8>,
+  amount_stablecoin: UFix64<N6>,
+) -> Reported<UFix64<N9>> {
+  if amount_stablecoin == UFix64::zero() {
+    Reported::exact(UFix64::new(u64::MAX))
+  } else {
+    saturate_opt(
+      collateral_ratio_wide(total_sol, usd_sol_price, amount_stablecoin).ok(),
+    )
+  }
+}
+
+/// Levercoin NAV from spot price, saturating at `u64::MAX`.
+/// Reports zero when stablecoin liabilities exceed collateral.
+///   `NAV = (total_sol * sol_usd_price - stablecoin * stablecoin_nav) / lever`
+#[must_use]
+pub fn levercoin_nav(
+  total_sol: UFix64<N9>,
+  sol_usd_price: UFix64<N8>,
+  stablecoin_supply: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+  levercoin_supply: UFix64<N6>,
+) -> Reported<UFix64<N9>> {
+  if levercoin_supply == UFix64::zero() {
+    Reported::exact(UFix64::one())
+  } else {
+    let nav = total_value_locked_wide(total_sol, sol_usd_price)
+      .ok()
+      .and_then(|tvl| {
+        let liabilities = stablecoin_supply
+          .widen::<u128>()
+          .checked_mul(&stablecoin_nav.widen::<u128>())?;
+        tvl
+          .convert::<N15>()
+          .saturating_sub(&liabilities)
+          .checked_div(&levercoin_supply.widen::<u128>())
+      });
+    saturate_opt(nav)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fix::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn tvl_exact() {
+    let tvl = total_value_locked(
+      UFix64::new(8_217_712_567_008),
+      UFix64::new(13_770_492_000),
+    );
+    assert_eq!(
+      tvl.exact_value(),
+      crate::exchange_math::total_value_locked(
+        UFix64::new(8_217_712_567_008),
+        UFix64::new(13_770_492_000),
+      )
+      .ok()
+    );
+  }
+
+  #[test]
+  fn tvl_saturates() {
+    let tvl =
+      total_value_locked(UFix64::new(u64::MAX), UFix64::new(25_000_000_000));
+    assert_eq!(tvl, Reported::saturated(UFix64::new(u64::MAX)));
+  }
+
+  #[test]
+  fn collateral_ratio_zero_supply() {
+    let cr = collateral_ratio(UFix64::new(1), UFix64::new(1), UFix64::zero());
+    assert_eq!(cr, Reported::exact(UFix64::new(u64::MAX)));
+  }
+
+  #[test]
+  fn levercoin_nav_underwater() {
+    let nav = levercoin_nav(
+      UFix64::new(1_000_000_000),
+      UFix64::new(10_000_000_000),
+      UFix64::new(200_000_000),
+      UFix64::one(),
+      UFix64::new(1_000_000),
+    );
+    assert_eq!(nav, Reported::exact(UFix64::zero()));
+  }
+}
@@ -0,0 +1,125 @@
+//! Zero-supply bootstrap handling.
+//!
+//! On a fresh deployment or a fork with empty mints, `cap / supply` NAV
+//! formulas are undefined. Tokens without supply are instead priced at a
+//! fixed initial NAV of $1, and [`Bootstrap`] reports which tokens are in that
+//! state so callers can tell a defined starting price from a market one.
+
+use fix::prelude::*;
+
+/// NAV of hyUSD and xSOL while their supply is zero.
+#[must_use]
+pub fn initial_token_nav() -> UFix64<N9> {
+  UFix64::one()
+}
+
+/// NAV of sHYUSD while its supply is zero.
+#[must_use]
+pub fn initial_lp_token_nav() -> UFix64<N6> {
+  UFix64::one()
+}
+
+/// Which protocol tokens have zero supply and are priced at initial NAV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bootstrap {
+  pub stablecoin: bool,
+  pub levercoin: bool,
+  pub lp_token: bool,
+}
+
+impl Bootstrap {
+  /// Derives bootstrap status from the three protocol token supplies.
+  #[must_use]
+  pub fn new(
+    stablecoin_supply: UFix64<N6>,
+    levercoin_supply: UFix64<N6>,
+    lp_token_supply: UFix64<N6>,
+  ) -> Bootstrap {
+    Bootstrap {
+      stablecoin: stablecoin_supply == UFix64::zero(),
+      levercoin: levercoin_supply == UFix64::zero(),
+      lp_token: lp_token_supply == UFix64::zero(),
+    }
+  }
+
+  /// True once every protocol token has supply.
+  #[must_use]
+  pub fn is_live(self) -> bool {
+    !(self.stablecoin || self.levercoin || self.lp_token)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+  use crate::error::CoreError::EmptyPoolWithdraw;
+  use crate::exchange_math::{
+    collateral_ratio, depeg_stablecoin_nav, next_levercoin_mint_nav,
+    next_levercoin_redeem_nav,
+  };
+  use crate::pyth::PriceRange;
+  use crate::stability_pool_math::{amount_token_to_withdraw, lp_token_nav};
+
+  #[test]
+  fn status_from_supplies() {
+    let live = Bootstrap::new(UFix64::new(1), UFix64::new(1), UFix64::new(1));
+    assert!(live.is_live());
+    let fresh = Bootstrap::new(UFix64::zero(), UFix64::zero(), UFix64::zero());
+    assert_eq!(
+      fresh,
+      Bootstrap {
+        stablecoin: true,
+        levercoin: true,
+        lp_token: true,
+      }
+    );
+    assert!(!fresh.is_live());
+  }
+
+  #[test]
+  fn fresh_deployment_navs() -> Result<()> {
+    let price =
+      PriceRange::new(UFix64::new(14_000_000_000), UFix64::new(14_100_000_000));
+    let cr = collateral_ratio(UFix64::zero(), price.lower, UFix64::zero())?;
+    assert_eq!(cr, UFix64::new(u64::MAX));
+    let stablecoin_nav =
+      depeg_stablecoin_nav(UFix64::zero(), price.lower, UFix64::zero())?;
+    assert_eq!(stablecoin_nav, initial_token_nav());
+    let mint_nav = next_levercoin_mint_nav(
+      UFix64::zero(),
+      price,
+      UFix64::zero(),
+      stablecoin_nav,
+      UFix64::zero(),
+    );
+    let redeem_nav = next_levercoin_redeem_nav(
+      UFix64::zero(),
+      price,
+      UFix64::zero(),
+      stablecoin_nav,
+      UFix64::zero(),
+    );
+    assert_eq!(mint_nav, Some(initial_token_nav()));
+    assert_eq!(redeem_nav, Some(initial_token_nav()));
+    Ok(())
+  }
+
+  #[test]
+  fn empty_pool() -> Result<()> {
+    let nav = lp_token_nav(
+      initial_token_nav(),
+      UFix64::zero(),
+      initial_token_nav(),
+      UFix64::zero(),
+      UFix64::zero(),
+    )?;
+    assert_eq!(nav, initial_lp_token_nav());
+    let withdraw =
+      amount_token_to_withdraw(UFix64::new(1), UFix64::zero(), UFix64::zero());
+    assert_eq!(withdraw, Err(EmptyPoolWithdraw.into()));
+    Ok(())
+  }
+}
@@ -5,6 +5,8 @@ use crate::error::CoreError::{
   LeverToStable, LstToToken, StableToLever, TokenToLst,
 };
 use crate::pyth::PriceRange;
+use crate::rescale::Rescale;
+use crate::rescale::Rounding::{Exact, Floor};
 
 /// Provides conversions between an LST and protocol tokens.
 pub struct Conversion {
@@ -30,10 +32,9 @@ impl Conversion {
   ) -> Result<UFix64<N6>> {
     amount_lst
       .mul_div_floor(self.lst_sol_price, UFix64::one())
-      .and_then(|sol| {
-        sol.mul_div_floor(self.usd_sol_price.lower.convert(), token_nav)
-      })
-      .map(UFix64::convert)
+      .zip(self.usd_sol_price.lower.rescale(Exact))
+      .and_then(|(sol, usd_sol)| sol.mul_div_floor(usd_sol, token_nav))
+      .and_then(|token| token.rescale(Floor))
       .ok_or(LstToToken.into())
   }
 
@@ -45,8 +46,9 @@ impl Conversion {
     token_nav: UFix64<N9>,
   ) -> Result<UFix64<N9>> {
     amount_token
-      .convert::<N9>()
-      .mul_div_floor(token_nav, self.usd_sol_price.upper.convert())
+      .rescale::<N9>(Floor)
+      .zip(self.usd_sol_price.upper.rescale(Exact))
+      .and_then(|(token, usd_sol)| token.mul_div_floor(token_nav, usd_sol))
       .and_then(|sol| sol.mul_div_floor(UFix64::one(), self.lst_sol_price))
       .ok_or(TokenToLst.into())
   }
@@ -1,4 +1,6 @@
-use anchor_lang::prelude::error_code;
+use std::fmt::{self, Display};
+
+use anchor_lang::prelude::{error_code, Pubkey};
 
 #[error_code]
 pub enum CoreError {
@@ -113,4 +115,138 @@ pub enum CoreError {
   YieldHarvestConfigValidation,
   #[msg("Arithmetic error while computing yield harvest allocation.")]
   YieldHarvestAllocation,
+  // `stake_pool`
+  #[msg("Stake pool account data is too short or malformed.")]
+  StakePoolDeserialize,
+  #[msg("Stake pool has not been updated for the current epoch.")]
+  StakePoolOutdated,
+  #[msg("Arithmetic error while computing stake pool token price.")]
+  StakePoolPrice,
+  // `amount_format`
+  #[msg("Amount string is not a valid unsigned decimal.")]
+  AmountParse,
+  #[msg("Amount string has more decimals than the token supports.")]
+  AmountPrecision,
+  #[msg("Amount string overflows token base units.")]
+  AmountOverflow,
+  #[msg("Amount string has a symbol other than the expected token.")]
+  AmountSymbol,
+  // `ui_units`
+  #[msg("UI amount must be finite and non-negative.")]
+  UiAmountInvalid,
+  // `pool_metrics`
+  #[msg("Arithmetic error while computing stability pool composition.")]
+  PoolComposition,
+  // `shyusd_pnl`
+  #[msg("Arithmetic error while computing sHYUSD position PnL.")]
+  ShyusdPnl,
+  #[msg("Withdrawal burns more sHYUSD than the tracked position holds.")]
+  ShyusdPnlOverdrawn,
+  // `nav_history`
+  #[msg("Arithmetic error while replaying events for NAV history.")]
+  NavHistory,
+  // `bootstrap`
+  #[msg("Stability pool has no LP token supply to withdraw against.")]
+  EmptyPoolWithdraw,
+  // `exchange_math`
+  #[msg("Arithmetic error while computing collateral ratio restoration.")]
+  CollateralRatioRestore,
+  #[msg("Target collateral ratio cannot be reached by redeeming stablecoin.")]
+  CollateralRatioUnrestorable,
+  // `revenue`
+  #[msg("Arithmetic error while computing protocol revenue.")]
+  Revenue,
+  #[msg("Fee vault closed below its opening balance net of withdrawals.")]
+  RevenueVaultShortfall,
+  // `volatility`
+  #[msg("Risk statistics need three usable xSOL NAV samples over time.")]
+  VolatilityWindow,
+  // `exchange_context`
+  #[msg("Exchange context snapshot could not be encoded or decoded.")]
+  ContextSnapshotEncoding,
+  // `validated_args`
+  #[msg("Instruction amount must be non-zero.")]
+  ZeroInstructionAmount,
+  #[msg("Slippage must expect a non-zero output and tolerate less than 100%.")]
+  SlippageToleranceRange,
+  // `stability_pool_math`
+  #[msg("Arithmetic error while computing remaining pool deposit capacity.")]
+  DepositCapacity,
+  // `xsol_carry`
+  #[msg("xSOL carry needs a collateral ratio above 1 and a holding period.")]
+  XsolCarryInputs,
+  // `fee_ledger`
+  #[msg("Arithmetic overflow while totalling fee legs.")]
+  FeeLedgerOverflow,
+  #[msg("Fee legs for one mint were recorded with different exponents.")]
+  FeeLedgerExponent,
+  // `operation_matrix`
+  #[msg("Rebalance is disabled in the current stability mode.")]
+  RebalanceDisabled,
+  // `sol_denomination`
+  #[msg("Arithmetic error while converting a USD value to SOL.")]
+  SolDenomination,
+  // `lst_mix`
+  #[msg("Arithmetic error while weighing LST vaults.")]
+  LstMix,
+  #[msg("LST target shares must not exceed 100% in total.")]
+  LstMixTargets,
+  // `pool_projection`
+  #[msg("Arithmetic error while projecting stability pool growth.")]
+  PoolProjection,
+  // `lst_sol_price`
+  #[msg("Cached LstSolPrice is zero.")]
+  LstSolPriceZero,
+  // `validated_args`
+  #[msg("Oracle confidence tolerance must be above 0% and below 100%.")]
+  OracleConfToleranceRange,
+}
+
+/// Account rejected before deserialization, e.g. when read from Jupiter's
+/// `AccountMap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+  /// No account at `key`
+  NotFound(Pubkey),
+
+  /// Owned by a program other than the one that writes the expected type
+  Owner {
+    key: Pubkey,
+    expected: Pubkey,
+    actual: Pubkey,
+  },
+
+  /// Anchor discriminator of another account type
+  Discriminator { key: Pubkey },
+}
+
+impl Display for AccountError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AccountError::NotFound(key) => write!(f, "Account not found {key}"),
+      AccountError::Owner {
+        key,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "Account {key} owned by {actual}, expected owner {expected}"
+      ),
+      AccountError::Discriminator { key } => {
+        write!(f, "Account {key} has the discriminator of another type")
+      }
+    }
+  }
+}
+
+impl std::error::Error for AccountError {}
+
+/// Errors of the `test-utils` helpers, numbered apart from [`CoreError`] so
+/// that enabling the feature leaves its codes unchanged.
+#[cfg(any(test, feature = "test-utils"))]
+#[error_code]
+pub enum TestUtilsError {
+  // `mock_pyth`
+  #[msg("Mock Pyth price does not fit a signed 64-bit price.")]
+  MockPriceUpdate = 7900,
 }
@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use anchor_lang::prelude::*;
 use fix::prelude::*;
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
@@ -5,13 +7,15 @@ use spl_token_interface::state::Mint;
 
 use crate::conversion::{Conversion, SwapConversion};
 use crate::error::CoreError::{
-  DestinationFeeSol, DestinationFeeStablecoin, LevercoinNav,
-  NoNextStabilityThreshold, RequestedStablecoinOverMaxMintable,
+  ContextSnapshotEncoding, DestinationFeeSol, DestinationFeeStablecoin,
+  LevercoinNav, NoNextStabilityThreshold, PythOraclePriceRange,
+  RequestedStablecoinOverMaxMintable,
 };
 use crate::exchange_math::{
-  collateral_ratio, depeg_stablecoin_nav, max_mintable_stablecoin,
-  max_swappable_stablecoin, next_levercoin_mint_nav, next_levercoin_redeem_nav,
-  total_value_locked,
+  collateral_ratio, depeg_stablecoin_nav, levercoin_mint_to_target_cr,
+  max_mintable_stablecoin, max_swappable_stablecoin, next_levercoin_mint_nav,
+  next_levercoin_redeem_nav, stablecoin_redeem_to_target_cr,
+  stablecoin_swap_to_target_cr, total_value_locked,
 };
 use crate::fee_controller::{
   FeeController, FeeExtract, LevercoinFees, StablecoinFees,
@@ -20,22 +24,45 @@ use crate::lst_sol_price::LstSolPrice;
 use crate::pyth::{query_pyth_price, OracleConfig, PriceRange};
 use crate::solana_clock::SolanaClock;
 use crate::stability_mode::{StabilityController, StabilityMode};
-use crate::stability_pool_math::stability_pool_cap;
+use crate::stability_pool_math::{
+  lp_token_nav, remaining_deposit_capacity, stability_pool_cap,
+};
 use crate::total_sol_cache::TotalSolCache;
 
 /// Container for common values needed in an exchange transaction.
+///
+/// NAVs are computed on first use and memoized, so the state they derive
+/// from is read-only once loaded; only fees can be swapped, via
+/// [`Self::with_fees`].
 #[derive(Clone)]
 pub struct ExchangeContext<C> {
-  pub clock: C,
-  pub total_sol: UFix64<N9>,
-  pub sol_usd_price: PriceRange<N8>,
-  pub stablecoin_supply: UFix64<N6>,
+  clock: C,
+  total_sol: UFix64<N9>,
+  sol_usd_price: PriceRange<N8>,
+  stablecoin_supply: UFix64<N6>,
   levercoin_supply: Option<UFix64<N6>>,
-  pub collateral_ratio: UFix64<N9>,
-  pub stability_controller: StabilityController,
-  pub stability_mode: StabilityMode,
+  collateral_ratio: UFix64<N9>,
+  stability_controller: StabilityController,
+  stability_mode: StabilityMode,
   stablecoin_fees: StablecoinFees,
   levercoin_fees: LevercoinFees,
+  stablecoin_nav: OnceLock<UFix64<N9>>,
+  levercoin_mint_nav: OnceLock<UFix64<N9>>,
+  levercoin_redeem_nav: OnceLock<UFix64<N9>>,
+}
+
+/// Reads a memoized value, computing and storing it on first success.
+fn memoize(
+  cell: &OnceLock<UFix64<N9>>,
+  compute: impl FnOnce() -> Result<UFix64<N9>>,
+) -> Result<UFix64<N9>> {
+  match cell.get() {
+    Some(value) => Ok(*value),
+    None => {
+      let value = compute()?;
+      Ok(*cell.get_or_init(|| value))
+    }
+  }
 }
 
 impl<C: SolanaClock> ExchangeContext<C> {
@@ -72,9 +99,73 @@ impl<C: SolanaClock> ExchangeContext<C> {
       stability_mode,
       stablecoin_fees,
       levercoin_fees,
+      stablecoin_nav: OnceLock::new(),
+      levercoin_mint_nav: OnceLock::new(),
+      levercoin_redeem_nav: OnceLock::new(),
+    })
+  }
+
+  /// Replaces the fee tables quotes are computed with, e.g. to preview a
+  /// proposed fee change. NAVs and the stability mode do not depend on fees.
+  pub fn with_fees(
+    self,
+    stablecoin_fees: StablecoinFees,
+    levercoin_fees: LevercoinFees,
+  ) -> Result<ExchangeContext<C>> {
+    stablecoin_fees.validate()?;
+    levercoin_fees.validate()?;
+    Ok(ExchangeContext {
+      stablecoin_fees,
+      levercoin_fees,
+      ..self
     })
   }
 
+  #[must_use]
+  pub fn clock(&self) -> &C {
+    &self.clock
+  }
+
+  #[must_use]
+  pub fn total_sol(&self) -> UFix64<N9> {
+    self.total_sol
+  }
+
+  #[must_use]
+  pub fn sol_usd_price(&self) -> PriceRange<N8> {
+    self.sol_usd_price
+  }
+
+  #[must_use]
+  pub fn stablecoin_supply(&self) -> UFix64<N6> {
+    self.stablecoin_supply
+  }
+
+  #[must_use]
+  pub fn collateral_ratio(&self) -> UFix64<N9> {
+    self.collateral_ratio
+  }
+
+  #[must_use]
+  pub fn stability_controller(&self) -> StabilityController {
+    self.stability_controller
+  }
+
+  #[must_use]
+  pub fn stability_mode(&self) -> StabilityMode {
+    self.stability_mode
+  }
+
+  #[must_use]
+  pub fn stablecoin_fees(&self) -> StablecoinFees {
+    self.stablecoin_fees
+  }
+
+  #[must_use]
+  pub fn levercoin_fees(&self) -> LevercoinFees {
+    self.levercoin_fees
+  }
+
   /// Computes TVL in USD, maintaining precision at 9 decimals.
   pub fn total_value_locked(&self) -> Result<UFix64<N9>> {
     total_value_locked(self.total_sol, self.sol_usd_price.lower)
@@ -85,36 +176,40 @@ impl<C: SolanaClock> ExchangeContext<C> {
   }
 
   pub fn levercoin_mint_nav(&self) -> Result<UFix64<N9>> {
-    next_levercoin_mint_nav(
-      self.total_sol,
-      self.sol_usd_price,
-      self.stablecoin_supply,
-      self.stablecoin_nav()?,
-      self.levercoin_supply()?,
-    )
-    .ok_or(LevercoinNav.into())
+    memoize(&self.levercoin_mint_nav, || {
+      next_levercoin_mint_nav(
+        self.total_sol,
+        self.sol_usd_price,
+        self.stablecoin_supply,
+        self.stablecoin_nav()?,
+        self.levercoin_supply()?,
+      )
+      .ok_or(LevercoinNav.into())
+    })
   }
 
   pub fn levercoin_redeem_nav(&self) -> Result<UFix64<N9>> {
-    next_levercoin_redeem_nav(
-      self.total_sol,
-      self.sol_usd_price,
-      self.stablecoin_supply,
-      self.stablecoin_nav()?,
-      self.levercoin_supply()?,
-    )
-    .ok_or(LevercoinNav.into())
+    memoize(&self.levercoin_redeem_nav, || {
+      next_levercoin_redeem_nav(
+        self.total_sol,
+        self.sol_usd_price,
+        self.stablecoin_supply,
+        self.stablecoin_nav()?,
+        self.levercoin_supply()?,
+      )
+      .ok_or(LevercoinNav.into())
+    })
   }
 
   pub fn stablecoin_nav(&self) -> Result<UFix64<N9>> {
-    match self.stability_mode {
+    memoize(&self.stablecoin_nav, || match self.stability_mode {
       StabilityMode::Depeg => depeg_stablecoin_nav(
         self.total_sol,
         self.sol_usd_price.lower,
         self.stablecoin_supply,
       ),
       _ => Ok(UFix64::one()),
-    }
+    })
   }
 
   /// Computes new collateral ratio and translates to a configured
@@ -345,6 +440,44 @@ impl<C: SolanaClock> ExchangeContext<C> {
     )
   }
 
+  /// SOL to deposit via levercoin mint to raise CR to `target`, priced at the
+  /// lower bound of the oracle range.
+  pub fn levercoin_mint_to_target_cr(
+    &self,
+    target: UFix64<N2>,
+  ) -> Result<UFix64<N9>> {
+    levercoin_mint_to_target_cr(
+      target,
+      self.total_sol,
+      self.sol_usd_price.lower,
+      self.stablecoin_supply,
+    )
+  }
+
+  /// Stablecoin to redeem to raise CR to `target`.
+  pub fn stablecoin_redeem_to_target_cr(
+    &self,
+    target: UFix64<N2>,
+  ) -> Result<UFix64<N6>> {
+    stablecoin_redeem_to_target_cr(
+      target,
+      self.total_value_locked()?,
+      self.stablecoin_supply,
+    )
+  }
+
+  /// Stablecoin to swap into levercoin to raise CR to `target`.
+  pub fn stablecoin_swap_to_target_cr(
+    &self,
+    target: UFix64<N2>,
+  ) -> Result<UFix64<N6>> {
+    stablecoin_swap_to_target_cr(
+      target,
+      self.total_value_locked()?,
+      self.stablecoin_supply,
+    )
+  }
+
   /// Checks the requested amount of stablecoin swap against protocol's current
   /// max.
   pub fn validate_stablecoin_swap_amount(
@@ -420,4 +553,214 @@ impl<C: SolanaClock> ExchangeContext<C> {
       levercoin_in_pool,
     )
   }
+
+  /// NAV of the stability pool's LP token (sHYUSD), valuing levercoin in the
+  /// pool at its mint NAV.
+  pub fn shyusd_nav(
+    &self,
+    stablecoin_in_pool: UFix64<N6>,
+    levercoin_in_pool: UFix64<N6>,
+    lp_token_supply: UFix64<N6>,
+  ) -> Result<UFix64<N6>> {
+    lp_token_nav(
+      self.stablecoin_nav()?,
+      stablecoin_in_pool,
+      self.levercoin_mint_nav()?,
+      levercoin_in_pool,
+      lp_token_supply,
+    )
+  }
+
+  /// Stablecoin that can be deposited to the stability pool before its
+  /// capitalization reaches `cap_limit`.
+  pub fn remaining_deposit_capacity(
+    &self,
+    stablecoin_in_pool: UFix64<N6>,
+    levercoin_in_pool: UFix64<N6>,
+    cap_limit: UFix64<N6>,
+  ) -> Result<UFix64<N6>> {
+    let pool_cap =
+      self.stability_pool_cap(stablecoin_in_pool, levercoin_in_pool)?;
+    remaining_deposit_capacity(pool_cap, cap_limit, self.stablecoin_nav()?)
+  }
+}
+
+/// Compact, ready-to-quote form of an [`ExchangeContext`].
+///
+/// Carries the inputs the context derives everything else from, so a fetcher
+/// process can parse Pyth and mint accounts once and ship the result to
+/// stateless quoting workers as borsh bytes. Collateral ratio and stability
+/// mode are recomputed on [`Self::restore`]; NAVs are memoized afresh.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ExchangeContextSnapshot {
+  pub slot: u64,
+  pub epoch_start_timestamp: i64,
+  pub epoch: u64,
+  pub leader_schedule_epoch: u64,
+  pub unix_timestamp: i64,
+  pub total_sol: UFixValue64,
+  pub sol_usd_lower: UFixValue64,
+  pub sol_usd_upper: UFixValue64,
+  pub stablecoin_supply: UFixValue64,
+  pub levercoin_supply: Option<UFixValue64>,
+  pub stability_threshold_1: UFixValue64,
+  pub stability_threshold_2: UFixValue64,
+  pub stablecoin_fees: StablecoinFees,
+  pub levercoin_fees: LevercoinFees,
+}
+
+impl<C: SolanaClock> ExchangeContext<C> {
+  /// Captures the context's inputs for [`ExchangeContextSnapshot::restore`].
+  #[must_use]
+  pub fn snapshot(&self) -> ExchangeContextSnapshot {
+    ExchangeContextSnapshot {
+      slot: self.clock.slot(),
+      epoch_start_timestamp: self.clock.epoch_start_timestamp(),
+      epoch: self.clock.epoch(),
+      leader_schedule_epoch: self.clock.leader_schedule_epoch(),
+      unix_timestamp: self.clock.unix_timestamp(),
+      total_sol: self.total_sol.into(),
+      sol_usd_lower: self.sol_usd_price.lower.into(),
+      sol_usd_upper: self.sol_usd_price.upper.into(),
+      stablecoin_supply: self.stablecoin_supply.into(),
+      levercoin_supply: self.levercoin_supply.map(Into::into),
+      stability_threshold_1: self
+        .stability_controller
+        .stability_threshold_1()
+        .into(),
+      stability_threshold_2: self
+        .stability_controller
+        .stability_threshold_2()
+        .into(),
+      stablecoin_fees: self.stablecoin_fees,
+      levercoin_fees: self.levercoin_fees,
+    }
+  }
+}
+
+impl ExchangeContextSnapshot {
+  /// Rebuilds the context at the captured clock, rejecting inputs that
+  /// [`ExchangeContext::load`] or [`ExchangeContext::with_fees`] would not
+  /// produce: an inverted price range, unordered stability thresholds or
+  /// invalid fee tables.
+  pub fn restore(&self) -> Result<ExchangeContext<Clock>> {
+    let clock = Clock {
+      slot: self.slot,
+      epoch_start_timestamp: self.epoch_start_timestamp,
+      epoch: self.epoch,
+      leader_schedule_epoch: self.leader_schedule_epoch,
+      unix_timestamp: self.unix_timestamp,
+    };
+    let total_sol = self.total_sol.try_into()?;
+    let sol_usd_lower: UFix64<N8> = self.sol_usd_lower.try_into()?;
+    let sol_usd_upper: UFix64<N8> = self.sol_usd_upper.try_into()?;
+    let sol_usd_price = (sol_usd_lower <= sol_usd_upper)
+      .then(|| PriceRange::new(sol_usd_lower, sol_usd_upper))
+      .ok_or(PythOraclePriceRange)?;
+    self.stablecoin_fees.validate()?;
+    self.levercoin_fees.validate()?;
+    let stablecoin_supply = self.stablecoin_supply.try_into()?;
+    let levercoin_supply =
+      self.levercoin_supply.map(TryInto::try_into).transpose()?;
+    let stability_controller = StabilityController::new(
+      self.stability_threshold_1.try_into()?,
+      self.stability_threshold_2.try_into()?,
+    )?;
+    let collateral_ratio =
+      collateral_ratio(total_sol, sol_usd_price.lower, stablecoin_supply)?;
+    let stability_mode =
+      stability_controller.stability_mode(collateral_ratio)?;
+    Ok(ExchangeContext {
+      clock,
+      total_sol,
+      sol_usd_price,
+      stablecoin_supply,
+      levercoin_supply,
+      collateral_ratio,
+      stability_controller,
+      stability_mode,
+      stablecoin_fees: self.stablecoin_fees,
+      levercoin_fees: self.levercoin_fees,
+      stablecoin_nav: OnceLock::new(),
+      levercoin_mint_nav: OnceLock::new(),
+      levercoin_redeem_nav: OnceLock::new(),
+    })
+  }
+
+  /// Borsh encoding of the snapshot.
+  pub fn to_bytes(&self) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    self
+      .serialize(&mut bytes)
+      .map_err(|_| ContextSnapshotEncoding)?;
+    Ok(bytes)
+  }
+
+  /// Decodes a snapshot produced by [`Self::to_bytes`].
+  pub fn from_bytes(bytes: &[u8]) -> Result<ExchangeContextSnapshot> {
+    ExchangeContextSnapshot::try_from_slice(bytes)
+      .map_err(|_| ContextSnapshotEncoding.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+  use crate::error::CoreError::{InvalidFees, StabilityValidation};
+  use crate::fee_controller::FeePair;
+  use crate::test_fixtures::{context, fee_pair, snapshot};
+
+  #[test]
+  fn snapshot_roundtrip() -> Result<()> {
+    let bytes = snapshot().to_bytes()?;
+    let context = ExchangeContextSnapshot::from_bytes(&bytes)?.restore()?;
+    assert_eq!(context.clock.epoch(), 3);
+    assert_eq!(context.collateral_ratio, UFix64::new(3_000_000_000));
+    assert_eq!(context.stability_mode, StabilityMode::Normal);
+    assert_eq!(context.snapshot().to_bytes()?, bytes);
+    Ok(())
+  }
+
+  #[test]
+  fn restore_rejects_invalid_inputs() {
+    let inverted_price = ExchangeContextSnapshot {
+      sol_usd_lower: UFix64::<N8>::new(15_200_000_000).into(),
+      ..snapshot()
+    };
+    assert_eq!(
+      inverted_price.restore().err(),
+      Some(PythOraclePriceRange.into())
+    );
+    let inverted_thresholds = ExchangeContextSnapshot {
+      stability_threshold_2: UFix64::<N2>::new(160).into(),
+      ..snapshot()
+    };
+    assert_eq!(
+      inverted_thresholds.restore().err(),
+      Some(StabilityValidation.into())
+    );
+    let whole_fee =
+      FeePair::new(UFix64::<N4>::one().into(), UFix64::<N4>::new(20).into());
+    let invalid_fees = ExchangeContextSnapshot {
+      stablecoin_fees: StablecoinFees::new(whole_fee, fee_pair()),
+      ..snapshot()
+    };
+    assert_eq!(invalid_fees.restore().err(), Some(InvalidFees.into()));
+  }
+
+  #[test]
+  fn shyusd_nav_values_levercoin_at_mint_nav() -> Result<()> {
+    let context = context()?;
+    // $1,000 hyUSD + 100 xSOL at its $4.04 mint NAV over 1,200 sHYUSD
+    let nav = context.shyusd_nav(
+      UFix64::new(1_000_000_000),
+      UFix64::new(100_000_000),
+      UFix64::new(1_200_000_000),
+    )?;
+    assert_eq!(nav, UFix64::new(1_170_000));
+    Ok(())
+  }
 }
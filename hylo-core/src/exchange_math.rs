@@ -1,11 +1,16 @@
 use anchor_lang::prelude::*;
 use fix::prelude::*;
+use fix::typenum::N15;
 
+use crate::bootstrap::initial_token_nav;
 use crate::error::CoreError::{
-  CollateralRatio, MaxMintable, MaxSwappable, StablecoinNav,
-  TargetCollateralRatioTooLow, TotalValueLocked,
+  CollateralRatio, CollateralRatioRestore, CollateralRatioUnrestorable,
+  MaxMintable, MaxSwappable, StablecoinNav, TargetCollateralRatioTooLow,
+  TotalValueLocked,
 };
 use crate::pyth::PriceRange;
+use crate::rescale::Rescale;
+use crate::rescale::Rounding::Exact;
 
 /// Computes the current collateral ratio (CR) of the protocol.
 ///   `CR = total_sol_usd / stablecoin_cap`
@@ -19,8 +24,9 @@ pub fn collateral_ratio(
   if amount_stablecoin == UFix64::zero() {
     Ok(UFix64::new(u64::MAX))
   } else {
-    total_sol
-      .mul_div_floor(usd_sol_price, amount_stablecoin.convert())
+    amount_stablecoin
+      .rescale(Exact)
+      .and_then(|stablecoin| total_sol.mul_div_floor(usd_sol_price, stablecoin))
       .ok_or(CollateralRatio.into())
   }
 }
@@ -35,6 +41,40 @@ pub fn total_value_locked(
     .ok_or(TotalValueLocked.into())
 }
 
+/// Widened counterpart to [`total_value_locked`].
+/// Keeps the product in `u128` so TVL beyond `u64::MAX` at 9 decimals
+/// (~$18.4B) does not overflow.
+pub fn total_value_locked_wide(
+  total_sol: UFix64<N9>,
+  sol_usd_price: UFix64<N8>,
+) -> Result<UFix128<N9>> {
+  total_sol
+    .widen::<u128>()
+    .checked_mul(&sol_usd_price.widen::<u128>())
+    .map(UFix128::convert)
+    .ok_or(TotalValueLocked.into())
+}
+
+/// Widened counterpart to [`collateral_ratio`], computing TVL in `u128`
+/// before dividing by stablecoin supply.
+///
+/// NB: If stablecoin supply is zero, returns `u128::MAX` to simulate infinity.
+pub fn collateral_ratio_wide(
+  total_sol: UFix64<N9>,
+  usd_sol_price: UFix64<N8>,
+  amount_stablecoin: UFix64<N6>,
+) -> Result<UFix128<N9>> {
+  if amount_stablecoin == UFix64::zero() {
+    Ok(UFix128::new(u128::MAX))
+  } else {
+    let stablecoin = amount_stablecoin.widen::<u128>();
+    total_value_locked_wide(total_sol, usd_sol_price)?
+      .convert::<N15>()
+      .checked_div(&stablecoin)
+      .ok_or(CollateralRatio.into())
+  }
+}
+
 /// Given the next collateral ratio threshold below the current, determines the
 /// amount of stablecoin that can safely be minted.
 ///
@@ -86,6 +126,104 @@ pub fn max_swappable_stablecoin(
     .ok_or(MaxSwappable.into())
 }
 
+/// USD value of collateral missing to reach the target collateral ratio,
+/// or zero if the protocol is already at or above it.
+///   `deficit = target_cr * stablecoin_supply - tvl`
+fn collateral_deficit(
+  target_collateral_ratio: UFix64<N2>,
+  total_value_locked: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> Option<UFix64<N6>> {
+  stablecoin_supply
+    .mul_div_ceil(target_collateral_ratio, UFix64::one())
+    .map(|target| target.saturating_sub(&total_value_locked.convert()))
+}
+
+/// SOL to deposit by minting levercoin to raise CR to the target.
+/// Levercoin minting adds collateral without adding stablecoin.
+///
+/// ```txt
+///                target_cr * stablecoin_supply - tvl
+/// sol_needed = ---------------------------------------
+///                         usd_sol_price
+/// ```
+///
+/// NB: Returns zero if CR is already at or above the target.
+pub fn levercoin_mint_to_target_cr(
+  target_collateral_ratio: UFix64<N2>,
+  total_sol: UFix64<N9>,
+  usd_sol_price: UFix64<N8>,
+  stablecoin_supply: UFix64<N6>,
+) -> Result<UFix64<N9>> {
+  let tvl = total_value_locked(total_sol, usd_sol_price)?;
+  collateral_deficit(target_collateral_ratio, tvl, stablecoin_supply)
+    .and_then(|deficit| deficit.rescale::<N9>(Exact))
+    .and_then(|deficit| deficit.mul_div_ceil(UFix64::one(), usd_sol_price))
+    .ok_or(CollateralRatioRestore.into())
+}
+
+/// Stablecoin to redeem at $1 NAV to raise CR to the target.
+/// Each redemption removes equal value from collateral and liabilities,
+/// which only raises CR while it is above 100%.
+///
+/// ```txt
+///                  target_cr * stablecoin_supply - tvl
+/// redeem_needed = -------------------------------------
+///                             target_cr - 1
+/// ```
+///
+/// NB: Returns zero if CR is already at or above the target.
+///
+/// # Errors
+/// * Target CR is not above 100%
+/// * CR is below 100%, so no redemption reaches the target
+pub fn stablecoin_redeem_to_target_cr(
+  target_collateral_ratio: UFix64<N2>,
+  total_value_locked: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> Result<UFix64<N6>> {
+  if target_collateral_ratio > UFix64::one() {
+    let deficit = collateral_deficit(
+      target_collateral_ratio,
+      total_value_locked,
+      stablecoin_supply,
+    );
+    let denominator = target_collateral_ratio.checked_sub(&UFix64::<N2>::one());
+    let redeem = deficit
+      .zip(denominator)
+      .and_then(|(n, d)| n.mul_div_ceil(UFix64::one(), d))
+      .ok_or(CollateralRatioRestore)?;
+    if redeem <= stablecoin_supply {
+      Ok(redeem)
+    } else {
+      Err(CollateralRatioUnrestorable.into())
+    }
+  } else {
+    Err(TargetCollateralRatioTooLow.into())
+  }
+}
+
+/// Stablecoin to swap into levercoin to raise CR to the target.
+/// Swaps shrink liabilities without changing TVL.
+///
+/// ```txt
+///                                         tvl
+/// swap_needed = stablecoin_supply - -----------
+///                                    target_cr
+/// ```
+///
+/// NB: Returns zero if CR is already at or above the target.
+pub fn stablecoin_swap_to_target_cr(
+  target_collateral_ratio: UFix64<N2>,
+  total_value_locked: UFix64<N9>,
+  stablecoin_supply: UFix64<N6>,
+) -> Result<UFix64<N6>> {
+  total_value_locked
+    .checked_div(&target_collateral_ratio)
+    .map(|capacity| stablecoin_supply.saturating_sub(&capacity.convert()))
+    .ok_or(CollateralRatioRestore.into())
+}
+
 /// Computes upper bound of levercoin NAV for minting.
 ///
 /// If the current supply of the levercoin is zero, the price is $1.
@@ -102,7 +240,7 @@ pub fn next_levercoin_mint_nav(
   levercoin_supply: UFix64<N6>,
 ) -> Option<UFix64<N9>> {
   if levercoin_supply == UFix64::zero() {
-    Some(UFix64::one())
+    Some(initial_token_nav())
   } else {
     let collateral_value =
       total_sol.mul_div_ceil(sol_usd_price.upper, UFix64::one())?;
@@ -116,6 +254,8 @@ pub fn next_levercoin_mint_nav(
 }
 
 /// Computes lower bound of levercoin NAV for redemption.
+///
+/// If the current supply of the levercoin is zero, the price is $1.
 #[must_use]
 pub fn next_levercoin_redeem_nav(
   total_sol: UFix64<N9>,
@@ -125,7 +265,7 @@ pub fn next_levercoin_redeem_nav(
   levercoin_supply: UFix64<N6>,
 ) -> Option<UFix64<N9>> {
   if levercoin_supply == UFix64::zero() {
-    Some(UFix64::one())
+    Some(initial_token_nav())
   } else {
     let collateral_value =
       total_sol.mul_div_floor(sol_usd_price.lower, UFix64::one())?;
@@ -141,21 +281,27 @@ pub fn next_levercoin_redeem_nav(
 /// Computes stablecoin NAV during a depeg scenario.
 /// In all other modes, the price of the stablecoin is fixed to $1.
 ///   `NAV = total_sol * sol_usd_price / supply`
+///
+/// NB: If stablecoin supply is zero, returns the initial NAV of $1.
 pub fn depeg_stablecoin_nav(
   total_collateral_sol: UFix64<N9>,
   sol_usd_price: UFix64<N8>,
   stablecoin_supply: UFix64<N6>,
 ) -> Result<UFix64<N9>> {
-  total_collateral_sol
-    .mul_div_floor(sol_usd_price.convert::<N8>(), stablecoin_supply.convert())
-    .ok_or(StablecoinNav.into())
+  if stablecoin_supply == UFix64::zero() {
+    Ok(initial_token_nav())
+  } else {
+    total_collateral_sol
+      .mul_div_floor(sol_usd_price.convert::<N8>(), stablecoin_supply.convert())
+      .ok_or(StablecoinNav.into())
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use anchor_lang::prelude::Result;
   use fix::prelude::typenum::N8;
-  use fix::prelude::UFix64;
+  use fix::prelude::{UFix128, UFix64};
   use proptest::prelude::*;
 
   use super::*;
@@ -265,6 +411,42 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn collateral_ratio_wide_matches() -> Result<()> {
+    let total_sol = UFix64::<N9>::new(8_217_712_567_008);
+    let usd_sol_price = UFix64::<N8>::new(13_770_492_000);
+    let amount_stablecoin = UFix64::<N6>::new(1_150_380_112_112);
+    let cr = collateral_ratio(total_sol, usd_sol_price, amount_stablecoin)?;
+    let wide =
+      collateral_ratio_wide(total_sol, usd_sol_price, amount_stablecoin)?;
+    assert_eq!(cr.widen::<u128>(), wide);
+    Ok(())
+  }
+
+  #[test]
+  fn total_value_locked_wide_extreme() -> Result<()> {
+    let total_sol = UFix64::<N9>::new(u64::MAX);
+    let usd_sol_price = UFix64::<N8>::new(25_000_000_000);
+    assert!(total_value_locked(total_sol, usd_sol_price).is_err());
+    let tvl = total_value_locked_wide(total_sol, usd_sol_price)?;
+    assert_eq!(UFix128::new(4_611_686_018_427_387_903_750), tvl);
+    Ok(())
+  }
+
+  #[test]
+  fn collateral_ratio_wide_extreme() -> Result<()> {
+    let total_sol = UFix64::<N9>::new(u64::MAX);
+    let usd_sol_price = UFix64::<N8>::new(25_000_000_000);
+    let amount_stablecoin = UFix64::<N6>::new(1_000_000);
+    assert!(
+      collateral_ratio(total_sol, usd_sol_price, amount_stablecoin).is_err()
+    );
+    let cr =
+      collateral_ratio_wide(total_sol, usd_sol_price, amount_stablecoin)?;
+    assert_eq!(UFix128::new(4_611_686_018_427_387_903_750), cr);
+    Ok(())
+  }
+
   #[test]
   fn depeg_stablecoin_low() -> Result<()> {
     let total_sol = UFix64::<N9>::new(1_666_312_671);
@@ -375,4 +557,67 @@ mod tests {
     assert_eq!(expected, got);
     Ok(())
   }
+
+  #[test]
+  fn restore_target_cr() -> Result<()> {
+    // 1000 SOL at $100 backing 80k hyUSD, CR 125%
+    let total_sol = UFix64::<N9>::new(1_000_000_000_000);
+    let usd_sol_price = UFix64::<N8>::new(10_000_000_000);
+    let stablecoin = UFix64::<N6>::new(80_000_000_000);
+    let tvl = total_value_locked(total_sol, usd_sol_price)?;
+    let target = UFix64::<N2>::new(150);
+    let sol = levercoin_mint_to_target_cr(
+      target,
+      total_sol,
+      usd_sol_price,
+      stablecoin,
+    )?;
+    assert_eq!(UFix64::new(200_000_000_000), sol);
+    let redeem = stablecoin_redeem_to_target_cr(target, tvl, stablecoin)?;
+    assert_eq!(UFix64::new(40_000_000_000), redeem);
+    let swap = stablecoin_swap_to_target_cr(target, tvl, stablecoin)?;
+    assert_eq!(UFix64::new(13_333_333_334), swap);
+    let new_cr = collateral_ratio(
+      total_sol,
+      usd_sol_price,
+      stablecoin.checked_sub(&swap).ok_or(CollateralRatio)?,
+    )?;
+    assert!(new_cr >= target.convert());
+    Ok(())
+  }
+
+  #[test]
+  fn restore_target_cr_already_met() -> Result<()> {
+    let total_sol = UFix64::<N9>::new(1_000_000_000_000);
+    let usd_sol_price = UFix64::<N8>::new(10_000_000_000);
+    let stablecoin = UFix64::<N6>::new(80_000_000_000);
+    let tvl = total_value_locked(total_sol, usd_sol_price)?;
+    let target = UFix64::<N2>::new(120);
+    assert_eq!(
+      UFix64::zero(),
+      levercoin_mint_to_target_cr(
+        target,
+        total_sol,
+        usd_sol_price,
+        stablecoin
+      )?
+    );
+    assert_eq!(
+      UFix64::zero(),
+      stablecoin_redeem_to_target_cr(target, tvl, stablecoin)?
+    );
+    assert_eq!(
+      UFix64::zero(),
+      stablecoin_swap_to_target_cr(target, tvl, stablecoin)?
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn redeem_cannot_restore_below_peg() {
+    let tvl = UFix64::<N9>::new(90_000_000_000_000);
+    let stablecoin = UFix64::<N6>::new(100_000_000_000);
+    let got = stablecoin_redeem_to_target_cr(UFix64::new(130), tvl, stablecoin);
+    assert!(got.is_err());
+  }
 }
@@ -0,0 +1,170 @@
+//! Fee legs of composite operations.
+//!
+//! Operations such as withdrawing from the stability pool and redeeming into
+//! an LST chain several fee extractions, each charged in its own token.
+//! [`FeeLedger`] accumulates those legs per mint with overflow checks, so
+//! composite quotes total their fees the same way.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+use crate::error::CoreError::{FeeLedgerExponent, FeeLedgerOverflow};
+use crate::fee_controller::FeeExtract;
+
+/// Fees charged per mint, each stored with its token's exponent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeLedger {
+  totals: BTreeMap<Pubkey, UFixValue64>,
+}
+
+impl FeeLedger {
+  #[must_use]
+  pub fn new() -> FeeLedger {
+    FeeLedger::default()
+  }
+
+  /// Ledger holding one leg of `amount` charged in `mint`.
+  #[must_use]
+  pub fn single<Exp: Integer>(mint: Pubkey, amount: UFix64<Exp>) -> FeeLedger {
+    FeeLedger {
+      totals: BTreeMap::from([(mint, amount.into())]),
+    }
+  }
+
+  /// Adds `amount` of fees charged in `mint`.
+  ///
+  /// # Errors
+  /// * Total for `mint` overflows
+  /// * `mint` was recorded with a different exponent
+  pub fn add<Exp: Integer>(
+    &mut self,
+    mint: Pubkey,
+    amount: UFix64<Exp>,
+  ) -> Result<()> {
+    let total = self.total::<Exp>(mint)?;
+    let total = total.checked_add(&amount).ok_or(FeeLedgerOverflow)?;
+    self.totals.insert(mint, total.into());
+    Ok(())
+  }
+
+  /// Adds the fees of an extraction charged in `mint`.
+  ///
+  /// # Errors
+  /// * See [`Self::add`]
+  pub fn add_extract<Exp: Integer>(
+    &mut self,
+    mint: Pubkey,
+    extract: &FeeExtract<Exp>,
+  ) -> Result<()> {
+    self.add(mint, extract.fees_extracted)
+  }
+
+  /// Ledger with `amount` of fees in `mint` added, for chaining legs.
+  ///
+  /// # Errors
+  /// * See [`Self::add`]
+  pub fn with<Exp: Integer>(
+    mut self,
+    mint: Pubkey,
+    amount: UFix64<Exp>,
+  ) -> Result<FeeLedger> {
+    self.add(mint, amount)?;
+    Ok(self)
+  }
+
+  /// Nets another ledger's legs into this one.
+  ///
+  /// # Errors
+  /// * A mint total overflows
+  /// * A mint is recorded with different exponents
+  pub fn merge(self, other: &FeeLedger) -> Result<FeeLedger> {
+    other
+      .totals
+      .iter()
+      .try_fold(self, |mut ledger, (mint, value)| {
+        let total = match ledger.totals.get(mint) {
+          None => Ok(*value),
+          Some(existing) if existing.exp == value.exp => existing
+            .bits
+            .checked_add(value.bits)
+            .map(|bits| UFixValue64 {
+              bits,
+              exp: value.exp,
+            })
+            .ok_or(FeeLedgerOverflow),
+          Some(_) => Err(FeeLedgerExponent),
+        }?;
+        ledger.totals.insert(*mint, total);
+        Ok(ledger)
+      })
+  }
+
+  /// Fees charged in `mint`, zero if none were recorded.
+  ///
+  /// # Errors
+  /// * `mint` was recorded with a different exponent
+  pub fn total<Exp: Integer>(&self, mint: Pubkey) -> Result<UFix64<Exp>> {
+    self.totals.get(&mint).map_or(Ok(UFix64::new(0)), |value| {
+      if i32::from(value.exp) == Exp::I32 {
+        Ok(UFix64::new(value.bits))
+      } else {
+        Err(FeeLedgerExponent.into())
+      }
+    })
+  }
+
+  /// Mints with fees recorded, in key order.
+  pub fn mints(&self) -> impl Iterator<Item = &Pubkey> {
+    self.totals.keys()
+  }
+
+  /// Recorded totals by mint, in key order.
+  pub fn iter(&self) -> impl Iterator<Item = (&Pubkey, &UFixValue64)> {
+    self.totals.iter()
+  }
+
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.totals.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn totals_per_mint() -> Result<()> {
+    let lst = Pubkey::new_unique();
+    let hyusd = Pubkey::new_unique();
+    let ledger = FeeLedger::new()
+      .with(lst, UFix64::<N9>::new(3))?
+      .with(hyusd, UFix64::<N6>::new(10))?
+      .with(lst, UFix64::<N9>::new(4))?;
+    assert_eq!(ledger.total::<N9>(lst)?, UFix64::new(7));
+    assert_eq!(ledger.total::<N6>(hyusd)?, UFix64::new(10));
+    assert_eq!(ledger.total::<N6>(Pubkey::new_unique())?, UFix64::zero());
+    assert_eq!(ledger.mints().count(), 2);
+    let single = FeeLedger::single(hyusd, UFix64::<N6>::new(10));
+    assert_eq!(
+      single.merge(&FeeLedger::single(lst, UFix64::<N9>::new(7)))?,
+      ledger
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_overflow_and_mixed_exponents() -> Result<()> {
+    let mint = Pubkey::new_unique();
+    let ledger = FeeLedger::new().with(mint, UFix64::<N6>::new(u64::MAX))?;
+    assert!(ledger.clone().with(mint, UFix64::<N6>::new(1)).is_err());
+    assert!(ledger.total::<N9>(mint).is_err());
+    let merged = ledger.clone().merge(&FeeLedger::new())?;
+    assert_eq!(merged, ledger);
+    assert!(ledger.clone().merge(&ledger).is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,139 @@
+//! Fee tables to quote with in place of the on-chain ones.
+//!
+//! Previews how a fee change would alter quotes before it is proposed
+//! on-chain. With the `serde` feature a [`FeeOverride`] reads from a config
+//! file in basis points, e.g. as JSON:
+//!
+//! ```json
+//! {
+//!   "stablecoin": {
+//!     "normal": { "mint": 10, "redeem": 20 },
+//!     "mode_1": { "mint": 25, "redeem": 15 }
+//!   }
+//! }
+//! ```
+//!
+//! A table left out keeps the fees the context was loaded with.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::exchange_context::ExchangeContext;
+use crate::fee_controller::{FeePair, LevercoinFees, StablecoinFees};
+use crate::solana_clock::SolanaClock;
+
+/// Mint and redeem fees in basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeePairBps {
+  pub mint: u64,
+  pub redeem: u64,
+}
+
+impl From<FeePairBps> for FeePair {
+  fn from(bps: FeePairBps) -> FeePair {
+    FeePair::new(
+      UFix64::<N4>::new(bps.mint).into(),
+      UFix64::<N4>::new(bps.redeem).into(),
+    )
+  }
+}
+
+/// hyUSD fees by stability mode, in basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StablecoinFeesBps {
+  pub normal: FeePairBps,
+  pub mode_1: FeePairBps,
+}
+
+impl From<StablecoinFeesBps> for StablecoinFees {
+  fn from(bps: StablecoinFeesBps) -> StablecoinFees {
+    StablecoinFees::new(bps.normal.into(), bps.mode_1.into())
+  }
+}
+
+/// xSOL fees by stability mode, in basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevercoinFeesBps {
+  pub normal: FeePairBps,
+  pub mode_1: FeePairBps,
+  pub mode_2: FeePairBps,
+}
+
+impl From<LevercoinFeesBps> for LevercoinFees {
+  fn from(bps: LevercoinFeesBps) -> LevercoinFees {
+    LevercoinFees::new(bps.normal.into(), bps.mode_1.into(), bps.mode_2.into())
+  }
+}
+
+/// Fee tables replacing those of a loaded context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FeeOverride {
+  pub stablecoin: Option<StablecoinFeesBps>,
+  pub levercoin: Option<LevercoinFeesBps>,
+}
+
+impl FeeOverride {
+  /// Applies the override to `context`, validating the new fees.
+  pub fn apply<C: SolanaClock>(
+    &self,
+    context: ExchangeContext<C>,
+  ) -> Result<ExchangeContext<C>> {
+    let stablecoin_fees = self
+      .stablecoin
+      .map_or(context.stablecoin_fees(), StablecoinFees::from);
+    let levercoin_fees = self
+      .levercoin
+      .map_or(context.levercoin_fees(), LevercoinFees::from);
+    context.with_fees(stablecoin_fees, levercoin_fees)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+
+  use super::*;
+  use crate::fee_controller::FeeController;
+  use crate::stability_mode::StabilityMode::{Mode1, Normal};
+  use crate::test_fixtures::context;
+
+  fn pair(mint: u64, redeem: u64) -> FeePairBps {
+    FeePairBps { mint, redeem }
+  }
+
+  #[test]
+  fn overrides_one_table() -> Result<()> {
+    let fee_override = FeeOverride {
+      stablecoin: Some(StablecoinFeesBps {
+        normal: pair(5, 30),
+        mode_1: pair(50, 10),
+      }),
+      levercoin: None,
+    };
+    let context = fee_override.apply(context()?)?;
+    let stablecoin_fees = context.stablecoin_fees();
+    assert_eq!(stablecoin_fees.mint_fee(Normal)?, UFix64::new(5));
+    assert_eq!(stablecoin_fees.redeem_fee(Mode1)?, UFix64::new(10));
+    assert_eq!(context.levercoin_fees().mint_fee(Normal)?, UFix64::new(10));
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_full_fee() -> Result<()> {
+    let fee_override = FeeOverride {
+      stablecoin: None,
+      levercoin: Some(LevercoinFeesBps {
+        normal: pair(10, 20),
+        mode_1: pair(10, 10_000),
+        mode_2: pair(10, 20),
+      }),
+    };
+    assert!(fee_override.apply(context()?).is_err());
+    Ok(())
+  }
+}
@@ -1,7 +1,12 @@
+use anchor_lang::Result;
+use fix::prelude::UFixValue64;
+
 use crate::fee_controller::{FeePair, LevercoinFees, StablecoinFees};
 use crate::lst_sol_price::LstSolPrice;
 use crate::slippage_config::SlippageConfig;
+use crate::stability_mode::StabilityMode;
 use crate::total_sol_cache::TotalSolCache;
+use crate::withdrawal_fee::WithdrawalFee;
 use crate::yields::{YieldHarvestCache, YieldHarvestConfig};
 
 impl From<hylo_idl::exchange::types::LstSolPrice> for LstSolPrice {
@@ -66,3 +71,33 @@ impl From<SlippageConfig> for hylo_idl::exchange::types::SlippageConfig {
     }
   }
 }
+
+impl From<hylo_idl::exchange::types::SlippageConfig> for SlippageConfig {
+  fn from(idl: hylo_idl::exchange::types::SlippageConfig) -> Self {
+    SlippageConfig {
+      expected_token_out: idl.expected_token_out.into(),
+      slippage_tolerance: idl.slippage_tolerance.into(),
+    }
+  }
+}
+
+impl From<hylo_idl::exchange::types::StabilityMode> for StabilityMode {
+  fn from(idl: hylo_idl::exchange::types::StabilityMode) -> Self {
+    match idl {
+      hylo_idl::exchange::types::StabilityMode::Normal => StabilityMode::Normal,
+      hylo_idl::exchange::types::StabilityMode::Mode1 => StabilityMode::Mode1,
+      hylo_idl::exchange::types::StabilityMode::Mode2 => StabilityMode::Mode2,
+      hylo_idl::exchange::types::StabilityMode::Depeg => StabilityMode::Depeg,
+    }
+  }
+}
+
+impl TryFrom<hylo_idl::stability_pool::types::UFixValue64> for WithdrawalFee {
+  type Error = anchor_lang::error::Error;
+
+  fn try_from(
+    idl: hylo_idl::stability_pool::types::UFixValue64,
+  ) -> Result<WithdrawalFee> {
+    WithdrawalFee::try_from(UFixValue64::from(idl))
+  }
+}
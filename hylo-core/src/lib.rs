@@ -1,21 +1,53 @@
 #![allow(clippy::missing_errors_doc)]
 
+pub mod amount_format;
+pub mod analytics;
+pub mod bootstrap;
 pub mod conversion;
 pub mod error;
 pub mod exchange_context;
 pub mod exchange_math;
 pub mod fee_controller;
+pub mod fee_ledger;
+pub mod fee_override;
 #[cfg(feature = "offchain")]
 pub mod idl_type_bridge;
+pub mod lst_mix;
 pub mod lst_sol_price;
 pub mod lst_swap_config;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_pyth;
+#[cfg(feature = "offchain")]
+pub mod nav_history;
+pub mod operation_matrix;
+pub mod pool_metrics;
+pub mod pool_projection;
+pub mod prelude;
 pub mod pyth;
+pub mod rescale;
+pub mod revenue;
+pub mod shyusd_pnl;
 pub mod slippage_config;
+pub mod sol_denomination;
 pub mod solana_clock;
 pub mod stability_mode;
 pub mod stability_pool_math;
+pub mod stake_pool;
+#[cfg(test)]
+mod test_fixtures;
+#[cfg(feature = "offchain")]
+pub mod token_amount;
 pub mod total_sol_cache;
+#[cfg(feature = "offchain")]
+pub mod ui_units;
 pub mod util;
+#[cfg(feature = "offchain")]
+pub mod validated_args;
+#[cfg(feature = "offchain")]
+pub mod volatility;
+pub mod withdrawal_fee;
+#[cfg(feature = "offchain")]
+pub mod xsol_carry;
 pub mod yields;
 
 #[cfg(feature = "offchain")]
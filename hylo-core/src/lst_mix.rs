@@ -0,0 +1,213 @@
+//! LST mix of the exchange collateral, and routing toward target weights.
+//!
+//! hyUSD and xSOL mint against, and redeem for, any registered LST, and each
+//! trade moves SOL value into or out of that LST's vault. Integrators who
+//! care about the protocol's exposure to single stake pools can route mints
+//! to LSTs below their target share and redemptions to LSTs above it.
+//! [`LstMix`] values each vault in SOL against concentration targets and
+//! ranks LSTs for either direction.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::{LstMix as LstMixError, LstMixTargets};
+
+/// One LST vault's collateral against its target share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LstWeight {
+  pub mint: Pubkey,
+
+  /// Vault balance valued in SOL
+  pub sol_value: UFix64<N9>,
+
+  /// Fraction of total collateral held in this vault
+  pub share: UFix64<N4>,
+  pub target: UFix64<N4>,
+
+  /// SOL to add before the vault reaches its target share
+  pub shortfall: UFix64<N9>,
+
+  /// SOL held above the target share
+  pub excess: UFix64<N9>,
+}
+
+/// Collateral across every LST vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LstMix {
+  pub total_sol: UFix64<N9>,
+  pub weights: Vec<LstWeight>,
+}
+
+impl LstMix {
+  /// Weighs `vaults`, each a mint with its balance valued in SOL, against
+  /// target shares. Mints missing from `targets` are targeted at zero, so
+  /// routing drains them.
+  pub fn new(
+    vaults: &[(Pubkey, UFix64<N9>)],
+    targets: &[(Pubkey, UFix64<N4>)],
+  ) -> Result<LstMix> {
+    targets
+      .iter()
+      .try_fold(UFix64::<N4>::zero(), |total, (_, target)| {
+        total.checked_add(target)
+      })
+      .filter(|total| *total <= UFix64::one())
+      .ok_or(LstMixTargets)?;
+    let total_sol = vaults
+      .iter()
+      .try_fold(UFix64::<N9>::zero(), |total, (_, sol_value)| {
+        total.checked_add(sol_value)
+      })
+      .ok_or(LstMixError)?;
+    let weights = vaults
+      .iter()
+      .map(|(mint, sol_value)| {
+        let target = targets
+          .iter()
+          .find(|(target_mint, _)| target_mint == mint)
+          .map_or(UFix64::zero(), |(_, target)| *target);
+        weight(*mint, *sol_value, target, total_sol)
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(LstMix { total_sol, weights })
+  }
+
+  /// LSTs to mint against, furthest below target first.
+  #[must_use]
+  pub fn deposit_ranking(&self) -> Vec<&LstWeight> {
+    let mut ranking = self.weights.iter().collect::<Vec<_>>();
+    ranking.sort_by(|a, b| {
+      b.shortfall.cmp(&a.shortfall).then(a.excess.cmp(&b.excess))
+    });
+    ranking
+  }
+
+  /// LSTs to redeem `amount_sol` from, furthest above target first. Vaults
+  /// holding less than `amount_sol` are left out.
+  #[must_use]
+  pub fn redeem_ranking(&self, amount_sol: UFix64<N9>) -> Vec<&LstWeight> {
+    let mut ranking = self
+      .weights
+      .iter()
+      .filter(|weight| weight.sol_value >= amount_sol)
+      .collect::<Vec<_>>();
+    ranking.sort_by(|a, b| {
+      b.excess.cmp(&a.excess).then(a.shortfall.cmp(&b.shortfall))
+    });
+    ranking
+  }
+
+  /// Largest distance of any vault from its target, in SOL.
+  #[must_use]
+  pub fn max_deviation(&self) -> UFix64<N9> {
+    self
+      .weights
+      .iter()
+      .map(|weight| weight.shortfall.max(weight.excess))
+      .max()
+      .unwrap_or(UFix64::zero())
+  }
+}
+
+/// Targets splitting collateral evenly across `mints`, with any remainder
+/// basis points given to the first.
+#[must_use]
+pub fn equal_targets(mints: &[Pubkey]) -> Vec<(Pubkey, UFix64<N4>)> {
+  let count = u64::try_from(mints.len()).unwrap_or(u64::MAX).max(1);
+  let each = UFix64::<N4>::one().bits / count;
+  let remainder = UFix64::<N4>::one().bits % count;
+  mints
+    .iter()
+    .enumerate()
+    .map(|(i, mint)| {
+      let extra = if i == 0 { remainder } else { 0 };
+      (*mint, UFix64::new(each + extra))
+    })
+    .collect()
+}
+
+fn weight(
+  mint: Pubkey,
+  sol_value: UFix64<N9>,
+  target: UFix64<N4>,
+  total_sol: UFix64<N9>,
+) -> Result<LstWeight> {
+  let share = if total_sol == UFix64::zero() {
+    UFix64::zero()
+  } else {
+    UFix64::<N4>::one()
+      .mul_div_floor(sol_value, total_sol)
+      .ok_or(LstMixError)?
+  };
+  let target_sol = total_sol
+    .mul_div_floor(target, UFix64::one())
+    .ok_or(LstMixError)?;
+  Ok(LstWeight {
+    mint,
+    sol_value,
+    share,
+    target,
+    shortfall: target_sol.saturating_sub(&sol_value),
+    excess: sol_value.saturating_sub(&target_sol),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+
+  use super::*;
+
+  fn sol(amount: u64) -> UFix64<N9> {
+    UFix64::new(amount * 1_000_000_000)
+  }
+
+  #[test]
+  fn routes_toward_targets() -> Result<()> {
+    let (jito, hylo, other) = (
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+    );
+    let vaults = [(jito, sol(700)), (hylo, sol(200)), (other, sol(100))];
+    let targets = [(jito, UFix64::new(5_000)), (hylo, UFix64::new(5_000))];
+    let mix = LstMix::new(&vaults, &targets)?;
+    assert_eq!(mix.total_sol, sol(1_000));
+    assert_eq!(mix.weights[0].share, UFix64::new(7_000));
+    assert_eq!(mix.weights[0].excess, sol(200));
+    assert_eq!(mix.weights[1].shortfall, sol(300));
+    assert_eq!(mix.weights[2].excess, sol(100));
+    let deposit = mix.deposit_ranking();
+    assert_eq!(deposit.first().map(|weight| weight.mint), Some(hylo));
+    let redeem = mix.redeem_ranking(sol(150));
+    assert_eq!(
+      redeem.iter().map(|weight| weight.mint).collect::<Vec<_>>(),
+      vec![jito, hylo]
+    );
+    assert_eq!(mix.max_deviation(), sol(300));
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_targets_over_whole() {
+    let mint = Pubkey::new_unique();
+    let targets = [(mint, UFix64::new(6_000)), (mint, UFix64::new(5_000))];
+    assert!(LstMix::new(&[(mint, sol(1))], &targets).is_err());
+  }
+
+  #[test]
+  fn equal_split() -> Result<()> {
+    let mints = [
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+    ];
+    let targets = equal_targets(&mints);
+    assert_eq!(targets[0].1, UFix64::new(3_334));
+    assert_eq!(targets[2].1, UFix64::new(3_333));
+    let vaults = mints.map(|mint| (mint, UFix64::zero()));
+    let mix = LstMix::new(&vaults, &targets)?;
+    assert_eq!(mix.weights[0].share, UFix64::zero());
+    Ok(())
+  }
+}
@@ -3,7 +3,7 @@ use fix::prelude::*;
 
 use crate::error::CoreError::{
   LstLstPriceConversion, LstSolPriceConversion, LstSolPriceDelta,
-  LstSolPriceEpochOrder, LstSolPriceOutdated,
+  LstSolPriceEpochOrder, LstSolPriceOutdated, LstSolPriceZero,
 };
 
 /// Captures the true LST price in SOL for the current epoch.
@@ -42,9 +42,16 @@ impl LstSolPrice {
     }
   }
 
+  /// Price for `current_epoch`, which must be the cached epoch. Conversions
+  /// divide by the price, so zero is rejected.
   pub fn get_epoch_price(&self, current_epoch: u64) -> Result<UFix64<N9>> {
     if current_epoch == self.epoch {
-      self.price.try_into()
+      let price: UFix64<N9> = self.price.try_into()?;
+      if price == UFix64::zero() {
+        Err(LstSolPriceZero.into())
+      } else {
+        Ok(price)
+      }
     } else {
       Err(LstSolPriceOutdated.into())
     }
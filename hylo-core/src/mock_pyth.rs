@@ -0,0 +1,153 @@
+//! Pyth price update accounts for tests and devnet bootstrap.
+//!
+//! [`query_pyth_price`](crate::pyth::query_pyth_price) validates a posted
+//! `PriceUpdateV2`, so exercising it takes account bytes carrying the
+//! receiver's discriminator, an exponent matching the fixed point type, and a
+//! publish time and slot relative to the clock under test. [`MockPriceUpdate`]
+//! builds them from a price, confidence and publish time.
+//!
+//! Only compiled with the `test-utils` feature.
+
+use anchor_lang::prelude::{Pubkey, Result};
+use anchor_lang::AccountSerialize;
+use fix::prelude::*;
+use fix::typenum::Integer;
+use pyth_solana_receiver_sdk::price_update::{
+  FeedId, PriceFeedMessage, PriceUpdateV2, VerificationLevel,
+};
+
+use crate::error::TestUtilsError::MockPriceUpdate as MockPriceUpdateError;
+use crate::pyth::SOL_USD;
+
+/// Price update as the Pyth receiver would post it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MockPriceUpdate<Exp: Integer> {
+  pub feed_id: FeedId,
+  pub price: UFix64<Exp>,
+  pub conf: UFix64<Exp>,
+  pub publish_time: i64,
+  pub posted_slot: u64,
+  pub verification_level: VerificationLevel,
+}
+
+impl<Exp: Integer> MockPriceUpdate<Exp> {
+  /// Fully verified SOL/USD update published at `publish_time` and posted at
+  /// `posted_slot`.
+  #[must_use]
+  pub fn sol_usd(
+    price: UFix64<Exp>,
+    conf: UFix64<Exp>,
+    publish_time: i64,
+    posted_slot: u64,
+  ) -> MockPriceUpdate<Exp> {
+    MockPriceUpdate {
+      feed_id: SOL_USD,
+      price,
+      conf,
+      publish_time,
+      posted_slot,
+      verification_level: VerificationLevel::Full,
+    }
+  }
+
+  /// Price update with the EMA price and previous publish time matching the
+  /// spot values.
+  pub fn price_update(&self) -> Result<PriceUpdateV2> {
+    let price =
+      i64::try_from(self.price.bits).map_err(|_| MockPriceUpdateError)?;
+    Ok(PriceUpdateV2 {
+      write_authority: Pubkey::default(),
+      verification_level: self.verification_level,
+      price_message: PriceFeedMessage {
+        feed_id: self.feed_id,
+        price,
+        conf: self.conf.bits,
+        exponent: Exp::to_i32(),
+        publish_time: self.publish_time,
+        prev_publish_time: self.publish_time,
+        ema_price: price,
+        ema_conf: self.conf.bits,
+      },
+      posted_slot: self.posted_slot,
+    })
+  }
+
+  /// Account data of the price update, discriminator included. The account
+  /// should be owned by `pyth_solana_receiver_sdk::ID`.
+  pub fn account_data(&self) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(PriceUpdateV2::LEN);
+    self.price_update()?.try_serialize(&mut data)?;
+    Ok(data)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Clock;
+  use anchor_lang::AccountDeserialize;
+
+  use super::*;
+  use crate::error::CoreError::{
+    PythOracleOutdated, PythOracleVerificationLevel,
+  };
+  use crate::pyth::{query_pyth_price, OracleConfig, PriceRange};
+
+  const PUBLISH_TIME: i64 = 1_700_000_000;
+
+  fn clock(unix_timestamp: i64) -> Clock {
+    Clock {
+      slot: 1_000,
+      unix_timestamp,
+      ..Clock::default()
+    }
+  }
+
+  fn config() -> OracleConfig<N8> {
+    OracleConfig::new(60, UFix64::new(200_000))
+  }
+
+  fn update() -> MockPriceUpdate<N8> {
+    MockPriceUpdate::sol_usd(
+      UFix64::new(15_000_000_000),
+      UFix64::new(10_000_000),
+      PUBLISH_TIME,
+      1_000,
+    )
+  }
+
+  #[test]
+  fn account_data_passes_validation() -> Result<()> {
+    let data = update().account_data()?;
+    let oracle = PriceUpdateV2::try_deserialize(&mut data.as_slice())?;
+    let range = query_pyth_price(&clock(PUBLISH_TIME + 30), &oracle, config())?;
+    assert_eq!(
+      range,
+      PriceRange::new(UFix64::new(14_990_000_000), UFix64::new(15_010_000_000))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn mocks_rejected_updates() -> Result<()> {
+    let stale = update().price_update()?;
+    assert_eq!(
+      query_pyth_price(&clock(PUBLISH_TIME + 61), &stale, config()),
+      Err(PythOracleOutdated.into())
+    );
+    let partial = MockPriceUpdate {
+      verification_level: VerificationLevel::Partial { num_signatures: 3 },
+      ..update()
+    }
+    .price_update()?;
+    assert_eq!(
+      query_pyth_price(&clock(PUBLISH_TIME), &partial, config()),
+      Err(PythOracleVerificationLevel.into())
+    );
+    let overflow = MockPriceUpdate {
+      price: UFix64::new(u64::MAX),
+      ..update()
+    };
+    assert!(overflow.price_update().is_err());
+    Ok(())
+  }
+}
@@ -0,0 +1,412 @@
+//! Historical NAV and collateral ratio series.
+//!
+//! Replays stored exchange and stability pool events to track total SOL and
+//! token supplies, then prices that state at each archived SOL/USD sample.
+//! Events the replay does not model (yield harvests, fee mints) drift the
+//! supplies, so interleave periodic [`ExchangeStats`] captures from
+//! `get_stats` to re-anchor them.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::analytics::{self, Reported};
+use crate::error::CoreError::NavHistory;
+use crate::exchange_math::depeg_stablecoin_nav;
+use crate::idl::exchange::events::{
+  ExchangeStats, MintLevercoinEventV2, MintStablecoinEventV2,
+  RedeemLevercoinEventV2, RedeemStablecoinEventV2, SwapLeverToStableEventV1,
+  SwapStableToLeverEventV1, UpdateLstPricesEvent,
+};
+use crate::idl::stability_pool::events::{
+  StabilityPoolStats, UserDepositEvent,
+};
+use crate::pyth::validate_price;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Decoded events that move NAV or collateral ratio inputs.
+pub enum HistoryEvent {
+  ExchangeStats(ExchangeStats),
+  PoolStats(StabilityPoolStats),
+  LstPrices(UpdateLstPricesEvent),
+  MintStablecoin(MintStablecoinEventV2),
+  RedeemStablecoin(RedeemStablecoinEventV2),
+  MintLevercoin(MintLevercoinEventV2),
+  RedeemLevercoin(RedeemLevercoinEventV2),
+  StableToLever(SwapStableToLeverEventV1),
+  LeverToStable(SwapLeverToStableEventV1),
+  Deposit(UserDepositEvent),
+}
+
+/// Event with the slot it landed in.
+pub struct StoredEvent {
+  pub slot: u64,
+  pub event: HistoryEvent,
+}
+
+/// Archived SOL/USD spot price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchivedPrice {
+  pub slot: u64,
+  pub timestamp: i64,
+  pub price: UFix64<N8>,
+}
+
+impl ArchivedPrice {
+  /// Reads the spot price of an archived price update, skipping the
+  /// freshness checks that only make sense against a live clock.
+  pub fn from_price_update(update: &PriceUpdateV2) -> Result<ArchivedPrice> {
+    let message = &update.price_message;
+    Ok(ArchivedPrice {
+      slot: update.posted_slot,
+      timestamp: message.publish_time,
+      price: validate_price(message.price, message.exponent)?,
+    })
+  }
+}
+
+/// Protocol inputs to NAV and CR as of the last applied event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryState {
+  pub total_sol: UFix64<N9>,
+  pub stablecoin_supply: UFix64<N6>,
+  pub levercoin_supply: UFix64<N6>,
+  pub lp_token_nav: Option<UFix64<N6>>,
+}
+
+impl HistoryState {
+  /// Advances the state past one event.
+  pub fn apply(self, event: &HistoryEvent) -> Result<HistoryState> {
+    match event {
+      HistoryEvent::ExchangeStats(stats) => Ok(HistoryState {
+        stablecoin_supply: stats.stablecoin_supply.try_into()?,
+        levercoin_supply: stats.levercoin_supply.try_into()?,
+        ..self
+      }),
+      HistoryEvent::PoolStats(stats) => Ok(HistoryState {
+        lp_token_nav: Some(stats.lp_token_nav.try_into()?),
+        ..self
+      }),
+      HistoryEvent::Deposit(event) => Ok(HistoryState {
+        lp_token_nav: Some(event.lp_token_nav.try_into()?),
+        ..self
+      }),
+      HistoryEvent::LstPrices(event) => Ok(HistoryState {
+        total_sol: event.new_total_sol.try_into()?,
+        ..self
+      }),
+      HistoryEvent::MintStablecoin(event) => Ok(HistoryState {
+        total_sol: add(
+          self.total_sol,
+          lst_in_sol(event.collateral_deposited, event.lst_sol_price)?,
+        )?,
+        stablecoin_supply: add(
+          self.stablecoin_supply,
+          event.minted.try_into()?,
+        )?,
+        ..self
+      }),
+      HistoryEvent::RedeemStablecoin(event) => Ok(HistoryState {
+        total_sol: self.total_sol.saturating_sub(&lst_in_sol(
+          event.collateral_withdrawn,
+          event.lst_sol_price,
+        )?),
+        stablecoin_supply: sub(self.stablecoin_supply, event.redeemed)?,
+        ..self
+      }),
+      HistoryEvent::MintLevercoin(event) => Ok(HistoryState {
+        total_sol: add(
+          self.total_sol,
+          lst_in_sol(event.collateral_deposited, event.lst_sol_price)?,
+        )?,
+        levercoin_supply: add(self.levercoin_supply, event.minted.try_into()?)?,
+        ..self
+      }),
+      HistoryEvent::RedeemLevercoin(event) => Ok(HistoryState {
+        total_sol: self.total_sol.saturating_sub(&lst_in_sol(
+          event.collateral_withdrawn,
+          event.lst_sol_price,
+        )?),
+        levercoin_supply: sub(self.levercoin_supply, event.redeemed)?,
+        ..self
+      }),
+      HistoryEvent::StableToLever(event) => Ok(HistoryState {
+        stablecoin_supply: sub(
+          self.stablecoin_supply,
+          event.stablecoin_burned,
+        )?,
+        levercoin_supply: add(
+          self.levercoin_supply,
+          event.levercoin_minted.try_into()?,
+        )?,
+        ..self
+      }),
+      HistoryEvent::LeverToStable(event) => {
+        let minted = add(
+          event.stablecoin_minted_user.try_into()?,
+          event.stablecoin_minted_fees.try_into()?,
+        )?;
+        Ok(HistoryState {
+          stablecoin_supply: add(self.stablecoin_supply, minted)?,
+          levercoin_supply: sub(self.levercoin_supply, event.levercoin_burned)?,
+          ..self
+        })
+      }
+    }
+  }
+
+  /// Prices the state at one SOL/USD sample.
+  #[must_use]
+  pub fn sample(self, price: &ArchivedPrice) -> NavSample {
+    let collateral_ratio = analytics::collateral_ratio(
+      self.total_sol,
+      price.price,
+      self.stablecoin_supply,
+    );
+    let hyusd_nav = if collateral_ratio.value >= UFix64::one() {
+      Reported::exact(UFix64::one())
+    } else {
+      depeg_stablecoin_nav(self.total_sol, price.price, self.stablecoin_supply)
+        .map_or_else(
+          |_| Reported::saturated(UFix64::new(u64::MAX)),
+          Reported::exact,
+        )
+    };
+    let xsol_nav = analytics::levercoin_nav(
+      self.total_sol,
+      price.price,
+      self.stablecoin_supply,
+      hyusd_nav.value,
+      self.levercoin_supply,
+    );
+    NavSample {
+      slot: price.slot,
+      timestamp: price.timestamp,
+      sol_usd_price: price.price,
+      hyusd_nav,
+      xsol_nav,
+      shyusd_nav: self.lp_token_nav,
+      collateral_ratio,
+    }
+  }
+}
+
+/// Adds an event amount to a tracked quantity.
+fn add<Exp>(tracked: UFix64<Exp>, amount: UFix64<Exp>) -> Result<UFix64<Exp>> {
+  tracked.checked_add(&amount).ok_or(NavHistory.into())
+}
+
+/// Subtracts an event amount, flooring at zero for events that precede the
+/// initial state.
+fn sub<Exp, V>(tracked: UFix64<Exp>, amount: V) -> Result<UFix64<Exp>>
+where
+  UFix64<Exp>: TryFrom<V, Error = Error>,
+{
+  Ok(tracked.saturating_sub(&amount.try_into()?))
+}
+
+/// Values an LST amount in SOL at the event's LST/SOL price.
+fn lst_in_sol<V>(amount: V, lst_sol_price: V) -> Result<UFix64<N9>>
+where
+  UFix64<N9>: TryFrom<V, Error = Error>,
+{
+  let amount: UFix64<N9> = amount.try_into()?;
+  amount
+    .mul_div_floor(lst_sol_price.try_into()?, UFix64::one())
+    .ok_or(NavHistory.into())
+}
+
+/// NAV and CR at one archived price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavSample {
+  pub slot: u64,
+  pub timestamp: i64,
+  pub sol_usd_price: UFix64<N8>,
+  pub hyusd_nav: Reported<UFix64<N9>>,
+  pub xsol_nav: Reported<UFix64<N9>>,
+
+  /// Last sHYUSD NAV seen in pool events, if any
+  pub shyusd_nav: Option<UFix64<N6>>,
+  pub collateral_ratio: Reported<UFix64<N9>>,
+}
+
+/// Sampling rate of a reconstructed history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+  /// One sample per archived price
+  Slot,
+
+  /// Last sample of each UTC day
+  Daily,
+}
+
+/// One point of a chart series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesPoint<T> {
+  pub slot: u64,
+  pub timestamp: i64,
+  pub value: T,
+}
+
+/// Per-token series ready for charting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NavSeries {
+  pub hyusd_nav: Vec<SeriesPoint<Reported<UFix64<N9>>>>,
+  pub xsol_nav: Vec<SeriesPoint<Reported<UFix64<N9>>>>,
+  pub shyusd_nav: Vec<SeriesPoint<UFix64<N6>>>,
+  pub collateral_ratio: Vec<SeriesPoint<Reported<UFix64<N9>>>>,
+}
+
+impl NavSeries {
+  /// Splits samples into one series per metric. sHYUSD points start at the
+  /// first sample with a known pool NAV.
+  #[must_use]
+  pub fn from_samples(samples: &[NavSample]) -> NavSeries {
+    let series = |value: fn(&NavSample) -> Reported<UFix64<N9>>| -> Vec<_> {
+      samples
+        .iter()
+        .map(|s| SeriesPoint {
+          slot: s.slot,
+          timestamp: s.timestamp,
+          value: value(s),
+        })
+        .collect()
+    };
+    NavSeries {
+      hyusd_nav: series(|s| s.hyusd_nav),
+      xsol_nav: series(|s| s.xsol_nav),
+      shyusd_nav: samples
+        .iter()
+        .filter_map(|s| {
+          s.shyusd_nav.map(|value| SeriesPoint {
+            slot: s.slot,
+            timestamp: s.timestamp,
+            value,
+          })
+        })
+        .collect(),
+      collateral_ratio: series(|s| s.collateral_ratio),
+    }
+  }
+}
+
+/// Replays `events` from `initial`, sampling NAV and CR at each price.
+///
+/// Events and prices must be sorted by slot. Events in a price's slot are
+/// applied before that price is sampled.
+pub fn reconstruct(
+  initial: HistoryState,
+  events: &[StoredEvent],
+  prices: &[ArchivedPrice],
+  resolution: Resolution,
+) -> Result<Vec<NavSample>> {
+  let (_, _, samples) = prices.iter().try_fold(
+    (initial, events, Vec::with_capacity(prices.len())),
+    |(state, pending, mut samples), price| {
+      let (applied, rest) =
+        pending.split_at(pending.partition_point(|e| e.slot <= price.slot));
+      let state = applied
+        .iter()
+        .try_fold(state, |state, stored| state.apply(&stored.event))?;
+      samples.push(state.sample(price));
+      Ok::<_, Error>((state, rest, samples))
+    },
+  )?;
+  Ok(match resolution {
+    Resolution::Slot => samples,
+    Resolution::Daily => samples
+      .into_iter()
+      .map(|s| (s.timestamp.div_euclid(SECONDS_PER_DAY), s))
+      .collect::<BTreeMap<_, _>>()
+      .into_values()
+      .collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::{Pubkey, Result};
+  use fix::prelude::*;
+
+  use super::*;
+  use crate::idl::exchange::types::UFixValue64;
+
+  fn value(bits: u64, exp: i8) -> UFixValue64 {
+    UFixValue64 { bits, exp }
+  }
+
+  fn price(slot: u64, timestamp: i64, bits: u64) -> ArchivedPrice {
+    ArchivedPrice {
+      slot,
+      timestamp,
+      price: UFix64::new(bits),
+    }
+  }
+
+  fn mint_stablecoin(slot: u64, minted: u64, sol: u64) -> StoredEvent {
+    StoredEvent {
+      slot,
+      event: HistoryEvent::MintStablecoin(MintStablecoinEventV2 {
+        minted: value(minted, -6),
+        nav: value(1_000_000_000, -9),
+        sol_usd_price: value(10_000_000_000, -8),
+        lst_mint: Pubkey::new_unique(),
+        lst_sol_price: value(1_000_000_000, -9),
+        collateral_deposited: value(sol, -9),
+        fees_deposited: value(0, -9),
+      }),
+    }
+  }
+
+  #[test]
+  fn events_apply_before_sample() -> Result<()> {
+    let events = [mint_stablecoin(10, 100_000_000, 20_000_000_000)];
+    let prices = [price(9, 0, 10_000_000_000), price(10, 1, 10_000_000_000)];
+    let samples =
+      reconstruct(HistoryState::default(), &events, &prices, Resolution::Slot)?;
+    assert_eq!(
+      samples.first().map(|s| s.collateral_ratio),
+      Some(Reported::exact(UFix64::new(u64::MAX)))
+    );
+    assert_eq!(
+      samples.get(1).map(|s| s.collateral_ratio),
+      Some(Reported::exact(UFix64::new(20_000_000_000)))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn depeg_nav_below_one() -> Result<()> {
+    let events = [mint_stablecoin(1, 100_000_000, 20_000_000_000)];
+    let prices = [price(2, 0, 400_000_000)];
+    let samples =
+      reconstruct(HistoryState::default(), &events, &prices, Resolution::Slot)?;
+    let sample = samples.first().ok_or(NavHistory)?;
+    assert_eq!(sample.hyusd_nav, Reported::exact(UFix64::new(800_000_000)));
+    assert_eq!(sample.xsol_nav, Reported::exact(UFix64::one()));
+    Ok(())
+  }
+
+  #[test]
+  fn daily_keeps_last_sample() -> Result<()> {
+    let prices = [
+      price(1, 100, 10_000_000_000),
+      price(2, 200, 11_000_000_000),
+      price(3, SECONDS_PER_DAY + 1, 12_000_000_000),
+    ];
+    let state = HistoryState {
+      total_sol: UFix64::new(1_000_000_000),
+      ..HistoryState::default()
+    };
+    let samples = reconstruct(state, &[], &prices, Resolution::Daily)?;
+    let slots: Vec<u64> = samples.iter().map(|s| s.slot).collect();
+    assert_eq!(slots, vec![2, 3]);
+    let series = NavSeries::from_samples(&samples);
+    assert_eq!(series.xsol_nav.len(), 2);
+    assert!(series.shyusd_nav.is_empty());
+    Ok(())
+  }
+}
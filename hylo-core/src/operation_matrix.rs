@@ -0,0 +1,186 @@
+//! Operations the protocol permits in each stability mode.
+//!
+//! As the collateral ratio falls through the stability thresholds, the
+//! exchange stops issuing hyUSD and then xSOL, and the stability pool
+//! rebalances in the direction that restores the ratio. [`OPERATION_MATRIX`]
+//! is the single table of those rules, and [`ProtocolOperation::check`]
+//! fails with the error the program raises for a blocked operation.
+//!
+//! | Operation                    | Normal | Mode1 | Mode2 | Depeg |
+//! |------------------------------|:------:|:-----:|:-----:|:-----:|
+//! | Mint hyUSD                   | yes    | yes   |       |       |
+//! | Redeem hyUSD                 | yes    | yes   | yes   | yes   |
+//! | Mint xSOL                    | yes    | yes   | yes   |       |
+//! | Redeem xSOL                  | yes    | yes   | yes   |       |
+//! | Swap hyUSD to xSOL           | yes    | yes   | yes   |       |
+//! | Swap xSOL to hyUSD           | yes    | yes   |       |       |
+//! | Swap LST                     | yes    | yes   | yes   | yes   |
+//! | Deposit to stability pool    | yes    | yes   | yes   | yes   |
+//! | Withdraw from stability pool | yes    | yes   | yes   | yes   |
+//! | Rebalance hyUSD to xSOL      |        | yes   | yes   |       |
+//! | Rebalance xSOL to hyUSD      | yes    |       |       |       |
+
+use std::fmt::Display;
+
+use anchor_lang::prelude::*;
+
+use crate::error::CoreError::{
+  self, NoValidLevercoinMintFee, NoValidLevercoinRedeemFee,
+  NoValidStablecoinMintFee, NoValidSwapFee, RebalanceDisabled,
+};
+use crate::operation_matrix::ProtocolOperation::{
+  Deposit, LstSwap, MintLevercoin, MintStablecoin, RebalanceLeverToStable,
+  RebalanceStableToLever, RedeemLevercoin, RedeemStablecoin, SwapLeverToStable,
+  SwapStableToLever, Withdraw,
+};
+use crate::stability_mode::StabilityMode;
+
+/// Operation of the exchange or stability pool gated by stability mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProtocolOperation {
+  MintStablecoin,
+  RedeemStablecoin,
+  MintLevercoin,
+  RedeemLevercoin,
+  SwapStableToLever,
+  SwapLeverToStable,
+  LstSwap,
+  Deposit,
+  Withdraw,
+  RebalanceStableToLever,
+  RebalanceLeverToStable,
+}
+
+/// Modes each operation runs in, as `[Normal, Mode1, Mode2, Depeg]`. Rows
+/// follow [`ProtocolOperation::ALL`].
+pub const OPERATION_MATRIX: [(ProtocolOperation, [bool; 4]); 11] = [
+  (MintStablecoin, [true, true, false, false]),
+  (RedeemStablecoin, [true, true, true, true]),
+  (MintLevercoin, [true, true, true, false]),
+  (RedeemLevercoin, [true, true, true, false]),
+  (SwapStableToLever, [true, true, true, false]),
+  (SwapLeverToStable, [true, true, false, false]),
+  (LstSwap, [true, true, true, true]),
+  (Deposit, [true, true, true, true]),
+  (Withdraw, [true, true, true, true]),
+  (RebalanceStableToLever, [false, true, true, false]),
+  (RebalanceLeverToStable, [true, false, false, false]),
+];
+
+impl ProtocolOperation {
+  /// Every operation, in [`OPERATION_MATRIX`] order.
+  pub const ALL: [ProtocolOperation; 11] = [
+    MintStablecoin,
+    RedeemStablecoin,
+    MintLevercoin,
+    RedeemLevercoin,
+    SwapStableToLever,
+    SwapLeverToStable,
+    LstSwap,
+    Deposit,
+    Withdraw,
+    RebalanceStableToLever,
+    RebalanceLeverToStable,
+  ];
+
+  /// Whether the program runs the operation in `mode`.
+  #[must_use]
+  pub fn is_active(self, mode: StabilityMode) -> bool {
+    OPERATION_MATRIX[self as usize].1[mode as usize]
+  }
+
+  /// Modes the operation runs in, from healthiest to most stressed.
+  pub fn active_modes(self) -> impl Iterator<Item = StabilityMode> {
+    StabilityMode::iter().filter(move |mode| self.is_active(*mode))
+  }
+
+  /// Error the program raises when the operation is attempted in a mode
+  /// that blocks it, `None` for operations every mode allows.
+  #[must_use]
+  pub fn blocked_error(self) -> Option<CoreError> {
+    match self {
+      MintStablecoin => Some(NoValidStablecoinMintFee),
+      MintLevercoin => Some(NoValidLevercoinMintFee),
+      RedeemLevercoin => Some(NoValidLevercoinRedeemFee),
+      SwapStableToLever | SwapLeverToStable => Some(NoValidSwapFee),
+      RebalanceStableToLever | RebalanceLeverToStable => {
+        Some(RebalanceDisabled)
+      }
+      RedeemStablecoin | LstSwap | Deposit | Withdraw => None,
+    }
+  }
+
+  /// Checks that the operation runs in `mode`.
+  pub fn check(self, mode: StabilityMode) -> Result<()> {
+    match self.blocked_error() {
+      Some(error) if !self.is_active(mode) => Err(error.into()),
+      _ => Ok(()),
+    }
+  }
+}
+
+impl Display for ProtocolOperation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MintStablecoin => f.write_str("mint_stablecoin"),
+      RedeemStablecoin => f.write_str("redeem_stablecoin"),
+      MintLevercoin => f.write_str("mint_levercoin"),
+      RedeemLevercoin => f.write_str("redeem_levercoin"),
+      SwapStableToLever => f.write_str("swap_stable_to_lever"),
+      SwapLeverToStable => f.write_str("swap_lever_to_stable"),
+      LstSwap => f.write_str("swap_lst"),
+      Deposit => f.write_str("user_deposit"),
+      Withdraw => f.write_str("user_withdraw"),
+      RebalanceStableToLever => f.write_str("rebalance_stable_to_lever"),
+      RebalanceLeverToStable => f.write_str("rebalance_lever_to_stable"),
+    }
+  }
+}
+
+impl StabilityMode {
+  /// Operations the program runs in this mode, in
+  /// [`ProtocolOperation::ALL`] order.
+  pub fn active_operations(self) -> impl Iterator<Item = ProtocolOperation> {
+    ProtocolOperation::ALL
+      .into_iter()
+      .filter(move |operation| operation.is_active(self))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::stability_mode::StabilityMode::{Depeg, Mode1, Mode2, Normal};
+
+  #[test]
+  fn matrix_rows_follow_operations() {
+    let rows = OPERATION_MATRIX.map(|(operation, _)| operation);
+    assert_eq!(rows, ProtocolOperation::ALL);
+  }
+
+  #[test]
+  fn blocked_operations_have_errors() {
+    ProtocolOperation::ALL.into_iter().for_each(|operation| {
+      let always = operation.active_modes().count() == StabilityMode::ALL.len();
+      assert_eq!(operation.blocked_error().is_none(), always);
+    });
+  }
+
+  #[test]
+  fn check_raises_program_errors() {
+    assert_eq!(MintStablecoin.check(Mode1), Ok(()));
+    assert_eq!(
+      MintStablecoin.check(Mode2),
+      Err(NoValidStablecoinMintFee.into())
+    );
+    assert_eq!(
+      RebalanceLeverToStable.check(Mode1),
+      Err(RebalanceDisabled.into())
+    );
+    assert_eq!(Withdraw.check(Depeg), Ok(()));
+    assert_eq!(
+      Normal.active_operations().count(),
+      ProtocolOperation::ALL.len() - 1
+    );
+  }
+}
@@ -0,0 +1,169 @@
+//! Health indicators for the stability pool.
+//!
+//! The pool holds hyUSD until a rebalance swaps part of it into xSOL, after
+//! which the split by value moves with the xSOL NAV. These metrics report
+//! that split, how much of the hyUSD supply is staked, and how far the split
+//! has drifted since the last rebalance.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::PoolComposition as PoolCompositionError;
+
+/// Split of the stability pool by USD value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolComposition {
+  pub stablecoin_value: UFix64<N6>,
+  pub levercoin_value: UFix64<N6>,
+
+  /// Fraction of pool value held in hyUSD
+  pub stablecoin_share: UFix64<N4>,
+}
+
+impl PoolComposition {
+  /// Values each side of the pool at its NAV.
+  /// An empty pool reports a hyUSD share of 100%.
+  pub fn new(
+    stablecoin_nav: UFix64<N9>,
+    stablecoin_in_pool: UFix64<N6>,
+    levercoin_nav: UFix64<N9>,
+    levercoin_in_pool: UFix64<N6>,
+  ) -> Result<PoolComposition> {
+    let stablecoin_value = stablecoin_in_pool
+      .mul_div_floor(stablecoin_nav, UFix64::one())
+      .ok_or(PoolCompositionError)?;
+    let levercoin_value = levercoin_in_pool
+      .mul_div_floor(levercoin_nav, UFix64::one())
+      .ok_or(PoolCompositionError)?;
+    let total = stablecoin_value
+      .checked_add(&levercoin_value)
+      .ok_or(PoolCompositionError)?;
+    let stablecoin_share = if total == UFix64::zero() {
+      UFix64::one()
+    } else {
+      share(stablecoin_value, total)?
+    };
+    Ok(PoolComposition {
+      stablecoin_value,
+      levercoin_value,
+      stablecoin_share,
+    })
+  }
+
+  /// Fraction of pool value held in xSOL.
+  #[must_use]
+  pub fn levercoin_share(&self) -> UFix64<N4> {
+    UFix64::one().saturating_sub(&self.stablecoin_share)
+  }
+}
+
+/// Change in hyUSD share between two compositions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompositionDrift {
+  /// Absolute change in the hyUSD share of pool value
+  pub magnitude: UFix64<N4>,
+
+  /// Whether value moved from hyUSD to xSOL
+  pub toward_levercoin: bool,
+}
+
+impl CompositionDrift {
+  /// Drift from the composition right after the last rebalance.
+  #[must_use]
+  pub fn since(
+    at_rebalance: &PoolComposition,
+    current: &PoolComposition,
+  ) -> CompositionDrift {
+    let (from, to) = (at_rebalance.stablecoin_share, current.stablecoin_share);
+    CompositionDrift {
+      magnitude: from.saturating_sub(&to).max(to.saturating_sub(&from)),
+      toward_levercoin: to < from,
+    }
+  }
+}
+
+/// Fraction of hyUSD supply deposited in the stability pool.
+/// Zero supply reports zero.
+pub fn pool_supply_share(
+  stablecoin_in_pool: UFix64<N6>,
+  stablecoin_supply: UFix64<N6>,
+) -> Result<UFix64<N4>> {
+  if stablecoin_supply == UFix64::zero() {
+    Ok(UFix64::zero())
+  } else {
+    share(stablecoin_in_pool, stablecoin_supply)
+  }
+}
+
+/// `part / whole` floored to basis points.
+fn share(part: UFix64<N6>, whole: UFix64<N6>) -> Result<UFix64<N4>> {
+  part
+    .mul_div_floor(UFix64::<N6>::one(), whole)
+    .map(UFix64::convert)
+    .ok_or(PoolCompositionError.into())
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn composition_split() -> Result<()> {
+    let composition = PoolComposition::new(
+      UFix64::one(),
+      UFix64::new(750_000_000),
+      UFix64::new(2_500_000_000),
+      UFix64::new(100_000_000),
+    )?;
+    assert_eq!(composition.levercoin_value, UFix64::new(250_000_000));
+    assert_eq!(composition.stablecoin_share, UFix64::new(7_500));
+    assert_eq!(composition.levercoin_share(), UFix64::new(2_500));
+    Ok(())
+  }
+
+  #[test]
+  fn composition_empty_pool() -> Result<()> {
+    let composition = PoolComposition::new(
+      UFix64::one(),
+      UFix64::zero(),
+      UFix64::one(),
+      UFix64::zero(),
+    )?;
+    assert_eq!(composition.stablecoin_share, UFix64::one());
+    Ok(())
+  }
+
+  #[test]
+  fn drift_toward_levercoin() -> Result<()> {
+    let at_rebalance = PoolComposition::new(
+      UFix64::one(),
+      UFix64::new(800_000_000),
+      UFix64::one(),
+      UFix64::new(200_000_000),
+    )?;
+    let current = PoolComposition::new(
+      UFix64::one(),
+      UFix64::new(800_000_000),
+      UFix64::new(3_000_000_000),
+      UFix64::new(200_000_000),
+    )?;
+    let drift = CompositionDrift::since(&at_rebalance, &current);
+    assert_eq!(drift.magnitude, UFix64::new(2_286));
+    assert!(drift.toward_levercoin);
+    Ok(())
+  }
+
+  #[test]
+  fn supply_share() -> Result<()> {
+    let share = pool_supply_share(UFix64::new(25), UFix64::new(100))?;
+    assert_eq!(share, UFix64::new(2_500));
+    assert_eq!(
+      pool_supply_share(UFix64::new(25), UFix64::zero())?,
+      UFix64::zero()
+    );
+    Ok(())
+  }
+}
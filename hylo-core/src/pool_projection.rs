@@ -0,0 +1,286 @@
+//! Projected stability pool growth under assumed flows.
+//!
+//! Plans for emissions or a deposit cap turn on how the pool grows: each
+//! epoch's deposits mint sHYUSD at the current LP token NAV, withdrawals burn
+//! it pro rata, and harvested yield is added to the hyUSD in the pool. The
+//! same yield spread over a larger pool earns each sHYUSD less, so
+//! [`PoolProjection`] reports per-epoch yield against the first epoch's as
+//! dilution.
+//!
+//! NAVs are held at the assumed values throughout, and withdrawal fees are
+//! left out, so withdrawals take their full pro rata share of the pool.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::PoolProjection as PoolProjectionError;
+use crate::stability_pool_math::{
+  amount_token_to_withdraw, lp_token_nav, lp_token_out,
+  remaining_deposit_capacity, stability_pool_cap,
+};
+
+/// Token balances of the stability pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolBalances {
+  pub stablecoin_in_pool: UFix64<N6>,
+  pub levercoin_in_pool: UFix64<N6>,
+  pub lp_token_supply: UFix64<N6>,
+}
+
+/// Flows assumed for one epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochFlows {
+  /// hyUSD offered for deposit
+  pub deposits: UFix64<N6>,
+
+  /// sHYUSD redeemed
+  pub withdrawals: UFix64<N6>,
+
+  /// hyUSD harvested into the pool at the end of the epoch
+  pub yield_to_pool: UFix64<N6>,
+}
+
+/// Pool at the end of one projected epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectedEpoch {
+  pub balances: PoolBalances,
+
+  /// Deposits taken before the pool reached its cap
+  pub deposits_accepted: UFix64<N6>,
+  pub pool_cap: UFix64<N6>,
+  pub lp_token_nav: UFix64<N6>,
+
+  /// Harvested yield over the pool's capitalization before the harvest
+  pub yield_rate: UFix64<N9>,
+
+  /// Fall in `yield_rate` from the first epoch
+  pub dilution: UFix64<N4>,
+}
+
+/// Assumptions held constant across a projection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolProjection {
+  pub stablecoin_nav: UFix64<N9>,
+  pub levercoin_nav: UFix64<N9>,
+
+  /// Capitalization above which deposits are turned away
+  pub cap_limit: Option<UFix64<N6>>,
+}
+
+impl PoolProjection {
+  /// Projects `start` through one epoch per entry of `flows`.
+  pub fn project(
+    &self,
+    start: PoolBalances,
+    flows: &[EpochFlows],
+  ) -> Result<Vec<ProjectedEpoch>> {
+    let epochs = flows
+      .iter()
+      .try_fold(
+        (start, Vec::with_capacity(flows.len())),
+        |(balances, mut epochs), epoch_flows| {
+          let epoch = self.step(balances, epoch_flows)?;
+          epochs.push(epoch);
+          Ok::<_, Error>((epoch.balances, epochs))
+        },
+      )?
+      .1;
+    let first_rate = epochs
+      .first()
+      .map_or(UFix64::zero(), |epoch| epoch.yield_rate);
+    epochs
+      .into_iter()
+      .map(|epoch| {
+        let dilution = dilution(epoch.yield_rate, first_rate)?;
+        Ok(ProjectedEpoch { dilution, ..epoch })
+      })
+      .collect()
+  }
+
+  fn pool_cap(&self, balances: &PoolBalances) -> Result<UFix64<N6>> {
+    stability_pool_cap(
+      self.stablecoin_nav,
+      balances.stablecoin_in_pool,
+      self.levercoin_nav,
+      balances.levercoin_in_pool,
+    )
+  }
+
+  fn nav(&self, balances: &PoolBalances) -> Result<UFix64<N6>> {
+    lp_token_nav(
+      self.stablecoin_nav,
+      balances.stablecoin_in_pool,
+      self.levercoin_nav,
+      balances.levercoin_in_pool,
+      balances.lp_token_supply,
+    )
+  }
+
+  fn deposit(
+    &self,
+    balances: PoolBalances,
+    offered: UFix64<N6>,
+  ) -> Result<(PoolBalances, UFix64<N6>)> {
+    let accepted = match self.cap_limit {
+      Some(cap_limit) => offered.min(remaining_deposit_capacity(
+        self.pool_cap(&balances)?,
+        cap_limit,
+        self.stablecoin_nav,
+      )?),
+      None => offered,
+    };
+    let minted = lp_token_out(accepted, self.nav(&balances)?)?;
+    let stablecoin_in_pool = balances.stablecoin_in_pool.checked_add(&accepted);
+    let lp_token_supply = balances.lp_token_supply.checked_add(&minted);
+    let balances = stablecoin_in_pool
+      .zip(lp_token_supply)
+      .map(|(stablecoin_in_pool, lp_token_supply)| PoolBalances {
+        stablecoin_in_pool,
+        lp_token_supply,
+        ..balances
+      })
+      .ok_or(PoolProjectionError)?;
+    Ok((balances, accepted))
+  }
+
+  fn withdraw(
+    balances: PoolBalances,
+    redeemed: UFix64<N6>,
+  ) -> Result<PoolBalances> {
+    let burned = redeemed.min(balances.lp_token_supply);
+    if burned == UFix64::zero() {
+      Ok(balances)
+    } else {
+      let supply = balances.lp_token_supply;
+      let stablecoin =
+        amount_token_to_withdraw(burned, supply, balances.stablecoin_in_pool)?;
+      let levercoin =
+        amount_token_to_withdraw(burned, supply, balances.levercoin_in_pool)?;
+      Ok(PoolBalances {
+        stablecoin_in_pool: balances
+          .stablecoin_in_pool
+          .saturating_sub(&stablecoin),
+        levercoin_in_pool: balances
+          .levercoin_in_pool
+          .saturating_sub(&levercoin),
+        lp_token_supply: supply.saturating_sub(&burned),
+      })
+    }
+  }
+
+  fn step(
+    &self,
+    balances: PoolBalances,
+    flows: &EpochFlows,
+  ) -> Result<ProjectedEpoch> {
+    let (balances, deposits_accepted) =
+      self.deposit(balances, flows.deposits)?;
+    let balances = Self::withdraw(balances, flows.withdrawals)?;
+    let cap_before_yield = self.pool_cap(&balances)?;
+    let yield_rate = if cap_before_yield == UFix64::zero() {
+      UFix64::zero()
+    } else {
+      UFix64::<N9>::one()
+        .mul_div_floor(flows.yield_to_pool, cap_before_yield)
+        .ok_or(PoolProjectionError)?
+    };
+    let balances = PoolBalances {
+      stablecoin_in_pool: balances
+        .stablecoin_in_pool
+        .checked_add(&flows.yield_to_pool)
+        .ok_or(PoolProjectionError)?,
+      ..balances
+    };
+    Ok(ProjectedEpoch {
+      balances,
+      deposits_accepted,
+      pool_cap: self.pool_cap(&balances)?,
+      lp_token_nav: self.nav(&balances)?,
+      yield_rate,
+      dilution: UFix64::zero(),
+    })
+  }
+}
+
+/// Fall from `first_rate` to `yield_rate`, zero if the first epoch earned
+/// nothing.
+fn dilution(
+  yield_rate: UFix64<N9>,
+  first_rate: UFix64<N9>,
+) -> Result<UFix64<N4>> {
+  if first_rate == UFix64::zero() {
+    Ok(UFix64::zero())
+  } else {
+    UFix64::<N4>::one()
+      .mul_div_floor(yield_rate, first_rate)
+      .map(|share| UFix64::one().saturating_sub(&share))
+      .ok_or(PoolProjectionError.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+
+  use super::*;
+
+  fn usd(amount: u64) -> UFix64<N6> {
+    UFix64::new(amount * 1_000_000)
+  }
+
+  fn projection(cap_limit: Option<UFix64<N6>>) -> PoolProjection {
+    PoolProjection {
+      stablecoin_nav: UFix64::one(),
+      levercoin_nav: UFix64::new(4_000_000_000),
+      cap_limit,
+    }
+  }
+
+  fn start() -> PoolBalances {
+    PoolBalances {
+      stablecoin_in_pool: usd(1_000),
+      levercoin_in_pool: UFix64::zero(),
+      lp_token_supply: usd(1_000),
+    }
+  }
+
+  #[test]
+  fn deposits_dilute_yield() -> Result<()> {
+    let flows = EpochFlows {
+      deposits: usd(1_000),
+      withdrawals: UFix64::zero(),
+      yield_to_pool: usd(10),
+    };
+    let epochs = projection(None).project(start(), &[flows, flows])?;
+    // 2,000 hyUSD earns 10, then 3,010 hyUSD earns 10
+    assert_eq!(epochs[0].yield_rate, UFix64::new(5_000_000));
+    assert_eq!(epochs[0].dilution, UFix64::zero());
+    assert_eq!(epochs[1].pool_cap, usd(3_020));
+    assert_eq!(epochs[1].yield_rate, UFix64::new(3_322_259));
+    assert_eq!(epochs[1].dilution, UFix64::new(3_356));
+    assert!(epochs[1].lp_token_nav > epochs[0].lp_token_nav);
+    Ok(())
+  }
+
+  #[test]
+  fn cap_limits_deposits_and_withdrawals_burn() -> Result<()> {
+    let flows = [
+      EpochFlows {
+        deposits: usd(1_000),
+        withdrawals: UFix64::zero(),
+        yield_to_pool: UFix64::zero(),
+      },
+      EpochFlows {
+        deposits: UFix64::zero(),
+        withdrawals: usd(500),
+        yield_to_pool: UFix64::zero(),
+      },
+    ];
+    let epochs = projection(Some(usd(1_500))).project(start(), &flows)?;
+    assert_eq!(epochs[0].deposits_accepted, usd(500));
+    assert_eq!(epochs[0].balances.lp_token_supply, usd(1_500));
+    assert_eq!(epochs[1].balances.lp_token_supply, usd(1_000));
+    assert_eq!(epochs[1].pool_cap, usd(1_000));
+    Ok(())
+  }
+}
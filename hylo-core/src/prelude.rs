@@ -0,0 +1,31 @@
+//! Common imports for working with Hylo protocol state.
+//!
+//! ```rust,ignore
+//! use hylo_core::prelude::*;
+//! ```
+
+pub use fix::prelude::*;
+
+pub use crate::conversion::{Conversion, SwapConversion};
+pub use crate::exchange_context::ExchangeContext;
+pub use crate::fee_controller::{
+  FeeController, FeeExtract, FeePair, LevercoinFees, StablecoinFees,
+};
+#[cfg(feature = "offchain")]
+pub use crate::idl::pda;
+#[cfg(feature = "offchain")]
+pub use crate::idl::tokens::{
+  TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL,
+};
+pub use crate::lst_sol_price::LstSolPrice;
+pub use crate::pyth::{OracleConfig, PriceRange, SOL_USD_PYTH_FEED};
+pub use crate::rescale::{Rescale, Rounding};
+pub use crate::solana_clock::SolanaClock;
+pub use crate::stability_mode::{
+  StabilityBand, StabilityController, StabilityMode,
+};
+#[cfg(feature = "offchain")]
+pub use crate::token_amount::{
+  HyusdAmount, LstAmount, ShyusdAmount, SolAmount, TokenAmount, XsolAmount, SOL,
+};
+pub use crate::total_sol_cache::TotalSolCache;
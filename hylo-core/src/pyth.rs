@@ -11,6 +11,8 @@ use crate::error::CoreError::{
   PythOracleNegativeTime, PythOracleOutdated, PythOraclePriceRange,
   PythOracleSlotInvalid, PythOracleVerificationLevel,
 };
+use crate::rescale::Rescale;
+use crate::rescale::Rounding::Exact;
 use crate::solana_clock::SolanaClock;
 
 pub const SOL_USD: FeedId = [
@@ -77,6 +79,36 @@ impl<Exp: Integer> PriceRange<Exp> {
   }
 }
 
+/// Uncertainty added to a stale oracle price for analytics that would rather
+/// proceed on the last known price than fail.
+///
+/// Never use for transaction pricing: the program rejects stale oracles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StaleFallback<Exp: Integer> {
+  /// Confidence widening per second since publish, as a ratio of price
+  pub uncertainty_per_sec: UFix64<Exp>,
+}
+
+impl<Exp: Integer> StaleFallback<Exp> {
+  #[must_use]
+  pub fn new(uncertainty_per_sec: UFix64<Exp>) -> StaleFallback<Exp> {
+    StaleFallback {
+      uncertainty_per_sec,
+    }
+  }
+}
+
+/// Oracle price range tagged with whether it was read past the oracle
+/// interval and widened by [`StaleFallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice<Exp: Integer> {
+  pub range: PriceRange<Exp>,
+  pub degraded: bool,
+
+  /// Seconds between the price publish time and the clock
+  pub staleness_secs: u64,
+}
+
 /// Checks the ratio of `conf / price` against given tolerance.
 /// Guards against unusually large spreads in the oracle price.
 fn validate_conf<Exp>(
@@ -114,9 +146,41 @@ fn validate_publish_time(
   }
 }
 
+/// Seconds elapsed from `publish_time` to `clock_time`, zero if published
+/// ahead of the clock.
+fn staleness_secs(publish_time: i64, clock_time: i64) -> Result<u64> {
+  if publish_time.is_positive() && clock_time.is_positive() {
+    Ok(
+      clock_time
+        .unsigned_abs()
+        .saturating_sub(publish_time.unsigned_abs()),
+    )
+  } else {
+    Err(PythOracleNegativeTime.into())
+  }
+}
+
+/// Widens `conf` by `uncertainty_per_sec * staleness_secs` of `price`.
+fn widen_conf<Exp>(
+  price: UFix64<Exp>,
+  conf: UFix64<Exp>,
+  uncertainty_per_sec: UFix64<Exp>,
+  staleness_secs: u64,
+) -> Result<UFix64<Exp>>
+where
+  UFix64<Exp>: FixExt,
+{
+  uncertainty_per_sec
+    .bits
+    .checked_mul(staleness_secs)
+    .and_then(|ratio| price.mul_div_ceil(UFix64::new(ratio), UFix64::one()))
+    .and_then(|widening| conf.checked_add(&widening))
+    .ok_or(PythOraclePriceRange.into())
+}
+
 /// Number of Solana slots in configured oracle interval time.
 fn slot_interval(oracle_interval_secs: u64) -> Option<u64> {
-  let time: UFix64<N2> = UFix64::<Z0>::new(oracle_interval_secs).convert();
+  let time = UFix64::<Z0>::new(oracle_interval_secs).rescale::<N2>(Exact)?;
   let slot_time = UFix64::<N2>::new(40); // 400ms slot time
   time.checked_div(&slot_time).map(|i| i.bits)
 }
@@ -137,7 +201,10 @@ fn validate_posted_slot(
 
 /// Ensures the `exp` given by Pyth matches the target exponent type.
 /// Also checks if the quoted price is negative.
-fn validate_price<Exp: Integer>(price: i64, exp: i32) -> Result<UFix64<Exp>> {
+pub(crate) fn validate_price<Exp: Integer>(
+  price: i64,
+  exp: i32,
+) -> Result<UFix64<Exp>> {
   if Exp::to_i32() != exp {
     Err(PythOracleExponent.into())
   } else if price <= 0 {
@@ -188,6 +255,56 @@ where
   PriceRange::from_conf(spot_price, spot_conf)
 }
 
+/// Like [`query_pyth_price`], but proceeds past the oracle interval with the
+/// last known price, widened per `fallback` and tagged degraded.
+/// Verification level, price and confidence checks still apply.
+pub fn query_pyth_price_with_fallback<Exp: Integer, C: SolanaClock>(
+  clock: &C,
+  oracle: &PriceUpdateV2,
+  config: OracleConfig<Exp>,
+  fallback: StaleFallback<Exp>,
+) -> Result<OraclePrice<Exp>>
+where
+  UFix64<Exp>: FixExt,
+{
+  let staleness_secs =
+    staleness_secs(oracle.price_message.publish_time, clock.unix_timestamp())?;
+  match query_pyth_price(clock, oracle, config) {
+    Ok(range) => Ok(OraclePrice {
+      range,
+      degraded: false,
+      staleness_secs,
+    }),
+    Err(err)
+      if err == PythOracleOutdated.into()
+        || err == PythOracleSlotInvalid.into() =>
+    {
+      validate_verification_level(oracle.verification_level)?;
+      let spot_price = validate_price(
+        oracle.price_message.price,
+        oracle.price_message.exponent,
+      )?;
+      let spot_conf = validate_conf(
+        spot_price,
+        UFix64::new(oracle.price_message.conf),
+        config.conf_tolerance,
+      )?;
+      let conf = widen_conf(
+        spot_price,
+        spot_conf,
+        fallback.uncertainty_per_sec,
+        staleness_secs,
+      )?;
+      Ok(OraclePrice {
+        range: PriceRange::from_conf(spot_price, conf)?,
+        degraded: true,
+        staleness_secs,
+      })
+    }
+    Err(err) => Err(err),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use fix::typenum::N8;
@@ -253,6 +370,19 @@ mod tests {
     assert_eq!(out, Some(2));
   }
 
+  #[test]
+  fn widen_conf_scales_with_staleness() -> Result<()> {
+    let price = UFix64::<N8>::new(15_000_000_000);
+    let conf = UFix64::<N8>::new(10_000_000);
+    // 0.001% per second
+    let rate = UFix64::<N8>::new(1_000);
+    assert_eq!(widen_conf(price, conf, rate, 0)?, conf);
+    assert_eq!(widen_conf(price, conf, rate, 120)?, UFix64::new(28_000_000));
+    assert_eq!(staleness_secs(1_000, 1_120)?, 120);
+    assert_eq!(staleness_secs(1_120, 1_000)?, 0);
+    Ok(())
+  }
+
   #[test]
   fn validate_confidence_pos() {
     let price = UFix64::<N8>::new(14_640_110_937);
@@ -0,0 +1,80 @@
+//! Checked rescaling between fixed point exponents.
+//!
+//! `convert` multiplies unchecked when adding decimals and truncates when
+//! dropping them. [`Rescale::rescale`] overflows to `None` instead, and drops
+//! decimals under an explicit [`Rounding`], so conversions such as hyUSD
+//! `N6` amounts into `N9` SOL math state which way they round.
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+pub use crate::amount_format::Rounding;
+
+/// Rescaling of a fixed point number to another exponent.
+pub trait Rescale {
+  /// Same value at exponent `ToExp`.
+  ///
+  /// Returns `None` on overflow, or under [`Rounding::Exact`] if nonzero
+  /// digits would be dropped.
+  fn rescale<ToExp: Integer>(self, rounding: Rounding)
+    -> Option<UFix64<ToExp>>;
+}
+
+impl<Exp: Integer> Rescale for UFix64<Exp> {
+  fn rescale<ToExp: Integer>(
+    self,
+    rounding: Rounding,
+  ) -> Option<UFix64<ToExp>> {
+    let shift = i32::from(Exp::to_i8()) - i32::from(ToExp::to_i8());
+    let ratio = 10u64.checked_pow(shift.unsigned_abs())?;
+    let bits = if shift >= 0 {
+      self.bits.checked_mul(ratio)
+    } else {
+      match rounding {
+        Rounding::Floor => Some(self.bits / ratio),
+        Rounding::Ceil => Some(self.bits.div_ceil(ratio)),
+        Rounding::Exact => {
+          self.bits.is_multiple_of(ratio).then_some(self.bits / ratio)
+        }
+      }
+    }?;
+    Some(UFix64::new(bits))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn adds_decimals_checked() {
+    let amount = UFix64::<N6>::new(1_500_000);
+    assert_eq!(
+      amount.rescale::<N9>(Rounding::Exact),
+      Some(UFix64::new(1_500_000_000))
+    );
+    assert_eq!(amount.rescale::<N6>(Rounding::Exact), Some(amount));
+    assert_eq!(
+      UFix64::<N6>::new(u64::MAX).rescale::<N9>(Rounding::Floor),
+      None
+    );
+  }
+
+  #[test]
+  fn drops_decimals_by_rounding() {
+    let amount = UFix64::<N9>::new(1_000_000_001);
+    assert_eq!(
+      amount.rescale::<N6>(Rounding::Floor),
+      Some(UFix64::new(1_000_000))
+    );
+    assert_eq!(
+      amount.rescale::<N6>(Rounding::Ceil),
+      Some(UFix64::new(1_000_001))
+    );
+    assert_eq!(amount.rescale::<N6>(Rounding::Exact), None);
+    assert_eq!(
+      Some(amount.convert::<N6>()),
+      amount.rescale::<N6>(Rounding::Floor)
+    );
+  }
+}
@@ -0,0 +1,308 @@
+//! Realized protocol revenue for treasury reporting.
+//!
+//! Fees land in per-mint fee vaults and are periodically withdrawn to the
+//! treasury, so a vault's revenue over a period is its closing balance plus
+//! withdrawals, less its opening balance ([`FeeVaultPeriod`]). Those deltas
+//! are in token units; [`RevenueBreakdown`] attributes the same fees to their
+//! source in USD by replaying mint, redeem, swap and harvest events, valued at
+//! the prices each event carries.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+use crate::error::CoreError::{Revenue, RevenueVaultShortfall};
+#[cfg(feature = "offchain")]
+use crate::idl::exchange::events::{
+  HarvestYieldEventV1, HarvestYieldEventV2, MintLevercoinEventV2,
+  MintStablecoinEventV2, RedeemLevercoinEventV2, RedeemStablecoinEventV2,
+  SwapLeverToStableEventV1, SwapLstEventV0, SwapStableToLeverEventV1,
+  WithdrawFeesEvent,
+};
+
+/// Origin of protocol revenue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevenueSource {
+  /// Fees on hyUSD and xSOL mints, paid in LST
+  MintFees,
+  /// Fees on hyUSD and xSOL redemptions, paid in LST
+  RedeemFees,
+  /// Fees on hyUSD/xSOL and LST/LST swaps
+  SwapFees,
+  /// Protocol share of harvested LST yield
+  YieldShare,
+}
+
+/// Fee vault balances over a period, in the vault token's base units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeVaultPeriod<Exp: Integer> {
+  pub opening: UFix64<Exp>,
+  pub closing: UFix64<Exp>,
+  pub withdrawn: UFix64<Exp>,
+}
+
+impl<Exp: Integer> FeeVaultPeriod<Exp> {
+  #[must_use]
+  pub fn new(opening: UFix64<Exp>, closing: UFix64<Exp>) -> Self {
+    FeeVaultPeriod {
+      opening,
+      closing,
+      withdrawn: UFix64::new(0),
+    }
+  }
+
+  /// Records a withdrawal to the treasury during the period.
+  pub fn withdraw(&mut self, amount: UFix64<Exp>) -> Result<()> {
+    self.withdrawn = self.withdrawn.checked_add(&amount).ok_or(Revenue)?;
+    Ok(())
+  }
+
+  /// Fees accrued to the vault during the period.
+  ///
+  /// # Errors
+  /// * Closing balance plus withdrawals is below the opening balance
+  pub fn realized(&self) -> Result<UFix64<Exp>> {
+    self
+      .closing
+      .checked_add(&self.withdrawn)
+      .ok_or(Revenue)?
+      .checked_sub(&self.opening)
+      .ok_or(RevenueVaultShortfall.into())
+  }
+}
+
+/// USD value of LST-denominated fees.
+pub fn lst_fees_usd(
+  fees: UFix64<N9>,
+  lst_sol_price: UFix64<N9>,
+  sol_usd_price: UFix64<N8>,
+) -> Result<UFix64<N6>> {
+  fees
+    .mul_div_floor(lst_sol_price, UFix64::one())
+    .and_then(|sol| sol.mul_div_floor(sol_usd_price, UFix64::one()))
+    .map(UFix64::convert)
+    .ok_or(Revenue.into())
+}
+
+/// USD value of fees paid in hyUSD or xSOL at the token's NAV.
+pub fn token_fees_usd(fees: UFix64<N6>, nav: UFix64<N9>) -> Result<UFix64<N6>> {
+  fees.mul_div_floor(nav, UFix64::one()).ok_or(Revenue.into())
+}
+
+/// Protocol revenue in USD by source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RevenueBreakdown {
+  pub mint_fees: UFix64<N6>,
+  pub redeem_fees: UFix64<N6>,
+  pub swap_fees: UFix64<N6>,
+  pub yield_share: UFix64<N6>,
+}
+
+impl RevenueBreakdown {
+  /// Revenue attributed to `source`.
+  #[must_use]
+  pub fn get(&self, source: RevenueSource) -> UFix64<N6> {
+    match source {
+      RevenueSource::MintFees => self.mint_fees,
+      RevenueSource::RedeemFees => self.redeem_fees,
+      RevenueSource::SwapFees => self.swap_fees,
+      RevenueSource::YieldShare => self.yield_share,
+    }
+  }
+
+  /// Adds `usd` to the revenue from `source`.
+  pub fn record(
+    &mut self,
+    source: RevenueSource,
+    usd: UFix64<N6>,
+  ) -> Result<()> {
+    let slot = match source {
+      RevenueSource::MintFees => &mut self.mint_fees,
+      RevenueSource::RedeemFees => &mut self.redeem_fees,
+      RevenueSource::SwapFees => &mut self.swap_fees,
+      RevenueSource::YieldShare => &mut self.yield_share,
+    };
+    *slot = slot.checked_add(&usd).ok_or(Revenue)?;
+    Ok(())
+  }
+
+  /// Revenue across all sources.
+  pub fn total(&self) -> Result<UFix64<N6>> {
+    self
+      .mint_fees
+      .checked_add(&self.redeem_fees)
+      .and_then(|sum| sum.checked_add(&self.swap_fees))
+      .and_then(|sum| sum.checked_add(&self.yield_share))
+      .ok_or(Revenue.into())
+  }
+}
+
+#[cfg(feature = "offchain")]
+impl<Exp: Integer> FeeVaultPeriod<Exp> {
+  /// Records a decoded [`WithdrawFeesEvent`] from this vault.
+  pub fn apply_withdraw_fees_event(
+    &mut self,
+    event: &WithdrawFeesEvent,
+  ) -> Result<()> {
+    self.withdraw(UFix64::new(event.amount))
+  }
+}
+
+#[cfg(feature = "offchain")]
+impl RevenueBreakdown {
+  /// Records the fees of a decoded [`MintStablecoinEventV2`].
+  pub fn apply_mint_stablecoin_event(
+    &mut self,
+    event: &MintStablecoinEventV2,
+  ) -> Result<()> {
+    let usd = lst_fees_usd(
+      event.fees_deposited.try_into()?,
+      event.lst_sol_price.try_into()?,
+      event.sol_usd_price.try_into()?,
+    )?;
+    self.record(RevenueSource::MintFees, usd)
+  }
+
+  /// Records the fees of a decoded [`MintLevercoinEventV2`].
+  pub fn apply_mint_levercoin_event(
+    &mut self,
+    event: &MintLevercoinEventV2,
+  ) -> Result<()> {
+    let usd = lst_fees_usd(
+      event.fees_deposited.try_into()?,
+      event.lst_sol_price.try_into()?,
+      event.sol_usd_price.try_into()?,
+    )?;
+    self.record(RevenueSource::MintFees, usd)
+  }
+
+  /// Records the fees of a decoded [`RedeemStablecoinEventV2`].
+  pub fn apply_redeem_stablecoin_event(
+    &mut self,
+    event: &RedeemStablecoinEventV2,
+  ) -> Result<()> {
+    let usd = lst_fees_usd(
+      event.fees_deposited.try_into()?,
+      event.lst_sol_price.try_into()?,
+      event.sol_usd_price.try_into()?,
+    )?;
+    self.record(RevenueSource::RedeemFees, usd)
+  }
+
+  /// Records the fees of a decoded [`RedeemLevercoinEventV2`].
+  pub fn apply_redeem_levercoin_event(
+    &mut self,
+    event: &RedeemLevercoinEventV2,
+  ) -> Result<()> {
+    let usd = lst_fees_usd(
+      event.fees_deposited.try_into()?,
+      event.lst_sol_price.try_into()?,
+      event.sol_usd_price.try_into()?,
+    )?;
+    self.record(RevenueSource::RedeemFees, usd)
+  }
+
+  /// Records the hyUSD fees of a decoded [`SwapStableToLeverEventV1`].
+  pub fn apply_swap_stable_to_lever_event(
+    &mut self,
+    event: &SwapStableToLeverEventV1,
+  ) -> Result<()> {
+    let usd = token_fees_usd(
+      event.stablecoin_fees.try_into()?,
+      event.stablecoin_nav.try_into()?,
+    )?;
+    self.record(RevenueSource::SwapFees, usd)
+  }
+
+  /// Records the hyUSD fees of a decoded [`SwapLeverToStableEventV1`].
+  pub fn apply_swap_lever_to_stable_event(
+    &mut self,
+    event: &SwapLeverToStableEventV1,
+  ) -> Result<()> {
+    let usd = token_fees_usd(
+      event.stablecoin_minted_fees.try_into()?,
+      event.stablecoin_nav.try_into()?,
+    )?;
+    self.record(RevenueSource::SwapFees, usd)
+  }
+
+  /// Records the input LST fees of a decoded [`SwapLstEventV0`]. The event
+  /// carries no prices, so the caller supplies them for `lst_a_mint`.
+  pub fn apply_swap_lst_event(
+    &mut self,
+    event: &SwapLstEventV0,
+    lst_sol_price: UFix64<N9>,
+    sol_usd_price: UFix64<N8>,
+  ) -> Result<()> {
+    let usd = lst_fees_usd(
+      event.lst_a_fees_extracted.try_into()?,
+      lst_sol_price,
+      sol_usd_price,
+    )?;
+    self.record(RevenueSource::SwapFees, usd)
+  }
+
+  /// Records the hyUSD extracted by a decoded [`HarvestYieldEventV1`].
+  pub fn apply_harvest_event_v1(
+    &mut self,
+    event: &HarvestYieldEventV1,
+    stablecoin_nav: UFix64<N9>,
+  ) -> Result<()> {
+    let usd = token_fees_usd(event.fees_extracted.try_into()?, stablecoin_nav)?;
+    self.record(RevenueSource::YieldShare, usd)
+  }
+
+  /// Records the fees extracted by a decoded [`HarvestYieldEventV2`], valued
+  /// at `harvest_token_nav`, the NAV of `harvest_token_mint`.
+  pub fn apply_harvest_event_v2(
+    &mut self,
+    event: &HarvestYieldEventV2,
+    harvest_token_nav: UFix64<N9>,
+  ) -> Result<()> {
+    let usd =
+      token_fees_usd(event.fees_extracted.try_into()?, harvest_token_nav)?;
+    self.record(RevenueSource::YieldShare, usd)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn vault_realized_includes_withdrawals() -> Result<()> {
+    let mut vault =
+      FeeVaultPeriod::<N9>::new(UFix64::new(5_000), UFix64::new(2_000));
+    vault.withdraw(UFix64::new(10_000))?;
+    assert_eq!(vault.realized()?, UFix64::new(7_000));
+    Ok(())
+  }
+
+  #[test]
+  fn vault_shortfall() {
+    let vault =
+      FeeVaultPeriod::<N9>::new(UFix64::new(5_000), UFix64::new(2_000));
+    assert_eq!(vault.realized(), Err(RevenueVaultShortfall.into()));
+  }
+
+  #[test]
+  fn breakdown_by_source() -> Result<()> {
+    let mut revenue = RevenueBreakdown::default();
+    let mint = lst_fees_usd(
+      UFix64::new(10_000_000),
+      UFix64::new(1_100_000_000),
+      UFix64::new(15_000_000_000),
+    )?;
+    assert_eq!(mint, UFix64::new(1_650_000));
+    revenue.record(RevenueSource::MintFees, mint)?;
+    let swap = token_fees_usd(UFix64::new(2_000_000), UFix64::one())?;
+    revenue.record(RevenueSource::SwapFees, swap)?;
+    revenue.record(RevenueSource::YieldShare, UFix64::new(350_000))?;
+    assert_eq!(revenue.get(RevenueSource::RedeemFees), UFix64::zero());
+    assert_eq!(revenue.total()?, UFix64::new(4_000_000));
+    Ok(())
+  }
+}
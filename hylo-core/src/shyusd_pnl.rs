@@ -0,0 +1,206 @@
+//! Cost basis and PnL for sHYUSD positions.
+//!
+//! Positions are tracked at average cost in USD. A deposit adds
+//! `lp_token_minted * lp_token_nav` to the basis; a withdrawal releases
+//! basis pro rata to the sHYUSD burned and realizes the difference against
+//! the USD value of the hyUSD and xSOL paid out, net of withdrawal fees.
+//! Feed events for one user in order; attributing events to users is left to
+//! the caller since they do not carry the depositor's address.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::{ShyusdPnl, ShyusdPnlOverdrawn};
+#[cfg(feature = "offchain")]
+use crate::idl::stability_pool::events::{
+  UserDepositEvent, UserWithdrawEventV1,
+};
+
+/// Profit or loss in USD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pnl {
+  Profit(UFix64<N6>),
+  Loss(UFix64<N6>),
+}
+
+impl Pnl {
+  /// PnL of receiving `value` for `cost`.
+  #[must_use]
+  pub fn between(value: UFix64<N6>, cost: UFix64<N6>) -> Pnl {
+    if value >= cost {
+      Pnl::Profit(value.saturating_sub(&cost))
+    } else {
+      Pnl::Loss(cost.saturating_sub(&value))
+    }
+  }
+}
+
+/// One user's sHYUSD position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShyusdPosition {
+  /// sHYUSD currently held
+  pub lp_tokens: UFix64<N6>,
+
+  /// USD cost of the sHYUSD currently held
+  pub cost_basis: UFix64<N6>,
+
+  /// USD paid out by withdrawals so far
+  pub realized_proceeds: UFix64<N6>,
+
+  /// Basis released by withdrawals so far
+  pub realized_cost: UFix64<N6>,
+}
+
+impl ShyusdPosition {
+  /// Records a deposit minting `lp_token_minted` at `lp_token_nav`.
+  pub fn deposit(
+    &mut self,
+    lp_token_minted: UFix64<N6>,
+    lp_token_nav: UFix64<N6>,
+  ) -> Result<()> {
+    let cost = lp_token_minted
+      .mul_div_ceil(lp_token_nav, UFix64::one())
+      .ok_or(ShyusdPnl)?;
+    self.lp_tokens = self
+      .lp_tokens
+      .checked_add(&lp_token_minted)
+      .ok_or(ShyusdPnl)?;
+    self.cost_basis = self.cost_basis.checked_add(&cost).ok_or(ShyusdPnl)?;
+    Ok(())
+  }
+
+  /// Records a withdrawal burning `lp_token_burned` for hyUSD and xSOL valued
+  /// at their NAVs.
+  pub fn withdraw(
+    &mut self,
+    lp_token_burned: UFix64<N6>,
+    stablecoin_withdrawn: UFix64<N6>,
+    stablecoin_nav: UFix64<N9>,
+    levercoin_withdrawn: UFix64<N6>,
+    levercoin_nav: UFix64<N9>,
+  ) -> Result<()> {
+    if lp_token_burned > self.lp_tokens {
+      Err(ShyusdPnlOverdrawn.into())
+    } else {
+      let released = self
+        .cost_basis
+        .mul_div_floor(lp_token_burned, self.lp_tokens)
+        .ok_or(ShyusdPnl)?;
+      let proceeds = stablecoin_withdrawn
+        .mul_div_floor(stablecoin_nav, UFix64::one())
+        .zip(levercoin_withdrawn.mul_div_floor(levercoin_nav, UFix64::one()))
+        .and_then(|(stable, lever)| stable.checked_add(&lever))
+        .ok_or(ShyusdPnl)?;
+      self.lp_tokens = self.lp_tokens.saturating_sub(&lp_token_burned);
+      self.cost_basis = self.cost_basis.saturating_sub(&released);
+      self.realized_cost =
+        self.realized_cost.checked_add(&released).ok_or(ShyusdPnl)?;
+      self.realized_proceeds = self
+        .realized_proceeds
+        .checked_add(&proceeds)
+        .ok_or(ShyusdPnl)?;
+      Ok(())
+    }
+  }
+
+  /// Average USD cost per sHYUSD held, `None` for an empty position.
+  #[must_use]
+  pub fn average_cost(&self) -> Option<UFix64<N6>> {
+    self.cost_basis.mul_div_floor(UFix64::one(), self.lp_tokens)
+  }
+
+  /// PnL realized by withdrawals.
+  #[must_use]
+  pub fn realized(&self) -> Pnl {
+    Pnl::between(self.realized_proceeds, self.realized_cost)
+  }
+
+  /// PnL of the held sHYUSD at the current LP token NAV.
+  pub fn unrealized(&self, lp_token_nav: UFix64<N6>) -> Result<Pnl> {
+    let value = self
+      .lp_tokens
+      .mul_div_floor(lp_token_nav, UFix64::one())
+      .ok_or(ShyusdPnl)?;
+    Ok(Pnl::between(value, self.cost_basis))
+  }
+}
+
+#[cfg(feature = "offchain")]
+impl ShyusdPosition {
+  /// Records a decoded [`UserDepositEvent`].
+  pub fn apply_deposit_event(
+    &mut self,
+    event: &UserDepositEvent,
+  ) -> Result<()> {
+    self.deposit(
+      event.lp_token_minted.try_into()?,
+      event.lp_token_nav.try_into()?,
+    )
+  }
+
+  /// Records a decoded [`UserWithdrawEventV1`].
+  pub fn apply_withdraw_event(
+    &mut self,
+    event: &UserWithdrawEventV1,
+  ) -> Result<()> {
+    self.withdraw(
+      event.lp_token_burned.try_into()?,
+      event.stablecoin_withdrawn.try_into()?,
+      event.stablecoin_nav.try_into()?,
+      event.levercoin_withdrawn.try_into()?,
+      event.levercoin_nav.try_into()?,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn average_cost_across_deposits() -> Result<()> {
+    let mut position = ShyusdPosition::default();
+    position.deposit(UFix64::new(100_000_000), UFix64::new(1_000_000))?;
+    position.deposit(UFix64::new(100_000_000), UFix64::new(1_100_000))?;
+    assert_eq!(position.cost_basis, UFix64::new(210_000_000));
+    assert_eq!(position.average_cost(), Some(UFix64::new(1_050_000)));
+    assert_eq!(
+      position.unrealized(UFix64::new(1_200_000))?,
+      Pnl::Profit(UFix64::new(30_000_000))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn partial_withdraw_realizes() -> Result<()> {
+    let mut position = ShyusdPosition::default();
+    position.deposit(UFix64::new(100_000_000), UFix64::new(1_000_000))?;
+    position.withdraw(
+      UFix64::new(50_000_000),
+      UFix64::new(40_000_000),
+      UFix64::one(),
+      UFix64::new(5_000_000),
+      UFix64::new(3_000_000_000),
+    )?;
+    assert_eq!(position.lp_tokens, UFix64::new(50_000_000));
+    assert_eq!(position.cost_basis, UFix64::new(50_000_000));
+    assert_eq!(position.realized(), Pnl::Profit(UFix64::new(5_000_000)));
+    Ok(())
+  }
+
+  #[test]
+  fn overdrawn_withdraw() {
+    let mut position = ShyusdPosition::default();
+    let out = position.withdraw(
+      UFix64::new(1),
+      UFix64::zero(),
+      UFix64::one(),
+      UFix64::zero(),
+      UFix64::one(),
+    );
+    assert_eq!(out, Err(ShyusdPnlOverdrawn.into()));
+  }
+}
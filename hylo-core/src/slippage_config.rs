@@ -30,18 +30,23 @@ impl SlippageConfig {
     self.slippage_tolerance.try_into()
   }
 
+  /// Lowest tolerable token amount, the expected amount less slippage.
+  pub fn min_token_out<Exp: Integer>(&self) -> Result<UFix64<Exp>> {
+    let expected = self.expected_token_out::<Exp>()?;
+    let tolerance = self.slippage_tolerance()?;
+    // Invert slippage and multiply with expected amount
+    UFix64::<N4>::one()
+      .checked_sub(&tolerance)
+      .and_then(|factor| expected.mul_div_floor(factor, UFix64::one()))
+      .ok_or(SlippageArithmetic.into())
+  }
+
   /// Checks token amount against the configured lowest tolerable amount
   pub fn validate_token_out<Exp: Integer>(
     &self,
     token_out: UFix64<Exp>,
   ) -> Result<()> {
-    let expected = self.expected_token_out()?;
-    let tolerance = self.slippage_tolerance()?;
-    // Invert slippage and multiply with expected amount
-    let tolerable_amount = UFix64::<N4>::one()
-      .checked_sub(&tolerance)
-      .and_then(|factor| expected.mul_div_floor(factor, UFix64::one()))
-      .ok_or(SlippageArithmetic)?;
+    let tolerable_amount = self.min_token_out::<Exp>()?;
     if token_out >= tolerable_amount {
       Ok(())
     } else {
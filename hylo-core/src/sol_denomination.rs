@@ -0,0 +1,103 @@
+//! SOL-denominated views of the exchange.
+//!
+//! [`ExchangeContext`] reports value in USD, but treasuries holding SOL
+//! measure against SOL. These views derive the same figures from the same
+//! context, converting USD at the lower SOL/USD price, the leg the
+//! collateral ratio and redemption NAVs use. TVL in SOL is the collateral
+//! itself and needs no price.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::SolDenomination;
+use crate::exchange_context::ExchangeContext;
+use crate::rescale::Rescale;
+use crate::rescale::Rounding::Floor;
+use crate::solana_clock::SolanaClock;
+
+/// Converts a USD value to SOL at `sol_usd_price`, rounding down.
+fn usd_to_sol(
+  usd: UFix64<N9>,
+  sol_usd_price: UFix64<N8>,
+) -> Result<UFix64<N9>> {
+  usd
+    .mul_div_floor(UFix64::one(), sol_usd_price)
+    .ok_or(SolDenomination.into())
+}
+
+/// Value in SOL of `amount` tokens at `nav_sol` SOL each, rounding down.
+fn token_value_sol(
+  amount: UFix64<N6>,
+  nav_sol: UFix64<N9>,
+) -> Result<UFix64<N9>> {
+  amount
+    .rescale::<N9>(Floor)
+    .and_then(|amount| amount.mul_div_floor(nav_sol, UFix64::one()))
+    .ok_or(SolDenomination.into())
+}
+
+impl<C: SolanaClock> ExchangeContext<C> {
+  /// TVL in SOL, the total SOL backing the protocol.
+  #[must_use]
+  pub fn total_value_locked_sol(&self) -> UFix64<N9> {
+    self.total_sol()
+  }
+
+  /// Stablecoin NAV in SOL, `1 / sol_usd_price` outside of depeg.
+  pub fn stablecoin_nav_sol(&self) -> Result<UFix64<N9>> {
+    usd_to_sol(self.stablecoin_nav()?, self.sol_usd_price().lower)
+  }
+
+  /// Levercoin NAV in SOL, from the redemption NAV.
+  pub fn levercoin_nav_sol(&self) -> Result<UFix64<N9>> {
+    usd_to_sol(self.levercoin_redeem_nav()?, self.sol_usd_price().lower)
+  }
+
+  /// Capitalization of the stability pool in SOL.
+  ///
+  /// NB: Levercoin is valued at [`Self::levercoin_nav_sol`], so this is
+  /// slightly below [`Self::stability_pool_cap`] converted at the lower
+  /// price, which values it at the mint NAV.
+  pub fn stability_pool_cap_sol(
+    &self,
+    stablecoin_in_pool: UFix64<N6>,
+    levercoin_in_pool: UFix64<N6>,
+  ) -> Result<UFix64<N9>> {
+    let stable_cap =
+      token_value_sol(stablecoin_in_pool, self.stablecoin_nav_sol()?)?;
+    let lever_cap =
+      token_value_sol(levercoin_in_pool, self.levercoin_nav_sol()?)?;
+    stable_cap
+      .checked_add(&lever_cap)
+      .ok_or(SolDenomination.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+
+  use super::*;
+  use crate::test_fixtures::context;
+
+  #[test]
+  fn navs_in_sol() -> Result<()> {
+    let context = context()?;
+    assert_eq!(context.total_value_locked_sol(), context.total_sol());
+    assert_eq!(context.stablecoin_nav_sol()?, UFix64::new(6_666_666));
+    // ($300,000 - $100,000) / 50,000 xSOL / $150
+    assert_eq!(context.levercoin_nav_sol()?, UFix64::new(26_666_666));
+    Ok(())
+  }
+
+  #[test]
+  fn pool_cap_in_sol() -> Result<()> {
+    let context = context()?;
+    let cap = context.stability_pool_cap_sol(
+      UFix64::new(1_000_000_000),
+      UFix64::new(100_000_000),
+    )?;
+    assert_eq!(cap, UFix64::new(9_333_332_600));
+    Ok(())
+  }
+}
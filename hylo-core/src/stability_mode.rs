@@ -4,13 +4,25 @@ use anchor_lang::prelude::*;
 use fix::prelude::*;
 
 use crate::error::CoreError::StabilityValidation;
+use crate::rescale::Rescale;
+use crate::rescale::Rounding::Exact;
 use crate::stability_mode::StabilityMode::{Depeg, Mode1, Mode2, Normal};
 
 /// Mode of operation based on the protocol's current collateral ratio.
-/// See whitepaper for more.
+/// See whitepaper for more. Modes order from healthiest to most stressed.
 #[derive(
-  Copy, Clone, Debug, AnchorSerialize, AnchorDeserialize, PartialEq, PartialOrd,
+  Copy,
+  Clone,
+  Debug,
+  AnchorSerialize,
+  AnchorDeserialize,
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Hash,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StabilityMode {
   Normal,
   Mode1,
@@ -18,6 +30,45 @@ pub enum StabilityMode {
   Depeg,
 }
 
+impl StabilityMode {
+  /// Every mode, from healthiest to most stressed.
+  pub const ALL: [StabilityMode; 4] = [Normal, Mode1, Mode2, Depeg];
+
+  /// Iterates [`Self::ALL`] in order.
+  pub fn iter() -> impl DoubleEndedIterator<Item = StabilityMode> {
+    StabilityMode::ALL.into_iter()
+  }
+
+  /// Whether the program runs with any stability restrictions.
+  #[must_use]
+  pub fn is_degraded(self) -> bool {
+    self != Normal
+  }
+
+  /// Next more stressed mode, `None` from [`Depeg`].
+  #[must_use]
+  pub fn worse(self) -> Option<StabilityMode> {
+    StabilityMode::iter().find(|mode| *mode > self)
+  }
+
+  /// Next healthier mode, `None` from [`Normal`].
+  #[must_use]
+  pub fn better(self) -> Option<StabilityMode> {
+    StabilityMode::iter().rev().find(|mode| *mode < self)
+  }
+
+  /// Label for display to end users.
+  #[must_use]
+  pub fn label(self) -> &'static str {
+    match self {
+      Normal => "Normal",
+      Mode1 => "Stability Mode 1",
+      Mode2 => "Stability Mode 2",
+      Depeg => "Depegged",
+    }
+  }
+}
+
 impl Display for StabilityMode {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -29,22 +80,98 @@ impl Display for StabilityMode {
   }
 }
 
+/// Collateral ratio at or above which the protocol runs in `mode`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StabilityBand {
+  pub threshold: UFix64<N2>,
+  pub mode: StabilityMode,
+}
+
+impl StabilityBand {
+  #[must_use]
+  pub fn new(threshold: UFix64<N2>, mode: StabilityMode) -> StabilityBand {
+    StabilityBand { threshold, mode }
+  }
+}
+
+/// Collateral ratio range in which the protocol runs in `mode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ModeBounds {
+  pub mode: StabilityMode,
+
+  /// Inclusive lower bound, `None` for [`Depeg`]
+  pub lower: Option<UFix64<N2>>,
+
+  /// Exclusive upper bound, `None` for the healthiest band
+  pub upper: Option<UFix64<N2>>,
+}
+
+impl ModeBounds {
+  /// Whether `collateral_ratio` falls within the bounds.
+  #[must_use]
+  pub fn contains(&self, collateral_ratio: UFix64<N9>) -> bool {
+    self
+      .lower
+      .is_none_or(|lower| meets(collateral_ratio, lower))
+      && self
+        .upper
+        .is_none_or(|upper| !meets(collateral_ratio, upper))
+  }
+}
+
+/// Whether `collateral_ratio` is at or above `threshold`. A threshold too
+/// large for `N9` is above every ratio.
+fn meets(collateral_ratio: UFix64<N9>, threshold: UFix64<N2>) -> bool {
+  threshold
+    .rescale::<N9>(Exact)
+    .is_some_and(|threshold| collateral_ratio >= threshold)
+}
+
+/// Maps collateral ratio to stability mode through `N` bands ordered from
+/// highest to lowest threshold. Ratios below every band are [`Depeg`].
+///
+/// The exchange program configures two thresholds over the 1.0 peg, which is
+/// the default of three bands built by [`StabilityController::new`].
 #[derive(Copy, Clone)]
-pub struct StabilityController {
-  pub stability_threshold_1: UFix64<N2>,
-  pub stability_threshold_2: UFix64<N2>,
+pub struct StabilityController<const N: usize = 3> {
+  pub bands: [StabilityBand; N],
 }
 
 impl StabilityController {
-  /// Parses stability thresholds into controller.
+  /// Parses the program's two stability thresholds into controller, with
+  /// bands `Normal >= threshold_1 > Mode1 >= threshold_2 > Mode2 >= 1.0`.
   pub fn new(
     stability_threshold_1: UFix64<N2>,
     stability_threshold_2: UFix64<N2>,
   ) -> Result<StabilityController> {
-    let controller = StabilityController {
-      stability_threshold_1,
-      stability_threshold_2,
-    };
+    StabilityController::from_bands([
+      StabilityBand::new(stability_threshold_1, Normal),
+      StabilityBand::new(stability_threshold_2, Mode1),
+      StabilityBand::new(UFix64::one(), Mode2),
+    ])
+  }
+
+  /// Threshold below which the protocol leaves `Normal` mode.
+  #[must_use]
+  pub fn stability_threshold_1(&self) -> UFix64<N2> {
+    let [normal, _, _] = self.bands;
+    normal.threshold
+  }
+
+  /// Threshold below which the protocol enters `Mode2`.
+  #[must_use]
+  pub fn stability_threshold_2(&self) -> UFix64<N2> {
+    let [_, mode_1, _] = self.bands;
+    mode_1.threshold
+  }
+}
+
+impl<const N: usize> StabilityController<N> {
+  /// Builds a controller from bands ordered by descending threshold.
+  pub fn from_bands(
+    bands: [StabilityBand; N],
+  ) -> Result<StabilityController<N>> {
+    let controller = StabilityController { bands };
     controller.validate()?;
     Ok(controller)
   }
@@ -56,18 +183,32 @@ impl StabilityController {
     collateral_ratio: UFix64<N9>,
   ) -> Result<StabilityMode> {
     Ok(
-      if collateral_ratio >= self.stability_threshold_1.convert() {
-        Normal
-      } else if collateral_ratio >= self.stability_threshold_2.convert() {
-        Mode1
-      } else if collateral_ratio >= UFix64::one() {
-        Mode2
-      } else {
-        Depeg
-      },
+      self
+        .bands
+        .iter()
+        .find(|band| meets(collateral_ratio, band.threshold))
+        .map_or(Depeg, |band| band.mode),
     )
   }
 
+  /// Collateral ratio range of the band `collateral_ratio` falls in.
+  pub fn mode_bounds(
+    &self,
+    collateral_ratio: UFix64<N9>,
+  ) -> Result<ModeBounds> {
+    let mode = self.stability_mode(collateral_ratio)?;
+    Ok(ModeBounds {
+      mode,
+      lower: self.next_stability_threshold(mode),
+      upper: self.prev_stability_threshold(mode),
+    })
+  }
+
+  /// Position of the band configured for `mode`.
+  fn band_index(&self, mode: StabilityMode) -> Option<usize> {
+    self.bands.iter().position(|band| band.mode == mode)
+  }
+
   /// Like [`Self::next_stability_threshold`] but in reverse order.
   /// Yields the previously higher threshold.
   #[must_use]
@@ -75,12 +216,12 @@ impl StabilityController {
     &self,
     mode: StabilityMode,
   ) -> Option<UFix64<N2>> {
-    match mode {
-      Normal => None,
-      Mode1 => Some(self.stability_threshold_1),
-      Mode2 => Some(self.stability_threshold_2),
-      Depeg => Some(UFix64::one()),
+    match self.band_index(mode) {
+      Some(index) => index.checked_sub(1).and_then(|i| self.bands.get(i)),
+      None if mode == Depeg => self.bands.last(),
+      None => None,
     }
+    .map(|band| band.threshold)
   }
 
   /// Given the current stability mode, returns the next lower CR threshold.
@@ -91,29 +232,41 @@ impl StabilityController {
     &self,
     mode: StabilityMode,
   ) -> Option<UFix64<N2>> {
-    match mode {
-      Normal => Some(self.stability_threshold_1),
-      Mode1 => Some(self.stability_threshold_2),
-      Mode2 => Some(UFix64::one()),
-      Depeg => None,
-    }
+    self
+      .band_index(mode)
+      .and_then(|i| self.bands.get(i))
+      .map(|band| band.threshold)
   }
 
-  /// Lowest tolerable threshold.
+  /// Lowest tolerable threshold, the lowest one above the 1.0 peg.
   #[must_use]
   pub fn min_stability_threshold(&self) -> UFix64<N2> {
-    self.stability_threshold_2
+    self
+      .bands
+      .iter()
+      .rev()
+      .map(|band| band.threshold)
+      .find(|threshold| *threshold > UFix64::one())
+      .unwrap_or_else(UFix64::one)
   }
 
-  /// Ensures stability thresholds:
-  ///   - Are greater than 1.0
-  ///   - Have 2 decimal places `X.XX`
-  ///   - Mode 1 threshold is greater than mode 2 threshold
+  /// Ensures stability bands:
+  ///   - Have strictly descending thresholds with 2 decimal places `X.XX`
+  ///   - Have strictly ascending modes, none of them `Depeg`
+  ///   - End at or above 1.0
   pub fn validate(&self) -> Result<()> {
-    let t1 = self.stability_threshold_1;
-    let t2 = self.stability_threshold_2;
-    let one = UFix64::one();
-    if t1 > t2 && t1 > one && t2 > one {
+    let ordered = self.bands.windows(2).all(|pair| match pair {
+      [higher, lower] => {
+        higher.threshold > lower.threshold && higher.mode < lower.mode
+      }
+      _ => false,
+    });
+    let pegged = self
+      .bands
+      .last()
+      .is_some_and(|band| band.threshold >= UFix64::one());
+    let no_depeg = self.bands.iter().all(|band| band.mode != Depeg);
+    if ordered && pegged && no_depeg {
       Ok(())
     } else {
       Err(StabilityValidation.into())
@@ -123,11 +276,100 @@ impl StabilityController {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
   #[test]
   fn stability_mode_ord() {
-    use super::StabilityMode::*;
     assert!(Normal < Mode1);
     assert!(Mode1 < Mode2);
     assert!(Mode2 < Depeg);
   }
+
+  #[test]
+  fn mode_neighbours() {
+    assert_eq!(StabilityMode::iter().count(), 4);
+    assert_eq!(Normal.better(), None);
+    assert_eq!(Normal.worse(), Some(Mode1));
+    assert_eq!(Mode2.better(), Some(Mode1));
+    assert_eq!(Depeg.worse(), None);
+    assert!(!Normal.is_degraded());
+    assert!(StabilityMode::iter()
+      .skip(1)
+      .all(StabilityMode::is_degraded));
+  }
+
+  #[test]
+  fn bounds_of_bands() -> Result<()> {
+    let controller =
+      StabilityController::new(UFix64::new(150), UFix64::new(130))?;
+    let mode1 = controller.mode_bounds(UFix64::new(1_400_000_000))?;
+    assert_eq!(
+      mode1,
+      ModeBounds {
+        mode: Mode1,
+        lower: Some(UFix64::new(130)),
+        upper: Some(UFix64::new(150)),
+      }
+    );
+    assert!(mode1.contains(UFix64::new(1_300_000_000)));
+    assert!(!mode1.contains(UFix64::new(1_500_000_000)));
+    let normal = controller.mode_bounds(UFix64::new(2_000_000_000))?;
+    assert_eq!(normal.upper, None);
+    let depeg = controller.mode_bounds(UFix64::new(900_000_000))?;
+    assert_eq!((depeg.lower, depeg.upper), (None, Some(UFix64::one())));
+    Ok(())
+  }
+
+  #[test]
+  fn two_threshold_bands() -> Result<()> {
+    let controller =
+      StabilityController::new(UFix64::new(150), UFix64::new(130))?;
+    let mode = |cr: u64| controller.stability_mode(UFix64::new(cr));
+    assert_eq!(mode(1_500_000_000)?, Normal);
+    assert_eq!(mode(1_499_999_999)?, Mode1);
+    assert_eq!(mode(1_300_000_000)?, Mode1);
+    assert_eq!(mode(1_000_000_000)?, Mode2);
+    assert_eq!(mode(999_999_999)?, Depeg);
+    assert_eq!(
+      controller.next_stability_threshold(Mode1),
+      Some(UFix64::new(130))
+    );
+    assert_eq!(
+      controller.prev_stability_threshold(Depeg),
+      Some(UFix64::one())
+    );
+    assert_eq!(controller.prev_stability_threshold(Normal), None);
+    assert_eq!(controller.min_stability_threshold(), UFix64::new(130));
+    assert!(
+      StabilityController::new(UFix64::new(130), UFix64::new(150)).is_err()
+    );
+    assert!(
+      StabilityController::new(UFix64::new(150), UFix64::new(100)).is_err()
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn config_driven_bands() -> Result<()> {
+    let controller = StabilityController::from_bands([
+      StabilityBand::new(UFix64::new(200), Normal),
+      StabilityBand::new(UFix64::new(120), Mode2),
+    ])?;
+    assert_eq!(
+      controller.stability_mode(UFix64::new(1_500_000_000))?,
+      Mode2
+    );
+    assert_eq!(
+      controller.stability_mode(UFix64::new(1_100_000_000))?,
+      Depeg
+    );
+    assert_eq!(controller.next_stability_threshold(Mode1), None);
+    assert_eq!(controller.min_stability_threshold(), UFix64::new(120));
+    let unordered = StabilityController::from_bands([
+      StabilityBand::new(UFix64::new(120), Normal),
+      StabilityBand::new(UFix64::new(200), Mode1),
+    ]);
+    assert!(unordered.is_err());
+    Ok(())
+  }
 }
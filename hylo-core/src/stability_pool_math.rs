@@ -1,12 +1,15 @@
 use anchor_lang::prelude::*;
 use fix::prelude::*;
 
+use crate::bootstrap::initial_lp_token_nav;
 use crate::conversion::SwapConversion;
 use crate::error::CoreError::{
-  LpTokenNav, LpTokenOut, StabilityPoolCap, StablecoinToSwap, TokenWithdraw,
+  DepositCapacity, EmptyPoolWithdraw, LpTokenNav, LpTokenOut, StabilityPoolCap,
+  StablecoinToSwap, TokenWithdraw,
 };
 use crate::fee_controller::FeeExtract;
 use crate::pyth::PriceRange;
+use crate::withdrawal_fee::WithdrawalFee;
 
 /// Calculates total dollar value of stablecoin and levercoin in stability pool.
 ///
@@ -36,6 +39,8 @@ pub fn stability_pool_cap(
 /// lp_token_nav =  --------------------
 ///                   lp_token_supply
 /// ```
+///
+/// NB: If LP token supply is zero, returns the initial NAV of $1.
 pub fn lp_token_nav(
   stablecoin_nav: UFix64<N9>,
   stablecoin_in_pool: UFix64<N6>,
@@ -44,7 +49,7 @@ pub fn lp_token_nav(
   lp_token_supply: UFix64<N6>,
 ) -> Result<UFix64<N6>> {
   if lp_token_supply == UFix64::zero() {
-    Ok(UFix64::one())
+    Ok(initial_lp_token_nav())
   } else {
     let total_cap = stability_pool_cap(
       stablecoin_nav,
@@ -59,24 +64,34 @@ pub fn lp_token_nav(
 }
 
 /// Simply divides the amount of stablecoin being deposited by the LP token NAV.
+/// Fails with [`LpTokenOut`] if the NAV is zero.
 pub fn lp_token_out(
   amount_stablecoin_in: UFix64<N6>,
   lp_token_nav: UFix64<N6>,
 ) -> Result<UFix64<N6>> {
-  amount_stablecoin_in
-    .mul_div_floor(UFix64::one(), lp_token_nav)
-    .ok_or(LpTokenOut.into())
+  if lp_token_nav == UFix64::zero() {
+    Err(LpTokenOut.into())
+  } else {
+    amount_stablecoin_in
+      .mul_div_floor(UFix64::one(), lp_token_nav)
+      .ok_or(LpTokenOut.into())
+  }
 }
 
 /// Computes amount of token to withdraw, given a user's LP equity in the pool.
+/// Fails with [`EmptyPoolWithdraw`] if there is no LP token supply.
 pub fn amount_token_to_withdraw(
   user_lp_token_amount: UFix64<N6>,
   lp_token_supply: UFix64<N6>,
   pool_amount: UFix64<N6>,
 ) -> Result<UFix64<N6>> {
-  user_lp_token_amount
-    .mul_div_floor(pool_amount, lp_token_supply)
-    .ok_or(TokenWithdraw.into())
+  if lp_token_supply == UFix64::zero() {
+    Err(EmptyPoolWithdraw.into())
+  } else {
+    user_lp_token_amount
+      .mul_div_floor(pool_amount, lp_token_supply)
+      .ok_or(TokenWithdraw.into())
+  }
 }
 
 /// Given the next target highest stability threshold, determines the amount
@@ -131,7 +146,7 @@ pub fn stablecoin_withdrawal_fee(
   stablecoin_nav: UFix64<N9>,
   levercoin_to_withdraw: UFix64<N6>,
   levercoin_nav: UFix64<N9>,
-  withdrawal_fee: UFix64<N4>,
+  withdrawal_fee: WithdrawalFee,
 ) -> Result<FeeExtract<N6>> {
   let allocation_cap = stability_pool_cap(
     stablecoin_nav,
@@ -142,7 +157,7 @@ pub fn stablecoin_withdrawal_fee(
   let FeeExtract {
     fees_extracted: proposed_fee_stablecoin,
     ..
-  } = FeeExtract::new(withdrawal_fee, allocation_cap)?;
+  } = withdrawal_fee.apply(allocation_cap)?;
   let fees_extracted = proposed_fee_stablecoin.min(stablecoin_in_pool);
   let amount_remaining = stablecoin_to_withdraw.saturating_sub(&fees_extracted);
   Ok(FeeExtract {
@@ -151,6 +166,25 @@ pub fn stablecoin_withdrawal_fee(
   })
 }
 
+/// Stablecoin that can still be deposited before the pool's capitalization
+/// reaches `cap_limit`, zero once it is at or above the limit.
+///
+/// ```txt
+///                      max(cap_limit - stability_pool_cap, 0)
+/// remaining_deposit = ----------------------------------------
+///                                  stable_nav
+/// ```
+pub fn remaining_deposit_capacity(
+  pool_cap: UFix64<N6>,
+  cap_limit: UFix64<N6>,
+  stablecoin_nav: UFix64<N9>,
+) -> Result<UFix64<N6>> {
+  cap_limit
+    .saturating_sub(&pool_cap)
+    .mul_div_floor(UFix64::one(), stablecoin_nav)
+    .ok_or(DepositCapacity.into())
+}
+
 #[cfg(test)]
 mod tests {
   use proptest::prelude::*;
@@ -422,4 +456,17 @@ mod tests {
     assert_eq!(levercoin_in_pool, got);
     Ok(())
   }
+
+  #[test]
+  fn remaining_deposit_capacity_below_and_above_limit() -> Result<()> {
+    let nav = UFix64::new(1_250_000_000);
+    let limit = UFix64::new(10_000_000_000);
+    let below =
+      remaining_deposit_capacity(UFix64::new(7_500_000_000), limit, nav)?;
+    assert_eq!(below, UFix64::new(2_000_000_000));
+    let above =
+      remaining_deposit_capacity(UFix64::new(12_000_000_000), limit, nav)?;
+    assert_eq!(above, UFix64::zero());
+    Ok(())
+  }
 }
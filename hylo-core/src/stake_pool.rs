@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::{
+  StakePoolDeserialize, StakePoolOutdated, StakePoolPrice,
+};
+use crate::lst_sol_price::LstSolPrice;
+
+/// Byte offset of `total_lamports` in an SPL stake pool account.
+/// Preceded by account type, three authorities, bump, validator list, reserve,
+/// pool mint, manager fee account, and token program.
+const TOTAL_LAMPORTS_OFFSET: usize = 258;
+
+/// Subset of SPL stake pool state needed to price its pool token.
+/// Sanctum single and multi validator pools share this layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakePoolSnapshot {
+  pub total_lamports: u64,
+  pub pool_token_supply: u64,
+  pub last_update_epoch: u64,
+}
+
+impl StakePoolSnapshot {
+  /// Reads snapshot fields from raw SPL stake pool account data.
+  pub fn try_from_spl_bytes(data: &[u8]) -> Result<StakePoolSnapshot> {
+    let read = |index: usize| {
+      let start = TOTAL_LAMPORTS_OFFSET + index * 8;
+      data
+        .get(start..start + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(StakePoolDeserialize)
+    };
+    Ok(StakePoolSnapshot {
+      total_lamports: read(0)?,
+      pool_token_supply: read(1)?,
+      last_update_epoch: read(2)?,
+    })
+  }
+
+  /// Pool token price in SOL, as `total_lamports / pool_token_supply`.
+  pub fn lst_sol_price(&self) -> Result<UFix64<N9>> {
+    UFix64::<N9>::new(self.total_lamports)
+      .mul_div_floor(UFix64::<N9>::one(), UFix64::new(self.pool_token_supply))
+      .ok_or(StakePoolPrice.into())
+  }
+
+  /// Estimates the LST price for `current_epoch` from stake pool state.
+  /// Requires the stake pool itself to be updated for the epoch.
+  pub fn estimate_epoch_price(
+    &self,
+    current_epoch: u64,
+  ) -> Result<LstSolPrice> {
+    if self.last_update_epoch == current_epoch {
+      let price = self.lst_sol_price()?;
+      Ok(LstSolPrice::new(price.into(), current_epoch))
+    } else {
+      Err(StakePoolOutdated.into())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::{StakePoolSnapshot, TOTAL_LAMPORTS_OFFSET};
+  use crate::error::CoreError::{StakePoolDeserialize, StakePoolOutdated};
+
+  fn spl_bytes(snapshot: StakePoolSnapshot) -> Vec<u8> {
+    let mut data = vec![0u8; TOTAL_LAMPORTS_OFFSET];
+    data.extend(snapshot.total_lamports.to_le_bytes());
+    data.extend(snapshot.pool_token_supply.to_le_bytes());
+    data.extend(snapshot.last_update_epoch.to_le_bytes());
+    data
+  }
+
+  #[test]
+  fn parse_round_trip() -> Result<()> {
+    let snapshot = StakePoolSnapshot {
+      total_lamports: 1_250_000_000_000,
+      pool_token_supply: 1_000_000_000_000,
+      last_update_epoch: 800,
+    };
+    let parsed = StakePoolSnapshot::try_from_spl_bytes(&spl_bytes(snapshot))?;
+    assert_eq!(parsed, snapshot);
+    assert_eq!(parsed.lst_sol_price()?, UFix64::new(1_250_000_000));
+    Ok(())
+  }
+
+  #[test]
+  fn truncated_account() {
+    let out = StakePoolSnapshot::try_from_spl_bytes(&[0u8; 270]);
+    assert_eq!(out, Err(StakePoolDeserialize.into()));
+  }
+
+  #[test]
+  fn outdated_pool() {
+    let snapshot = StakePoolSnapshot {
+      total_lamports: 1,
+      pool_token_supply: 1,
+      last_update_epoch: 799,
+    };
+    let out = snapshot.estimate_epoch_price(800);
+    assert_eq!(out, Err(StakePoolOutdated.into()));
+  }
+}
@@ -0,0 +1,38 @@
+//! Exchange state shared by unit tests across modules.
+
+use anchor_lang::prelude::{Clock, Result};
+use fix::prelude::*;
+
+use crate::exchange_context::{ExchangeContext, ExchangeContextSnapshot};
+use crate::fee_controller::{FeePair, LevercoinFees, StablecoinFees};
+
+/// 0.1% to mint, 0.2% to redeem.
+pub fn fee_pair() -> FeePair {
+  FeePair::new(UFix64::<N4>::new(10).into(), UFix64::<N4>::new(20).into())
+}
+
+/// 2,000 SOL at $150-151 backing 100,000 hyUSD and 50,000 xSOL, charging
+/// [`fee_pair`] in every mode.
+pub fn snapshot() -> ExchangeContextSnapshot {
+  ExchangeContextSnapshot {
+    slot: 1,
+    epoch_start_timestamp: 2,
+    epoch: 3,
+    leader_schedule_epoch: 4,
+    unix_timestamp: 5,
+    total_sol: UFix64::<N9>::new(2_000_000_000_000).into(),
+    sol_usd_lower: UFix64::<N8>::new(15_000_000_000).into(),
+    sol_usd_upper: UFix64::<N8>::new(15_100_000_000).into(),
+    stablecoin_supply: UFix64::<N6>::new(100_000_000_000).into(),
+    levercoin_supply: Some(UFix64::<N6>::new(50_000_000_000).into()),
+    stability_threshold_1: UFix64::<N2>::new(150).into(),
+    stability_threshold_2: UFix64::<N2>::new(130).into(),
+    stablecoin_fees: StablecoinFees::new(fee_pair(), fee_pair()),
+    levercoin_fees: LevercoinFees::new(fee_pair(), fee_pair(), fee_pair()),
+  }
+}
+
+/// Context restored from [`snapshot`].
+pub fn context() -> Result<ExchangeContext<Clock>> {
+  snapshot().restore()
+}
@@ -0,0 +1,173 @@
+//! Amounts tagged with the token they denominate.
+//!
+//! Raw `UFix64<N6>` values are interchangeable between hyUSD, xSOL, and
+//! sHYUSD, and `UFix64<N9>` between SOL and every LST. [`TokenAmount`] carries
+//! the token as a type parameter so that mixing them is a compile error.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+use fix::prelude::*;
+
+use crate::amount_format::{format_amount, parse_amount};
+use crate::error::CoreError::AmountSymbol;
+use crate::idl::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
+
+/// Native SOL, identified by the wrapped SOL mint.
+pub struct SOL;
+
+impl TokenMint for SOL {
+  type Exp = N9;
+  const MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+  const SYMBOL: &'static str = "SOL";
+}
+
+/// Amount of token `T` in its base units.
+///
+/// `Display` renders `1,234.56 hyUSD`; `FromStr` accepts the amount with or
+/// without a trailing ` hyUSD`.
+pub struct TokenAmount<T: TokenMint> {
+  amount: UFix64<T::Exp>,
+  token: PhantomData<T>,
+}
+
+pub type HyusdAmount = TokenAmount<HYUSD>;
+pub type XsolAmount = TokenAmount<XSOL>;
+pub type ShyusdAmount = TokenAmount<SHYUSD>;
+pub type SolAmount = TokenAmount<SOL>;
+
+/// Amount of the LST with mint marker `M`.
+pub type LstAmount<M> = TokenAmount<M>;
+
+impl<T: TokenMint> TokenAmount<T> {
+  #[must_use]
+  pub fn new(amount: UFix64<T::Exp>) -> TokenAmount<T> {
+    TokenAmount {
+      amount,
+      token: PhantomData,
+    }
+  }
+
+  #[must_use]
+  pub fn from_base_units(bits: u64) -> TokenAmount<T> {
+    TokenAmount::new(UFix64::new(bits))
+  }
+
+  #[must_use]
+  pub fn zero() -> TokenAmount<T> {
+    TokenAmount::from_base_units(0)
+  }
+
+  #[must_use]
+  pub fn amount(self) -> UFix64<T::Exp> {
+    self.amount
+  }
+
+  #[must_use]
+  pub fn base_units(self) -> u64 {
+    self.amount.bits
+  }
+
+  #[must_use]
+  pub fn checked_add(self, other: TokenAmount<T>) -> Option<TokenAmount<T>> {
+    self.amount.checked_add(&other.amount).map(TokenAmount::new)
+  }
+
+  #[must_use]
+  pub fn checked_sub(self, other: TokenAmount<T>) -> Option<TokenAmount<T>> {
+    self.amount.checked_sub(&other.amount).map(TokenAmount::new)
+  }
+}
+
+impl<T: TokenMint> From<TokenAmount<T>> for UFixValue64 {
+  fn from(amount: TokenAmount<T>) -> UFixValue64 {
+    amount.amount.into()
+  }
+}
+
+impl<T: TokenMint> Clone for TokenAmount<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T: TokenMint> Copy for TokenAmount<T> {}
+
+impl<T: TokenMint> PartialEq for TokenAmount<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.amount == other.amount
+  }
+}
+
+impl<T: TokenMint> Eq for TokenAmount<T> {}
+
+impl<T: TokenMint> PartialOrd for TokenAmount<T> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<T: TokenMint> Ord for TokenAmount<T> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.amount.cmp(&other.amount)
+  }
+}
+
+impl<T: TokenMint> Debug for TokenAmount<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "TokenAmount({self})")
+  }
+}
+
+impl<T: TokenMint> Display for TokenAmount<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {}", format_amount(self.amount), T::SYMBOL)
+  }
+}
+
+impl<T: TokenMint> FromStr for TokenAmount<T> {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<TokenAmount<T>> {
+    match s.split_once(' ') {
+      Some((_, symbol)) if symbol != T::SYMBOL => Err(AmountSymbol.into()),
+      Some((amount, _)) => parse_amount(amount).map(TokenAmount::new),
+      None => parse_amount(s).map(TokenAmount::new),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+
+  use super::{HyusdAmount, SolAmount, XsolAmount};
+  use crate::error::CoreError::AmountSymbol;
+
+  #[test]
+  fn display_with_symbol() {
+    let amount = HyusdAmount::from_base_units(1_234_560_000);
+    assert_eq!(amount.to_string(), "1,234.56 hyUSD");
+    let sol = SolAmount::from_base_units(1_500_000_000);
+    assert_eq!(sol.to_string(), "1.5 SOL");
+  }
+
+  #[test]
+  fn parse_with_symbol() -> Result<()> {
+    let amount: XsolAmount = "1,000.5 xSOL".parse()?;
+    assert_eq!(amount.base_units(), 1_000_500_000);
+    let bare: XsolAmount = "1000.5".parse()?;
+    assert_eq!(amount, bare);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_wrong_symbol() {
+    let amount = "10 hyUSD".parse::<XsolAmount>();
+    assert_eq!(amount, Err(AmountSymbol.into()));
+  }
+}
@@ -0,0 +1,154 @@
+//! Checked conversions between UI numbers and token base units.
+//!
+//! Conversions go through the shortest decimal representation of the input,
+//! so `0.1_f64` becomes exactly `0.1` rather than the nearest binary fraction
+//! scaled by `10^decimals`. Digits beyond the token's decimals are handled by
+//! an explicit [`Rounding`], with [`Rounding::Exact`] surfacing any loss.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use fix::typenum::Integer;
+use rust_decimal::Decimal;
+
+pub use crate::amount_format::Rounding;
+use crate::amount_format::{format_amount_plain, parse_amount_rounded};
+use crate::error::CoreError::UiAmountInvalid;
+
+/// Converts a UI `f64` into base units.
+///
+/// # Errors
+/// * `value` is NaN, infinite, or negative
+/// * Precision loss under [`Rounding::Exact`]
+/// * Overflow of `u64` base units
+pub fn from_f64<Exp: Integer>(
+  value: f64,
+  rounding: Rounding,
+) -> Result<UFix64<Exp>> {
+  if value.is_finite() && value >= 0.0 {
+    parse_amount_rounded(&value.abs().to_string(), rounding)
+  } else {
+    Err(UiAmountInvalid.into())
+  }
+}
+
+/// Nearest `f64` to the amount.
+#[must_use]
+pub fn to_f64<Exp: Integer>(amount: UFix64<Exp>) -> f64 {
+  // NB: Plain digit strings always parse as f64
+  format_amount_plain(amount).parse().unwrap_or(f64::NAN)
+}
+
+/// `f64` for the amount, only if it converts back to the same base units.
+#[must_use]
+pub fn to_f64_exact<Exp: Integer>(amount: UFix64<Exp>) -> Option<f64> {
+  let value = to_f64(amount);
+  from_f64::<Exp>(value, Rounding::Exact)
+    .ok()
+    .filter(|back| *back == amount)
+    .map(|_| value)
+}
+
+/// Converts a [`Decimal`] into base units.
+///
+/// # Errors
+/// * `value` is negative
+/// * Precision loss under [`Rounding::Exact`]
+/// * Overflow of `u64` base units
+pub fn from_decimal<Exp: Integer>(
+  value: Decimal,
+  rounding: Rounding,
+) -> Result<UFix64<Exp>> {
+  if value.is_sign_negative() && !value.is_zero() {
+    Err(UiAmountInvalid.into())
+  } else {
+    parse_amount_rounded(&value.abs().to_string(), rounding)
+  }
+}
+
+/// Exact [`Decimal`] for the amount.
+#[must_use]
+pub fn to_decimal<Exp: Integer>(amount: UFix64<Exp>) -> Decimal {
+  let scale = u32::from(Exp::to_i8().unsigned_abs());
+  Decimal::from_i128_with_scale(i128::from(amount.bits), scale)
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+  use proptest::prelude::*;
+  use rust_decimal::Decimal;
+
+  use super::*;
+  use crate::error::CoreError::{AmountOverflow, AmountPrecision};
+
+  proptest! {
+    #[test]
+    fn decimal_round_trip(bits in any::<u64>()) {
+      let amount = UFix64::<N9>::new(bits);
+      let back = from_decimal::<N9>(to_decimal(amount), Rounding::Exact)?;
+      prop_assert_eq!(back, amount);
+    }
+
+    #[test]
+    fn f64_exact_round_trip(bits in any::<u64>()) {
+      let amount = UFix64::<N6>::new(bits);
+      if let Some(value) = to_f64_exact(amount) {
+        prop_assert_eq!(from_f64::<N6>(value, Rounding::Exact)?, amount);
+      }
+    }
+  }
+
+  #[test]
+  fn from_f64_rounding() -> Result<()> {
+    assert_eq!(from_f64::<N6>(0.1, Rounding::Exact)?, UFix64::new(100_000));
+    assert_eq!(
+      from_f64::<N6>(1.000_000_5, Rounding::Floor)?,
+      UFix64::new(1_000_000)
+    );
+    assert_eq!(
+      from_f64::<N6>(1.000_000_5, Rounding::Ceil)?,
+      UFix64::new(1_000_001)
+    );
+    assert_eq!(
+      from_f64::<N6>(1.000_000_5, Rounding::Exact),
+      Err(AmountPrecision.into())
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn from_f64_rejects() {
+    let invalid = Err(UiAmountInvalid.into());
+    assert_eq!(from_f64::<N6>(f64::NAN, Rounding::Floor), invalid);
+    assert_eq!(from_f64::<N6>(f64::INFINITY, Rounding::Floor), invalid);
+    assert_eq!(from_f64::<N6>(-1.0, Rounding::Floor), invalid);
+    assert_eq!(
+      from_f64::<N6>(1e20, Rounding::Floor),
+      Err(AmountOverflow.into())
+    );
+  }
+
+  #[test]
+  fn to_f64_lossy() {
+    let amount = UFix64::<N9>::new(u64::MAX);
+    assert_eq!(to_f64_exact(amount), None);
+    let value = to_f64(UFix64::<N9>::new(1_500_000_000));
+    assert!((value - 1.5).abs() < f64::EPSILON);
+  }
+
+  #[test]
+  fn from_decimal_rounding() -> Result<()> {
+    let value = Decimal::new(12_345, 4);
+    assert_eq!(
+      from_decimal::<N2>(value, Rounding::Floor)?,
+      UFix64::new(123)
+    );
+    assert_eq!(from_decimal::<N2>(value, Rounding::Ceil)?, UFix64::new(124));
+    assert_eq!(
+      from_decimal::<N2>(-value, Rounding::Floor),
+      Err(UiAmountInvalid.into())
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,269 @@
+//! Validated constructors for generated instruction arguments.
+//!
+//! The `args::*` structs from `declare_program!` take raw `u64` amounts and
+//! unchecked fee or slippage values, so a zero amount or a 100% fee is only
+//! caught on-chain. These constructors take typed amounts and reject such
+//! inputs with a [`CoreError`](crate::error::CoreError) before any
+//! instruction is built.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+use fix::typenum::Integer;
+
+use crate::error::CoreError::{
+  InvalidFees, OracleConfToleranceRange, SlippageToleranceRange,
+  ZeroInstructionAmount,
+};
+use crate::fee_controller::{FeeController, LevercoinFees, StablecoinFees};
+use crate::idl::exchange::client::args as exchange_args;
+use crate::idl::exchange::types as exchange_types;
+use crate::idl::stability_pool::client::args as pool_args;
+use crate::idl::stability_pool::types as pool_types;
+use crate::slippage_config::SlippageConfig;
+use crate::stability_mode::StabilityController;
+
+/// Base units of a non-zero amount.
+fn nonzero<Exp: Integer>(amount: UFix64<Exp>) -> Result<u64> {
+  if amount > UFix64::new(0) {
+    Ok(amount.bits)
+  } else {
+    Err(ZeroInstructionAmount.into())
+  }
+}
+
+/// Checks that slippage expects some output and tolerates less than 100%.
+fn slippage<Exp: Integer>(
+  config: Option<SlippageConfig>,
+) -> Result<Option<exchange_types::SlippageConfig>> {
+  config
+    .map(|config| {
+      let expected = config.expected_token_out::<Exp>()?;
+      let tolerance = config.slippage_tolerance()?;
+      if expected > UFix64::new(0) && tolerance < UFix64::one() {
+        Ok(config.into())
+      } else {
+        Err(SlippageToleranceRange.into())
+      }
+    })
+    .transpose()
+}
+
+/// Checks that a fee is below 100%.
+fn fee(fee: UFix64<N4>) -> Result<UFixValue64> {
+  if fee < UFix64::one() {
+    Ok(fee.into())
+  } else {
+    Err(InvalidFees.into())
+  }
+}
+
+/// Mints hyUSD from `amount_lst` of an LST.
+pub fn mint_stablecoin(
+  amount_lst: UFix64<N9>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::MintStablecoin> {
+  Ok(exchange_args::MintStablecoin {
+    amount_lst_to_deposit: nonzero(amount_lst)?,
+    slippage_config: slippage::<N6>(slippage_config)?,
+  })
+}
+
+/// Mints xSOL from `amount_lst` of an LST.
+pub fn mint_levercoin(
+  amount_lst: UFix64<N9>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::MintLevercoin> {
+  Ok(exchange_args::MintLevercoin {
+    amount_lst_to_deposit: nonzero(amount_lst)?,
+    slippage_config: slippage::<N6>(slippage_config)?,
+  })
+}
+
+/// Redeems `amount` hyUSD for an LST.
+pub fn redeem_stablecoin(
+  amount: UFix64<N6>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::RedeemStablecoin> {
+  Ok(exchange_args::RedeemStablecoin {
+    amount_to_redeem: nonzero(amount)?,
+    slippage_config: slippage::<N9>(slippage_config)?,
+  })
+}
+
+/// Redeems `amount` xSOL for an LST.
+pub fn redeem_levercoin(
+  amount: UFix64<N6>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::RedeemLevercoin> {
+  Ok(exchange_args::RedeemLevercoin {
+    amount_to_redeem: nonzero(amount)?,
+    slippage_config: slippage::<N9>(slippage_config)?,
+  })
+}
+
+/// Swaps `amount` hyUSD to xSOL.
+pub fn swap_stable_to_lever(
+  amount: UFix64<N6>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::SwapStableToLever> {
+  Ok(exchange_args::SwapStableToLever {
+    amount_stablecoin: nonzero(amount)?,
+    slippage_config: slippage::<N6>(slippage_config)?,
+  })
+}
+
+/// Swaps `amount` xSOL to hyUSD.
+pub fn swap_lever_to_stable(
+  amount: UFix64<N6>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::SwapLeverToStable> {
+  Ok(exchange_args::SwapLeverToStable {
+    amount_levercoin: nonzero(amount)?,
+    slippage_config: slippage::<N6>(slippage_config)?,
+  })
+}
+
+/// Swaps `amount_lst_a` of one LST to another.
+pub fn swap_lst(
+  amount_lst_a: UFix64<N9>,
+  slippage_config: Option<SlippageConfig>,
+) -> Result<exchange_args::SwapLst> {
+  Ok(exchange_args::SwapLst {
+    amount_lst_a: nonzero(amount_lst_a)?,
+    slippage_config: slippage::<N9>(slippage_config)?,
+  })
+}
+
+/// Deposits `amount` hyUSD into the stability pool.
+pub fn user_deposit(amount: UFix64<N6>) -> Result<pool_args::UserDeposit> {
+  Ok(pool_args::UserDeposit {
+    amount_stablecoin: nonzero(amount)?,
+  })
+}
+
+/// Withdraws `amount` sHYUSD from the stability pool.
+pub fn user_withdraw(amount: UFix64<N6>) -> Result<pool_args::UserWithdraw> {
+  Ok(pool_args::UserWithdraw {
+    amount_lp_token: nonzero(amount)?,
+  })
+}
+
+/// Sets the LST to LST swap fee.
+pub fn update_lst_swap_fee(
+  new_fee: UFix64<N4>,
+) -> Result<exchange_args::UpdateLstSwapFee> {
+  Ok(exchange_args::UpdateLstSwapFee {
+    new_lst_swap_fee: fee(new_fee)?.into(),
+  })
+}
+
+/// Sets the stability pool withdrawal fee.
+pub fn update_withdrawal_fee(
+  new_fee: UFix64<N4>,
+) -> Result<pool_args::UpdateWithdrawalFee> {
+  let UFixValue64 { bits, exp } = fee(new_fee)?;
+  Ok(pool_args::UpdateWithdrawalFee {
+    new_withdrawal_fee: pool_types::UFixValue64 { bits, exp },
+  })
+}
+
+/// Sets hyUSD mint and redeem fees, each below 100%.
+pub fn update_stablecoin_fees(
+  new_fees: exchange_types::StablecoinFees,
+) -> Result<exchange_args::UpdateStablecoinFees> {
+  StablecoinFees::from(new_fees).validate()?;
+  Ok(exchange_args::UpdateStablecoinFees {
+    new_stablecoin_fees: new_fees,
+  })
+}
+
+/// Sets xSOL mint and redeem fees, each below 100%.
+pub fn update_levercoin_fees(
+  new_fees: exchange_types::LevercoinFees,
+) -> Result<exchange_args::UpdateLevercoinFees> {
+  LevercoinFees::from(new_fees).validate()?;
+  Ok(exchange_args::UpdateLevercoinFees {
+    new_levercoin_fees: new_fees,
+  })
+}
+
+/// Sets the oracle confidence tolerance, as a share of price above 0% and
+/// below 100%.
+pub fn update_oracle_conf_tolerance(
+  tolerance: UFix64<N8>,
+) -> Result<exchange_args::UpdateOracleConfTolerance> {
+  if tolerance > UFix64::zero() && tolerance < UFix64::one() {
+    Ok(exchange_args::UpdateOracleConfTolerance {
+      new_oracle_conf_tolerance: UFixValue64::from(tolerance).into(),
+    })
+  } else {
+    Err(OracleConfToleranceRange.into())
+  }
+}
+
+/// Sets stability thresholds, `threshold_1 > threshold_2 > 1.0`.
+pub fn update_stability_thresholds(
+  threshold_1: UFix64<N2>,
+  threshold_2: UFix64<N2>,
+) -> Result<exchange_args::UpdateStabilityThresholds> {
+  StabilityController::new(threshold_1, threshold_2)?;
+  Ok(exchange_args::UpdateStabilityThresholds {
+    new_stability_threshold_1: UFixValue64::from(threshold_1).into(),
+    new_stability_threshold_2: UFixValue64::from(threshold_2).into(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn rejects_zero_amount() {
+    assert_eq!(
+      redeem_stablecoin(UFix64::zero(), None).map(|a| a.amount_to_redeem),
+      Err(ZeroInstructionAmount.into())
+    );
+  }
+
+  #[test]
+  fn rejects_full_slippage_tolerance() {
+    let config = SlippageConfig::new(UFix64::<N6>::new(1_000), UFix64::one());
+    assert_eq!(
+      mint_stablecoin(UFix64::new(1), Some(config))
+        .map(|a| a.amount_lst_to_deposit),
+      Err(SlippageToleranceRange.into())
+    );
+  }
+
+  #[test]
+  fn accepts_valid_args() -> Result<()> {
+    let config = SlippageConfig::new(UFix64::<N9>::new(1_000), UFix64::new(50));
+    let args = swap_lst(UFix64::new(5_000), Some(config))?;
+    assert_eq!(args.amount_lst_a, 5_000);
+    assert_eq!(user_withdraw(UFix64::new(7))?.amount_lp_token, 7);
+    assert_eq!(
+      update_withdrawal_fee(UFix64::one()).map(|a| a.new_withdrawal_fee.bits),
+      Err(InvalidFees.into())
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_oracle_conf_tolerance_out_of_range() -> Result<()> {
+    let args = update_oracle_conf_tolerance(UFix64::new(200_000))?;
+    assert_eq!(args.new_oracle_conf_tolerance.bits, 200_000);
+    [UFix64::zero(), UFix64::one()]
+      .into_iter()
+      .for_each(|tolerance| {
+        assert_eq!(
+          update_oracle_conf_tolerance(tolerance)
+            .map(|a| a.new_oracle_conf_tolerance.bits),
+          Err(OracleConfToleranceRange.into())
+        );
+      });
+    Ok(())
+  }
+}
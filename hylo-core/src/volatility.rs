@@ -0,0 +1,172 @@
+//! xSOL risk statistics over a reconstructed NAV history.
+//!
+//! Computes annualized volatility of xSOL NAV log returns, maximum drawdown
+//! from a running peak, and beta of xSOL returns to SOL/USD returns from
+//! [`NavSample`]s built by [`crate::nav_history::reconstruct`]. Statistics are
+//! approximate by nature, so they are computed in `f64`. Samples whose xSOL
+//! NAV saturated or hit zero are skipped.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::VolatilityWindow;
+use crate::nav_history::NavSample;
+use crate::ui_units::to_f64;
+
+const SECONDS_PER_YEAR: f64 = 31_536_000.0;
+
+/// Risk statistics for xSOL over one window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XsolRiskStats {
+  /// Samples used, after skipping saturated or zero NAVs
+  pub samples: usize,
+
+  /// Annualized standard deviation of NAV log returns
+  pub volatility: f64,
+
+  /// Largest peak-to-trough NAV decline, as a fraction of the peak
+  pub max_drawdown: f64,
+
+  /// Covariance of xSOL and SOL returns over SOL variance, `None` when SOL
+  /// did not move
+  pub beta_to_sol: Option<f64>,
+}
+
+fn count(n: usize) -> f64 {
+  f64::from(u32::try_from(n).unwrap_or(u32::MAX))
+}
+
+fn mean(values: &[f64]) -> f64 {
+  values.iter().sum::<f64>() / count(values.len())
+}
+
+/// Sample covariance of two equally long series.
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+  let (mean_a, mean_b) = (mean(a), mean(b));
+  let sum: f64 = a
+    .iter()
+    .zip(b)
+    .map(|(x, y)| (x - mean_a) * (y - mean_b))
+    .sum();
+  sum / count(a.len().saturating_sub(1))
+}
+
+/// Log returns between consecutive values.
+fn log_returns(values: &[f64]) -> Vec<f64> {
+  values
+    .windows(2)
+    .map(|pair| match pair {
+      [prev, next] => (next / prev).ln(),
+      _ => f64::NAN,
+    })
+    .collect()
+}
+
+/// Largest decline from a running peak, as a fraction of that peak.
+#[must_use]
+pub fn max_drawdown(values: &[f64]) -> f64 {
+  let (_, drawdown) =
+    values
+      .iter()
+      .fold((f64::MIN, 0.0_f64), |(peak, drawdown), value| {
+        let peak = peak.max(*value);
+        (peak, drawdown.max((peak - value) / peak))
+      });
+  drawdown
+}
+
+/// Volatility, drawdown and beta of xSOL over `samples`, sorted by time.
+///
+/// # Errors
+/// * Fewer than three usable samples
+/// * Samples span no time
+pub fn xsol_risk_stats(samples: &[NavSample]) -> Result<XsolRiskStats> {
+  let usable: Vec<(i64, f64, f64)> = samples
+    .iter()
+    .filter_map(|s| {
+      s.xsol_nav
+        .exact_value()
+        .filter(|nav| *nav > UFix64::zero())
+        .map(|nav| (s.timestamp, to_f64(nav), to_f64(s.sol_usd_price)))
+    })
+    .collect();
+  match (usable.first(), usable.last()) {
+    (Some((start, _, _)), Some((end, _, _)))
+      if usable.len() >= 3 && end > start =>
+    {
+      let navs: Vec<f64> = usable.iter().map(|(_, nav, _)| *nav).collect();
+      let sols: Vec<f64> = usable.iter().map(|(_, _, sol)| *sol).collect();
+      let xsol_returns = log_returns(&navs);
+      let sol_returns = log_returns(&sols);
+      let span = f64::from(i32::try_from(end - start).unwrap_or(i32::MAX));
+      let periods_per_year =
+        SECONDS_PER_YEAR * count(xsol_returns.len()) / span;
+      let variance = covariance(&xsol_returns, &xsol_returns);
+      let sol_variance = covariance(&sol_returns, &sol_returns);
+      let beta_to_sol = (sol_variance > 0.0)
+        .then(|| covariance(&xsol_returns, &sol_returns) / sol_variance);
+      Ok(XsolRiskStats {
+        samples: usable.len(),
+        volatility: (variance * periods_per_year).sqrt(),
+        max_drawdown: max_drawdown(&navs),
+        beta_to_sol,
+      })
+    }
+    _ => Err(VolatilityWindow.into()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+  use crate::analytics::Reported;
+
+  fn sample(timestamp: i64, xsol_nav: u64, sol_usd: u64) -> NavSample {
+    NavSample {
+      slot: 0,
+      timestamp,
+      sol_usd_price: UFix64::new(sol_usd),
+      hyusd_nav: Reported::exact(UFix64::one()),
+      xsol_nav: Reported::exact(UFix64::new(xsol_nav)),
+      shyusd_nav: None,
+      collateral_ratio: Reported::exact(UFix64::one()),
+    }
+  }
+
+  #[test]
+  fn drawdown_from_running_peak() {
+    let drawdown = max_drawdown(&[1.0, 2.0, 1.5, 3.0, 1.5, 2.0]);
+    assert!((drawdown - 0.5).abs() < 1e-12);
+  }
+
+  #[test]
+  fn leveraged_returns_have_beta_above_one() -> Result<()> {
+    let samples = [
+      sample(0, 1_000_000_000, 10_000_000_000),
+      sample(86_400, 1_200_000_000, 11_000_000_000),
+      sample(172_800, 900_000_000, 9_500_000_000),
+      sample(259_200, 1_100_000_000, 10_500_000_000),
+    ];
+    let stats = xsol_risk_stats(&samples)?;
+    assert_eq!(stats.samples, 4);
+    assert!(stats.beta_to_sol.is_some_and(|beta| beta > 1.0));
+    assert!((stats.max_drawdown - 0.25).abs() < 1e-12);
+    assert!(stats.volatility > 0.0);
+    Ok(())
+  }
+
+  #[test]
+  fn saturated_samples_skipped() {
+    let mut saturated = sample(86_400, 0, 10_000_000_000);
+    saturated.xsol_nav = Reported::saturated(UFix64::new(u64::MAX));
+    let samples = [
+      sample(0, 1_000_000_000, 10_000_000_000),
+      saturated,
+      sample(172_800, 1_100_000_000, 10_500_000_000),
+    ];
+    assert_eq!(xsol_risk_stats(&samples), Err(VolatilityWindow.into()));
+  }
+}
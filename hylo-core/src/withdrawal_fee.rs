@@ -0,0 +1,74 @@
+//! Stability pool withdrawal fee.
+//!
+//! The pool config stores the fee as a serialized `UFixValue64`. Quoting
+//! reads it through [`WithdrawalFee`] so the exponent and range are checked
+//! once, rather than converting raw bits at each withdrawal.
+
+use anchor_lang::Result;
+use fix::prelude::*;
+
+use crate::error::CoreError::InvalidFees;
+use crate::fee_controller::FeeExtract;
+
+/// Fee taken from sHYUSD withdrawals, as a fraction of the withdrawn value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawalFee {
+  fee: UFix64<N4>,
+}
+
+impl WithdrawalFee {
+  /// Fee must be less than 100%.
+  pub fn new(fee: UFix64<N4>) -> Result<WithdrawalFee> {
+    if fee < UFix64::one() {
+      Ok(WithdrawalFee { fee })
+    } else {
+      Err(InvalidFees.into())
+    }
+  }
+
+  #[must_use]
+  pub fn fee(&self) -> UFix64<N4> {
+    self.fee
+  }
+
+  /// Extracts the fee from a withdrawn amount.
+  pub fn apply<Exp>(&self, amount: UFix64<Exp>) -> Result<FeeExtract<Exp>> {
+    FeeExtract::new(self.fee, amount)
+  }
+}
+
+impl TryFrom<UFixValue64> for WithdrawalFee {
+  type Error = anchor_lang::error::Error;
+
+  fn try_from(serialized_fee: UFixValue64) -> Result<WithdrawalFee> {
+    WithdrawalFee::new(serialized_fee.try_into()?)
+  }
+}
+
+impl From<WithdrawalFee> for UFixValue64 {
+  fn from(withdrawal_fee: WithdrawalFee) -> UFixValue64 {
+    withdrawal_fee.fee.into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn apply_fee() -> Result<()> {
+    let withdrawal_fee = WithdrawalFee::try_from(UFixValue64::new(10, -4))?;
+    let result = withdrawal_fee.apply(UFix64::<N6>::new(1_000_000_000))?;
+    assert_eq!(result.fees_extracted, UFix64::new(1_000_000));
+    assert_eq!(result.amount_remaining, UFix64::new(999_000_000));
+    assert_eq!(UFixValue64::from(withdrawal_fee), UFixValue64::new(10, -4));
+    Ok(())
+  }
+
+  #[test]
+  fn reject_invalid_fee() {
+    assert!(WithdrawalFee::try_from(UFixValue64::new(10_000, -4)).is_err());
+    assert!(WithdrawalFee::try_from(UFixValue64::new(10, -2)).is_err());
+    assert!(WithdrawalFee::new(UFix64::zero()).is_ok());
+  }
+}
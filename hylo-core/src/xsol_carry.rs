@@ -0,0 +1,205 @@
+//! Funding-rate style carry of holding xSOL.
+//!
+//! xSOL is leveraged SOL exposure with no explicit borrow rate. Its holding
+//! cost or benefit comes from the protocol instead, and is quoted here as
+//! annualized rates on xSOL NAV that leverage traders can set against perp
+//! funding:
+//!
+//! * Yield share: LST staking yield left unharvested accrues to xSOL, on all of
+//!   the collateral behind it
+//! * Leverage decay: against `L` times the SOL log return, compounding returns
+//!   at leverage `L` lose `L (L - 1) σ² / 2` per year
+//! * Fee drag: mint and redeem fees of the stability mode, amortized over the
+//!   holding period
+//!
+//! Leverage follows from collateral ratio as `CR / (CR - 1)`. Like
+//! [`crate::volatility`], rates are approximate and computed in `f64`.
+
+use anchor_lang::prelude::*;
+use fix::prelude::*;
+
+use crate::error::CoreError::XsolCarryInputs;
+use crate::fee_controller::{FeeController, LevercoinFees};
+use crate::stability_mode::StabilityController;
+use crate::stability_mode::StabilityMode::{self, Mode1, Mode2, Normal};
+use crate::ui_units::to_f64;
+use crate::yields::YieldHarvestConfig;
+
+const SECONDS_PER_YEAR: f64 = 31_536_000.0;
+
+/// Market assumptions the protocol state does not carry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CarryAssumptions {
+  /// Annual staking yield of the LST collateral, e.g. `0.07`
+  pub lst_yield: f64,
+
+  /// Annualized volatility of SOL/USD
+  pub sol_volatility: f64,
+
+  /// Time between minting and redeeming xSOL, in seconds
+  pub holding_period: u32,
+}
+
+/// Annualized carry of xSOL in one stability mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XsolCarry {
+  pub mode: StabilityMode,
+
+  /// Effective leverage to SOL at the collateral ratio priced
+  pub leverage: f64,
+
+  /// Unharvested yield accruing to xSOL
+  pub yield_share: f64,
+
+  /// Compounding loss against `leverage` times the SOL log return
+  pub leverage_decay: f64,
+
+  /// Mint and redeem fees amortized over the holding period
+  pub fee_drag: f64,
+
+  /// `yield_share - leverage_decay - fee_drag`
+  pub net_rate: f64,
+
+  /// Net carry per unit of SOL notional with the perp sign convention,
+  /// positive when holders pay
+  pub funding_equivalent: f64,
+}
+
+/// Carry of xSOL minted and redeemed in `mode` at `collateral_ratio`.
+///
+/// # Errors
+/// * Collateral ratio at or below 1, or zero holding period
+/// * `mode` has no levercoin fees
+/// * Harvest allocation or fees fail to parse
+pub fn xsol_carry(
+  mode: StabilityMode,
+  collateral_ratio: UFix64<N9>,
+  levercoin_fees: &LevercoinFees,
+  harvest: &YieldHarvestConfig,
+  assumptions: &CarryAssumptions,
+) -> Result<XsolCarry> {
+  let cr = to_f64(collateral_ratio);
+  if cr > 1.0 && assumptions.holding_period > 0 {
+    let leverage = cr / (cr - 1.0);
+    let retained = 1.0 - to_f64(harvest.allocation()?);
+    let round_trip = to_f64(levercoin_fees.mint_fee(mode)?)
+      + to_f64(levercoin_fees.redeem_fee(mode)?);
+    let years = f64::from(assumptions.holding_period) / SECONDS_PER_YEAR;
+    let yield_share = retained * assumptions.lst_yield * leverage;
+    let leverage_decay =
+      leverage * (leverage - 1.0) * assumptions.sol_volatility.powi(2) / 2.0;
+    let fee_drag = round_trip / years;
+    let net_rate = yield_share - leverage_decay - fee_drag;
+    Ok(XsolCarry {
+      mode,
+      leverage,
+      yield_share,
+      leverage_decay,
+      fee_drag,
+      net_rate,
+      funding_equivalent: -net_rate / leverage,
+    })
+  } else {
+    Err(XsolCarryInputs.into())
+  }
+}
+
+/// Carry in each mode xSOL can be minted in.
+///
+/// `Normal` is priced at the current collateral ratio, or at threshold 1 when
+/// the protocol is already below it. `Mode1` and `Mode2` are priced at the
+/// threshold where they begin, their least leveraged point.
+///
+/// # Errors
+/// * See [`xsol_carry`]
+pub fn xsol_carry_by_mode(
+  collateral_ratio: UFix64<N9>,
+  controller: &StabilityController,
+  levercoin_fees: &LevercoinFees,
+  harvest: &YieldHarvestConfig,
+  assumptions: &CarryAssumptions,
+) -> Result<Vec<XsolCarry>> {
+  let threshold_1 = controller.stability_threshold_1().convert();
+  [
+    (Normal, collateral_ratio.max(threshold_1)),
+    (Mode1, threshold_1),
+    (Mode2, controller.stability_threshold_2().convert()),
+  ]
+  .into_iter()
+  .map(|(mode, cr)| xsol_carry(mode, cr, levercoin_fees, harvest, assumptions))
+  .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Result;
+  use fix::prelude::*;
+
+  use super::*;
+  use crate::fee_controller::FeePair;
+
+  fn fees() -> LevercoinFees {
+    let pair = |mint, redeem| {
+      FeePair::new(
+        UFix64::<N4>::new(mint).into(),
+        UFix64::<N4>::new(redeem).into(),
+      )
+    };
+    LevercoinFees::new(pair(50, 50), pair(0, 100), pair(0, 200))
+  }
+
+  fn harvest(allocation: u64) -> YieldHarvestConfig {
+    YieldHarvestConfig {
+      allocation: UFix64::<N4>::new(allocation).into(),
+      fee: UFix64::<N4>::new(500).into(),
+    }
+  }
+
+  const ASSUMPTIONS: CarryAssumptions = CarryAssumptions {
+    lst_yield: 0.07,
+    sol_volatility: 0.6,
+    holding_period: 31_536_000,
+  };
+
+  #[test]
+  fn carry_at_double_leverage() -> Result<()> {
+    let carry = xsol_carry(
+      Normal,
+      UFix64::new(2_000_000_000),
+      &fees(),
+      &harvest(5000),
+      &ASSUMPTIONS,
+    )?;
+    assert!((carry.leverage - 2.0).abs() < 1e-12);
+    assert!((carry.yield_share - 0.07).abs() < 1e-12);
+    assert!((carry.leverage_decay - 0.36).abs() < 1e-12);
+    assert!((carry.fee_drag - 0.01).abs() < 1e-12);
+    assert!((carry.funding_equivalent - 0.15).abs() < 1e-12);
+    Ok(())
+  }
+
+  #[test]
+  fn stressed_modes_are_more_leveraged() -> Result<()> {
+    let controller =
+      StabilityController::new(UFix64::new(150), UFix64::new(130))?;
+    let carries = xsol_carry_by_mode(
+      UFix64::new(2_000_000_000),
+      &controller,
+      &fees(),
+      &harvest(10_000),
+      &ASSUMPTIONS,
+    )?;
+    let leverages: Vec<f64> = carries.iter().map(|c| c.leverage).collect();
+    assert!(leverages.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(carries.iter().all(|c| c.yield_share.abs() < 1e-12));
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_undercollateralized() {
+    assert_eq!(
+      xsol_carry(Normal, UFix64::one(), &fees(), &harvest(5000), &ASSUMPTIONS),
+      Err(XsolCarryInputs.into())
+    );
+  }
+}
@@ -0,0 +1,51 @@
+//! Program IDs and PDA seeds mirrored from `idls/`.
+//!
+//! Without the `exchange` or `stability-pool` feature these stand in for the
+//! matching module generated by `declare_program!`, so consumers that only
+//! derive addresses skip Anchor entirely, and consumers of one program still
+//! reach the other's ID and seeds.
+
+use solana_pubkey::{pubkey, Pubkey};
+
+pub const TOKEN_PROGRAM: Pubkey =
+  pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub const ASSOCIATED_TOKEN_PROGRAM: Pubkey =
+  pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub const TOKEN_METADATA_PROGRAM: Pubkey =
+  pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+#[cfg(not(feature = "exchange"))]
+pub mod exchange {
+  use solana_pubkey::{pubkey, Pubkey};
+
+  pub const ID: Pubkey =
+    pubkey!("HYEXCHtHkBagdStcJCp3xbbb9B7sdMdWXFNj6mdsG4hn");
+
+  pub mod constants {
+    pub const FEE_AUTH: [u8; 8] = *b"fee_auth";
+    pub const HYLO: [u8; 4] = *b"hylo";
+    pub const HYUSD: [u8; 5] = *b"hyUSD";
+    pub const LST_HEADER: [u8; 10] = *b"lst_header";
+    pub const LST_REGISTRY_AUTH: [u8; 17] = *b"lst_registry_auth";
+    pub const MINT_AUTH: [u8; 9] = *b"mint_auth";
+    pub const POOL_AUTH: [u8; 9] = *b"pool_auth";
+    pub const VAULT_AUTH: [u8; 10] = *b"vault_auth";
+    pub const XSOL: [u8; 4] = *b"xSOL";
+  }
+}
+
+#[cfg(not(feature = "stability-pool"))]
+pub mod stability_pool {
+  use solana_pubkey::{pubkey, Pubkey};
+
+  pub const ID: Pubkey =
+    pubkey!("HysTabVUfmQBFcmzu1ctRd1Y1fxd66RBpboy1bmtDSQQ");
+
+  pub mod constants {
+    pub const POOL_AUTH: [u8; 9] = *b"pool_auth";
+    pub const POOL_CONFIG: [u8; 11] = *b"pool_config";
+    pub const STAKED_HYUSD: [u8; 12] = *b"staked_hyUSD";
+  }
+}
@@ -0,0 +1,80 @@
+//! Conversions to and from the Solana SDK 3.x crate line.
+//!
+//! The SDK is built against SDK 2.x types, which Anchor re-exports. With the
+//! `solana-v3` feature, [`SdkCompat`] moves pubkeys and instructions across to
+//! the types used by `solana-instruction` 3, so consumers pinned to the newer
+//! SDK can submit Hylo instructions without sharing a dependency tree.
+
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+/// Lossless conversion between an SDK 2.x type and its 3.x counterpart.
+pub trait SdkCompat: Sized {
+  type V3;
+
+  #[must_use]
+  fn to_v3(&self) -> Self::V3;
+
+  #[must_use]
+  fn from_v3(v3: &Self::V3) -> Self;
+}
+
+impl SdkCompat for Pubkey {
+  type V3 = solana_pubkey_v3::Pubkey;
+
+  fn to_v3(&self) -> solana_pubkey_v3::Pubkey {
+    solana_pubkey_v3::Pubkey::new_from_array(self.to_bytes())
+  }
+
+  fn from_v3(v3: &solana_pubkey_v3::Pubkey) -> Pubkey {
+    Pubkey::new_from_array(v3.to_bytes())
+  }
+}
+
+impl SdkCompat for AccountMeta {
+  type V3 = solana_instruction_v3::AccountMeta;
+
+  fn to_v3(&self) -> solana_instruction_v3::AccountMeta {
+    solana_instruction_v3::AccountMeta {
+      pubkey: self.pubkey.to_v3(),
+      is_signer: self.is_signer,
+      is_writable: self.is_writable,
+    }
+  }
+
+  fn from_v3(v3: &solana_instruction_v3::AccountMeta) -> AccountMeta {
+    AccountMeta {
+      pubkey: Pubkey::from_v3(&v3.pubkey),
+      is_signer: v3.is_signer,
+      is_writable: v3.is_writable,
+    }
+  }
+}
+
+impl SdkCompat for Instruction {
+  type V3 = solana_instruction_v3::Instruction;
+
+  fn to_v3(&self) -> solana_instruction_v3::Instruction {
+    solana_instruction_v3::Instruction {
+      program_id: self.program_id.to_v3(),
+      accounts: self.accounts.iter().map(SdkCompat::to_v3).collect(),
+      data: self.data.clone(),
+    }
+  }
+
+  fn from_v3(v3: &solana_instruction_v3::Instruction) -> Instruction {
+    Instruction {
+      program_id: Pubkey::from_v3(&v3.program_id),
+      accounts: v3.accounts.iter().map(AccountMeta::from_v3).collect(),
+      data: v3.data.clone(),
+    }
+  }
+}
+
+/// Converts a batch of instructions, e.g. a full Hylo transaction.
+#[must_use]
+pub fn instructions_to_v3(
+  instructions: &[Instruction],
+) -> Vec<solana_instruction_v3::Instruction> {
+  instructions.iter().map(SdkCompat::to_v3).collect()
+}
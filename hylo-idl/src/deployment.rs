@@ -0,0 +1,468 @@
+//! Program IDs, mints, PDAs and oracle feed of one Hylo deployment.
+//!
+//! Protocol token mints and every protocol PDA derive from the two program
+//! IDs, so a [`Deployment`] is fixed by those and its SOL/USD Pyth feed.
+//! [`Deployment::MAINNET`] backs the constants in [`crate::pda`] and
+//! [`crate::tokens`]. A fork bootstrapped under fresh program IDs is
+//! described with [`Deployment::custom`].
+//!
+//! Account and instruction builders take no [`Deployment`] and always target
+//! mainnet. Instructions for another deployment are built as for mainnet and
+//! retargeted with [`Deployment::remap_instructions`].
+
+use std::collections::HashMap;
+
+use solana_address_lookup_table_interface::program as address_lookup_table;
+use solana_instruction::Instruction;
+use solana_loader_v3_interface::get_program_data_address;
+use solana_pubkey::Pubkey;
+
+use crate::pda::{associated_token_address, metadata, SOL_USD_PYTH_FEED};
+use crate::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
+use crate::{exchange, stability_pool};
+
+/// Addresses of one Hylo deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Deployment {
+  pub exchange_program: Pubkey,
+  pub stability_pool_program: Pubkey,
+  pub hyusd_mint: Pubkey,
+  pub xsol_mint: Pubkey,
+  pub shyusd_mint: Pubkey,
+  pub sol_usd_pyth_feed: Pubkey,
+}
+
+impl Deployment {
+  /// Canonical mainnet deployment.
+  pub const MAINNET: Deployment = Deployment {
+    exchange_program: exchange::ID,
+    stability_pool_program: stability_pool::ID,
+    hyusd_mint: HYUSD::MINT,
+    xsol_mint: XSOL::MINT,
+    shyusd_mint: SHYUSD::MINT,
+    sol_usd_pyth_feed: SOL_USD_PYTH_FEED,
+  };
+
+  /// Deployment of programs at `exchange_program` and
+  /// `stability_pool_program`, reading SOL/USD from `sol_usd_pyth_feed`.
+  #[must_use]
+  pub fn custom(
+    exchange_program: Pubkey,
+    stability_pool_program: Pubkey,
+    sol_usd_pyth_feed: Pubkey,
+  ) -> Deployment {
+    let find = |seed: &[u8], program: &Pubkey| {
+      Pubkey::find_program_address(&[seed], program).0
+    };
+    Deployment {
+      exchange_program,
+      stability_pool_program,
+      hyusd_mint: find(exchange::constants::HYUSD.as_ref(), &exchange_program),
+      xsol_mint: find(exchange::constants::XSOL.as_ref(), &exchange_program),
+      shyusd_mint: find(
+        stability_pool::constants::STAKED_HYUSD.as_ref(),
+        &stability_pool_program,
+      ),
+      sol_usd_pyth_feed,
+    }
+  }
+
+  fn exchange_pda(&self, seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &self.exchange_program).0
+  }
+
+  fn stability_pool_pda(&self, seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &self.stability_pool_program).0
+  }
+
+  /// This deployment's mint for a protocol token marker; LSTs and other
+  /// tokens keep their mint.
+  #[must_use]
+  pub fn mint<T: TokenMint>(&self) -> Pubkey {
+    match T::MINT {
+      HYUSD::MINT => self.hyusd_mint,
+      XSOL::MINT => self.xsol_mint,
+      SHYUSD::MINT => self.shyusd_mint,
+      mint => mint,
+    }
+  }
+
+  #[must_use]
+  pub fn hylo(&self) -> Pubkey {
+    self.exchange_pda(&[exchange::constants::HYLO.as_ref()])
+  }
+
+  #[must_use]
+  pub fn hyusd_auth(&self) -> Pubkey {
+    self.exchange_pda(&[
+      exchange::constants::MINT_AUTH.as_ref(),
+      self.hyusd_mint.as_ref(),
+    ])
+  }
+
+  #[must_use]
+  pub fn xsol_auth(&self) -> Pubkey {
+    self.exchange_pda(&[
+      exchange::constants::MINT_AUTH.as_ref(),
+      self.xsol_mint.as_ref(),
+    ])
+  }
+
+  #[must_use]
+  pub fn lst_registry_auth(&self) -> Pubkey {
+    self.exchange_pda(&[exchange::constants::LST_REGISTRY_AUTH.as_ref()])
+  }
+
+  #[must_use]
+  pub fn new_lst_registry(&self, slot: u64) -> Pubkey {
+    Pubkey::find_program_address(
+      &[self.lst_registry_auth().as_ref(), &slot.to_le_bytes()],
+      &address_lookup_table::ID,
+    )
+    .0
+  }
+
+  #[must_use]
+  pub fn exchange_event_auth(&self) -> Pubkey {
+    self.exchange_pda(&[b"__event_authority"])
+  }
+
+  #[must_use]
+  pub fn stability_pool_event_auth(&self) -> Pubkey {
+    self.stability_pool_pda(&[b"__event_authority"])
+  }
+
+  #[must_use]
+  pub fn pool_config(&self) -> Pubkey {
+    self.stability_pool_pda(&[stability_pool::constants::POOL_CONFIG.as_ref()])
+  }
+
+  #[must_use]
+  pub fn shyusd_auth(&self) -> Pubkey {
+    self.stability_pool_pda(&[
+      exchange::constants::MINT_AUTH.as_ref(),
+      self.shyusd_mint.as_ref(),
+    ])
+  }
+
+  #[must_use]
+  pub fn pool_auth(&self) -> Pubkey {
+    self.stability_pool_pda(&[stability_pool::constants::POOL_AUTH.as_ref()])
+  }
+
+  #[must_use]
+  pub fn hyusd_pool(&self) -> Pubkey {
+    self.hyusd_ata(self.pool_auth())
+  }
+
+  #[must_use]
+  pub fn xsol_pool(&self) -> Pubkey {
+    self.xsol_ata(self.pool_auth())
+  }
+
+  #[must_use]
+  pub fn exchange_program_data(&self) -> Pubkey {
+    get_program_data_address(&self.exchange_program)
+  }
+
+  #[must_use]
+  pub fn stability_pool_program_data(&self) -> Pubkey {
+    get_program_data_address(&self.stability_pool_program)
+  }
+
+  #[must_use]
+  pub fn hyusd_ata(&self, auth: Pubkey) -> Pubkey {
+    associated_token_address(&auth, &self.hyusd_mint)
+  }
+
+  #[must_use]
+  pub fn xsol_ata(&self, auth: Pubkey) -> Pubkey {
+    associated_token_address(&auth, &self.xsol_mint)
+  }
+
+  #[must_use]
+  pub fn shyusd_ata(&self, auth: Pubkey) -> Pubkey {
+    associated_token_address(&auth, &self.shyusd_mint)
+  }
+
+  #[must_use]
+  pub fn vault(&self, mint: Pubkey) -> Pubkey {
+    associated_token_address(&self.vault_auth(mint), &mint)
+  }
+
+  #[must_use]
+  pub fn vault_auth(&self, mint: Pubkey) -> Pubkey {
+    self
+      .exchange_pda(&[exchange::constants::VAULT_AUTH.as_ref(), mint.as_ref()])
+  }
+
+  #[must_use]
+  pub fn lst_header(&self, mint: Pubkey) -> Pubkey {
+    self
+      .exchange_pda(&[exchange::constants::LST_HEADER.as_ref(), mint.as_ref()])
+  }
+
+  #[must_use]
+  pub fn fee_vault(&self, mint: Pubkey) -> Pubkey {
+    associated_token_address(&self.fee_auth(mint), &mint)
+  }
+
+  #[must_use]
+  pub fn fee_auth(&self, mint: Pubkey) -> Pubkey {
+    self.exchange_pda(&[exchange::constants::FEE_AUTH.as_ref(), mint.as_ref()])
+  }
+
+  /// Mainnet addresses that do not depend on instruction inputs, paired with
+  /// their counterpart in this deployment.
+  fn fixed_addresses(&self) -> HashMap<Pubkey, Pubkey> {
+    let main = Deployment::MAINNET;
+    [
+      (main.exchange_program, self.exchange_program),
+      (main.stability_pool_program, self.stability_pool_program),
+      (main.hyusd_mint, self.hyusd_mint),
+      (main.xsol_mint, self.xsol_mint),
+      (main.shyusd_mint, self.shyusd_mint),
+      (main.sol_usd_pyth_feed, self.sol_usd_pyth_feed),
+      (main.hylo(), self.hylo()),
+      (main.hyusd_auth(), self.hyusd_auth()),
+      (main.xsol_auth(), self.xsol_auth()),
+      (main.shyusd_auth(), self.shyusd_auth()),
+      (main.lst_registry_auth(), self.lst_registry_auth()),
+      (main.exchange_event_auth(), self.exchange_event_auth()),
+      (
+        main.stability_pool_event_auth(),
+        self.stability_pool_event_auth(),
+      ),
+      (main.pool_config(), self.pool_config()),
+      (main.pool_auth(), self.pool_auth()),
+      (main.hyusd_pool(), self.hyusd_pool()),
+      (main.xsol_pool(), self.xsol_pool()),
+      (main.exchange_program_data(), self.exchange_program_data()),
+      (
+        main.stability_pool_program_data(),
+        self.stability_pool_program_data(),
+      ),
+      (metadata(main.hyusd_mint), metadata(self.hyusd_mint)),
+      (metadata(main.xsol_mint), metadata(self.xsol_mint)),
+      (metadata(main.shyusd_mint), metadata(self.shyusd_mint)),
+    ]
+    .into_iter()
+    .collect()
+  }
+
+  /// Retargets instructions built against [`Deployment::MAINNET`] to this
+  /// deployment.
+  ///
+  /// Program IDs, mints, the Pyth feed and fixed PDAs are swapped directly.
+  /// Per-mint vaults, headers and fee accounts, and protocol token ATAs, are
+  /// swapped for every account the instructions reference. LST registry
+  /// lookup tables are keyed by slot and returned unchanged, as are mainnet
+  /// address lookup tables, which a custom deployment must not use.
+  #[must_use]
+  pub fn remap_instructions(
+    &self,
+    instructions: Vec<Instruction>,
+  ) -> Vec<Instruction> {
+    if *self == Deployment::MAINNET {
+      instructions
+    } else {
+      let main = Deployment::MAINNET;
+      let fixed = self.fixed_addresses();
+      let keyed: HashMap<Pubkey, Pubkey> = instructions
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .flat_map(|meta| {
+          let key = meta.pubkey;
+          let mapped = fixed.get(&key).copied().unwrap_or(key);
+          [
+            (main.vault(key), self.vault(mapped)),
+            (main.vault_auth(key), self.vault_auth(mapped)),
+            (main.lst_header(key), self.lst_header(mapped)),
+            (main.fee_vault(key), self.fee_vault(mapped)),
+            (main.fee_auth(key), self.fee_auth(mapped)),
+            (main.hyusd_ata(key), self.hyusd_ata(mapped)),
+            (main.xsol_ata(key), self.xsol_ata(mapped)),
+            (main.shyusd_ata(key), self.shyusd_ata(mapped)),
+          ]
+        })
+        .collect();
+      let remap = |key: Pubkey| {
+        fixed
+          .get(&key)
+          .or_else(|| keyed.get(&key))
+          .copied()
+          .unwrap_or(key)
+      };
+      instructions
+        .into_iter()
+        .map(|ix| Instruction {
+          program_id: remap(ix.program_id),
+          accounts: ix
+            .accounts
+            .into_iter()
+            .map(|meta| solana_instruction::AccountMeta {
+              pubkey: remap(meta.pubkey),
+              ..meta
+            })
+            .collect(),
+          data: ix.data,
+        })
+        .collect()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn custom() -> Deployment {
+    Deployment::custom(
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+    )
+  }
+
+  #[test]
+  fn custom_pdas_differ_from_mainnet() {
+    let main = Deployment::MAINNET;
+    let fork = custom();
+    let lst = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let addresses = |d: &Deployment| {
+      [
+        d.hyusd_mint,
+        d.xsol_mint,
+        d.shyusd_mint,
+        d.hylo(),
+        d.hyusd_auth(),
+        d.xsol_auth(),
+        d.shyusd_auth(),
+        d.lst_registry_auth(),
+        d.exchange_event_auth(),
+        d.stability_pool_event_auth(),
+        d.pool_config(),
+        d.pool_auth(),
+        d.hyusd_pool(),
+        d.xsol_pool(),
+        d.vault(lst),
+        d.vault_auth(lst),
+        d.lst_header(lst),
+        d.fee_vault(lst),
+        d.fee_auth(lst),
+        d.hyusd_ata(user),
+        d.xsol_ata(user),
+        d.shyusd_ata(user),
+      ]
+    };
+    addresses(&main)
+      .into_iter()
+      .zip(addresses(&fork))
+      .for_each(|(main, fork)| assert_ne!(main, fork));
+  }
+
+  #[test]
+  fn remapping_mainnet_is_identity() {
+    let ix = Instruction {
+      program_id: exchange::ID,
+      accounts: vec![solana_instruction::AccountMeta::new(
+        Deployment::MAINNET.hylo(),
+        false,
+      )],
+      data: vec![1],
+    };
+    assert_eq!(
+      Deployment::MAINNET.remap_instructions(vec![ix.clone()]),
+      vec![ix]
+    );
+  }
+
+  #[cfg(feature = "exchange")]
+  #[test]
+  fn remapped_mint_matches_direct_build() {
+    use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+    use anchor_spl::{associated_token, token};
+
+    use crate::exchange::client::{accounts, args};
+    use crate::exchange::instruction_builders;
+
+    let fork = custom();
+    let user = Pubkey::new_unique();
+    let lst_mint = Pubkey::new_unique();
+    let args = args::MintStablecoin {
+      amount_lst_to_deposit: 1_000,
+      slippage_config: None,
+    };
+    let direct = Instruction {
+      program_id: fork.exchange_program,
+      accounts: accounts::MintStablecoin {
+        user,
+        hylo: fork.hylo(),
+        fee_auth: fork.fee_auth(lst_mint),
+        vault_auth: fork.vault_auth(lst_mint),
+        stablecoin_auth: fork.hyusd_auth(),
+        fee_vault: fork.fee_vault(lst_mint),
+        lst_vault: fork.vault(lst_mint),
+        lst_header: fork.lst_header(lst_mint),
+        user_lst_ta: associated_token_address(&user, &lst_mint),
+        user_stablecoin_ta: fork.hyusd_ata(user),
+        lst_mint,
+        stablecoin_mint: fork.hyusd_mint,
+        sol_usd_pyth_feed: fork.sol_usd_pyth_feed,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+        event_authority: fork.exchange_event_auth(),
+        program: fork.exchange_program,
+      }
+      .to_account_metas(None),
+      data: args.data(),
+    };
+    let built = instruction_builders::mint_stablecoin(user, lst_mint, &args);
+    assert_eq!(fork.remap_instructions(vec![built]), vec![direct]);
+  }
+
+  #[cfg(feature = "stability-pool")]
+  #[test]
+  fn remapped_deposit_matches_direct_build() {
+    use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+    use anchor_spl::{associated_token, token};
+
+    use crate::stability_pool::client::{accounts, args};
+    use crate::stability_pool::instruction_builders;
+
+    let fork = custom();
+    let user = Pubkey::new_unique();
+    let args = args::UserDeposit {
+      amount_stablecoin: 1_000,
+    };
+    let direct = Instruction {
+      program_id: fork.stability_pool_program,
+      accounts: accounts::UserDeposit {
+        user,
+        pool_config: fork.pool_config(),
+        hylo: fork.hylo(),
+        stablecoin_mint: fork.hyusd_mint,
+        levercoin_mint: fork.xsol_mint,
+        user_stablecoin_ta: fork.hyusd_ata(user),
+        user_lp_token_ta: fork.shyusd_ata(user),
+        pool_auth: fork.pool_auth(),
+        stablecoin_pool: fork.hyusd_pool(),
+        levercoin_pool: fork.xsol_pool(),
+        lp_token_auth: fork.shyusd_auth(),
+        lp_token_mint: fork.shyusd_mint,
+        sol_usd_pyth_feed: fork.sol_usd_pyth_feed,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+        event_authority: fork.stability_pool_event_auth(),
+        program: fork.stability_pool_program,
+      }
+      .to_account_metas(None),
+      data: args.data(),
+    };
+    let built = instruction_builders::user_deposit(user, &args);
+    assert_eq!(fork.remap_instructions(vec![built]), vec![direct]);
+  }
+}
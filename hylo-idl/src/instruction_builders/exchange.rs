@@ -10,7 +10,7 @@ use crate::exchange::account_builders;
 use crate::exchange::client::{accounts, args};
 use crate::pda::{self, metadata};
 use crate::tokens::{TokenMint, HYUSD, XSOL};
-use crate::{exchange, stability_pool};
+use crate::{ata, exchange, stability_pool};
 
 #[must_use]
 pub fn mint_stablecoin(
@@ -277,6 +277,60 @@ pub fn update_stability_pool(
   }
 }
 
+#[must_use]
+pub fn update_stability_thresholds(
+  admin: Pubkey,
+  args: &args::UpdateStabilityThresholds,
+) -> Instruction {
+  let accounts = accounts::UpdateStabilityThresholds {
+    admin,
+    hylo: *pda::HYLO,
+    event_authority: *pda::EXCHANGE_EVENT_AUTH,
+    program: exchange::ID,
+  };
+  Instruction {
+    program_id: exchange::ID,
+    accounts: accounts.to_account_metas(None),
+    data: args.data(),
+  }
+}
+
+#[must_use]
+pub fn update_stablecoin_fees(
+  admin: Pubkey,
+  args: &args::UpdateStablecoinFees,
+) -> Instruction {
+  let accounts = accounts::UpdateStablecoinFees {
+    admin,
+    hylo: *pda::HYLO,
+    event_authority: *pda::EXCHANGE_EVENT_AUTH,
+    program: exchange::ID,
+  };
+  Instruction {
+    program_id: exchange::ID,
+    accounts: accounts.to_account_metas(None),
+    data: args.data(),
+  }
+}
+
+#[must_use]
+pub fn update_levercoin_fees(
+  admin: Pubkey,
+  args: &args::UpdateLevercoinFees,
+) -> Instruction {
+  let accounts = accounts::UpdateLevercoinFees {
+    admin,
+    hylo: *pda::HYLO,
+    event_authority: *pda::EXCHANGE_EVENT_AUTH,
+    program: exchange::ID,
+  };
+  Instruction {
+    program_id: exchange::ID,
+    accounts: accounts.to_account_metas(None),
+    data: args.data(),
+  }
+}
+
 #[must_use]
 pub fn harvest_yield(
   payer: Pubkey,
@@ -369,3 +423,31 @@ pub fn update_lst_swap_fee(
     data: args.data(),
   }
 }
+
+#[must_use]
+pub fn withdraw_fees(
+  payer: Pubkey,
+  treasury: Pubkey,
+  fee_token_mint: Pubkey,
+) -> Instruction {
+  let accounts = accounts::WithdrawFees {
+    payer,
+    treasury,
+    hylo: *pda::HYLO,
+    fee_auth: pda::fee_auth(fee_token_mint),
+    fee_vault: pda::fee_vault(fee_token_mint),
+    treasury_ata: ata!(treasury, fee_token_mint),
+    fee_token_mint,
+    associated_token_program: associated_token::ID,
+    token_program: token::ID,
+    system_program: system_program::ID,
+    event_authority: *pda::EXCHANGE_EVENT_AUTH,
+    program: exchange::ID,
+  };
+  let args = args::WithdrawFees {};
+  Instruction {
+    program_id: exchange::ID,
+    accounts: accounts.to_account_metas(None),
+    data: args.data(),
+  }
+}
@@ -1,2 +1,4 @@
+#[cfg(feature = "exchange")]
 pub mod exchange;
+#[cfg(feature = "stability-pool")]
 pub mod stability_pool;
@@ -1,27 +1,84 @@
+//! # Hylo IDL
+//!
+//! Anchor-generated clients for the exchange and stability pool programs,
+//! plus token markers and PDA helpers.
+//!
+//! The default `codegen` feature compiles `declare_program!` for both
+//! programs. Each program can also be enabled alone with the `exchange` or
+//! `stability-pool` feature, e.g. `default-features = false, features =
+//! ["stability-pool"]` for a pool-only integration. A program whose feature
+//! is off keeps its `ID` and `constants` from [`addresses`]. Consumers that
+//! only need addresses build with `default-features = false`, which keeps
+//! [`tokens`], [`pda`], and each program's `ID` and `constants` but drops
+//! Anchor and the generated client code. This stands in for an
+//! `addresses-only` feature: Cargo features are additive, so one could not
+//! switch off code that another dependency enables by default.
+//!
+//! [`deployment::Deployment`] bundles the program IDs, mints, PDAs and Pyth
+//! feed of mainnet or a custom deployment. Builders target mainnet, and
+//! [`deployment::Deployment::remap_instructions`] retargets their output.
+//! [`stake_pools`] maps supported LST mints to their stake pools and Sanctum
+//! calculators.
+//!
+//! Each program module's `errors` holds its custom error enum, recovered
+//! from the IDL since `declare_program!` does not generate one.
+//!
+//! Types come from the Solana SDK 2.x crates. The `solana-v3` feature adds
+//! [`compat`] conversions for consumers on SDK 3.x.
+
 #![allow(clippy::pub_underscore_fields)]
 
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
 extern crate anchor_lang;
 
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
 mod codegen {
+  #[cfg(feature = "exchange")]
   anchor_lang::declare_program!(hylo_exchange);
+  #[cfg(feature = "stability-pool")]
   anchor_lang::declare_program!(hylo_stability_pool);
 }
 
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
 mod account_builders;
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
 mod instruction_builders;
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
+mod program_errors;
 
+#[cfg(feature = "exchange")]
 pub mod exchange {
   pub use super::account_builders::exchange as account_builders;
   pub use super::codegen::hylo_exchange::*;
   pub use super::instruction_builders::exchange as instruction_builders;
+  pub use super::program_errors::exchange as errors;
+
+  /// IDL this module was generated from.
+  pub const IDL_JSON: &str = include_str!("../idls/hylo_exchange.json");
 }
 
+#[cfg(feature = "stability-pool")]
 pub mod stability_pool {
   pub use super::account_builders::stability_pool as account_builders;
   pub use super::codegen::hylo_stability_pool::*;
   pub use super::instruction_builders::stability_pool as instruction_builders;
+  pub use super::program_errors::stability_pool as errors;
+
+  /// IDL this module was generated from.
+  pub const IDL_JSON: &str = include_str!("../idls/hylo_stability_pool.json");
 }
 
+#[cfg(not(feature = "exchange"))]
+pub use addresses::exchange;
+#[cfg(not(feature = "stability-pool"))]
+pub use addresses::stability_pool;
+
+pub mod addresses;
+#[cfg(feature = "solana-v3")]
+pub mod compat;
+pub mod deployment;
 pub mod pda;
+pub mod stake_pools;
 pub mod tokens;
+#[cfg(any(feature = "exchange", feature = "stability-pool"))]
 pub mod type_bridge;
@@ -1,12 +1,11 @@
 use std::sync::LazyLock;
 
-use anchor_lang::prelude::Pubkey;
-use anchor_lang::solana_program::pubkey;
-use solana_address_lookup_table_interface::program as address_lookup_table;
-use solana_loader_v3_interface::get_program_data_address;
+use solana_pubkey::{pubkey, Pubkey};
 
-use crate::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
-use crate::{exchange, stability_pool};
+use crate::addresses::{
+  ASSOCIATED_TOKEN_PROGRAM, TOKEN_METADATA_PROGRAM, TOKEN_PROGRAM,
+};
+use crate::deployment::Deployment;
 
 macro_rules! lazy {
   ($x:expr) => {
@@ -14,134 +13,113 @@ macro_rules! lazy {
   };
 }
 
-macro_rules! pda {
-  ($program_id:expr, $base:expr) => {
-    Pubkey::find_program_address(&[$base.as_ref()], &$program_id).0
-  };
-  ($program_id:expr, $base:expr, $key:expr) => {
-    Pubkey::find_program_address(&[$base.as_ref(), $key.as_ref()], &$program_id)
-      .0
-  };
-}
-
 #[macro_export]
 macro_rules! ata {
   ($auth:expr, $mint:expr) => {
-    anchor_spl::associated_token::get_associated_token_address(&$auth, &$mint)
+    $crate::pda::associated_token_address(&$auth, &$mint)
   };
 }
 
+/// Associated token account of `auth` for a classic SPL Token `mint`.
+#[must_use]
+pub fn associated_token_address(auth: &Pubkey, mint: &Pubkey) -> Pubkey {
+  Pubkey::find_program_address(
+    &[auth.as_ref(), TOKEN_PROGRAM.as_ref(), mint.as_ref()],
+    &ASSOCIATED_TOKEN_PROGRAM,
+  )
+  .0
+}
+
 #[must_use]
 pub fn metadata(mint: Pubkey) -> Pubkey {
   Pubkey::find_program_address(
     &[
       "metadata".as_ref(),
-      mpl_token_metadata::ID.as_ref(),
+      TOKEN_METADATA_PROGRAM.as_ref(),
       mint.as_ref(),
     ],
-    &mpl_token_metadata::ID,
+    &TOKEN_METADATA_PROGRAM,
   )
   .0
 }
 
+const MAINNET: Deployment = Deployment::MAINNET;
+
 #[must_use]
 pub fn hyusd_ata(auth: Pubkey) -> Pubkey {
-  ata!(&auth, &HYUSD::MINT)
+  MAINNET.hyusd_ata(auth)
 }
 
 #[must_use]
 pub fn xsol_ata(auth: Pubkey) -> Pubkey {
-  ata!(&auth, &XSOL::MINT)
+  MAINNET.xsol_ata(auth)
 }
 
 #[must_use]
 pub fn shyusd_ata(auth: Pubkey) -> Pubkey {
-  ata!(&auth, &SHYUSD::MINT)
+  MAINNET.shyusd_ata(auth)
 }
 
 #[must_use]
 pub fn vault(mint: Pubkey) -> Pubkey {
-  ata!(&vault_auth(mint), &mint)
+  MAINNET.vault(mint)
 }
 
 #[must_use]
 pub fn vault_auth(mint: Pubkey) -> Pubkey {
-  pda!(exchange::ID, exchange::constants::VAULT_AUTH, mint)
+  MAINNET.vault_auth(mint)
 }
 
 #[must_use]
 pub fn new_lst_registry(slot: u64) -> Pubkey {
-  Pubkey::find_program_address(
-    &[LST_REGISTRY_AUTH.as_ref(), &slot.to_le_bytes()],
-    &address_lookup_table::ID,
-  )
-  .0
+  MAINNET.new_lst_registry(slot)
 }
 
 #[must_use]
 pub fn lst_header(mint: Pubkey) -> Pubkey {
-  pda!(exchange::ID, exchange::constants::LST_HEADER, mint)
+  MAINNET.lst_header(mint)
 }
 
 #[must_use]
 pub fn fee_vault(mint: Pubkey) -> Pubkey {
-  ata!(&fee_auth(mint), &mint)
+  MAINNET.fee_vault(mint)
 }
 
 #[must_use]
 pub fn fee_auth(mint: Pubkey) -> Pubkey {
-  pda!(exchange::ID, exchange::constants::FEE_AUTH, mint)
+  MAINNET.fee_auth(mint)
 }
 
-pub static HYLO: LazyLock<Pubkey> =
-  lazy!(pda!(exchange::ID, exchange::constants::HYLO));
+pub static HYLO: LazyLock<Pubkey> = lazy!(MAINNET.hylo());
 
-pub static HYUSD_AUTH: LazyLock<Pubkey> = lazy!(pda!(
-  exchange::ID,
-  exchange::constants::MINT_AUTH,
-  HYUSD::MINT
-));
+pub static HYUSD_AUTH: LazyLock<Pubkey> = lazy!(MAINNET.hyusd_auth());
 
-pub static XSOL_AUTH: LazyLock<Pubkey> = lazy!(pda!(
-  exchange::ID,
-  exchange::constants::MINT_AUTH,
-  XSOL::MINT
-));
+pub static XSOL_AUTH: LazyLock<Pubkey> = lazy!(MAINNET.xsol_auth());
 
 pub static LST_REGISTRY_AUTH: LazyLock<Pubkey> =
-  lazy!(pda!(exchange::ID, exchange::constants::LST_REGISTRY_AUTH));
+  lazy!(MAINNET.lst_registry_auth());
 
 pub static EXCHANGE_EVENT_AUTH: LazyLock<Pubkey> =
-  lazy!(pda!(exchange::ID, "__event_authority"));
+  lazy!(MAINNET.exchange_event_auth());
 
 pub static STABILITY_POOL_EVENT_AUTH: LazyLock<Pubkey> =
-  lazy!(pda!(stability_pool::ID, "__event_authority"));
+  lazy!(MAINNET.stability_pool_event_auth());
 
-pub static POOL_CONFIG: LazyLock<Pubkey> = lazy!(pda!(
-  stability_pool::ID,
-  stability_pool::constants::POOL_CONFIG
-));
+pub static POOL_CONFIG: LazyLock<Pubkey> = lazy!(MAINNET.pool_config());
 
-pub static SHYUSD_AUTH: LazyLock<Pubkey> = lazy!(pda!(
-  stability_pool::ID,
-  exchange::constants::MINT_AUTH,
-  SHYUSD::MINT
-));
+pub static SHYUSD_AUTH: LazyLock<Pubkey> = lazy!(MAINNET.shyusd_auth());
 
-pub static POOL_AUTH: LazyLock<Pubkey> = lazy!(pda!(
-  stability_pool::ID,
-  stability_pool::constants::POOL_AUTH
-));
+pub static POOL_AUTH: LazyLock<Pubkey> = lazy!(MAINNET.pool_auth());
 
-pub static HYUSD_POOL: LazyLock<Pubkey> = lazy!(ata!(POOL_AUTH, HYUSD::MINT));
+pub static HYUSD_POOL: LazyLock<Pubkey> = lazy!(MAINNET.hyusd_pool());
 
-pub static XSOL_POOL: LazyLock<Pubkey> = lazy!(ata!(POOL_AUTH, XSOL::MINT));
+pub static XSOL_POOL: LazyLock<Pubkey> = lazy!(MAINNET.xsol_pool());
 
 pub static STABILITY_POOL_PROGRAM_DATA: LazyLock<Pubkey> =
-  lazy!(get_program_data_address(&stability_pool::ID));
+  lazy!(MAINNET.stability_pool_program_data());
 
 pub static EXCHANGE_PROGRAM_DATA: LazyLock<Pubkey> =
-  lazy!(get_program_data_address(&exchange::ID));
+  lazy!(MAINNET.exchange_program_data());
 
 pub const SOL_USD_PYTH_FEED: Pubkey =
   pubkey!("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE");
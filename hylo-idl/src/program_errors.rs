@@ -0,0 +1,214 @@
+//! Custom errors of each program, from the `errors` section of its IDL.
+//!
+//! `declare_program!` does not generate error types, so failed transactions
+//! only carry an error number and name. These enums restore the variant,
+//! numbered from 6000 in IDL order, and convert into
+//! [`anchor_lang::error::Error`] like any `#[error_code]` enum. Both
+//! programs number from 6000, so match on the name as well as the number
+//! when the failing program is unknown.
+
+#[cfg(feature = "exchange")]
+pub mod exchange {
+  use anchor_lang::prelude::error_code;
+
+  #[error_code]
+  #[derive(PartialEq, Eq)]
+  pub enum ExchangeError {
+    #[msg(
+      "Cannot redeem levercoin due to stability mode. NAV would be 0 or lower."
+    )]
+    LevercoinRedeemDisabled,
+    #[msg("Levercoin to stablecoin swap disabled due to stability mode.")]
+    LeverToStableDisabled,
+    #[msg("Stablecoin to levercoin swap disabled due to stability mode.")]
+    StableToLeverDisabled,
+    #[msg("Error during CPI to Sanctum LST/SOL calculator.")]
+    SanctumCpi,
+    #[msg("LST registry cannot be initialized twice.")]
+    LstRegistryAlreadyInitialized,
+    #[msg(
+      "LST specific accounts found empty when attempting to load registry."
+    )]
+    LstRegistryEmpty,
+    #[msg(
+      "Sanctum calculator contexts in LST registry preamble are malformed."
+    )]
+    LstRegistryPreamble,
+    #[msg("Failed to deserialize registry lookup table.")]
+    LstRegistryLookupTableDeser,
+    #[msg("Contents of LST registry did not match remaining_accounts.")]
+    LstRegistryLookupTableInvalid,
+    #[msg("Mint/vault/pool accounts in registry block do not match header.")]
+    LstBlockInvalid,
+    #[msg(
+      "Attempted to register an LST with invalid Sanctum context accounts."
+    )]
+    LstContextInvalid,
+    #[msg("Addition overflow while computing total SOL in LST registry.")]
+    LstAdditionOverflow,
+    #[msg(
+      "Cached LST price not from current epoch. Run pricing crank to update."
+    )]
+    LstPriceOutdated,
+    #[msg("Failed to compute delta between current and previous LST prices.")]
+    LstPriceDelta,
+    #[msg("Found current epoch less than previous in LST header.")]
+    LstPriceEpochsInvalid,
+    #[msg("Overflow while computing LST SOL appreciation.")]
+    LstSolAppreciation,
+    #[msg("Stablecoin mint disabled. Protocol is in Mode2 or Depeg.")]
+    StablecoinMintDisabled,
+    #[msg("Levercoin mint disabled. Protocol is in Depeg.")]
+    LevercoinMintDisabled,
+    #[msg("Yield harvest configuration percentages failed validation.")]
+    YieldHarvestConfigValidation,
+    #[msg("Yield harvest disabled due to protocol collateral ratio.")]
+    YieldHarvestDisabled,
+    #[msg("Arithmetic error while computing yield harvest allocation.")]
+    YieldHarvestAllocation,
+    #[msg("Yield harvest already occurred during this epoch.")]
+    YieldHarvestEpoch,
+    #[msg("Yield harvest has not yet occurred during this epoch.")]
+    YieldHarvestHasNotRun,
+    #[msg("Cannot withdraw amount of 0 in a redemption action.")]
+    ZeroRedeem,
+    #[msg("Cannot deposit amount of 0 in a minting action.")]
+    ZeroMint,
+    #[msg("Cannot swap amount of 0.")]
+    ZeroSwap,
+    #[msg("Cannot swap from an asset to itself.")]
+    IdentitySwap,
+  }
+
+  impl ExchangeError {
+    /// Every variant, in IDL order.
+    pub const ALL: [ExchangeError; 27] = [
+      ExchangeError::LevercoinRedeemDisabled,
+      ExchangeError::LeverToStableDisabled,
+      ExchangeError::StableToLeverDisabled,
+      ExchangeError::SanctumCpi,
+      ExchangeError::LstRegistryAlreadyInitialized,
+      ExchangeError::LstRegistryEmpty,
+      ExchangeError::LstRegistryPreamble,
+      ExchangeError::LstRegistryLookupTableDeser,
+      ExchangeError::LstRegistryLookupTableInvalid,
+      ExchangeError::LstBlockInvalid,
+      ExchangeError::LstContextInvalid,
+      ExchangeError::LstAdditionOverflow,
+      ExchangeError::LstPriceOutdated,
+      ExchangeError::LstPriceDelta,
+      ExchangeError::LstPriceEpochsInvalid,
+      ExchangeError::LstSolAppreciation,
+      ExchangeError::StablecoinMintDisabled,
+      ExchangeError::LevercoinMintDisabled,
+      ExchangeError::YieldHarvestConfigValidation,
+      ExchangeError::YieldHarvestDisabled,
+      ExchangeError::YieldHarvestAllocation,
+      ExchangeError::YieldHarvestEpoch,
+      ExchangeError::YieldHarvestHasNotRun,
+      ExchangeError::ZeroRedeem,
+      ExchangeError::ZeroMint,
+      ExchangeError::ZeroSwap,
+      ExchangeError::IdentitySwap,
+    ];
+
+    /// Variant with error number `code`.
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<ExchangeError> {
+      ExchangeError::ALL
+        .into_iter()
+        .find(|error| u32::from(*error) == code)
+    }
+  }
+}
+
+#[cfg(feature = "stability-pool")]
+pub mod stability_pool {
+  use anchor_lang::prelude::error_code;
+
+  #[error_code]
+  #[derive(PartialEq, Eq)]
+  pub enum StabilityPoolError {
+    #[msg(
+      "Rebalance is disabled due to stability or pool amount constraint not \
+       being met."
+    )]
+    RebalanceDisabled,
+    #[msg("Deposits to pool disabled due to active rebalancing.")]
+    DepositDisabled,
+  }
+
+  impl StabilityPoolError {
+    /// Every variant, in IDL order.
+    pub const ALL: [StabilityPoolError; 2] = [
+      StabilityPoolError::RebalanceDisabled,
+      StabilityPoolError::DepositDisabled,
+    ];
+
+    /// Variant with error number `code`.
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<StabilityPoolError> {
+      StabilityPoolError::ALL
+        .into_iter()
+        .find(|error| u32::from(*error) == code)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::{anyhow, Result};
+  use serde_json::Value;
+
+  /// Code and name of each entry in the `errors` section of `idl`.
+  fn idl_errors(idl: &str) -> Result<Vec<(u32, String)>> {
+    let idl: Value = serde_json::from_str(idl)?;
+    idl["errors"]
+      .as_array()
+      .ok_or(anyhow!("IDL has no errors section"))?
+      .iter()
+      .map(|error| {
+        let code = error["code"]
+          .as_u64()
+          .ok_or(anyhow!("Error without code: {error}"))?;
+        let name = error["name"]
+          .as_str()
+          .ok_or(anyhow!("Error without name: {error}"))?;
+        Ok((u32::try_from(code)?, name.to_string()))
+      })
+      .collect()
+  }
+
+  #[cfg(feature = "exchange")]
+  #[test]
+  fn exchange_errors_match_idl() -> Result<()> {
+    use super::exchange::ExchangeError;
+    use crate::exchange::IDL_JSON;
+    let declared = ExchangeError::ALL
+      .into_iter()
+      .map(|error| (u32::from(error), error.name()))
+      .collect::<Vec<_>>();
+    assert_eq!(declared, idl_errors(IDL_JSON)?);
+    ExchangeError::ALL.into_iter().for_each(|error| {
+      assert_eq!(ExchangeError::from_code(u32::from(error)), Some(error));
+    });
+    assert_eq!(ExchangeError::from_code(7000), None);
+    Ok(())
+  }
+
+  #[cfg(feature = "stability-pool")]
+  #[test]
+  fn stability_pool_errors_match_idl() -> Result<()> {
+    use super::stability_pool::StabilityPoolError;
+    use crate::stability_pool::IDL_JSON;
+    let declared = StabilityPoolError::ALL
+      .into_iter()
+      .map(|error| (u32::from(error), error.name()))
+      .collect::<Vec<_>>();
+    assert_eq!(declared, idl_errors(IDL_JSON)?);
+    StabilityPoolError::ALL.into_iter().for_each(|error| {
+      assert_eq!(StabilityPoolError::from_code(u32::from(error)), Some(error));
+    });
+    Ok(())
+  }
+}
@@ -0,0 +1,112 @@
+//! Stake pool and Sanctum calculator addresses of supported LSTs.
+//!
+//! Registering an LST and pricing it locally both need its stake pool state,
+//! the stake pool program owning it, and the Sanctum calculator reading it.
+//! [`LstStakePool::find`] resolves these from the LST mint.
+
+use solana_loader_v3_interface::get_program_data_address;
+use solana_pubkey::{pubkey, Pubkey};
+
+use crate::tokens::{TokenMint, HYLOSOL, JITOSOL};
+
+/// Seed of the calculator state PDA in each Sanctum calculator program.
+const CALCULATOR_STATE_SEED: &[u8] = b"state";
+
+/// Stake pool program family, as in the exchange's `LstStakePoolProgram`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StakePoolProgram {
+  /// SPL stake pool program
+  Spl,
+  /// Sanctum deployment of the SPL program for single validator pools
+  SanctumSpl,
+  /// Sanctum deployment of the SPL program for multi validator pools
+  SanctumSplMulti,
+}
+
+impl StakePoolProgram {
+  #[must_use]
+  pub const fn program_id(self) -> Pubkey {
+    match self {
+      StakePoolProgram::Spl => {
+        pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy")
+      }
+      StakePoolProgram::SanctumSpl => {
+        pubkey!("SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY")
+      }
+      StakePoolProgram::SanctumSplMulti => {
+        pubkey!("SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn")
+      }
+    }
+  }
+
+  /// Sanctum SOL value calculator for pools of this program.
+  #[must_use]
+  pub const fn calculator_program(self) -> Pubkey {
+    match self {
+      StakePoolProgram::Spl => {
+        pubkey!("sp1V4h2gWorkGhVcazBc22Hfo2f5sd7jcjT4EDPrWFF")
+      }
+      StakePoolProgram::SanctumSpl => {
+        pubkey!("sspUE1vrh7xRoXxGsg7vR1zde2WdGtJRbyK9uRumBDy")
+      }
+      StakePoolProgram::SanctumSplMulti => {
+        pubkey!("ssmbu3KZxgonUtjEMCKspZzxvUQCxAFnyh1rcHUeEDo")
+      }
+    }
+  }
+
+  #[must_use]
+  pub fn calculator_state(self) -> Pubkey {
+    Pubkey::find_program_address(
+      &[CALCULATOR_STATE_SEED],
+      &self.calculator_program(),
+    )
+    .0
+  }
+
+  #[must_use]
+  pub fn program_data(self) -> Pubkey {
+    get_program_data_address(&self.program_id())
+  }
+}
+
+/// Stake pool backing one LST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LstStakePool {
+  pub mint: Pubkey,
+  pub stake_pool_state: Pubkey,
+  pub program: StakePoolProgram,
+}
+
+pub const JITOSOL_STAKE_POOL: LstStakePool = LstStakePool {
+  mint: JITOSOL::MINT,
+  stake_pool_state: pubkey!("Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb"),
+  program: StakePoolProgram::Spl,
+};
+
+pub const HYLOSOL_STAKE_POOL: LstStakePool = LstStakePool {
+  mint: HYLOSOL::MINT,
+  stake_pool_state: pubkey!("hy1oDeVCVRDGkxS26qLVDvRhDpZGfWJ6w9AMvwMegwL"),
+  program: StakePoolProgram::SanctumSplMulti,
+};
+
+/// Stake pools of every LST with a known registration.
+pub const KNOWN_LST_STAKE_POOLS: [LstStakePool; 2] =
+  [JITOSOL_STAKE_POOL, HYLOSOL_STAKE_POOL];
+
+impl LstStakePool {
+  /// Known stake pool for `mint`, if any.
+  #[must_use]
+  pub fn find(mint: &Pubkey) -> Option<LstStakePool> {
+    KNOWN_LST_STAKE_POOLS
+      .iter()
+      .find(|pool| pool.mint == *mint)
+      .copied()
+  }
+
+  /// Known stake pool for the LST marker `L`, if any.
+  #[must_use]
+  pub fn of<L: TokenMint>() -> Option<LstStakePool> {
+    LstStakePool::find(&L::MINT)
+  }
+}
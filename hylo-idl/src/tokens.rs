@@ -1,9 +1,8 @@
-use anchor_lang::prelude::Pubkey;
-use anchor_lang::solana_program::pubkey;
 use anyhow::{anyhow, Result};
 use fix::prelude::{N6, N9};
 use fix::typenum::Integer;
 use paste::paste;
+use solana_pubkey::{pubkey, Pubkey};
 
 macro_rules! try_from_pubkey {
   ($token:ty) => {
@@ -24,6 +23,7 @@ macro_rules! try_from_pubkey {
 pub trait TokenMint {
   type Exp: Integer;
   const MINT: Pubkey;
+  const SYMBOL: &'static str;
 }
 
 pub struct HYUSD;
@@ -31,6 +31,7 @@ pub struct HYUSD;
 impl TokenMint for HYUSD {
   type Exp = N6;
   const MINT: Pubkey = pubkey!("5YMkXAYccHSGnHn9nob9xEvv6Pvka9DZWH7nTbotTu9E");
+  const SYMBOL: &'static str = "hyUSD";
 }
 
 try_from_pubkey!(HYUSD);
@@ -40,6 +41,7 @@ pub struct SHYUSD;
 impl TokenMint for SHYUSD {
   type Exp = N6;
   const MINT: Pubkey = pubkey!("HnnGv3HrSqjRpgdFmx7vQGjntNEoex1SU4e9Lxcxuihz");
+  const SYMBOL: &'static str = "sHYUSD";
 }
 
 try_from_pubkey!(SHYUSD);
@@ -49,6 +51,7 @@ pub struct XSOL;
 impl TokenMint for XSOL {
   type Exp = N6;
   const MINT: Pubkey = pubkey!("4sWNB8zGWHkh6UnmwiEtzNxL4XrN7uK9tosbESbJFfVs");
+  const SYMBOL: &'static str = "xSOL";
 }
 
 try_from_pubkey!(XSOL);
@@ -58,6 +61,7 @@ pub struct JITOSOL;
 impl TokenMint for JITOSOL {
   type Exp = N9;
   const MINT: Pubkey = pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn");
+  const SYMBOL: &'static str = "jitoSOL";
 }
 
 try_from_pubkey!(JITOSOL);
@@ -67,6 +71,7 @@ pub struct HYLOSOL;
 impl TokenMint for HYLOSOL {
   type Exp = N9;
   const MINT: Pubkey = pubkey!("hy1oXYgrBW6PVcJ4s6s2FKavRdwgWTXdfE69AxT7kPT");
+  const SYMBOL: &'static str = "hyloSOL";
 }
 
 try_from_pubkey!(HYLOSOL);
@@ -1,55 +1,132 @@
+//! Conversions between the programs' IDL fixed point type and `fix` types.
+//!
+//! Both programs store decimals as `UFixValue64 { bits, exp }`, generated
+//! once per program. Each IDL value converts to and from the runtime
+//! [`UFixValue64`] and any typed [`UFix64`], and the two programs' values
+//! convert into each other. Typed conversions out of the IDL fail if the
+//! stored exponent differs from the target's.
+//!
+//! ```rust,ignore
+//! let total_sol: UFix64<N9> = hylo.total_sol_cache.total_sol.try_into()?;
+//! let expected: exchange::types::UFixValue64 = UFix64::<N6>::one().into();
+//! ```
+
 use fix::prelude::{UFix64, UFixValue64};
 use fix::typenum::Integer;
 
-impl From<crate::exchange::types::UFixValue64> for UFixValue64 {
-  fn from(idl: crate::exchange::types::UFixValue64) -> Self {
-    UFixValue64 {
-      bits: idl.bits,
-      exp: idl.exp,
+/// Implements the conversions for one program's IDL `UFixValue64`.
+macro_rules! bridge_ufixvalue64 {
+  ($feature:literal, $program:ident) => {
+    #[cfg(feature = $feature)]
+    impl From<crate::$program::types::UFixValue64> for UFixValue64 {
+      fn from(idl: crate::$program::types::UFixValue64) -> Self {
+        UFixValue64 {
+          bits: idl.bits,
+          exp: idl.exp,
+        }
+      }
     }
-  }
+
+    #[cfg(feature = $feature)]
+    impl From<UFixValue64> for crate::$program::types::UFixValue64 {
+      fn from(value: UFixValue64) -> Self {
+        crate::$program::types::UFixValue64 {
+          bits: value.bits,
+          exp: value.exp,
+        }
+      }
+    }
+
+    #[cfg(feature = $feature)]
+    impl<Exp: Integer> TryFrom<crate::$program::types::UFixValue64>
+      for UFix64<Exp>
+    {
+      type Error = anchor_lang::error::Error;
+
+      fn try_from(
+        idl: crate::$program::types::UFixValue64,
+      ) -> Result<Self, Self::Error> {
+        let value: UFixValue64 = idl.into();
+        value.try_into()
+      }
+    }
+
+    #[cfg(feature = $feature)]
+    impl<Exp: Integer> From<UFix64<Exp>>
+      for crate::$program::types::UFixValue64
+    {
+      fn from(value: UFix64<Exp>) -> Self {
+        let value: UFixValue64 = value.into();
+        value.into()
+      }
+    }
+  };
 }
 
-impl<Exp: Integer> TryFrom<crate::exchange::types::UFixValue64>
-  for UFix64<Exp>
-{
-  type Error = anchor_lang::error::Error;
+bridge_ufixvalue64!("exchange", exchange);
+bridge_ufixvalue64!("stability-pool", stability_pool);
 
-  fn try_from(
-    idl: crate::exchange::types::UFixValue64,
-  ) -> Result<Self, Self::Error> {
-    let value: UFixValue64 = idl.into();
-    value.try_into()
+#[cfg(all(feature = "exchange", feature = "stability-pool"))]
+impl From<crate::exchange::types::UFixValue64>
+  for crate::stability_pool::types::UFixValue64
+{
+  fn from(idl: crate::exchange::types::UFixValue64) -> Self {
+    UFixValue64::from(idl).into()
   }
 }
 
-impl From<crate::stability_pool::types::UFixValue64> for UFixValue64 {
+#[cfg(all(feature = "exchange", feature = "stability-pool"))]
+impl From<crate::stability_pool::types::UFixValue64>
+  for crate::exchange::types::UFixValue64
+{
   fn from(idl: crate::stability_pool::types::UFixValue64) -> Self {
-    UFixValue64 {
-      bits: idl.bits,
-      exp: idl.exp,
-    }
+    UFixValue64::from(idl).into()
   }
 }
 
-impl<Exp: Integer> TryFrom<crate::stability_pool::types::UFixValue64>
-  for UFix64<Exp>
-{
-  type Error = anchor_lang::error::Error;
+#[cfg(all(test, feature = "exchange", feature = "stability-pool"))]
+mod tests {
+  use fix::prelude::*;
+  use fix::typenum::Integer;
 
-  fn try_from(
-    idl: crate::stability_pool::types::UFixValue64,
-  ) -> Result<Self, Self::Error> {
-    let value: UFixValue64 = idl.into();
-    value.try_into()
+  use crate::exchange::types::UFixValue64 as ExchangeValue;
+  use crate::stability_pool::types::UFixValue64 as PoolValue;
+
+  /// Round trips `value` through both programs' IDL types.
+  fn round_trip<Exp: Integer>(value: UFix64<Exp>) -> anyhow::Result<()> {
+    let exchange: ExchangeValue = value.into();
+    let pool: PoolValue = value.into();
+    assert_eq!(i32::from(exchange.exp), Exp::I32);
+    assert_eq!(UFix64::<Exp>::try_from(exchange)?, value);
+    assert_eq!(UFix64::<Exp>::try_from(pool)?, value);
+    let bridged: PoolValue = exchange.into();
+    assert_eq!(UFix64::<Exp>::try_from(bridged)?, value);
+    let bridged: ExchangeValue = pool.into();
+    assert_eq!(UFix64::<Exp>::try_from(bridged)?, value);
+    Ok(())
   }
-}
 
-impl From<UFixValue64> for crate::exchange::types::UFixValue64 {
-  fn from(idl: UFixValue64) -> Self {
-    crate::exchange::types::UFixValue64 {
-      bits: idl.bits,
-      exp: idl.exp,
-    }
+  #[test]
+  fn round_trips_program_exponents() -> anyhow::Result<()> {
+    round_trip(UFix64::<N2>::new(150))?;
+    round_trip(UFix64::<N4>::new(25))?;
+    round_trip(UFix64::<N6>::new(1_234_567))?;
+    round_trip(UFix64::<N8>::new(15_000_000_000))?;
+    round_trip(UFix64::<N9>::new(u64::MAX))?;
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_mismatched_exponent() {
+    let value: ExchangeValue = UFix64::<N6>::new(1_000_000).into();
+    assert!(UFix64::<N9>::try_from(value).is_err());
+    assert!(UFix64::<N6>::try_from(value).is_ok());
+  }
+
+  #[test]
+  fn runtime_value_keeps_bits_and_exp() {
+    let idl = PoolValue { bits: 42, exp: -4 };
+    let value: UFixValue64 = idl.into();
+    assert_eq!((value.bits, value.exp), (42, -4));
   }
 }
@@ -1,5 +1,6 @@
 pub mod quotes;
 pub mod util;
+pub mod zero_copy;
 
 pub use hylo_core;
 pub use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
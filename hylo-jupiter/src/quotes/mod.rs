@@ -1,3 +1,5 @@
+mod routes;
+mod shared;
 mod state;
 
 pub mod token_operation;
@@ -5,6 +7,8 @@ pub mod token_operation;
 use fix::typenum::N9;
 use hylo_idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
 
+pub use routes::*;
+pub use shared::*;
 pub use state::*;
 
 pub trait LST: TokenMint<Exp = N9> {}
@@ -0,0 +1,39 @@
+//! Pairs a router enables, and the accounts they depend on.
+//!
+//! Jupiter polls every account returned from `get_accounts_to_update`, but an
+//! integrator embedding only some Hylo pairs never quotes the rest. Stability
+//! pool accounts are read only by sHYUSD pairs, so they are left out of the
+//! account list unless an enabled pair trades sHYUSD.
+
+use anchor_lang::prelude::Pubkey;
+use hylo_idl::deployment::Deployment;
+
+/// Pairs quoted through Hylo.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EnabledPairs {
+  /// Every pair Hylo supports
+  #[default]
+  All,
+
+  /// Only these `(input, output)` mint pairs
+  Only(Vec<(Pubkey, Pubkey)>),
+}
+
+impl EnabledPairs {
+  /// Whether any enabled pair trades `mint` in either direction.
+  #[must_use]
+  pub fn trades(&self, mint: &Pubkey) -> bool {
+    match self {
+      EnabledPairs::All => true,
+      EnabledPairs::Only(pairs) => pairs
+        .iter()
+        .any(|(input, output)| input == mint || output == mint),
+    }
+  }
+
+  /// Whether quoting needs the stability pool accounts of `deployment`.
+  #[must_use]
+  pub fn stability_pool(&self, deployment: &Deployment) -> bool {
+    self.trades(&deployment.shyusd_mint)
+  }
+}
@@ -0,0 +1,86 @@
+//! Protocol state shared between one updater and many quoting threads.
+//!
+//! [`SharedProtocolState`] keeps the latest [`ProtocolState`] behind an
+//! `Arc`. Quoting threads take a snapshot and quote without holding the lock,
+//! while the updater swaps in freshly built states, so mint and Pyth accounts
+//! are never cloned per thread.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use anyhow::Result;
+use fix::typenum::Integer;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::TokenMint;
+use jupiter_amm_interface::{ClockRef, Quote};
+
+use crate::quotes::token_operation::TokenOperation;
+use crate::quotes::ProtocolState;
+use crate::util::quote;
+
+/// Cheaply cloneable handle to the latest protocol state.
+pub struct SharedProtocolState<C: SolanaClock> {
+  current: Arc<RwLock<Arc<ProtocolState<C>>>>,
+}
+
+impl<C: SolanaClock> Clone for SharedProtocolState<C> {
+  fn clone(&self) -> Self {
+    SharedProtocolState {
+      current: Arc::clone(&self.current),
+    }
+  }
+}
+
+impl<C: SolanaClock> SharedProtocolState<C> {
+  #[must_use]
+  pub fn new(state: ProtocolState<C>) -> SharedProtocolState<C> {
+    SharedProtocolState {
+      current: Arc::new(RwLock::new(Arc::new(state))),
+    }
+  }
+
+  /// Latest state. Holds no lock once returned, so later updates do not
+  /// affect it.
+  #[must_use]
+  pub fn snapshot(&self) -> Arc<ProtocolState<C>> {
+    let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+    Arc::clone(&current)
+  }
+
+  /// Publishes `state` to all handles, returning the state it replaced.
+  pub fn replace(&self, state: ProtocolState<C>) -> Arc<ProtocolState<C>> {
+    let mut current =
+      self.current.write().unwrap_or_else(PoisonError::into_inner);
+    std::mem::replace(&mut *current, Arc::new(state))
+  }
+
+  /// Builds the next state from the latest one and publishes it. Quoting
+  /// continues on the previous state while `next` runs.
+  ///
+  /// # Errors
+  /// * Propagates errors from `next`, leaving the current state published
+  pub fn update<F>(&self, next: F) -> Result<()>
+  where
+    F: FnOnce(&ProtocolState<C>) -> Result<ProtocolState<C>>,
+  {
+    let state = next(&self.snapshot())?;
+    self.replace(state);
+    Ok(())
+  }
+}
+
+impl SharedProtocolState<ClockRef> {
+  /// Quotes `IN -> OUT` against the latest state.
+  ///
+  /// # Errors
+  /// * Quote math
+  /// * Fee decimal conversion
+  pub fn quote<IN, OUT>(&self, amount: u64) -> Result<Quote>
+  where
+    IN: TokenMint,
+    OUT: TokenMint,
+    ProtocolState<ClockRef>: TokenOperation<IN, OUT>,
+    <ProtocolState<ClockRef> as TokenOperation<IN, OUT>>::FeeExp: Integer,
+  {
+    quote::<IN, OUT>(&self.snapshot(), amount)
+  }
+}
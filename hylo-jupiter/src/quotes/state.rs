@@ -3,7 +3,9 @@
 //! Contains the `ProtocolState` struct and its construction from protocol
 //! accounts.
 
+use anchor_lang::prelude::Pubkey;
 use anyhow::{anyhow, Result};
+use fix::typenum::N8;
 use hylo_core::exchange_context::ExchangeContext;
 use hylo_core::fee_controller::{LevercoinFees, StablecoinFees};
 use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
@@ -13,11 +15,106 @@ use hylo_core::pyth::OracleConfig;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_core::stability_mode::StabilityController;
 use hylo_core::total_sol_cache::TotalSolCache;
+use hylo_idl::deployment::Deployment;
 use hylo_idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
+use jupiter_amm_interface::AccountMap;
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use spl_token_interface::state::{Account as TokenAccount, Mint};
 
-use crate::quotes::LST;
+use crate::quotes::{EnabledPairs, LST};
+use crate::util::{account_map_get, account_spl_get};
+use crate::zero_copy::{hylo_view, lst_header_view, HyloView};
+
+/// Stability pool accounts read by SHYUSD pairs
+#[derive(Clone)]
+pub struct StabilityPoolState {
+  /// SHYUSD mint account
+  pub shyusd_mint: Mint,
+
+  /// Stability pool configuration
+  pub pool_config: PoolConfig,
+
+  /// HYUSD stability pool token account
+  pub hyusd_pool: TokenAccount,
+
+  /// XSOL stability pool token account
+  pub xsol_pool: TokenAccount,
+}
+
+impl StabilityPoolState {
+  /// Reads the stability pool accounts of `deployment` from Jupiter's
+  /// `AccountMap`.
+  ///
+  /// # Errors
+  /// * Any stability pool account missing or malformed
+  pub fn from_account_map(
+    deployment: &Deployment,
+    account_map: &AccountMap,
+  ) -> Result<Self> {
+    Ok(Self {
+      shyusd_mint: account_spl_get(account_map, &deployment.shyusd_mint)?,
+      pool_config: account_map_get(
+        account_map,
+        &deployment.pool_config(),
+        &deployment.stability_pool_program,
+      )?,
+      hyusd_pool: account_spl_get(account_map, &deployment.hyusd_pool())?,
+      xsol_pool: account_spl_get(account_map, &deployment.xsol_pool())?,
+    })
+  }
+}
+
+/// Exchange parameters quoting reads from the [`Hylo`] account.
+struct ExchangeParams {
+  total_sol_cache: TotalSolCache,
+  oracle_config: OracleConfig<N8>,
+  stability_controller: StabilityController,
+  stablecoin_fees: StablecoinFees,
+  levercoin_fees: LevercoinFees,
+  lst_swap_config: LstSwapConfig,
+}
+
+impl TryFrom<&Hylo> for ExchangeParams {
+  type Error = anyhow::Error;
+
+  fn try_from(hylo: &Hylo) -> Result<ExchangeParams> {
+    Ok(ExchangeParams {
+      total_sol_cache: hylo.total_sol_cache.into(),
+      oracle_config: OracleConfig::new(
+        hylo.oracle_interval_secs,
+        hylo.oracle_conf_tolerance.try_into()?,
+      ),
+      stability_controller: StabilityController::new(
+        hylo.stability_threshold_1.try_into()?,
+        hylo.stability_threshold_2.try_into()?,
+      )?,
+      stablecoin_fees: hylo.stablecoin_fees.into(),
+      levercoin_fees: hylo.levercoin_fees.into(),
+      lst_swap_config: LstSwapConfig::new(hylo.lst_swap_fee.into())?,
+    })
+  }
+}
+
+impl TryFrom<HyloView<'_>> for ExchangeParams {
+  type Error = anyhow::Error;
+
+  fn try_from(hylo: HyloView<'_>) -> Result<ExchangeParams> {
+    Ok(ExchangeParams {
+      total_sol_cache: hylo.total_sol_cache(),
+      oracle_config: OracleConfig::new(
+        hylo.oracle_interval_secs(),
+        hylo.oracle_conf_tolerance().try_into()?,
+      ),
+      stability_controller: StabilityController::new(
+        hylo.stability_threshold_1().try_into()?,
+        hylo.stability_threshold_2().try_into()?,
+      )?,
+      stablecoin_fees: hylo.stablecoin_fees(),
+      levercoin_fees: hylo.levercoin_fees(),
+      lst_swap_config: LstSwapConfig::new(hylo.lst_swap_fee())?,
+    })
+  }
+}
 
 /// Complete snapshot of Hylo protocol state
 #[derive(Clone)]
@@ -37,17 +134,8 @@ pub struct ProtocolState<C: SolanaClock> {
   /// XSOL mint account
   pub xsol_mint: Mint,
 
-  /// SHYUSD mint account
-  pub shyusd_mint: Mint,
-
-  /// Stability pool configuration
-  pub pool_config: PoolConfig,
-
-  /// HYUSD stability pool token account
-  pub hyusd_pool: TokenAccount,
-
-  /// XSOL stability pool token account
-  pub xsol_pool: TokenAccount,
+  /// Stability pool accounts, if any enabled pair trades SHYUSD
+  pub stability_pool: Option<StabilityPoolState>,
 
   /// Timestamp of when this state was fetched
   pub fetched_at: i64,
@@ -69,32 +157,40 @@ impl<C: SolanaClock> ProtocolState<C> {
     hylosol_header: LstHeader,
     hyusd_mint: Mint,
     xsol_mint: Mint,
-    shyusd_mint: Mint,
-    pool_config: PoolConfig,
-    hyusd_pool: TokenAccount,
-    xsol_pool: TokenAccount,
+    stability_pool: Option<StabilityPoolState>,
+    sol_usd: &PriceUpdateV2,
+  ) -> Result<Self> {
+    Self::assemble(
+      clock,
+      ExchangeParams::try_from(hylo)?,
+      jitosol_header,
+      hylosol_header,
+      hyusd_mint,
+      xsol_mint,
+      stability_pool,
+      sol_usd,
+    )
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn assemble(
+    clock: C,
+    params: ExchangeParams,
+    jitosol_header: LstHeader,
+    hylosol_header: LstHeader,
+    hyusd_mint: Mint,
+    xsol_mint: Mint,
+    stability_pool: Option<StabilityPoolState>,
     sol_usd: &PriceUpdateV2,
   ) -> Result<Self> {
     let fetched_at = clock.unix_timestamp();
-    let total_sol_cache: TotalSolCache = hylo.total_sol_cache.into();
-    let oracle_config = OracleConfig::new(
-      hylo.oracle_interval_secs,
-      hylo.oracle_conf_tolerance.try_into()?,
-    );
-    let stability_controller = StabilityController::new(
-      hylo.stability_threshold_1.try_into()?,
-      hylo.stability_threshold_2.try_into()?,
-    )?;
-    let hyusd_fees: StablecoinFees = hylo.stablecoin_fees.into();
-    let xsol_fees: LevercoinFees = hylo.levercoin_fees.into();
-    let lst_swap_config = LstSwapConfig::new(hylo.lst_swap_fee.into())?;
     let exchange_context = ExchangeContext::load(
       clock,
-      &total_sol_cache,
-      stability_controller,
-      oracle_config,
-      hyusd_fees,
-      xsol_fees,
+      &params.total_sol_cache,
+      params.stability_controller,
+      params.oracle_config,
+      params.stablecoin_fees,
+      params.levercoin_fees,
       sol_usd,
       &hyusd_mint,
       Some(&xsol_mint),
@@ -105,15 +201,120 @@ impl<C: SolanaClock> ProtocolState<C> {
       hylosol_header,
       hyusd_mint,
       xsol_mint,
-      shyusd_mint,
-      pool_config,
-      hyusd_pool,
-      xsol_pool,
+      stability_pool,
       fetched_at,
-      lst_swap_config,
+      lst_swap_config: params.lst_swap_config,
     })
   }
 
+  /// Accounts [`ProtocolState::from_account_map`] reads for `deployment`,
+  /// as returned from Jupiter's `get_accounts_to_update`.
+  #[must_use]
+  pub fn accounts_to_update(deployment: &Deployment) -> Vec<Pubkey> {
+    Self::accounts_to_update_for(deployment, &EnabledPairs::All)
+  }
+
+  /// Accounts [`ProtocolState::from_account_map_for`] reads to quote
+  /// `pairs`. Stability pool accounts are left out unless a pair trades
+  /// SHYUSD.
+  #[must_use]
+  pub fn accounts_to_update_for(
+    deployment: &Deployment,
+    pairs: &EnabledPairs,
+  ) -> Vec<Pubkey> {
+    let exchange = [
+      deployment.hylo(),
+      deployment.lst_header(JITOSOL::MINT),
+      deployment.lst_header(HYLOSOL::MINT),
+      deployment.hyusd_mint,
+      deployment.xsol_mint,
+    ];
+    let stability_pool = [
+      deployment.shyusd_mint,
+      deployment.pool_config(),
+      deployment.hyusd_pool(),
+      deployment.xsol_pool(),
+    ];
+    let stability_pool = if pairs.stability_pool(deployment) {
+      stability_pool.as_slice()
+    } else {
+      &[]
+    };
+    exchange
+      .iter()
+      .chain(stability_pool)
+      .chain([&deployment.sol_usd_pyth_feed])
+      .copied()
+      .collect()
+  }
+
+  /// Builds state for `deployment` from Jupiter's `AccountMap`.
+  ///
+  /// # Errors
+  /// * Any of [`ProtocolState::accounts_to_update`] missing or malformed
+  /// * Propagates errors from `ExchangeContext::load`
+  pub fn from_account_map(
+    clock: C,
+    deployment: &Deployment,
+    account_map: &AccountMap,
+  ) -> Result<Self> {
+    Self::from_account_map_for(
+      clock,
+      deployment,
+      &EnabledPairs::All,
+      account_map,
+    )
+  }
+
+  /// Builds state for quoting `pairs` from Jupiter's `AccountMap`.
+  ///
+  /// # Errors
+  /// * Any of [`ProtocolState::accounts_to_update_for`] missing or malformed
+  /// * Propagates errors from `ExchangeContext::load`
+  pub fn from_account_map_for(
+    clock: C,
+    deployment: &Deployment,
+    pairs: &EnabledPairs,
+    account_map: &AccountMap,
+  ) -> Result<Self> {
+    let exchange = &deployment.exchange_program;
+    let hylo = hylo_view(account_map, &deployment.hylo(), exchange)?;
+    let sol_usd: PriceUpdateV2 = account_map_get(
+      account_map,
+      &deployment.sol_usd_pyth_feed,
+      &pyth_solana_receiver_sdk::ID,
+    )?;
+    let stability_pool = pairs
+      .stability_pool(deployment)
+      .then(|| StabilityPoolState::from_account_map(deployment, account_map))
+      .transpose()?;
+    let header = |mint| {
+      lst_header_view(account_map, &deployment.lst_header(mint), exchange)?
+        .header()
+    };
+    Self::assemble(
+      clock,
+      ExchangeParams::try_from(hylo)?,
+      header(JITOSOL::MINT)?,
+      header(HYLOSOL::MINT)?,
+      account_spl_get(account_map, &deployment.hyusd_mint)?,
+      account_spl_get(account_map, &deployment.xsol_mint)?,
+      stability_pool,
+      &sol_usd,
+    )
+  }
+
+  /// Stability pool accounts, tracked while a SHYUSD pair is enabled.
+  ///
+  /// # Errors
+  /// * No enabled pair trades SHYUSD
+  pub fn stability_pool(&self) -> Result<&StabilityPoolState> {
+    self
+      .stability_pool
+      .as_ref()
+      .ok_or(anyhow!("Stability pool accounts not tracked"))
+  }
+
   /// Selects an [`LstHeader`] field given a token implementing [`LST`].
   ///
   /// # Errors
@@ -127,6 +328,35 @@ impl<C: SolanaClock> ProtocolState<C> {
   }
 }
 
+/// Accessors for the stability pool accounts that were fields of
+/// [`ProtocolState`] before they moved into [`StabilityPoolState`]. Each is
+/// `None` when no enabled pair trades SHYUSD.
+impl<C: SolanaClock> ProtocolState<C> {
+  #[deprecated(note = "read `stability_pool.shyusd_mint` instead")]
+  #[must_use]
+  pub fn shyusd_mint(&self) -> Option<&Mint> {
+    self.stability_pool.as_ref().map(|pool| &pool.shyusd_mint)
+  }
+
+  #[deprecated(note = "read `stability_pool.pool_config` instead")]
+  #[must_use]
+  pub fn pool_config(&self) -> Option<&PoolConfig> {
+    self.stability_pool.as_ref().map(|pool| &pool.pool_config)
+  }
+
+  #[deprecated(note = "read `stability_pool.hyusd_pool` instead")]
+  #[must_use]
+  pub fn hyusd_pool(&self) -> Option<&TokenAccount> {
+    self.stability_pool.as_ref().map(|pool| &pool.hyusd_pool)
+  }
+
+  #[deprecated(note = "read `stability_pool.xsol_pool` instead")]
+  #[must_use]
+  pub fn xsol_pool(&self) -> Option<&TokenAccount> {
+    self.stability_pool.as_ref().map(|pool| &pool.xsol_pool)
+  }
+}
+
 // impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
 //   type Error = anyhow::Error;
 
@@ -3,9 +3,10 @@
 use anyhow::{ensure, Result};
 use fix::prelude::*;
 use hylo_core::fee_controller::FeeExtract;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::operation_matrix::ProtocolOperation;
 use hylo_core::solana_clock::SolanaClock;
-use hylo_core::stability_mode::StabilityMode;
 use hylo_idl::tokens::{TokenMint, HYUSD, XSOL};
 
 use super::{
@@ -25,7 +26,8 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, HYUSD>
     in_amount: UFix64<N9>,
   ) -> Result<MintOperationOutput> {
     ensure!(
-      self.exchange_context.stability_mode <= StabilityMode::Mode1,
+      ProtocolOperation::MintStablecoin
+        .is_active(self.exchange_context.stability_mode()),
       "Mint operations disabled in current stability mode"
     );
     let lst_header = self.lst_header::<L>()?;
@@ -49,6 +51,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, HYUSD>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -82,6 +85,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<HYUSD, L>
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: lst_out,
     })
   }
@@ -98,7 +102,8 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
     in_amount: UFix64<N9>,
   ) -> Result<MintOperationOutput> {
     ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
+      ProtocolOperation::MintLevercoin
+        .is_active(self.exchange_context.stability_mode()),
       "Levercoin mint disabled in current stability mode"
     );
     let lst_header = self.lst_header::<L>()?;
@@ -119,6 +124,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -135,7 +141,8 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
   ) -> Result<RedeemOperationOutput> {
     ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
+      ProtocolOperation::RedeemLevercoin
+        .is_active(self.exchange_context.stability_mode()),
       "Levercoin redemption disabled in current stability mode"
     );
     let lst_header = self.lst_header::<L>()?;
@@ -156,6 +163,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: lst_out,
     })
   }
@@ -170,7 +178,8 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
     in_amount: UFix64<<HYUSD as TokenMint>::Exp>,
   ) -> Result<SwapOperationOutput> {
     ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
+      ProtocolOperation::SwapStableToLever
+        .is_active(self.exchange_context.stability_mode()),
       "Swaps are disabled in current stability mode"
     );
     let FeeExtract {
@@ -188,6 +197,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -202,10 +212,8 @@ impl<C: SolanaClock> TokenOperation<XSOL, HYUSD> for ProtocolState<C> {
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
   ) -> Result<SwapOperationOutput> {
     ensure!(
-      matches!(
-        self.exchange_context.stability_mode,
-        StabilityMode::Normal | StabilityMode::Mode1
-      ),
+      ProtocolOperation::SwapLeverToStable
+        .is_active(self.exchange_context.stability_mode()),
       "Swaps are disabled in current stability mode"
     );
     let converted = self
@@ -226,6 +234,7 @@ impl<C: SolanaClock> TokenOperation<XSOL, HYUSD> for ProtocolState<C> {
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fees_extracted),
       fee_base: hyusd_total,
     })
   }
@@ -246,7 +255,7 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
       amount_remaining,
     } = self.lst_swap_config.apply_fee(in_amount)?;
 
-    let epoch = self.exchange_context.clock.epoch();
+    let epoch = self.exchange_context.clock().epoch();
     let lst_in_header = self.lst_header::<L1>()?;
     let lst_out_header = self.lst_header::<L2>()?;
 
@@ -260,6 +269,7 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L1::MINT,
+      fees: FeeLedger::single(L1::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
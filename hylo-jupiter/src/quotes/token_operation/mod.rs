@@ -7,14 +7,20 @@ use anchor_lang::prelude::Pubkey;
 use anyhow::Result;
 use fix::prelude::{UFix64, N6, N9};
 use fix::typenum::Integer;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OperationOutput<InExp: Integer, OutExp: Integer, FeeExp: Integer> {
   pub in_amount: UFix64<InExp>,
   pub out_amount: UFix64<OutExp>,
   pub fee_amount: UFix64<FeeExp>,
   pub fee_mint: Pubkey,
+
+  /// Fees per mint. Holds `fee_amount` under `fee_mint`, and for composite
+  /// operations the legs charged in other tokens
+  pub fees: FeeLedger,
+
   pub fee_base: UFix64<FeeExp>,
 }
 
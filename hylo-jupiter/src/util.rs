@@ -1,8 +1,10 @@
 use anchor_lang::prelude::{AccountDeserialize, Pubkey};
-use anyhow::{anyhow, Context, Result};
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, ensure, Context, Result};
 use fix::num_traits::FromPrimitive;
 use fix::prelude::UFix64;
 use fix::typenum::Integer;
+use hylo_core::error::AccountError;
 use hylo_core::idl::tokens::TokenMint;
 use jupiter_amm_interface::{
   AccountMap, ClockRef, Quote, SwapMode, SwapParams,
@@ -75,32 +77,97 @@ where
   operation_to_quote(op)
 }
 
-/// Finds and deserializes an account in Jupiter's `AccountMap`.
+/// Borrows raw account data from Jupiter's `AccountMap`.
 ///
 /// # Errors
 /// * Account not found in map
+pub fn account_map_data<'a>(
+  account_map: &'a AccountMap,
+  key: &Pubkey,
+) -> Result<&'a [u8]> {
+  account_map
+    .get(key)
+    .map(|account| account.data.as_slice())
+    .ok_or(AccountError::NotFound(*key).into())
+}
+
+/// Borrows raw account data from Jupiter's `AccountMap`, checking the
+/// account is owned by `owner`.
+///
+/// # Errors
+/// * Account not found in map
+/// * Account owned by another program
+pub fn account_map_owned<'a>(
+  account_map: &'a AccountMap,
+  key: &Pubkey,
+  owner: &Pubkey,
+) -> Result<&'a [u8]> {
+  let account = account_map.get(key).ok_or(AccountError::NotFound(*key))?;
+  if account.owner == *owner {
+    Ok(account.data.as_slice())
+  } else {
+    Err(
+      AccountError::Owner {
+        key: *key,
+        expected: *owner,
+        actual: account.owner,
+      }
+      .into(),
+    )
+  }
+}
+
+/// Borrows raw account data from Jupiter's `AccountMap`, checking the
+/// account is owned by `owner` and starts with `discriminator`.
+///
+/// # Errors
+/// * Account not found in map
+/// * Account owned by another program
+/// * Discriminator of another account type
+pub fn account_map_typed<'a>(
+  account_map: &'a AccountMap,
+  key: &Pubkey,
+  owner: &Pubkey,
+  discriminator: &[u8],
+) -> Result<&'a [u8]> {
+  let data = account_map_owned(account_map, key, owner)?;
+  ensure!(
+    data.starts_with(discriminator),
+    AccountError::Discriminator { key: *key }
+  );
+  Ok(data)
+}
+
+/// Finds and deserializes an Anchor account owned by `owner` in Jupiter's
+/// `AccountMap`.
+///
+/// # Errors
+/// * Account not found in map
+/// * Account owned by another program
+/// * Discriminator of another account type
 /// * Deserialization to `A` fails
-pub fn account_map_get<A: AccountDeserialize>(
+pub fn account_map_get<A: AccountDeserialize + Discriminator>(
   account_map: &AccountMap,
   key: &Pubkey,
+  owner: &Pubkey,
 ) -> Result<A> {
-  let account = account_map
-    .get(key)
-    .ok_or(anyhow!("Account not found {key}"))?;
-  let mut bytes = account.data.as_slice();
-  let out = A::try_deserialize(&mut bytes)?;
+  let mut bytes = account_map_typed(account_map, key, owner, A::DISCRIMINATOR)?;
+  let out = A::try_deserialize_unchecked(&mut bytes)?;
   Ok(out)
 }
 
+/// Finds and unpacks an SPL Token account in Jupiter's `AccountMap`.
+///
+/// # Errors
+/// * Account not found in map
+/// * Account not owned by the SPL Token program
+/// * Unpacking to `A` fails
 pub fn account_spl_get<A: Pack + IsInitialized>(
   account_map: &AccountMap,
   key: &Pubkey,
 ) -> Result<A> {
-  let account = account_map
-    .get(key)
-    .ok_or(anyhow!("Account not found {key}"))?;
-  let mut bytes = account.data.as_slice();
-  let out = A::unpack(&mut bytes)?;
+  let bytes = account_map_owned(account_map, key, &spl_token_interface::ID)?;
+  let out = A::unpack(bytes)?;
   Ok(out)
 }
 
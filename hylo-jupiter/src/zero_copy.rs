@@ -0,0 +1,434 @@
+//! Zero-copy readers for fixed-layout exchange accounts.
+//!
+//! [`Hylo`] and [`LstHeader`] are Borsh encoded without variable length
+//! fields, so every field sits at a constant offset after the discriminator.
+//! These views read only the fields needed for quoting directly from account
+//! bytes, avoiding a full deserialization on every `update()`.
+//! [`ProtocolState::from_account_map`](crate::quotes::ProtocolState::from_account_map)
+//! loads both accounts through them.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, ensure, Result};
+use fix::prelude::UFixValue64;
+use hylo_core::fee_controller::{FeePair, LevercoinFees, StablecoinFees};
+use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_core::idl::exchange::types::{
+  LstSolPrice as IdlLstSolPrice, LstStakePoolProgram,
+  UFixValue64 as IdlUFixValue64,
+};
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::total_sol_cache::TotalSolCache;
+use jupiter_amm_interface::AccountMap;
+
+use crate::util::account_map_typed;
+
+const DISCRIMINATOR_LEN: usize = 8;
+const PUBKEY_LEN: usize = 32;
+const UFIX_VALUE_LEN: usize = 9;
+const FEE_PAIR_LEN: usize = 2 * UFIX_VALUE_LEN;
+const LST_SOL_PRICE_LEN: usize = UFIX_VALUE_LEN + 8;
+const LST_HEADER_RESERVED_LEN: usize = 64;
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+  let mut buf = [0u8; 8];
+  buf.copy_from_slice(&data[offset..offset + 8]);
+  u64::from_le_bytes(buf)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+  let mut buf = [0u8; PUBKEY_LEN];
+  buf.copy_from_slice(&data[offset..offset + PUBKEY_LEN]);
+  Pubkey::new_from_array(buf)
+}
+
+fn read_ufix_value(data: &[u8], offset: usize) -> UFixValue64 {
+  UFixValue64 {
+    bits: read_u64(data, offset),
+    exp: i8::from_le_bytes([data[offset + 8]]),
+  }
+}
+
+fn read_fee_pair(data: &[u8], offset: usize) -> FeePair {
+  FeePair::new(
+    read_ufix_value(data, offset),
+    read_ufix_value(data, offset + UFIX_VALUE_LEN),
+  )
+}
+
+fn read_lst_sol_price(data: &[u8], offset: usize) -> LstSolPrice {
+  LstSolPrice::new(
+    read_ufix_value(data, offset),
+    read_u64(data, offset + UFIX_VALUE_LEN),
+  )
+}
+
+fn validate(data: &[u8], discriminator: &[u8], len: usize) -> Result<()> {
+  ensure!(data.len() >= len, "Account data too short: {}", data.len());
+  ensure!(
+    &data[..DISCRIMINATOR_LEN] == discriminator,
+    "Account discriminator mismatch"
+  );
+  Ok(())
+}
+
+/// Borrowed view over [`Hylo`] account bytes.
+#[derive(Clone, Copy)]
+pub struct HyloView<'a>(&'a [u8]);
+
+impl<'a> HyloView<'a> {
+  const ORACLE_INTERVAL_OFFSET: usize = DISCRIMINATOR_LEN + 6 * PUBKEY_LEN + 6;
+  const STABLECOIN_FEES_OFFSET: usize = Self::ORACLE_INTERVAL_OFFSET + 8;
+  const LEVERCOIN_FEES_OFFSET: usize =
+    Self::STABLECOIN_FEES_OFFSET + 2 * FEE_PAIR_LEN;
+  const TOTAL_SOL_CACHE_OFFSET: usize =
+    Self::LEVERCOIN_FEES_OFFSET + 3 * FEE_PAIR_LEN;
+  const YIELD_HARVEST_CACHE_OFFSET: usize =
+    Self::TOTAL_SOL_CACHE_OFFSET + 8 + UFIX_VALUE_LEN;
+  const YIELD_HARVEST_CONFIG_OFFSET: usize =
+    Self::YIELD_HARVEST_CACHE_OFFSET + 8 + 2 * UFIX_VALUE_LEN;
+  const THRESHOLD_1_OFFSET: usize =
+    Self::YIELD_HARVEST_CONFIG_OFFSET + 2 * UFIX_VALUE_LEN;
+  const THRESHOLD_2_OFFSET: usize = Self::THRESHOLD_1_OFFSET + UFIX_VALUE_LEN;
+  const ORACLE_CONF_OFFSET: usize = Self::THRESHOLD_2_OFFSET + UFIX_VALUE_LEN;
+  const SOL_USD_ORACLE_OFFSET: usize =
+    Self::ORACLE_CONF_OFFSET + UFIX_VALUE_LEN;
+  const LST_SWAP_FEE_OFFSET: usize = Self::SOL_USD_ORACLE_OFFSET + PUBKEY_LEN;
+
+  /// Minimum data length, excluding the trailing reserved bytes.
+  pub const LEN: usize = Self::LST_SWAP_FEE_OFFSET + UFIX_VALUE_LEN;
+
+  /// Wraps account data after checking length and discriminator.
+  ///
+  /// # Errors
+  /// * Data shorter than [`HyloView::LEN`]
+  /// * Discriminator does not match [`Hylo`]
+  pub fn try_new(data: &'a [u8]) -> Result<HyloView<'a>> {
+    validate(data, Hylo::DISCRIMINATOR, Self::LEN)?;
+    Ok(HyloView(data))
+  }
+
+  #[must_use]
+  pub fn oracle_interval_secs(&self) -> u64 {
+    read_u64(self.0, Self::ORACLE_INTERVAL_OFFSET)
+  }
+
+  #[must_use]
+  pub fn stablecoin_fees(&self) -> StablecoinFees {
+    let offset = Self::STABLECOIN_FEES_OFFSET;
+    StablecoinFees::new(
+      read_fee_pair(self.0, offset),
+      read_fee_pair(self.0, offset + FEE_PAIR_LEN),
+    )
+  }
+
+  #[must_use]
+  pub fn levercoin_fees(&self) -> LevercoinFees {
+    let offset = Self::LEVERCOIN_FEES_OFFSET;
+    LevercoinFees::new(
+      read_fee_pair(self.0, offset),
+      read_fee_pair(self.0, offset + FEE_PAIR_LEN),
+      read_fee_pair(self.0, offset + 2 * FEE_PAIR_LEN),
+    )
+  }
+
+  #[must_use]
+  pub fn total_sol_cache(&self) -> TotalSolCache {
+    TotalSolCache {
+      current_update_epoch: read_u64(self.0, Self::TOTAL_SOL_CACHE_OFFSET),
+      total_sol: read_ufix_value(self.0, Self::TOTAL_SOL_CACHE_OFFSET + 8),
+    }
+  }
+
+  #[must_use]
+  pub fn stability_threshold_1(&self) -> UFixValue64 {
+    read_ufix_value(self.0, Self::THRESHOLD_1_OFFSET)
+  }
+
+  #[must_use]
+  pub fn stability_threshold_2(&self) -> UFixValue64 {
+    read_ufix_value(self.0, Self::THRESHOLD_2_OFFSET)
+  }
+
+  #[must_use]
+  pub fn oracle_conf_tolerance(&self) -> UFixValue64 {
+    read_ufix_value(self.0, Self::ORACLE_CONF_OFFSET)
+  }
+
+  #[must_use]
+  pub fn sol_usd_oracle(&self) -> Pubkey {
+    read_pubkey(self.0, Self::SOL_USD_ORACLE_OFFSET)
+  }
+
+  #[must_use]
+  pub fn lst_swap_fee(&self) -> UFixValue64 {
+    read_ufix_value(self.0, Self::LST_SWAP_FEE_OFFSET)
+  }
+}
+
+/// Borrowed view over [`LstHeader`] account bytes.
+#[derive(Clone, Copy)]
+pub struct LstHeaderView<'a>(&'a [u8]);
+
+impl<'a> LstHeaderView<'a> {
+  const MINT_OFFSET: usize = DISCRIMINATOR_LEN;
+  const VAULT_OFFSET: usize = Self::MINT_OFFSET + PUBKEY_LEN;
+  const POOL_STATE_OFFSET: usize = Self::VAULT_OFFSET + PUBKEY_LEN;
+  const STAKE_PROGRAM_OFFSET: usize = Self::POOL_STATE_OFFSET + PUBKEY_LEN;
+  const PREV_PRICE_OFFSET: usize = Self::STAKE_PROGRAM_OFFSET + 1;
+  const PRICE_OFFSET: usize = Self::PREV_PRICE_OFFSET + LST_SOL_PRICE_LEN;
+  const LAST_HARVEST_OFFSET: usize = Self::PRICE_OFFSET + LST_SOL_PRICE_LEN;
+
+  /// Minimum data length, excluding the trailing reserved bytes.
+  pub const LEN: usize = Self::LAST_HARVEST_OFFSET + 8;
+
+  /// Wraps account data after checking length and discriminator.
+  ///
+  /// # Errors
+  /// * Data shorter than [`LstHeaderView::LEN`]
+  /// * Discriminator does not match [`LstHeader`]
+  pub fn try_new(data: &'a [u8]) -> Result<LstHeaderView<'a>> {
+    validate(data, LstHeader::DISCRIMINATOR, Self::LEN)?;
+    Ok(LstHeaderView(data))
+  }
+
+  #[must_use]
+  pub fn mint(&self) -> Pubkey {
+    read_pubkey(self.0, Self::MINT_OFFSET)
+  }
+
+  #[must_use]
+  pub fn vault(&self) -> Pubkey {
+    read_pubkey(self.0, Self::VAULT_OFFSET)
+  }
+
+  #[must_use]
+  pub fn pool_state(&self) -> Pubkey {
+    read_pubkey(self.0, Self::POOL_STATE_OFFSET)
+  }
+
+  /// Stake pool program the LST is priced from.
+  ///
+  /// # Errors
+  /// * Unknown program variant
+  pub fn stake_program(&self) -> Result<LstStakePoolProgram> {
+    match self.0[Self::STAKE_PROGRAM_OFFSET] {
+      0 => Ok(LstStakePoolProgram::Spl),
+      1 => Ok(LstStakePoolProgram::SanctumSpl),
+      2 => Ok(LstStakePoolProgram::SanctumSplMulti),
+      3 => Ok(LstStakePoolProgram::Marinade),
+      variant => Err(anyhow!("Unknown LST stake program {variant}")),
+    }
+  }
+
+  #[must_use]
+  pub fn prev_price_sol(&self) -> LstSolPrice {
+    read_lst_sol_price(self.0, Self::PREV_PRICE_OFFSET)
+  }
+
+  #[must_use]
+  pub fn price_sol(&self) -> LstSolPrice {
+    read_lst_sol_price(self.0, Self::PRICE_OFFSET)
+  }
+
+  #[must_use]
+  pub fn last_yield_harvest_epoch(&self) -> u64 {
+    read_u64(self.0, Self::LAST_HARVEST_OFFSET)
+  }
+
+  /// Copies the header out of the account bytes, field by field.
+  ///
+  /// # Errors
+  /// * Unknown stake program variant
+  /// * Data ends before the reserved bytes
+  pub fn header(&self) -> Result<LstHeader> {
+    let idl_price = |price: LstSolPrice| IdlLstSolPrice {
+      price: IdlUFixValue64 {
+        bits: price.price.bits,
+        exp: price.price.exp,
+      },
+      epoch: price.epoch,
+    };
+    let reserved = self
+      .0
+      .get(Self::LEN..Self::LEN + LST_HEADER_RESERVED_LEN)
+      .and_then(|reserved| reserved.try_into().ok())
+      .ok_or(anyhow!("LST header data too short: {}", self.0.len()))?;
+    Ok(LstHeader {
+      mint: self.mint(),
+      vault: self.vault(),
+      pool_state: self.pool_state(),
+      stake_program: self.stake_program()?,
+      prev_price_sol: idl_price(self.prev_price_sol()),
+      price_sol: idl_price(self.price_sol()),
+      last_yield_harvest_epoch: self.last_yield_harvest_epoch(),
+      _reserved: reserved,
+    })
+  }
+}
+
+/// Finds an account owned by `owner` in Jupiter's `AccountMap` and wraps it
+/// in a [`HyloView`].
+///
+/// # Errors
+/// * Account not found in map
+/// * Account owned by another program
+/// * Length or discriminator check fails
+pub fn hylo_view<'a>(
+  account_map: &'a AccountMap,
+  key: &Pubkey,
+  owner: &Pubkey,
+) -> Result<HyloView<'a>> {
+  let data = account_map_typed(account_map, key, owner, Hylo::DISCRIMINATOR)?;
+  HyloView::try_new(data)
+}
+
+/// Finds an account owned by `owner` in Jupiter's `AccountMap` and wraps it
+/// in a [`LstHeaderView`].
+///
+/// # Errors
+/// * Account not found in map
+/// * Account owned by another program
+/// * Length or discriminator check fails
+pub fn lst_header_view<'a>(
+  account_map: &'a AccountMap,
+  key: &Pubkey,
+  owner: &Pubkey,
+) -> Result<LstHeaderView<'a>> {
+  let data =
+    account_map_typed(account_map, key, owner, LstHeader::DISCRIMINATOR)?;
+  LstHeaderView::try_new(data)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs::File;
+
+  use anchor_lang::prelude::Pubkey;
+  use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize};
+  use anyhow::{anyhow, Result};
+  use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+  use hylo_core::idl::exchange::types::{
+    LstSolPrice, LstStakePoolProgram, UFixValue64,
+  };
+  use serde_json::Value;
+
+  use super::{HyloView, LstHeaderView};
+
+  /// Snapshot account data by name, as stored in the routing fixtures.
+  fn snapshot_data(name: &str) -> Result<Vec<u8>> {
+    let path = format!(
+      "{}/tests/data/protocol-state-918-37508.json",
+      env!("CARGO_MANIFEST_DIR")
+    );
+    let mut snapshot: Value = serde_json::from_reader(File::open(path)?)?;
+    let data = snapshot
+      .get_mut(name)
+      .and_then(|account| account.get_mut("data"))
+      .map(Value::take)
+      .ok_or(anyhow!("Snapshot has no {name} account"))?;
+    Ok(serde_json::from_value(data)?)
+  }
+
+  /// Borsh bytes of `value`, to compare core and IDL types field by field.
+  fn borsh<T: AnchorSerialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(value.try_to_vec()?)
+  }
+
+  #[test]
+  fn hylo_view_matches_snapshot() -> Result<()> {
+    let data = snapshot_data("hylo")?;
+    let hylo = Hylo::try_deserialize(&mut data.as_slice())?;
+    let view = HyloView::try_new(&data)?;
+    assert_eq!(view.oracle_interval_secs(), hylo.oracle_interval_secs);
+    assert_eq!(
+      borsh(&view.stablecoin_fees())?,
+      borsh(&hylo.stablecoin_fees)?
+    );
+    assert_eq!(borsh(&view.levercoin_fees())?, borsh(&hylo.levercoin_fees)?);
+    assert_eq!(
+      borsh(&view.total_sol_cache())?,
+      borsh(&hylo.total_sol_cache)?
+    );
+    assert_eq!(
+      borsh(&view.stability_threshold_1())?,
+      borsh(&hylo.stability_threshold_1)?
+    );
+    assert_eq!(
+      borsh(&view.stability_threshold_2())?,
+      borsh(&hylo.stability_threshold_2)?
+    );
+    assert_eq!(
+      borsh(&view.oracle_conf_tolerance())?,
+      borsh(&hylo.oracle_conf_tolerance)?
+    );
+    assert_eq!(view.sol_usd_oracle(), hylo.sol_usd_oracle);
+    assert_eq!(borsh(&view.lst_swap_fee())?, borsh(&hylo.lst_swap_fee)?);
+    Ok(())
+  }
+
+  #[test]
+  fn lst_header_views_match_snapshot() -> Result<()> {
+    ["jitosol_header", "hylosol_header"]
+      .iter()
+      .try_for_each(|name| {
+        let data = snapshot_data(name)?;
+        let header = LstHeader::try_deserialize(&mut data.as_slice())?;
+        let view = LstHeaderView::try_new(&data)?;
+        assert_eq!(view.mint(), header.mint);
+        assert_eq!(view.vault(), header.vault);
+        assert_eq!(view.pool_state(), header.pool_state);
+        assert_eq!(
+          borsh(&view.stake_program()?)?,
+          borsh(&header.stake_program)?
+        );
+        assert_eq!(view.prev_price_sol(), header.prev_price_sol.into());
+        assert_eq!(view.price_sol(), header.price_sol.into());
+        assert_eq!(
+          view.last_yield_harvest_epoch(),
+          header.last_yield_harvest_epoch
+        );
+        assert_eq!(borsh(&view.header()?)?, borsh(&header)?);
+        Ok(())
+      })
+  }
+
+  #[test]
+  fn lst_header_matches_borsh() -> Result<()> {
+    let header = LstHeader {
+      mint: Pubkey::new_unique(),
+      vault: Pubkey::new_unique(),
+      pool_state: Pubkey::new_unique(),
+      stake_program: LstStakePoolProgram::SanctumSplMulti,
+      prev_price_sol: LstSolPrice {
+        price: UFixValue64 {
+          bits: 1_210_000_000,
+          exp: -9,
+        },
+        epoch: 899,
+      },
+      price_sol: LstSolPrice {
+        price: UFixValue64 {
+          bits: 1_211_000_000,
+          exp: -9,
+        },
+        epoch: 900,
+      },
+      last_yield_harvest_epoch: 899,
+      _reserved: [0; 64],
+    };
+    let mut data = Vec::new();
+    header.try_serialize(&mut data)?;
+    let view = LstHeaderView::try_new(&data)?;
+    assert_eq!(view.mint(), header.mint);
+    assert_eq!(view.prev_price_sol(), header.prev_price_sol.into());
+    assert_eq!(view.price_sol(), header.price_sol.into());
+    assert_eq!(view.last_yield_harvest_epoch(), 899);
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_short_data() {
+    assert!(LstHeaderView::try_new(&[0u8; 16]).is_err());
+  }
+}
@@ -0,0 +1,534 @@
+//! End-to-end routing tests against a local Jupiter router harness.
+//!
+//! [`Router`] walks the pipeline Jupiter runs for every AMM: collect
+//! `accounts_to_update`, serve them from an `AccountMap`, build state on a
+//! shared `ClockRef`, quote each pair and hand the quoted amounts back as
+//! `SwapParams`. Accounts come from a mainnet snapshot, so a
+//! `jupiter-amm-interface` release that changes any of these types or their
+//! semantics fails here before it reaches the router.
+//!
+//! The snapshot is frozen at one slot, so [`Router::warp`] moves the shared
+//! clock forward, [`Router::refresh_pyth`] republishes the SOL/USD price at
+//! the new time, and [`Router::crank`] rolls the epoch-keyed caches the way
+//! the keeper cranks do. Together they reach epoch boundaries and oracle
+//! staleness deterministically.
+//!
+//! [`damaged_accounts_fail_cleanly`] fuzzes the same pipeline with bit flips,
+//! truncations and extreme values written into snapshot accounts. Raise
+//! `PROPTEST_CASES` for a longer run.
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use anyhow::{anyhow, Result};
+use fix::prelude::{UFix64, N6, N9};
+use fix::typenum::Integer;
+use hylo_core::error::AccountError;
+use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::deployment::Deployment;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_jupiter::quotes::token_operation::TokenOperation;
+use hylo_jupiter::quotes::{EnabledPairs, ProtocolState, SharedProtocolState};
+use hylo_jupiter::util::validate_swap_params;
+use jupiter_amm_interface::{
+  AccountMap, ClockRef, Quote, SwapMode, SwapParams,
+};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use serde_json::from_reader;
+
+/// Local stand-in for the Jupiter router, holding one registered Hylo AMM.
+struct Router {
+  account_map: AccountMap,
+  clock: ClockRef,
+  state: SharedProtocolState<ClockRef>,
+}
+
+impl Router {
+  /// Registers Hylo's mainnet deployment against the snapshot accounts.
+  fn load() -> Result<Router> {
+    let path = format!(
+      "{}/tests/data/protocol-state-918-37508.json",
+      env!("CARGO_MANIFEST_DIR")
+    );
+    let mut snapshot: HashMap<String, _> = from_reader(File::open(path)?)?;
+    let mut take = |name: &str| {
+      snapshot
+        .remove(name)
+        .ok_or(anyhow!("Snapshot has no {name} account"))
+    };
+    let deployment = Deployment::MAINNET;
+    let account_map = [
+      ("hylo", deployment.hylo()),
+      ("jitosol_header", deployment.lst_header(JITOSOL::MINT)),
+      ("hylosol_header", deployment.lst_header(HYLOSOL::MINT)),
+      ("hyusd_mint", deployment.hyusd_mint),
+      ("xsol_mint", deployment.xsol_mint),
+      ("shyusd_mint", deployment.shyusd_mint),
+      ("pool_config", deployment.pool_config()),
+      ("hyusd_pool", deployment.hyusd_pool()),
+      ("xsol_pool", deployment.xsol_pool()),
+      ("sol_usd_pyth", deployment.sol_usd_pyth_feed),
+    ]
+    .into_iter()
+    .map(|(name, key)| take(name).map(|account| (key, account)))
+    .collect::<Result<AccountMap>>()?;
+    let clock: Clock = bincode::deserialize(&take("clock")?.data)?;
+    let clock = ClockRef::from(clock);
+    let state = ProtocolState::from_account_map(
+      clock.clone(),
+      &deployment,
+      &Router::fetch(&account_map, &deployment)?,
+    )?;
+    Ok(Router {
+      account_map,
+      clock,
+      state: SharedProtocolState::new(state),
+    })
+  }
+
+  /// Serves exactly the accounts the AMM asks for, as the router's account
+  /// fetcher does.
+  fn fetch(
+    account_map: &AccountMap,
+    deployment: &Deployment,
+  ) -> Result<AccountMap> {
+    Router::fetch_for(account_map, deployment, &EnabledPairs::All)
+  }
+
+  /// Serves the accounts the AMM asks for to quote `pairs`.
+  fn fetch_for(
+    account_map: &AccountMap,
+    deployment: &Deployment,
+    pairs: &EnabledPairs,
+  ) -> Result<AccountMap> {
+    ProtocolState::<ClockRef>::accounts_to_update_for(deployment, pairs)
+      .into_iter()
+      .map(|key| {
+        account_map
+          .get(&key)
+          .cloned()
+          .map(|account| (key, account))
+          .ok_or(anyhow!("Router has no account {key}"))
+      })
+      .collect()
+  }
+
+  /// Rebuilds state from freshly fetched accounts, as the router does on
+  /// every account update.
+  fn update(&self) -> Result<()> {
+    let deployment = Deployment::MAINNET;
+    let accounts = Router::fetch(&self.account_map, &deployment)?;
+    self.state.update(|_| {
+      ProtocolState::from_account_map(
+        self.clock.clone(),
+        &deployment,
+        &accounts,
+      )
+    })
+  }
+
+  /// Moves the shared clock forward by `epochs` and `secs`, producing slots
+  /// at 400ms. Crossing an epoch starts the new epoch at the warped time.
+  fn warp(&self, epochs: u64, secs: u64) {
+    let unix_timestamp =
+      self.clock.unix_timestamp().saturating_add_unsigned(secs);
+    let epoch_start_timestamp = if epochs == 0 {
+      self.clock.epoch_start_timestamp()
+    } else {
+      unix_timestamp
+    };
+    self.clock.update(Clock {
+      slot: self.clock.slot() + secs * 5 / 2,
+      epoch_start_timestamp,
+      epoch: self.clock.epoch() + epochs,
+      leader_schedule_epoch: self.clock.leader_schedule_epoch() + epochs,
+      unix_timestamp,
+    });
+  }
+
+  /// Rewrites the Anchor account at `key` in place.
+  fn rewrite<A>(
+    &mut self,
+    key: &Pubkey,
+    edit: impl FnOnce(&mut A),
+  ) -> Result<()>
+  where
+    A: AccountDeserialize + AccountSerialize,
+  {
+    let account = self
+      .account_map
+      .get_mut(key)
+      .ok_or(anyhow!("Router has no account {key}"))?;
+    let mut state = A::try_deserialize(&mut account.data.as_slice())?;
+    edit(&mut state);
+    account.data.clear();
+    state.try_serialize(&mut account.data)?;
+    Ok(())
+  }
+
+  /// Republishes the SOL/USD price at the current clock, unchanged.
+  fn refresh_pyth(&mut self) -> Result<()> {
+    let (slot, unix_timestamp) =
+      (self.clock.slot(), self.clock.unix_timestamp());
+    let feed = Deployment::MAINNET.sol_usd_pyth_feed;
+    self.rewrite(&feed, |price_update: &mut PriceUpdateV2| {
+      let message = &mut price_update.price_message;
+      message.prev_publish_time = message.publish_time;
+      message.publish_time = unix_timestamp;
+      price_update.posted_slot = slot;
+    })
+  }
+
+  /// Rolls the total SOL cache and LST prices into the current epoch, as
+  /// the keeper cranks do at an epoch boundary. LST prices carry over
+  /// unchanged.
+  fn crank(&mut self) -> Result<()> {
+    let deployment = Deployment::MAINNET;
+    let epoch = self.clock.epoch();
+    self.rewrite(&deployment.hylo(), |hylo: &mut Hylo| {
+      hylo.total_sol_cache.current_update_epoch = epoch;
+    })?;
+    [JITOSOL::MINT, HYLOSOL::MINT].iter().try_for_each(|mint| {
+      self.rewrite(&deployment.lst_header(*mint), |header: &mut LstHeader| {
+        header.prev_price_sol = header.price_sol;
+        header.price_sol.epoch = epoch;
+      })
+    })
+  }
+
+  /// Quotes `amount` of `IN` and checks the quote is routable.
+  fn route<IN, OUT>(&self, amount: u64) -> Result<Quote>
+  where
+    IN: TokenMint,
+    OUT: TokenMint,
+    ProtocolState<ClockRef>: TokenOperation<IN, OUT>,
+    <ProtocolState<ClockRef> as TokenOperation<IN, OUT>>::FeeExp: Integer,
+  {
+    let quote = self.state.quote::<IN, OUT>(amount)?;
+    assert_eq!(quote.in_amount, amount);
+    assert!(
+      quote.out_amount > 0,
+      "{} -> {} quoted zero",
+      IN::MINT,
+      OUT::MINT
+    );
+    assert!(quote.fee_pct < rust_decimal::Decimal::ONE);
+    assert!([IN::MINT, OUT::MINT].contains(&quote.fee_mint));
+    Ok(quote)
+  }
+}
+
+/// Quote entry points of every supported pair.
+type QuoteFn = fn(&SharedProtocolState<ClockRef>, u64) -> Result<Quote>;
+
+const PAIRS: [QuoteFn; 13] = [
+  SharedProtocolState::quote::<JITOSOL, HYUSD>,
+  SharedProtocolState::quote::<HYUSD, JITOSOL>,
+  SharedProtocolState::quote::<HYLOSOL, HYUSD>,
+  SharedProtocolState::quote::<HYUSD, HYLOSOL>,
+  SharedProtocolState::quote::<JITOSOL, XSOL>,
+  SharedProtocolState::quote::<XSOL, JITOSOL>,
+  SharedProtocolState::quote::<HYUSD, XSOL>,
+  SharedProtocolState::quote::<XSOL, HYUSD>,
+  SharedProtocolState::quote::<JITOSOL, HYLOSOL>,
+  SharedProtocolState::quote::<HYLOSOL, JITOSOL>,
+  SharedProtocolState::quote::<HYUSD, SHYUSD>,
+  SharedProtocolState::quote::<SHYUSD, HYUSD>,
+  SharedProtocolState::quote::<SHYUSD, JITOSOL>,
+];
+
+/// Damage done to the data of one snapshot account. Offsets wrap around the
+/// data length.
+#[derive(Debug, Clone)]
+enum Mutation {
+  FlipBit { offset: usize, bit: u8 },
+  Truncate { len: usize },
+  Overwrite { offset: usize, value: u64 },
+}
+
+impl Mutation {
+  fn apply(&self, data: &mut Vec<u8>) {
+    let wrap = |offset: usize| offset.checked_rem(data.len());
+    match *self {
+      Mutation::FlipBit { offset, bit } => {
+        if let Some(offset) = wrap(offset) {
+          data[offset] ^= 1 << bit;
+        }
+      }
+      Mutation::Truncate { len } => {
+        data.truncate(wrap(len).unwrap_or_default());
+      }
+      Mutation::Overwrite { offset, value } => {
+        if let Some(offset) = wrap(offset) {
+          data[offset..]
+            .iter_mut()
+            .zip(value.to_le_bytes())
+            .for_each(|(byte, value)| *byte = value);
+        }
+      }
+    }
+  }
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+  let extreme = prop_oneof![
+    Just(0),
+    Just(1),
+    Just(u64::MAX),
+    Just(i64::MAX.unsigned_abs()),
+    Just(i64::MIN.unsigned_abs()),
+  ];
+  prop_oneof![
+    (any::<usize>(), 0..8u8)
+      .prop_map(|(offset, bit)| Mutation::FlipBit { offset, bit }),
+    any::<usize>().prop_map(|len| Mutation::Truncate { len }),
+    (any::<usize>(), extreme)
+      .prop_map(|(offset, value)| Mutation::Overwrite { offset, value }),
+  ]
+}
+
+/// Fails the case unless `error` carries a message for the router to log.
+fn check_error(error: &anyhow::Error) -> Result<(), TestCaseError> {
+  prop_assert!(!error.to_string().is_empty(), "Unlabelled error {error:?}");
+  Ok(())
+}
+
+/// Swap parameters the router passes back for `quote`.
+fn swap_params<'a>(
+  quote: &Quote,
+  swap_mode: SwapMode,
+  source_mint: Pubkey,
+  destination_mint: Pubkey,
+  jupiter_program_id: &'a Pubkey,
+) -> SwapParams<'a, 'a> {
+  SwapParams {
+    swap_mode,
+    in_amount: quote.in_amount,
+    out_amount: quote.out_amount,
+    source_mint,
+    destination_mint,
+    source_token_account: Pubkey::new_unique(),
+    destination_token_account: Pubkey::new_unique(),
+    token_transfer_authority: Pubkey::new_unique(),
+    user: Pubkey::new_unique(),
+    payer: Pubkey::new_unique(),
+    quote_mint_to_referrer: None,
+    jupiter_program_id,
+    missing_dynamic_accounts_as_default: false,
+  }
+}
+
+#[test]
+fn accounts_to_update_cover_state() -> Result<()> {
+  let router = Router::load()?;
+  let fetched = Router::fetch(&router.account_map, &Deployment::MAINNET)?;
+  assert_eq!(fetched.len(), router.account_map.len());
+  Ok(())
+}
+
+#[test]
+fn accounts_to_update_follow_enabled_pairs() -> Result<()> {
+  let router = Router::load()?;
+  let deployment = Deployment::MAINNET;
+  let pairs = EnabledPairs::Only(vec![
+    (JITOSOL::MINT, HYUSD::MINT),
+    (HYUSD::MINT, XSOL::MINT),
+  ]);
+  let fetched = Router::fetch_for(&router.account_map, &deployment, &pairs)?;
+  assert_eq!(fetched.len(), router.account_map.len() - 4);
+  assert!(!fetched.contains_key(&deployment.pool_config()));
+  let state = SharedProtocolState::new(ProtocolState::from_account_map_for(
+    router.clock.clone(),
+    &deployment,
+    &pairs,
+    &fetched,
+  )?);
+  let quote = state.quote::<JITOSOL, HYUSD>(1_000_000_000)?;
+  assert_eq!(
+    quote.out_amount,
+    router.route::<JITOSOL, HYUSD>(1_000_000_000)?.out_amount
+  );
+  assert!(state.quote::<HYUSD, SHYUSD>(1_000_000).is_err());
+  Ok(())
+}
+
+#[test]
+fn rejects_look_alike_accounts() -> Result<()> {
+  let router = Router::load()?;
+  let deployment = Deployment::MAINNET;
+  let hylo = deployment.hylo();
+  let rejection = |account_map: &AccountMap| {
+    ProtocolState::from_account_map(
+      router.clock.clone(),
+      &deployment,
+      account_map,
+    )
+    .err()
+    .and_then(|error| error.downcast::<AccountError>().ok())
+  };
+
+  let mut foreign_owner = router.account_map.clone();
+  let impostor = Pubkey::new_unique();
+  foreign_owner
+    .get_mut(&hylo)
+    .ok_or(anyhow!("No hylo account"))?
+    .owner = impostor;
+  assert_eq!(
+    rejection(&foreign_owner),
+    Some(AccountError::Owner {
+      key: hylo,
+      expected: deployment.exchange_program,
+      actual: impostor,
+    })
+  );
+
+  let mut wrong_type = router.account_map.clone();
+  let header = router
+    .account_map
+    .get(&deployment.lst_header(JITOSOL::MINT))
+    .cloned()
+    .ok_or(anyhow!("No header account"))?;
+  wrong_type.insert(hylo, header);
+  assert_eq!(
+    rejection(&wrong_type),
+    Some(AccountError::Discriminator { key: hylo })
+  );
+  Ok(())
+}
+
+#[test]
+fn exchange_pairs_route() -> Result<()> {
+  let router = Router::load()?;
+  router.route::<JITOSOL, HYUSD>(1_000_000_000)?;
+  router.route::<HYUSD, JITOSOL>(1_000_000)?;
+  router.route::<HYLOSOL, XSOL>(1_000_000_000)?;
+  router.route::<XSOL, HYLOSOL>(1_000_000)?;
+  router.route::<HYUSD, XSOL>(1_000_000)?;
+  router.route::<XSOL, HYUSD>(1_000_000)?;
+  router.route::<JITOSOL, HYLOSOL>(1_000_000_000)?;
+  Ok(())
+}
+
+#[test]
+fn stability_pool_pairs_route() -> Result<()> {
+  let router = Router::load()?;
+  router.route::<HYUSD, SHYUSD>(1_000_000)?;
+  router.route::<SHYUSD, HYUSD>(1_000_000)?;
+  router.route::<SHYUSD, JITOSOL>(1_000_000)?;
+  Ok(())
+}
+
+#[test]
+fn lst_withdrawal_records_fees_per_mint() -> Result<()> {
+  let router = Router::load()?;
+  let state = router.state.snapshot();
+  let op = TokenOperation::<SHYUSD, JITOSOL>::compute_output(
+    &*state,
+    UFix64::new(1_000_000_000),
+  )?;
+  assert_eq!(op.fee_mint, JITOSOL::MINT);
+  assert_eq!(op.fees.total::<N9>(JITOSOL::MINT)?, op.fee_amount);
+  assert_eq!(op.fee_amount, UFix64::new(7_476_917));
+  assert_eq!(op.fees.total::<N6>(HYUSD::MINT)?, UFix64::new(1_161_948));
+  assert_eq!(op.fees.mints().count(), 2);
+  Ok(())
+}
+
+#[test]
+fn quotes_survive_update() -> Result<()> {
+  let router = Router::load()?;
+  let before = router.route::<XSOL, HYUSD>(1_000_000)?;
+  router.update()?;
+  let after = router.route::<XSOL, HYUSD>(1_000_000)?;
+  assert_eq!(before.out_amount, after.out_amount);
+  assert_eq!(before.fee_amount, after.fee_amount);
+  Ok(())
+}
+
+#[test]
+fn stale_oracle_needs_refresh() -> Result<()> {
+  let mut router = Router::load()?;
+  let before = router.route::<XSOL, HYUSD>(1_000_000)?;
+  router.warp(0, 3_600);
+  assert!(router.update().is_err());
+  router.refresh_pyth()?;
+  router.update()?;
+  let after = router.route::<XSOL, HYUSD>(1_000_000)?;
+  assert_eq!(before.out_amount, after.out_amount);
+  Ok(())
+}
+
+#[test]
+fn epoch_boundary_needs_crank() -> Result<()> {
+  let mut router = Router::load()?;
+  let before = router.route::<JITOSOL, HYUSD>(1_000_000_000)?;
+  router.warp(1, 0);
+  assert!(router.state.quote::<JITOSOL, HYUSD>(1_000_000_000).is_err());
+  assert!(router.update().is_err());
+  router.crank()?;
+  router.update()?;
+  let after = router.route::<JITOSOL, HYUSD>(1_000_000_000)?;
+  assert_eq!(before.out_amount, after.out_amount);
+  router.route::<JITOSOL, HYLOSOL>(1_000_000_000)?;
+  Ok(())
+}
+
+proptest! {
+  #[test]
+  fn damaged_accounts_fail_cleanly(
+    account in any::<Index>(),
+    mutations in prop::collection::vec(mutation(), 1..4),
+    amount in prop_oneof![Just(0), Just(u64::MAX), any::<u64>()],
+  ) {
+    let mut router =
+      Router::load().map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let mut keys = router.account_map.keys().copied().collect::<Vec<_>>();
+    keys.sort();
+    let key = account.get(&keys);
+    let data = &mut router
+      .account_map
+      .get_mut(key)
+      .ok_or(TestCaseError::fail("Key left the account map"))?
+      .data;
+    mutations.iter().for_each(|mutation| mutation.apply(data));
+    if let Err(error) = router.update() {
+      check_error(&error)?;
+    }
+    PAIRS.iter().try_for_each(|quote| {
+      quote(&router.state, amount)
+        .err()
+        .map_or(Ok(()), |error| check_error(&error))
+    })?;
+  }
+}
+
+#[test]
+fn swap_params_survive_validation() -> Result<()> {
+  let router = Router::load()?;
+  let quote = router.route::<JITOSOL, HYUSD>(1_000_000_000)?;
+  let jupiter = Pubkey::new_unique();
+  let exact_in = swap_params(
+    &quote,
+    SwapMode::ExactIn,
+    JITOSOL::MINT,
+    HYUSD::MINT,
+    &jupiter,
+  );
+  let validated = validate_swap_params(&exact_in)?;
+  assert_eq!(validated.in_amount, quote.in_amount);
+  assert_eq!(validated.out_amount, quote.out_amount);
+  let exact_out = swap_params(
+    &quote,
+    SwapMode::ExactOut,
+    JITOSOL::MINT,
+    HYUSD::MINT,
+    &jupiter,
+  );
+  assert!(validate_swap_params(&exact_out).is_err());
+  Ok(())
+}
@@ -0,0 +1,171 @@
+//! Latency benchmarks for the state-based quote hot path.
+//!
+//! Runs against the committed mainnet snapshot so results are comparable
+//! across revisions. Criterion reports changes beyond the noise threshold as
+//! regressions; `bin/bench.sh` compares a branch against a baseline saved
+//! from `main`.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use criterion::Criterion;
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::idl::exchange::accounts::Hylo;
+use hylo_core::pyth::OracleConfig;
+use hylo_core::stability_mode::StabilityController;
+use hylo_core::total_sol_cache::TotalSolCache;
+use hylo_idl::deployment::Deployment;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_jupiter::quotes::{
+  ProtocolState as JupiterState, SharedProtocolState,
+};
+use hylo_quotes::prelude::{ProtocolAccounts, ProtocolState};
+use jupiter_amm_interface::{AccountMap, ClockRef};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use serde_json::from_reader;
+use solana_program_pack::Pack;
+use spl_token_interface::state::Mint;
+
+const PAIRS: [(&str, Pubkey, Pubkey, u64); 8] = [
+  ("jitosol_hyusd", JITOSOL::MINT, HYUSD::MINT, 1_000_000_000),
+  ("hyusd_jitosol", HYUSD::MINT, JITOSOL::MINT, 1_000_000),
+  ("jitosol_xsol", JITOSOL::MINT, XSOL::MINT, 1_000_000_000),
+  ("xsol_jitosol", XSOL::MINT, JITOSOL::MINT, 1_000_000),
+  ("hyusd_xsol", HYUSD::MINT, XSOL::MINT, 1_000_000),
+  ("xsol_hyusd", XSOL::MINT, HYUSD::MINT, 1_000_000),
+  (
+    "jitosol_hylosol",
+    JITOSOL::MINT,
+    HYLOSOL::MINT,
+    1_000_000_000,
+  ),
+  ("hyusd_shyusd", HYUSD::MINT, SHYUSD::MINT, 1_000_000),
+];
+
+fn snapshot_path() -> String {
+  format!(
+    "{}/tests/data/protocol-state-918-37508.json",
+    env!("CARGO_MANIFEST_DIR")
+  )
+}
+
+fn load_accounts() -> Result<ProtocolAccounts> {
+  Ok(from_reader(File::open(snapshot_path())?)?)
+}
+
+/// Snapshot accounts keyed by their mainnet address, as Jupiter's account
+/// fetcher hands them to `update()`, and the snapshot clock.
+fn load_account_map(deployment: &Deployment) -> Result<(AccountMap, Clock)> {
+  let mut snapshot: HashMap<String, _> =
+    from_reader(File::open(snapshot_path())?)?;
+  let mut take = |name: &str| {
+    snapshot
+      .remove(name)
+      .ok_or(anyhow!("Snapshot has no {name} account"))
+  };
+  let account_map = [
+    ("hylo", deployment.hylo()),
+    ("jitosol_header", deployment.lst_header(JITOSOL::MINT)),
+    ("hylosol_header", deployment.lst_header(HYLOSOL::MINT)),
+    ("hyusd_mint", deployment.hyusd_mint),
+    ("xsol_mint", deployment.xsol_mint),
+    ("shyusd_mint", deployment.shyusd_mint),
+    ("pool_config", deployment.pool_config()),
+    ("hyusd_pool", deployment.hyusd_pool()),
+    ("xsol_pool", deployment.xsol_pool()),
+    ("sol_usd_pyth", deployment.sol_usd_pyth_feed),
+  ]
+  .into_iter()
+  .map(|(name, key)| take(name).map(|account| (key, account)))
+  .collect::<Result<AccountMap>>()?;
+  let clock = bincode::deserialize(&take("clock")?.data)?;
+  Ok((account_map, clock))
+}
+
+fn bench_deserialize(c: &mut Criterion, accounts: &ProtocolAccounts) {
+  c.bench_function("protocol_state/try_from_accounts", |b| {
+    b.iter(|| ProtocolState::<Clock>::try_from(accounts));
+  });
+}
+
+fn bench_exchange_context_load(
+  c: &mut Criterion,
+  accounts: &ProtocolAccounts,
+) -> Result<()> {
+  let hylo = Hylo::try_deserialize(&mut accounts.hylo.data.as_slice())?;
+  let sol_usd =
+    PriceUpdateV2::try_deserialize(&mut accounts.sol_usd_pyth.data.as_slice())?;
+  let hyusd_mint = Mint::unpack(&accounts.hyusd_mint.data)?;
+  let xsol_mint = Mint::unpack(&accounts.xsol_mint.data)?;
+  let clock: Clock = bincode::deserialize(&accounts.clock.data)?;
+  let total_sol_cache: TotalSolCache = hylo.total_sol_cache.into();
+  let oracle_config = OracleConfig::new(
+    hylo.oracle_interval_secs,
+    hylo.oracle_conf_tolerance.try_into()?,
+  );
+  let stability_controller = StabilityController::new(
+    hylo.stability_threshold_1.try_into()?,
+    hylo.stability_threshold_2.try_into()?,
+  )?;
+  c.bench_function("exchange_context/load", |b| {
+    b.iter(|| {
+      ExchangeContext::load(
+        clock.clone(),
+        &total_sol_cache,
+        stability_controller,
+        oracle_config,
+        hylo.stablecoin_fees.into(),
+        hylo.levercoin_fees.into(),
+        &sol_usd,
+        &hyusd_mint,
+        Some(&xsol_mint),
+      )
+    });
+  });
+  Ok(())
+}
+
+/// Jupiter's `update()`: rebuilds and swaps in the shared state from a
+/// freshly fetched account map.
+fn bench_jupiter_update(c: &mut Criterion) -> Result<()> {
+  let deployment = Deployment::MAINNET;
+  let (account_map, clock) = load_account_map(&deployment)?;
+  let clock = ClockRef::from(clock);
+  let build =
+    || JupiterState::from_account_map(clock.clone(), &deployment, &account_map);
+  let state = SharedProtocolState::new(build()?);
+  c.bench_function("jupiter/update", |b| {
+    b.iter(|| state.update(|_| build()));
+  });
+  Ok(())
+}
+
+fn bench_quotes(c: &mut Criterion, state: &ProtocolState<Clock>) {
+  let mut group = c.benchmark_group("quote");
+  PAIRS.iter().for_each(|(name, input, output, amount)| {
+    group.bench_function(*name, |b| {
+      b.iter(|| state.runtime_output(*input, *output, *amount));
+    });
+  });
+  group.finish();
+}
+
+fn main() -> Result<()> {
+  let mut c = Criterion::default()
+    .noise_threshold(0.05)
+    .measurement_time(Duration::from_secs(5))
+    .configure_from_args();
+  let accounts = load_accounts()?;
+  let state = ProtocolState::<Clock>::try_from(&accounts)?;
+  bench_deserialize(&mut c, &accounts);
+  bench_exchange_context_load(&mut c, &accounts)?;
+  bench_jupiter_update(&mut c)?;
+  bench_quotes(&mut c, &state);
+  c.final_summary();
+  Ok(())
+}
@@ -0,0 +1,412 @@
+//! Historical protocol accounts from archived account writes.
+//!
+//! Backtests need the protocol as it was at a past slot, which RPC cannot
+//! serve. [`AccountArchive`] indexes account writes exported from a Geyser
+//! plugin or a Bigtable account table, and rebuilds [`ProtocolAccounts`] at
+//! any archived slot from the latest write of each account at or before it.
+//! The result feeds the same machinery as live state: a
+//! [`SnapshotStateProvider`] for quoting, or a [`ReplayCase`] pre-state.
+//!
+//! Both exports are JSON Lines, one account write per line:
+//!
+//! - [`ArchiveFormat::Geyser`] - `pubkey`, `slot`, `write_version`, `lamports`,
+//!   `owner`, base64 `data`, `executable`, `rent_epoch`, and optionally the
+//!   `txn_signature` that made the write
+//! - [`ArchiveFormat::Bigtable`] - `key` as `<pubkey>/<slot in hex>` with the
+//!   RPC account encoding: `lamports`, `owner`, `executable`, `rentEpoch`, and
+//!   `data` as `[<base64>, "base64"]`
+//!
+//! Writes within a slot are ordered by `write_version` for Geyser and by
+//! line order for Bigtable. Replay cases need the pre-state of a transaction
+//! within its slot, so they are only built from Geyser exports carrying
+//! `txn_signature`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::BufRead;
+use std::ops::RangeBounds;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Context, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::Deserialize;
+
+use crate::protocol_state::{
+  ProtocolAccounts, ProtocolState, SnapshotStateProvider,
+};
+use crate::replay::ReplayCase;
+
+/// Export an archive was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+  /// Account writes streamed by a Geyser plugin
+  Geyser,
+
+  /// Rows of a Bigtable account table export
+  Bigtable,
+}
+
+/// Geyser account write.
+#[derive(Deserialize)]
+struct GeyserRecord {
+  pubkey: String,
+  slot: u64,
+  write_version: u64,
+  lamports: u64,
+  owner: String,
+  data: String,
+  executable: bool,
+  rent_epoch: u64,
+  #[serde(default)]
+  txn_signature: Option<String>,
+}
+
+/// Bigtable account row.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BigtableRecord {
+  key: String,
+  lamports: u64,
+  owner: String,
+  data: (String, String),
+  executable: bool,
+  rent_epoch: u64,
+}
+
+/// One archived write of an account.
+#[derive(Debug, Clone)]
+pub struct ArchivedAccount {
+  pub pubkey: Pubkey,
+  pub slot: u64,
+
+  /// Order of the write within its slot
+  pub write_version: u64,
+
+  pub account: Account,
+
+  /// Transaction that made the write, when the export records it
+  pub txn_signature: Option<String>,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+  Pubkey::from_str(value).map_err(|e| anyhow!("Invalid pubkey {value}: {e}"))
+}
+
+impl ArchivedAccount {
+  /// Decodes one JSON line of an archive in `format`. Bigtable rows take
+  /// `line_index` as their write version.
+  ///
+  /// # Errors
+  /// * Malformed JSON, pubkey, row key or base64 data
+  pub fn decode(
+    line: &str,
+    format: ArchiveFormat,
+    line_index: u64,
+  ) -> Result<ArchivedAccount> {
+    match format {
+      ArchiveFormat::Geyser => {
+        let record: GeyserRecord = serde_json::from_str(line)?;
+        Ok(ArchivedAccount {
+          pubkey: parse_pubkey(&record.pubkey)?,
+          slot: record.slot,
+          write_version: record.write_version,
+          account: Account {
+            lamports: record.lamports,
+            data: BASE64_STANDARD.decode(record.data)?,
+            owner: parse_pubkey(&record.owner)?,
+            executable: record.executable,
+            rent_epoch: record.rent_epoch,
+          },
+          txn_signature: record.txn_signature,
+        })
+      }
+      ArchiveFormat::Bigtable => {
+        let record: BigtableRecord = serde_json::from_str(line)?;
+        let (pubkey, slot) = record
+          .key
+          .split_once('/')
+          .ok_or(anyhow!("Invalid Bigtable row key {}", record.key))?;
+        let (data, encoding) = record.data;
+        ensure!(encoding == "base64", "Unsupported data encoding {encoding}");
+        Ok(ArchivedAccount {
+          pubkey: parse_pubkey(pubkey)?,
+          slot: u64::from_str_radix(slot, 16)
+            .with_context(|| format!("Invalid slot in row key {slot}"))?,
+          write_version: line_index,
+          account: Account {
+            lamports: record.lamports,
+            data: BASE64_STANDARD.decode(data)?,
+            owner: parse_pubkey(&record.owner)?,
+            executable: record.executable,
+            rent_epoch: record.rent_epoch,
+          },
+          txn_signature: None,
+        })
+      }
+    }
+  }
+}
+
+/// Position of a write: the slot, then the write version within the slot.
+type WritePosition = (u64, u64);
+
+/// Archived writes of the protocol accounts, indexed by slot.
+#[derive(Debug, Clone, Default)]
+pub struct AccountArchive {
+  writes: HashMap<Pubkey, BTreeMap<WritePosition, Account>>,
+
+  /// Position of each transaction's first protocol account write
+  transactions: HashMap<String, WritePosition>,
+}
+
+impl AccountArchive {
+  /// Indexes writes, keeping only [`ProtocolAccounts::pubkeys`].
+  #[must_use]
+  pub fn new(writes: impl IntoIterator<Item = ArchivedAccount>) -> Self {
+    let keys = ProtocolAccounts::pubkeys();
+    writes
+      .into_iter()
+      .filter(|write| keys.contains(&write.pubkey))
+      .fold(AccountArchive::default(), |mut archive, write| {
+        let position = (write.slot, write.write_version);
+        if let Some(signature) = write.txn_signature {
+          archive
+            .transactions
+            .entry(signature)
+            .and_modify(|first| *first = position.min(*first))
+            .or_insert(position);
+        }
+        archive
+          .writes
+          .entry(write.pubkey)
+          .or_default()
+          .insert(position, write.account);
+        archive
+      })
+  }
+
+  /// Reads an archive in `format`, skipping blank lines.
+  ///
+  /// # Errors
+  /// * IO failure
+  /// * Any line fails to decode, reported with its line number
+  pub fn read(reader: impl BufRead, format: ArchiveFormat) -> Result<Self> {
+    let writes = reader
+      .lines()
+      .enumerate()
+      .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+      .map(|(index, line)| {
+        let line_index = u64::try_from(index)?;
+        ArchivedAccount::decode(&line?, format, line_index)
+          .with_context(|| format!("Invalid archive line {}", index + 1))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Self::new(writes))
+  }
+
+  /// Slots at which any protocol account was written, ascending.
+  #[must_use]
+  pub fn slots(&self) -> BTreeSet<u64> {
+    self
+      .writes
+      .values()
+      .flat_map(|writes| writes.keys().map(|(slot, _)| *slot))
+      .collect()
+  }
+
+  /// Latest write of `pubkey` within `positions`.
+  fn latest_write(
+    &self,
+    pubkey: &Pubkey,
+    positions: impl RangeBounds<WritePosition>,
+  ) -> Option<&Account> {
+    self
+      .writes
+      .get(pubkey)
+      .and_then(|writes| writes.range(positions).next_back())
+      .map(|(_, account)| account)
+  }
+
+  /// Latest write of `pubkey` at or before `slot`.
+  #[must_use]
+  pub fn account_at(&self, pubkey: &Pubkey, slot: u64) -> Option<&Account> {
+    self.latest_write(pubkey, ..=(slot, u64::MAX))
+  }
+
+  /// Protocol accounts from the latest write of each within `positions`.
+  fn accounts_within(
+    &self,
+    positions: impl RangeBounds<WritePosition> + Clone,
+  ) -> Result<ProtocolAccounts> {
+    let pubkeys = ProtocolAccounts::pubkeys();
+    let accounts: Vec<Option<Account>> = pubkeys
+      .iter()
+      .map(|pubkey| self.latest_write(pubkey, positions.clone()).cloned())
+      .collect();
+    ProtocolAccounts::try_from((pubkeys.as_slice(), accounts.as_slice()))
+  }
+
+  /// Protocol accounts as of `slot`.
+  ///
+  /// # Errors
+  /// * An account has no write at or before `slot`
+  pub fn accounts_at(&self, slot: u64) -> Result<ProtocolAccounts> {
+    self
+      .accounts_within(..=(slot, u64::MAX))
+      .with_context(|| format!("Incomplete archive at slot {slot}"))
+  }
+
+  /// Protocol state as of `slot`.
+  ///
+  /// # Errors
+  /// * Accounts incomplete at `slot`
+  /// * Propagates errors from building [`ProtocolState`]
+  pub fn state_at(&self, slot: u64) -> Result<ProtocolState<Clock>> {
+    ProtocolState::try_from(&self.accounts_at(slot)?)
+  }
+
+  /// State provider serving the protocol as of `slot`.
+  ///
+  /// # Errors
+  /// * Propagates errors from [`AccountArchive::state_at`]
+  pub fn provider_at(&self, slot: u64) -> Result<SnapshotStateProvider<Clock>> {
+    self.state_at(slot).map(SnapshotStateProvider::new)
+  }
+
+  /// Replay case for the transaction `signature`, with the protocol
+  /// accounts as it loaded them: the latest writes before its first write,
+  /// including those of earlier transactions in its slot.
+  ///
+  /// # Errors
+  /// * Transaction wrote no archived protocol account
+  /// * Accounts incomplete before the transaction
+  pub fn replay_case(
+    &self,
+    signature: String,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    observed_out: u64,
+  ) -> Result<ReplayCase> {
+    let position = *self.transactions.get(&signature).ok_or(anyhow!(
+      "Transaction {signature} wrote no archived protocol account"
+    ))?;
+    let pre_state = self.accounts_within(..position).with_context(|| {
+      format!("Incomplete archive before transaction {signature}")
+    })?;
+    Ok(ReplayCase {
+      signature,
+      input_mint,
+      output_mint,
+      amount_in,
+      observed_out,
+      pre_state,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn geyser_line(
+    pubkey: Pubkey,
+    slot: u64,
+    version: u64,
+    data: &[u8],
+  ) -> String {
+    json!({
+      "pubkey": pubkey.to_string(),
+      "slot": slot,
+      "write_version": version,
+      "lamports": 1,
+      "owner": Pubkey::default().to_string(),
+      "data": BASE64_STANDARD.encode(data),
+      "executable": false,
+      "rent_epoch": 0,
+    })
+    .to_string()
+  }
+
+  #[test]
+  fn latest_write_at_slot() -> Result<()> {
+    let key = ProtocolAccounts::pubkeys()[0];
+    let lines = [
+      geyser_line(key, 10, 1, &[1]),
+      geyser_line(key, 12, 3, &[3]),
+      geyser_line(key, 12, 2, &[2]),
+      geyser_line(Pubkey::new_unique(), 11, 1, &[9]),
+    ]
+    .join("\n");
+    let archive =
+      AccountArchive::read(lines.as_bytes(), ArchiveFormat::Geyser)?;
+    assert_eq!(archive.slots(), BTreeSet::from([10, 12]));
+    let data = |slot| archive.account_at(&key, slot).map(|a| a.data.clone());
+    assert_eq!(data(9), None);
+    assert_eq!(data(11), Some(vec![1]));
+    assert_eq!(data(12), Some(vec![3]));
+    assert!(archive.accounts_at(12).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn pre_state_precedes_transaction_in_slot() -> Result<()> {
+    let key = ProtocolAccounts::pubkeys()[0];
+    let signed = |version, data: &[u8], signature: &str| {
+      let mut line: serde_json::Value =
+        serde_json::from_str(&geyser_line(key, 12, version, data))?;
+      line["txn_signature"] = json!(signature);
+      Ok::<_, anyhow::Error>(line.to_string())
+    };
+    let lines = [
+      geyser_line(key, 10, 1, &[1]),
+      signed(2, &[2], "earlier")?,
+      signed(3, &[3], "swap")?,
+      signed(4, &[4], "swap")?,
+      signed(5, &[5], "later")?,
+    ]
+    .join("\n");
+    let archive =
+      AccountArchive::read(lines.as_bytes(), ArchiveFormat::Geyser)?;
+    let position = archive.transactions.get("swap").copied();
+    assert_eq!(position, Some((12, 3)));
+    let pre_state = position
+      .and_then(|position| archive.latest_write(&key, ..position))
+      .map(|a| a.data.clone());
+    assert_eq!(pre_state, Some(vec![2]));
+    assert!(archive
+      .replay_case(
+        "missing".to_string(),
+        Pubkey::default(),
+        Pubkey::default(),
+        0,
+        0
+      )
+      .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn decodes_bigtable_row() -> Result<()> {
+    let key = Pubkey::new_unique();
+    let line = json!({
+      "key": format!("{key}/{:016x}", 300),
+      "lamports": 5,
+      "owner": Pubkey::default().to_string(),
+      "data": [BASE64_STANDARD.encode([7, 8]), "base64"],
+      "executable": false,
+      "rentEpoch": 0,
+    })
+    .to_string();
+    let write = ArchivedAccount::decode(&line, ArchiveFormat::Bigtable, 4)?;
+    assert_eq!(write.pubkey, key);
+    assert_eq!(write.slot, 300);
+    assert_eq!(write.write_version, 4);
+    assert_eq!(write.account.data, vec![7, 8]);
+    Ok(())
+  }
+}
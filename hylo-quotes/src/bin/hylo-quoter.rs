@@ -0,0 +1,81 @@
+//! HTTP quoting service backed by the WebSocket account cache.
+//!
+//! Configured through the environment:
+//! * `RPC_URL` - HTTP RPC endpoint, defaults to mainnet
+//! * `WS_URL` - WebSocket endpoint, defaults to mainnet
+//! * `HYLO_QUOTER_ADDR` - listen address, defaults to `0.0.0.0:8080`
+//! * `HYLO_FEE_OVERRIDE` - optional JSON [`FeeOverride`] file to quote with
+//!   instead of on-chain fees, for previewing fee changes. Only set it on
+//!   preview deployments: every quote then uses the override.
+//!
+//! Serves `/health` and `/ready` alongside the quoting routes.
+
+use std::sync::Arc;
+use std::{env, fs};
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_lang::prelude::Clock;
+use anyhow::{Context, Result};
+use hylo_core::fee_override::FeeOverride;
+use hylo_quotes::health::{self, HealthService};
+use hylo_quotes::protocol_state::{
+  FeeOverrideProvider, StateProvider, WebsocketStateProvider,
+};
+use hylo_quotes::service::router;
+use tokio::net::TcpListener;
+
+fn env_or(key: &str, default: &str) -> String {
+  env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+/// Fee override read from `HYLO_FEE_OVERRIDE`, or none if unset.
+fn fee_override() -> Result<Option<FeeOverride>> {
+  env::var("HYLO_FEE_OVERRIDE").ok().map_or(Ok(None), |path| {
+    let json = fs::read_to_string(&path)
+      .with_context(|| format!("Failed to read {path}"))?;
+    serde_json::from_str(&json)
+      .map(Some)
+      .with_context(|| format!("Invalid fee override in {path}"))
+  })
+}
+
+/// Serves quotes from `provider` on `addr` until the listener fails.
+async fn serve<P: StateProvider<Clock> + 'static>(
+  provider: Arc<P>,
+  rpc_client: Arc<RpcClient>,
+  addr: &str,
+) -> Result<()> {
+  let health =
+    HealthService::new(rpc_client).with_provider(Arc::clone(&provider) as _);
+  let listener = TcpListener::bind(addr)
+    .await
+    .with_context(|| format!("Failed to bind {addr}"))?;
+  let app = router(provider).merge(health::router(Arc::new(health)));
+  axum::serve(listener, app)
+    .await
+    .context("Quoting service stopped")
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+  let rpc_url = env_or("RPC_URL", "https://api.mainnet-beta.solana.com");
+  let ws_url = env_or("WS_URL", "wss://api.mainnet-beta.solana.com");
+  let addr = env_or("HYLO_QUOTER_ADDR", "0.0.0.0:8080");
+  let rpc_client = Arc::new(RpcClient::new_with_commitment(
+    rpc_url,
+    CommitmentConfig::confirmed(),
+  ));
+  let fee_override = fee_override()?;
+  let provider = WebsocketStateProvider::connect(&rpc_client, &ws_url).await?;
+  match fee_override {
+    Some(fee_override) => {
+      eprintln!(
+        "Quoting with fee override, not on-chain fees: {fee_override:?}"
+      );
+      let provider = FeeOverrideProvider::new(provider, fee_override);
+      serve(Arc::new(provider), rpc_client, &addr).await
+    }
+    None => serve(Arc::new(provider), rpc_client, &addr).await,
+  }
+}
@@ -0,0 +1,114 @@
+//! Stability pool deposit limits for sHYUSD quotes.
+//!
+//! The stability pool program does not cap deposits itself, but integrators
+//! may bound its capitalization, the USD value of hyUSD and xSOL it holds.
+//! A [`DepositCap`] sets that bound on protocol state, and HYUSD -> SHYUSD
+//! quotes either fail with [`DepositCapExceeded`] or shrink to the remaining
+//! capacity, per its [`DepositCapMode`].
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use anyhow::Result;
+use fix::prelude::*;
+
+/// Deposit larger than the stablecoin the pool can still take under its cap.
+///
+/// Returned as the error of refused deposit quotes; recover it with
+/// `anyhow::Error::downcast_ref::<DepositCapExceeded>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositCapExceeded {
+  /// Stablecoin the quote asked to deposit
+  pub requested: UFix64<N6>,
+
+  /// Stablecoin that fits under the cap
+  pub remaining: UFix64<N6>,
+}
+
+impl Display for DepositCapExceeded {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Deposit of {} hyUSD base units exceeds remaining pool capacity of {}",
+      self.requested.bits, self.remaining.bits
+    )
+  }
+}
+
+impl Error for DepositCapExceeded {}
+
+/// How deposit quotes treat an amount above the remaining capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepositCapMode {
+  /// Refuse the quote with [`DepositCapExceeded`].
+  #[default]
+  Flag,
+
+  /// Quote a deposit of the remaining capacity instead.
+  Clamp,
+}
+
+/// Limit on stability pool capitalization applied to deposit quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositCap {
+  /// Highest pool capitalization in USD
+  pub limit: UFix64<N6>,
+
+  /// Treatment of deposits above the remaining capacity
+  pub mode: DepositCapMode,
+}
+
+impl DepositCap {
+  #[must_use]
+  pub fn new(limit: UFix64<N6>, mode: DepositCapMode) -> DepositCap {
+    DepositCap { limit, mode }
+  }
+
+  /// Amount of a `requested` deposit to quote given the `remaining`
+  /// stablecoin capacity.
+  ///
+  /// # Errors
+  /// * [`DepositCapExceeded`] if the deposit does not fit and the mode is
+  ///   [`DepositCapMode::Flag`], or nothing fits at all
+  pub fn apply(
+    self,
+    requested: UFix64<N6>,
+    remaining: UFix64<N6>,
+  ) -> Result<UFix64<N6>> {
+    let exceeded = DepositCapExceeded {
+      requested,
+      remaining,
+    };
+    match self.mode {
+      _ if requested <= remaining => Ok(requested),
+      DepositCapMode::Clamp if remaining > UFix64::zero() => Ok(remaining),
+      DepositCapMode::Flag | DepositCapMode::Clamp => Err(exceeded.into()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn flag_refuses_and_clamp_shrinks() {
+    let requested = UFix64::new(5_000_000);
+    let remaining = UFix64::new(2_000_000);
+    let flag = DepositCap::new(UFix64::zero(), DepositCapMode::Flag);
+    assert_eq!(
+      flag
+        .apply(requested, remaining)
+        .err()
+        .and_then(|e| e.downcast::<DepositCapExceeded>().ok()),
+      Some(DepositCapExceeded {
+        requested,
+        remaining
+      })
+    );
+    assert_eq!(flag.apply(remaining, remaining).ok(), Some(remaining));
+    let clamp = DepositCap::new(UFix64::zero(), DepositCapMode::Clamp);
+    assert_eq!(clamp.apply(requested, remaining).ok(), Some(remaining));
+    assert!(clamp.apply(requested, UFix64::zero()).is_err());
+  }
+}
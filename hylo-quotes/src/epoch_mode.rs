@@ -0,0 +1,161 @@
+//! Quoting across Solana epoch boundaries.
+//!
+//! When an epoch rolls over, the total SOL cache and LST header prices stay
+//! on the previous epoch until the `update_lst_prices` crank runs, and the
+//! program rejects every operation in between. Protocol state detects that
+//! window as an [`EpochTransition`], and [`EpochMode`] selects whether quotes
+//! fail with it or proceed on LST prices refreshed from stake pools.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+
+/// Window after an epoch rollover in which cached protocol prices lag.
+///
+/// Returned as the error of quotes refused during the window; recover it with
+/// `anyhow::Error::downcast_ref::<EpochTransition>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochTransition {
+  /// Current Solana epoch
+  pub epoch: u64,
+
+  /// Epoch of the last total SOL cache update
+  pub cache_epoch: u64,
+
+  /// LSTs whose header price is from an earlier epoch
+  pub stale_lsts: Vec<Pubkey>,
+}
+
+impl EpochTransition {
+  /// Detects a transition from the cache epoch and each LST's header price
+  /// epoch, or `None` if everything is current.
+  #[must_use]
+  pub fn detect(
+    epoch: u64,
+    cache_epoch: u64,
+    lst_epochs: impl IntoIterator<Item = (Pubkey, u64)>,
+  ) -> Option<EpochTransition> {
+    let stale_lsts: Vec<Pubkey> = lst_epochs
+      .into_iter()
+      .filter(|(_, lst_epoch)| *lst_epoch < epoch)
+      .map(|(mint, _)| mint)
+      .collect();
+    (cache_epoch < epoch || !stale_lsts.is_empty()).then_some(EpochTransition {
+      epoch,
+      cache_epoch,
+      stale_lsts,
+    })
+  }
+}
+
+impl Display for EpochTransition {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Epoch {} transition: total SOL cache at epoch {}, {} stale LST prices",
+      self.epoch,
+      self.cache_epoch,
+      self.stale_lsts.len()
+    )
+  }
+}
+
+impl Error for EpochTransition {}
+
+/// How quoting treats an [`EpochTransition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EpochMode {
+  /// Refuse quotes with the transition as a typed error.
+  #[default]
+  Status,
+
+  /// Quote on LST prices refreshed from stake pools, tolerating a lagging
+  /// total SOL cache. Quotes are indicative until the crank runs.
+  Refresh,
+}
+
+impl EpochMode {
+  /// Checks a detected `transition` against the mode, given the LSTs whose
+  /// prices were refreshed from stake pools.
+  ///
+  /// # Errors
+  /// * [`EpochTransition`] if the mode is [`EpochMode::Status`], or any stale
+  ///   LST was not refreshed
+  pub fn check(
+    self,
+    transition: Option<&EpochTransition>,
+    refreshed: &[Pubkey],
+  ) -> Result<()> {
+    match (self, transition) {
+      (_, None) => Ok(()),
+      (EpochMode::Status, Some(transition)) => Err(transition.clone().into()),
+      (EpochMode::Refresh, Some(transition)) => {
+        let stale_lsts: Vec<Pubkey> = transition
+          .stale_lsts
+          .iter()
+          .filter(|mint| !refreshed.contains(mint))
+          .copied()
+          .collect();
+        if stale_lsts.is_empty() {
+          Ok(())
+        } else {
+          Err(
+            EpochTransition {
+              stale_lsts,
+              ..transition.clone()
+            }
+            .into(),
+          )
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
+
+  use super::*;
+
+  #[test]
+  fn detects_lagging_prices() {
+    let current = [(JITOSOL::MINT, 800), (HYLOSOL::MINT, 800)];
+    assert_eq!(EpochTransition::detect(800, 800, current), None);
+    let transition =
+      EpochTransition::detect(801, 800, [(JITOSOL::MINT, 801), current[1]]);
+    assert_eq!(
+      transition,
+      Some(EpochTransition {
+        epoch: 801,
+        cache_epoch: 800,
+        stale_lsts: vec![HYLOSOL::MINT],
+      })
+    );
+  }
+
+  #[test]
+  fn refresh_requires_every_stale_lst() {
+    let transition = EpochTransition {
+      epoch: 801,
+      cache_epoch: 800,
+      stale_lsts: vec![JITOSOL::MINT, HYLOSOL::MINT],
+    };
+    let status = EpochMode::Status.check(Some(&transition), &[]);
+    assert_eq!(
+      status
+        .err()
+        .and_then(|e| e.downcast::<EpochTransition>().ok()),
+      Some(transition.clone())
+    );
+    assert!(EpochMode::Refresh
+      .check(Some(&transition), &[JITOSOL::MINT])
+      .is_err());
+    assert!(EpochMode::Refresh
+      .check(Some(&transition), &[JITOSOL::MINT, HYLOSOL::MINT])
+      .is_ok());
+    assert!(EpochMode::Status.check(None, &[]).is_ok());
+  }
+}
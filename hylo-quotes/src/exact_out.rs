@@ -0,0 +1,193 @@
+//! Exact output quotes by bisection over the forward quote.
+//!
+//! Inverting a quote analytically needs the fee rate and NAV to be fixed
+//! over the trade. Trades large enough to push the collateral ratio into
+//! another stability mode switch the fee schedule part way, and in `Depeg`
+//! the stablecoin NAV moves with the ratio, so no closed form exists for
+//! them. [`ProtocolState::exact_out`] instead searches the forward quote for
+//! the smallest input that yields at least the requested output.
+//!
+//! The search brackets the answer by doubling, then bisects the bracket,
+//! keeping an input known to fall short below and one known to suffice or
+//! exceed the mintable maximum above. Each phase takes at most [`u64::BITS`]
+//! quotes, and the returned input is always one whose forward quote was
+//! computed and met the target, so rounding in the forward quote cannot make it
+//! fall short. A mode change can make output fall as input grows; the result is
+//! then the smallest sufficient input of its bracket, and one base unit less
+//! falls short.
+
+use std::iter;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Result};
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::mintable_clamp::is_over_max_mintable;
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::OperationOutputValue;
+
+/// Smallest input found for a requested output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactOutQuote {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+
+  /// Requested output in base units
+  pub amount_out: u64,
+
+  /// Forward quote of the solved input, with `out_amount` at least
+  /// `amount_out`
+  pub output: OperationOutputValue,
+
+  /// Forward quotes computed by the search
+  pub quotes: u32,
+}
+
+/// Smallest input whose output from `quote` meets `amount_out`, with that
+/// output and the number of quotes taken.
+///
+/// Inputs over the mintable maximum bound the search from above, since the
+/// maximum only rejects inputs larger than ones it accepts. Any other quote
+/// failure is returned as is.
+fn solve<F>(amount_out: u64, quote: F) -> Result<(u64, u64, u32)>
+where
+  F: Fn(u64) -> Result<u64>,
+{
+  ensure!(amount_out > 0, "Requested output must be positive");
+  let capped = |amount_in| match quote(amount_in) {
+    Ok(out) => Ok(Some(out)),
+    Err(e) if is_over_max_mintable(&e) => Ok(None),
+    Err(e) => Err(e),
+  };
+  let is_upper = |out: Option<u64>| out.is_none_or(|out| out >= amount_out);
+  let (high, high_out, quotes) =
+    iter::successors(Some(amount_out), |probe| probe.checked_mul(2))
+      .zip(1u32..)
+      .map(|(probe, quotes)| {
+        Ok::<_, anyhow::Error>((probe, capped(probe)?, quotes))
+      })
+      .find(|step| !matches!(step, Ok((_, out, _)) if !is_upper(*out)))
+      .ok_or(anyhow!("No input yields {amount_out}"))??;
+  let low = if quotes == 1 { 0 } else { high / 2 };
+  let (_, high, high_out, quotes) = (0..u64::BITS).try_fold(
+    (low, high, high_out, quotes),
+    |(low, high, high_out, quotes), _| {
+      if high - low <= 1 {
+        Ok::<_, anyhow::Error>((low, high, high_out, quotes))
+      } else {
+        let mid = low + (high - low) / 2;
+        let out = capped(mid)?;
+        if is_upper(out) {
+          Ok((low, mid, out, quotes + 1))
+        } else {
+          Ok((mid, high, high_out, quotes + 1))
+        }
+      }
+    },
+  )?;
+  high_out
+    .map(|out| (high, out, quotes))
+    .ok_or(anyhow!("No input yields {amount_out}"))
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Smallest input of `input_mint` that quotes to at least `amount_out`
+  /// base units of `output_mint`.
+  ///
+  /// # Errors
+  /// * Unsupported pair or zero `amount_out`
+  /// * No input under the mintable maximum quotes to `amount_out`
+  /// * A quote tried by the search fails for another reason
+  pub fn exact_out(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_out: u64,
+  ) -> Result<ExactOutQuote> {
+    let quote = |amount_in| {
+      self
+        .runtime_output(input_mint, output_mint, amount_in)
+        .map(|output| output.out_amount.bits)
+    };
+    let (amount_in, _, quotes) = solve(amount_out, quote)?;
+    let output = self.runtime_output(input_mint, output_mint, amount_in)?;
+    Ok(ExactOutQuote {
+      input_mint,
+      output_mint,
+      amount_out,
+      output,
+      quotes,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::error::CoreError::RequestedStablecoinOverMaxMintable;
+
+  use super::*;
+
+  fn over_max_mintable() -> anyhow::Error {
+    anchor_lang::error::Error::from(RequestedStablecoinOverMaxMintable).into()
+  }
+
+  /// 0.5% fee up to 1_000_000 input, 3% past it, floored.
+  fn mode_switching(amount_in: u64) -> Result<u64> {
+    let fee_bps = if amount_in > 1_000_000 { 300 } else { 50 };
+    Ok(amount_in * (10_000 - fee_bps) / 10_000)
+  }
+
+  #[test]
+  fn smallest_sufficient_input() -> Result<()> {
+    let (amount_in, out, quotes) = solve(500_000, mode_switching)?;
+    assert!(out >= 500_000);
+    assert!(mode_switching(amount_in - 1)? < 500_000);
+    assert!(quotes <= 2 * u64::BITS);
+    Ok(())
+  }
+
+  #[test]
+  fn crosses_fee_switch() -> Result<()> {
+    let (amount_in, out, _) = solve(995_100, mode_switching)?;
+    assert!(amount_in > 1_000_000);
+    assert!(out >= 995_100);
+    assert!(mode_switching(amount_in - 1)? < 995_100);
+    Ok(())
+  }
+
+  #[test]
+  fn unreachable_output() {
+    let capped = |amount_in: u64| {
+      if amount_in <= 1_000 {
+        Ok(amount_in)
+      } else {
+        Err(over_max_mintable())
+      }
+    };
+    assert!(solve(2_000, capped).is_err());
+    assert!(solve(0, capped).is_err());
+  }
+
+  #[test]
+  fn bisects_below_cap() -> Result<()> {
+    let capped = |amount_in: u64| {
+      if amount_in <= 2_000 {
+        Ok(amount_in * 2 / 3)
+      } else {
+        Err(over_max_mintable())
+      }
+    };
+    assert_eq!(solve(1_200, capped)?.0, 1_800);
+    Ok(())
+  }
+
+  #[test]
+  fn propagates_other_failures() {
+    let failing = |amount_in: u64| {
+      ensure!(amount_in <= 2_000, "RPC unavailable");
+      Ok(amount_in / 2)
+    };
+    let err = solve(1_500, failing).err().map(|e| e.to_string());
+    assert_eq!(err.as_deref(), Some("RPC unavailable"));
+  }
+}
@@ -0,0 +1,147 @@
+//! Versioned golden quote vectors.
+//!
+//! A [`GoldenSet`] pins a protocol account snapshot to the expected output of
+//! each supported pair. Regenerate with [`GoldenSet::generate`] from a
+//! known-good revision, and run [`GoldenSet::verify`] after refactors to
+//! confirm quote parity.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use hylo_core::solana_clock::SolanaClock;
+use serde::{Deserialize, Serialize};
+
+use crate::protocol_state::ProtocolState;
+
+/// Current golden vector format version.
+pub const GOLDEN_VERSION: u32 = 1;
+
+/// Expected output for one pair and input amount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenVector {
+  #[serde(with = "base58")]
+  pub input_mint: Pubkey,
+
+  #[serde(with = "base58")]
+  pub output_mint: Pubkey,
+
+  /// Input amount in base units
+  pub amount_in: u64,
+
+  /// Expected output amount in base units
+  pub out_amount: u64,
+
+  /// Expected fee in base units of the fee mint, when recorded
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub fee_amount: Option<u64>,
+}
+
+impl GoldenVector {
+  /// Recomputes this vector against `state`.
+  ///
+  /// # Errors
+  /// * Unsupported pair or operation math
+  pub fn compute<C: SolanaClock>(
+    &self,
+    state: &ProtocolState<C>,
+  ) -> Result<GoldenVector> {
+    let op = state.runtime_output(
+      self.input_mint,
+      self.output_mint,
+      self.amount_in,
+    )?;
+    Ok(GoldenVector {
+      out_amount: op.out_amount.bits,
+      fee_amount: self.fee_amount.map(|_| op.fee_amount.bits),
+      ..self.clone()
+    })
+  }
+}
+
+/// Snapshot file name paired with its expected vectors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenSet {
+  pub version: u32,
+
+  /// `ProtocolAccounts` JSON file the vectors were computed from
+  pub snapshot: String,
+
+  pub vectors: Vec<GoldenVector>,
+}
+
+impl GoldenSet {
+  /// Computes vectors with fees for each `(input, output, amount)` request.
+  ///
+  /// # Errors
+  /// * Unsupported pair or operation math
+  pub fn generate<C: SolanaClock>(
+    snapshot: impl Into<String>,
+    state: &ProtocolState<C>,
+    requests: &[(Pubkey, Pubkey, u64)],
+  ) -> Result<GoldenSet> {
+    let vectors = requests
+      .iter()
+      .map(|&(input_mint, output_mint, amount_in)| {
+        let op = state.runtime_output(input_mint, output_mint, amount_in)?;
+        Ok(GoldenVector {
+          input_mint,
+          output_mint,
+          amount_in,
+          out_amount: op.out_amount.bits,
+          fee_amount: Some(op.fee_amount.bits),
+        })
+      })
+      .collect::<Result<_>>()?;
+    Ok(GoldenSet {
+      version: GOLDEN_VERSION,
+      snapshot: snapshot.into(),
+      vectors,
+    })
+  }
+
+  /// Returns `(expected, actual)` for every vector that no longer matches.
+  ///
+  /// # Errors
+  /// * Unsupported format version
+  /// * Unsupported pair or operation math
+  pub fn verify<C: SolanaClock>(
+    &self,
+    state: &ProtocolState<C>,
+  ) -> Result<Vec<(GoldenVector, GoldenVector)>> {
+    if self.version == GOLDEN_VERSION {
+      self
+        .vectors
+        .iter()
+        .map(|vector| Ok((vector.clone(), vector.compute(state)?)))
+        .filter(
+          |pair| !matches!(pair, Ok((expected, actual)) if expected == actual),
+        )
+        .collect()
+    } else {
+      Err(anyhow!(
+        "Unsupported golden vector version {}",
+        self.version
+      ))
+    }
+  }
+}
+
+mod base58 {
+  use std::str::FromStr;
+
+  use anchor_lang::prelude::Pubkey;
+  use serde::{de, Deserialize, Deserializer, Serializer};
+
+  pub fn serialize<S: Serializer>(
+    key: &Pubkey,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(key)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(de::Error::custom)
+  }
+}
@@ -0,0 +1,238 @@
+//! Health endpoints for quoter and keeper services.
+//!
+//! Lets orchestration systems restart a service that has stopped making
+//! progress. [`HealthService`] checks RPC connectivity, and optionally the
+//! age of a state provider's account cache and SOL/USD price, and the last
+//! successful round of each task in a [`TaskHealth`].
+//!
+//! * `GET /health` - every check, with status 503 if any fails
+//! * `GET /ready` - RPC connectivity and state loading only, with status 503
+//!   until both pass
+//!
+//! Both respond with a [`HealthReport`].
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_lang::prelude::Clock;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use hylo_clients::keeper::health::{TaskHealth, TaskStatus};
+use serde::Serialize;
+
+use crate::protocol_state::StateProvider;
+
+/// Default longest age of the account cache before the service is degraded.
+pub const DEFAULT_MAX_CACHE_AGE: Duration = Duration::from_secs(60);
+
+/// Outcome of a single health check.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+  /// `rpc`, `state`, `account_cache`, `oracle`, or `task:<name>`
+  pub name: String,
+  pub healthy: bool,
+  pub detail: String,
+}
+
+impl HealthCheck {
+  fn new(name: impl Into<String>, healthy: bool, detail: String) -> Self {
+    HealthCheck {
+      name: name.into(),
+      healthy,
+      detail,
+    }
+  }
+}
+
+/// Health of the service at `checked_at`, rendered as JSON with status 200
+/// when healthy and 503 otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+  pub healthy: bool,
+  pub checked_at: i64,
+  pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+  fn new(checked_at: i64, checks: Vec<HealthCheck>) -> Self {
+    HealthReport {
+      healthy: checks.iter().all(|check| check.healthy),
+      checked_at,
+      checks,
+    }
+  }
+}
+
+impl IntoResponse for HealthReport {
+  fn into_response(self) -> Response {
+    let status = if self.healthy {
+      StatusCode::OK
+    } else {
+      StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(self)).into_response()
+  }
+}
+
+/// Health checks for one service.
+pub struct HealthService {
+  rpc_client: Arc<RpcClient>,
+  provider: Option<Arc<dyn StateProvider<Clock>>>,
+  tasks: Option<TaskHealth>,
+  max_cache_age: Duration,
+}
+
+impl HealthService {
+  /// Service checking only connectivity to `rpc_client`.
+  #[must_use]
+  pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+    HealthService {
+      rpc_client,
+      provider: None,
+      tasks: None,
+      max_cache_age: DEFAULT_MAX_CACHE_AGE,
+    }
+  }
+
+  /// Checks that `provider` serves state fetched within the cache age, with
+  /// a SOL/USD price the program still accepts.
+  #[must_use]
+  pub fn with_provider(self, provider: Arc<dyn StateProvider<Clock>>) -> Self {
+    HealthService {
+      provider: Some(provider),
+      ..self
+    }
+  }
+
+  /// Checks that every task registered in `tasks` succeeded within its age.
+  #[must_use]
+  pub fn with_tasks(self, tasks: TaskHealth) -> Self {
+    HealthService {
+      tasks: Some(tasks),
+      ..self
+    }
+  }
+
+  /// Overrides [`DEFAULT_MAX_CACHE_AGE`].
+  #[must_use]
+  pub fn with_max_cache_age(self, max_cache_age: Duration) -> Self {
+    HealthService {
+      max_cache_age,
+      ..self
+    }
+  }
+
+  /// Runs every check.
+  pub async fn report(&self) -> HealthReport {
+    let now = unix_now();
+    let rpc = self.check_rpc().await;
+    let state = self.check_state(now).await;
+    let tasks = self
+      .tasks
+      .iter()
+      .flat_map(TaskHealth::statuses)
+      .map(|status| task_check(&status));
+    HealthReport::new(
+      now,
+      [rpc].into_iter().chain(state).chain(tasks).collect(),
+    )
+  }
+
+  /// Checks RPC connectivity and that the provider, if any, loads state.
+  pub async fn readiness(&self) -> HealthReport {
+    let now = unix_now();
+    let rpc = self.check_rpc().await;
+    let state = match &self.provider {
+      Some(provider) => Some(match provider.fetch_state().await {
+        Ok(_) => HealthCheck::new("state", true, "loaded".to_string()),
+        Err(e) => HealthCheck::new("state", false, format!("{e:#}")),
+      }),
+      None => None,
+    };
+    HealthReport::new(now, [rpc].into_iter().chain(state).collect())
+  }
+
+  async fn check_rpc(&self) -> HealthCheck {
+    match self.rpc_client.get_slot().await {
+      Ok(slot) => HealthCheck::new("rpc", true, format!("slot {slot}")),
+      Err(e) => HealthCheck::new("rpc", false, e.to_string()),
+    }
+  }
+
+  /// Account cache and oracle age checks, or a failed `state` check if the
+  /// provider cannot serve state.
+  async fn check_state(&self, now: i64) -> Vec<HealthCheck> {
+    match &self.provider {
+      None => vec![],
+      Some(provider) => match provider.fetch_state().await {
+        Err(e) => vec![HealthCheck::new("state", false, format!("{e:#}"))],
+        Ok(state) => {
+          let cache_age = now.saturating_sub(state.fetched_at);
+          let max_cache_age =
+            i64::try_from(self.max_cache_age.as_secs()).unwrap_or(i64::MAX);
+          let oracle_age = now.saturating_sub(state.oracle_publish_time);
+          let max_oracle_age =
+            i64::try_from(state.oracle_interval_secs).unwrap_or(i64::MAX);
+          vec![
+            HealthCheck::new(
+              "account_cache",
+              cache_age <= max_cache_age,
+              format!("{cache_age}s old, allowed {max_cache_age}s"),
+            ),
+            HealthCheck::new(
+              "oracle",
+              oracle_age <= max_oracle_age,
+              format!("{oracle_age}s old, allowed {max_oracle_age}s"),
+            ),
+          ]
+        }
+      },
+    }
+  }
+}
+
+fn task_check(status: &TaskStatus) -> HealthCheck {
+  let since = if status.ever_succeeded {
+    format!("last success {}s ago", status.since_success.as_secs())
+  } else {
+    format!("no success in {}s", status.since_success.as_secs())
+  };
+  let detail = match &status.last_error {
+    Some(error) if status.consecutive_failures > 0 => format!(
+      "{since}, allowed {}s, {} failures since: {error}",
+      status.max_age.as_secs(),
+      status.consecutive_failures
+    ),
+    _ => format!("{since}, allowed {}s", status.max_age.as_secs()),
+  };
+  HealthCheck::new(format!("task:{}", status.task), status.is_healthy(), detail)
+}
+
+fn unix_now() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |since| {
+      i64::try_from(since.as_secs()).unwrap_or(i64::MAX)
+    })
+}
+
+/// Builds the health router, to be merged into a service's own router.
+#[must_use]
+pub fn router(service: Arc<HealthService>) -> Router {
+  Router::new()
+    .route("/health", get(health))
+    .route("/ready", get(ready))
+    .with_state(service)
+}
+
+async fn health(State(service): State<Arc<HealthService>>) -> HealthReport {
+  service.report().await
+}
+
+async fn ready(State(service): State<Arc<HealthService>>) -> HealthReport {
+  service.readiness().await
+}
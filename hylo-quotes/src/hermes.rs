@@ -0,0 +1,292 @@
+//! Pre-trade SOL/USD refresh from Pyth Hermes.
+//!
+//! Quotes computed against the last posted SOL/USD price drift from execution
+//! when the trade bundles a newer Pyth update. [`HermesClient`] pulls the
+//! latest signed update, [`update_sol_usd_feed_instruction`] writes it into
+//! the push oracle feed Hylo reads, and [`with_fresh_price`] applies the same
+//! price to fetched [`ProtocolAccounts`] so that quotes see what the chain
+//! will.
+//!
+//! NB: Hylo only reads its configured SOL/USD feed, the push oracle account
+//! on shard [`SOL_USD_SHARD`]. Updates posted to any other account are never
+//! seen by the program. The VAA must be posted and verified through the
+//! Wormhole receiver beforehand; atomic posting with trimmed signatures is
+//! partial and is rejected.
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::pubkey;
+use anchor_lang::{
+  system_program, AccountDeserialize, AccountSerialize, AnchorSerialize,
+};
+use anyhow::{anyhow, Context, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use hylo_core::pyth::SOL_USD;
+use pyth_solana_receiver_sdk::pda::{get_config_address, get_treasury_address};
+use pyth_solana_receiver_sdk::price_update::{
+  FeedId, PriceFeedMessage, PriceUpdateV2, VerificationLevel,
+};
+use pyth_solana_receiver_sdk::PostUpdateParams;
+use pythnet_sdk::messages::Message;
+use pythnet_sdk::wire::from_slice;
+use pythnet_sdk::wire::v1::{AccumulatorUpdateData, MerklePriceUpdate, Proof};
+use serde::Deserialize;
+
+use crate::protocol_state::ProtocolAccounts;
+
+/// Public Hermes endpoint operated by the Pyth Data Association.
+pub const HERMES_URL: &str = "https://hermes.pyth.network";
+
+/// Pyth push oracle, owner of the sponsored price feed accounts.
+pub const PUSH_ORACLE_PROGRAM: Pubkey =
+  pubkey!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+
+/// Push oracle shard holding the SOL/USD feed Hylo reads.
+pub const SOL_USD_SHARD: u16 = 0;
+
+/// Anchor discriminator of the push oracle's `update_price_feed` instruction.
+const UPDATE_PRICE_FEED_DISCRIMINATOR: [u8; 8] =
+  [28, 9, 93, 150, 86, 153, 188, 115];
+
+#[derive(Deserialize)]
+struct LatestUpdates {
+  binary: BinaryUpdate,
+}
+
+#[derive(Deserialize)]
+struct BinaryUpdate {
+  data: Vec<String>,
+}
+
+/// Signed SOL/USD update from Hermes.
+#[derive(Clone)]
+pub struct HermesUpdate {
+  /// VAA signing the Merkle root of the update
+  pub vaa: Vec<u8>,
+
+  /// Merkle proof for the SOL/USD message
+  pub merkle_price_update: MerklePriceUpdate,
+
+  /// Decoded SOL/USD price message
+  pub price_message: PriceFeedMessage,
+}
+
+impl HermesUpdate {
+  /// Decodes a Pythnet accumulator update carrying the SOL/USD feed.
+  ///
+  /// # Errors
+  /// * Bytes are not an accumulator update
+  /// * Update does not contain a SOL/USD price message
+  pub fn from_accumulator_bytes(bytes: &[u8]) -> Result<HermesUpdate> {
+    let update = AccumulatorUpdateData::try_from_slice(bytes)
+      .map_err(|e| anyhow!("Invalid accumulator update: {e:?}"))?;
+    let Proof::WormholeMerkle { vaa, updates } = update.proof;
+    updates
+      .into_iter()
+      .find_map(|merkle_price_update| {
+        match from_slice::<byteorder::BE, Message>(
+          merkle_price_update.message.as_ref(),
+        ) {
+          Ok(Message::PriceFeedMessage(price_message))
+            if price_message.feed_id == SOL_USD =>
+          {
+            Some((merkle_price_update, price_message))
+          }
+          _ => None,
+        }
+      })
+      .map(|(merkle_price_update, price_message)| HermesUpdate {
+        vaa: vaa.into(),
+        merkle_price_update,
+        price_message,
+      })
+      .ok_or(anyhow!("Accumulator update has no SOL/USD price"))
+  }
+}
+
+/// HTTP client for the Hermes price service.
+pub struct HermesClient {
+  http: reqwest::Client,
+  endpoint: String,
+}
+
+impl HermesClient {
+  #[must_use]
+  pub fn new(endpoint: &str) -> HermesClient {
+    HermesClient {
+      http: reqwest::Client::new(),
+      endpoint: endpoint.trim_end_matches('/').to_string(),
+    }
+  }
+
+  /// Fetches the latest signed SOL/USD update.
+  ///
+  /// # Errors
+  /// * Request fails or returns a non-success status
+  /// * Response cannot be decoded into a SOL/USD update
+  pub async fn latest_sol_usd(&self) -> Result<HermesUpdate> {
+    let feed_id = hex::encode(SOL_USD);
+    let url = format!(
+      "{}/v2/updates/price/latest?ids[]={feed_id}&encoding=base64",
+      self.endpoint
+    );
+    let response: LatestUpdates = self
+      .http
+      .get(&url)
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await
+      .context("Invalid Hermes response")?;
+    let data = response
+      .binary
+      .data
+      .first()
+      .ok_or(anyhow!("Hermes returned no update data"))?;
+    let bytes = BASE64_STANDARD.decode(data)?;
+    HermesUpdate::from_accumulator_bytes(&bytes)
+  }
+}
+
+impl Default for HermesClient {
+  fn default() -> Self {
+    HermesClient::new(HERMES_URL)
+  }
+}
+
+/// Push oracle price feed account for `feed_id` on `shard_id`.
+#[must_use]
+pub fn push_feed_address(shard_id: u16, feed_id: &FeedId) -> Pubkey {
+  Pubkey::find_program_address(
+    &[&shard_id.to_le_bytes(), feed_id],
+    &PUSH_ORACLE_PROGRAM,
+  )
+  .0
+}
+
+/// Builds the push oracle `update_price_feed` instruction writing `update`
+/// into the SOL/USD feed Hylo reads, from a verified `encoded_vaa` account.
+///
+/// # Errors
+/// * Instruction arguments fail to serialize
+pub fn update_sol_usd_feed_instruction(
+  payer: Pubkey,
+  encoded_vaa: Pubkey,
+  update: &HermesUpdate,
+  treasury_id: u8,
+) -> Result<Instruction> {
+  let params = PostUpdateParams {
+    merkle_price_update: update.merkle_price_update.clone(),
+    treasury_id,
+  };
+  let data = [
+    UPDATE_PRICE_FEED_DISCRIMINATOR.to_vec(),
+    params.try_to_vec()?,
+    SOL_USD_SHARD.try_to_vec()?,
+    SOL_USD.try_to_vec()?,
+  ]
+  .concat();
+  Ok(Instruction {
+    program_id: PUSH_ORACLE_PROGRAM,
+    accounts: vec![
+      AccountMeta::new(payer, true),
+      AccountMeta::new_readonly(pyth_solana_receiver_sdk::ID, false),
+      AccountMeta::new_readonly(encoded_vaa, false),
+      AccountMeta::new_readonly(get_config_address(), false),
+      AccountMeta::new(get_treasury_address(treasury_id), false),
+      AccountMeta::new(push_feed_address(SOL_USD_SHARD, &SOL_USD), false),
+      AccountMeta::new_readonly(system_program::ID, false),
+    ],
+    data,
+  })
+}
+
+/// Replaces the SOL/USD oracle in `accounts` with `update` as the push
+/// oracle would write it at the current slot. Like the push oracle, keeps the
+/// posted price if `update` is not newer.
+///
+/// # Errors
+/// * Oracle or clock account fails to deserialize
+/// * Oracle account does not carry the SOL/USD feed
+/// * Oracle account fails to serialize
+pub fn with_fresh_price(
+  accounts: &ProtocolAccounts,
+  update: &HermesUpdate,
+) -> Result<ProtocolAccounts> {
+  let oracle =
+    PriceUpdateV2::try_deserialize(&mut accounts.sol_usd_pyth.data.as_slice())
+      .map_err(|e| anyhow!("Failed to deserialize Pyth: {e}"))?;
+  if oracle.price_message.feed_id != SOL_USD {
+    Err(anyhow!("Oracle account is not the SOL/USD feed"))
+  } else if update.price_message.publish_time
+    <= oracle.price_message.publish_time
+  {
+    Ok(accounts.clone())
+  } else {
+    let clock: Clock = bincode::deserialize(&accounts.clock.data)
+      .map_err(|e| anyhow!("Failed to deserialize clock: {e}"))?;
+    let fresh = PriceUpdateV2 {
+      write_authority: oracle.write_authority,
+      verification_level: VerificationLevel::Full,
+      price_message: update.price_message,
+      posted_slot: clock.slot,
+    };
+    let mut data = Vec::with_capacity(PriceUpdateV2::LEN);
+    fresh
+      .try_serialize(&mut data)
+      .map_err(|e| anyhow!("Failed to serialize Pyth: {e}"))?;
+    let mut fresh_accounts = accounts.clone();
+    fresh_accounts.sol_usd_pyth.data = data;
+    Ok(fresh_accounts)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::pyth::SOL_USD_PYTH_FEED;
+  use pythnet_sdk::accumulators::merkle::MerklePath;
+
+  use super::*;
+
+  #[test]
+  fn updates_the_feed_hylo_reads() -> Result<()> {
+    assert_eq!(
+      push_feed_address(SOL_USD_SHARD, &SOL_USD),
+      SOL_USD_PYTH_FEED
+    );
+    let update = HermesUpdate {
+      vaa: Vec::new(),
+      merkle_price_update: MerklePriceUpdate {
+        message: Vec::new().into(),
+        proof: MerklePath::new(Vec::new()),
+      },
+      price_message: PriceFeedMessage {
+        feed_id: SOL_USD,
+        price: 15_000_000_000,
+        conf: 10_000_000,
+        exponent: -8,
+        publish_time: 1_700_000_000,
+        prev_publish_time: 1_699_999_999,
+        ema_price: 15_000_000_000,
+        ema_conf: 10_000_000,
+      },
+    };
+    let ix = update_sol_usd_feed_instruction(
+      Pubkey::new_unique(),
+      Pubkey::new_unique(),
+      &update,
+      0,
+    )?;
+    let written = ix
+      .accounts
+      .iter()
+      .filter(|meta| meta.is_writable && !meta.is_signer)
+      .map(|meta| meta.pubkey)
+      .collect::<Vec<_>>();
+    assert_eq!(written, vec![get_treasury_address(0), SOL_USD_PYTH_FEED]);
+    assert!(ix.data.ends_with(&SOL_USD));
+    Ok(())
+  }
+}
@@ -104,21 +104,54 @@ use fix::prelude::{UFix64, UFixValue64};
 use fix::typenum::Integer;
 use hylo_idl::tokens::{HYLOSOL, JITOSOL};
 
+#[cfg(feature = "archive")]
+pub mod archive;
+mod deposit_cap;
+mod epoch_mode;
+pub mod exact_out;
+pub mod golden;
+#[cfg(feature = "quoter")]
+pub mod health;
+#[cfg(feature = "hermes")]
+pub mod hermes;
+pub mod max_trade;
+pub mod mintable_clamp;
+mod mode_policy;
+pub mod plan;
+pub mod pool_schedule;
+mod preconditions;
 pub mod prelude;
+pub mod preview;
 pub mod protocol_state;
 mod protocol_state_strategy;
+mod quote_args;
+mod quote_context;
+mod quote_freshness;
 mod quote_metadata;
 mod quote_strategy;
+pub mod replay;
+pub mod round_trip;
+pub mod route_health;
 mod runtime_quote_strategy;
+#[cfg(feature = "quoter")]
+pub mod service;
 pub mod simulated_operation;
 mod simulation_strategy;
 pub mod token_operation;
 
+pub use deposit_cap::{DepositCap, DepositCapExceeded, DepositCapMode};
+pub use epoch_mode::{EpochMode, EpochTransition};
 pub use hylo_clients::util::LST;
+pub use mode_policy::{
+  is_allowed, mode_descriptor, ModeDescriptor, ModePolicy,
+};
 pub use protocol_state_strategy::ProtocolStateStrategy;
+pub use quote_args::FromQuote;
+pub use quote_context::{QuoteContext, QuoteWithContext};
+pub use quote_freshness::QuoteFreshness;
 pub use quote_metadata::{Operation, QuoteMetadata};
 pub use quote_strategy::QuoteStrategy;
-pub use runtime_quote_strategy::RuntimeQuoteStrategy;
+pub use runtime_quote_strategy::{pair_metadata, RuntimeQuoteStrategy};
 pub use simulated_operation::ComputeUnitInfo;
 pub use simulation_strategy::SimulationStrategy;
 
@@ -145,6 +178,10 @@ pub struct ExecutableQuote<InExp: Integer, OutExp: Integer, FeeExp: Integer> {
   pub fee_mint: Pubkey,
   pub instructions: Vec<Instruction>,
   pub address_lookup_tables: Vec<Pubkey>,
+
+  /// Prices behind the quote, `None` for simulated quotes, which are priced
+  /// by the chain at simulation time
+  pub freshness: Option<QuoteFreshness>,
 }
 
 /// Executable quote with runtime exponent information.
@@ -158,6 +195,10 @@ pub struct ExecutableQuoteValue {
   pub fee_mint: Pubkey,
   pub instructions: Vec<Instruction>,
   pub address_lookup_tables: Vec<Pubkey>,
+
+  /// Prices behind the quote, `None` for simulated quotes, which are priced
+  /// by the chain at simulation time
+  pub freshness: Option<QuoteFreshness>,
 }
 
 impl<InExp: Integer, OutExp: Integer, FeeExp: Integer>
@@ -175,6 +216,7 @@ impl<InExp: Integer, OutExp: Integer, FeeExp: Integer>
       fee_mint: quote.fee_mint,
       instructions: quote.instructions,
       address_lookup_tables: quote.address_lookup_tables,
+      freshness: quote.freshness,
     }
   }
 }
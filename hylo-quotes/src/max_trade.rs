@@ -0,0 +1,185 @@
+//! Largest trade within a price impact limit.
+//!
+//! Hylo fills at NAV, so a trade's price moves only when its size changes
+//! the fees it pays or the NAV it fills at: pushing the collateral ratio into
+//! another stability mode switches the fee schedule, and in `Depeg` the
+//! stablecoin NAV itself moves with the ratio. Impact is the shortfall of a
+//! trade's rate against the rate for one whole input token, in basis points.
+//!
+//! [`ProtocolState::max_trade_size`] searches for the largest input whose
+//! impact stays within a limit, complementing the hard caps of
+//! [`hylo_core::exchange_context::ExchangeContext::max_mintable_stablecoin`]
+//! and `max_swappable_stablecoin`: sizes past a hard cap fail to quote and
+//! count as over the limit. The search assumes impact does not fall as size
+//! grows, which holds for every pair whose fees rise through the modes.
+//!
+//! Pairs without a hard cap would otherwise search up to `u64::MAX`, where
+//! quotes stop meaning anything, so the search never goes past the input
+//! available to trade: the supply of hyUSD, xSOL or sHYUSD, or for an LST
+//! the protocol's total SOL in that LST. The protocol cannot pay out more of
+//! an LST than that, and xSOL mints, which no pool limits, are held to the
+//! same bound.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use hylo_core::lst_sol_price::LstSolPrice;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::{RuntimePair, RUNTIME_PAIRS};
+use crate::LST;
+
+/// Largest input of a pair quoted within an impact limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxTradeSize {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+
+  /// Input amount in base units
+  pub amount_in: u64,
+
+  /// Output for `amount_in`, in base units
+  pub amount_out: u64,
+
+  /// Impact of `amount_in`; one bit is one basis point
+  pub impact: UFix64<N4>,
+}
+
+/// Rate shortfall of `amount_out / amount_in` against
+/// `unit_out / unit_in`, rounded up to a basis point.
+fn impact(
+  unit_in: u64,
+  unit_out: u64,
+  amount_in: u64,
+  amount_out: u64,
+) -> Option<UFix64<N4>> {
+  let expected = u128::from(unit_out).checked_mul(u128::from(amount_in))?;
+  let actual = u128::from(amount_out).checked_mul(u128::from(unit_in))?;
+  let shortfall = expected.saturating_sub(actual).checked_mul(10_000)?;
+  let bps = shortfall.checked_div(expected)?
+    + u128::from(shortfall.checked_rem(expected)? > 0);
+  u64::try_from(bps).ok().map(UFix64::new)
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Impact of trading `amount_in` base units of `pair`.
+  ///
+  /// # Errors
+  /// * Unsupported pair
+  /// * Either quote fails, or one whole input token quotes to nothing
+  pub fn price_impact(
+    &self,
+    pair: &RuntimePair,
+    amount_in: u64,
+  ) -> Result<UFix64<N4>> {
+    let quote = |amount| {
+      self
+        .runtime_output(pair.input_mint, pair.output_mint, amount)
+        .map(|output| output.out_amount.bits)
+    };
+    let unit_out = quote(pair.unit_in)?;
+    let amount_out = quote(amount_in)?;
+    impact(pair.unit_in, unit_out, amount_in, amount_out).ok_or(anyhow!(
+      "No reference rate for {} -> {}",
+      pair.input_mint,
+      pair.output_mint
+    ))
+  }
+
+  /// Most input of `pair` there is to trade, see the module docs.
+  fn available_input(&self, pair: &RuntimePair) -> Result<u64> {
+    match pair.input_mint {
+      HYUSD::MINT => Ok(self.hyusd_mint.supply),
+      XSOL::MINT => Ok(self.xsol_mint.supply),
+      SHYUSD::MINT => Ok(self.shyusd_mint.supply),
+      JITOSOL::MINT => self.total_sol_in::<JITOSOL>(),
+      HYLOSOL::MINT => self.total_sol_in::<HYLOSOL>(),
+      mint => Err(anyhow!("No available input for {mint}")),
+    }
+  }
+
+  /// Protocol's total SOL in base units of `L`.
+  fn total_sol_in<L: LST>(&self) -> Result<u64> {
+    let price: LstSolPrice = self.lst_header::<L>()?.price_sol.into();
+    let price = price.get_epoch_price(self.exchange_context.clock().epoch())?;
+    self
+      .exchange_context
+      .total_sol()
+      .mul_div_floor(UFix64::one(), price)
+      .map(|amount| amount.bits)
+      .ok_or(anyhow!("Failed to convert total SOL to {}", L::MINT))
+  }
+
+  /// Largest input of `pair` whose impact is at most `max_impact`, from one
+  /// whole input token up to the input available. Zero when less than one
+  /// whole token is available.
+  ///
+  /// # Errors
+  /// * Unsupported pair
+  /// * One whole input token does not quote
+  pub fn max_trade_size(
+    &self,
+    pair: &RuntimePair,
+    max_impact: UFix64<N4>,
+  ) -> Result<MaxTradeSize> {
+    let available = self.available_input(pair)?;
+    let (amount_in, amount_out, impact) = if available < pair.unit_in {
+      (0, 0, UFix64::zero())
+    } else {
+      self.search(pair, max_impact, available)?
+    };
+    Ok(MaxTradeSize {
+      input_mint: pair.input_mint,
+      output_mint: pair.output_mint,
+      amount_in,
+      amount_out,
+      impact,
+    })
+  }
+
+  /// Bisects `unit_in..=available` for the largest input within
+  /// `max_impact`, returning it with its output and impact.
+  fn search(
+    &self,
+    pair: &RuntimePair,
+    max_impact: UFix64<N4>,
+    available: u64,
+  ) -> Result<(u64, u64, UFix64<N4>)> {
+    let quote = |amount| {
+      self
+        .runtime_output(pair.input_mint, pair.output_mint, amount)
+        .map(|output| output.out_amount.bits)
+    };
+    let unit_out = quote(pair.unit_in)?;
+    let within = |amount| {
+      quote(amount).ok().and_then(|amount_out| {
+        impact(pair.unit_in, unit_out, amount, amount_out)
+          .filter(|impact| *impact <= max_impact)
+          .map(|impact| (amount_out, impact))
+      })
+    };
+    let start = (pair.unit_in, available, (unit_out, UFix64::zero()));
+    let (amount_in, _, (amount_out, impact)) =
+      (0..u64::BITS).fold(start, |(low, high, best), _| {
+        let mid = low + (high - low).div_ceil(2);
+        if low == high {
+          (low, high, best)
+        } else {
+          within(mid).map_or((low, mid - 1, best), |found| (mid, high, found))
+        }
+      });
+    Ok((amount_in, amount_out, impact))
+  }
+
+  /// [`Self::max_trade_size`] for every supported pair, skipping pairs that
+  /// do not quote at all in the current state.
+  #[must_use]
+  pub fn max_trade_sizes(&self, max_impact: UFix64<N4>) -> Vec<MaxTradeSize> {
+    RUNTIME_PAIRS
+      .iter()
+      .filter_map(|pair| self.max_trade_size(pair, max_impact).ok())
+      .collect()
+  }
+}
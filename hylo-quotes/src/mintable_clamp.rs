@@ -0,0 +1,151 @@
+//! Retrying trades that exceed the mintable stablecoin maximum.
+//!
+//! Minting hyUSD, or swapping xSOL into it, fails with
+//! `RequestedStablecoinOverMaxMintable` once the output would push the
+//! collateral ratio below the lowest stability threshold. Rather than surface
+//! the failure, [`ProtocolState::clamp_to_mintable`] finds the largest input
+//! the maximum still admits and optionally quotes it, reporting what was left
+//! unfilled so callers can queue the remainder.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use hylo_core::error::CoreError::RequestedStablecoinOverMaxMintable;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::OperationOutputValue;
+
+/// Whether `error` is the program's `RequestedStablecoinOverMaxMintable`,
+/// anywhere in its chain.
+#[must_use]
+pub fn is_over_max_mintable(error: &anyhow::Error) -> bool {
+  let over =
+    anchor_lang::error::Error::from(RequestedStablecoinOverMaxMintable);
+  error.chain().any(|cause| {
+    cause
+      .downcast_ref::<anchor_lang::error::Error>()
+      .is_some_and(|e| *e == over)
+  })
+}
+
+/// Trade fitted under the mintable maximum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintableClamp {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+
+  /// Input originally requested, in base units
+  pub requested_in: u64,
+
+  /// Largest input up to `requested_in` the maximum admits
+  pub max_amount_in: u64,
+
+  /// Input left unfilled, `requested_in - max_amount_in`
+  pub shortfall: u64,
+
+  /// Quote at `max_amount_in`, when re-quoting was asked for and the
+  /// maximum admits any input
+  pub quote: Option<OperationOutputValue>,
+}
+
+/// Largest amount up to `amount_in` that `fits`, given that `amount_in`
+/// does not and that fitting amounts are contiguous from zero.
+fn largest_fitting(amount_in: u64, fits: impl Fn(u64) -> bool) -> u64 {
+  let (low, _) = (0..u64::BITS).fold((0, amount_in), |(low, high), _| {
+    if high - low <= 1 {
+      (low, high)
+    } else {
+      let mid = low + (high - low) / 2;
+      if fits(mid) {
+        (mid, high)
+      } else {
+        (low, mid)
+      }
+    }
+  });
+  low
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Largest input up to `amount_in` of `input_mint` for `output_mint` that
+  /// quotes without exceeding the mintable or swappable maximum.
+  ///
+  /// Quote failures other than the maximum count as fitting, so they surface
+  /// when the clamped amount is quoted rather than shrinking it.
+  #[must_use]
+  pub fn max_mintable_input(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+  ) -> u64 {
+    let fits = |amount| {
+      self
+        .runtime_output(input_mint, output_mint, amount)
+        .err()
+        .is_none_or(|e| !is_over_max_mintable(&e))
+    };
+    if fits(amount_in) {
+      amount_in
+    } else {
+      largest_fitting(amount_in, fits)
+    }
+  }
+
+  /// Fits a trade of `amount_in` under the mintable maximum, quoting the
+  /// fitted amount if `requote`.
+  ///
+  /// # Errors
+  /// * Re-quoting the fitted amount fails
+  pub fn clamp_to_mintable(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    requote: bool,
+  ) -> Result<MintableClamp> {
+    let max_amount_in =
+      self.max_mintable_input(input_mint, output_mint, amount_in);
+    let quote = if requote && max_amount_in > 0 {
+      Some(self.runtime_output(input_mint, output_mint, max_amount_in)?)
+    } else {
+      None
+    };
+    Ok(MintableClamp {
+      input_mint,
+      output_mint,
+      requested_in: amount_in,
+      max_amount_in,
+      shortfall: amount_in - max_amount_in,
+      quote,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::anyhow;
+
+  use super::*;
+
+  #[test]
+  fn finds_largest_fitting_amount() {
+    assert_eq!(largest_fitting(1_000, |amount| amount <= 637), 637);
+    assert_eq!(largest_fitting(1_000, |amount| amount <= 999), 999);
+    assert_eq!(largest_fitting(1_000, |_| false), 0);
+    assert_eq!(
+      largest_fitting(u64::MAX, |amount| amount <= 1 << 40),
+      1 << 40
+    );
+  }
+
+  #[test]
+  fn recognizes_max_mintable_error() {
+    let over = anyhow::Error::from(anchor_lang::error::Error::from(
+      RequestedStablecoinOverMaxMintable,
+    ))
+    .context("Quote failed");
+    assert!(is_over_max_mintable(&over));
+    assert!(!is_over_max_mintable(&anyhow!("Unsupported pair")));
+  }
+}
@@ -0,0 +1,124 @@
+//! Stability mode gating for quoted operations.
+//!
+//! The exchange program rejects some operations once the collateral ratio
+//! falls below its stability thresholds. [`ModePolicy`] mirrors those rules so
+//! quotes are refused for pairs the program would reject, and can be switched
+//! off to price what-if scenarios in simulation. [`mode_descriptor`] bundles
+//! the mode at a collateral ratio with its bounds and the operations it
+//! allows.
+
+use anyhow::{ensure, Result};
+use fix::prelude::*;
+use hylo_core::operation_matrix::ProtocolOperation;
+use hylo_core::stability_mode::{
+  ModeBounds, StabilityController, StabilityMode,
+};
+
+use crate::Operation;
+
+/// Whether the exchange program accepts `operation` in `mode`, per
+/// [`OPERATION_MATRIX`](hylo_core::operation_matrix::OPERATION_MATRIX).
+#[must_use]
+pub fn is_allowed(operation: Operation, mode: StabilityMode) -> bool {
+  ProtocolOperation::from(operation).is_active(mode)
+}
+
+/// How quoting treats operations blocked in the current stability mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModePolicy {
+  /// Refuse operations the program blocks in the current mode.
+  #[default]
+  Enforce,
+
+  /// Quote every operation regardless of mode, for simulation.
+  Override,
+}
+
+impl ModePolicy {
+  /// Checks `operation` against the policy in `mode`.
+  ///
+  /// # Errors
+  /// * Policy is [`ModePolicy::Enforce`] and the program blocks `operation` in
+  ///   `mode`
+  pub fn check(self, operation: Operation, mode: StabilityMode) -> Result<()> {
+    ensure!(
+      self == ModePolicy::Override || is_allowed(operation, mode),
+      "{operation} is disabled in {mode} stability mode"
+    );
+    Ok(())
+  }
+}
+
+/// Stability mode at a collateral ratio with what it permits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeDescriptor {
+  pub bounds: ModeBounds,
+
+  /// Operations the program accepts in the mode, in [`Operation::ALL`] order
+  pub allowed: Vec<Operation>,
+}
+
+/// Describes the stability mode `collateral_ratio` falls in under
+/// `controller`.
+///
+/// # Errors
+/// * Stability mode lookup fails
+pub fn mode_descriptor(
+  controller: &StabilityController,
+  collateral_ratio: UFix64<N9>,
+) -> Result<ModeDescriptor> {
+  let bounds = controller.mode_bounds(collateral_ratio)?;
+  let allowed = Operation::ALL
+    .into_iter()
+    .filter(|operation| is_allowed(*operation, bounds.mode))
+    .collect();
+  Ok(ModeDescriptor { bounds, allowed })
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::stability_mode::StabilityMode::{Depeg, Mode1, Mode2, Normal};
+
+  use super::*;
+
+  #[test]
+  fn depeg_blocks_levercoin_paths() {
+    let blocked = [
+      Operation::MintStablecoin,
+      Operation::MintLevercoin,
+      Operation::RedeemLevercoin,
+      Operation::SwapStableToLever,
+      Operation::SwapLeverToStable,
+    ];
+    assert!(blocked.iter().all(|&op| !is_allowed(op, Depeg)));
+    assert!(is_allowed(Operation::RedeemStablecoin, Depeg));
+    assert!(is_allowed(Operation::WithdrawFromStabilityPool, Depeg));
+    assert!(!is_allowed(Operation::MintStablecoin, Mode2));
+    assert!(is_allowed(Operation::MintLevercoin, Mode2));
+    assert!(is_allowed(Operation::SwapLeverToStable, Mode1));
+    assert!(is_allowed(Operation::MintStablecoin, Normal));
+  }
+
+  #[test]
+  fn descriptor_lists_allowed_operations() -> Result<()> {
+    let controller =
+      StabilityController::new(UFix64::new(150), UFix64::new(130))?;
+    let mode2 = mode_descriptor(&controller, UFix64::new(1_100_000_000))?;
+    assert_eq!(mode2.bounds.mode, Mode2);
+    assert!(!mode2.allowed.contains(&Operation::MintStablecoin));
+    assert!(mode2.allowed.contains(&Operation::MintLevercoin));
+    let normal = mode_descriptor(&controller, UFix64::new(2_000_000_000))?;
+    assert_eq!(normal.allowed, Operation::ALL);
+    Ok(())
+  }
+
+  #[test]
+  fn override_allows_everything() {
+    assert!(ModePolicy::Enforce
+      .check(Operation::MintLevercoin, Depeg)
+      .is_err());
+    assert!(ModePolicy::Override
+      .check(Operation::MintLevercoin, Depeg)
+      .is_ok());
+  }
+}
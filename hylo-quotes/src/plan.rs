@@ -0,0 +1,319 @@
+//! Multi-leg Hylo operations.
+//!
+//! A [`Plan`] chains [`SwapLeg`]s, each a quoted Hylo operation whose output
+//! funds the next leg, e.g. `jitoSOL -> hyUSD -> sHYUSD`. Quoting, previews
+//! and transaction building share this one representation: every leg keeps
+//! its own quote and instructions, and the plan joins them into a single
+//! transaction.
+//!
+//! Legs after the first spend the expected output of the leg before them. If
+//! a leg fills below its quote, the next one is short and the whole
+//! transaction fails instead of executing in part.
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Result};
+use fix::prelude::UFixValue64;
+use futures::{StreamExt, TryStreamExt};
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::preview::TransactionPreview;
+use crate::protocol_state::ProtocolState;
+use crate::{ExecutableQuoteValue, QuoteMetadata, RuntimeQuoteStrategy};
+
+/// One quoted operation of a plan.
+#[derive(Clone, Debug)]
+pub struct SwapLeg {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub metadata: QuoteMetadata,
+  pub quote: ExecutableQuoteValue,
+}
+
+impl SwapLeg {
+  /// Quotes `amount_in` base units of `input_mint` into `output_mint`.
+  ///
+  /// # Errors
+  /// * Unsupported pair or quote computation fails
+  pub async fn quote<C: SolanaClock>(
+    strategy: &(impl RuntimeQuoteStrategy<C> + Sync),
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> Result<SwapLeg> {
+    let (quote, metadata) = strategy
+      .runtime_quote_with_metadata(
+        input_mint,
+        output_mint,
+        amount_in,
+        user,
+        slippage_tolerance,
+      )
+      .await?;
+    Ok(SwapLeg {
+      input_mint,
+      output_mint,
+      metadata,
+      quote,
+    })
+  }
+}
+
+/// Non-empty chain of legs, each spending the previous leg's output.
+#[derive(Clone, Debug)]
+pub struct Plan {
+  legs: Vec<SwapLeg>,
+}
+
+impl Plan {
+  /// Plan from already quoted legs.
+  ///
+  /// # Errors
+  /// * No legs
+  /// * A leg's input mint is not the previous leg's output mint
+  /// * A leg spends more than the previous leg is quoted to return
+  pub fn new(legs: Vec<SwapLeg>) -> Result<Plan> {
+    ensure!(!legs.is_empty(), "Plan has no legs");
+    legs.windows(2).try_for_each(|pair| match pair {
+      [prev, next] => {
+        ensure!(
+          prev.output_mint == next.input_mint,
+          "Leg into {} cannot be followed by a leg from {}",
+          prev.output_mint,
+          next.input_mint
+        );
+        ensure!(
+          next.quote.amount_in.bits <= prev.quote.amount_out.bits,
+          "Leg from {} spends {} of {} base units quoted",
+          next.input_mint,
+          next.quote.amount_in.bits,
+          prev.quote.amount_out.bits
+        );
+        Ok(())
+      }
+      _ => Ok(()),
+    })?;
+    Ok(Plan { legs })
+  }
+
+  /// Quotes `amount_in` base units along `route`, a list of mints from
+  /// input to output, feeding each leg's expected output into the next.
+  ///
+  /// Use a strategy over a single state snapshot so that every leg prices
+  /// against the same protocol state.
+  ///
+  /// # Errors
+  /// * Route has fewer than two mints
+  /// * Any leg's pair is unsupported or its quote fails
+  pub async fn quote<C: SolanaClock>(
+    strategy: &(impl RuntimeQuoteStrategy<C> + Sync),
+    route: &[Pubkey],
+    amount_in: u64,
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> Result<Plan> {
+    let pairs: Vec<(Pubkey, Pubkey)> = route
+      .windows(2)
+      .filter_map(|pair| match pair {
+        [input, output] => Some((*input, *output)),
+        _ => None,
+      })
+      .collect();
+    let legs = futures::stream::iter(pairs)
+      .map(Ok)
+      .try_fold(Vec::new(), |mut legs: Vec<SwapLeg>, (input, output)| {
+        let amount = legs
+          .last()
+          .map_or(amount_in, |leg| leg.quote.amount_out.bits);
+        async move {
+          let leg = SwapLeg::quote(
+            strategy,
+            input,
+            output,
+            amount,
+            user,
+            slippage_tolerance,
+          )
+          .await?;
+          legs.push(leg);
+          Ok::<_, anyhow::Error>(legs)
+        }
+      })
+      .await?;
+    Plan::new(legs)
+  }
+
+  #[must_use]
+  pub fn legs(&self) -> &[SwapLeg] {
+    &self.legs
+  }
+
+  fn first(&self) -> Result<&SwapLeg> {
+    self.legs.first().ok_or(anyhow!("Plan has no legs"))
+  }
+
+  fn last(&self) -> Result<&SwapLeg> {
+    self.legs.last().ok_or(anyhow!("Plan has no legs"))
+  }
+
+  /// Mint spent by the first leg.
+  ///
+  /// # Errors
+  /// * Plan has no legs
+  pub fn input_mint(&self) -> Result<Pubkey> {
+    self.first().map(|leg| leg.input_mint)
+  }
+
+  /// Mint received from the last leg.
+  ///
+  /// # Errors
+  /// * Plan has no legs
+  pub fn output_mint(&self) -> Result<Pubkey> {
+    self.last().map(|leg| leg.output_mint)
+  }
+
+  /// Amount spent by the first leg.
+  ///
+  /// # Errors
+  /// * Plan has no legs
+  pub fn amount_in(&self) -> Result<UFixValue64> {
+    self.first().map(|leg| leg.quote.amount_in)
+  }
+
+  /// Amount the last leg is quoted to return.
+  ///
+  /// # Errors
+  /// * Plan has no legs
+  pub fn amount_out(&self) -> Result<UFixValue64> {
+    self.last().map(|leg| leg.quote.amount_out)
+  }
+
+  /// Compute units of every leg together.
+  #[must_use]
+  pub fn compute_units(&self) -> u64 {
+    self.legs.iter().map(|leg| leg.quote.compute_units).sum()
+  }
+
+  /// Instructions of every leg in order. Repeats of an identical
+  /// instruction, such as idempotent ATA creation, are kept only once.
+  #[must_use]
+  pub fn instructions(&self) -> Vec<Instruction> {
+    self
+      .legs
+      .iter()
+      .flat_map(|leg| &leg.quote.instructions)
+      .fold(Vec::new(), |mut instructions, ix| {
+        if !instructions.contains(ix) {
+          instructions.push(ix.clone());
+        }
+        instructions
+      })
+  }
+
+  /// Lookup tables needed by any leg, without repeats.
+  #[must_use]
+  pub fn address_lookup_tables(&self) -> Vec<Pubkey> {
+    self
+      .legs
+      .iter()
+      .flat_map(|leg| &leg.quote.address_lookup_tables)
+      .fold(Vec::new(), |mut tables, table| {
+        if !tables.contains(table) {
+          tables.push(*table);
+        }
+        tables
+      })
+  }
+
+  /// Previews the plan's joined instructions against `state`.
+  #[must_use]
+  pub fn preview<C: SolanaClock>(
+    &self,
+    state: &ProtocolState<C>,
+  ) -> TransactionPreview {
+    state.preview_transaction(&self.instructions())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fix::prelude::*;
+  use hylo_idl::tokens::{TokenMint, HYUSD, JITOSOL, SHYUSD};
+
+  use super::*;
+  use crate::{ComputeUnitStrategy, Operation};
+
+  fn leg(
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    amount_out: u64,
+    instructions: Vec<Instruction>,
+  ) -> SwapLeg {
+    SwapLeg {
+      input_mint,
+      output_mint,
+      metadata: QuoteMetadata::new(Operation::MintStablecoin, "leg"),
+      quote: ExecutableQuoteValue {
+        amount_in: UFix64::<N6>::new(amount_in).into(),
+        amount_out: UFix64::<N6>::new(amount_out).into(),
+        compute_units: 100_000,
+        compute_unit_strategy: ComputeUnitStrategy::Estimated,
+        fee_amount: UFix64::<N6>::new(0).into(),
+        fee_mint: input_mint,
+        instructions,
+        address_lookup_tables: vec![HYUSD::MINT],
+        freshness: None,
+      },
+    }
+  }
+
+  #[test]
+  fn chains_legs_and_joins_instructions() -> Result<()> {
+    let ata = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+    let mint = Instruction::new_with_bytes(Pubkey::new_unique(), &[2], vec![]);
+    let deposit =
+      Instruction::new_with_bytes(Pubkey::new_unique(), &[3], vec![]);
+    let plan = Plan::new(vec![
+      leg(
+        JITOSOL::MINT,
+        HYUSD::MINT,
+        10,
+        150,
+        vec![ata.clone(), mint.clone()],
+      ),
+      leg(
+        HYUSD::MINT,
+        SHYUSD::MINT,
+        150,
+        140,
+        vec![ata.clone(), deposit.clone()],
+      ),
+    ])?;
+    assert_eq!(plan.input_mint()?, JITOSOL::MINT);
+    assert_eq!(plan.output_mint()?, SHYUSD::MINT);
+    assert_eq!(plan.amount_out()?.bits, 140);
+    assert_eq!(plan.compute_units(), 200_000);
+    assert_eq!(plan.instructions(), vec![ata, mint, deposit]);
+    assert_eq!(plan.address_lookup_tables(), vec![HYUSD::MINT]);
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_broken_chains() {
+    let mint = leg(JITOSOL::MINT, HYUSD::MINT, 10, 150, vec![]);
+    assert!(Plan::new(vec![]).is_err());
+    assert!(Plan::new(vec![
+      mint.clone(),
+      leg(SHYUSD::MINT, HYUSD::MINT, 150, 150, vec![])
+    ])
+    .is_err());
+    assert!(Plan::new(vec![
+      mint,
+      leg(HYUSD::MINT, SHYUSD::MINT, 151, 150, vec![])
+    ])
+    .is_err());
+  }
+}
@@ -0,0 +1,173 @@
+//! Stability pool entries and exits split across transactions.
+//!
+//! The pool withdrawal fee is a flat share of the value withdrawn, so
+//! splitting an exit does not change it. What does depend on size is the
+//! redemption that follows a withdrawal into an LST: redeeming the pool's
+//! hyUSD and xSOL share in one go can push the collateral ratio into a mode
+//! with higher fees. [`ProtocolState::pool_schedule`] sizes each step under
+//! a per-transaction limit and a price impact limit, and splits the total
+//! into equal steps, refusing schedules longer than a step limit.
+//!
+//! Every step is quoted against the same state. Steps are meant to land in
+//! separate transactions or periods, so re-plan the remainder once a step
+//! lands and the state has moved.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Result};
+use fix::prelude::*;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{TokenMint, SHYUSD};
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::RUNTIME_PAIRS;
+
+/// Bounds on a single step of a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepLimits {
+  /// Most input per transaction, in base units
+  pub max_amount_in: u64,
+
+  /// Price impact a step may take; one bit is one basis point
+  pub max_impact: UFix64<N4>,
+
+  /// Most steps a schedule may take before it is refused
+  pub max_steps: u64,
+}
+
+/// One quoted transaction of a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStep {
+  /// Input amount in base units
+  pub amount_in: u64,
+
+  /// Output amount in base units
+  pub amount_out: u64,
+
+  /// Fees paid in the schedule's fee mint, in base units
+  pub fee_amount: u64,
+}
+
+/// Equal steps entering or exiting the stability pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolSchedule {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub fee_mint: Pubkey,
+  pub steps: Vec<PoolStep>,
+}
+
+impl PoolSchedule {
+  /// Output of all steps, in base units.
+  #[must_use]
+  pub fn amount_out(&self) -> u64 {
+    self
+      .steps
+      .iter()
+      .fold(0, |total, step| total.saturating_add(step.amount_out))
+  }
+
+  /// Fees of all steps, in base units of [`Self::fee_mint`].
+  #[must_use]
+  pub fn fee_amount(&self) -> u64 {
+    self
+      .steps
+      .iter()
+      .fold(0, |total, step| total.saturating_add(step.fee_amount))
+  }
+}
+
+/// Splits `amount` into the fewest near-equal parts of at most `max_part`,
+/// larger parts first.
+fn split(amount: u64, max_part: u64) -> Option<Vec<u64>> {
+  let parts = amount.div_ceil(max_part.max(1));
+  let base = amount.checked_div(parts)?;
+  let larger = amount.checked_rem(parts)?;
+  Some(
+    (0..parts)
+      .map(|part| base + u64::from(part < larger))
+      .collect(),
+  )
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Schedule of `amount_in` base units from `input_mint` to `output_mint`,
+  /// where one side is sHYUSD.
+  ///
+  /// # Errors
+  /// * Not a supported stability pool pair
+  /// * Zero amount or per-transaction limit
+  /// * One whole input token does not quote
+  /// * No step size is within the impact limit
+  /// * `amount_in` needs more than `limits.max_steps` steps
+  /// * A step fails to quote
+  pub fn pool_schedule(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    limits: &StepLimits,
+  ) -> Result<PoolSchedule> {
+    let pair = RUNTIME_PAIRS
+      .iter()
+      .filter(|pair| {
+        pair.input_mint == SHYUSD::MINT || pair.output_mint == SHYUSD::MINT
+      })
+      .find(|pair| {
+        pair.input_mint == input_mint && pair.output_mint == output_mint
+      })
+      .ok_or(anyhow!(
+        "Not a stability pool pair {input_mint} -> {output_mint}"
+      ))?;
+    ensure!(amount_in > 0, "Nothing to schedule");
+    ensure!(limits.max_amount_in > 0, "Zero per-transaction limit");
+    let max_step = self
+      .max_trade_size(pair, limits.max_impact)?
+      .amount_in
+      .min(limits.max_amount_in);
+    ensure!(max_step > 0, "No size within the impact limit");
+    let step_count = amount_in.div_ceil(max_step);
+    ensure!(
+      step_count <= limits.max_steps,
+      "{amount_in} needs {step_count} steps, over the limit of {}",
+      limits.max_steps
+    );
+    let parts = split(amount_in, max_step)
+      .ok_or(anyhow!("Failed to split {amount_in} into steps"))?;
+    let outputs = parts
+      .into_iter()
+      .map(|amount| self.runtime_output(input_mint, output_mint, amount))
+      .collect::<Result<Vec<_>>>()?;
+    let fee_mint = outputs.first().map_or(input_mint, |op| op.fee_mint);
+    let steps = outputs
+      .into_iter()
+      .map(|op| PoolStep {
+        amount_in: op.in_amount.bits,
+        amount_out: op.out_amount.bits,
+        fee_amount: op.fee_amount.bits,
+      })
+      .collect();
+    Ok(PoolSchedule {
+      input_mint,
+      output_mint,
+      fee_mint,
+      steps,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn splits_into_near_equal_parts() {
+    assert_eq!(split(10, 4), Some(vec![4, 3, 3]));
+    assert_eq!(split(12, 4), Some(vec![4, 4, 4]));
+    assert_eq!(split(3, 4), Some(vec![3]));
+  }
+
+  #[test]
+  fn nothing_to_split() {
+    assert_eq!(split(0, 4), None);
+  }
+}
@@ -0,0 +1,157 @@
+//! Program preconditions checked before building a transaction.
+//!
+//! The exchange program rejects a trade whose SOL/USD price has aged past the
+//! oracle interval, whose total SOL cache or LST price is from a past epoch,
+//! whose operation the stability mode blocks, or whose stablecoin output
+//! exceeds the mintable or swappable maximum. Each rejection still costs the
+//! user a transaction fee. [`ProtocolState::check_preconditions`] runs the
+//! same checks client side and fails with the program's own
+//! [`CoreError`](hylo_core::error::CoreError), recovered with
+//! `anyhow::Error::downcast_ref::<anchor_lang::error::Error>`.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Result};
+use hylo_core::error::CoreError::{
+  LstSolPriceOutdated, PythOracleOutdated, TotalSolCacheOutdated,
+};
+use hylo_core::operation_matrix::ProtocolOperation;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_core::stability_mode::StabilityMode;
+
+use crate::protocol_state::ProtocolState;
+use crate::{pair_metadata, Operation, QuoteFreshness};
+
+/// Checks that `mode` does not block `operation`.
+fn mode_precondition(
+  operation: Operation,
+  mode: StabilityMode,
+) -> anchor_lang::Result<()> {
+  ProtocolOperation::from(operation).check(mode)
+}
+
+/// Checks that the prices in `freshness` are accepted at `unix_timestamp` in
+/// `epoch`.
+fn freshness_precondition(
+  freshness: &QuoteFreshness,
+  unix_timestamp: i64,
+  epoch: u64,
+) -> anchor_lang::Result<()> {
+  if unix_timestamp > freshness.valid_until {
+    Err(PythOracleOutdated.into())
+  } else if freshness.total_sol_cache_epoch < epoch {
+    Err(TotalSolCacheOutdated.into())
+  } else if freshness
+    .lst_price_epoch
+    .is_some_and(|lst_epoch| lst_epoch < epoch)
+  {
+    Err(LstSolPriceOutdated.into())
+  } else {
+    Ok(())
+  }
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Checks a trade of `amount_in` base units from `input_mint` to
+  /// `output_mint` against the program's preconditions at `clock`, typically
+  /// the current cluster clock or this state's own.
+  ///
+  /// Checks run in the program's order: oracle age, total SOL cache and LST
+  /// price epochs, stability mode, then amount limits. LST prices estimated
+  /// from stake pools count as outdated, since the program has not seen
+  /// them.
+  ///
+  /// # Errors
+  /// * Unsupported pair
+  /// * `PythOracleOutdated` past the oracle interval
+  /// * `TotalSolCacheOutdated` or `LstSolPriceOutdated` for prices from a past
+  ///   epoch
+  /// * `NoValid*Fee` errors for operations the stability mode blocks
+  /// * `RequestedStablecoinOverMaxMintable` past the mintable or swappable
+  ///   maximum, or any other quote failure
+  pub fn check_preconditions(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    clock: &impl SolanaClock,
+  ) -> Result<()> {
+    let operation = pair_metadata(input_mint, output_mint)
+      .map(|metadata| metadata.operation)
+      .ok_or(anyhow!("Unsupported pair {input_mint} -> {output_mint}"))?;
+    let estimated = self
+      .estimated_lst_prices
+      .iter()
+      .any(|mint| *mint == input_mint || *mint == output_mint);
+    ensure!(
+      !estimated,
+      anchor_lang::error::Error::from(LstSolPriceOutdated)
+    );
+    freshness_precondition(
+      &self.quote_freshness(input_mint, output_mint),
+      clock.unix_timestamp(),
+      clock.epoch(),
+    )?;
+    mode_precondition(operation, self.exchange_context.stability_mode())?;
+    self.runtime_output(input_mint, output_mint, amount_in)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use hylo_core::error::CoreError::{
+    NoValidLevercoinRedeemFee, NoValidStablecoinMintFee, NoValidSwapFee,
+  };
+  use hylo_core::stability_mode::StabilityMode::{Depeg, Mode1, Mode2};
+
+  use super::*;
+
+  const FRESHNESS: QuoteFreshness = QuoteFreshness {
+    oracle_publish_time: 1_000,
+    lst_price_epoch: Some(700),
+    total_sol_cache_epoch: 700,
+    valid_until: 1_060,
+  };
+
+  #[test]
+  fn maps_blocked_modes_to_fee_errors() {
+    assert_eq!(mode_precondition(Operation::MintStablecoin, Mode1), Ok(()));
+    assert_eq!(
+      mode_precondition(Operation::MintStablecoin, Mode2),
+      Err(NoValidStablecoinMintFee.into())
+    );
+    assert_eq!(
+      mode_precondition(Operation::RedeemLevercoin, Depeg),
+      Err(NoValidLevercoinRedeemFee.into())
+    );
+    assert_eq!(
+      mode_precondition(Operation::SwapLeverToStable, Depeg),
+      Err(NoValidSwapFee.into())
+    );
+    assert_eq!(
+      mode_precondition(Operation::RedeemStablecoin, Depeg),
+      Ok(())
+    );
+  }
+
+  #[test]
+  fn flags_stale_prices_in_program_order() {
+    let current_cache = QuoteFreshness {
+      total_sol_cache_epoch: 701,
+      ..FRESHNESS
+    };
+    assert_eq!(freshness_precondition(&FRESHNESS, 1_060, 700), Ok(()));
+    assert_eq!(
+      freshness_precondition(&FRESHNESS, 1_061, 701),
+      Err(PythOracleOutdated.into())
+    );
+    assert_eq!(
+      freshness_precondition(&FRESHNESS, 1_010, 701),
+      Err(TotalSolCacheOutdated.into())
+    );
+    assert_eq!(
+      freshness_precondition(&current_cache, 1_010, 701),
+      Err(LstSolPriceOutdated.into())
+    );
+  }
+}
@@ -5,13 +5,16 @@ pub use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
 pub use anchor_client::Cluster;
 pub use anchor_lang::prelude::Pubkey;
 pub use anyhow::Result;
-pub use fix::prelude::*;
-// Token types
-pub use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+// Core protocol types, tokens, PDAs, and UFix aliases
+pub use hylo_core::prelude::*;
 
+// Multi-leg plans
+pub use crate::plan::{Plan, SwapLeg};
 // Protocol state
 pub use crate::protocol_state::{
-  ProtocolAccounts, ProtocolState, RpcStateProvider, StateProvider,
+  ProtocolAccounts, ProtocolState, ReadAfterWrite, RpcStateProvider,
+  SnapshotStateProvider, StakePoolRefreshProvider, StateProvider,
+  WebsocketStateProvider,
 };
 // SimulatedOperation (event extraction)
 pub use crate::simulated_operation::{
@@ -20,8 +23,8 @@ pub use crate::simulated_operation::{
 // TokenOperation (pure math)
 pub use crate::token_operation::{
   LstSwapOperationOutput, MintOperationOutput, OperationOutput,
-  RedeemOperationOutput, SwapOperationOutput, TokenOperation,
-  TokenOperationExt,
+  OperationOutputValue, RedeemOperationOutput, SwapOperationOutput,
+  TokenOperation, TokenOperationExt,
 };
 // Strategy implementations
 pub use crate::ProtocolStateStrategy;
@@ -31,7 +34,9 @@ pub use crate::QuoteStrategy;
 pub use crate::LST;
 // Core quote types
 pub use crate::{
-  ComputeUnitInfo, ComputeUnitStrategy, ExecutableQuote, ExecutableQuoteValue,
-  Operation, QuoteMetadata, DEFAULT_CUS_WITH_BUFFER,
+  ComputeUnitInfo, ComputeUnitStrategy, DepositCap, DepositCapExceeded,
+  DepositCapMode, EpochMode, EpochTransition, ExecutableQuote,
+  ExecutableQuoteValue, ModePolicy, Operation, QuoteContext, QuoteMetadata,
+  QuoteWithContext, DEFAULT_CUS_WITH_BUFFER,
 };
 pub use crate::{RuntimeQuoteStrategy, SimulationStrategy};
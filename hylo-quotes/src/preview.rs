@@ -0,0 +1,409 @@
+//! Human-readable previews of built Hylo transactions.
+//!
+//! Decodes each exchange and stability pool instruction in a transaction,
+//! reprices it against a [`ProtocolState`] with the same math used for quotes,
+//! and renders the effect for wallet confirmation screens:
+//!
+//! ```txt
+//! Mint ~123.4 hyUSD from 1 jitoSOL, fee 0.05%, min received 123 hyUSD
+//! ```
+//!
+//! Instructions for other programs, such as compute budget or ATA creation,
+//! are skipped. A Hylo instruction that cannot be priced, such as a stability
+//! pool withdrawal while the pool holds xSOL, is listed as unpriced with the
+//! reason, and the rest of the transaction is still previewed.
+
+use std::fmt::{self, Display};
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use fix::prelude::*;
+use hylo_core::amount_format::format_amount;
+use hylo_core::slippage_config::SlippageConfig;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_core::token_amount::TokenAmount;
+use hylo_idl::exchange::client::args as exchange_args;
+use hylo_idl::exchange::types::SlippageConfig as IdlSlippageConfig;
+use hylo_idl::stability_pool::client::args as stability_pool_args;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_idl::{exchange, stability_pool};
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::{TokenOperation, TokenOperationExt};
+use crate::Operation;
+
+/// Token amount with its rendering, e.g. `1,234.56 hyUSD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewAmount {
+  pub mint: Pubkey,
+  pub amount: UFixValue64,
+  pub display: String,
+}
+
+impl PreviewAmount {
+  fn new<T: TokenMint>(amount: UFix64<T::Exp>) -> PreviewAmount {
+    PreviewAmount {
+      mint: T::MINT,
+      amount: amount.into(),
+      display: TokenAmount::<T>::new(amount).to_string(),
+    }
+  }
+}
+
+impl Display for PreviewAmount {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.display)
+  }
+}
+
+/// Effect of one Hylo instruction, priced against protocol state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionPreview {
+  pub operation: Operation,
+  pub amount_in: PreviewAmount,
+
+  /// Output the quote math expects at current state
+  pub expected_out: PreviewAmount,
+
+  /// Output floor enforced on chain by the instruction's slippage config
+  pub min_out: Option<PreviewAmount>,
+  pub fee: PreviewAmount,
+
+  /// Fee as a share of the amount it was taken from
+  pub fee_rate: UFix64<N4>,
+}
+
+impl Display for InstructionPreview {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let InstructionPreview {
+      amount_in,
+      expected_out,
+      ..
+    } = self;
+    match self.operation {
+      Operation::MintStablecoin | Operation::MintLevercoin => {
+        write!(f, "Mint ~{expected_out} from {amount_in}")
+      }
+      Operation::RedeemStablecoin | Operation::RedeemLevercoin => {
+        write!(f, "Redeem {amount_in} for ~{expected_out}")
+      }
+      Operation::SwapStableToLever
+      | Operation::SwapLeverToStable
+      | Operation::LstSwap => {
+        write!(f, "Swap {amount_in} for ~{expected_out}")
+      }
+      Operation::DepositToStabilityPool => {
+        write!(f, "Deposit {amount_in} for ~{expected_out}")
+      }
+      Operation::WithdrawFromStabilityPool
+      | Operation::WithdrawAndRedeemFromStabilityPool => {
+        write!(f, "Withdraw {amount_in} for ~{expected_out}")
+      }
+    }?;
+    let fee_percent = format_amount(UFix64::<N2>::new(self.fee_rate.bits));
+    write!(f, ", fee {fee_percent}%")?;
+    self
+      .min_out
+      .as_ref()
+      .map_or(Ok(()), |min_out| write!(f, ", min received {min_out}"))
+  }
+}
+
+/// Preview of one Hylo instruction within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewEntry {
+  Priced(InstructionPreview),
+
+  /// Instruction that failed to decode or price, with the reason
+  Unpriced {
+    program_id: Pubkey,
+    reason: String,
+  },
+}
+
+impl Display for PreviewEntry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PreviewEntry::Priced(preview) => write!(f, "{preview}"),
+      PreviewEntry::Unpriced { program_id, reason } => {
+        write!(f, "Unable to preview {program_id} instruction: {reason}")
+      }
+    }
+  }
+}
+
+/// Effects of every Hylo instruction in a transaction, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionPreview {
+  pub instructions: Vec<PreviewEntry>,
+}
+
+impl Display for TransactionPreview {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self
+      .instructions
+      .iter()
+      .enumerate()
+      .try_for_each(|(i, preview)| match i {
+        0 => write!(f, "{preview}"),
+        _ => write!(f, "\n{preview}"),
+      })
+  }
+}
+
+/// Hylo instruction decoded into its token pair and arguments.
+struct DecodedInstruction {
+  operation: Operation,
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+  amount_in: u64,
+  slippage_config: Option<SlippageConfig>,
+}
+
+impl DecodedInstruction {
+  fn new(
+    operation: Operation,
+    (input_mint, output_mint): (Pubkey, Pubkey),
+    amount_in: u64,
+    slippage_config: Option<IdlSlippageConfig>,
+  ) -> DecodedInstruction {
+    DecodedInstruction {
+      operation,
+      input_mint,
+      output_mint,
+      amount_in,
+      slippage_config: slippage_config.map(Into::into),
+    }
+  }
+}
+
+/// Reads the account at `index` in the instruction's account list.
+fn account(ix: &Instruction, index: usize) -> Result<Pubkey> {
+  ix.accounts
+    .get(index)
+    .map(|meta| meta.pubkey)
+    .with_context(|| format!("Instruction missing account {index}"))
+}
+
+/// Splits instruction data into its discriminator and serialized args.
+fn split_data(ix: &Instruction) -> Result<(&[u8], &[u8])> {
+  ix.data
+    .split_at_checked(8)
+    .context("Instruction data shorter than discriminator")
+}
+
+/// Decodes an exchange instruction. Account indices follow the IDL order.
+fn decode_exchange(ix: &Instruction) -> Result<DecodedInstruction> {
+  let (discriminator, mut data) = split_data(ix)?;
+  match discriminator {
+    exchange_args::MintStablecoin::DISCRIMINATOR => {
+      let args = exchange_args::MintStablecoin::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::MintStablecoin,
+        (account(ix, 10)?, HYUSD::MINT),
+        args.amount_lst_to_deposit,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::RedeemStablecoin::DISCRIMINATOR => {
+      let args = exchange_args::RedeemStablecoin::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::RedeemStablecoin,
+        (HYUSD::MINT, account(ix, 10)?),
+        args.amount_to_redeem,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::MintLevercoin::DISCRIMINATOR => {
+      let args = exchange_args::MintLevercoin::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::MintLevercoin,
+        (account(ix, 10)?, XSOL::MINT),
+        args.amount_lst_to_deposit,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::RedeemLevercoin::DISCRIMINATOR => {
+      let args = exchange_args::RedeemLevercoin::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::RedeemLevercoin,
+        (XSOL::MINT, account(ix, 11)?),
+        args.amount_to_redeem,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::SwapStableToLever::DISCRIMINATOR => {
+      let args = exchange_args::SwapStableToLever::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::SwapStableToLever,
+        (HYUSD::MINT, XSOL::MINT),
+        args.amount_stablecoin,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::SwapLeverToStable::DISCRIMINATOR => {
+      let args = exchange_args::SwapLeverToStable::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::SwapLeverToStable,
+        (XSOL::MINT, HYUSD::MINT),
+        args.amount_levercoin,
+        args.slippage_config,
+      ))
+    }
+    exchange_args::SwapLst::DISCRIMINATOR => {
+      let args = exchange_args::SwapLst::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::LstSwap,
+        (account(ix, 2)?, account(ix, 7)?),
+        args.amount_lst_a,
+        args.slippage_config,
+      ))
+    }
+    _ => Err(anyhow!("Unsupported exchange instruction")),
+  }
+}
+
+/// Decodes a stability pool instruction.
+fn decode_stability_pool(ix: &Instruction) -> Result<DecodedInstruction> {
+  let (discriminator, mut data) = split_data(ix)?;
+  match discriminator {
+    stability_pool_args::UserDeposit::DISCRIMINATOR => {
+      let args = stability_pool_args::UserDeposit::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::DepositToStabilityPool,
+        (HYUSD::MINT, SHYUSD::MINT),
+        args.amount_stablecoin,
+        None,
+      ))
+    }
+    stability_pool_args::UserWithdraw::DISCRIMINATOR => {
+      let args = stability_pool_args::UserWithdraw::deserialize(&mut data)?;
+      Ok(DecodedInstruction::new(
+        Operation::WithdrawFromStabilityPool,
+        (SHYUSD::MINT, HYUSD::MINT),
+        args.amount_lp_token,
+        None,
+      ))
+    }
+    _ => Err(anyhow!("Unsupported stability pool instruction")),
+  }
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Prices a decoded instruction for a statically known pair.
+  fn preview_pair<IN: TokenMint, OUT: TokenMint>(
+    &self,
+    decoded: DecodedInstruction,
+  ) -> Result<InstructionPreview>
+  where
+    Self: TokenOperation<IN, OUT>,
+  {
+    let op = self.output::<IN, OUT>(UFix64::new(decoded.amount_in))?;
+    // Fees are taken in either the input or the output token
+    let fee = if op.fee_mint == IN::MINT {
+      PreviewAmount::new::<IN>(UFix64::new(op.fee_amount.bits))
+    } else {
+      PreviewAmount::new::<OUT>(UFix64::new(op.fee_amount.bits))
+    };
+    let min_out = decoded
+      .slippage_config
+      .map(|config| config.min_token_out().map(PreviewAmount::new::<OUT>))
+      .transpose()?;
+    Ok(InstructionPreview {
+      operation: decoded.operation,
+      amount_in: PreviewAmount::new::<IN>(op.in_amount),
+      expected_out: PreviewAmount::new::<OUT>(op.out_amount),
+      min_out,
+      fee,
+      fee_rate: UFix64::<N4>::one()
+        .mul_div_floor(op.fee_amount, op.fee_base)
+        .unwrap_or_default(),
+    })
+  }
+
+  /// Previews a single instruction, or `None` if it is not a Hylo exchange
+  /// or stability pool instruction.
+  ///
+  /// # Errors
+  /// * Instruction data or accounts do not match the IDL
+  /// * Unsupported pair or quote computation fails
+  pub fn preview_instruction(
+    &self,
+    ix: &Instruction,
+  ) -> Result<Option<InstructionPreview>> {
+    let decoded = match ix.program_id {
+      exchange::ID => Some(decode_exchange(ix)?),
+      stability_pool::ID => Some(decode_stability_pool(ix)?),
+      _ => None,
+    };
+    decoded
+      .map(|decoded| self.preview_decoded(decoded))
+      .transpose()
+  }
+
+  /// Previews every Hylo instruction of a built transaction. Instructions
+  /// that fail to decode or price are listed as [`PreviewEntry::Unpriced`].
+  #[must_use]
+  pub fn preview_transaction(
+    &self,
+    instructions: &[Instruction],
+  ) -> TransactionPreview {
+    let instructions = instructions
+      .iter()
+      .filter_map(|ix| {
+        self.preview_instruction(ix).transpose().map(|preview| {
+          preview.map_or_else(
+            |e| PreviewEntry::Unpriced {
+              program_id: ix.program_id,
+              reason: format!("{e:#}"),
+            },
+            PreviewEntry::Priced,
+          )
+        })
+      })
+      .collect();
+    TransactionPreview { instructions }
+  }
+}
+
+macro_rules! preview_pairs {
+  ($(($in:ty, $out:ty)),* $(,)?) => {
+    impl<C: SolanaClock> ProtocolState<C> {
+      /// Dispatches a decoded instruction to its typed pair.
+      fn preview_decoded(
+        &self,
+        decoded: DecodedInstruction,
+      ) -> Result<InstructionPreview> {
+        match (decoded.input_mint, decoded.output_mint) {
+          $(
+            (<$in>::MINT, <$out>::MINT) => {
+              self.preview_pair::<$in, $out>(decoded)
+            },
+          )*
+          (input_mint, output_mint) => {
+            Err(anyhow!("Unsupported pair {input_mint} -> {output_mint}"))
+          }
+        }
+      }
+    }
+  };
+}
+
+preview_pairs! {
+  (JITOSOL, HYUSD),
+  (HYUSD, JITOSOL),
+  (HYLOSOL, HYUSD),
+  (HYUSD, HYLOSOL),
+  (JITOSOL, XSOL),
+  (XSOL, JITOSOL),
+  (HYLOSOL, XSOL),
+  (XSOL, HYLOSOL),
+  (HYUSD, XSOL),
+  (XSOL, HYUSD),
+  (JITOSOL, HYLOSOL),
+  (HYLOSOL, JITOSOL),
+  (HYUSD, SHYUSD),
+  (SHYUSD, HYUSD),
+}
@@ -2,6 +2,14 @@
 
 use std::convert::TryFrom;
 
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use anchor_client::solana_client::client_error::{
+  ClientError, ClientErrorKind,
+};
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcAccountInfoConfig;
+use anchor_client::solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED;
+use anchor_client::solana_client::rpc_request::RpcError;
 use anchor_client::solana_sdk::account::Account;
 use anchor_lang::prelude::Pubkey;
 use anchor_lang::solana_program::sysvar;
@@ -74,6 +82,56 @@ impl ProtocolAccounts {
     11
   }
 
+  /// Reads every protocol account in one `getMultipleAccounts` call, with
+  /// the slot they were read at. Given `min_context_slot`, only a node that
+  /// has processed that slot answers, and `None` means the node lags it.
+  ///
+  /// # Errors
+  /// Returns error if the RPC call fails or an account is missing
+  pub async fn fetch(
+    rpc_client: &RpcClient,
+    min_context_slot: Option<u64>,
+  ) -> Result<Option<(u64, ProtocolAccounts)>> {
+    let pubkeys = Self::pubkeys();
+    let config = RpcAccountInfoConfig {
+      encoding: Some(UiAccountEncoding::Base64),
+      commitment: Some(rpc_client.commitment()),
+      min_context_slot,
+      ..RpcAccountInfoConfig::default()
+    };
+    match rpc_client
+      .get_multiple_accounts_with_config(&pubkeys, config)
+      .await
+    {
+      Ok(response) => {
+        let accounts =
+          Self::try_from((pubkeys.as_slice(), response.value.as_slice()))?;
+        Ok(Some((response.context.slot, accounts)))
+      }
+      Err(e) if min_context_slot_not_reached(&e) => Ok(None),
+      Err(e) => Err(anyhow!("Failed to fetch accounts from RPC: {e}")),
+    }
+  }
+
+  /// Accounts paired with their pubkeys, in [`Self::pubkeys`] order.
+  #[must_use]
+  pub fn into_keyed(self) -> Vec<(Pubkey, Account)> {
+    let accounts = [
+      self.hylo,
+      self.jitosol_header,
+      self.hylosol_header,
+      self.hyusd_mint,
+      self.shyusd_mint,
+      self.xsol_mint,
+      self.pool_config,
+      self.hyusd_pool,
+      self.xsol_pool,
+      self.sol_usd_pyth,
+      self.clock,
+    ];
+    Self::pubkeys().into_iter().zip(accounts).collect()
+  }
+
   /// Validate that pubkeys and accounts match expected protocol accounts
   ///
   /// Validates:
@@ -120,6 +178,16 @@ impl ProtocolAccounts {
   }
 }
 
+fn min_context_slot_not_reached(error: &ClientError) -> bool {
+  matches!(
+    error.kind(),
+    ClientErrorKind::RpcError(RpcError::RpcResponseError {
+      code: JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+      ..
+    })
+  )
+}
+
 /// Convert from RPC response (pubkeys and accounts) to `ProtocolAccounts`
 ///
 /// Validates that:
@@ -0,0 +1,231 @@
+//! In-memory protocol account cache shared by streaming providers.
+//!
+//! Every cached copy carries the [`WriteVersion`] it was observed at, so that
+//! writes arriving out of order, or an RPC reseed racing a subscription, never
+//! replace an account with an older copy.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, ensure, Result};
+use tokio::sync::RwLock;
+
+use crate::protocol_state::ProtocolAccounts;
+
+/// Position of an account write: the slot, then the write version within the
+/// slot. RPC and WebSocket reads have no write version and use zero.
+pub(crate) type WriteVersion = (u64, u64);
+
+/// Latest known copy of each [`ProtocolAccounts`] key.
+#[derive(Clone)]
+pub(crate) struct AccountCache {
+  accounts: Arc<RwLock<HashMap<Pubkey, (Account, WriteVersion)>>>,
+  created_at: Instant,
+
+  /// Milliseconds after `created_at` of the last write
+  updated_at: Arc<AtomicU64>,
+}
+
+impl AccountCache {
+  /// Cache holding `accounts`, all observed at `slot`.
+  pub(crate) fn new(
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+    slot: u64,
+  ) -> AccountCache {
+    let accounts = accounts
+      .into_iter()
+      .map(|(pubkey, account)| (pubkey, (account, (slot, 0))))
+      .collect();
+    AccountCache {
+      accounts: Arc::new(RwLock::new(accounts)),
+      created_at: Instant::now(),
+      updated_at: Arc::new(AtomicU64::new(0)),
+    }
+  }
+
+  /// Seeds every protocol account with one `getMultipleAccounts` call.
+  pub(crate) async fn seed(rpc_client: &RpcClient) -> Result<AccountCache> {
+    let (slot, accounts) = ProtocolAccounts::fetch(rpc_client, None)
+      .await?
+      .ok_or(anyhow!("Protocol accounts unavailable from RPC"))?;
+    Ok(AccountCache::new(accounts.into_keyed(), slot))
+  }
+
+  /// Refreshes every protocol account from RPC, covering updates missed
+  /// while a subscription was down.
+  pub(crate) async fn reseed(&self, rpc_client: &RpcClient) -> Result<()> {
+    self.reseed_at(rpc_client, None).await.map(|_| ())
+  }
+
+  /// Refreshes every protocol account from a node that has processed
+  /// `min_context_slot`, returning whether the node had.
+  async fn reseed_at(
+    &self,
+    rpc_client: &RpcClient,
+    min_context_slot: Option<u64>,
+  ) -> Result<bool> {
+    let fetched = ProtocolAccounts::fetch(rpc_client, min_context_slot).await?;
+    match fetched {
+      Some((slot, accounts)) => {
+        let mut cache = self.accounts.write().await;
+        accounts
+          .into_keyed()
+          .into_iter()
+          .for_each(|(pubkey, account)| {
+            replace_if_newer(&mut cache, pubkey, account, (slot, 0));
+          });
+        self.touch();
+        Ok(true)
+      }
+      None => Ok(false),
+    }
+  }
+
+  /// Replaces the cached copy of `pubkey` if `version` is newer than the
+  /// cached one.
+  #[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+      level = "trace",
+      skip_all,
+      fields(%pubkey, lamports = account.lamports, len = account.data.len()),
+    )
+  )]
+  pub(crate) async fn insert(
+    &self,
+    pubkey: Pubkey,
+    account: Account,
+    version: WriteVersion,
+  ) {
+    let mut cache = self.accounts.write().await;
+    replace_if_newer(&mut cache, pubkey, account, version);
+    self.touch();
+  }
+
+  /// Time since the last write to the cache.
+  pub(crate) fn age(&self) -> Duration {
+    let updated_at =
+      Duration::from_millis(self.updated_at.load(Ordering::Relaxed));
+    self.created_at.elapsed().saturating_sub(updated_at)
+  }
+
+  /// Snapshot of cached accounts in [`ProtocolAccounts`] form.
+  pub(crate) async fn snapshot(&self) -> Result<ProtocolAccounts> {
+    let pubkeys = ProtocolAccounts::pubkeys();
+    let cache = self.accounts.read().await;
+    let accounts: Vec<Option<Account>> = pubkeys
+      .iter()
+      .map(|key| cache.get(key).map(|(account, _)| account.clone()))
+      .collect();
+    ProtocolAccounts::try_from((pubkeys.as_slice(), accounts.as_slice()))
+  }
+
+  /// Like [`Self::snapshot`], but refuses a cache not written to within
+  /// `max_age`. The clock sysvar changes every slot, so a live subscription
+  /// writes far more often than any sensible bound.
+  pub(crate) async fn fresh_snapshot(
+    &self,
+    max_age: Duration,
+  ) -> Result<ProtocolAccounts> {
+    let age = self.age();
+    ensure!(
+      age <= max_age,
+      "Account cache is stale: last update {}ms ago",
+      age.as_millis()
+    );
+    self.snapshot().await
+  }
+
+  /// Snapshot in which every account was observed at `slot` or later.
+  ///
+  /// An account the streams have not written since `slot` may be current or
+  /// may still be missing a write from `slot`, so unless every account has
+  /// been observed past it, the cache is reseeded from a node that has
+  /// processed `slot`. `None` while that node lags.
+  pub(crate) async fn snapshot_at(
+    &self,
+    rpc_client: &RpcClient,
+    slot: u64,
+  ) -> Result<Option<ProtocolAccounts>> {
+    let caught_up = self
+      .accounts
+      .read()
+      .await
+      .values()
+      .all(|(_, (observed, _))| *observed >= slot);
+    if caught_up || self.reseed_at(rpc_client, Some(slot)).await? {
+      self.snapshot().await.map(Some)
+    } else {
+      Ok(None)
+    }
+  }
+
+  fn touch(&self) {
+    let elapsed = self.created_at.elapsed().as_millis();
+    self.updated_at.store(
+      u64::try_from(elapsed).unwrap_or(u64::MAX),
+      Ordering::Relaxed,
+    );
+  }
+}
+
+fn replace_if_newer(
+  cache: &mut HashMap<Pubkey, (Account, WriteVersion)>,
+  pubkey: Pubkey,
+  account: Account,
+  version: WriteVersion,
+) {
+  let newer = cache
+    .get(&pubkey)
+    .is_none_or(|(_, cached)| version > *cached);
+  if newer {
+    cache.insert(pubkey, (account, version));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn account(lamports: u64) -> Account {
+    Account {
+      lamports,
+      ..Account::default()
+    }
+  }
+
+  async fn lamports(cache: &AccountCache, pubkey: &Pubkey) -> Option<u64> {
+    let accounts = cache.accounts.read().await;
+    accounts.get(pubkey).map(|(account, _)| account.lamports)
+  }
+
+  #[tokio::test]
+  async fn keeps_newest_write() {
+    let pubkey = Pubkey::new_unique();
+    let cache = AccountCache::new([(pubkey, account(1))], 10);
+    cache.insert(pubkey, account(2), (10, 5)).await;
+    assert_eq!(lamports(&cache, &pubkey).await, Some(2));
+    cache.insert(pubkey, account(3), (10, 4)).await;
+    assert_eq!(lamports(&cache, &pubkey).await, Some(2));
+    cache.insert(pubkey, account(4), (9, 100)).await;
+    assert_eq!(lamports(&cache, &pubkey).await, Some(2));
+    cache.insert(pubkey, account(5), (11, 0)).await;
+    assert_eq!(lamports(&cache, &pubkey).await, Some(5));
+  }
+
+  #[tokio::test]
+  async fn writes_reset_age() {
+    let cache = AccountCache::new([], 10);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(cache.age() >= Duration::from_millis(20));
+    cache
+      .insert(Pubkey::new_unique(), account(1), (10, 0))
+      .await;
+    assert!(cache.age() < Duration::from_millis(20));
+  }
+}
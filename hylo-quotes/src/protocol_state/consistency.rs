@@ -0,0 +1,131 @@
+//! Read-after-write consistency for protocol state.
+//!
+//! A trade lands at some slot, but lagging RPC nodes and streaming caches
+//! can keep serving the accounts from before it. [`ReadAfterWrite`] polls
+//! [`StateProvider::fetch_state_at`] until every protocol account reflects
+//! the transaction's slot, then returns that state, so bots quote their next
+//! trade against accounts that include their last.
+//!
+//! A recent clock sysvar alone proves nothing for caches: each account
+//! arrives on its own notification, and the clock's can overtake the trade's
+//! writes. Cache-backed providers track the slot each account was observed
+//! at, and [`RpcStateProvider`](super::RpcStateProvider) reads with
+//! `minContextSlot`, so only a node that has processed the slot answers.
+
+use std::time::Duration;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::signature::Signature;
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use hylo_core::exchange_context::ExchangeContext;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::{ProtocolState, StateProvider};
+
+/// Polls a state provider until it reflects a given slot.
+pub struct ReadAfterWrite<S> {
+  provider: S,
+  poll_interval: Duration,
+  timeout: Duration,
+}
+
+impl<S> ReadAfterWrite<S> {
+  /// Waits on `provider`, polling every 200ms for up to 30s.
+  #[must_use]
+  pub fn new(provider: S) -> ReadAfterWrite<S> {
+    ReadAfterWrite {
+      provider,
+      poll_interval: Duration::from_millis(200),
+      timeout: Duration::from_secs(30),
+    }
+  }
+
+  /// Interval between state fetches while the state lags.
+  #[must_use]
+  pub fn with_poll_interval(self, poll_interval: Duration) -> Self {
+    ReadAfterWrite {
+      poll_interval,
+      ..self
+    }
+  }
+
+  /// Longest wait before giving up on the state catching up.
+  #[must_use]
+  pub fn with_timeout(self, timeout: Duration) -> Self {
+    ReadAfterWrite { timeout, ..self }
+  }
+
+  /// Fetches state until every protocol account reflects `slot`.
+  ///
+  /// # Errors
+  /// * State fetch fails
+  /// * State still lags `slot` after the timeout
+  pub async fn state_at<C: SolanaClock>(
+    &self,
+    slot: u64,
+  ) -> Result<ProtocolState<C>>
+  where
+    S: StateProvider<C>,
+  {
+    let poll = stream::repeat(())
+      .map(Ok::<(), anyhow::Error>)
+      .and_then(|()| self.provider.fetch_state_at(slot))
+      .try_filter_map(|state| async move {
+        if state.is_none() {
+          tokio::time::sleep(self.poll_interval).await;
+        }
+        Ok(state)
+      })
+      .try_next();
+    tokio::time::timeout(self.timeout, poll)
+      .await
+      .map_err(|_| anyhow!("State did not reach slot {slot} in time"))??
+      .ok_or(anyhow!("State polling ended before slot {slot}"))
+  }
+
+  /// Exchange context at or past `slot`.
+  ///
+  /// # Errors
+  /// * See [`Self::state_at`]
+  pub async fn exchange_context_at<C: SolanaClock>(
+    &self,
+    slot: u64,
+  ) -> Result<ExchangeContext<C>>
+  where
+    S: StateProvider<C>,
+  {
+    self
+      .state_at(slot)
+      .await
+      .map(|state| state.exchange_context)
+  }
+
+  /// Exchange context reflecting the landed transaction `signature`.
+  ///
+  /// # Errors
+  /// * Signature status lookup fails
+  /// * Transaction has not landed or executed with an error
+  /// * See [`Self::state_at`]
+  pub async fn exchange_context_after<C: SolanaClock>(
+    &self,
+    rpc_client: &RpcClient,
+    signature: &Signature,
+  ) -> Result<ExchangeContext<C>>
+  where
+    S: StateProvider<C>,
+  {
+    let status = rpc_client
+      .get_signature_statuses(&[*signature])
+      .await?
+      .value
+      .into_iter()
+      .next()
+      .flatten()
+      .ok_or(anyhow!("Transaction {signature} has not landed"))?;
+    match status.err {
+      Some(err) => Err(anyhow!("Transaction {signature} failed: {err}")),
+      None => self.exchange_context_at(status.slot).await,
+    }
+  }
+}
@@ -0,0 +1,311 @@
+//! Yellowstone Geyser gRPC backend for the protocol account cache.
+//!
+//! Subscribes to account updates filtered to the [`ProtocolAccounts`] keys
+//! (Hylo PDAs, mints, pool token accounts, the Pyth feed, and the clock),
+//! pushed as soon as their slot is confirmed where WebSocket notifications
+//! lag.
+//!
+//! The cache keeps the write at the highest slot, so the subscription stays
+//! at confirmed commitment: a processed write from a fork that is later
+//! abandoned would sit above every canonical write until the account next
+//! changed.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::{Clock, Pubkey};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures::future;
+use futures::sink::{Sink, SinkExt};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use tokio::task::JoinHandle;
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+use yellowstone_grpc_proto::geyser::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::geyser::{
+  CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+  SubscribeRequestPing, SubscribeUpdate, SubscribeUpdateAccountInfo,
+};
+
+use crate::protocol_state::cache::AccountCache;
+use crate::protocol_state::{ProtocolAccounts, ProtocolState, StateProvider};
+
+/// Filter name attached to the account subscription.
+const ACCOUNTS_FILTER: &str = "hylo";
+
+/// Geyser endpoint and credentials.
+#[derive(Debug, Clone)]
+pub struct GeyserConfig {
+  /// gRPC endpoint URL
+  pub endpoint: String,
+
+  /// Optional `x-token` authentication header
+  pub x_token: Option<String>,
+}
+
+/// Request sink and update stream of one Geyser subscription.
+type Subscription = (
+  Pin<Box<dyn Sink<SubscribeRequest, Error = anyhow::Error> + Send>>,
+  BoxStream<'static, Result<SubscribeUpdate>>,
+);
+
+/// State provider serving protocol accounts from a Geyser-fed cache.
+///
+/// The subscription task answers server pings to keep the stream open
+/// through load balancers, and resubscribes after a delay when it drops
+/// anyway. Like [`WebsocketStateProvider`](super::WebsocketStateProvider),
+/// `fetch_state` errors once the cache is older than the staleness bound.
+pub struct GeyserStateProvider {
+  accounts: AccountCache,
+  rpc_client: Arc<RpcClient>,
+  subscription: JoinHandle<()>,
+  max_staleness: Duration,
+}
+
+impl GeyserStateProvider {
+  /// Seeds the cache over RPC and starts the Geyser subscription task, which
+  /// resubscribes 1s after the stream drops. Cached state is served for up
+  /// to 10s after the last update.
+  ///
+  /// # Errors
+  /// * Initial account fetch fails or an account is missing
+  /// * gRPC connection or subscription fails
+  pub async fn connect(
+    rpc_client: &RpcClient,
+    config: GeyserConfig,
+  ) -> Result<Self> {
+    let accounts = AccountCache::seed(rpc_client).await?;
+    let subscription = open(&config).await?;
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(
+      rpc_client.url(),
+      rpc_client.commitment(),
+    ));
+    let subscription = tokio::spawn(maintain(
+      subscription,
+      config,
+      rpc_client.clone(),
+      accounts.clone(),
+      Duration::from_secs(1),
+    ));
+    Ok(Self {
+      accounts,
+      rpc_client,
+      subscription,
+      max_staleness: Duration::from_secs(10),
+    })
+  }
+
+  /// Longest time since the last update that cached state is served.
+  #[must_use]
+  pub fn with_max_staleness(self, max_staleness: Duration) -> Self {
+    GeyserStateProvider {
+      max_staleness,
+      ..self
+    }
+  }
+
+  /// Whether the subscription task is running and the cache is within the
+  /// staleness bound.
+  #[must_use]
+  pub fn is_live(&self) -> bool {
+    !self.subscription.is_finished()
+      && self.accounts.age() <= self.max_staleness
+  }
+
+  /// Snapshot of the cached accounts in [`ProtocolAccounts`] form.
+  ///
+  /// # Errors
+  /// * Cached account set is incomplete
+  pub async fn accounts(&self) -> Result<ProtocolAccounts> {
+    self.accounts.snapshot().await
+  }
+}
+
+impl Drop for GeyserStateProvider {
+  fn drop(&mut self) {
+    self.subscription.abort();
+  }
+}
+
+#[async_trait]
+impl StateProvider<Clock> for GeyserStateProvider {
+  async fn fetch_state(&self) -> Result<ProtocolState<Clock>> {
+    let accounts = self.accounts.fresh_snapshot(self.max_staleness).await?;
+    ProtocolState::try_from(&accounts)
+  }
+
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<Clock>>> {
+    self
+      .accounts
+      .snapshot_at(&self.rpc_client, slot)
+      .await?
+      .map(|accounts| ProtocolState::try_from(&accounts))
+      .transpose()
+  }
+}
+
+/// Connects to the Geyser endpoint and opens the account subscription.
+async fn open(config: &GeyserConfig) -> Result<Subscription> {
+  let mut client =
+    GeyserGrpcClient::build_from_shared(config.endpoint.clone())?
+      .x_token(config.x_token.clone())?
+      .tls_config(ClientTlsConfig::new().with_native_roots())?
+      .connect()
+      .await
+      .context("Failed to connect to Geyser endpoint")?;
+  let (sink, stream) = client
+    .subscribe_with_request(Some(subscribe_request()))
+    .await
+    .context("Failed to subscribe to Geyser account updates")?;
+  let sink = sink.sink_map_err(anyhow::Error::from);
+  let stream =
+    stream.map_err(|status| anyhow!("Geyser stream error: {status}"));
+  Ok((Box::pin(sink), stream.boxed()))
+}
+
+/// Streams updates from `subscription` into the cache, then resubscribes
+/// every `retry_delay` for as long as the provider lives.
+async fn maintain(
+  subscription: Subscription,
+  config: GeyserConfig,
+  rpc_client: Arc<RpcClient>,
+  accounts: AccountCache,
+  retry_delay: Duration,
+) {
+  let (config, rpc_client, accounts) = (&config, &*rpc_client, &accounts);
+  let resubscriptions = stream::repeat(()).then(|()| async move {
+    tokio::time::sleep(retry_delay).await;
+    open(config).await
+  });
+  stream::once(future::ready(Ok(subscription)))
+    .chain(resubscriptions)
+    .for_each(|subscription| async move {
+      let closed = match subscription {
+        Ok(subscription) => {
+          stream_updates(subscription, rpc_client, accounts).await
+        }
+        Err(e) => Err(e),
+      };
+      #[cfg(feature = "tracing")]
+      if let Err(e) = closed {
+        tracing::warn!("Geyser subscription down, resubscribing: {e:#}");
+      }
+      #[cfg(not(feature = "tracing"))]
+      drop(closed);
+    })
+    .await;
+}
+
+/// Writes account updates into the cache and answers pings until the stream
+/// ends. The cache is reseeded first, so updates missed before a
+/// resubscription are not lost.
+async fn stream_updates(
+  (sink, updates): Subscription,
+  rpc_client: &RpcClient,
+  accounts: &AccountCache,
+) -> Result<()> {
+  accounts.reseed(rpc_client).await?;
+  updates
+    .try_fold(sink, |mut sink, update| async move {
+      match update.update_oneof {
+        Some(UpdateOneof::Account(update)) => {
+          if let Some(info) = update.account {
+            let version = (update.slot, info.write_version);
+            let (pubkey, account) = decode_account(info)?;
+            accounts.insert(pubkey, account, version).await;
+          }
+        }
+        Some(UpdateOneof::Ping(_)) => sink.send(ping_request()).await?,
+        _ => {}
+      }
+      Ok(sink)
+    })
+    .await?;
+  Err(anyhow!("Geyser stream closed"))
+}
+
+/// Account subscription filtered to every protocol account key.
+fn subscribe_request() -> SubscribeRequest {
+  let filter = SubscribeRequestFilterAccounts {
+    account: ProtocolAccounts::pubkeys()
+      .iter()
+      .map(ToString::to_string)
+      .collect(),
+    ..Default::default()
+  };
+  SubscribeRequest {
+    accounts: HashMap::from([(ACCOUNTS_FILTER.to_string(), filter)]),
+    commitment: Some(CommitmentLevel::Confirmed.into()),
+    ..Default::default()
+  }
+}
+
+/// Reply to a server ping, keeping idle streams open.
+fn ping_request() -> SubscribeRequest {
+  SubscribeRequest {
+    ping: Some(SubscribeRequestPing { id: 1 }),
+    ..Default::default()
+  }
+}
+
+fn decode_account(
+  info: SubscribeUpdateAccountInfo,
+) -> Result<(Pubkey, Account)> {
+  let pubkey = Pubkey::try_from(info.pubkey.as_slice())
+    .map_err(|_| anyhow!("Invalid account pubkey in Geyser update"))?;
+  let owner = Pubkey::try_from(info.owner.as_slice())
+    .map_err(|_| anyhow!("Invalid owner pubkey in Geyser update"))?;
+  let account = Account {
+    lamports: info.lamports,
+    data: info.data,
+    owner,
+    executable: info.executable,
+    rent_epoch: info.rent_epoch,
+  };
+  Ok((pubkey, account))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn request_filters_protocol_accounts() {
+    let request = subscribe_request();
+    let filter = &request.accounts[ACCOUNTS_FILTER];
+    assert_eq!(filter.account.len(), ProtocolAccounts::expected_count());
+  }
+
+  #[test]
+  fn ping_reply_carries_no_filters() {
+    let request = ping_request();
+    assert!(request.ping.is_some());
+    assert!(request.accounts.is_empty());
+  }
+
+  #[test]
+  fn decodes_account_update() -> Result<()> {
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let info = SubscribeUpdateAccountInfo {
+      pubkey: pubkey.to_bytes().to_vec(),
+      lamports: 42,
+      owner: owner.to_bytes().to_vec(),
+      data: vec![1, 2, 3],
+      ..Default::default()
+    };
+    let (decoded_key, account) = decode_account(info)?;
+    assert_eq!(decoded_key, pubkey);
+    assert_eq!(account.owner, owner);
+    assert_eq!(account.lamports, 42);
+    assert_eq!(account.data, vec![1, 2, 3]);
+    Ok(())
+  }
+}
@@ -1,7 +1,19 @@
 mod accounts;
+mod cache;
+mod consistency;
+#[cfg(feature = "geyser")]
+mod geyser;
 mod provider;
 mod state;
+mod websocket;
 
 pub use accounts::ProtocolAccounts;
-pub use provider::{RpcStateProvider, StateProvider};
+pub use consistency::ReadAfterWrite;
+#[cfg(feature = "geyser")]
+pub use geyser::{GeyserConfig, GeyserStateProvider};
+pub use provider::{
+  FeeOverrideProvider, RpcStateProvider, SnapshotStateProvider,
+  StakePoolRefreshProvider, StateProvider,
+};
 pub use state::ProtocolState;
+pub use websocket::WebsocketStateProvider;
@@ -8,9 +8,12 @@ use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
 use anchor_lang::prelude::Clock;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use hylo_core::fee_override::FeeOverride;
 use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{HYLOSOL, JITOSOL};
 
 use crate::protocol_state::{ProtocolAccounts, ProtocolState};
+use crate::LST;
 
 /// Trait for fetching protocol state from a data source
 #[async_trait]
@@ -20,6 +23,23 @@ pub trait StateProvider<C: SolanaClock>: Send + Sync {
   /// # Errors
   /// Returns error if state fetching fails.
   async fn fetch_state(&self) -> Result<ProtocolState<C>>;
+
+  /// Fetch state in which every protocol account reflects `slot` or later,
+  /// or `None` if the source has not caught up yet.
+  ///
+  /// The default checks the state's clock sysvar, which covers the other
+  /// accounts only for sources reading them all at one slot. Sources that
+  /// can lag per account override it.
+  ///
+  /// # Errors
+  /// Returns error if state fetching fails.
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<C>>> {
+    let state = self.fetch_state().await?;
+    Ok((state.exchange_context.clock().slot() >= slot).then_some(state))
+  }
 }
 
 // Implement StateProvider for Arc<T> where T: StateProvider
@@ -30,6 +50,39 @@ impl<T: StateProvider<C>, C: SolanaClock> StateProvider<C>
   async fn fetch_state(&self) -> Result<ProtocolState<C>> {
     (**self).fetch_state().await
   }
+
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<C>>> {
+    (**self).fetch_state_at(slot).await
+  }
+}
+
+// ============================================================================
+// SNAPSHOT STATE PROVIDER
+// ============================================================================
+
+/// State provider serving one already fetched state, so that several quotes
+/// and their context are computed against the same snapshot.
+pub struct SnapshotStateProvider<C: SolanaClock> {
+  state: ProtocolState<C>,
+}
+
+impl<C: SolanaClock> SnapshotStateProvider<C> {
+  #[must_use]
+  pub fn new(state: ProtocolState<C>) -> Self {
+    Self { state }
+  }
+}
+
+#[async_trait]
+impl<C: SolanaClock + Clone + Send + Sync> StateProvider<C>
+  for SnapshotStateProvider<C>
+{
+  async fn fetch_state(&self) -> Result<ProtocolState<C>> {
+    Ok(self.state.clone())
+  }
 }
 
 // ============================================================================
@@ -67,6 +120,151 @@ impl StateProvider<Clock> for RpcStateProvider {
     ))?;
     ProtocolState::try_from(&accounts)
   }
+
+  /// Reads from a node that has processed `slot`, so every account reflects
+  /// it.
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<Clock>>> {
+    ProtocolAccounts::fetch(&self.rpc_client, Some(slot))
+      .await?
+      .map(|(_, accounts)| ProtocolState::try_from(&accounts))
+      .transpose()
+  }
+}
+
+// ============================================================================
+// STAKE POOL REFRESH PROVIDER
+// ============================================================================
+
+/// State provider that, during an epoch transition, replaces lagging LST
+/// header prices with estimates from their stake pool accounts.
+///
+/// Pair with [`crate::EpochMode::Refresh`] so that quotes proceed on the
+/// refreshed prices. Stake pools not yet updated for the epoch are left
+/// stale, and quotes involving them still report the transition.
+pub struct StakePoolRefreshProvider<S> {
+  inner: S,
+  rpc_client: Arc<RpcClient>,
+}
+
+impl<S> StakePoolRefreshProvider<S> {
+  /// Wraps `inner`, fetching stake pool accounts with `rpc_client`.
+  #[must_use]
+  pub fn new(inner: S, rpc_client: Arc<RpcClient>) -> Self {
+    Self { inner, rpc_client }
+  }
+
+  /// Refreshes lagging LST prices of `state` from their stake pools.
+  async fn refresh(
+    &self,
+    state: ProtocolState<Clock>,
+  ) -> Result<ProtocolState<Clock>> {
+    let stale = state
+      .epoch_transition
+      .as_ref()
+      .is_some_and(|transition| !transition.stale_lsts.is_empty());
+    if stale {
+      let pubkeys = [
+        state.jitosol_header.pool_state,
+        state.hylosol_header.pool_state,
+      ];
+      let pools = self
+        .rpc_client
+        .get_multiple_accounts(&pubkeys)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch stake pools from RPC: {e}"))?;
+      match pools.as_slice() {
+        [Some(jitosol_pool), Some(hylosol_pool)] => {
+          let state = refresh_lst::<JITOSOL>(state, &jitosol_pool.data);
+          Ok(refresh_lst::<HYLOSOL>(state, &hylosol_pool.data))
+        }
+        _ => Err(anyhow!("Stake pool account not found")),
+      }
+    } else {
+      Ok(state)
+    }
+  }
+}
+
+#[async_trait]
+impl<S: StateProvider<Clock>> StateProvider<Clock>
+  for StakePoolRefreshProvider<S>
+{
+  async fn fetch_state(&self) -> Result<ProtocolState<Clock>> {
+    let state = self.inner.fetch_state().await?;
+    self.refresh(state).await
+  }
+
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<Clock>>> {
+    match self.inner.fetch_state_at(slot).await? {
+      Some(state) => self.refresh(state).await.map(Some),
+      None => Ok(None),
+    }
+  }
+}
+
+/// Applies the stake pool fallback for `L`, keeping `state` unchanged if the
+/// stake pool cannot price the current epoch.
+fn refresh_lst<L: LST>(
+  state: ProtocolState<Clock>,
+  stake_pool_data: &[u8],
+) -> ProtocolState<Clock> {
+  state
+    .clone()
+    .with_stake_pool_fallback::<L>(stake_pool_data)
+    .unwrap_or(state)
+}
+
+// ============================================================================
+// FEE OVERRIDE PROVIDER
+// ============================================================================
+
+/// State provider wrapping another, quoting with overridden fee tables to
+/// preview a fee change before it is proposed on-chain.
+pub struct FeeOverrideProvider<S> {
+  inner: S,
+  fee_override: FeeOverride,
+}
+
+impl<S> FeeOverrideProvider<S> {
+  /// Wraps `inner`, applying `fee_override` to every fetched state.
+  #[must_use]
+  pub fn new(inner: S, fee_override: FeeOverride) -> Self {
+    Self {
+      inner,
+      fee_override,
+    }
+  }
+}
+
+#[async_trait]
+impl<S: StateProvider<C>, C: SolanaClock> StateProvider<C>
+  for FeeOverrideProvider<S>
+{
+  async fn fetch_state(&self) -> Result<ProtocolState<C>> {
+    self
+      .inner
+      .fetch_state()
+      .await?
+      .with_fee_override(&self.fee_override)
+  }
+
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<C>>> {
+    self
+      .inner
+      .fetch_state_at(slot)
+      .await?
+      .map(|state| state.with_fee_override(&self.fee_override))
+      .transpose()
+  }
 }
 
 #[cfg(test)]
@@ -99,16 +297,18 @@ mod tests {
 
     // Verify timestamp is set and matches clock
     assert!(state.fetched_at > 0);
-    let clock_timestamp = state.exchange_context.clock.unix_timestamp();
+    let clock_timestamp = state.exchange_context.clock().unix_timestamp();
     assert_eq!(state.fetched_at, clock_timestamp);
 
     // Verify exchange context has valid data
-    assert!(state.exchange_context.total_sol > UFix64::<N9>::zero());
-    assert!(state.exchange_context.collateral_ratio > UFix64::<N9>::zero());
-    assert!(state.exchange_context.sol_usd_price.lower > UFix64::<N8>::zero());
+    assert!(state.exchange_context.total_sol() > UFix64::<N9>::zero());
+    assert!(state.exchange_context.collateral_ratio() > UFix64::<N9>::zero());
+    assert!(
+      state.exchange_context.sol_usd_price().lower > UFix64::<N8>::zero()
+    );
     assert!(
-      state.exchange_context.sol_usd_price.upper
-        >= state.exchange_context.sol_usd_price.lower
+      state.exchange_context.sol_usd_price().upper
+        >= state.exchange_context.sol_usd_price().lower
     );
 
     // Verify mint accounts have valid data
@@ -117,6 +317,6 @@ mod tests {
     assert!(state.shyusd_mint.decimals > 0);
 
     // Verify clock has reasonable values (slot is u64, so just check it's set)
-    assert!(state.exchange_context.clock.slot() > 0);
+    assert!(state.exchange_context.clock().slot() > 0);
   }
 }
@@ -4,24 +4,33 @@
 //! accounts.
 
 use anchor_client::solana_sdk::clock::{Clock, UnixTimestamp};
+use anchor_lang::prelude::Pubkey;
 use anchor_lang::AccountDeserialize;
 use anyhow::{anyhow, Result};
+use fix::prelude::{UFix64, N6};
+use hylo_core::bootstrap::Bootstrap;
 use hylo_core::exchange_context::ExchangeContext;
 use hylo_core::fee_controller::{LevercoinFees, StablecoinFees};
+use hylo_core::fee_override::FeeOverride;
 use hylo_core::idl::exchange::accounts::{Hylo, LstHeader};
+use hylo_core::idl::exchange::types::LstSolPrice as IdlLstSolPrice;
 use hylo_core::idl::stability_pool::accounts::PoolConfig;
 use hylo_core::lst_swap_config::LstSwapConfig;
 use hylo_core::pyth::OracleConfig;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_core::stability_mode::StabilityController;
+use hylo_core::stake_pool::StakePoolSnapshot;
 use hylo_core::total_sol_cache::TotalSolCache;
 use hylo_idl::tokens::{TokenMint, HYLOSOL, JITOSOL};
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use solana_program_pack::Pack;
 use spl_token_interface::state::{Account as TokenAccount, Mint};
 
+use crate::deposit_cap::DepositCap;
+use crate::epoch_mode::{EpochMode, EpochTransition};
+use crate::mode_policy::ModePolicy;
 use crate::protocol_state::ProtocolAccounts;
-use crate::LST;
+use crate::{Operation, LST};
 
 /// Complete snapshot of Hylo protocol state
 #[derive(Clone)]
@@ -56,16 +65,53 @@ pub struct ProtocolState<C: SolanaClock> {
   /// Timestamp of when this state was fetched
   pub fetched_at: UnixTimestamp,
 
+  /// Publish time of the SOL/USD price the state was loaded with
+  pub oracle_publish_time: UnixTimestamp,
+
+  /// Maximum SOL/USD price age the program accepts, in seconds
+  pub oracle_interval_secs: u64,
+
   /// LST swap configuration
   pub lst_swap_config: LstSwapConfig,
+
+  /// LST mints whose header price was estimated from stake pool state
+  pub estimated_lst_prices: Vec<Pubkey>,
+
+  /// Stability mode gating applied to operations quoted from this state
+  pub mode_policy: ModePolicy,
+
+  /// Lagging cache and LST prices, if the state sits in an epoch transition
+  pub epoch_transition: Option<EpochTransition>,
+
+  /// Treatment of quotes during an epoch transition
+  pub epoch_mode: EpochMode,
+
+  /// Limit on stability pool capitalization applied to deposit quotes
+  pub deposit_cap: Option<DepositCap>,
 }
 
 impl<C: SolanaClock> ProtocolState<C> {
   /// Build `ProtocolState` from deserialized accounts and a clock.
   ///
+  /// A total SOL cache lagging the clock epoch is loaded as is and recorded
+  /// in [`ProtocolState::epoch_transition`] rather than failing the build.
+  ///
   /// # Errors
   /// * Propagates errors from `ExchangeContext::load`.
   #[allow(clippy::too_many_arguments)]
+  #[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+      skip_all,
+      err,
+      fields(
+        slot = clock.slot(),
+        epoch = clock.epoch(),
+        mode = tracing::field::Empty,
+        collateral_ratio = tracing::field::Empty,
+      ),
+    )
+  )]
   pub fn build(
     clock: C,
     hylo: &Hylo,
@@ -80,7 +126,18 @@ impl<C: SolanaClock> ProtocolState<C> {
     sol_usd: &PriceUpdateV2,
   ) -> Result<Self> {
     let fetched_at = clock.unix_timestamp();
-    let total_sol_cache: TotalSolCache = hylo.total_sol_cache.into();
+    let epoch = clock.epoch();
+    let lagging_cache: TotalSolCache = hylo.total_sol_cache.into();
+    let epoch_transition = EpochTransition::detect(
+      epoch,
+      lagging_cache.current_update_epoch,
+      [&jitosol_header, &hylosol_header]
+        .map(|header| (header.mint, header.price_sol.epoch)),
+    );
+    let total_sol_cache = TotalSolCache {
+      current_update_epoch: epoch.max(lagging_cache.current_update_epoch),
+      ..lagging_cache
+    };
     let oracle_config = OracleConfig::new(
       hylo.oracle_interval_secs,
       hylo.oracle_conf_tolerance.try_into()?,
@@ -103,6 +160,16 @@ impl<C: SolanaClock> ProtocolState<C> {
       &hyusd_mint,
       Some(&xsol_mint),
     )?;
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+      .record(
+        "mode",
+        tracing::field::display(exchange_context.stability_mode()),
+      )
+      .record(
+        "collateral_ratio",
+        hylo_core::ui_units::to_f64(exchange_context.collateral_ratio()),
+      );
     Ok(Self {
       exchange_context,
       jitosol_header,
@@ -114,7 +181,14 @@ impl<C: SolanaClock> ProtocolState<C> {
       hyusd_pool,
       xsol_pool,
       fetched_at,
+      oracle_publish_time: sol_usd.price_message.publish_time,
+      oracle_interval_secs: hylo.oracle_interval_secs,
       lst_swap_config,
+      estimated_lst_prices: Vec::new(),
+      mode_policy: ModePolicy::default(),
+      epoch_transition,
+      epoch_mode: EpochMode::default(),
+      deposit_cap: None,
     })
   }
 
@@ -129,6 +203,163 @@ impl<C: SolanaClock> ProtocolState<C> {
       _ => Err(anyhow!("LstHeader not found for {}", L::MINT)),
     }
   }
+
+  /// Mutable counterpart to [`ProtocolState::lst_header`].
+  ///
+  /// # Errors
+  /// * LST does not have a corresponding header field in this struct
+  pub fn lst_header_mut<L: LST>(&mut self) -> Result<&mut LstHeader> {
+    match L::MINT {
+      JITOSOL::MINT => Ok(&mut self.jitosol_header),
+      HYLOSOL::MINT => Ok(&mut self.hylosol_header),
+      _ => Err(anyhow!("LstHeader not found for {}", L::MINT)),
+    }
+  }
+
+  /// Replaces an LST price lagging the current epoch with one computed from
+  /// its stake pool account, and flags the LST as estimated.
+  /// Leaves the header untouched if its cached price is already current.
+  ///
+  /// Estimated quotes are indicative: the program rejects the operation until
+  /// the `update_lst_prices` crank has run for the epoch.
+  ///
+  /// # Errors
+  /// * LST header not found
+  /// * Stake pool data malformed or not updated for the current epoch
+  pub fn with_stake_pool_fallback<L: LST>(
+    mut self,
+    stake_pool_data: &[u8],
+  ) -> Result<Self> {
+    let epoch = self.exchange_context.clock().epoch();
+    let header = self.lst_header_mut::<L>()?;
+    if header.price_sol.epoch < epoch {
+      let estimate = StakePoolSnapshot::try_from_spl_bytes(stake_pool_data)?
+        .estimate_epoch_price(epoch)?;
+      header.price_sol = IdlLstSolPrice {
+        price: estimate.price.into(),
+        epoch: estimate.epoch,
+      };
+      self.estimated_lst_prices.push(L::MINT);
+    }
+    Ok(self)
+  }
+
+  /// Whether quotes involving `L` use an estimated price.
+  #[must_use]
+  pub fn is_price_estimated<L: LST>(&self) -> bool {
+    self.estimated_lst_prices.contains(&L::MINT)
+  }
+
+  /// Replaces the stability mode policy, e.g. with [`ModePolicy::Override`]
+  /// to quote operations the program currently blocks.
+  #[must_use]
+  pub fn with_mode_policy(self, mode_policy: ModePolicy) -> Self {
+    Self {
+      mode_policy,
+      ..self
+    }
+  }
+
+  /// Checks `operation` against the mode policy in the current stability
+  /// mode.
+  ///
+  /// # Errors
+  /// * Operation is blocked in the current stability mode
+  pub fn check_mode(&self, operation: Operation) -> Result<()> {
+    self
+      .mode_policy
+      .check(operation, self.exchange_context.stability_mode())
+  }
+
+  /// Replaces the epoch mode, e.g. with [`EpochMode::Refresh`] to quote on
+  /// stake pool prices during an epoch transition.
+  #[must_use]
+  pub fn with_epoch_mode(self, epoch_mode: EpochMode) -> Self {
+    Self { epoch_mode, ..self }
+  }
+
+  /// Checks the state against the epoch mode.
+  ///
+  /// # Errors
+  /// * [`EpochTransition`] while cached prices lag the current epoch
+  pub fn check_epoch(&self) -> Result<()> {
+    self
+      .epoch_mode
+      .check(self.epoch_transition.as_ref(), &self.estimated_lst_prices)
+  }
+
+  /// Checks that `operation` can be quoted from this state, against both the
+  /// epoch mode and the stability mode policy.
+  ///
+  /// # Errors
+  /// * [`EpochTransition`] while cached prices lag the current epoch
+  /// * Operation is blocked in the current stability mode
+  pub fn check_operation(&self, operation: Operation) -> Result<()> {
+    self.check_epoch()?;
+    self.check_mode(operation)
+  }
+
+  /// Quotes with the fee tables of `fee_override` in place of the on-chain
+  /// ones, to preview a fee change.
+  ///
+  /// # Errors
+  /// * An overriding fee is 100% or more
+  pub fn with_fee_override(self, fee_override: &FeeOverride) -> Result<Self> {
+    let exchange_context = fee_override.apply(self.exchange_context)?;
+    Ok(Self {
+      exchange_context,
+      ..self
+    })
+  }
+
+  /// Sets a limit on stability pool capitalization for deposit quotes.
+  #[must_use]
+  pub fn with_deposit_cap(self, deposit_cap: Option<DepositCap>) -> Self {
+    Self {
+      deposit_cap,
+      ..self
+    }
+  }
+
+  /// Stablecoin the stability pool can take before reaching its deposit cap,
+  /// or `None` without a cap.
+  ///
+  /// # Errors
+  /// * Pool capitalization or NAV arithmetic
+  pub fn remaining_deposit_capacity(&self) -> Result<Option<UFix64<N6>>> {
+    self
+      .deposit_cap
+      .map(|cap| {
+        self.exchange_context.remaining_deposit_capacity(
+          UFix64::new(self.hyusd_pool.amount),
+          UFix64::new(self.xsol_pool.amount),
+          cap.limit,
+        )
+      })
+      .transpose()
+      .map_err(Into::into)
+  }
+
+  /// Amount of a stablecoin deposit to quote under the deposit cap.
+  ///
+  /// # Errors
+  /// * [`crate::DepositCapExceeded`] if the deposit does not fit the cap
+  pub fn check_deposit(&self, amount: UFix64<N6>) -> Result<UFix64<N6>> {
+    match (self.deposit_cap, self.remaining_deposit_capacity()?) {
+      (Some(cap), Some(remaining)) => cap.apply(amount, remaining),
+      _ => Ok(amount),
+    }
+  }
+
+  /// Which protocol tokens have zero supply and quote at initial NAV.
+  #[must_use]
+  pub fn bootstrap(&self) -> Bootstrap {
+    Bootstrap::new(
+      UFix64::new(self.hyusd_mint.supply),
+      UFix64::new(self.xsol_mint.supply),
+      UFix64::new(self.shyusd_mint.supply),
+    )
+  }
 }
 
 impl TryFrom<&ProtocolAccounts> for ProtocolState<Clock> {
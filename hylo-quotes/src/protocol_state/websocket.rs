@@ -0,0 +1,195 @@
+//! WebSocket-backed account cache for protocol state.
+//!
+//! Seeds every [`ProtocolAccounts`] key with one `getMultipleAccounts` call,
+//! then keeps them current through `accountSubscribe` notifications so that
+//! [`StateProvider::fetch_state`] never touches RPC on the hot path.
+//!
+//! When the socket drops, the subscription task reconnects after a delay and
+//! reseeds the cache over RPC to cover the gap. Until then the cache stops
+//! receiving updates, and `fetch_state` errors once it is older than the
+//! staleness bound rather than serving outdated accounts.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use anchor_client::solana_client::nonblocking::pubsub_client::PubsubClient;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcAccountInfoConfig;
+use anchor_client::solana_sdk::account::Account;
+use anchor_lang::prelude::Clock;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::{self, try_join_all};
+use futures::stream::{self, select_all, StreamExt};
+use tokio::task::JoinHandle;
+
+use crate::protocol_state::cache::AccountCache;
+use crate::protocol_state::{ProtocolAccounts, ProtocolState, StateProvider};
+
+/// State provider serving protocol accounts from a WebSocket-fed cache.
+pub struct WebsocketStateProvider {
+  accounts: AccountCache,
+  rpc_client: Arc<RpcClient>,
+  subscription: JoinHandle<()>,
+  max_staleness: Duration,
+}
+
+impl WebsocketStateProvider {
+  /// Seeds the cache over RPC and starts the subscription task, which
+  /// resubscribes 1s after the socket drops. Cached state is served for up
+  /// to 10s after the last notification.
+  ///
+  /// # Arguments
+  /// * `rpc_client` - Solana RPC client for the initial snapshot, whose URL and
+  ///   commitment are reused to reseed the cache
+  /// * `ws_url` - WebSocket endpoint for account notifications
+  ///
+  /// # Errors
+  /// * Initial account fetch fails or an account is missing
+  /// * WebSocket connection fails
+  pub async fn connect(rpc_client: &RpcClient, ws_url: &str) -> Result<Self> {
+    let accounts = AccountCache::seed(rpc_client).await?;
+    let pubsub = PubsubClient::new(ws_url)
+      .await
+      .map_err(|e| anyhow!("Failed to connect to {ws_url}: {e}"))?;
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(
+      rpc_client.url(),
+      rpc_client.commitment(),
+    ));
+    let subscription = tokio::spawn(maintain(
+      pubsub,
+      ws_url.to_string(),
+      rpc_client.clone(),
+      accounts.clone(),
+      Duration::from_secs(1),
+    ));
+    Ok(Self {
+      accounts,
+      rpc_client,
+      subscription,
+      max_staleness: Duration::from_secs(10),
+    })
+  }
+
+  /// Longest time since the last notification that cached state is served.
+  #[must_use]
+  pub fn with_max_staleness(self, max_staleness: Duration) -> Self {
+    WebsocketStateProvider {
+      max_staleness,
+      ..self
+    }
+  }
+
+  /// Whether the subscription task is running and the cache is within the
+  /// staleness bound.
+  #[must_use]
+  pub fn is_live(&self) -> bool {
+    !self.subscription.is_finished()
+      && self.accounts.age() <= self.max_staleness
+  }
+
+  /// Snapshot of the cached accounts in [`ProtocolAccounts`] form.
+  ///
+  /// # Errors
+  /// * Cached account set is incomplete
+  pub async fn accounts(&self) -> Result<ProtocolAccounts> {
+    self.accounts.snapshot().await
+  }
+}
+
+impl Drop for WebsocketStateProvider {
+  fn drop(&mut self) {
+    self.subscription.abort();
+  }
+}
+
+#[async_trait]
+impl StateProvider<Clock> for WebsocketStateProvider {
+  async fn fetch_state(&self) -> Result<ProtocolState<Clock>> {
+    let accounts = self.accounts.fresh_snapshot(self.max_staleness).await?;
+    ProtocolState::try_from(&accounts)
+  }
+
+  async fn fetch_state_at(
+    &self,
+    slot: u64,
+  ) -> Result<Option<ProtocolState<Clock>>> {
+    self
+      .accounts
+      .snapshot_at(&self.rpc_client, slot)
+      .await?
+      .map(|accounts| ProtocolState::try_from(&accounts))
+      .transpose()
+  }
+}
+
+/// Streams notifications from `pubsub` into the cache, then reconnects
+/// every `retry_delay` for as long as the provider lives.
+async fn maintain(
+  pubsub: PubsubClient,
+  ws_url: String,
+  rpc_client: Arc<RpcClient>,
+  accounts: AccountCache,
+  retry_delay: Duration,
+) {
+  let (ws_url, rpc_client, accounts) = (&ws_url, &*rpc_client, &accounts);
+  let reconnects = stream::repeat(()).then(|()| async move {
+    tokio::time::sleep(retry_delay).await;
+    PubsubClient::new(ws_url)
+      .await
+      .map_err(|e| anyhow!("Failed to reconnect to {ws_url}: {e}"))
+  });
+  stream::once(future::ready(Ok(pubsub)))
+    .chain(reconnects)
+    .for_each(|pubsub| async move {
+      let closed = match pubsub {
+        Ok(pubsub) => subscribe(pubsub, rpc_client, accounts).await,
+        Err(e) => Err(e),
+      };
+      #[cfg(feature = "tracing")]
+      if let Err(e) = closed {
+        tracing::warn!("Account subscription down, resubscribing: {e:#}");
+      }
+      #[cfg(not(feature = "tracing"))]
+      drop(closed);
+    })
+    .await;
+}
+
+/// Writes account notifications into the cache until the socket closes.
+///
+/// The cache is reseeded once every subscription is open, so updates missed
+/// before a reconnect are not lost.
+async fn subscribe(
+  pubsub: PubsubClient,
+  rpc_client: &RpcClient,
+  accounts: &AccountCache,
+) -> Result<()> {
+  let config = RpcAccountInfoConfig {
+    encoding: Some(UiAccountEncoding::Base64),
+    commitment: Some(rpc_client.commitment()),
+    ..Default::default()
+  };
+  let pubkeys = ProtocolAccounts::pubkeys();
+  let (pubsub, config) = (&pubsub, &config);
+  let streams = try_join_all(pubkeys.iter().map(|&key| async move {
+    let (stream, _unsubscribe) = pubsub
+      .account_subscribe(&key, Some(config.clone()))
+      .await
+      .map_err(|e| anyhow!("Failed to subscribe to {key}: {e}"))?;
+    Ok::<_, anyhow::Error>(
+      stream.map(move |response| (key, response.context.slot, response.value)),
+    )
+  }))
+  .await?;
+  accounts.reseed(rpc_client).await?;
+  select_all(streams)
+    .for_each(|(pubkey, slot, ui_account)| async move {
+      if let Some(account) = ui_account.decode::<Account>() {
+        accounts.insert(pubkey, account, (slot, 0)).await;
+      }
+    })
+    .await;
+  Err(anyhow!("Account subscription stream closed"))
+}
@@ -9,7 +9,7 @@ use hylo_clients::syntax_helpers::InstructionBuilderExt;
 use hylo_clients::transaction::{LstSwapArgs, MintArgs, RedeemArgs, SwapArgs};
 use hylo_core::slippage_config::SlippageConfig;
 use hylo_core::solana_clock::SolanaClock;
-use hylo_idl::tokens::{HYUSD, XSOL};
+use hylo_idl::tokens::{TokenMint, HYUSD, XSOL};
 
 use crate::protocol_state::{ProtocolState, StateProvider};
 use crate::protocol_state_strategy::ProtocolStateStrategy;
@@ -39,7 +39,7 @@ where
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<MintQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.compute_output(UFix64::new(amount_in))?;
     let args = MintArgs {
       amount: UFix64::<N9>::new(amount_in),
@@ -60,6 +60,7 @@ where
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(L::MINT, HYUSD::MINT)),
     })
   }
 }
@@ -79,7 +80,7 @@ where
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<RedeemQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.compute_output(UFix64::new(amount_in))?;
     let args = RedeemArgs {
       amount: UFix64::<N6>::new(amount_in),
@@ -100,6 +101,7 @@ where
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(HYUSD::MINT, L::MINT)),
     })
   }
 }
@@ -119,7 +121,7 @@ where
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<MintQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.compute_output(UFix64::new(amount_in))?;
     let args = MintArgs {
       amount: UFix64::<N9>::new(amount_in),
@@ -140,6 +142,7 @@ where
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(L::MINT, XSOL::MINT)),
     })
   }
 }
@@ -159,7 +162,7 @@ where
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<RedeemQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.compute_output(UFix64::new(amount_in))?;
     let args = RedeemArgs {
       amount: UFix64::<N6>::new(amount_in),
@@ -180,6 +183,7 @@ where
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(XSOL::MINT, L::MINT)),
     })
   }
 }
@@ -197,7 +201,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<HYUSD, XSOL, C>
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<SwapQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.output::<HYUSD, XSOL>(UFix64::new(amount_in))?;
     let args = SwapArgs {
       amount: UFix64::<N6>::new(amount_in),
@@ -219,6 +223,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<HYUSD, XSOL, C>
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(HYUSD::MINT, XSOL::MINT)),
     })
   }
 }
@@ -236,7 +241,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<XSOL, HYUSD, C>
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<SwapQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.output::<XSOL, HYUSD>(UFix64::new(amount_in))?;
     let args = SwapArgs {
       amount: UFix64::<N6>::new(amount_in),
@@ -258,6 +263,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<XSOL, HYUSD, C>
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(XSOL::MINT, HYUSD::MINT)),
     })
   }
 }
@@ -277,7 +283,7 @@ where
     user: Pubkey,
     slippage_tolerance: u64,
   ) -> Result<LstSwapQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let amount = UFix64::<N9>::new(amount_in);
     let op = state.compute_output(amount)?;
     let args = LstSwapArgs {
@@ -301,6 +307,7 @@ where
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(L1::MINT, L2::MINT)),
     })
   }
 }
@@ -1,25 +1,96 @@
 //! Quote strategy using protocol state.
 //!
 //! Computes quotes using protocol state and SDK machinery like
-//! `ExchangeContext`, without requiring transaction simulation.
+//! `ExchangeContext`, without requiring transaction simulation. Operations the
+//! program blocks in the current stability mode are refused unless the
+//! strategy's [`ModePolicy`] is overridden, quotes during an epoch
+//! transition are handled per its [`EpochMode`], and deposits are held under
+//! its optional [`DepositCap`].
 
 mod exchange;
 mod stability_pool;
 
+use anyhow::Result;
 use async_trait::async_trait;
 use hylo_core::solana_clock::SolanaClock;
 
-use crate::protocol_state::StateProvider;
+use crate::deposit_cap::DepositCap;
+use crate::epoch_mode::EpochMode;
+use crate::mode_policy::ModePolicy;
+use crate::protocol_state::{ProtocolState, StateProvider};
 use crate::runtime_quote_strategy::RuntimeQuoteStrategy;
 
 pub struct ProtocolStateStrategy<S> {
   pub state_provider: S,
+  pub mode_policy: ModePolicy,
+  pub epoch_mode: EpochMode,
+  pub deposit_cap: Option<DepositCap>,
 }
 
 impl<S> ProtocolStateStrategy<S> {
   #[must_use]
   pub fn new(state_provider: S) -> Self {
-    Self { state_provider }
+    Self {
+      state_provider,
+      mode_policy: ModePolicy::default(),
+      epoch_mode: EpochMode::default(),
+      deposit_cap: None,
+    }
+  }
+
+  /// Replaces the stability mode policy applied to fetched state.
+  #[must_use]
+  pub fn with_mode_policy(self, mode_policy: ModePolicy) -> Self {
+    Self {
+      mode_policy,
+      ..self
+    }
+  }
+
+  /// Replaces the epoch mode applied to fetched state.
+  #[must_use]
+  pub fn with_epoch_mode(self, epoch_mode: EpochMode) -> Self {
+    Self { epoch_mode, ..self }
+  }
+
+  /// Replaces the stability pool deposit cap applied to fetched state.
+  #[must_use]
+  pub fn with_deposit_cap(self, deposit_cap: Option<DepositCap>) -> Self {
+    Self {
+      deposit_cap,
+      ..self
+    }
+  }
+
+  /// Fetches state from the provider with this strategy's mode policy, epoch
+  /// mode and deposit cap. With the `tracing` feature, records the state's
+  /// stability mode and collateral ratio on the current quote span.
+  ///
+  /// # Errors
+  /// * State fetch fails
+  pub(crate) async fn fetch_state<C: SolanaClock>(
+    &self,
+  ) -> Result<ProtocolState<C>>
+  where
+    S: StateProvider<C>,
+  {
+    let state = self.state_provider.fetch_state().await?;
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+      .record(
+        "mode",
+        tracing::field::display(state.exchange_context.stability_mode()),
+      )
+      .record(
+        "collateral_ratio",
+        hylo_core::ui_units::to_f64(state.exchange_context.collateral_ratio()),
+      );
+    Ok(
+      state
+        .with_mode_policy(self.mode_policy)
+        .with_epoch_mode(self.epoch_mode)
+        .with_deposit_cap(self.deposit_cap),
+    )
   }
 }
 
@@ -44,10 +44,11 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<HYUSD, SHYUSD, C>
     user: Pubkey,
     _slippage_tolerance: u64,
   ) -> Result<DepositQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.output::<HYUSD, SHYUSD>(UFix64::new(amount_in))?;
+    // Deposit the quoted amount, which a deposit cap may have clamped
     let args = StabilityPoolArgs {
-      amount: UFix64::<N6>::new(amount_in),
+      amount: op.in_amount,
       user,
     };
     let instructions =
@@ -63,6 +64,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<HYUSD, SHYUSD, C>
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(HYUSD::MINT, SHYUSD::MINT)),
     })
   }
 }
@@ -80,7 +82,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<SHYUSD, HYUSD, C>
     user: Pubkey,
     _slippage_tolerance: u64,
   ) -> Result<WithdrawQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let op = state.output::<SHYUSD, HYUSD>(UFix64::new(amount_in))?;
     let args = StabilityPoolArgs {
       amount: UFix64::<N6>::new(amount_in),
@@ -99,6 +101,7 @@ impl<S: StateProvider<C>, C: SolanaClock> QuoteStrategy<SHYUSD, HYUSD, C>
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(SHYUSD::MINT, HYUSD::MINT)),
     })
   }
 }
@@ -116,7 +119,7 @@ impl<L: LST + Local, S: StateProvider<C>, C: SolanaClock>
     user: Pubkey,
     _slippage_tolerance: u64,
   ) -> Result<WithdrawRedeemQuote> {
-    let state = self.state_provider.fetch_state().await?;
+    let state = self.fetch_state::<C>().await?;
     let lp_tokens_to_burn = UFix64::<N6>::new(amount_in);
 
     // Compute quote
@@ -202,6 +205,7 @@ impl<L: LST + Local, S: StateProvider<C>, C: SolanaClock>
       fee_mint: op.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: Some(state.quote_freshness(SHYUSD::MINT, L::MINT)),
     })
   }
 }
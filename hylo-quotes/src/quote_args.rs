@@ -0,0 +1,267 @@
+//! Instruction args populated from quotes.
+//!
+//! Fills the IDL `args` structs with a quote's input amount and a
+//! [`SlippageConfig`] built from its expected output, so the minimum output
+//! enforced on chain always matches what was quoted.
+//!
+//! ```rust,ignore
+//! let args: exchange_args::MintStablecoin =
+//!   op.into_args_with_slippage(UFix64::<N4>::new(50));
+//! ```
+
+use fix::prelude::*;
+use fix::typenum::Integer;
+use hylo_core::slippage_config::SlippageConfig;
+use hylo_idl::exchange::client::args as exchange_args;
+use hylo_idl::stability_pool::client::args as stability_pool_args;
+
+use crate::token_operation::OperationOutput;
+use crate::ExecutableQuote;
+
+/// IDL args that can be populated from a quoted input and output.
+pub trait FromQuote<InExp: Integer, OutExp: Integer> {
+  /// Builds args for `amount_in`, tolerating `slippage_tolerance` below
+  /// `amount_out`.
+  fn from_quote(
+    amount_in: UFix64<InExp>,
+    amount_out: UFix64<OutExp>,
+    slippage_tolerance: UFix64<N4>,
+  ) -> Self;
+}
+
+macro_rules! from_quote_with_slippage {
+  ($args:ty, $amount:ident, $in:ty, $out:ty) => {
+    impl FromQuote<$in, $out> for $args {
+      fn from_quote(
+        amount_in: UFix64<$in>,
+        amount_out: UFix64<$out>,
+        slippage_tolerance: UFix64<N4>,
+      ) -> Self {
+        Self {
+          $amount: amount_in.bits,
+          slippage_config: Some(
+            SlippageConfig::new(amount_out, slippage_tolerance).into(),
+          ),
+        }
+      }
+    }
+  };
+}
+
+from_quote_with_slippage!(
+  exchange_args::MintStablecoin,
+  amount_lst_to_deposit,
+  N9,
+  N6
+);
+from_quote_with_slippage!(
+  exchange_args::MintLevercoin,
+  amount_lst_to_deposit,
+  N9,
+  N6
+);
+from_quote_with_slippage!(
+  exchange_args::RedeemStablecoin,
+  amount_to_redeem,
+  N6,
+  N9
+);
+from_quote_with_slippage!(
+  exchange_args::RedeemLevercoin,
+  amount_to_redeem,
+  N6,
+  N9
+);
+from_quote_with_slippage!(
+  exchange_args::SwapStableToLever,
+  amount_stablecoin,
+  N6,
+  N6
+);
+from_quote_with_slippage!(
+  exchange_args::SwapLeverToStable,
+  amount_levercoin,
+  N6,
+  N6
+);
+from_quote_with_slippage!(exchange_args::SwapLst, amount_lst_a, N9, N9);
+
+/// Stability pool instructions take no slippage config; the quoted output
+/// is informational only.
+impl FromQuote<N6, N6> for stability_pool_args::UserDeposit {
+  fn from_quote(
+    amount_in: UFix64<N6>,
+    _amount_out: UFix64<N6>,
+    _slippage_tolerance: UFix64<N4>,
+  ) -> Self {
+    Self {
+      amount_stablecoin: amount_in.bits,
+    }
+  }
+}
+
+impl FromQuote<N6, N6> for stability_pool_args::UserWithdraw {
+  fn from_quote(
+    amount_in: UFix64<N6>,
+    _amount_out: UFix64<N6>,
+    _slippage_tolerance: UFix64<N4>,
+  ) -> Self {
+    Self {
+      amount_lp_token: amount_in.bits,
+    }
+  }
+}
+
+impl<InExp: Integer, OutExp: Integer, FeeExp: Integer>
+  OperationOutput<InExp, OutExp, FeeExp>
+{
+  /// Populates instruction args from this output.
+  #[must_use]
+  pub fn into_args_with_slippage<A: FromQuote<InExp, OutExp>>(
+    self,
+    slippage_tolerance: UFix64<N4>,
+  ) -> A {
+    A::from_quote(self.in_amount, self.out_amount, slippage_tolerance)
+  }
+}
+
+impl<InExp: Integer, OutExp: Integer, FeeExp: Integer>
+  ExecutableQuote<InExp, OutExp, FeeExp>
+{
+  /// Populates instruction args from this quote.
+  #[must_use]
+  pub fn args_with_slippage<A: FromQuote<InExp, OutExp>>(
+    &self,
+    slippage_tolerance: UFix64<N4>,
+  ) -> A {
+    A::from_quote(self.amount_in, self.amount_out, slippage_tolerance)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anchor_lang::prelude::Pubkey;
+  use hylo_core::fee_ledger::FeeLedger;
+  use hylo_idl::exchange::types::SlippageConfig as IdlSlippageConfig;
+
+  use super::*;
+  use crate::token_operation::{
+    LstSwapOperationOutput, MintOperationOutput, RedeemOperationOutput,
+    SwapOperationOutput,
+  };
+  use crate::ComputeUnitStrategy;
+
+  const TOLERANCE: UFix64<N4> = UFix64::constant(50);
+
+  fn output<InExp: Integer, OutExp: Integer, FeeExp: Integer>(
+  ) -> OperationOutput<InExp, OutExp, FeeExp> {
+    OperationOutput {
+      in_amount: UFix64::new(1_234_567),
+      out_amount: UFix64::new(7_654_321),
+      fee_amount: UFix64::zero(),
+      fee_mint: Pubkey::default(),
+      fees: FeeLedger::new(),
+      fee_base: UFix64::zero(),
+    }
+  }
+
+  /// Expected output and tolerance of `config`, with their exponents.
+  fn slippage(
+    config: Option<IdlSlippageConfig>,
+  ) -> Option<(UFixValue64, UFixValue64)> {
+    config.map(|config| {
+      (
+        config.expected_token_out.into(),
+        config.slippage_tolerance.into(),
+      )
+    })
+  }
+
+  fn expected<OutExp: Integer>(
+    amount_out: UFix64<OutExp>,
+  ) -> Option<(UFixValue64, UFixValue64)> {
+    Some((amount_out.into(), TOLERANCE.into()))
+  }
+
+  #[test]
+  fn mint_stablecoin() {
+    let op: MintOperationOutput = output();
+    let args: exchange_args::MintStablecoin =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_lst_to_deposit, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn mint_levercoin() {
+    let op: MintOperationOutput = output();
+    let args: exchange_args::MintLevercoin =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_lst_to_deposit, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn redeem_stablecoin() {
+    let op: RedeemOperationOutput = output();
+    let args: exchange_args::RedeemStablecoin =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_to_redeem, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn redeem_levercoin() {
+    let op: RedeemOperationOutput = output();
+    let args: exchange_args::RedeemLevercoin =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_to_redeem, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn swap_stable_to_lever() {
+    let op: SwapOperationOutput = output();
+    let args: exchange_args::SwapStableToLever =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_stablecoin, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn swap_lever_to_stable() {
+    let op: SwapOperationOutput = output();
+    let args: exchange_args::SwapLeverToStable =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_levercoin, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn swap_lst() {
+    let op: LstSwapOperationOutput = output();
+    let args: exchange_args::SwapLst =
+      op.clone().into_args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_lst_a, op.in_amount.bits);
+    assert_eq!(slippage(args.slippage_config), expected(op.out_amount));
+  }
+
+  #[test]
+  fn executable_quote_args() {
+    let quote = ExecutableQuote::<N9, N6, N9> {
+      amount_in: UFix64::new(1_234_567),
+      amount_out: UFix64::new(7_654_321),
+      compute_units: 0,
+      compute_unit_strategy: ComputeUnitStrategy::Estimated,
+      fee_amount: UFix64::zero(),
+      fee_mint: Pubkey::default(),
+      instructions: Vec::new(),
+      address_lookup_tables: Vec::new(),
+      freshness: None,
+    };
+    let args: exchange_args::MintStablecoin =
+      quote.args_with_slippage(TOLERANCE);
+    assert_eq!(args.amount_lst_to_deposit, quote.amount_in.bits);
+    assert_eq!(slippage(args.slippage_config), expected(quote.amount_out));
+  }
+}
@@ -0,0 +1,112 @@
+//! Protocol context captured alongside a quote.
+//!
+//! Routers with their own risk filters need the state a quote was priced
+//! against, not just its amounts. [`QuoteContext`] records stability mode,
+//! collateral ratio, oracle freshness, remaining hyUSD capacity, and
+//! bootstrap status from the same [`ProtocolState`] snapshot that produced the
+//! quote.
+
+use anchor_client::solana_sdk::clock::UnixTimestamp;
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use fix::prelude::*;
+use hylo_core::bootstrap::Bootstrap;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_core::stability_mode::StabilityMode;
+
+use crate::protocol_state::{
+  ProtocolState, SnapshotStateProvider, StateProvider,
+};
+use crate::{
+  ExecutableQuoteValue, ProtocolStateStrategy, QuoteMetadata,
+  RuntimeQuoteStrategy,
+};
+
+/// Protocol state relevant to judging a quote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteContext {
+  pub stability_mode: StabilityMode,
+  pub collateral_ratio: UFix64<N9>,
+  pub oracle_publish_time: UnixTimestamp,
+  pub fetched_at: UnixTimestamp,
+
+  /// hyUSD mintable before the lowest stability threshold
+  pub max_mintable_stablecoin: UFix64<N6>,
+
+  /// hyUSD reachable by swapping xSOL before the lowest stability threshold
+  pub max_swappable_stablecoin: UFix64<N6>,
+
+  /// Tokens priced at initial NAV because their supply is zero
+  pub bootstrap: Bootstrap,
+}
+
+impl QuoteContext {
+  /// Reads the context from a protocol state snapshot.
+  ///
+  /// # Errors
+  /// * Capacity math fails
+  pub fn from_state<C: SolanaClock>(
+    state: &ProtocolState<C>,
+  ) -> Result<QuoteContext> {
+    let ctx = &state.exchange_context;
+    Ok(QuoteContext {
+      stability_mode: ctx.stability_mode(),
+      collateral_ratio: ctx.collateral_ratio(),
+      oracle_publish_time: state.oracle_publish_time,
+      fetched_at: state.fetched_at,
+      max_mintable_stablecoin: ctx.max_mintable_stablecoin()?,
+      max_swappable_stablecoin: ctx.max_swappable_stablecoin()?,
+      bootstrap: state.bootstrap(),
+    })
+  }
+
+  /// Seconds between the oracle publish time and the state fetch.
+  #[must_use]
+  pub fn oracle_age_secs(&self) -> i64 {
+    self.fetched_at.saturating_sub(self.oracle_publish_time)
+  }
+}
+
+/// Quote paired with the protocol context it was computed in.
+#[derive(Debug, Clone)]
+pub struct QuoteWithContext<Q> {
+  pub quote: Q,
+  pub context: QuoteContext,
+}
+
+impl<S> ProtocolStateStrategy<S> {
+  /// Quotes a runtime pair and captures context from the same state snapshot.
+  ///
+  /// # Errors
+  /// * State fetch fails
+  /// * Unsupported pair or quote computation fails
+  pub async fn runtime_quote_with_context<C>(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+    user: Pubkey,
+    slippage_tolerance: u64,
+  ) -> Result<QuoteWithContext<(ExecutableQuoteValue, QuoteMetadata)>>
+  where
+    S: StateProvider<C>,
+    C: SolanaClock + Clone + Send + Sync,
+    ProtocolStateStrategy<SnapshotStateProvider<C>>: RuntimeQuoteStrategy<C>,
+  {
+    let state = self.fetch_state::<C>().await?;
+    let context = QuoteContext::from_state(&state)?;
+    let quote = ProtocolStateStrategy::new(SnapshotStateProvider::new(state))
+      .with_mode_policy(self.mode_policy)
+      .with_epoch_mode(self.epoch_mode)
+      .with_deposit_cap(self.deposit_cap)
+      .runtime_quote_with_metadata(
+        input_mint,
+        output_mint,
+        amount_in,
+        user,
+        slippage_tolerance,
+      )
+      .await?;
+    Ok(QuoteWithContext { quote, context })
+  }
+}
@@ -0,0 +1,146 @@
+//! Staleness tags attached to quotes.
+//!
+//! A quote is only as good as the prices behind it. The program rejects a
+//! SOL/USD price older than its oracle interval, and a total SOL cache or LST
+//! prices from a past epoch, so a quote cached past any of these points will
+//! fail or misprice. [`QuoteFreshness`] records these inputs and the derived
+//! `valid_until`, and consumers with a tighter tolerance can check the age
+//! themselves.
+
+use anchor_client::solana_sdk::clock::UnixTimestamp;
+use anchor_lang::prelude::Pubkey;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::ProtocolState;
+
+/// Price timestamps a quote was computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteFreshness {
+  /// Publish time of the SOL/USD price
+  pub oracle_publish_time: UnixTimestamp,
+
+  /// Oldest LST price epoch among the quote's mints, `None` if it trades no
+  /// LST
+  pub lst_price_epoch: Option<u64>,
+
+  /// Epoch the total SOL cache was last updated in
+  pub total_sol_cache_epoch: u64,
+
+  /// Last timestamp the program accepts the SOL/USD price at
+  pub valid_until: UnixTimestamp,
+}
+
+impl QuoteFreshness {
+  /// Seconds since the SOL/USD price was published.
+  #[must_use]
+  pub fn age_secs(&self, unix_timestamp: UnixTimestamp) -> i64 {
+    unix_timestamp.saturating_sub(self.oracle_publish_time)
+  }
+
+  /// Whether the quote's prices are still accepted at `unix_timestamp` in
+  /// `epoch`.
+  #[must_use]
+  pub fn is_valid_at(&self, unix_timestamp: UnixTimestamp, epoch: u64) -> bool {
+    unix_timestamp <= self.valid_until
+      && self.total_sol_cache_epoch >= epoch
+      && self
+        .lst_price_epoch
+        .is_none_or(|lst_epoch| lst_epoch >= epoch)
+  }
+
+  /// Whether the quote is valid and its SOL/USD price at most `max_age`
+  /// seconds old.
+  #[must_use]
+  pub fn is_within(
+    &self,
+    unix_timestamp: UnixTimestamp,
+    epoch: u64,
+    max_age: i64,
+  ) -> bool {
+    self.is_valid_at(unix_timestamp, epoch)
+      && self.age_secs(unix_timestamp) <= max_age
+  }
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Epoch the total SOL cache was last updated in on chain.
+  fn total_sol_cache_epoch(&self) -> u64 {
+    self
+      .epoch_transition
+      .as_ref()
+      .map_or(self.exchange_context.clock().epoch(), |transition| {
+        transition.cache_epoch
+      })
+  }
+
+  /// Freshness of a quote between `input_mint` and `output_mint` computed
+  /// from this state.
+  #[must_use]
+  pub fn quote_freshness(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+  ) -> QuoteFreshness {
+    let lst_price_epoch = [&self.jitosol_header, &self.hylosol_header]
+      .into_iter()
+      .filter(|header| header.mint == input_mint || header.mint == output_mint)
+      .map(|header| header.price_sol.epoch)
+      .min();
+    let interval = i64::try_from(self.oracle_interval_secs).unwrap_or(i64::MAX);
+    QuoteFreshness {
+      oracle_publish_time: self.oracle_publish_time,
+      lst_price_epoch,
+      total_sol_cache_epoch: self.total_sol_cache_epoch(),
+      valid_until: self.oracle_publish_time.saturating_add(interval),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FRESHNESS: QuoteFreshness = QuoteFreshness {
+    oracle_publish_time: 1_000,
+    lst_price_epoch: Some(700),
+    total_sol_cache_epoch: 700,
+    valid_until: 1_060,
+  };
+
+  #[test]
+  fn expires_with_oracle_interval() {
+    assert!(FRESHNESS.is_valid_at(1_060, 700));
+    assert!(!FRESHNESS.is_valid_at(1_061, 700));
+  }
+
+  #[test]
+  fn expires_with_lst_epoch() {
+    assert!(!FRESHNESS.is_valid_at(1_010, 701));
+    let no_lst = QuoteFreshness {
+      lst_price_epoch: None,
+      ..FRESHNESS
+    };
+    assert!(!no_lst.is_valid_at(1_010, 701));
+    let current = QuoteFreshness {
+      total_sol_cache_epoch: 701,
+      ..no_lst
+    };
+    assert!(current.is_valid_at(1_010, 701));
+  }
+
+  #[test]
+  fn expires_with_total_sol_cache_epoch() {
+    let lagging = QuoteFreshness {
+      lst_price_epoch: None,
+      total_sol_cache_epoch: 699,
+      ..FRESHNESS
+    };
+    assert!(!lagging.is_valid_at(1_010, 700));
+  }
+
+  #[test]
+  fn tighter_tolerance() {
+    assert!(FRESHNESS.is_within(1_010, 700, 10));
+    assert!(!FRESHNESS.is_within(1_011, 700, 10));
+  }
+}
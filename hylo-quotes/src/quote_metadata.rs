@@ -1,5 +1,7 @@
 //! Quote metadata types
 
+use hylo_core::operation_matrix::ProtocolOperation;
+
 /// Operation type for a quote
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Operation {
@@ -16,6 +18,20 @@ pub enum Operation {
 }
 
 impl Operation {
+  /// Every quoted operation.
+  pub const ALL: [Operation; 10] = [
+    Operation::MintStablecoin,
+    Operation::RedeemStablecoin,
+    Operation::MintLevercoin,
+    Operation::RedeemLevercoin,
+    Operation::SwapStableToLever,
+    Operation::SwapLeverToStable,
+    Operation::LstSwap,
+    Operation::DepositToStabilityPool,
+    Operation::WithdrawFromStabilityPool,
+    Operation::WithdrawAndRedeemFromStabilityPool,
+  ];
+
   #[must_use]
   pub const fn as_str(&self) -> &'static str {
     match self {
@@ -47,6 +63,25 @@ impl std::fmt::Display for Operation {
   }
 }
 
+impl From<Operation> for ProtocolOperation {
+  fn from(operation: Operation) -> ProtocolOperation {
+    match operation {
+      Operation::MintStablecoin => ProtocolOperation::MintStablecoin,
+      Operation::RedeemStablecoin => ProtocolOperation::RedeemStablecoin,
+      Operation::MintLevercoin => ProtocolOperation::MintLevercoin,
+      Operation::RedeemLevercoin => ProtocolOperation::RedeemLevercoin,
+      Operation::SwapStableToLever => ProtocolOperation::SwapStableToLever,
+      Operation::SwapLeverToStable => ProtocolOperation::SwapLeverToStable,
+      Operation::LstSwap => ProtocolOperation::LstSwap,
+      Operation::DepositToStabilityPool => ProtocolOperation::Deposit,
+      Operation::WithdrawFromStabilityPool
+      | Operation::WithdrawAndRedeemFromStabilityPool => {
+        ProtocolOperation::Withdraw
+      }
+    }
+  }
+}
+
 /// Metadata for a quote route.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QuoteMetadata {
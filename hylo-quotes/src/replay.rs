@@ -0,0 +1,182 @@
+//! Differential replay of recorded transactions against SDK math.
+//!
+//! Each [`ReplayCase`] pairs the protocol accounts observed before a mainnet
+//! transaction with the token amount the user actually received. Replaying
+//! recomputes the output through [`ProtocolState`] and flags any case where
+//! SDK math diverges from program math beyond a tolerance.
+//!
+//! Cases are derived from a [`RecordedTransaction`], so that both amounts
+//! come from the token balances in the transaction's own metadata rather
+//! than from SDK output.
+
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::protocol_state::{ProtocolAccounts, ProtocolState};
+
+/// Recorded mainnet transaction with its pre-state and observed output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayCase {
+  /// Transaction signature, for reporting
+  pub signature: String,
+
+  /// Mint the user sent
+  pub input_mint: Pubkey,
+
+  /// Mint the user received
+  pub output_mint: Pubkey,
+
+  /// Input amount in base units
+  pub amount_in: u64,
+
+  /// Output balance change observed on-chain, in base units
+  pub observed_out: u64,
+
+  /// Protocol accounts as the transaction loaded them, including writes of
+  /// earlier transactions in its slot
+  pub pre_state: ProtocolAccounts,
+}
+
+/// Token account balance from a transaction's `preTokenBalances` or
+/// `postTokenBalances`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBalance {
+  pub mint: Pubkey,
+  pub owner: Pubkey,
+
+  /// Balance in base units
+  pub amount: u64,
+}
+
+/// Mainnet transaction recorded for replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedTransaction {
+  pub signature: String,
+
+  /// Wallet that sent the input and received the output
+  pub owner: Pubkey,
+
+  pub pre_token_balances: Vec<TokenBalance>,
+  pub post_token_balances: Vec<TokenBalance>,
+
+  /// Protocol accounts as the transaction loaded them
+  pub pre_state: ProtocolAccounts,
+}
+
+impl RecordedTransaction {
+  /// Net change in the owner's balance of `mint`.
+  #[must_use]
+  pub fn balance_delta(&self, mint: Pubkey) -> i128 {
+    let total = |balances: &[TokenBalance]| {
+      balances
+        .iter()
+        .filter(|b| b.owner == self.owner && b.mint == mint)
+        .map(|b| i128::from(b.amount))
+        .sum::<i128>()
+    };
+    total(&self.post_token_balances) - total(&self.pre_token_balances)
+  }
+}
+
+impl TryFrom<&RecordedTransaction> for ReplayCase {
+  type Error = anyhow::Error;
+
+  /// Takes the only mint the owner lost as input and the only mint it
+  /// gained as output.
+  fn try_from(tx: &RecordedTransaction) -> Result<ReplayCase> {
+    let mut mints = tx
+      .pre_token_balances
+      .iter()
+      .chain(&tx.post_token_balances)
+      .filter(|b| b.owner == tx.owner)
+      .map(|b| b.mint)
+      .collect::<Vec<_>>();
+    mints.sort_unstable();
+    mints.dedup();
+    let deltas = mints
+      .into_iter()
+      .map(|mint| (mint, tx.balance_delta(mint)))
+      .filter(|(_, delta)| *delta != 0)
+      .collect::<Vec<_>>();
+    match deltas.as_slice() {
+      [(a, da), (b, db)] if da.signum() != db.signum() => {
+        let ((input_mint, sent), (output_mint, received)) = if *da < 0 {
+          ((*a, -da), (*b, *db))
+        } else {
+          ((*b, -db), (*a, *da))
+        };
+        Ok(ReplayCase {
+          signature: tx.signature.clone(),
+          input_mint,
+          output_mint,
+          amount_in: u64::try_from(sent)?,
+          observed_out: u64::try_from(received)?,
+          pre_state: tx.pre_state.clone(),
+        })
+      }
+      _ => Err(anyhow!(
+        "Expected one sent and one received mint in {}, found {deltas:?}",
+        tx.signature
+      )),
+    }
+  }
+}
+
+/// Result of replaying a single case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayOutcome {
+  pub signature: String,
+  pub expected_out: u64,
+  pub observed_out: u64,
+}
+
+impl ReplayOutcome {
+  /// Absolute difference between SDK and on-chain output.
+  #[must_use]
+  pub fn divergence(&self) -> u64 {
+    self.expected_out.abs_diff(self.observed_out)
+  }
+
+  /// Whether the divergence is within `tolerance` base units.
+  #[must_use]
+  pub fn matches(&self, tolerance: u64) -> bool {
+    self.divergence() <= tolerance
+  }
+}
+
+impl ReplayCase {
+  /// Recomputes expected output from the recorded pre-state.
+  ///
+  /// # Errors
+  /// * Pre-state accounts fail deserialization
+  /// * Unsupported pair or operation math
+  pub fn replay(&self) -> Result<ReplayOutcome> {
+    let state = ProtocolState::<Clock>::try_from(&self.pre_state)
+      .with_context(|| format!("Invalid pre-state for {}", self.signature))?;
+    let output = state
+      .runtime_output(self.input_mint, self.output_mint, self.amount_in)
+      .with_context(|| format!("Replay failed for {}", self.signature))?;
+    Ok(ReplayOutcome {
+      signature: self.signature.clone(),
+      expected_out: output.out_amount.bits,
+      observed_out: self.observed_out,
+    })
+  }
+}
+
+/// Replays a corpus, returning only outcomes diverging beyond `tolerance`.
+///
+/// # Errors
+/// * Any case fails to replay
+pub fn replay_divergences(
+  cases: &[ReplayCase],
+  tolerance: u64,
+) -> Result<Vec<ReplayOutcome>> {
+  cases
+    .iter()
+    .map(ReplayCase::replay)
+    .filter(|outcome| !matches!(outcome, Ok(o) if o.matches(tolerance)))
+    .collect()
+}
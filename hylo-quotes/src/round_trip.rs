@@ -0,0 +1,71 @@
+//! Round-trip cost of entering and exiting a position.
+//!
+//! Composes a pair's forward operation with its reverse (mint then redeem,
+//! or a swap both ways) against one [`ProtocolState`], and reports what the
+//! trip loses to fees and NAV spread. LPs and arbitrageurs size positions
+//! against this figure.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use hylo_core::solana_clock::SolanaClock;
+
+use crate::protocol_state::ProtocolState;
+
+/// Outcome of running `amount_in` through a pair and back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTripCost {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+
+  /// Input amount in base units
+  pub amount_in: u64,
+
+  /// Amount of the output token held between the two legs, in base units
+  pub amount_mid: u64,
+
+  /// Input token recovered by the reverse leg, in base units
+  pub amount_back: u64,
+
+  /// Share of `amount_in` lost over the trip; one bit is one basis point
+  pub loss: UFix64<N4>,
+}
+
+impl RoundTripCost {
+  /// Loss in basis points.
+  #[must_use]
+  pub fn loss_bps(&self) -> u64 {
+    self.loss.bits
+  }
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Quotes `input_mint -> output_mint -> input_mint` for `amount_in` base
+  /// units and reports the loss.
+  ///
+  /// # Errors
+  /// * Either direction of the pair is unsupported
+  /// * Underlying operation math
+  pub fn round_trip_cost(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount_in: u64,
+  ) -> Result<RoundTripCost> {
+    let forward = self.runtime_output(input_mint, output_mint, amount_in)?;
+    let back =
+      self.runtime_output(output_mint, input_mint, forward.out_amount.bits)?;
+    let lost = amount_in.saturating_sub(back.out_amount.bits);
+    let loss = UFix64::<N4>::new(lost)
+      .mul_div_ceil(UFix64::one(), UFix64::new(amount_in))
+      .ok_or(anyhow!("Round trip of zero {input_mint}"))?;
+    Ok(RoundTripCost {
+      input_mint,
+      output_mint,
+      amount_in,
+      amount_mid: forward.out_amount.bits,
+      amount_back: back.out_amount.bits,
+      loss,
+    })
+  }
+}
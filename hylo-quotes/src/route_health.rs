@@ -0,0 +1,258 @@
+//! Route health scores for aggregators.
+//!
+//! A quote can be correct for the state it was computed from and still fail
+//! or go stale by the time it lands. [`ProtocolState::route_health`] rates a
+//! pair on the inputs that decide that, each scored from 0 to 100:
+//!
+//! * Oracle age: seconds since the SOL/USD price was published
+//! * LST price age: epochs the cached LST and total SOL prices lag
+//! * Threshold margin: collateral ratio above the lowest threshold the program
+//!   still runs the operation at
+//! * Remaining capacity: stablecoin left to mint, swap into or deposit
+//!
+//! The route's score is its worst component, so aggregators can rank or drop
+//! routes on a single [`RouteHealth`].
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use fix::prelude::*;
+use hylo_core::rescale::Rescale;
+use hylo_core::rescale::Rounding::Exact;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_core::stability_mode::StabilityMode::Depeg;
+
+use crate::mode_policy::is_allowed;
+use crate::protocol_state::ProtocolState;
+use crate::{pair_metadata, Operation};
+
+/// Scores at or above which a route is [`RouteHealth::Healthy`] and
+/// [`RouteHealth::Degraded`].
+const HEALTHY_SCORE: u8 = 80;
+const DEGRADED_SCORE: u8 = 30;
+
+/// Coarse health of a route, from its score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RouteHealth {
+  /// Likely to fail or requote; route elsewhere
+  Unhealthy,
+
+  /// Usable, but de-prioritize against other venues
+  Degraded,
+
+  Healthy,
+}
+
+impl RouteHealth {
+  #[must_use]
+  pub fn from_score(score: u8) -> RouteHealth {
+    match score {
+      s if s >= HEALTHY_SCORE => RouteHealth::Healthy,
+      s if s >= DEGRADED_SCORE => RouteHealth::Degraded,
+      _ => RouteHealth::Unhealthy,
+    }
+  }
+}
+
+/// Levels at which each component scores 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteHealthConfig {
+  /// Oracle age in seconds at which the oracle scores 0
+  pub max_oracle_age: i64,
+
+  /// Collateral ratio margin scoring 100, e.g. `0.2` above the threshold
+  pub comfortable_margin: UFix64<N9>,
+
+  /// Remaining stablecoin capacity scoring 100
+  pub comfortable_capacity: UFix64<N6>,
+}
+
+impl Default for RouteHealthConfig {
+  fn default() -> Self {
+    RouteHealthConfig {
+      max_oracle_age: 60,
+      comfortable_margin: UFix64::new(200_000_000),
+      comfortable_capacity: UFix64::new(100_000_000_000),
+    }
+  }
+}
+
+/// Health of one pair with the inputs behind its score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteHealthReport {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+  pub operation: Operation,
+
+  /// Seconds between the SOL/USD publish time and the state fetch
+  pub oracle_age: i64,
+
+  /// Epochs the oldest price the route depends on lags the clock
+  pub epochs_behind: u64,
+
+  /// Collateral ratio above the lowest threshold the operation runs at,
+  /// `None` if it runs in every mode
+  pub threshold_margin: Option<UFix64<N9>>,
+
+  /// Stablecoin the operation can still mint, swap into or deposit, `None`
+  /// if it is not capped
+  pub remaining_capacity: Option<UFix64<N6>>,
+
+  /// Worst component score, from 0 to 100
+  pub score: u8,
+  pub health: RouteHealth,
+}
+
+/// `value` as a share of `full`, from 0 to 100.
+fn scale(value: u64, full: u64) -> u8 {
+  let score = u128::from(value)
+    .saturating_mul(100)
+    .checked_div(u128::from(full))
+    .unwrap_or(100)
+    .min(100);
+  u8::try_from(score).unwrap_or(100)
+}
+
+impl<C: SolanaClock> ProtocolState<C> {
+  /// Health of the `input_mint -> output_mint` route under the default
+  /// [`RouteHealthConfig`].
+  ///
+  /// # Errors
+  /// * See [`Self::route_health_with`]
+  pub fn route_health(
+    &self,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+  ) -> Result<RouteHealthReport> {
+    self.route_health_with(
+      &RouteHealthConfig::default(),
+      input_mint,
+      output_mint,
+    )
+  }
+
+  /// Health of the `input_mint -> output_mint` route under `config`.
+  ///
+  /// # Errors
+  /// * Unsupported pair
+  /// * Threshold or capacity arithmetic
+  pub fn route_health_with(
+    &self,
+    config: &RouteHealthConfig,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+  ) -> Result<RouteHealthReport> {
+    let operation = pair_metadata(input_mint, output_mint)
+      .map(|metadata| metadata.operation)
+      .ok_or(anyhow!("Unsupported pair {input_mint} -> {output_mint}"))?;
+    let ctx = &self.exchange_context;
+
+    let oracle_age = self.fetched_at.saturating_sub(self.oracle_publish_time);
+    let oracle_score = scale(
+      u64::try_from(config.max_oracle_age.saturating_sub(oracle_age))
+        .unwrap_or(0),
+      u64::try_from(config.max_oracle_age).unwrap_or(0),
+    );
+
+    let epoch = ctx.clock().epoch();
+    let cache_epoch = match operation {
+      Operation::DepositToStabilityPool
+      | Operation::WithdrawFromStabilityPool => epoch,
+      _ => self
+        .epoch_transition
+        .as_ref()
+        .map_or(epoch, |transition| transition.cache_epoch),
+    };
+    let epochs_behind = [&self.jitosol_header, &self.hylosol_header]
+      .into_iter()
+      .filter(|header| header.mint == input_mint || header.mint == output_mint)
+      .map(|header| header.price_sol.epoch)
+      .chain([cache_epoch])
+      .map(|price_epoch| epoch.saturating_sub(price_epoch))
+      .max()
+      .unwrap_or(0);
+    let epoch_score = match (epochs_behind, self.check_epoch()) {
+      (0, _) => 100,
+      (_, Ok(())) => 50,
+      (_, Err(_)) => 0,
+    };
+
+    let threshold_margin = if is_allowed(operation, Depeg) {
+      None
+    } else {
+      let lowest = ctx
+        .stability_controller()
+        .bands
+        .iter()
+        .filter(|band| is_allowed(operation, band.mode))
+        .try_fold(UFix64::new(u64::MAX), |lowest, band| {
+          band
+            .threshold
+            .rescale::<N9>(Exact)
+            .map(|threshold| threshold.min(lowest))
+        })
+        .ok_or(anyhow!("Stability threshold overflow"))?;
+      Some(ctx.collateral_ratio().saturating_sub(&lowest))
+    };
+    let margin_score = threshold_margin.map_or(100, |margin| {
+      scale(margin.bits, config.comfortable_margin.bits)
+    });
+
+    // Below the lowest threshold the capacity math underflows; nothing is
+    // left to mint or swap into.
+    let exhausted = ctx.collateral_ratio()
+      <= ctx
+        .stability_controller()
+        .min_stability_threshold()
+        .rescale::<N9>(Exact)
+        .ok_or(anyhow!("Stability threshold overflow"))?;
+    let remaining_capacity = match operation {
+      Operation::MintStablecoin | Operation::SwapLeverToStable if exhausted => {
+        Some(UFix64::zero())
+      }
+      Operation::MintStablecoin => Some(ctx.max_mintable_stablecoin()?),
+      Operation::SwapLeverToStable => Some(ctx.max_swappable_stablecoin()?),
+      Operation::DepositToStabilityPool => self.remaining_deposit_capacity()?,
+      _ => None,
+    };
+    let capacity_score = remaining_capacity.map_or(100, |capacity| {
+      scale(capacity.bits, config.comfortable_capacity.bits)
+    });
+
+    let score = [oracle_score, epoch_score, margin_score, capacity_score]
+      .into_iter()
+      .min()
+      .unwrap_or(0);
+    Ok(RouteHealthReport {
+      input_mint,
+      output_mint,
+      operation,
+      oracle_age,
+      epochs_behind,
+      threshold_margin,
+      remaining_capacity,
+      score,
+      health: RouteHealth::from_score(score),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn scores_map_to_health() {
+    assert_eq!(RouteHealth::from_score(100), RouteHealth::Healthy);
+    assert_eq!(RouteHealth::from_score(80), RouteHealth::Healthy);
+    assert_eq!(RouteHealth::from_score(79), RouteHealth::Degraded);
+    assert_eq!(RouteHealth::from_score(30), RouteHealth::Degraded);
+    assert_eq!(RouteHealth::from_score(0), RouteHealth::Unhealthy);
+  }
+
+  #[test]
+  fn scale_saturates() {
+    assert_eq!(scale(50, 200), 25);
+    assert_eq!(scale(500, 200), 100);
+    assert_eq!(scale(0, 0), 100);
+  }
+}
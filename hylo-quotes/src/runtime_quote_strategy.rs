@@ -10,10 +10,29 @@ use crate::ExecutableQuoteValue;
 
 macro_rules! runtime_quote_strategies {
     ($(($in:ty, $out:ty, $op:expr, $desc:expr)),* $(,)?) => {
+      /// Operation and description of a supported pair of mints.
+      #[must_use]
+      pub fn pair_metadata(input_mint: Pubkey, output_mint: Pubkey) -> Option<QuoteMetadata> {
+        match (input_mint, output_mint) {
+          $(
+            (<$in>::MINT, <$out>::MINT) => Some(QuoteMetadata::new($op, $desc)),
+          )*
+          _ => None,
+        }
+      }
+
       /// Runtime dispatch trait bridging untyped `Pubkey` pair to typed `QuoteStrategy`.
       #[async_trait]
       pub trait RuntimeQuoteStrategy<C: SolanaClock>: $( QuoteStrategy<$in, $out, C> + )* {
         /// Fetches quote based on input and output mints.
+        #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err, fields(
+          %input_mint,
+          %output_mint,
+          amount_in,
+          slippage_tolerance,
+          mode = tracing::field::Empty,
+          collateral_ratio = tracing::field::Empty,
+        )))]
         async fn runtime_quote(
           &self,
           input_mint: Pubkey,
@@ -34,6 +53,14 @@ macro_rules! runtime_quote_strategies {
         }
 
         /// Fetches quote based on input and output mints with relevant metadata.
+        #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err, fields(
+          %input_mint,
+          %output_mint,
+          amount_in,
+          slippage_tolerance,
+          mode = tracing::field::Empty,
+          collateral_ratio = tracing::field::Empty,
+        )))]
         async fn runtime_quote_with_metadata(
           &self,
           input_mint: Pubkey,
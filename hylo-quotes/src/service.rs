@@ -0,0 +1,230 @@
+//! HTTP quoting service for non-Rust consumers.
+//!
+//! Serves quotes and protocol stats from any [`StateProvider`], typically a
+//! [`WebsocketStateProvider`](crate::protocol_state::WebsocketStateProvider)
+//! so that requests are answered from memory. Amounts are JSON encoded as
+//! `{ "bits": <u64>, "exp": <i8> }`.
+//!
+//! * `GET /quote?input_mint=..&output_mint=..&amount=..`
+//! * `GET /stats`
+//! * `GET /max-amounts`
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use anyhow::{Context, Result};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use fix::prelude::{UFix64, UFixValue64};
+use fix::typenum::N6;
+use hylo_core::withdrawal_fee::WithdrawalFee;
+use serde::{Deserialize, Serialize};
+
+use crate::protocol_state::{ProtocolState, StateProvider};
+
+/// Query parameters for `GET /quote`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuoteParams {
+  pub input_mint: String,
+  pub output_mint: String,
+
+  /// Input amount in base units
+  pub amount: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteResponse {
+  pub in_amount: UFixValue64,
+  pub out_amount: UFixValue64,
+  pub fee_amount: UFixValue64,
+  pub fee_mint: String,
+  pub fee_base: UFixValue64,
+
+  /// Fees charged per mint, including legs outside `fee_mint`
+  pub fees: BTreeMap<String, UFixValue64>,
+
+  /// Publish time of the SOL/USD price the quote was computed from
+  pub oracle_publish_time: i64,
+
+  /// Oldest LST price epoch among the pair's mints
+  pub lst_price_epoch: Option<u64>,
+
+  /// Epoch the total SOL cache was last updated in
+  pub total_sol_cache_epoch: u64,
+
+  /// Last timestamp the program accepts the quote's SOL/USD price at
+  pub valid_until: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsResponse {
+  pub fetched_at: i64,
+  pub stability_mode: String,
+  pub collateral_ratio: UFixValue64,
+  pub total_sol: UFixValue64,
+  pub total_value_locked: UFixValue64,
+  pub total_value_locked_sol: UFixValue64,
+  pub sol_usd_price_lower: UFixValue64,
+  pub sol_usd_price_upper: UFixValue64,
+  pub hyusd_supply: UFixValue64,
+  pub hyusd_nav: UFixValue64,
+  pub hyusd_nav_sol: UFixValue64,
+  pub xsol_supply: UFixValue64,
+  pub xsol_mint_nav: UFixValue64,
+  pub xsol_redeem_nav: UFixValue64,
+  pub xsol_nav_sol: UFixValue64,
+  pub shyusd_supply: UFixValue64,
+  pub stability_pool_cap_sol: UFixValue64,
+  pub shyusd_withdrawal_fee: UFixValue64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MaxAmountsResponse {
+  /// HYUSD mintable before the lowest CR threshold
+  pub max_mintable_hyusd: UFixValue64,
+
+  /// HYUSD reachable by swapping from XSOL before the lowest CR threshold
+  pub max_swappable_hyusd: UFixValue64,
+}
+
+/// Error rendered as `{ "error": <message> }`.
+///
+/// Request errors convert with `?` and render with status 400. Failures to
+/// load protocol state, such as an RPC outage or a dead subscription, are
+/// not the caller's fault and render with status 503.
+pub struct ServiceError {
+  status: StatusCode,
+  error: anyhow::Error,
+}
+
+impl ServiceError {
+  /// Upstream failure rendered with status 503.
+  #[must_use]
+  pub fn unavailable(error: anyhow::Error) -> ServiceError {
+    ServiceError {
+      status: StatusCode::SERVICE_UNAVAILABLE,
+      error,
+    }
+  }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ServiceError {
+  fn from(err: E) -> Self {
+    ServiceError {
+      status: StatusCode::BAD_REQUEST,
+      error: err.into(),
+    }
+  }
+}
+
+impl IntoResponse for ServiceError {
+  fn into_response(self) -> Response {
+    let body =
+      Json(serde_json::json!({ "error": format!("{:#}", self.error) }));
+    (self.status, body).into_response()
+  }
+}
+
+type ServiceResult<T> = std::result::Result<Json<T>, ServiceError>;
+
+/// Builds the service router over a shared state provider.
+#[must_use]
+pub fn router<P: StateProvider<Clock> + 'static>(provider: Arc<P>) -> Router {
+  Router::new()
+    .route("/quote", get(quote::<P>))
+    .route("/stats", get(stats::<P>))
+    .route("/max-amounts", get(max_amounts::<P>))
+    .with_state(provider)
+}
+
+fn parse_mint(name: &str, value: &str) -> Result<Pubkey> {
+  Pubkey::from_str(value).with_context(|| format!("Invalid {name} {value}"))
+}
+
+/// Fetches state, reporting a failure as unavailable.
+async fn fetch_state<P: StateProvider<Clock>>(
+  provider: &P,
+) -> std::result::Result<ProtocolState<Clock>, ServiceError> {
+  provider
+    .fetch_state()
+    .await
+    .map_err(ServiceError::unavailable)
+}
+
+async fn quote<P: StateProvider<Clock>>(
+  State(provider): State<Arc<P>>,
+  Query(params): Query<QuoteParams>,
+) -> ServiceResult<QuoteResponse> {
+  let input_mint = parse_mint("input_mint", &params.input_mint)?;
+  let output_mint = parse_mint("output_mint", &params.output_mint)?;
+  let state = fetch_state(&*provider).await?;
+  let op = state.runtime_output(input_mint, output_mint, params.amount)?;
+  let freshness = state.quote_freshness(input_mint, output_mint);
+  Ok(Json(QuoteResponse {
+    in_amount: op.in_amount,
+    out_amount: op.out_amount,
+    fee_amount: op.fee_amount,
+    fee_mint: op.fee_mint.to_string(),
+    fee_base: op.fee_base,
+    fees: op
+      .fees
+      .iter()
+      .map(|(mint, total)| (mint.to_string(), *total))
+      .collect(),
+    oracle_publish_time: freshness.oracle_publish_time,
+    lst_price_epoch: freshness.lst_price_epoch,
+    total_sol_cache_epoch: freshness.total_sol_cache_epoch,
+    valid_until: freshness.valid_until,
+  }))
+}
+
+async fn stats<P: StateProvider<Clock>>(
+  State(provider): State<Arc<P>>,
+) -> ServiceResult<StatsResponse> {
+  let state = fetch_state(&*provider).await?;
+  let ctx = &state.exchange_context;
+  Ok(Json(StatsResponse {
+    fetched_at: state.fetched_at,
+    stability_mode: ctx.stability_mode().to_string(),
+    collateral_ratio: ctx.collateral_ratio().into(),
+    total_sol: ctx.total_sol().into(),
+    total_value_locked: ctx.total_value_locked()?.into(),
+    total_value_locked_sol: ctx.total_value_locked_sol().into(),
+    sol_usd_price_lower: ctx.sol_usd_price().lower.into(),
+    sol_usd_price_upper: ctx.sol_usd_price().upper.into(),
+    hyusd_supply: ctx.stablecoin_supply().into(),
+    hyusd_nav: ctx.stablecoin_nav()?.into(),
+    hyusd_nav_sol: ctx.stablecoin_nav_sol()?.into(),
+    xsol_supply: ctx.levercoin_supply()?.into(),
+    xsol_mint_nav: ctx.levercoin_mint_nav()?.into(),
+    xsol_redeem_nav: ctx.levercoin_redeem_nav()?.into(),
+    xsol_nav_sol: ctx.levercoin_nav_sol()?.into(),
+    shyusd_supply: UFix64::<N6>::new(state.shyusd_mint.supply).into(),
+    stability_pool_cap_sol: ctx
+      .stability_pool_cap_sol(
+        UFix64::new(state.hyusd_pool.amount),
+        UFix64::new(state.xsol_pool.amount),
+      )?
+      .into(),
+    shyusd_withdrawal_fee: WithdrawalFee::try_from(
+      state.pool_config.withdrawal_fee,
+    )?
+    .into(),
+  }))
+}
+
+async fn max_amounts<P: StateProvider<Clock>>(
+  State(provider): State<Arc<P>>,
+) -> ServiceResult<MaxAmountsResponse> {
+  let state = fetch_state(&*provider).await?;
+  let ctx = &state.exchange_context;
+  Ok(Json(MaxAmountsResponse {
+    max_mintable_hyusd: ctx.max_mintable_stablecoin()?.into(),
+    max_swappable_hyusd: ctx.max_swappable_stablecoin()?.into(),
+  }))
+}
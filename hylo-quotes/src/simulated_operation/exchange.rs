@@ -3,6 +3,7 @@
 use anyhow::{Context, Result};
 use fix::prelude::*;
 use hylo_clients::prelude::ExchangeClient;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_idl::exchange::events::{
   MintLevercoinEventV2, MintStablecoinEventV2, RedeemLevercoinEventV2,
   RedeemStablecoinEventV2, SwapLeverToStableEventV1, SwapLstEventV0,
@@ -35,6 +36,7 @@ impl<L: LST + Local> SimulatedOperation<L, HYUSD> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fee_amount),
       fee_base,
     })
   }
@@ -57,6 +59,7 @@ impl<L: LST + Local> SimulatedOperation<HYUSD, L> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fee_amount),
       fee_base,
     })
   }
@@ -80,6 +83,7 @@ impl<L: LST + Local> SimulatedOperation<L, XSOL> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fee_amount),
       fee_base,
     })
   }
@@ -102,6 +106,7 @@ impl<L: LST + Local> SimulatedOperation<XSOL, L> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fee_amount),
       fee_base,
     })
   }
@@ -124,6 +129,7 @@ impl SimulatedOperation<HYUSD, XSOL> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fee_amount),
       fee_base,
     })
   }
@@ -146,6 +152,7 @@ impl SimulatedOperation<XSOL, HYUSD> for ExchangeClient {
       out_amount,
       fee_amount,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fee_amount),
       fee_base,
     })
   }
@@ -167,6 +174,7 @@ impl<L1: LST + Local, L2: LST + Local> SimulatedOperation<L1, L2>
       out_amount,
       fee_amount,
       fee_mint: L1::MINT,
+      fees: FeeLedger::single(L1::MINT, fee_amount),
       fee_base: in_amount,
     })
   }
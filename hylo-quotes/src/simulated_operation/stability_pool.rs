@@ -3,6 +3,7 @@
 use anyhow::{bail, Context, Result};
 use fix::prelude::*;
 use hylo_clients::prelude::StabilityPoolClient;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_idl::stability_pool::events::{UserDepositEvent, UserWithdrawEventV1};
 use hylo_idl::tokens::{TokenMint, HYUSD, SHYUSD};
 
@@ -22,6 +23,7 @@ impl SimulatedOperation<HYUSD, SHYUSD> for StabilityPoolClient {
       out_amount,
       fee_amount: UFix64::zero(),
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::new(),
       fee_base: in_amount,
     })
   }
@@ -47,6 +49,7 @@ impl SimulatedOperation<SHYUSD, HYUSD> for StabilityPoolClient {
       out_amount,
       fee_amount,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fee_amount),
       fee_base,
     })
   }
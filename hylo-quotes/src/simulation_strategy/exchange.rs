@@ -68,6 +68,7 @@ impl<L: LST + Local, C: SolanaClock> QuoteStrategy<L, HYUSD, C>
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -121,6 +122,7 @@ impl<L: LST + Local, C: SolanaClock> QuoteStrategy<HYUSD, L, C>
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -174,6 +176,7 @@ impl<L: LST + Local, C: SolanaClock> QuoteStrategy<L, XSOL, C>
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -227,6 +230,7 @@ impl<L: LST + Local, C: SolanaClock> QuoteStrategy<XSOL, L, C>
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -279,6 +283,7 @@ impl<C: SolanaClock> QuoteStrategy<HYUSD, XSOL, C> for SimulationStrategy {
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -331,6 +336,7 @@ impl<C: SolanaClock> QuoteStrategy<XSOL, HYUSD, C> for SimulationStrategy {
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -388,6 +394,7 @@ impl<C: SolanaClock, L1: LST + Local, L2: LST + Local> QuoteStrategy<L1, L2, C>
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
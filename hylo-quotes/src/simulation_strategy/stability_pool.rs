@@ -67,6 +67,7 @@ impl<C: SolanaClock> QuoteStrategy<HYUSD, SHYUSD, C> for SimulationStrategy {
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -108,6 +109,7 @@ impl<C: SolanaClock> QuoteStrategy<SHYUSD, HYUSD, C> for SimulationStrategy {
       fee_mint: output.fee_mint,
       instructions,
       address_lookup_tables,
+      freshness: None,
     })
   }
 }
@@ -252,6 +254,7 @@ impl<L: LST + Local, C: SolanaClock> QuoteStrategy<SHYUSD, L, C>
         .iter()
         .map(|lut| lut.key)
         .collect(),
+      freshness: None,
     })
   }
 }
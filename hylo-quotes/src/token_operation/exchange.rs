@@ -1,11 +1,11 @@
 //! `TokenOperation` implementations for exchange pairs.
 
-use anyhow::{ensure, Result};
+use anyhow::Result;
 use fix::prelude::*;
 use hylo_core::fee_controller::FeeExtract;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_core::lst_sol_price::LstSolPrice;
 use hylo_core::solana_clock::SolanaClock;
-use hylo_core::stability_mode::StabilityMode;
 use hylo_idl::tokens::{TokenMint, HYUSD, XSOL};
 
 use crate::protocol_state::ProtocolState;
@@ -13,7 +13,7 @@ use crate::token_operation::{
   LstSwapOperationOutput, MintOperationOutput, OperationOutput,
   RedeemOperationOutput, SwapOperationOutput, TokenOperation,
 };
-use crate::{Local, LST};
+use crate::{Local, Operation, LST};
 
 /// Mint stablecoin (HYUSD) from LST collateral.
 impl<L: LST + Local, C: SolanaClock> TokenOperation<L, HYUSD>
@@ -25,10 +25,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, HYUSD>
     &self,
     in_amount: UFix64<N9>,
   ) -> Result<MintOperationOutput> {
-    ensure!(
-      self.exchange_context.stability_mode <= StabilityMode::Mode1,
-      "Mint operations disabled in current stability mode"
-    );
+    self.check_operation(Operation::MintStablecoin)?;
     let lst_header = self.lst_header::<L>()?;
     let lst_price = lst_header.price_sol.into();
     let FeeExtract {
@@ -50,6 +47,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, HYUSD>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -65,6 +63,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<HYUSD, L>
     &self,
     in_amount: UFix64<<HYUSD as TokenMint>::Exp>,
   ) -> Result<RedeemOperationOutput> {
+    self.check_operation(Operation::RedeemStablecoin)?;
     let lst_header = self.lst_header::<L>()?;
     let lst_price = lst_header.price_sol.into();
     let stablecoin_nav = self.exchange_context.stablecoin_nav()?;
@@ -83,6 +82,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<HYUSD, L>
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: lst_out,
     })
   }
@@ -98,10 +98,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
     &self,
     in_amount: UFix64<N9>,
   ) -> Result<MintOperationOutput> {
-    ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
-      "Levercoin mint disabled in current stability mode"
-    );
+    self.check_operation(Operation::MintLevercoin)?;
     let lst_header = self.lst_header::<L>()?;
     let lst_price = lst_header.price_sol.into();
     let FeeExtract {
@@ -120,6 +117,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<L, XSOL>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -135,10 +133,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
     &self,
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
   ) -> Result<RedeemOperationOutput> {
-    ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
-      "Levercoin redemption disabled in current stability mode"
-    );
+    self.check_operation(Operation::RedeemLevercoin)?;
     let lst_header = self.lst_header::<L>()?;
     let lst_price = lst_header.price_sol.into();
     let xsol_nav = self.exchange_context.levercoin_redeem_nav()?;
@@ -157,6 +152,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<XSOL, L>
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: L::MINT,
+      fees: FeeLedger::single(L::MINT, fees_extracted),
       fee_base: lst_out,
     })
   }
@@ -170,10 +166,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
     &self,
     in_amount: UFix64<<HYUSD as TokenMint>::Exp>,
   ) -> Result<SwapOperationOutput> {
-    ensure!(
-      self.exchange_context.stability_mode != StabilityMode::Depeg,
-      "Swaps are disabled in current stability mode"
-    );
+    self.check_operation(Operation::SwapStableToLever)?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
@@ -189,6 +182,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, XSOL> for ProtocolState<C> {
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
@@ -202,13 +196,7 @@ impl<C: SolanaClock> TokenOperation<XSOL, HYUSD> for ProtocolState<C> {
     &self,
     in_amount: UFix64<<XSOL as TokenMint>::Exp>,
   ) -> Result<SwapOperationOutput> {
-    ensure!(
-      matches!(
-        self.exchange_context.stability_mode,
-        StabilityMode::Normal | StabilityMode::Mode1
-      ),
-      "Swaps are disabled in current stability mode"
-    );
+    self.check_operation(Operation::SwapLeverToStable)?;
     let converted = self
       .exchange_context
       .swap_conversion()?
@@ -227,6 +215,7 @@ impl<C: SolanaClock> TokenOperation<XSOL, HYUSD> for ProtocolState<C> {
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fees_extracted),
       fee_base: hyusd_total,
     })
   }
@@ -242,12 +231,13 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
     &self,
     in_amount: UFix64<N9>,
   ) -> Result<LstSwapOperationOutput> {
+    self.check_operation(Operation::LstSwap)?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
     } = self.lst_swap_config.apply_fee(in_amount)?;
 
-    let epoch = self.exchange_context.clock.epoch();
+    let epoch = self.exchange_context.clock().epoch();
     let lst_in_header = self.lst_header::<L1>()?;
     let lst_out_header = self.lst_header::<L2>()?;
 
@@ -261,6 +251,7 @@ impl<L1: LST + Local, L2: LST + Local, C: SolanaClock> TokenOperation<L1, L2>
       out_amount,
       fee_amount: fees_extracted,
       fee_mint: L1::MINT,
+      fees: FeeLedger::single(L1::MINT, fees_extracted),
       fee_base: in_amount,
     })
   }
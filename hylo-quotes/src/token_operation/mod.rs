@@ -1,23 +1,61 @@
 //! Token operation trait for pure protocol math.
 
 mod exchange;
+mod runtime;
 mod stability_pool;
 
 use anchor_lang::prelude::Pubkey;
 use anyhow::Result;
-use fix::prelude::{UFix64, N6, N9};
+use fix::prelude::{UFix64, UFixValue64, N6, N9};
 use fix::typenum::Integer;
+use hylo_core::fee_ledger::FeeLedger;
+use hylo_core::token_amount::TokenAmount;
 use hylo_idl::tokens::TokenMint;
+pub use runtime::{RuntimePair, RUNTIME_PAIRS};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OperationOutput<InExp: Integer, OutExp: Integer, FeeExp: Integer> {
   pub in_amount: UFix64<InExp>,
   pub out_amount: UFix64<OutExp>,
   pub fee_amount: UFix64<FeeExp>,
   pub fee_mint: Pubkey,
+
+  /// Fees per mint. Holds `fee_amount` under `fee_mint`, and for composite
+  /// operations the legs charged in other tokens
+  pub fees: FeeLedger,
+
   pub fee_base: UFix64<FeeExp>,
 }
 
+/// Operation output with runtime exponent information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationOutputValue {
+  pub in_amount: UFixValue64,
+  pub out_amount: UFixValue64,
+  pub fee_amount: UFixValue64,
+  pub fee_mint: Pubkey,
+
+  /// Fees per mint, see [`OperationOutput::fees`]
+  pub fees: FeeLedger,
+
+  pub fee_base: UFixValue64,
+}
+
+impl<InExp: Integer, OutExp: Integer, FeeExp: Integer>
+  From<OperationOutput<InExp, OutExp, FeeExp>> for OperationOutputValue
+{
+  fn from(op: OperationOutput<InExp, OutExp, FeeExp>) -> OperationOutputValue {
+    OperationOutputValue {
+      in_amount: op.in_amount.into(),
+      out_amount: op.out_amount.into(),
+      fee_amount: op.fee_amount.into(),
+      fee_mint: op.fee_mint,
+      fees: op.fees,
+      fee_base: op.fee_base.into(),
+    }
+  }
+}
+
 pub type MintOperationOutput = OperationOutput<N9, N6, N9>;
 pub type RedeemOperationOutput = OperationOutput<N6, N9, N9>;
 pub type SwapOperationOutput = OperationOutput<N6, N6, N6>;
@@ -56,6 +94,21 @@ pub trait TokenOperationExt {
     IN: TokenMint,
     OUT: TokenMint,
     <Self as TokenOperation<IN, OUT>>::FeeExp: Integer;
+
+  /// Typed counterpart to [`TokenOperationExt::output`], returning only the
+  /// output amount.
+  ///
+  /// # Errors
+  /// * Arithmetic or mode restrictions.
+  fn token_output<IN, OUT>(
+    &self,
+    amount_in: TokenAmount<IN>,
+  ) -> Result<TokenAmount<OUT>>
+  where
+    Self: TokenOperation<IN, OUT>,
+    IN: TokenMint,
+    OUT: TokenMint,
+    <Self as TokenOperation<IN, OUT>>::FeeExp: Integer;
 }
 
 impl<X> TokenOperationExt for X {
@@ -77,4 +130,19 @@ impl<X> TokenOperationExt for X {
   {
     TokenOperation::<IN, OUT>::compute_output(self, amount_in)
   }
+
+  fn token_output<IN, OUT>(
+    &self,
+    amount_in: TokenAmount<IN>,
+  ) -> Result<TokenAmount<OUT>>
+  where
+    Self: TokenOperation<IN, OUT>,
+    IN: TokenMint,
+    OUT: TokenMint,
+    <Self as TokenOperation<IN, OUT>>::FeeExp: Integer,
+  {
+    let op =
+      TokenOperation::<IN, OUT>::compute_output(self, amount_in.amount())?;
+    Ok(TokenAmount::new(op.out_amount))
+  }
 }
@@ -0,0 +1,81 @@
+//! Runtime dispatch from untyped mint pairs to [`TokenOperation`].
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use fix::prelude::UFix64;
+use fix::typenum::Integer;
+use hylo_core::solana_clock::SolanaClock;
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+
+use crate::protocol_state::ProtocolState;
+use crate::token_operation::{OperationOutputValue, TokenOperationExt};
+
+/// Mint pair supported by [`ProtocolState::runtime_output`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimePair {
+  pub input_mint: Pubkey,
+  pub output_mint: Pubkey,
+
+  /// One whole input token in base units
+  pub unit_in: u64,
+}
+
+macro_rules! runtime_outputs {
+  ($(($in:ty, $out:ty)),* $(,)?) => {
+    /// Every pair [`ProtocolState::runtime_output`] dispatches.
+    pub const RUNTIME_PAIRS: &[RuntimePair] = &[
+      $(
+        RuntimePair {
+          input_mint: <$in>::MINT,
+          output_mint: <$out>::MINT,
+          unit_in: 10_u64
+            .pow(<<$in as TokenMint>::Exp as Integer>::I32.unsigned_abs()),
+        },
+      )*
+    ];
+
+    impl<C: SolanaClock> ProtocolState<C> {
+      /// Computes [`TokenOperation`](super::TokenOperation) output for a pair
+      /// of mints known only at runtime, with `amount_in` in base units.
+      ///
+      /// # Errors
+      /// * Unsupported pair
+      /// * Underlying operation math
+      pub fn runtime_output(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount_in: u64,
+      ) -> Result<OperationOutputValue> {
+        match (input_mint, output_mint) {
+          $(
+            (<$in>::MINT, <$out>::MINT) => {
+              let op = self.output::<$in, $out>(UFix64::new(amount_in))?;
+              Ok(op.into())
+            },
+          )*
+          _ => Err(anyhow!("Unsupported pair {input_mint} -> {output_mint}")),
+        }
+      }
+    }
+  };
+}
+
+runtime_outputs! {
+  (JITOSOL, HYUSD),
+  (HYUSD, JITOSOL),
+  (HYLOSOL, HYUSD),
+  (HYUSD, HYLOSOL),
+  (JITOSOL, XSOL),
+  (XSOL, JITOSOL),
+  (HYLOSOL, XSOL),
+  (XSOL, HYLOSOL),
+  (HYUSD, XSOL),
+  (XSOL, HYUSD),
+  (JITOSOL, HYLOSOL),
+  (HYLOSOL, JITOSOL),
+  (HYUSD, SHYUSD),
+  (SHYUSD, HYUSD),
+  (SHYUSD, JITOSOL),
+  (SHYUSD, HYLOSOL),
+}
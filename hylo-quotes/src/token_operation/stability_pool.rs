@@ -3,11 +3,12 @@
 use anyhow::{ensure, Context, Result};
 use fix::prelude::*;
 use hylo_core::fee_controller::FeeExtract;
+use hylo_core::fee_ledger::FeeLedger;
 use hylo_core::solana_clock::SolanaClock;
 use hylo_core::stability_pool_math::{
-  amount_token_to_withdraw, lp_token_nav, lp_token_out,
-  stablecoin_withdrawal_fee,
+  amount_token_to_withdraw, lp_token_out, stablecoin_withdrawal_fee,
 };
+use hylo_core::withdrawal_fee::WithdrawalFee;
 use hylo_idl::tokens::{TokenMint, HYUSD, SHYUSD, XSOL};
 
 use crate::protocol_state::ProtocolState;
@@ -15,7 +16,7 @@ use crate::token_operation::{
   OperationOutput, RedeemOperationOutput, SwapOperationOutput, TokenOperation,
   TokenOperationExt,
 };
-use crate::{Local, LST};
+use crate::{Local, Operation, LST};
 
 /// Deposit stablecoin (HYUSD) into stability pool for LP token (SHYUSD).
 impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
@@ -25,10 +26,10 @@ impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<SwapOperationOutput> {
-    let shyusd_nav = lp_token_nav(
-      self.exchange_context.stablecoin_nav()?,
+    self.check_operation(Operation::DepositToStabilityPool)?;
+    let in_amount = self.check_deposit(in_amount)?;
+    let shyusd_nav = self.exchange_context.shyusd_nav(
       UFix64::new(self.hyusd_pool.amount),
-      self.exchange_context.levercoin_mint_nav()?,
       UFix64::new(self.xsol_pool.amount),
       UFix64::new(self.shyusd_mint.supply),
     )?;
@@ -38,6 +39,7 @@ impl<C: SolanaClock> TokenOperation<HYUSD, SHYUSD> for ProtocolState<C> {
       out_amount: shyusd_out,
       fee_amount: UFix64::<N6>::zero(),
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::new(),
       fee_base: in_amount,
     })
   }
@@ -51,6 +53,7 @@ impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<SwapOperationOutput> {
+    self.check_operation(Operation::WithdrawFromStabilityPool)?;
     ensure!(
       self.xsol_pool.amount == 0,
       "SHYUSD -> HYUSD not possible: levercoin present in pool"
@@ -59,16 +62,18 @@ impl<C: SolanaClock> TokenOperation<SHYUSD, HYUSD> for ProtocolState<C> {
     let hyusd_in_pool = UFix64::new(self.hyusd_pool.amount);
     let hyusd_to_withdraw =
       amount_token_to_withdraw(in_amount, shyusd_supply, hyusd_in_pool)?;
-    let withdrawal_fee = self.pool_config.withdrawal_fee.try_into()?;
+    let withdrawal_fee: WithdrawalFee =
+      self.pool_config.withdrawal_fee.try_into()?;
     let FeeExtract {
       fees_extracted,
       amount_remaining,
-    } = FeeExtract::new(withdrawal_fee, hyusd_to_withdraw)?;
+    } = withdrawal_fee.apply(hyusd_to_withdraw)?;
     Ok(OperationOutput {
       in_amount,
       out_amount: amount_remaining,
       fee_amount: fees_extracted,
       fee_mint: HYUSD::MINT,
+      fees: FeeLedger::single(HYUSD::MINT, fees_extracted),
       fee_base: hyusd_to_withdraw,
     })
   }
@@ -84,6 +89,7 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<SHYUSD, L>
     &self,
     in_amount: UFix64<N6>,
   ) -> Result<RedeemOperationOutput> {
+    self.check_operation(Operation::WithdrawAndRedeemFromStabilityPool)?;
     let lp_token_supply = UFix64::new(self.shyusd_mint.supply);
     let stablecoin_in_pool = UFix64::new(self.hyusd_pool.amount);
 
@@ -101,8 +107,8 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<SHYUSD, L>
     let stablecoin_nav = self.exchange_context.stablecoin_nav()?;
     let levercoin_nav = self.exchange_context.levercoin_mint_nav()?;
     let FeeExtract {
+      fees_extracted: withdrawal_fees,
       amount_remaining: stablecoin_amount_remaining,
-      ..
     } = stablecoin_withdrawal_fee(
       stablecoin_in_pool,
       stablecoin_to_withdraw,
@@ -113,36 +119,39 @@ impl<L: LST + Local, C: SolanaClock> TokenOperation<SHYUSD, L>
     )?;
 
     // Redeem stablecoin for LST
-    let (lst_from_stablecoin, fee_from_stablecoin) =
+    let (lst_from_stablecoin, fees_from_stablecoin) =
       if stablecoin_amount_remaining > UFix64::zero() {
         let op = self.output::<HYUSD, L>(stablecoin_amount_remaining)?;
-        (op.out_amount, op.fee_amount)
+        (op.out_amount, op.fees)
       } else {
-        (UFix64::zero(), UFix64::zero())
+        (UFix64::zero(), FeeLedger::new())
       };
 
     // Redeem levercoin for LST
-    let (lst_from_levercoin, fee_from_levercoin) =
+    let (lst_from_levercoin, fees_from_levercoin) =
       if levercoin_to_withdraw > UFix64::zero() {
         let op = self.output::<XSOL, L>(levercoin_to_withdraw)?;
-        (op.out_amount, op.fee_amount)
+        (op.out_amount, op.fees)
       } else {
-        (UFix64::zero(), UFix64::zero())
+        (UFix64::zero(), FeeLedger::new())
       };
 
-    // Sum LST outputs and redemption fees
+    // Sum LST outputs; the withdrawal fee stays in stablecoin and the
+    // redemption fees are charged in LST
     let out_amount = lst_from_stablecoin
       .checked_add(&lst_from_levercoin)
       .context("out_amount overflow")?;
-    let fee_amount = fee_from_stablecoin
-      .checked_add(&fee_from_levercoin)
-      .context("fee_amount overflow")?;
+    let fees = FeeLedger::single(HYUSD::MINT, withdrawal_fees)
+      .merge(&fees_from_stablecoin)?
+      .merge(&fees_from_levercoin)?;
+    let fee_amount = fees.total(L::MINT)?;
 
     Ok(OperationOutput {
       in_amount,
       out_amount,
       fee_amount,
       fee_mint: L::MINT,
+      fees,
       fee_base: out_amount
         .checked_add(&fee_amount)
         .context("fee_base overflow")?,
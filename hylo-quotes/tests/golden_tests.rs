@@ -0,0 +1,28 @@
+//! Golden quote vector parity tests.
+
+use std::fs::File;
+
+use anchor_lang::solana_program::clock::Clock;
+use anyhow::Result;
+use hylo_quotes::golden::GoldenSet;
+use hylo_quotes::prelude::{ProtocolAccounts, ProtocolState};
+use serde_json::from_reader;
+
+fn data_path(name: &str) -> String {
+  format!("{}/tests/data/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn verify_golden_set(name: &str) -> Result<()> {
+  let set: GoldenSet = from_reader(File::open(data_path(name))?)?;
+  let accounts: ProtocolAccounts =
+    from_reader(File::open(data_path(&set.snapshot))?)?;
+  let state = ProtocolState::<Clock>::try_from(&accounts)?;
+  let mismatches = set.verify(&state)?;
+  assert!(mismatches.is_empty(), "Golden mismatches: {mismatches:#?}");
+  Ok(())
+}
+
+#[test]
+fn golden_v1_918_37508() -> Result<()> {
+  verify_golden_set("golden/v1-918-37508.json")
+}
@@ -0,0 +1,100 @@
+//! Differential replay of recorded transactions against SDK math.
+
+use std::fs::File;
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use hylo_idl::tokens::{TokenMint, HYUSD, JITOSOL};
+use hylo_quotes::prelude::ProtocolAccounts;
+use hylo_quotes::replay::{
+  replay_divergences, RecordedTransaction, ReplayCase, TokenBalance,
+};
+use serde_json::from_reader;
+
+fn load_accounts() -> Result<ProtocolAccounts> {
+  let path = format!(
+    "{}/tests/data/protocol-state-918-37508.json",
+    env!("CARGO_MANIFEST_DIR")
+  );
+  let file = File::open(path)?;
+  Ok(from_reader(file)?)
+}
+
+fn balance(mint: Pubkey, owner: Pubkey, amount: u64) -> TokenBalance {
+  TokenBalance {
+    mint,
+    owner,
+    amount,
+  }
+}
+
+#[test]
+fn case_from_token_balance_deltas() -> Result<()> {
+  let owner = Pubkey::new_unique();
+  let other = Pubkey::new_unique();
+  let tx = RecordedTransaction {
+    signature: "balances".to_string(),
+    owner,
+    pre_token_balances: vec![
+      balance(JITOSOL::MINT, owner, 5_000_000_000),
+      balance(HYUSD::MINT, other, 9_000_000),
+    ],
+    post_token_balances: vec![
+      balance(JITOSOL::MINT, owner, 4_000_000_000),
+      balance(HYUSD::MINT, owner, 154_000_000),
+      balance(HYUSD::MINT, other, 1_000_000),
+    ],
+    pre_state: load_accounts()?,
+  };
+  let case = ReplayCase::try_from(&tx)?;
+  assert_eq!(
+    (case.input_mint, case.output_mint),
+    (JITOSOL::MINT, HYUSD::MINT)
+  );
+  assert_eq!(
+    (case.amount_in, case.observed_out),
+    (1_000_000_000, 154_000_000)
+  );
+  let unchanged = RecordedTransaction {
+    post_token_balances: tx.pre_token_balances.clone(),
+    ..tx
+  };
+  assert!(ReplayCase::try_from(&unchanged).is_err());
+  Ok(())
+}
+
+#[test]
+fn replay_flags_divergence() -> Result<()> {
+  let case = ReplayCase {
+    signature: "snapshot-918-37508".to_string(),
+    input_mint: JITOSOL::MINT,
+    output_mint: HYUSD::MINT,
+    amount_in: 1_000_000_000,
+    observed_out: 154_000_000,
+    pre_state: load_accounts()?,
+  };
+  let divergences = replay_divergences(&[case], 1)?;
+  assert_eq!(divergences.len(), 1);
+  Ok(())
+}
+
+/// Replays recorded mainnet transactions, one [`RecordedTransaction`] JSON
+/// per file, from the directory in `HYLO_REPLAY_CORPUS`. Input and output
+/// amounts come from each transaction's token balances.
+#[test]
+#[ignore = "requires HYLO_REPLAY_CORPUS directory of recorded transactions"]
+fn replay_matches_mainnet() -> Result<()> {
+  let dir = std::env::var("HYLO_REPLAY_CORPUS")?;
+  let cases = std::fs::read_dir(Path::new(&dir))?
+    .map(|entry| {
+      let file = File::open(entry?.path())?;
+      let tx: RecordedTransaction = from_reader(file)?;
+      ReplayCase::try_from(&tx)
+    })
+    .collect::<Result<Vec<_>>>()?;
+  assert!(!cases.is_empty(), "No recorded transactions in {dir}");
+  let divergences = replay_divergences(&cases, 0)?;
+  assert!(divergences.is_empty(), "Divergent cases: {divergences:#?}");
+  Ok(())
+}
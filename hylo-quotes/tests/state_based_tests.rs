@@ -3,15 +3,27 @@
 use std::fs::File;
 
 use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_lang::prelude::Pubkey;
 use anchor_lang::solana_program::clock::Clock;
-use anyhow::Result;
+use anchor_lang::InstructionData;
+use anyhow::{anyhow, Result};
 use fix::prelude::*;
 use hylo_clients::prelude::CommitmentConfig;
-use hylo_idl::tokens::{HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_core::error::CoreError::NoValidStablecoinMintFee;
+use hylo_idl::stability_pool;
+use hylo_idl::stability_pool::client::args::{UserDeposit, UserWithdraw};
+use hylo_idl::tokens::{TokenMint, HYLOSOL, HYUSD, JITOSOL, SHYUSD, XSOL};
+use hylo_quotes::pool_schedule::StepLimits;
 use hylo_quotes::prelude::{
   ProtocolAccounts, ProtocolState, TokenOperationExt,
 };
+use hylo_quotes::preview::PreviewEntry;
+use hylo_quotes::route_health::RouteHealth;
+use hylo_quotes::token_operation::{RuntimePair, RUNTIME_PAIRS};
 use serde_json::{from_reader, to_writer};
+use solana_program_pack::Pack;
+use spl_token_interface::state::Mint;
 
 /// Pulls needed accounts from RPC into a file indexed by epoch and slot.
 ///
@@ -38,13 +50,37 @@ pub async fn dump_protocol_accounts() -> Result<()> {
   Ok(())
 }
 
-fn load_state() -> Result<ProtocolState<Clock>> {
+fn load_accounts() -> Result<ProtocolAccounts> {
   let path = format!(
     "{}/tests/data/protocol-state-918-37508.json",
     env!("CARGO_MANIFEST_DIR")
   );
   let file = File::open(path)?;
-  let accounts = from_reader::<_, ProtocolAccounts>(file)?;
+  Ok(from_reader::<_, ProtocolAccounts>(file)?)
+}
+
+fn load_state() -> Result<ProtocolState<Clock>> {
+  ProtocolState::try_from(&load_accounts()?)
+}
+
+/// Snapshot with hyUSD supply raised until the collateral ratio sits just
+/// under the lowest stability threshold.
+fn load_state_below_threshold() -> Result<ProtocolState<Clock>> {
+  let mut accounts = load_accounts()?;
+  let ctx = ProtocolState::try_from(&accounts)?.exchange_context;
+  let target_cr = ctx
+    .stability_controller()
+    .min_stability_threshold()
+    .checked_sub(&UFix64::new(1))
+    .ok_or(anyhow!("Threshold underflow"))?;
+  let supply = ctx
+    .total_value_locked()?
+    .mul_div_ceil(UFix64::<N2>::one(), target_cr)
+    .ok_or(anyhow!("Supply overflow"))?
+    .convert::<N6>();
+  let mut mint = Mint::unpack(&accounts.hyusd_mint.data)?;
+  mint.supply = supply.bits;
+  Mint::pack(mint, &mut accounts.hyusd_mint.data)?;
   ProtocolState::try_from(&accounts)
 }
 
@@ -119,3 +155,231 @@ fn hyusd_to_shyusd() -> Result<()> {
   assert_eq!(op.out_amount, UFix64::<N6>::new(860_623));
   Ok(())
 }
+
+#[test]
+fn shyusd_to_jitosol_fees_per_mint() -> Result<()> {
+  let state = load_state()?;
+  let op = state.output::<SHYUSD, JITOSOL>(UFix64::new(1_000_000_000))?;
+  assert_eq!(op.fee_mint, JITOSOL::MINT);
+  assert_eq!(op.fees.total::<N9>(JITOSOL::MINT)?, op.fee_amount);
+  assert_eq!(op.fee_amount, UFix64::new(7_476_917));
+  assert_eq!(op.fees.total::<N6>(HYUSD::MINT)?, UFix64::new(1_161_948));
+  assert_eq!(op.fees.mints().count(), 2);
+  Ok(())
+}
+
+#[test]
+fn route_health_below_threshold() -> Result<()> {
+  let state = load_state_below_threshold()?;
+  let ctx = &state.exchange_context;
+  let threshold = ctx.stability_controller().min_stability_threshold();
+  assert!(ctx.collateral_ratio() < threshold.convert());
+  [(JITOSOL::MINT, HYUSD::MINT), (XSOL::MINT, HYUSD::MINT)]
+    .into_iter()
+    .try_for_each(|(input_mint, output_mint)| {
+      let report = state.route_health(input_mint, output_mint)?;
+      assert_eq!(report.remaining_capacity, Some(UFix64::zero()));
+      assert_eq!(report.health, RouteHealth::Unhealthy);
+      Ok(())
+    })
+}
+
+#[test]
+fn exact_out_under_cap() -> Result<()> {
+  let state = load_state()?;
+  let quote = state.exact_out(XSOL::MINT, HYUSD::MINT, 1_000_000_000_000)?;
+  assert!(quote.output.out_amount.bits >= 1_000_000_000_000);
+  Ok(())
+}
+
+#[test]
+fn exact_out_past_cap() -> Result<()> {
+  let state = load_state()?;
+  let max = state.exchange_context.max_swappable_stablecoin()?;
+  let err = state
+    .exact_out(XSOL::MINT, HYUSD::MINT, max.bits + 1)
+    .err()
+    .map(|e| e.to_string());
+  assert_eq!(err, Some(format!("No input yields {}", max.bits + 1)));
+  Ok(())
+}
+
+#[test]
+fn exact_out_propagates_quote_errors() -> Result<()> {
+  let state = load_state()?;
+  let mode2 = anchor_lang::error::Error::from(NoValidStablecoinMintFee);
+  let err = state
+    .exact_out(JITOSOL::MINT, HYUSD::MINT, 20_000_000_000_000)
+    .err()
+    .ok_or(anyhow!("Mint into Mode2 should fail"))?;
+  assert!(err.chain().any(|cause| cause
+    .downcast_ref::<anchor_lang::error::Error>()
+    == Some(&mode2)));
+  Ok(())
+}
+
+fn runtime_pair(
+  input_mint: Pubkey,
+  output_mint: Pubkey,
+) -> Result<&'static RuntimePair> {
+  RUNTIME_PAIRS
+    .iter()
+    .find(|pair| {
+      pair.input_mint == input_mint && pair.output_mint == output_mint
+    })
+    .ok_or(anyhow!("No runtime pair {input_mint} -> {output_mint}"))
+}
+
+#[test]
+fn price_impact_of_one_token_is_zero() -> Result<()> {
+  let state = load_state()?;
+  let pair = runtime_pair(XSOL::MINT, JITOSOL::MINT)?;
+  assert_eq!(state.price_impact(pair, pair.unit_in)?, UFix64::zero());
+  let unsupported = RuntimePair {
+    output_mint: XSOL::MINT,
+    ..*pair
+  };
+  assert!(state.price_impact(&unsupported, pair.unit_in).is_err());
+  Ok(())
+}
+
+#[test]
+fn max_trade_size_capped_pair() -> Result<()> {
+  let state = load_state()?;
+  let pair = runtime_pair(JITOSOL::MINT, HYUSD::MINT)?;
+  let limit = UFix64::new(100);
+  let max = state.max_trade_size(pair, limit)?;
+  assert_eq!(max.amount_in, 125_325_821_559_658);
+  assert_eq!(max.amount_out, 19_326_733_033_484);
+  assert_eq!(state.price_impact(pair, max.amount_in)?, max.impact);
+  assert!(state
+    .price_impact(pair, max.amount_in + 1)
+    .map_or(true, |impact| impact > limit));
+  Ok(())
+}
+
+#[test]
+fn max_trade_size_uncapped_pair_stops_at_supply() -> Result<()> {
+  let state = load_state()?;
+  let pair = runtime_pair(SHYUSD::MINT, HYUSD::MINT)?;
+  let max = state.max_trade_size(pair, UFix64::new(100))?;
+  assert_eq!(max.amount_in, state.shyusd_mint.supply);
+  assert_eq!(max.amount_out, 30_565_218_757_756);
+  Ok(())
+}
+
+#[test]
+fn max_trade_size_without_liquidity() -> Result<()> {
+  let mut state = load_state()?;
+  state.shyusd_mint.supply = 0;
+  let pair = runtime_pair(SHYUSD::MINT, HYUSD::MINT)?;
+  let max = state.max_trade_size(pair, UFix64::new(100))?;
+  assert_eq!((max.amount_in, max.amount_out), (0, 0));
+  Ok(())
+}
+
+#[test]
+fn round_trip_costs() -> Result<()> {
+  let state = load_state()?;
+  let lst = state.round_trip_cost(JITOSOL::MINT, HYUSD::MINT, 1_000_000_000)?;
+  assert_eq!(
+    (lst.amount_mid, lst.amount_back, lst.loss_bps()),
+    (154_211_899, 992_325_237, 77)
+  );
+  let swap = state.round_trip_cost(HYUSD::MINT, XSOL::MINT, 1_000_000_000)?;
+  assert_eq!(
+    (swap.amount_mid, swap.amount_back, swap.loss_bps()),
+    (2_077_779_987, 950_061_756, 500)
+  );
+  assert!(state
+    .round_trip_cost(JITOSOL::MINT, HYUSD::MINT, 0)
+    .is_err());
+  Ok(())
+}
+
+#[test]
+fn pool_schedule_splits_exit() -> Result<()> {
+  let state = load_state()?;
+  let limits = StepLimits {
+    max_amount_in: 1_000_000_000,
+    max_impact: UFix64::new(100),
+    max_steps: 3,
+  };
+  let schedule =
+    state.pool_schedule(SHYUSD::MINT, JITOSOL::MINT, 2_500_000_000, &limits)?;
+  let amounts_in = schedule
+    .steps
+    .iter()
+    .map(|step| step.amount_in)
+    .collect::<Vec<_>>();
+  assert_eq!(amounts_in, [833_333_334, 833_333_333, 833_333_333]);
+  assert_eq!(schedule.fee_mint, JITOSOL::MINT);
+  let first = state.runtime_output(SHYUSD::MINT, JITOSOL::MINT, 833_333_334)?;
+  assert_eq!(schedule.steps[0].amount_out, first.out_amount.bits);
+  assert_eq!(schedule.steps[0].fee_amount, first.fee_amount.bits);
+  let err = state
+    .pool_schedule(SHYUSD::MINT, JITOSOL::MINT, 3_000_000_001, &limits)
+    .err()
+    .map(|e| e.to_string());
+  assert_eq!(
+    err,
+    Some("3000000001 needs 4 steps, over the limit of 3".to_string())
+  );
+  Ok(())
+}
+
+#[test]
+fn pool_schedule_without_liquidity() -> Result<()> {
+  let mut state = load_state()?;
+  state.shyusd_mint.supply = 0;
+  let limits = StepLimits {
+    max_amount_in: 1_000_000_000,
+    max_impact: UFix64::new(100),
+    max_steps: u64::MAX,
+  };
+  let err = state
+    .pool_schedule(SHYUSD::MINT, HYUSD::MINT, 1_000_000, &limits)
+    .err()
+    .map(|e| e.to_string());
+  assert_eq!(err, Some("No size within the impact limit".to_string()));
+  Ok(())
+}
+
+#[test]
+fn preview_lists_unpriced_withdrawal() -> Result<()> {
+  let mut state = load_state()?;
+  state.xsol_pool.amount = 1;
+  let instruction = |data: Vec<u8>| Instruction {
+    program_id: stability_pool::ID,
+    accounts: Vec::new(),
+    data,
+  };
+  let preview = state.preview_transaction(&[
+    instruction(
+      UserDeposit {
+        amount_stablecoin: 1_000_000,
+      }
+      .data(),
+    ),
+    instruction(
+      UserWithdraw {
+        amount_lp_token: 1_000_000,
+      }
+      .data(),
+    ),
+  ]);
+  assert_eq!(preview.instructions.len(), 2);
+  assert!(matches!(
+    preview.instructions.first(),
+    Some(PreviewEntry::Priced(_))
+  ));
+  assert_eq!(
+    preview.instructions.get(1),
+    Some(&PreviewEntry::Unpriced {
+      program_id: stability_pool::ID,
+      reason: "SHYUSD -> HYUSD not possible: levercoin present in pool"
+        .to_string(),
+    })
+  );
+  Ok(())
+}